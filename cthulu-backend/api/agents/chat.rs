@@ -37,22 +37,6 @@ fn make_summary(text: &str) -> String {
     format!("{}...", &truncated[..boundary])
 }
 
-/// Best-effort process termination, platform-specific.
-fn kill_pid(pid: u32) {
-    #[cfg(unix)]
-    {
-        let _ = std::process::Command::new("kill")
-            .args(["-TERM", &pid.to_string()])
-            .spawn();
-    }
-    #[cfg(windows)]
-    {
-        let _ = std::process::Command::new("taskkill")
-            .args(["/PID", &pid.to_string(), "/F"])
-            .spawn();
-    }
-}
-
 /// Build the path for node attachment files.
 fn attachments_path(data_dir: &std::path::Path, flow_id: &str, node_id: &str) -> std::path::PathBuf {
     data_dir.join("attachments").join(flow_id).join(node_id)
@@ -280,7 +264,7 @@ pub(crate) async fn delete_session(
 
         if let Some(session) = flow_sessions.get_session(&session_id) {
             if let Some(pid) = session.active_pid {
-                kill_pid(pid);
+                crate::platform::kill_tree(pid);
             }
             // Clean up worktree group if present
             if let Some(ref wt_meta) = session.worktree_group {
@@ -360,7 +344,7 @@ pub(crate) async fn stop_chat(
 
         if let Some(session) = flow_sessions.get_session_mut(&sid) {
             if let Some(pid) = session.active_pid.take() {
-                kill_pid(pid);
+                crate::platform::kill_tree(pid);
             }
             session.busy = false;
             session.busy_since = None;
@@ -456,7 +440,7 @@ pub(crate) async fn kill_session(
     if let Some(flow_sessions) = all_sessions.get_mut(&key) {
         if let Some(session) = flow_sessions.get_session_mut(&session_id) {
             if let Some(pid) = session.active_pid.take() {
-                kill_pid(pid);
+                crate::platform::kill_tree(pid);
             }
             session.busy = false;
             session.busy_since = None;
@@ -1274,16 +1258,17 @@ pub(crate) async fn chat(
                     "spawning persistent claude for agent chat"
                 );
 
-                match Command::new("claude")
-                    .args(&args)
+                let mut cmd = Command::new(crate::platform::claude_bin());
+                cmd.args(&args)
                     .current_dir(&working_dir)
                     .env_remove("CLAUDECODE")
                     .env("CLAUDECODE", "")
                     .stdin(Stdio::piped())
                     .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .spawn()
-                {
+                    .stderr(Stdio::piped());
+                // Its own process group, so killing the session also stops its tools
+                crate::platform::isolate(&mut cmd);
+                match cmd.spawn() {
                     Ok(mut child) => {
                         if let Some(pid) = child.id() {
                             let mut all_sessions = sessions_ref.write().await;
@@ -1671,7 +1656,7 @@ pub(crate) async fn chat(
 
 /// Parse a raw Claude stdout line into a list of (event_type, data_json) pairs
 /// for broadcasting. Same parsing logic as the old inline read loop.
-fn parse_claude_line_to_sse_events(line: &str) -> Vec<(String, String)> {
+pub(crate) fn parse_claude_line_to_sse_events(line: &str) -> Vec<(String, String)> {
     let mut events = Vec::new();
 
     if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(line) {
@@ -2017,7 +2002,10 @@ fn node_config_summary(node: &Node) -> String {
     match node.kind.as_str() {
         "cron" => {
             let schedule = node.config.get("schedule").and_then(|v| v.as_str()).unwrap_or("?");
-            format!("schedule: {schedule}")
+            match node.config.get("timezone").and_then(|v| v.as_str()) {
+                Some(tz) => format!("schedule: {schedule} ({tz})"),
+                None => format!("schedule: {schedule}"),
+            }
         }
         "rss" => {
             let url = node.config.get("url").and_then(|v| v.as_str()).unwrap_or("?");
@@ -2038,24 +2026,92 @@ fn node_config_summary(node: &Node) -> String {
             let poll = node.config.get("poll_interval").and_then(|v| v.as_u64()).unwrap_or(60);
             format!("poll: {poll}s")
         }
+        "github-push" => {
+            let branches = node.config.get("branches").and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(", "))
+                .unwrap_or_else(|| "main".into());
+            format!("branches: {branches}")
+        }
+        "github-issue" | "github-release" => {
+            let repos = node.config.get("repos").and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|r| r.get("slug").and_then(|v| v.as_str())).collect::<Vec<_>>().join(", "))
+                .unwrap_or_else(|| "?".into());
+            format!("repos: {repos}")
+        }
         "market-data" => "(fetches BTC/ETH, Fear & Greed, S&P 500)".into(),
+        "feedback-rank" => {
+            let min = node.config.get("min_score").and_then(|v| v.as_i64())
+                .map_or("keep all".to_string(), |m| format!("min_score: {m}"));
+            format!("rank by 👍/👎 feedback, {min}")
+        }
+        "trending" => {
+            let hours = node.config.get("window_hours").and_then(|v| v.as_i64()).unwrap_or(24);
+            let min = node.config.get("min_sources").and_then(|v| v.as_u64()).unwrap_or(1);
+            format!("rank by coverage over {hours}h, min_sources: {min}")
+        }
+        "vector-search" | "vector-store" => {
+            let collection = node.config.get("collection").and_then(|v| v.as_str()).unwrap_or("default");
+            let backend = node.config.get("backend").and_then(|v| v.as_str()).unwrap_or("local");
+            format!("collection: {collection} ({backend})")
+        }
+        "audio" => {
+            let provider = node.config.get("provider").and_then(|v| v.as_str()).unwrap_or("openai");
+            let mut targets = Vec::new();
+            if node.config.get("feed").and_then(|v| v.as_bool()) != Some(false) {
+                targets.push("podcast feed".to_string());
+            }
+            if node.config.get("bot_token_env").and_then(|v| v.as_str()).is_some() {
+                let channel = node.config.get("user_email").or(node.config.get("channel")).and_then(|v| v.as_str()).unwrap_or("?");
+                targets.push(format!("Slack {channel}"));
+            }
+            format!("TTS via {provider} → {}", targets.join(" + "))
+        }
         "claude-code" => {
             let prompt = node.config.get("prompt").and_then(|v| v.as_str()).unwrap_or("(inline)");
             format!("prompt: {prompt}")
         }
+        "openai" => {
+            let model = node.config.get("model").and_then(|v| v.as_str()).unwrap_or("gpt-4o-mini");
+            let prompt = node.config.get("prompt").and_then(|v| v.as_str()).unwrap_or("(inline)");
+            format!("model: {model}, prompt: {prompt}")
+        }
+        "ollama" => {
+            let model = node.config.get("model").and_then(|v| v.as_str()).unwrap_or("?");
+            let prompt = node.config.get("prompt").and_then(|v| v.as_str()).unwrap_or("(inline)");
+            format!("local model: {model}, prompt: {prompt}")
+        }
         "slack" => {
-            let channel = node.config.get("channel").and_then(|v| v.as_str()).unwrap_or("?");
+            let channel = match node.config.get("user_email").and_then(|v| v.as_str()) {
+                Some(email) => format!("DM {email}"),
+                None => node.config.get("channel").and_then(|v| v.as_str()).unwrap_or("?").to_string(),
+            };
             let method = if node.config.get("bot_token_env").and_then(|v| v.as_str()).is_some() {
                 "bot"
             } else {
                 "webhook"
             };
-            format!("{method}, channel: {channel}")
+            let feedback = if node.config.get("feedback").and_then(|v| v.as_bool()) == Some(true) {
+                ", 👍/👎 feedback"
+            } else {
+                ""
+            };
+            format!("{method}, channel: {channel}{feedback}")
         }
         "notion" => {
             let db = node.config.get("database_id").and_then(|v| v.as_str()).unwrap_or("?");
             format!("database: {db}")
         }
+        "email" => {
+            let to: Vec<&str> = node.config.get("to")
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            format!("to: {}", to.join(", "))
+        }
+        "desktop" => {
+            let title = node.config.get("title").and_then(|v| v.as_str()).unwrap_or("(first heading)");
+            format!("local notification: {title}")
+        }
         "manual" => "(triggered manually)".into(),
         "webhook" => {
             let path = node.config.get("path").and_then(|v| v.as_str()).unwrap_or("?");
@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use axum::extract::State;
+use axum::Json;
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::AppState;
+use crate::flows::history::MAX_RUNS_PER_FLOW;
+use crate::tasks::archive::ArchiveStore;
+use crate::tasks::ask::{self, DEFAULT_MAX_ITEMS, DEFAULT_MAX_RUNS};
+use crate::tasks::executors::Executor;
+use crate::tasks::executors::claude_code::ClaudeCodeExecutor;
+use crate::tasks::executors::sandbox::{self, SandboxExecutor};
+
+/// Tools the executor may use without an agent: the sources are in the
+/// prompt, so it only needs to read.
+const DEFAULT_PERMISSIONS: &[&str] = &["Read"];
+
+#[derive(Deserialize)]
+pub(crate) struct AskRequest {
+    question: String,
+    /// Only retrieve from this flow's runs and items.
+    flow_id: Option<String>,
+    max_runs: Option<usize>,
+    max_items: Option<usize>,
+    /// Agent whose permissions and system prompt the executor runs with.
+    agent_id: Option<String>,
+    /// `sandbox` runs the executor in the sandbox provider, as for flows.
+    runtime: Option<String>,
+}
+
+type ApiError = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(json!({ "error": message.into() })))
+}
+
+/// POST /ask — answer a question from stored run outputs and archived items,
+/// citing them as `[n]`.
+pub(crate) async fn ask(
+    State(state): State<AppState>,
+    Json(body): Json<AskRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let question = body.question.trim();
+    if question.is_empty() {
+        return Err(error(StatusCode::BAD_REQUEST, "question is required"));
+    }
+
+    let flows = match &body.flow_id {
+        Some(id) => vec![
+            state
+                .flow_repo
+                .get_flow(id)
+                .await
+                .ok_or_else(|| error(StatusCode::NOT_FOUND, "flow not found"))?,
+        ],
+        None => state.flow_repo.list_flows().await,
+    };
+    let mut history = Vec::with_capacity(flows.len());
+    for flow in flows {
+        let runs = state.flow_repo.get_runs(&flow.id, MAX_RUNS_PER_FLOW).await;
+        history.push((flow, runs));
+    }
+    let runs = ask::relevant_runs(&history, question, body.max_runs.unwrap_or(DEFAULT_MAX_RUNS));
+
+    let terms: Vec<String> = ask::terms(question).into_iter().collect();
+    let items = ArchiveStore::new(&state.data_dir)
+        .search_any(&terms.join(" "), body.flow_id.as_deref(), body.max_items.unwrap_or(DEFAULT_MAX_ITEMS))
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, format!("archive search failed: {e}")))?;
+
+    let mut sources = ask::citations(&runs, &items);
+    if sources.is_empty() {
+        return Ok(Json(json!({
+            "question": question,
+            "answer": "No stored runs or archived items match this question.",
+            "citations": [],
+        })));
+    }
+
+    let (permissions, append_system_prompt) = match &body.agent_id {
+        Some(agent_id) => {
+            let agent = state
+                .agent_repo
+                .get(agent_id)
+                .await
+                .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("agent '{agent_id}' not found")))?;
+            (agent.permissions, agent.append_system_prompt)
+        }
+        None => (DEFAULT_PERMISSIONS.iter().map(|p| p.to_string()).collect(), None),
+    };
+    // Claude runs straight on the host only under the host sandbox backend
+    let sandboxed = body.runtime.as_deref() == Some("sandbox") || !sandbox::runs_on_host(&*state.sandbox_provider);
+    let executor: Box<dyn Executor> = if sandboxed {
+        Box::new(SandboxExecutor::new(
+            state.sandbox_provider.clone(),
+            permissions,
+            append_system_prompt,
+        ))
+    } else {
+        Box::new(ClaudeCodeExecutor::new(permissions, append_system_prompt))
+    };
+
+    let prompt = ask::build_prompt(question, &sources);
+    let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    tracing::info!(sources = sources.len(), prompt_chars = prompt.len(), "Answering question");
+    let result = executor
+        .execute(&prompt, &working_dir)
+        .await
+        .map_err(|e| error(StatusCode::BAD_GATEWAY, format!("executor failed: {e}")))?;
+
+    ask::mark_cited(&result.text, &mut sources);
+    Ok(Json(json!({
+        "question": question,
+        "answer": result.text,
+        "citations": sources,
+        "cost_usd": result.cost_usd,
+    })))
+}
@@ -0,0 +1,70 @@
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, header};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use hyper::StatusCode;
+use serde_json::json;
+
+use crate::api::AppState;
+use crate::tasks::audio::{self, AudioStore};
+
+/// GET /audio/{flow_id}/feed.xml — the flow's `audio` sink episodes as a
+/// podcast feed. Enclosure URLs use the host the feed was fetched from.
+pub(crate) async fn feed(
+    State(state): State<AppState>,
+    Path(flow_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(flow) = state.flow_repo.get_flow(&flow_id).await else {
+        return (StatusCode::NOT_FOUND, "flow not found").into_response();
+    };
+    let episodes = AudioStore::new(&state.data_dir).episodes(&flow_id);
+    let xml = audio::feed_xml(&flow_id, &flow.name, &base_url(&headers), &episodes);
+    ([(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")], xml).into_response()
+}
+
+/// GET /audio/{flow_id}/episodes — the flow's episodes, newest first.
+pub(crate) async fn list_episodes(
+    State(state): State<AppState>,
+    Path(flow_id): Path<String>,
+) -> Response {
+    if state.flow_repo.get_flow(&flow_id).await.is_none() {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "flow not found" }))).into_response();
+    }
+    Json(json!({ "episodes": AudioStore::new(&state.data_dir).episodes(&flow_id) })).into_response()
+}
+
+/// GET /audio/{flow_id}/episodes/{file} — an episode's MP3.
+pub(crate) async fn episode(
+    State(state): State<AppState>,
+    Path((flow_id, file)): Path<(String, String)>,
+) -> Response {
+    if state.flow_repo.get_flow(&flow_id).await.is_none() {
+        return (StatusCode::NOT_FOUND, "flow not found").into_response();
+    }
+    let Some(path) = AudioStore::new(&state.data_dir).episode_path(&flow_id, &file) else {
+        return (StatusCode::NOT_FOUND, "episode not found").into_response();
+    };
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => ([(header::CONTENT_TYPE, "audio/mpeg")], bytes).into_response(),
+        Err(e) => {
+            tracing::error!(path = %path.display(), error = %e, "Failed to read episode");
+            (StatusCode::NOT_FOUND, "episode not found").into_response()
+        }
+    }
+}
+
+/// `scheme://host` the request came in on, honouring a proxy's
+/// `X-Forwarded-Proto`.
+fn base_url(headers: &HeaderMap) -> String {
+    let host = headers
+        .get("x-forwarded-host")
+        .or_else(|| headers.get(header::HOST))
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("localhost");
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("http");
+    format!("{scheme}://{host}")
+}
@@ -0,0 +1,13 @@
+pub mod handlers;
+
+use axum::routing::get;
+use axum::Router;
+
+use crate::api::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/audio/{flow_id}/feed.xml", get(handlers::feed))
+        .route("/audio/{flow_id}/episodes", get(handlers::list_episodes))
+        .route("/audio/{flow_id}/episodes/{file}", get(handlers::episode))
+}
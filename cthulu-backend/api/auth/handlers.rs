@@ -13,13 +13,13 @@ use crate::api::AppState;
 use super::repository;
 
 /// Returns whether a token is currently loaded, plus expiry and account info
-/// extracted from the macOS Keychain credentials blob.
+/// extracted from the Claude CLI's stored credentials blob.
 pub(crate) async fn token_status(State(state): State<AppState>) -> impl IntoResponse {
     let token = state.oauth_token.read().await;
     let has_token = token.is_some();
     drop(token);
 
-    // Try to read richer info from the Keychain blob
+    // Try to read richer info from the credentials blob
     let creds = repository::read_full_credentials()
         .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok());
 
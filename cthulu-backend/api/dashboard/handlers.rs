@@ -147,7 +147,7 @@ pub(crate) async fn get_messages(State(state): State<AppState>) -> impl IntoResp
     }
 
     // Resolve the Slack token from the configured env var name
-    let token = match std::env::var(&config.slack_token_env) {
+    let token = match crate::secrets::var(&config.slack_token_env) {
         Ok(t) if !t.is_empty() => t,
         _ => {
             return (StatusCode::BAD_REQUEST, Json(json!({
@@ -330,7 +330,7 @@ Keep each summary under 100 words. Focus on what matters: decisions made, proble
     );
 
     // Spawn Claude CLI (same pattern as prompts/handlers.rs summarize_session)
-    let mut child = Command::new("claude")
+    let mut child = Command::new(crate::platform::claude_bin())
         .arg("--print")
         .arg("--allowedTools")
         .arg("")
@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use axum::extract::Query;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::AppState;
+use crate::egress::{self, EgressQuery};
+
+const DEFAULT_LIMIT: usize = 200;
+
+#[derive(Deserialize)]
+struct EgressParams {
+    flow_id: Option<String>,
+    node_id: Option<String>,
+    host: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Recent outbound requests, newest first, with how many went to each
+/// host — e.g. `?flow_id=...` to see which APIs a flow is hammering.
+async fn list_egress(Query(params): Query<EgressParams>) -> Json<Value> {
+    let query = EgressQuery {
+        flow_id: params.flow_id,
+        node_id: params.node_id,
+        host: params.host,
+    };
+    let requests = egress::recent(&query);
+    let mut by_host: BTreeMap<&str, usize> = BTreeMap::new();
+    for request in &requests {
+        *by_host.entry(&request.host).or_default() += 1;
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    Json(json!({
+        "total": requests.len(),
+        "by_host": by_host,
+        "requests": &requests[..requests.len().min(limit)],
+    }))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/admin/egress", get(list_egress))
+}
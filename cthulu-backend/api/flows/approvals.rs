@@ -0,0 +1,111 @@
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::Json;
+use hyper::StatusCode;
+use serde_json::{json, Value};
+
+use super::handlers::{caller, save_update};
+use crate::api::AppState;
+use crate::flows::Flow;
+use crate::flows::approvals::{self, ChangeStore, PendingChange};
+
+type ApiError = (StatusCode, Json<Value>);
+
+/// GET /flows/{id}/changes — edits waiting for an owner, oldest first.
+pub(crate) async fn list_changes(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let flow = find_flow(&state, &id).await?;
+    let changes = ChangeStore::new(&state.data_dir).list(&id);
+    Ok(Json(json!({ "owners": flow.owners, "changes": changes })))
+}
+
+/// POST /flows/{id}/changes/{change_id}/approve — an owner puts a pending
+/// change live. It's refused (and kept) if the flow changed since it was
+/// made, so the owner doesn't approve edits to a flow that's gone.
+pub(crate) async fn approve_change(
+    State(state): State<AppState>,
+    Path((id, change_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let (mut flow, change) = owned_change(&state, &id, &change_id, &headers).await?;
+    if flow.version != change.base_version {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "the flow changed after this edit was made; reject it and edit again",
+                "base_version": change.base_version,
+                "server_version": flow.version,
+            })),
+        ));
+    }
+    let change = take(&state, &id, &change_id)?;
+
+    tracing::info!(flow = %flow.name, change = %change_id, approver = ?caller(&headers), "Flow change approved");
+    change.changes.apply(&mut flow);
+    save_update(&state, flow).await
+}
+
+/// POST /flows/{id}/changes/{change_id}/reject — an owner drops a pending
+/// change.
+pub(crate) async fn reject_change(
+    State(state): State<AppState>,
+    Path((id, change_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let (flow, _) = owned_change(&state, &id, &change_id, &headers).await?;
+    take(&state, &id, &change_id)?;
+    tracing::info!(flow = %flow.name, change = %change_id, "Flow change rejected");
+    Ok(Json(json!({ "rejected": true })))
+}
+
+async fn find_flow(state: &AppState, id: &str) -> Result<Flow, ApiError> {
+    state.flow_repo.get_flow(id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })
+}
+
+/// The flow and one of its pending changes, if the caller owns the flow.
+async fn owned_change(
+    state: &AppState,
+    id: &str,
+    change_id: &str,
+    headers: &HeaderMap,
+) -> Result<(Flow, PendingChange), ApiError> {
+    let flow = find_flow(state, id).await?;
+    let change = ChangeStore::new(&state.data_dir)
+        .list(id)
+        .into_iter()
+        .find(|c| c.id == change_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "pending change not found" })),
+            )
+        })?;
+    if !approvals::is_owner(&flow, caller(headers)) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "only an owner can approve or reject changes", "owners": flow.owners })),
+        ));
+    }
+    Ok((flow, change))
+}
+
+fn take(state: &AppState, id: &str, change_id: &str) -> Result<PendingChange, ApiError> {
+    match ChangeStore::new(&state.data_dir).take(id, change_id) {
+        Ok(Some(change)) => Ok(change),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "pending change not found" })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to update pending changes: {e}") })),
+        )),
+    }
+}
@@ -0,0 +1,157 @@
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::Json;
+use chrono::Utc;
+use futures::stream::Stream;
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::api::agents::chat::parse_claude_line_to_sse_events;
+use crate::api::AppState;
+use crate::flows::history::{ChatMessage, ChatRole, FlowRun, MAX_RUNS_PER_FLOW, RunStatus};
+use crate::flows::Flow;
+use crate::tasks::executors::claude_code::{ClaudeCodeExecutor, ClaudeSession};
+use crate::tasks::executors::{Executor, LineSink};
+
+/// Tools the chat may use without an agent: the output is in the first
+/// prompt, so it only needs to read.
+const DEFAULT_PERMISSIONS: &[&str] = &["Read"];
+
+#[derive(Deserialize)]
+pub(crate) struct FlowChatRequest {
+    message: String,
+    /// Run to discuss; the latest successful run with output by default.
+    run_id: Option<String>,
+    /// Agent whose permissions and system prompt the chat runs with.
+    agent_id: Option<String>,
+}
+
+type ApiError = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(json!({ "error": message.into() })))
+}
+
+/// POST /flows/{id}/chat — ask a follow-up question about a run's output.
+/// The first message starts a Claude session seeded with the output; later
+/// ones resume it. Streams `session`, then the agent chat events (`text`,
+/// `tool_use`, `result`, ...), then `done` or `error`. The conversation is
+/// saved on the run.
+pub(crate) async fn chat(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<FlowChatRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let message = body.message.trim().to_string();
+    if message.is_empty() {
+        return Err(error(StatusCode::BAD_REQUEST, "message is required"));
+    }
+    let flow = state
+        .flow_repo
+        .get_flow(&id)
+        .await
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "flow not found"))?;
+    let runs = state.flow_repo.get_runs(&id, MAX_RUNS_PER_FLOW).await;
+    let run = match &body.run_id {
+        Some(run_id) => runs.into_iter().find(|r| &r.id == run_id),
+        None => runs
+            .into_iter()
+            .find(|r| r.status == RunStatus::Success && r.output.is_some()),
+    }
+    .ok_or_else(|| error(StatusCode::NOT_FOUND, "no run with output to chat about"))?;
+
+    let (permissions, append_system_prompt) = match &body.agent_id {
+        Some(agent_id) => {
+            let agent = state
+                .agent_repo
+                .get(agent_id)
+                .await
+                .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("agent '{agent_id}' not found")))?;
+            (agent.permissions, agent.append_system_prompt)
+        }
+        None => (DEFAULT_PERMISSIONS.iter().map(|p| p.to_string()).collect(), None),
+    };
+
+    let (session, prompt) = match &run.chat {
+        Some(chat) => (ClaudeSession::Resume(chat.session_id.clone()), message.clone()),
+        None => (ClaudeSession::New(Uuid::new_v4().to_string()), seed_prompt(&flow, &run, &message)),
+    };
+    let session_id = match &session {
+        ClaudeSession::New(id) | ClaudeSession::Resume(id) => id.clone(),
+    };
+    let executor = ClaudeCodeExecutor::new(permissions, append_system_prompt).with_session(session);
+
+    let (line_tx, mut line_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let line_sink: LineSink = Arc::new(move |line| {
+        let _ = line_tx.send(line);
+    });
+    let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let execution = tokio::spawn(async move {
+        executor
+            .execute_streaming(&prompt, &working_dir, Some(line_sink))
+            .await
+    });
+
+    let flow_repo = state.flow_repo.clone();
+    let run_id = run.id.clone();
+    let asked_at = Utc::now();
+    let stream = async_stream::stream! {
+        yield Ok(Event::default().event("session").data(
+            json!({ "run_id": run_id, "session_id": session_id }).to_string()
+        ));
+
+        // Closes when the executor (and its line sink) is dropped
+        while let Some(line) = line_rx.recv().await {
+            for (event, data) in parse_claude_line_to_sse_events(&line) {
+                yield Ok(Event::default().event(event).data(data));
+            }
+        }
+
+        let result = match execution.await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::anyhow!("chat task failed: {e}")),
+        };
+        match result {
+            Ok(result) => {
+                let messages = vec![
+                    ChatMessage { role: ChatRole::User, text: message, at: asked_at },
+                    ChatMessage { role: ChatRole::Assistant, text: result.text.clone(), at: Utc::now() },
+                ];
+                if let Err(e) = flow_repo.append_run_chat(&id, &run_id, &session_id, messages).await {
+                    tracing::warn!(flow_id = %id, run_id = %run_id, error = %e, "Failed to save run chat");
+                }
+                yield Ok(Event::default().event("done").data(
+                    json!({ "text": result.text, "cost": result.cost_usd }).to_string()
+                ));
+            }
+            Err(e) => {
+                tracing::error!(flow_id = %id, run_id = %run_id, error = %e, "Run chat failed");
+                yield Ok(Event::default().event("error").data(
+                    json!({ "message": format!("{e:#}") }).to_string()
+                ));
+            }
+        }
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15))))
+}
+
+/// The first message of a run's chat: the output it's about, then the
+/// user's question.
+fn seed_prompt(flow: &Flow, run: &FlowRun, message: &str) -> String {
+    let finished = run.finished_at.unwrap_or(run.started_at);
+    format!(
+        "Below is the output of the automated flow \"{}\" from its run on {}. \
+         Answer the user's questions about it; say so when the output doesn't cover something.\n\n\
+         ## Flow output\n\n{}\n\n## Question\n\n{message}\n",
+        flow.name,
+        finished.format("%Y-%m-%d %H:%M UTC"),
+        run.output.as_deref().unwrap_or("(this run produced no output)").trim(),
+    )
+}
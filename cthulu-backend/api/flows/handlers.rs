@@ -1,17 +1,49 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, header};
 use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use chrono::Utc;
 use futures::stream::Stream;
 use hyper::StatusCode;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use std::convert::Infallible;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::api::AppState;
 use crate::api::changes::{ChangeType, ResourceChangeEvent, ResourceType};
+use crate::flows::diagram;
+use crate::flows::dry_run::{self, DryRun};
+use crate::flows::experiments::{PromptSplit, variant_stats};
+use crate::flows::history::{FlowRun, MAX_RUNS_PER_FLOW, RunCursor, RunQuery, RunStatus};
+use crate::flows::inputs;
+use crate::flows::profile;
+use crate::flows::repository::StaleRevision;
+use crate::flows::subscriptions::SubscriptionStore;
+use crate::flows::tokens::TokenStore;
+use crate::flows::window::{self, Gate};
 use crate::flows::{Edge, Flow, Node};
+use crate::maintenance;
+use crate::tasks::executors::cache::ExecutorCache;
+use crate::tasks::sources::cache::SourceCache;
+use crate::tasks::archive::{ArchiveStore, DEFAULT_SEARCH_LIMIT};
+use crate::tasks::feedback::FeedbackStore;
+use crate::tasks::trending::TrendingStore;
+use crate::templates;
+use crate::flows::costs::{Budget, CostLedger};
+use crate::flows::wait::WaitStore;
+use crate::flows::aggregate::AggregateStore;
+use crate::flows::run_index::RunIndex;
+use crate::flows::pauses::PauseStore;
+use crate::flows::run_logs::RunLogStore;
+use crate::flows::run_outputs::{RunOutputStore, RunOutputs};
+use crate::flows::approvals::{self, ChangeStore, FlowChanges};
+use crate::tasks::audio::AudioStore;
+use crate::tasks::vectors::VectorStore;
+use crate::tasks::sources::seen::SeenStore;
 
 pub(crate) async fn list_flows(State(state): State<AppState>) -> Json<Value> {
     let flows = state.flow_repo.list_flows().await;
@@ -24,6 +56,8 @@ pub(crate) async fn list_flows(State(state): State<AppState>) -> Json<Value> {
                 "name": f.name,
                 "description": f.description,
                 "enabled": f.enabled,
+                "owners": f.owners,
+                "require_approval": f.require_approval,
                 "node_count": f.nodes.len(),
                 "edge_count": f.edges.len(),
                 "created_at": f.created_at,
@@ -35,10 +69,12 @@ pub(crate) async fn list_flows(State(state): State<AppState>) -> Json<Value> {
     Json(json!({ "flows": summaries }))
 }
 
+/// GET /flows/{id}, with its revision as the `ETag` for `If-Match` on a
+/// later `PUT`.
 pub(crate) async fn get_flow(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<([(header::HeaderName, String); 1], Json<Value>), (StatusCode, Json<Value>)> {
     let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
@@ -46,7 +82,59 @@ pub(crate) async fn get_flow(
         )
     })?;
 
-    Ok(Json(serde_json::to_value(&flow).unwrap()))
+    Ok(([(header::ETAG, etag(&flow))], Json(serde_json::to_value(&flow).unwrap())))
+}
+
+/// A flow's revision as an entity tag.
+fn etag(flow: &Flow) -> String {
+    format!("\"{}\"", flow.version)
+}
+
+/// The revision an edit was based on: the body's `version`, else the
+/// `If-Match` ETag. `None` when the caller gave neither.
+fn base_version(
+    version: Option<u64>,
+    headers: &HeaderMap,
+) -> Result<Option<u64>, (StatusCode, Json<Value>)> {
+    let Some(if_match) = version.is_none().then(|| headers.get(header::IF_MATCH)).flatten() else {
+        return Ok(version);
+    };
+    if_match
+        .to_str()
+        .ok()
+        .and_then(|tag| tag.trim().trim_start_matches("W/").trim_matches('"').parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "If-Match must be the ETag from GET /flows/{id}" })),
+            )
+        })
+}
+
+/// 409 for an edit based on a revision `current` has moved past, with the
+/// flow as it is now for the caller to redo the edit on.
+fn stale(current: &Flow) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::CONFLICT,
+        Json(json!({
+            "error": "conflict",
+            "server_version": current.version,
+            "flow": current,
+        })),
+    )
+}
+
+/// Header naming who is making a request, for flow ownership. It's taken
+/// at its word: cthulu has no logins.
+const USER_HEADER: &str = "x-cthulu-user";
+
+pub(crate) fn caller(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(USER_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|user| !user.is_empty())
 }
 
 #[derive(Deserialize)]
@@ -58,13 +146,22 @@ pub(crate) struct CreateFlowRequest {
     nodes: Vec<Node>,
     #[serde(default)]
     edges: Vec<Edge>,
+    /// Defaults to whoever creates the flow.
+    #[serde(default)]
+    owners: Option<Vec<String>>,
+    #[serde(default)]
+    require_approval: bool,
 }
 
 pub(crate) async fn create_flow(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<CreateFlowRequest>,
 ) -> (StatusCode, Json<Value>) {
     let now = Utc::now();
+    let owners = body
+        .owners
+        .unwrap_or_else(|| caller(&headers).map(String::from).into_iter().collect());
     let flow = Flow {
         id: Uuid::new_v4().to_string(),
         name: body.name,
@@ -73,6 +170,8 @@ pub(crate) async fn create_flow(
         nodes: body.nodes,
         edges: body.edges,
         version: 0,
+        owners,
+        require_approval: body.require_approval,
         created_at: now,
         updated_at: now,
     };
@@ -102,25 +201,20 @@ pub(crate) async fn create_flow(
 
 #[derive(Deserialize)]
 pub(crate) struct UpdateFlowRequest {
-    #[serde(default)]
-    name: Option<String>,
-    #[serde(default)]
-    description: Option<String>,
-    #[serde(default)]
-    enabled: Option<bool>,
-    #[serde(default)]
-    nodes: Option<Vec<Node>>,
-    #[serde(default)]
-    edges: Option<Vec<Edge>>,
+    #[serde(flatten)]
+    changes: FlowChanges,
     #[serde(default)]
     version: Option<u64>,
 }
 
+/// PUT /flows/{id}. On a flow that requires approval, an edit by anyone but
+/// an owner is held as a pending change (202) instead.
 pub(crate) async fn update_flow(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(body): Json<UpdateFlowRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
     let mut flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
@@ -128,42 +222,44 @@ pub(crate) async fn update_flow(
         )
     })?;
 
-    // Optimistic concurrency: reject stale writes
-    if let Some(client_version) = body.version {
-        if client_version < flow.version {
-            return Err((
-                StatusCode::CONFLICT,
-                Json(json!({
-                    "error": "conflict",
-                    "server_version": flow.version
-                })),
-            ));
-        }
+    // Optimistic concurrency: reject edits of a revision since replaced
+    if base_version(body.version, &headers)?.is_some_and(|base| base != flow.version) {
+        return Err(stale(&flow));
     }
 
-    if let Some(name) = body.name {
-        flow.name = name;
-    }
-    if let Some(description) = body.description {
-        flow.description = description;
-    }
-    if let Some(enabled) = body.enabled {
-        flow.enabled = enabled;
-    }
-    if let Some(nodes) = body.nodes {
-        flow.nodes = nodes;
-    }
-    if let Some(edges) = body.edges {
-        flow.edges = edges;
+    let user = caller(&headers);
+    if approvals::needs_approval(&flow, user) {
+        let pending = ChangeStore::new(&state.data_dir)
+            .propose(&flow, user, body.changes)
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "error": format!("failed to save pending change: {e}") })),
+                )
+            })?;
+        tracing::info!(flow = %flow.name, change = %pending.id, author = ?user, "Flow change awaiting approval");
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({ "pending_change": pending, "owners": flow.owners })),
+        ));
     }
-    flow.version += 1;
-    flow.updated_at = Utc::now();
 
-    state.flow_repo.save_flow(flow.clone()).await.map_err(|e| {
-        (
+    body.changes.apply(&mut flow);
+    Ok((StatusCode::OK, save_update(&state, flow).await?))
+}
+
+/// Save an edited flow, restart its trigger and announce the change.
+pub(crate) async fn save_update(
+    state: &AppState,
+    flow: Flow,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let id = flow.id.clone();
+    state.flow_repo.save_flow(flow.clone()).await.map_err(|e| match e.downcast_ref::<StaleRevision>() {
+        Some(StaleRevision { current }) => stale(current),
+        None => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({ "error": format!("failed to save flow: {e}") })),
-        )
+        ),
     })?;
 
     // Restart scheduler trigger (handles enable/disable/config changes)
@@ -174,17 +270,29 @@ pub(crate) async fn update_flow(
     let _ = state.changes_tx.send(ResourceChangeEvent {
         resource_type: ResourceType::Flow,
         change_type: ChangeType::Updated,
-        resource_id: id.clone(),
+        resource_id: id,
         timestamp: chrono::Utc::now(),
     });
 
     Ok(Json(serde_json::to_value(&flow).unwrap()))
 }
 
+/// DELETE /flows/{id}. Only an owner may delete a flow that requires
+/// approval.
 pub(crate) async fn delete_flow(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if let Some(flow) = state.flow_repo.get_flow(&id).await
+        && approvals::needs_approval(&flow, caller(&headers))
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "only an owner can delete this flow", "owners": flow.owners })),
+        ));
+    }
+
     // Stop scheduler trigger before deleting
     state.scheduler.stop_flow(&id).await;
 
@@ -201,6 +309,27 @@ pub(crate) async fn delete_flow(
             Json(json!({ "error": "flow not found" })),
         ));
     }
+    if let Err(e) = TokenStore::new(&state.data_dir).delete_flow(&id) {
+        tracing::warn!(flow_id = %id, error = %e, "Failed to revoke the flow's trigger tokens");
+    }
+    if let Err(e) = CostLedger::new(&state.data_dir).delete_flow(&id) {
+        tracing::warn!(flow_id = %id, error = %e, "Failed to clear the flow's cost ledger");
+    }
+    if let Err(e) = AggregateStore::new(&state.data_dir).delete_flow(&id) {
+        tracing::warn!(flow_id = %id, error = %e, "Failed to drop the flow's held aggregate batches");
+    }
+    if let Err(e) = ChangeStore::new(&state.data_dir).delete_flow(&id) {
+        tracing::warn!(flow_id = %id, error = %e, "Failed to drop the flow's pending changes");
+    }
+    if let Err(e) = RunOutputStore::new(&state.data_dir).delete_flow(&id) {
+        tracing::warn!(flow_id = %id, error = %e, "Failed to remove the flow's saved node outputs");
+    }
+    if let Err(e) = RunLogStore::new(&state.data_dir).delete_flow(&id) {
+        tracing::warn!(flow_id = %id, error = %e, "Failed to remove the flow's run logs");
+    }
+    if let Err(e) = PauseStore::new(&state.data_dir).delete_flow(&id) {
+        tracing::warn!(flow_id = %id, error = %e, "Failed to forget the flow's paused schedule");
+    }
 
     let _ = state.changes_tx.send(ResourceChangeEvent {
         resource_type: ResourceType::Flow,
@@ -229,6 +358,7 @@ pub(crate) async fn trigger_flow(
             Json(json!({ "error": "flow not found" })),
         )
     })?;
+    check_maintenance()?;
 
     // Check if this is a PR trigger request
     let trigger_body: Option<TriggerFlowRequest> = if body.trim().is_empty() {
@@ -258,94 +388,831 @@ pub(crate) async fn trigger_flow(
     }
 
     // Default: one-shot flow execution
+    let runner = flow_runner(&state);
+
+    let flow_repo = state.flow_repo.clone();
+    let flow_name = flow.name.clone();
+
+    tokio::spawn(async move {
+        match runner.execute(&flow, &*flow_repo, None).await {
+            Ok(run) => {
+                tracing::info!(
+                    flow = %flow_name,
+                    run_id = %run.id,
+                    "Flow execution completed"
+                );
+            }
+            Err(e) => {
+                tracing::error!(flow = %flow_name, error = %e, "Flow execution failed");
+            }
+        }
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "triggered", "flow_id": id })),
+    ))
+}
+
+fn flow_runner(state: &AppState) -> crate::flows::runner::FlowRunner {
     let session_bridge = crate::flows::session_bridge::SessionBridge {
         sessions: state.interact_sessions.clone(),
         sessions_path: state.sessions_path.clone(),
         data_dir: state.data_dir.clone(),
         session_streams: state.session_streams.clone(),
     };
-    let runner = crate::flows::runner::FlowRunner {
+    crate::flows::runner::FlowRunner {
         http_client: state.http_client.clone(),
         github_client: state.github_client.clone(),
         events_tx: Some(state.events_tx.clone()),
         sandbox_provider: Some(state.sandbox_provider.clone()),
         agent_repo: Some(state.agent_repo.clone()),
         session_bridge: Some(session_bridge),
-    };
+        executor_cache: Some(ExecutorCache::new(&state.data_dir)),
+        source_cache: Some(SourceCache::new(&state.data_dir)),
+        seen_store: Some(SeenStore::new(&state.data_dir)),
+        archive: Some(ArchiveStore::new(&state.data_dir)),
+        subscriptions: Some(SubscriptionStore::new(&state.data_dir)),
+        feedback: Some(FeedbackStore::new(&state.data_dir)),
+        trending: Some(TrendingStore::new(&state.data_dir)),
+        vectors: Some(VectorStore::new(&state.data_dir)),
+        audio: Some(AudioStore::new(&state.data_dir)),
+        costs: Some(CostLedger::new(&state.data_dir)),
+        waits: Some(WaitStore::new(&state.data_dir)),
+        aggregates: Some(AggregateStore::new(&state.data_dir)),
+        run_outputs: Some(RunOutputStore::new(&state.data_dir)),
+        prompt_repo: Some(state.prompt_repo.clone()),
+    }
+}
+
+/// GET /flows/{id}/inputs — the run parameters declared on the flow's trigger.
+pub(crate) async fn get_flow_inputs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    let specs = inputs::declared_inputs(&flow).map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": format!("{e:#}") })),
+        )
+    })?;
+    Ok(Json(json!({ "flow_id": id, "inputs": specs })))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RunFlowRequest {
+    #[serde(default)]
+    inputs: serde_json::Map<String, Value>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// POST /flows/{id}/run — run a flow once with `inputs` validated against its
+/// declared inputs and injected as trigger context. Outside the trigger's
+/// `active_window` the run is `"dropped"` or `"queued"` until it opens; when
+/// the trigger sets `debounce_secs`, the call joins the pending batch instead
+/// (`"batched"`). With `dry_run`, see `dry_run_flow`.
+pub(crate) async fn run_flow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<RunFlowRequest>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    if body.dry_run {
+        return dry_run_flow(&state, flow, &body.inputs).await;
+    }
+    start_run(&state, flow, &body.inputs).await
+}
+
+/// Run `flow` once as a dry run and wait for it: sources and transforms run
+/// for real, executors echo their prompts, and what the sinks would have
+/// delivered comes back as `deliveries`. The trigger's window and debounce
+/// don't apply.
+async fn dry_run_flow(
+    state: &AppState,
+    flow: Flow,
+    provided: &serde_json::Map<String, Value>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    check_maintenance()?;
+    let specs = inputs::declared_inputs(&flow).map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": format!("{e:#}") })),
+        )
+    })?;
+    let vars = inputs::resolve_inputs(&specs, provided).map_err(|errors| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalid inputs", "details": errors })),
+        )
+    })?;
+
+    let capture = Arc::new(DryRun::default());
+    let runner = flow_runner(state);
+    let run = dry_run::scope(Some(capture.clone()), runner.execute(&flow, &*state.flow_repo, Some(vars)))
+        .await
+        .map_err(|e| {
+            tracing::error!(flow = %flow.name, error = %e, "Dry run failed");
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": format!("{e:#}"), "deliveries": capture.deliveries() })),
+            )
+        })?;
+    tracing::info!(flow = %flow.name, run_id = %run.id, "Dry run completed");
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "status": "dry_run", "flow_id": flow.id, "run": run, "deliveries": capture.deliveries() })),
+    ))
+}
+
+/// Run inputs from a webhook-style request body: a JSON object (bare or
+/// under `inputs`) or form fields, typed per the flow's declared inputs.
+/// An empty body means no inputs.
+pub(crate) fn body_inputs(
+    flow: &Flow,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<serde_json::Map<String, Value>, (StatusCode, Json<Value>)> {
+    body_fields(flow, headers, body).map(unwrap_inputs)
+}
+
+/// The body's top-level fields as sent, before any `inputs` wrapper is
+/// unwrapped.
+pub(crate) fn body_fields(
+    flow: &Flow,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<serde_json::Map<String, Value>, (StatusCode, Json<Value>)> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(serde_json::Map::new());
+    }
+    let is_form = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/x-www-form-urlencoded"));
+    if is_form {
+        let fields: Vec<(String, String)> = serde_urlencoded::from_bytes(body).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("invalid form body: {e}") })),
+            )
+        })?;
+        let specs = inputs::declared_inputs(flow).unwrap_or_default();
+        return Ok(inputs::from_form(&specs, fields));
+    }
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Object(object)) => Ok(object),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "body must be a JSON object or form fields" })),
+        )),
+    }
+}
 
+/// The object under `inputs` when that is the only field, else the fields
+/// themselves.
+pub(crate) fn unwrap_inputs(mut fields: serde_json::Map<String, Value>) -> serde_json::Map<String, Value> {
+    match fields.remove("inputs") {
+        Some(Value::Object(inputs)) if fields.is_empty() => inputs,
+        Some(inputs) => {
+            fields.insert("inputs".to_string(), inputs);
+            fields
+        }
+        None => fields,
+    }
+}
+
+/// Refuse to start runs on request while maintenance mode is on, so the
+/// caller retries later rather than the run waiting in memory.
+fn check_maintenance() -> Result<(), (StatusCode, Json<Value>)> {
+    if maintenance::enabled() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "server is in maintenance mode" })),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate `provided` against the flow's declared inputs and start a run,
+/// honouring its trigger window and debounce. Shared by `POST
+/// /flows/{id}/run` and trigger tokens.
+pub(crate) async fn start_run(
+    state: &AppState,
+    flow: Flow,
+    provided: &serde_json::Map<String, Value>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    check_maintenance()?;
+    let id = flow.id.clone();
+    let specs = inputs::declared_inputs(&flow).map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": format!("{e:#}") })),
+        )
+    })?;
+    let vars = inputs::resolve_inputs(&specs, provided).map_err(|errors| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalid inputs", "details": errors })),
+        )
+    })?;
+
+    match window::gate(&flow, Utc::now()) {
+        Gate::Open => {}
+        Gate::Drop => {
+            return Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "dropped", "flow_id": id, "inputs": vars })),
+            ));
+        }
+        Gate::Queue(open_at) => {
+            let runner = flow_runner(state);
+            let flow_repo = state.flow_repo.clone();
+            let (job_flow, context) = (flow.clone(), vars.clone());
+            state
+                .scheduler
+                .defer_until_open(&flow, open_at, async move {
+                    if let Err(e) = runner.execute(&job_flow, &*flow_repo, Some(context)).await {
+                        tracing::error!(flow = %job_flow.name, error = %e, "Queued flow run failed");
+                    }
+                })
+                .await;
+            return Ok((
+                StatusCode::ACCEPTED,
+                Json(json!({ "status": "queued", "run_at": open_at, "flow_id": id, "inputs": vars })),
+            ));
+        }
+    }
+
+    let Some(context) = state.scheduler.debounce_event(&flow, vars.clone()).await else {
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({ "status": "batched", "flow_id": id, "inputs": vars })),
+        ));
+    };
+    let runner = flow_runner(state);
     let flow_repo = state.flow_repo.clone();
     let flow_name = flow.name.clone();
 
     tokio::spawn(async move {
-        match runner.execute(&flow, &*flow_repo, None).await {
+        match runner.execute(&flow, &*flow_repo, Some(context)).await {
             Ok(run) => {
-                tracing::info!(
-                    flow = %flow_name,
-                    run_id = %run.id,
-                    "Flow execution completed"
-                );
+                tracing::info!(flow = %flow_name, run_id = %run.id, "Flow run with inputs completed");
             }
             Err(e) => {
-                tracing::error!(flow = %flow_name, error = %e, "Flow execution failed");
+                tracing::error!(flow = %flow_name, error = %e, "Flow run with inputs failed");
             }
         }
     });
 
     Ok((
         StatusCode::ACCEPTED,
-        Json(json!({ "status": "triggered", "flow_id": id })),
+        Json(json!({ "status": "triggered", "flow_id": id, "inputs": vars })),
+    ))
+}
+
+/// POST /runs/{id}/resume — run a failed run's flow again from the level it
+/// failed at, reusing the saved outputs of the nodes before it instead of
+/// running them again.
+pub(crate) async fn resume_run(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    check_maintenance()?;
+    let (flow, run) = super::report::find_run(&state, &run_id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "run not found" })),
+        )
+    })?;
+    if !matches!(run.status, RunStatus::Failed | RunStatus::Interrupted) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "only failed or interrupted runs can be resumed" })),
+        ));
+    }
+    if run.dry_run {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "dry runs can't be resumed; start a new one" })),
+        ));
+    }
+    let saved = RunOutputStore::new(&state.data_dir).load(&flow.id, &run.id).ok_or_else(|| {
+        (
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "no node outputs were saved for this run; start a new one" })),
+        )
+    })?;
+    let from_level = RunOutputs::failed_level(&run);
+
+    let runner = flow_runner(&state);
+    let flow_repo = state.flow_repo.clone();
+    let flow_id = flow.id.clone();
+    tokio::spawn(async move {
+        match runner.resume_failed(&flow, &*flow_repo, &run, saved).await {
+            Ok(resumed) => {
+                tracing::info!(flow = %flow.name, run_id = %resumed.id, resumed_from = %run.id, "Resumed flow run completed");
+            }
+            Err(e) => {
+                tracing::error!(flow = %flow.name, resumed_from = %run.id, error = %e, "Resumed flow run failed");
+            }
+        }
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "resumed", "flow_id": flow_id, "resumed_from": run_id, "from_level": from_level })),
     ))
 }
 
+/// GET /flows/{id}/costs — the flow's spend this day, week and month and in
+/// total, against its trigger's budget.
+pub(crate) async fn get_costs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    let budget = Budget::for_flow(&flow).map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": format!("{e:#}") })),
+        )
+    })?;
+    let summary = CostLedger::new(&state.data_dir)
+        .summary(&id, Utc::now().date_naive())
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("{e:#}") })),
+            )
+        })?;
+    Ok(Json(json!({
+        "flow_id": id,
+        "spent": summary,
+        "budget": {
+            "max_cost_usd": budget.max_run_usd,
+            "max_flow_cost_usd": budget.max_flow_usd,
+            "cost_period": budget.period,
+        },
+    })))
+}
+
+/// Most runs one page holds.
+const MAX_RUNS_PAGE: usize = 500;
+
+#[derive(Deserialize)]
+pub(crate) struct RunsQuery {
+    flow_id: Option<String>,
+    /// Comma-separated, e.g. `failed,interrupted`.
+    status: Option<String>,
+    /// RFC 3339; runs started at or after.
+    since: Option<String>,
+    /// RFC 3339; runs started before.
+    until: Option<String>,
+    /// The flow's trigger kind, e.g. `cron`.
+    trigger: Option<String>,
+    /// `newest` (default) or `oldest` first.
+    order: Option<String>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+impl RunsQuery {
+    fn into_run_query(self) -> Result<RunQuery, (StatusCode, Json<Value>)> {
+        let bad = |error: String| (StatusCode::BAD_REQUEST, Json(json!({ "error": error })));
+        let statuses = self
+            .status
+            .iter()
+            .flat_map(|s| s.split(','))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| serde_json::from_value(json!(s)).map_err(|_| bad(format!("unknown status '{s}'"))))
+            .collect::<Result<Vec<RunStatus>, _>>()?;
+        let time = |value: Option<String>, name: &str| {
+            value
+                .map(|v| {
+                    chrono::DateTime::parse_from_rfc3339(&v)
+                        .map(|t| t.with_timezone(&Utc))
+                        .map_err(|_| bad(format!("{name} must be an RFC 3339 time")))
+                })
+                .transpose()
+        };
+        let oldest_first = match self.order.as_deref() {
+            None | Some("newest") => false,
+            Some("oldest") => true,
+            Some(_) => return Err(bad("order must be newest or oldest".to_string())),
+        };
+        let cursor = self
+            .cursor
+            .map(|c| RunCursor::parse(&c).ok_or_else(|| bad("invalid cursor".to_string())))
+            .transpose()?;
+        Ok(RunQuery {
+            flow_id: self.flow_id,
+            statuses,
+            since: time(self.since, "since")?,
+            until: time(self.until, "until")?,
+            trigger: self.trigger,
+            oldest_first,
+            limit: self.limit.unwrap_or(MAX_RUNS_PER_FLOW).clamp(1, MAX_RUNS_PAGE),
+            cursor,
+        })
+    }
+}
+
+/// GET /flows/{id}/runs — the flow's runs, newest first, filtered and paged
+/// as `GET /runs` does.
 pub(crate) async fn get_runs(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Json<Value> {
-    let runs = state.flow_repo.get_runs(&id, 100).await;
-    Json(json!({ "runs": runs }))
+    Query(mut query): Query<RunsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    query.flow_id = Some(id);
+    let page = state.flow_repo.query_runs(&query.into_run_query()?).await;
+    Ok(Json(json!(page)))
 }
 
-pub(crate) async fn stream_runs(
+/// GET /runs — runs of every flow, filtered by `flow_id`, `status`,
+/// `since`, `until` and `trigger`, a page of `limit` at a time: pass a
+/// page's `next_cursor` as `cursor` for the next.
+pub(crate) async fn list_runs(
     State(state): State<AppState>,
-    Path(flow_id): Path<String>,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let mut rx = state.events_tx.subscribe();
-    let stream = async_stream::stream! {
-        loop {
-            match rx.recv().await {
-                Ok(event) => {
-                    if event.flow_id != flow_id {
-                        continue;
-                    }
-                    let sse_event_name = event.event_type.as_sse_event();
-                    let data = serde_json::to_string(&event).unwrap_or_default();
-                    yield Ok(Event::default().event(sse_event_name).data(data));
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                    tracing::warn!(flow_id = %flow_id, skipped = n, "SSE subscriber lagged");
-                    continue;
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                    break;
-                }
-            }
-        }
-    };
-    Sse::new(stream).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15)))
+    Query(query): Query<RunsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let page = state.flow_repo.query_runs(&query.into_run_query()?).await;
+    Ok(Json(json!(page)))
 }
 
-pub(crate) async fn get_node_types() -> Json<Value> {
-    Json(json!({
-        "node_types": [
-            {
-                "kind": "cron",
-                "node_type": "trigger",
-                "label": "Cron Schedule",
-                "config_schema": {
-                    "schedule": { "type": "string", "description": "Cron expression (5-field)", "required": true },
-                    "working_dir": { "type": "string", "description": "Working directory", "default": "." }
-                }
-            },
+#[derive(Deserialize)]
+pub(crate) struct RunSearchQuery {
+    #[serde(default)]
+    q: String,
+    flow_id: Option<String>,
+    limit: Option<usize>,
+}
+
+/// GET /runs/search?q= — full-text search over finished runs' node outputs
+/// and final text, best-matching runs first.
+pub(crate) async fn search_runs(
+    State(state): State<AppState>,
+    Query(query): Query<RunSearchQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if query.q.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "q is required" })),
+        ));
+    }
+    let failed = |e: anyhow::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("search failed: {e}") })),
+        )
+    };
+
+    let index = RunIndex::new(&state.data_dir);
+    index
+        .sync(state.flow_repo.as_ref(), &RunOutputStore::new(&state.data_dir))
+        .await
+        .map_err(failed)?;
+    let results = index
+        .search(&query.q, query.flow_id.as_deref(), query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .await
+        .map_err(failed)?;
+
+    Ok(Json(json!({ "query": query.q, "results": results })))
+}
+
+/// One run, e.g. a citation from `POST /api/ask`.
+pub(crate) async fn get_run(
+    State(state): State<AppState>,
+    Path((id, run_id)): Path<(String, String)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    state
+        .flow_repo
+        .get_runs(&id, MAX_RUNS_PER_FLOW)
+        .await
+        .into_iter()
+        .find(|run| run.id == run_id)
+        .map(|run| Json(json!(run)))
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "run not found" })),
+            )
+        })
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RateRunRequest {
+    rating: u8,
+}
+
+/// Record a 1–5 quality score for a run (feeds prompt A/B comparisons).
+pub(crate) async fn rate_run(
+    State(state): State<AppState>,
+    Path((id, run_id)): Path<(String, String)>,
+    Json(body): Json<RateRunRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if !(1..=5).contains(&body.rating) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "rating must be between 1 and 5" })),
+        ));
+    }
+
+    let runs = state.flow_repo.get_runs(&id, MAX_RUNS_PER_FLOW).await;
+    if !runs.iter().any(|r| r.id == run_id) {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "run not found" })),
+        ));
+    }
+
+    state
+        .flow_repo
+        .set_run_rating(&id, &run_id, body.rating)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("failed to save rating: {e}") })),
+            )
+        })?;
+
+    Ok(Json(json!({ "run_id": run_id, "rating": body.rating })))
+}
+
+/// Per-variant metrics for every executor node running a `prompt_ab` split.
+pub(crate) async fn prompt_experiments(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    let runs = state.flow_repo.get_runs(&id, MAX_RUNS_PER_FLOW).await;
+
+    let experiments: Vec<Value> = flow
+        .nodes
+        .iter()
+        .filter_map(|node| {
+            let split = PromptSplit::from_node(node)?;
+            Some(json!({
+                "node_id": node.id,
+                "label": node.label,
+                "split": split,
+                "variants": variant_stats(&runs, &node.id),
+            }))
+        })
+        .collect();
+
+    Ok(Json(json!({ "experiments": experiments })))
+}
+
+/// Time and cost per node and node kind over the flow's run history.
+pub(crate) async fn get_profile(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    let runs = state.flow_repo.get_runs(&id, MAX_RUNS_PER_FLOW).await;
+    Ok(Json(json!(profile::profile(&flow, &runs))))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct GraphQuery {
+    /// `mermaid` (default) or `dot`.
+    format: Option<String>,
+}
+
+/// GET /flows/{id}/graph?format=mermaid|dot — the flow's nodes and edges as
+/// a text diagram, for design docs and PRs.
+pub(crate) async fn get_graph(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<GraphQuery>,
+) -> Response {
+    let Some(format) = diagram::Format::parse(query.format.as_deref()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "format must be mermaid or dot" })),
+        )
+            .into_response();
+    };
+    let Some(flow) = state.flow_repo.get_flow(&id).await else {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "flow not found" }))).into_response();
+    };
+    (
+        [(header::CONTENT_TYPE, format.content_type())],
+        diagram::render(&flow, format),
+    )
+        .into_response()
+}
+
+const YAML_CONTENT_TYPE: &str = "text/yaml; charset=utf-8";
+
+/// GET /flows/{id}/export — the flow as template YAML, credentials taken
+/// out, for `POST /templates/import-yaml` on another server or to keep in
+/// git.
+pub(crate) async fn export_flow(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let Some(flow) = state.flow_repo.get_flow(&id).await else {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "flow not found" }))).into_response();
+    };
+    let yaml = match templates::flow_to_export_yaml(&flow) {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": format!("flow can't be exported: {e}") })),
+            )
+                .into_response();
+        }
+    };
+    let filename: String = flow
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    (
+        [
+            (header::CONTENT_TYPE, YAML_CONTENT_TYPE.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.yaml\"", filename.trim_matches('-'))),
+        ],
+        yaml,
+    )
+        .into_response()
+}
+
+/// GET /flows/export — every flow in one YAML bundle, by name, which
+/// `POST /templates/import-yaml` takes whole.
+pub(crate) async fn export_flows(State(state): State<AppState>) -> Response {
+    let mut flows = state.flow_repo.list_flows().await;
+    flows.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    (
+        [
+            (header::CONTENT_TYPE, YAML_CONTENT_TYPE),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"flows.yaml\""),
+        ],
+        templates::flows_to_bundle(&flows),
+    )
+        .into_response()
+}
+
+const OUTPUTS_FEED_LIMIT: usize = 20;
+
+#[derive(Deserialize)]
+pub(crate) struct FeedQuery {
+    token: Option<String>,
+}
+
+/// JSON Feed (https://jsonfeed.org/version/1.1) of a flow's recent successful
+/// run outputs. Requires `CTHULU_FEED_TOKEN`, passed as a bearer token or
+/// `?token=` for feed readers that can't set headers.
+pub(crate) async fn outputs_feed(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let Some(expected) = state.feed_token.as_deref() else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "outputs feed is disabled (set CTHULU_FEED_TOKEN)" })),
+        ));
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or(query.token.as_deref());
+    // Compare digests, so how long the check takes says nothing about how
+    // much of the token was right.
+    let digest = |token: &str| Sha256::digest(token.as_bytes());
+    let valid = provided.is_some_and(|provided| digest(provided) == digest(expected));
+    if !valid {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid or missing feed token" })),
+        ));
+    }
+
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    let runs = state.flow_repo.get_runs(&id, MAX_RUNS_PER_FLOW).await;
+
+    Ok(Json(build_outputs_feed(&flow, &runs)))
+}
+
+fn build_outputs_feed(flow: &Flow, runs: &[FlowRun]) -> Value {
+    let items: Vec<Value> = runs
+        .iter()
+        .filter(|r| r.status == RunStatus::Success)
+        .filter_map(|r| {
+            let output = r.output.as_deref()?;
+            let published = r.finished_at.unwrap_or(r.started_at);
+            Some(json!({
+                "id": r.id,
+                "title": format!("{} — {}", flow.name, published.format("%Y-%m-%d %H:%M UTC")),
+                "content_text": output,
+                "date_published": published.to_rfc3339(),
+            }))
+        })
+        .take(OUTPUTS_FEED_LIMIT)
+        .collect();
+
+    json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": flow.name,
+        "description": flow.description,
+        "items": items,
+    })
+}
+
+pub(crate) async fn stream_runs(
+    State(state): State<AppState>,
+    Path(flow_id): Path<String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut rx = state.events_tx.subscribe();
+    let stream = async_stream::stream! {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if event.flow_id != flow_id {
+                        continue;
+                    }
+                    let sse_event_name = event.event_type.as_sse_event();
+                    let data = serde_json::to_string(&event).unwrap_or_default();
+                    yield Ok(Event::default().event(sse_event_name).data(data));
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(flow_id = %flow_id, skipped = n, "SSE subscriber lagged");
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    break;
+                }
+            }
+        }
+    };
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15)))
+}
+
+pub(crate) async fn get_node_types() -> Json<Value> {
+    Json(json!({
+        "node_types": [
+            {
+                "kind": "cron",
+                "node_type": "trigger",
+                "label": "Cron Schedule",
+                "config_schema": {
+                    "schedule": { "type": "string", "description": "Cron expression (5-field)", "required": true },
+                    "timezone": { "type": "string", "description": "IANA timezone the schedule runs in, e.g. Europe/London", "default": "UTC" },
+                    "working_dir": { "type": "string", "description": "Working directory", "default": "." },
+                    "active_window": { "type": "object", "description": "Only run during these hours: { days: [\"mon-fri\"], start: \"09:00\", end: \"18:00\", timezone, outside: drop|queue }. Events outside it are dropped, or queued until it opens" },
+                    "timeout_secs": { "type": "number", "description": "Fail the run after this many seconds, killing whatever is still running (0 = no limit)", "default": 0 },
+                    "node_timeout_secs": { "type": "number", "description": "Default time limit for each node; nodes can set their own timeout_secs (0 = no limit)", "default": 0 },
+                    "on_timeout": { "type": "string", "description": "When a node times out: continue (fail it and what depends on it) or abort the run", "default": "continue" },
+                    "on_interrupt": { "type": "string", "description": "For runs the server stopped in the middle of: mark them interrupted, or rerun them once with the same inputs", "default": "mark" },
+                    "public_intake": { "type": "object", "description": "Accept public submissions at POST /api/intake/{flow_id} as run inputs: { per_minute, per_hour, max_bytes, captcha: { provider: turnstile|hcaptcha|recaptcha, secret_env } }" },
+                    "max_cost_usd": { "type": "number", "description": "Abort a run once its executors have cost more than this" },
+                    "max_flow_cost_usd": { "type": "number", "description": "Abort runs once the flow has cost more than this per cost_period; later runs don't start" },
+                    "cost_period": { "type": "string", "description": "Period max_flow_cost_usd covers: day, week or month (UTC)", "default": "month" },
+                    "max_concurrent_runs": { "type": "number", "description": "Runs of this flow at once; more queue until one finishes (unset = no limit)" },
+                    "priority": { "type": "number", "description": "Place of this flow's runs in the run and executor queues; higher goes first", "default": 0 },
+                    "priority_when": { "type": "array", "description": "Rules [{ condition, priority }] setting an event's priority from its context variables; the first that holds wins" },
+                    "retention": { "type": "object", "description": "{ keep_last_n_runs, max_age_days } overriding the server's run retention for this flow" },
+                    "cost_alert": { "type": "object", "description": "Sink told when a budget stops a run: { kind: slack|email|..., config: {...} }" }
+                }
+            },
             {
                 "kind": "github-pr",
                 "node_type": "trigger",
@@ -355,7 +1222,108 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                     "poll_interval": { "type": "number", "description": "Poll interval in seconds", "default": 60 },
                     "skip_drafts": { "type": "boolean", "default": true },
                     "review_on_push": { "type": "boolean", "default": false },
-                    "max_diff_size": { "type": "number", "description": "Max inline diff size in bytes", "default": 50000 }
+                    "review_on_ready": { "type": "boolean", "description": "Review a PR when it moves from draft to ready, with {{review_type}} = ready_for_review, even if it was reviewed as a draft", "default": true },
+                    "auto_review": { "type": "boolean", "description": "Review new PRs automatically; turn off for flows that only answer comment commands", "default": true },
+                    "commands": { "type": "array", "description": "/cthulu <command> PR comments this flow answers (maintainers only). Adds {{command}}, {{command_args}} and {{requested_by}}", "default": ["review"] },
+                    "max_diff_size": { "type": "number", "description": "Max inline diff size in bytes", "default": 50000 },
+                    "repo_context": { "type": "boolean", "description": "Add {{languages}}, {{codeowners}} and {{incident_tags}} to the review context", "default": true },
+                    "incident_label": { "type": "string", "description": "Issue label marking incidents, for {{incident_tags}}", "default": "incident" },
+                    "mention_owners": { "type": "boolean", "description": "Fill {{owner_mentions}} with the CODEOWNERS of touched files", "default": true },
+                    "request_owner_reviews": { "type": "boolean", "description": "Request those code owners as reviewers on the first review", "default": false },
+                    "critical_paths": { "type": "array", "description": "Globs whose changes raise {{risk_score}}, e.g. migrations/**", "default": [] },
+                    "pre_review": { "type": "object", "description": "Run a test/lint command in a sandbox at the PR head first: { command, timeout_secs, max_output_chars, network, reports: [{ path, format }] }. Adds {{test_status}}, {{test_results}} and {{ci_reports}}" },
+                    "active_window": { "type": "object", "description": "Only run during these hours: { days: [\"mon-fri\"], start: \"09:00\", end: \"18:00\", timezone, outside: drop|queue }. Events outside it are dropped, or queued until it opens" },
+                    "timeout_secs": { "type": "number", "description": "Fail the run after this many seconds, killing whatever is still running (0 = no limit)", "default": 0 },
+                    "node_timeout_secs": { "type": "number", "description": "Default time limit for each node; nodes can set their own timeout_secs (0 = no limit)", "default": 0 },
+                    "on_timeout": { "type": "string", "description": "When a node times out: continue (fail it and what depends on it) or abort the run", "default": "continue" },
+                    "on_interrupt": { "type": "string", "description": "For runs the server stopped in the middle of: mark them interrupted, or rerun them once with the same inputs", "default": "mark" },
+                    "public_intake": { "type": "object", "description": "Accept public submissions at POST /api/intake/{flow_id} as run inputs: { per_minute, per_hour, max_bytes, captcha: { provider: turnstile|hcaptcha|recaptcha, secret_env } }" },
+                    "max_cost_usd": { "type": "number", "description": "Abort a run once its executors have cost more than this" },
+                    "max_flow_cost_usd": { "type": "number", "description": "Abort runs once the flow has cost more than this per cost_period; later runs don't start" },
+                    "cost_period": { "type": "string", "description": "Period max_flow_cost_usd covers: day, week or month (UTC)", "default": "month" },
+                    "max_concurrent_runs": { "type": "number", "description": "Runs of this flow at once; more queue until one finishes (unset = no limit)" },
+                    "priority": { "type": "number", "description": "Place of this flow's runs in the run and executor queues; higher goes first", "default": 0 },
+                    "priority_when": { "type": "array", "description": "Rules [{ condition, priority }] setting an event's priority from its context variables; the first that holds wins" },
+                    "retention": { "type": "object", "description": "{ keep_last_n_runs, max_age_days } overriding the server's run retention for this flow" },
+                    "cost_alert": { "type": "object", "description": "Sink told when a budget stops a run: { kind: slack|email|..., config: {...} }" }
+                }
+            },
+            {
+                "kind": "github-push",
+                "node_type": "trigger",
+                "label": "GitHub Push",
+                "config_schema": {
+                    "repos": { "type": "array", "description": "Repository configs [{slug, path}]", "required": true },
+                    "branches": { "type": "array", "description": "Branches to watch (globs allowed, e.g. release/*)", "default": ["main"] },
+                    "poll_interval": { "type": "number", "description": "Poll interval in seconds", "default": 60 },
+                    "max_diff_size": { "type": "number", "description": "Max inline diff size in bytes", "default": 50000 },
+                    "critical_paths": { "type": "array", "description": "Globs whose changes raise {{risk_score}}", "default": [] },
+                    "active_window": { "type": "object", "description": "Only run during these hours: { days: [\"mon-fri\"], start: \"09:00\", end: \"18:00\", timezone, outside: drop|queue }. Events outside it are dropped, or queued until it opens" },
+                    "timeout_secs": { "type": "number", "description": "Fail the run after this many seconds, killing whatever is still running (0 = no limit)", "default": 0 },
+                    "node_timeout_secs": { "type": "number", "description": "Default time limit for each node; nodes can set their own timeout_secs (0 = no limit)", "default": 0 },
+                    "on_timeout": { "type": "string", "description": "When a node times out: continue (fail it and what depends on it) or abort the run", "default": "continue" },
+                    "on_interrupt": { "type": "string", "description": "For runs the server stopped in the middle of: mark them interrupted, or rerun them once with the same inputs", "default": "mark" },
+                    "public_intake": { "type": "object", "description": "Accept public submissions at POST /api/intake/{flow_id} as run inputs: { per_minute, per_hour, max_bytes, captcha: { provider: turnstile|hcaptcha|recaptcha, secret_env } }" },
+                    "max_cost_usd": { "type": "number", "description": "Abort a run once its executors have cost more than this" },
+                    "max_flow_cost_usd": { "type": "number", "description": "Abort runs once the flow has cost more than this per cost_period; later runs don't start" },
+                    "cost_period": { "type": "string", "description": "Period max_flow_cost_usd covers: day, week or month (UTC)", "default": "month" },
+                    "max_concurrent_runs": { "type": "number", "description": "Runs of this flow at once; more queue until one finishes (unset = no limit)" },
+                    "priority": { "type": "number", "description": "Place of this flow's runs in the run and executor queues; higher goes first", "default": 0 },
+                    "priority_when": { "type": "array", "description": "Rules [{ condition, priority }] setting an event's priority from its context variables; the first that holds wins" },
+                    "retention": { "type": "object", "description": "{ keep_last_n_runs, max_age_days } overriding the server's run retention for this flow" },
+                    "cost_alert": { "type": "object", "description": "Sink told when a budget stops a run: { kind: slack|email|..., config: {...} }" }
+                }
+            },
+            {
+                "kind": "github-issue",
+                "node_type": "trigger",
+                "label": "GitHub Issue",
+                "config_schema": {
+                    "repos": { "type": "array", "description": "Repository configs [{slug, path}]", "required": true },
+                    "without_labels": { "type": "array", "description": "Skip issues opened with any of these labels, e.g. triaged", "default": [] },
+                    "unlabeled_only": { "type": "boolean", "description": "Only trigger on issues opened without labels", "default": false },
+                    "poll_interval": { "type": "number", "description": "Poll interval in seconds", "default": 60 },
+                    "debounce_secs": { "type": "number", "description": "Coalesce issues opened within this many seconds into one run (0 = run per issue)", "default": 0 },
+                    "max_batch": { "type": "number", "description": "Run a debounced batch early once it holds this many events", "default": 50 },
+                    "active_window": { "type": "object", "description": "Only run during these hours: { days: [\"mon-fri\"], start: \"09:00\", end: \"18:00\", timezone, outside: drop|queue }. Events outside it are dropped, or queued until it opens" },
+                    "timeout_secs": { "type": "number", "description": "Fail the run after this many seconds, killing whatever is still running (0 = no limit)", "default": 0 },
+                    "node_timeout_secs": { "type": "number", "description": "Default time limit for each node; nodes can set their own timeout_secs (0 = no limit)", "default": 0 },
+                    "on_timeout": { "type": "string", "description": "When a node times out: continue (fail it and what depends on it) or abort the run", "default": "continue" },
+                    "on_interrupt": { "type": "string", "description": "For runs the server stopped in the middle of: mark them interrupted, or rerun them once with the same inputs", "default": "mark" },
+                    "public_intake": { "type": "object", "description": "Accept public submissions at POST /api/intake/{flow_id} as run inputs: { per_minute, per_hour, max_bytes, captcha: { provider: turnstile|hcaptcha|recaptcha, secret_env } }" },
+                    "max_cost_usd": { "type": "number", "description": "Abort a run once its executors have cost more than this" },
+                    "max_flow_cost_usd": { "type": "number", "description": "Abort runs once the flow has cost more than this per cost_period; later runs don't start" },
+                    "cost_period": { "type": "string", "description": "Period max_flow_cost_usd covers: day, week or month (UTC)", "default": "month" },
+                    "max_concurrent_runs": { "type": "number", "description": "Runs of this flow at once; more queue until one finishes (unset = no limit)" },
+                    "priority": { "type": "number", "description": "Place of this flow's runs in the run and executor queues; higher goes first", "default": 0 },
+                    "priority_when": { "type": "array", "description": "Rules [{ condition, priority }] setting an event's priority from its context variables; the first that holds wins" },
+                    "retention": { "type": "object", "description": "{ keep_last_n_runs, max_age_days } overriding the server's run retention for this flow" },
+                    "cost_alert": { "type": "object", "description": "Sink told when a budget stops a run: { kind: slack|email|..., config: {...} }" }
+                }
+            },
+            {
+                "kind": "github-release",
+                "node_type": "trigger",
+                "label": "GitHub Release",
+                "config_schema": {
+                    "repos": { "type": "array", "description": "Repository configs [{slug, path}]", "required": true },
+                    "include_prereleases": { "type": "boolean", "description": "Also run on pre-releases", "default": false },
+                    "tag_pattern": { "type": "string", "description": "Only releases whose tag matches this glob, e.g. v*" },
+                    "poll_interval": { "type": "number", "description": "Poll interval in seconds", "default": 300 },
+                    "active_window": { "type": "object", "description": "Only run during these hours: { days: [\"mon-fri\"], start: \"09:00\", end: \"18:00\", timezone, outside: drop|queue }. Events outside it are dropped, or queued until it opens" },
+                    "timeout_secs": { "type": "number", "description": "Fail the run after this many seconds, killing whatever is still running (0 = no limit)", "default": 0 },
+                    "node_timeout_secs": { "type": "number", "description": "Default time limit for each node; nodes can set their own timeout_secs (0 = no limit)", "default": 0 },
+                    "on_timeout": { "type": "string", "description": "When a node times out: continue (fail it and what depends on it) or abort the run", "default": "continue" },
+                    "on_interrupt": { "type": "string", "description": "For runs the server stopped in the middle of: mark them interrupted, or rerun them once with the same inputs", "default": "mark" },
+                    "public_intake": { "type": "object", "description": "Accept public submissions at POST /api/intake/{flow_id} as run inputs: { per_minute, per_hour, max_bytes, captcha: { provider: turnstile|hcaptcha|recaptcha, secret_env } }" },
+                    "max_cost_usd": { "type": "number", "description": "Abort a run once its executors have cost more than this" },
+                    "max_flow_cost_usd": { "type": "number", "description": "Abort runs once the flow has cost more than this per cost_period; later runs don't start" },
+                    "cost_period": { "type": "string", "description": "Period max_flow_cost_usd covers: day, week or month (UTC)", "default": "month" },
+                    "max_concurrent_runs": { "type": "number", "description": "Runs of this flow at once; more queue until one finishes (unset = no limit)" },
+                    "priority": { "type": "number", "description": "Place of this flow's runs in the run and executor queues; higher goes first", "default": 0 },
+                    "priority_when": { "type": "array", "description": "Rules [{ condition, priority }] setting an event's priority from its context variables; the first that holds wins" },
+                    "retention": { "type": "object", "description": "{ keep_last_n_runs, max_age_days } overriding the server's run retention for this flow" },
+                    "cost_alert": { "type": "object", "description": "Sink told when a budget stops a run: { kind: slack|email|..., config: {...} }" }
                 }
             },
             {
@@ -363,14 +1331,46 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "node_type": "trigger",
                 "label": "Webhook",
                 "config_schema": {
-                    "path": { "type": "string", "description": "Webhook URL path", "required": true }
+                    "path": { "type": "string", "description": "Webhook URL path", "required": true },
+                    "active_window": { "type": "object", "description": "Only run during these hours: { days: [\"mon-fri\"], start: \"09:00\", end: \"18:00\", timezone, outside: drop|queue }. Events outside it are dropped, or queued until it opens" },
+                    "timeout_secs": { "type": "number", "description": "Fail the run after this many seconds, killing whatever is still running (0 = no limit)", "default": 0 },
+                    "node_timeout_secs": { "type": "number", "description": "Default time limit for each node; nodes can set their own timeout_secs (0 = no limit)", "default": 0 },
+                    "on_timeout": { "type": "string", "description": "When a node times out: continue (fail it and what depends on it) or abort the run", "default": "continue" },
+                    "on_interrupt": { "type": "string", "description": "For runs the server stopped in the middle of: mark them interrupted, or rerun them once with the same inputs", "default": "mark" },
+                    "public_intake": { "type": "object", "description": "Accept public submissions at POST /api/intake/{flow_id} as run inputs: { per_minute, per_hour, max_bytes, captcha: { provider: turnstile|hcaptcha|recaptcha, secret_env } }" },
+                    "max_cost_usd": { "type": "number", "description": "Abort a run once its executors have cost more than this" },
+                    "max_flow_cost_usd": { "type": "number", "description": "Abort runs once the flow has cost more than this per cost_period; later runs don't start" },
+                    "cost_period": { "type": "string", "description": "Period max_flow_cost_usd covers: day, week or month (UTC)", "default": "month" },
+                    "max_concurrent_runs": { "type": "number", "description": "Runs of this flow at once; more queue until one finishes (unset = no limit)" },
+                    "priority": { "type": "number", "description": "Place of this flow's runs in the run and executor queues; higher goes first", "default": 0 },
+                    "priority_when": { "type": "array", "description": "Rules [{ condition, priority }] setting an event's priority from its context variables; the first that holds wins" },
+                    "retention": { "type": "object", "description": "{ keep_last_n_runs, max_age_days } overriding the server's run retention for this flow" },
+                    "cost_alert": { "type": "object", "description": "Sink told when a budget stops a run: { kind: slack|email|..., config: {...} }" }
                 }
             },
             {
                 "kind": "manual",
                 "node_type": "trigger",
                 "label": "Manual Trigger",
-                "config_schema": {}
+                "config_schema": {
+                    "inputs": { "type": "array", "description": "Run parameters [{name, type: string|number|boolean, required, default, options}], passed via POST /flows/{id}/run" },
+                    "debounce_secs": { "type": "number", "description": "Coalesce runs requested within this many seconds into one run (0 = run per request)", "default": 0 },
+                    "max_batch": { "type": "number", "description": "Run a debounced batch early once it holds this many events", "default": 50 },
+                    "active_window": { "type": "object", "description": "Only run during these hours: { days: [\"mon-fri\"], start: \"09:00\", end: \"18:00\", timezone, outside: drop|queue }. Events outside it are dropped, or queued until it opens" },
+                    "timeout_secs": { "type": "number", "description": "Fail the run after this many seconds, killing whatever is still running (0 = no limit)", "default": 0 },
+                    "node_timeout_secs": { "type": "number", "description": "Default time limit for each node; nodes can set their own timeout_secs (0 = no limit)", "default": 0 },
+                    "on_timeout": { "type": "string", "description": "When a node times out: continue (fail it and what depends on it) or abort the run", "default": "continue" },
+                    "on_interrupt": { "type": "string", "description": "For runs the server stopped in the middle of: mark them interrupted, or rerun them once with the same inputs", "default": "mark" },
+                    "public_intake": { "type": "object", "description": "Accept public submissions at POST /api/intake/{flow_id} as run inputs: { per_minute, per_hour, max_bytes, captcha: { provider: turnstile|hcaptcha|recaptcha, secret_env } }" },
+                    "max_cost_usd": { "type": "number", "description": "Abort a run once its executors have cost more than this" },
+                    "max_flow_cost_usd": { "type": "number", "description": "Abort runs once the flow has cost more than this per cost_period; later runs don't start" },
+                    "cost_period": { "type": "string", "description": "Period max_flow_cost_usd covers: day, week or month (UTC)", "default": "month" },
+                    "max_concurrent_runs": { "type": "number", "description": "Runs of this flow at once; more queue until one finishes (unset = no limit)" },
+                    "priority": { "type": "number", "description": "Place of this flow's runs in the run and executor queues; higher goes first", "default": 0 },
+                    "priority_when": { "type": "array", "description": "Rules [{ condition, priority }] setting an event's priority from its context variables; the first that holds wins" },
+                    "retention": { "type": "object", "description": "{ keep_last_n_runs, max_age_days } overriding the server's run retention for this flow" },
+                    "cost_alert": { "type": "object", "description": "Sink told when a budget stops a run: { kind: slack|email|..., config: {...} }" }
+                }
             },
             {
                 "kind": "rss",
@@ -379,7 +1379,10 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "config_schema": {
                     "url": { "type": "string", "description": "Feed URL", "required": true },
                     "limit": { "type": "number", "description": "Max items to fetch", "default": 10 },
-                    "keywords": { "type": "array", "description": "Filter items by keywords (case-insensitive, any match)", "default": [] }
+                    "keywords": { "type": "array", "description": "Filter items by keywords (case-insensitive, any match)", "default": [] },
+                    "dedupe": { "type": "boolean", "description": "Skip items already emitted by earlier successful runs", "default": false },
+                    "archive": { "type": "boolean", "description": "Keep fetched items in the archive searchable at /api/search", "default": false },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the items fetched with the same config within N seconds (0 = off)", "default": 0 }
                 }
             },
             {
@@ -388,7 +1391,34 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "label": "Web Scrape",
                 "config_schema": {
                     "url": { "type": "string", "description": "Page URL to scrape", "required": true },
-                    "keywords": { "type": "array", "description": "Filter by keywords (case-insensitive, any match)", "default": [] }
+                    "keywords": { "type": "array", "description": "Filter by keywords (case-insensitive, any match)", "default": [] },
+                    "sandboxed": { "type": "boolean", "description": "Fetch the page inside the sandbox provider instead of on the host", "default": false },
+                    "render": { "type": "boolean", "description": "Render JS-heavy pages in headless Chromium inside the sandbox (needs a non-host sandbox provider; no cookies)", "default": false },
+                    "wait_for_selector": { "type": "string", "description": "When rendering, fail unless this CSS selector is in the rendered page" },
+                    "dedupe": { "type": "boolean", "description": "Skip items already emitted by earlier successful runs", "default": false },
+                    "archive": { "type": "boolean", "description": "Keep fetched items in the archive searchable at /api/search", "default": false },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the items fetched with the same config within N seconds (0 = off)", "default": 0 }
+                }
+            },
+            {
+                "kind": "web-interactive",
+                "node_type": "source",
+                "label": "Web Scraper (Interactive)",
+                "config_schema": {
+                    "url": { "type": "string", "description": "Page URL to open", "required": true },
+                    "steps": { "type": "array", "description": "Browser steps run before extraction: {action: click|fill|wait_for|sleep, selector, value, timeout_ms, ms}" },
+                    "webdriver_url_env": { "type": "string", "description": "Env var for WebDriver endpoint (falls back to http://localhost:9515)", "default": "WEBDRIVER_URL" },
+                    "base_url": { "type": "string", "description": "Base URL for resolving relative links" },
+                    "items_selector": { "type": "string", "description": "CSS selector for item containers", "required": true },
+                    "title_selector": { "type": "string", "description": "CSS selector for title within item" },
+                    "url_selector": { "type": "string", "description": "CSS selector for link within item" },
+                    "summary_selector": { "type": "string", "description": "CSS selector for summary within item" },
+                    "date_selector": { "type": "string", "description": "CSS selector for date within item" },
+                    "date_format": { "type": "string", "description": "Date format string (e.g. %Y-%m-%d)" },
+                    "limit": { "type": "number", "description": "Max items to extract", "default": 10 },
+                    "dedupe": { "type": "boolean", "description": "Skip items already emitted by earlier successful runs", "default": false },
+                    "archive": { "type": "boolean", "description": "Keep fetched items in the archive searchable at /api/search", "default": false },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the items fetched with the same config within N seconds (0 = off)", "default": 0 }
                 }
             },
             {
@@ -397,7 +1427,32 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "label": "GitHub Merged PRs",
                 "config_schema": {
                     "repos": { "type": "array", "description": "Repository slugs [\"owner/repo\"]", "required": true },
-                    "since_days": { "type": "number", "description": "Days to look back", "default": 7 }
+                    "since_days": { "type": "number", "description": "Days to look back", "default": 7 },
+                    "dedupe": { "type": "boolean", "description": "Skip items already emitted by earlier successful runs", "default": false },
+                    "archive": { "type": "boolean", "description": "Keep fetched items in the archive searchable at /api/search", "default": false },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the items fetched with the same config within N seconds (0 = off)", "default": 0 }
+                }
+            },
+            {
+                "kind": "github-activity",
+                "node_type": "source",
+                "label": "GitHub Activity",
+                "config_schema": {
+                    "users": { "type": "array", "description": "GitHub logins, one item per person", "required": true },
+                    "repos": { "type": "array", "description": "Limit to these repository slugs [\"owner/repo\"]" },
+                    "orgs": { "type": "array", "description": "Limit to these organizations" },
+                    "since_hours": { "type": "number", "description": "Hours to look back", "default": 24 }
+                }
+            },
+            {
+                "kind": "github-repo-health",
+                "node_type": "source",
+                "label": "GitHub Repo Health",
+                "config_schema": {
+                    "repos": { "type": "array", "description": "Repository slugs [\"owner/repo\"]", "required": true },
+                    "signals": { "type": "array", "description": "Subset of stale-branches, pr-age, unanswered-issues, dependencies (default: all)" },
+                    "stale_days": { "type": "number", "description": "Days without commits before a branch is stale", "default": 30 },
+                    "response_days": { "type": "number", "description": "Days without a comment before an issue counts as unanswered", "default": 7 }
                 }
             },
             {
@@ -413,7 +1468,15 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                     "summary_selector": { "type": "string", "description": "CSS selector for summary within item" },
                     "date_selector": { "type": "string", "description": "CSS selector for date within item" },
                     "date_format": { "type": "string", "description": "Date format string (e.g. %Y-%m-%d)" },
-                    "limit": { "type": "number", "description": "Max items to extract", "default": 10 }
+                    "limit": { "type": "number", "description": "Max items to extract", "default": 10 },
+                    "next_page_selector": { "type": "string", "description": "CSS selector for the next-page link (enables pagination on the same origin)" },
+                    "max_pages": { "type": "number", "description": "Max pages to follow when paginating", "default": 1 },
+                    "cookies_env": { "type": "string", "description": "Env var holding a Cookie header for gated pages" },
+                    "login": { "type": "object", "description": "Form login run before scraping: {url, fields, fields_env}" },
+                    "sandboxed": { "type": "boolean", "description": "Fetch the page inside the sandbox provider instead of on the host", "default": false },
+                    "dedupe": { "type": "boolean", "description": "Skip items already emitted by earlier successful runs", "default": false },
+                    "archive": { "type": "boolean", "description": "Keep fetched items in the archive searchable at /api/search", "default": false },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the items fetched with the same config within N seconds (0 = off)", "default": 0 }
                 }
             },
             {
@@ -422,14 +1485,288 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "label": "Market Data",
                 "config_schema": {}
             },
+            {
+                "kind": "market-quotes",
+                "node_type": "source",
+                "label": "Market Quotes",
+                "config_schema": {
+                    "symbols": { "type": "array", "description": "Ticker symbols [\"AAPL\", \"^GSPC\", \"BTC-USD\"]", "required": true }
+                }
+            },
+            {
+                "kind": "sql",
+                "node_type": "source",
+                "label": "SQL Query",
+                "config_schema": {
+                    "connection_env": { "type": "string", "description": "Env var holding a postgres:// or mysql:// connection URL", "required": true },
+                    "query": { "type": "string", "description": "Read-only SELECT query; each row becomes an item", "required": true },
+                    "title_column": { "type": "string", "description": "Column used as the item title", "required": true },
+                    "summary_column": { "type": "string", "description": "Column used as the item summary (defaults to all other columns)" }
+                }
+            },
+            {
+                "kind": "sentry-issues",
+                "node_type": "source",
+                "label": "Sentry Issues",
+                "config_schema": {
+                    "organization": { "type": "string", "description": "Sentry organization slug", "required": true },
+                    "project": { "type": "string", "description": "Sentry project slug", "required": true },
+                    "token_env": { "type": "string", "description": "Env var for Sentry auth token", "default": "SENTRY_AUTH_TOKEN" },
+                    "base_url": { "type": "string", "description": "Sentry base URL (self-hosted)", "default": "https://sentry.io" },
+                    "since_hours": { "type": "number", "description": "Only issues seen in the last N hours", "default": 24 },
+                    "limit": { "type": "number", "description": "Max issues to fetch", "default": 10 }
+                }
+            },
+            {
+                "kind": "file-watch",
+                "node_type": "source",
+                "label": "File Watcher",
+                "config_schema": {
+                    "pattern": { "type": "string", "description": "File glob, e.g. ~/Downloads/**/*.csv", "required": true },
+                    "since_hours": { "type": "number", "description": "Lookback when the flow has no previous successful run", "default": 24 },
+                    "snippet_chars": { "type": "number", "description": "Include the first N characters of text files (0 = off)", "default": 0 },
+                    "limit": { "type": "number", "description": "Max files to emit (newest first)", "default": 10 }
+                }
+            },
+            {
+                "kind": "ci-report",
+                "node_type": "source",
+                "label": "CI Report",
+                "config_schema": {
+                    "path": { "type": "string", "description": "Report file, glob (newest match) or http(s) URL", "required": true },
+                    "format": { "type": "string", "description": "lcov, llvm-cov (cargo llvm-cov --json), clippy (--message-format=json) or eslint (--format json)", "required": true },
+                    "token_env": { "type": "string", "description": "Env var with a bearer token for URL downloads" },
+                    "limit": { "type": "number", "description": "Max per-file or per-finding items after the headline", "default": 10 }
+                }
+            },
+            {
+                "kind": "arxiv",
+                "node_type": "source",
+                "label": "arXiv Papers",
+                "config_schema": {
+                    "categories": { "type": "array", "description": "arXiv categories, e.g. cs.CL, cs.LG" },
+                    "keywords": { "type": "array", "description": "Match any of these terms anywhere in the paper metadata" },
+                    "limit": { "type": "number", "description": "Max papers to fetch (newest first)", "default": 10 },
+                    "dedupe": { "type": "boolean", "description": "Skip items already emitted by earlier successful runs", "default": false },
+                    "archive": { "type": "boolean", "description": "Keep fetched items in the archive searchable at /api/search", "default": false },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the items fetched with the same config within N seconds (0 = off)", "default": 0 }
+                }
+            },
+            {
+                "kind": "stackexchange",
+                "node_type": "source",
+                "label": "Stack Exchange Questions",
+                "config_schema": {
+                    "tags": { "type": "array", "description": "Question tags; any tag matches", "required": true },
+                    "site": { "type": "string", "description": "Stack Exchange site, e.g. stackoverflow, serverfault", "default": "stackoverflow" },
+                    "min_score": { "type": "number", "description": "Minimum question score", "default": 0 },
+                    "min_answers": { "type": "number", "description": "Minimum answer count", "default": 0 },
+                    "unanswered": { "type": "boolean", "description": "Only questions without an accepted or upvoted answer", "default": false },
+                    "since_days": { "type": "number", "description": "Only questions asked within this many days", "default": 7 },
+                    "key_env": { "type": "string", "description": "Env var with a Stack Apps key (raises the request quota)" },
+                    "limit": { "type": "number", "description": "Max questions to fetch (newest first)", "default": 10 },
+                    "dedupe": { "type": "boolean", "description": "Skip items already emitted by earlier successful runs", "default": false },
+                    "archive": { "type": "boolean", "description": "Keep fetched items in the archive searchable at /api/search", "default": false },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the items fetched with the same config within N seconds (0 = off)", "default": 0 }
+                }
+            },
+            {
+                "kind": "pocket",
+                "node_type": "source",
+                "label": "Pocket Saves",
+                "config_schema": {
+                    "consumer_key_env": { "type": "string", "description": "Env var for Pocket consumer key", "default": "POCKET_CONSUMER_KEY" },
+                    "access_token_env": { "type": "string", "description": "Env var for Pocket access token", "default": "POCKET_ACCESS_TOKEN" },
+                    "tag": { "type": "string", "description": "Only items with this tag" },
+                    "since_days": { "type": "number", "description": "Items saved in the last N days", "default": 7 },
+                    "limit": { "type": "number", "description": "Max items to fetch", "default": 10 }
+                }
+            },
+            {
+                "kind": "readwise",
+                "node_type": "source",
+                "label": "Readwise Reader",
+                "config_schema": {
+                    "token_env": { "type": "string", "description": "Env var for Readwise access token", "default": "READWISE_TOKEN" },
+                    "location": { "type": "string", "description": "Reader location (new, later, shortlist, archive, feed)" },
+                    "since_days": { "type": "number", "description": "Documents saved in the last N days", "default": 7 },
+                    "limit": { "type": "number", "description": "Max documents to fetch", "default": 10 }
+                }
+            },
+            {
+                "kind": "weather",
+                "node_type": "source",
+                "label": "Weather (Open-Meteo)",
+                "config_schema": {
+                    "latitude": { "type": "number", "description": "Latitude in decimal degrees", "required": true },
+                    "longitude": { "type": "number", "description": "Longitude in decimal degrees", "required": true },
+                    "location": { "type": "string", "description": "Display name for the location" },
+                    "days": { "type": "number", "description": "Forecast days (1-16)", "default": 1 }
+                }
+            },
+            {
+                "kind": "vector-search",
+                "node_type": "source",
+                "label": "Vector Search",
+                "config_schema": {
+                    "query": { "type": "string", "description": "Search text, rendered with trigger variables (e.g. {{question}})", "required": true },
+                    "collection": { "type": "string", "description": "Collection filled by a vector-store sink", "default": "default" },
+                    "top_k": { "type": "number", "description": "Max items returned", "default": 5 },
+                    "min_score": { "type": "number", "description": "Drop hits below this cosine similarity" },
+                    "backend": { "type": "string", "description": "local, qdrant or pgvector", "default": "local" }
+                }
+            },
             {
                 "kind": "claude-code",
                 "node_type": "executor",
                 "label": "Claude Code",
                 "config_schema": {
                     "agent_id": { "type": "string", "description": "ID of the agent to use", "required": true },
-                    "prompt": { "type": "string", "description": "Prompt file path or inline prompt", "required": true },
-                    "working_dir": { "type": "string", "description": "Working directory", "default": "." }
+                    "prompt": { "type": "string", "description": "Prompt file path or inline prompt (unless prompt_ref is set)" },
+                    "prompt_ref": { "type": "string", "description": "Prompt library id or title, optionally pinned as name@version" },
+                    "prompt_ab": { "type": "object", "description": "A/B test two prompt_refs: { a, b, b_percent } sends b_percent% of runs to b" },
+                    "working_dir": { "type": "string", "description": "Working directory", "default": "." },
+                    "model": { "type": "string", "description": "Claude model, e.g. haiku or sonnet (defaults to the CLI's)" },
+                    "fallback_models": { "type": "array", "description": "Models tried in order when the previous one fails or reports low confidence; runtime:model (e.g. openai:gpt-4o-mini) switches runtime" },
+                    "min_confidence": { "type": "number", "description": "Escalate to the next fallback model when the output states a lower confidence (0-1)" },
+                    "attempt_timeout_secs": { "type": "number", "description": "Move on to the next fallback model after this many seconds" },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the previous output for an identical prompt within N seconds (0 = off)", "default": 0 },
+                    "for_each": { "type": "boolean", "description": "Run once per upstream item and join the outputs, instead of once on all items", "default": false },
+                    "concurrency": { "type": "number", "description": "for_each or batch calls run at a time", "default": 4 },
+                    "batch_tokens": { "type": "number", "description": "Split items too long for one prompt into batches of about this many tokens, then merge the outputs with one more call" },
+                    "reduce_prompt": { "type": "string", "description": "Instructions for merging batch_tokens outputs, given as {{content}}" },
+                    "run_when": { "type": "object", "description": "Only run when context vars match, e.g. { risk_level: [high, medium] }" },
+                    "sandbox": { "type": "object", "description": "Run in a sandbox limited to { filesystem: read-only|workspace, network: off|all|[hosts] }; the Claude API stays reachable" }
+                }
+            },
+            {
+                "kind": "openai",
+                "node_type": "executor",
+                "label": "OpenAI API",
+                "config_schema": {
+                    "prompt": { "type": "string", "description": "Prompt file path or inline prompt (unless prompt_ref is set)" },
+                    "prompt_ref": { "type": "string", "description": "Prompt library id or title, optionally pinned as name@version" },
+                    "model": { "type": "string", "description": "Model name", "default": "gpt-4o-mini" },
+                    "fallback_models": { "type": "array", "description": "Models tried in order when the previous one fails or reports low confidence; runtime:model (e.g. openai:gpt-4o-mini) switches runtime" },
+                    "min_confidence": { "type": "number", "description": "Escalate to the next fallback model when the output states a lower confidence (0-1)" },
+                    "attempt_timeout_secs": { "type": "number", "description": "Move on to the next fallback model after this many seconds" },
+                    "temperature": { "type": "number", "description": "Sampling temperature" },
+                    "max_tokens": { "type": "number", "description": "Max tokens in the completion" },
+                    "system_prompt": { "type": "string", "description": "System instructions (the agent's system prompt is appended when agent_id is set)" },
+                    "api": { "type": "string", "description": "chat (Chat Completions) or responses (Responses API)", "default": "chat" },
+                    "base_url": { "type": "string", "description": "API base URL, for OpenAI-compatible gateways", "default": "https://api.openai.com/v1" },
+                    "api_key_env": { "type": "string", "description": "Env var holding the API key", "default": "OPENAI_API_KEY" },
+                    "agent_id": { "type": "string", "description": "Optional agent whose system prompt is used" },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the previous output for an identical prompt within N seconds (0 = off)", "default": 0 },
+                    "for_each": { "type": "boolean", "description": "Run once per upstream item and join the outputs, instead of once on all items", "default": false },
+                    "concurrency": { "type": "number", "description": "for_each or batch calls run at a time", "default": 4 },
+                    "batch_tokens": { "type": "number", "description": "Split items too long for one prompt into batches of about this many tokens, then merge the outputs with one more call" },
+                    "reduce_prompt": { "type": "string", "description": "Instructions for merging batch_tokens outputs, given as {{content}}" },
+                    "run_when": { "type": "object", "description": "Only run when context vars match, e.g. { risk_level: [high, medium] }" }
+                }
+            },
+            {
+                "kind": "ollama",
+                "node_type": "executor",
+                "label": "Local LLM (Ollama)",
+                "config_schema": {
+                    "prompt": { "type": "string", "description": "Prompt file path or inline prompt (unless prompt_ref is set)" },
+                    "prompt_ref": { "type": "string", "description": "Prompt library id or title, optionally pinned as name@version" },
+                    "model": { "type": "string", "description": "Local model name, e.g. llama3.1", "required": true },
+                    "fallback_models": { "type": "array", "description": "Models tried in order when the previous one fails or reports low confidence; runtime:model (e.g. openai:gpt-4o-mini) switches runtime" },
+                    "min_confidence": { "type": "number", "description": "Escalate to the next fallback model when the output states a lower confidence (0-1)" },
+                    "attempt_timeout_secs": { "type": "number", "description": "Move on to the next fallback model after this many seconds" },
+                    "temperature": { "type": "number", "description": "Sampling temperature" },
+                    "max_tokens": { "type": "number", "description": "Max tokens in the completion" },
+                    "num_ctx": { "type": "number", "description": "Context window size (ollama api only)" },
+                    "system_prompt": { "type": "string", "description": "System instructions (the agent's system prompt is appended when agent_id is set)" },
+                    "api": { "type": "string", "description": "ollama (native /api/chat) or openai (OpenAI-compatible server such as llama.cpp, vLLM or LM Studio)", "default": "ollama" },
+                    "base_url": { "type": "string", "description": "Server URL (defaults to OLLAMA_HOST)", "default": "http://localhost:11434" },
+                    "api_key_env": { "type": "string", "description": "Env var holding an API key, if the openai-compatible server needs one" },
+                    "agent_id": { "type": "string", "description": "Optional agent whose system prompt is used" },
+                    "cache_ttl_secs": { "type": "number", "description": "Reuse the previous output for an identical prompt within N seconds (0 = off)", "default": 0 },
+                    "for_each": { "type": "boolean", "description": "Run once per upstream item and join the outputs, instead of once on all items", "default": false },
+                    "concurrency": { "type": "number", "description": "for_each or batch calls run at a time", "default": 4 },
+                    "batch_tokens": { "type": "number", "description": "Split items too long for one prompt into batches of about this many tokens, then merge the outputs with one more call" },
+                    "reduce_prompt": { "type": "string", "description": "Instructions for merging batch_tokens outputs, given as {{content}}" },
+                    "run_when": { "type": "object", "description": "Only run when context vars match, e.g. { risk_level: [high, medium] }" }
+                }
+            },
+            {
+                "kind": "feedback-rank",
+                "node_type": "executor",
+                "label": "Feedback Rank",
+                "config_schema": {
+                    "min_score": { "type": "number", "description": "Drop items scoring below this (votes on similar past items)" },
+                    "limit": { "type": "number", "description": "Keep at most this many items" }
+                }
+            },
+            {
+                "kind": "trending",
+                "node_type": "executor",
+                "label": "Trending",
+                "config_schema": {
+                    "window_hours": { "type": "number", "description": "How far back coverage by other feeds counts", "default": 24 },
+                    "min_sources": { "type": "number", "description": "Drop stories covered by fewer feeds than this", "default": 1 },
+                    "collapse": { "type": "boolean", "description": "Keep one item per story", "default": true },
+                    "annotate": { "type": "boolean", "description": "Prefix summaries with coverage, e.g. \"appears in 4 feeds today\"", "default": true },
+                    "limit": { "type": "number", "description": "Keep at most this many items" }
+                }
+            },
+            {
+                "kind": "transform",
+                "node_type": "executor",
+                "label": "Transform",
+                "config_schema": {
+                    "extract": { "type": "string", "description": "jq-style path into JSON input or context vars, e.g. .data.items[].title; values one per line" },
+                    "rename": { "type": "object", "description": "Context keys to rename, e.g. { pr_title: title }" },
+                    "limit": { "type": "number", "description": "Keep the first N items" },
+                    "truncate": { "type": "number", "description": "Cut item summaries (or text) to N characters" },
+                    "item_template": { "type": "string", "description": "Rendered per item with {{item_title}}, {{item_url}}, {{item_summary}}, {{item_index}}, one per line" },
+                    "template": { "type": "string", "description": "Rendered once with context vars, {{content}} and {{item_count}}" }
+                }
+            },
+            {
+                "kind": "branch",
+                "node_type": "executor",
+                "label": "Branch",
+                "config_schema": {
+                    "condition": { "type": "string", "description": "e.g. count > 0 and text contains \"outage\" — routes the input down out-edges labeled true or false" }
+                }
+            },
+            {
+                "kind": "for-each",
+                "node_type": "executor",
+                "label": "For Each",
+                "config_schema": {
+                    "concurrency": { "type": "number", "description": "Items whose loop body (nodes behind out-edges labeled each) runs at a time", "default": 4 }
+                }
+            },
+            {
+                "kind": "subflow",
+                "node_type": "executor",
+                "label": "Subflow",
+                "config_schema": {
+                    "flow_id": { "type": "string", "description": "Flow to run with this node's context and its input as {{content}}; its final output is passed on", "required": true }
+                }
+            },
+            {
+                "kind": "wait",
+                "node_type": "executor",
+                "label": "Wait",
+                "config_schema": {
+                    "wait_secs": { "type": "number", "description": "Seconds to hold the run before passing the input on" },
+                    "until": { "type": "string", "description": "Hold the run until the next HH:MM, or an RFC 3339 time, instead" },
+                    "timezone": { "type": "string", "description": "IANA timezone for an HH:MM until", "default": "UTC" }
+                }
+            },
+            {
+                "kind": "aggregate",
+                "node_type": "executor",
+                "label": "Aggregate",
+                "config_schema": {
+                    "release_count": { "type": "number", "description": "Pass the batch on once it holds this many items" },
+                    "release_after_secs": { "type": "number", "description": "Pass the batch on once this long has passed since its first item" }
                 }
             },
             {
@@ -439,7 +1776,10 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "config_schema": {
                     "webhook_url_env": { "type": "string", "description": "Env var for webhook URL" },
                     "bot_token_env": { "type": "string", "description": "Env var for bot token" },
-                    "channel": { "type": "string", "description": "Channel name (required with bot_token_env)" }
+                    "channel": { "type": "string", "description": "Channel name (required with bot_token_env unless user_email is set)" },
+                    "user_email": { "type": "string", "description": "Send as a DM to this Slack user instead (requires bot_token_env)" },
+                    "feedback": { "type": "boolean", "description": "Post incoming items with 👍/👎 buttons whose votes feed feedback-rank nodes (requires bot_token_env)", "default": false },
+                    "title": { "type": "string", "description": "Feedback digest header (defaults to the flow name)" }
                 }
             },
             {
@@ -450,6 +1790,85 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                     "token_env": { "type": "string", "description": "Env var for Notion token", "required": true },
                     "database_id": { "type": "string", "description": "Notion database ID", "required": true }
                 }
+            },
+            {
+                "kind": "pocket",
+                "node_type": "sink",
+                "label": "Pocket",
+                "config_schema": {
+                    "consumer_key_env": { "type": "string", "description": "Env var for Pocket consumer key", "default": "POCKET_CONSUMER_KEY" },
+                    "access_token_env": { "type": "string", "description": "Env var for Pocket access token", "default": "POCKET_ACCESS_TOKEN" },
+                    "tags": { "type": "array", "description": "Tags applied to every saved link" }
+                }
+            },
+            {
+                "kind": "email",
+                "node_type": "sink",
+                "label": "Email",
+                "config_schema": {
+                    "to": { "type": "array", "description": "Recipient addresses", "required": true },
+                    "subject": { "type": "string", "description": "Subject (default: first heading of the output)" },
+                    "smtp_url_env": { "type": "string", "description": "Env var for SMTP URL", "default": "SMTP_URL" },
+                    "from_env": { "type": "string", "description": "Env var for sender address", "default": "SMTP_FROM" }
+                }
+            },
+            {
+                "kind": "desktop",
+                "node_type": "sink",
+                "label": "Desktop Notification",
+                "config_schema": {
+                    "title": { "type": "string", "description": "Notification title (default: first heading of the output)" },
+                    "sound": { "type": "boolean", "description": "Play the default notification sound", "default": false }
+                }
+            },
+            {
+                "kind": "personalized-digest",
+                "node_type": "sink",
+                "label": "Personalized Digest",
+                "config_schema": {
+                    "limit": { "type": "number", "description": "Max items per subscriber", "default": 10 },
+                    "min_score": { "type": "number", "description": "Minimum interest score to keep an item (0 keeps all, reranked)", "default": 1 },
+                    "title": { "type": "string", "description": "Digest heading (default: flow name)" }
+                }
+            },
+            {
+                "kind": "readwise",
+                "node_type": "sink",
+                "label": "Readwise Reader",
+                "config_schema": {
+                    "token_env": { "type": "string", "description": "Env var for Readwise access token", "default": "READWISE_TOKEN" },
+                    "tags": { "type": "array", "description": "Tags applied to every saved link" }
+                }
+            },
+            {
+                "kind": "vector-store",
+                "node_type": "sink",
+                "label": "Vector Store",
+                "config_schema": {
+                    "collection": { "type": "string", "description": "Collection to embed items into", "default": "default" },
+                    "backend": { "type": "string", "description": "local, qdrant or pgvector", "default": "local" }
+                }
+            },
+            {
+                "kind": "audio",
+                "node_type": "sink",
+                "label": "Audio Digest",
+                "config_schema": {
+                    "provider": { "type": "string", "description": "openai (or any OpenAI-compatible /audio/speech) or piper (local)", "default": "openai" },
+                    "voice": { "type": "string", "description": "Voice name (openai)", "default": "alloy" },
+                    "model": { "type": "string", "description": "Speech model (openai)", "default": "gpt-4o-mini-tts" },
+                    "instructions": { "type": "string", "description": "Speaking style, e.g. calm radio host (openai)" },
+                    "base_url": { "type": "string", "description": "Speech API base URL (openai)", "default": "https://api.openai.com/v1" },
+                    "api_key_env": { "type": "string", "description": "Env var holding the API key (openai)", "default": "OPENAI_API_KEY" },
+                    "voice_model": { "type": "string", "description": "Path to the .onnx voice (piper)" },
+                    "title": { "type": "string", "description": "Episode title (the date is appended; defaults to the flow name)" },
+                    "feed": { "type": "boolean", "description": "Publish to GET /api/audio/{flow_id}/feed.xml", "default": true },
+                    "keep_episodes": { "type": "number", "description": "Episodes kept in the feed", "default": 30 },
+                    "bot_token_env": { "type": "string", "description": "Env var for the Slack bot token, to upload the MP3 to Slack" },
+                    "channel": { "type": "string", "description": "Slack channel ID to upload to" },
+                    "user_email": { "type": "string", "description": "Upload to this user's Slack DM instead" },
+                    "comment": { "type": "string", "description": "Slack message posted with the file" }
+                }
             }
          ]
     }))
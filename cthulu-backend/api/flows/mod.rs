@@ -1,4 +1,7 @@
+pub mod approvals;
+pub mod chat;
 pub mod handlers;
+pub mod report;
 
 use axum::routing::{get, post};
 use axum::Router;
@@ -9,15 +12,36 @@ pub fn router() -> Router<AppState> {
     Router::new()
         // Flow CRUD
         .route("/flows", get(handlers::list_flows).post(handlers::create_flow))
+        .route("/flows/export", get(handlers::export_flows))
         .route(
             "/flows/{id}",
             get(handlers::get_flow)
                 .put(handlers::update_flow)
                 .delete(handlers::delete_flow),
         )
+        .route("/flows/{id}/changes", get(approvals::list_changes))
+        .route("/flows/{id}/changes/{change_id}/approve", post(approvals::approve_change))
+        .route("/flows/{id}/changes/{change_id}/reject", post(approvals::reject_change))
         .route("/flows/{id}/trigger", post(handlers::trigger_flow))
+        .route("/flows/{id}/run", post(handlers::run_flow))
+        .route("/flows/{id}/chat", post(chat::chat))
+        .route("/flows/{id}/inputs", get(handlers::get_flow_inputs))
         .route("/flows/{id}/runs", get(handlers::get_runs))
         .route("/flows/{id}/runs/live", get(handlers::stream_runs))
+        .route("/flows/{id}/runs/{run_id}", get(handlers::get_run))
+        .route("/flows/{id}/runs/{run_id}/rating", post(handlers::rate_run))
+        .route("/flows/{id}/costs", get(handlers::get_costs))
+        .route("/flows/{id}/prompt-experiments", get(handlers::prompt_experiments))
+        .route("/flows/{id}/profile", get(handlers::get_profile))
+        .route("/flows/{id}/graph", get(handlers::get_graph))
+        .route("/flows/{id}/export", get(handlers::export_flow))
+        .route("/flows/{id}/outputs.json", get(handlers::outputs_feed))
+        .route("/runs", get(handlers::list_runs))
+        .route("/runs/search", get(handlers::search_runs))
+        .route("/runs/{id}/report", get(report::report))
+        .route("/runs/{id}/timeline", get(report::timeline))
+        .route("/runs/{id}/logs", get(report::logs))
+        .route("/runs/{id}/resume", post(handlers::resume_run))
         .route("/node-types", get(handlers::get_node_types))
         .route("/prompt-files", get(handlers::list_prompt_files))
 }
@@ -0,0 +1,121 @@
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::api::AppState;
+use crate::flows::Flow;
+use crate::flows::history::{FlowRun, MAX_RUNS_PER_FLOW};
+use crate::flows::report::{self, Format};
+use crate::flows::run_logs::RunLogStore;
+use crate::flows::timeline;
+
+/// The run with `run_id` and its flow; run ids are unique across flows.
+pub(crate) async fn find_run(state: &AppState, run_id: &str) -> Option<(Flow, FlowRun)> {
+    for flow in state.flow_repo.list_flows().await {
+        let runs = state.flow_repo.get_runs(&flow.id, MAX_RUNS_PER_FLOW).await;
+        if let Some(run) = runs.into_iter().find(|r| r.id == run_id) {
+            return Some((flow, run));
+        }
+    }
+    None
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ReportQuery {
+    /// `html` (default) or `pdf`.
+    format: Option<String>,
+}
+
+/// GET /runs/{id}/report?format=pdf|html — a run's output and node timeline
+/// as a standalone document to share outside Slack or the UI.
+pub(crate) async fn report(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    Query(query): Query<ReportQuery>,
+) -> Response {
+    let Some(format) = Format::parse(query.format.as_deref()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "format must be html or pdf" })),
+        )
+            .into_response();
+    };
+
+    let Some((flow, run)) = find_run(&state, &run_id).await else {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "run not found" }))).into_response();
+    };
+
+    let filename = format!(
+        "{}-{}.{}",
+        flow.name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect::<String>()
+            .trim_matches('-'),
+        run.started_at.format("%Y%m%d-%H%M"),
+        format.extension()
+    );
+    let (body, disposition) = match format {
+        Format::Html => (report::render_html(&flow, &run).into_bytes(), "inline"),
+        Format::Pdf => (report::render_pdf(&flow, &run), "attachment"),
+    };
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("{disposition}; filename=\"{filename}\"")),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// GET /runs/{id}/timeline — node and level spans of a run, Gantt-style,
+/// with how long each node queued for a slot after its inputs were ready.
+pub(crate) async fn timeline(State(state): State<AppState>, Path(run_id): Path<String>) -> Response {
+    let Some((flow, run)) = find_run(&state, &run_id).await else {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "run not found" }))).into_response();
+    };
+    Json(timeline::timeline(&flow, &run, chrono::Utc::now())).into_response()
+}
+
+#[derive(Deserialize)]
+pub(crate) struct LogsQuery {
+    /// Only events at this level or more severe (`error`, `warn`, ...).
+    level: Option<String>,
+    /// Only events from the node with this label.
+    node: Option<String>,
+}
+
+/// GET /runs/{id}/logs?level=&node= — the structured tracing events the run
+/// emitted, oldest first.
+pub(crate) async fn logs(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    Query(query): Query<LogsQuery>,
+) -> Response {
+    let level = match query.level.as_deref().map(str::parse::<tracing::Level>) {
+        None => None,
+        Some(Ok(level)) => Some(level),
+        Some(Err(_)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "level must be error, warn, info, debug or trace" })),
+            )
+                .into_response();
+        }
+    };
+    let Some((flow, run)) = find_run(&state, &run_id).await else {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "run not found" }))).into_response();
+    };
+    let events: Vec<_> = RunLogStore::new(&state.data_dir)
+        .load(&flow.id, &run.id)
+        .into_iter()
+        .filter(|event| level.is_none_or(|level| event.at_least(level)))
+        .filter(|event| query.node.is_none() || event.node == query.node)
+        .collect();
+    Json(json!({ "flow_id": flow.id, "run_id": run.id, "events": events })).into_response()
+}
@@ -0,0 +1,48 @@
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::AppState;
+use crate::harness::{self, Harness};
+
+#[derive(Deserialize)]
+struct HarnessParams {
+    flow_id: Option<String>,
+}
+
+type ApiError = (StatusCode, Json<Value>);
+
+fn current() -> Result<std::sync::Arc<Harness>, ApiError> {
+    harness::current().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "not running in test harness mode" })),
+        )
+    })
+}
+
+/// The harness's mocks and fixtures, with the requests and executor prompts
+/// it answered (of `?flow_id=...`), oldest first — what a test asserts on.
+async fn get_harness(Query(params): Query<HarnessParams>) -> Result<Json<Value>, ApiError> {
+    let harness = current()?;
+    let flow_id = params.flow_id.as_deref();
+    Ok(Json(json!({
+        "mocks": harness.config().mocks,
+        "executors": harness.config().executors,
+        "requests": harness.requests(flow_id),
+        "prompts": harness.prompts(flow_id),
+    })))
+}
+
+/// Forget the recorded requests and prompts, between test cases.
+async fn clear_harness() -> Result<Json<Value>, ApiError> {
+    current()?.clear();
+    Ok(Json(json!({ "cleared": true })))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/admin/harness", get(get_harness).delete(clear_harness))
+}
@@ -0,0 +1,131 @@
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+use axum::extract::{ConnectInfo, Path, State};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use hyper::{StatusCode, header};
+use serde_json::{Map, Value, json};
+
+use crate::api::AppState;
+use crate::api::flows::handlers::{body_fields, start_run, unwrap_inputs};
+use crate::flows::intake::{CAPTCHA_FIELDS, IntakeConfig};
+
+/// POST /intake/{flow_id} — public, unauthenticated submission to a flow
+/// whose trigger sets `public_intake`. The JSON or form body becomes the
+/// run's inputs (only those the trigger declares). Rate-limited per client
+/// and per flow, and captcha-checked when configured.
+pub(crate) async fn submit(
+    State(state): State<AppState>,
+    Path(flow_id): Path<String>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    // Flows without an intake don't admit to existing
+    let not_found = || (StatusCode::NOT_FOUND, Json(json!({ "error": "not found" }))).into_response();
+    let Some(flow) = state.flow_repo.get_flow(&flow_id).await.filter(|f| f.enabled) else {
+        return not_found();
+    };
+    let config = match IntakeConfig::for_flow(&flow) {
+        Ok(Some(config)) => config,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            tracing::warn!(flow = %flow.name, error = %e, "Invalid public_intake config");
+            return not_found();
+        }
+    };
+
+    if body.len() > config.max_bytes {
+        return error(StatusCode::PAYLOAD_TOO_LARGE, format!("submissions are limited to {} bytes", config.max_bytes));
+    }
+
+    let forwarded_for = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok());
+    let peer = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+    let client = state.intake_limiter.client_ip(forwarded_for, peer);
+    let limits = [
+        (format!("{flow_id}:{client}"), config.per_minute, Duration::from_secs(60)),
+        (flow_id.clone(), config.per_hour, Duration::from_secs(3600)),
+    ];
+    if let Err(retry_after) = state.intake_limiter.check(&limits, Instant::now()) {
+        let retry_after = retry_after.as_secs().max(1);
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            Json(json!({ "error": "too many submissions, try again later", "retry_after": retry_after })),
+        )
+            .into_response();
+    }
+
+    let (provided, captcha_token) = match body_fields(&flow, &headers, &body) {
+        Ok(fields) => take_captcha_token(fields),
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    if let Some(captcha) = &config.captcha {
+        let Some(token) = captcha_token.filter(|t| !t.is_empty()) else {
+            return error(StatusCode::BAD_REQUEST, "captcha required".to_string());
+        };
+        match captcha.verify(&state.http_client, &token, &client).await {
+            Ok(true) => {}
+            Ok(false) => return error(StatusCode::FORBIDDEN, "captcha failed".to_string()),
+            Err(e) => {
+                tracing::error!(flow = %flow.name, error = %e, "Captcha verification failed");
+                return error(StatusCode::BAD_GATEWAY, "captcha could not be verified".to_string());
+            }
+        }
+    }
+
+    tracing::info!(flow = %flow.name, client = %client, "Public intake submission");
+    match start_run(&state, flow, &provided).await {
+        // Report the outcome, not the flow's internals
+        Ok((status, Json(result))) => (status, Json(json!({ "status": result["status"] }))).into_response(),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+/// Split the captcha token off a submission's top-level fields, then unwrap
+/// its inputs; the token sits beside `inputs`, not inside it.
+fn take_captcha_token(mut fields: Map<String, Value>) -> (Map<String, Value>, Option<String>) {
+    let token = CAPTCHA_FIELDS
+        .iter()
+        .filter_map(|field| fields.remove(*field))
+        .find_map(|token| token.as_str().map(str::to_string));
+    (unwrap_inputs(fields), token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_take_captcha_token() {
+        let (inputs, token) = take_captcha_token(object(json!({
+            "inputs": { "repo": "acme/app" },
+            "captcha_token": "tok"
+        })));
+        assert_eq!(inputs, object(json!({ "repo": "acme/app" })));
+        assert_eq!(token.as_deref(), Some("tok"));
+
+        let (inputs, token) = take_captcha_token(object(json!({
+            "repo": "acme/app",
+            "cf-turnstile-response": "tok"
+        })));
+        assert_eq!(inputs, object(json!({ "repo": "acme/app" })));
+        assert_eq!(token.as_deref(), Some("tok"));
+
+        let (inputs, token) = take_captcha_token(object(json!({ "inputs": { "repo": "acme/app" } })));
+        assert_eq!(inputs, object(json!({ "repo": "acme/app" })));
+        assert_eq!(token, None);
+    }
+}
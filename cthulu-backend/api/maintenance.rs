@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use super::AppState;
+use crate::maintenance::{self, Status};
+
+/// Longest a request may wait for running work to drain.
+const MAX_WAIT_SECS: u64 = 600;
+
+#[derive(Deserialize)]
+struct MaintenanceRequest {
+    enabled: bool,
+    /// When turning it on, wait up to this long for running work to drain.
+    wait_secs: Option<u64>,
+}
+
+/// Whether maintenance mode is on and the runs it's waiting on.
+async fn get_maintenance() -> Json<Status> {
+    Json(maintenance::status())
+}
+
+/// Turn maintenance mode on (pausing triggers and pollers while running
+/// work drains) or off.
+async fn set_maintenance(Json(body): Json<MaintenanceRequest>) -> Json<Status> {
+    if !body.enabled {
+        return Json(maintenance::disable());
+    }
+    let wait = Duration::from_secs(body.wait_secs.unwrap_or(0).min(MAX_WAIT_SECS));
+    Json(maintenance::drain(wait).await)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/admin/maintenance", get(get_maintenance).post(set_maintenance))
+}
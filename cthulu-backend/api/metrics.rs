@@ -0,0 +1,22 @@
+use axum::Router;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+
+use super::AppState;
+use crate::monitor::ResourceUsage;
+
+/// The server's resource use, in Prometheus text format.
+async fn get_metrics() -> impl IntoResponse {
+    let usage = tokio::task::spawn_blocking(ResourceUsage::sample)
+        .await
+        .unwrap_or_default();
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        usage.to_prometheus(),
+    )
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/metrics", get(get_metrics))
+}
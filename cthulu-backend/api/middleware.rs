@@ -1,11 +1,16 @@
 use axum::{
+    Json,
     body::Body,
-    http::{Request, Uri},
+    extract::State,
+    http::{Method, Request, StatusCode, Uri},
     middleware::Next,
     response::{IntoResponse, Redirect, Response},
 };
+use serde_json::json;
 use tracing::Span;
 
+use super::AppState;
+
 pub async fn enrich_current_span_middleware(req: Request<Body>, next: Next) -> Response {
     let uri: &Uri = req.uri();
 
@@ -17,15 +22,37 @@ pub async fn enrich_current_span_middleware(req: Request<Body>, next: Next) -> R
 
     let current_span = Span::current();
 
-    current_span.record("http.uri", &uri.path());
+    current_span.record("http.uri", redact_path(uri.path()).as_str());
     current_span.record("http.host", &host);
     if let Some(query) = uri.query() {
-        current_span.record("http.query", &query);
+        current_span.record("http.query", redact_query(query).as_str());
     }
 
     next.run(req).await
 }
 
+/// `path` with trigger tokens (`/api/trigger/{token}`) blanked out, so the
+/// secrets don't end up in traces and Sentry.
+fn redact_path(path: &str) -> String {
+    match path.strip_prefix("/api/trigger/") {
+        Some(token) if !token.is_empty() => "/api/trigger/[redacted]".to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// `query` with the values of `token` parameters (the outputs feed's
+/// `?token=`) blanked out, for the same reason.
+fn redact_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if name.eq_ignore_ascii_case("token") => format!("{name}=[redacted]"),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
 pub async fn strip_trailing_slash(req: Request<Body>, next: Next) -> Response {
     let uri = req.uri();
 
@@ -44,3 +71,27 @@ pub async fn strip_trailing_slash(req: Request<Body>, next: Next) -> Response {
         next.run(req).await
     }
 }
+
+/// In read-only mode (`CTHULU_READ_ONLY`), turn away every request that
+/// could change something. Inbound webhooks still get through: they're
+/// triggers, like cron, not edits.
+pub async fn reject_writes(State(state): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    let safe = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    if state.read_only && !safe && !req.uri().path().starts_with("/api/webhooks/") {
+        return (StatusCode::FORBIDDEN, Json(json!({ "error": "server is read-only" }))).into_response();
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction() {
+        assert_eq!(redact_path("/api/trigger/abc123"), "/api/trigger/[redacted]");
+        assert_eq!(redact_path("/api/flows"), "/api/flows");
+        assert_eq!(redact_query("token=s3cret&limit=5"), "token=[redacted]&limit=5");
+        assert_eq!(redact_query("limit=5"), "limit=5");
+    }
+}
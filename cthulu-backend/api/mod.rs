@@ -1,15 +1,27 @@
 pub mod agents;
+pub mod ask;
+pub mod audio;
 pub mod auth;
 pub mod changes;
 pub mod dashboard;
+pub mod egress;
 pub mod flows;
+pub mod harness;
 pub mod hooks;
+pub mod intake;
+pub mod maintenance;
+pub mod metrics;
 
 pub mod middleware;
 pub mod prompts;
 mod routes;
 pub mod scheduler;
+pub mod search;
+pub mod secrets;
+pub mod subscriptions;
 pub mod templates;
+pub mod tokens;
+pub mod webhooks;
 
 use axum::Router;
 use serde::{Deserialize, Serialize};
@@ -22,6 +34,7 @@ use crate::agent_sdk::AgentSession;
 use crate::agents::repository::AgentRepository;
 use crate::api::changes::ResourceChangeEvent;
 use crate::flows::events::RunEvent;
+use crate::flows::intake::IntakeLimiter;
 use crate::flows::repository::FlowRepository;
 use crate::flows::scheduler::FlowScheduler;
 use crate::flows::session_bridge::FlowRunMeta;
@@ -332,6 +345,20 @@ pub struct AppState {
     pub global_hook_tx: Arc<broadcast::Sender<String>>,
     /// The port the server is listening on (used in hook URLs).
     pub server_port: u16,
+    /// Bearer token for `/api/flows/{id}/outputs.json` (CTHULU_FEED_TOKEN env).
+    /// The feed is disabled when unset.
+    pub feed_token: Option<String>,
+    /// Shared secret for `/api/webhooks/github` (GITHUB_WEBHOOK_SECRET env).
+    /// Webhooks are disabled, and github-pr triggers poll, when unset.
+    pub github_webhook_secret: Option<String>,
+    /// Signing secret for `/api/webhooks/slack/actions` (SLACK_SIGNING_SECRET
+    /// env). 👍/👎 digest feedback is not recorded when unset.
+    pub slack_signing_secret: Option<String>,
+    /// Recent submissions to `/api/intake/{flow_id}`, for its rate limits.
+    pub intake_limiter: IntakeLimiter,
+    /// Answer every mutating request with a 403 (CTHULU_READ_ONLY env), for
+    /// a dashboard shown to people who shouldn't change anything.
+    pub read_only: bool,
 }
 
 impl AppState {
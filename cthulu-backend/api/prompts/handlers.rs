@@ -52,6 +52,8 @@ pub(crate) async fn create_prompt(
         source_flow_name: body.source_flow_name,
         tags: body.tags,
         created_at: Utc::now(),
+        version: 1,
+        history: vec![],
     };
 
     let id = prompt.id.clone();
@@ -124,7 +126,7 @@ pub(crate) async fn update_prompt(
         prompt.title = title;
     }
     if let Some(summary) = body.summary {
-        prompt.summary = summary;
+        prompt.set_summary(summary);
     }
     if let Some(tags) = body.tags {
         prompt.tags = tags;
@@ -177,7 +179,7 @@ Respond ONLY with valid JSON in this exact format:
 
     // Use --allowedTools with no tools to prevent arbitrary tool execution
     // (the summarize endpoint is HTTP-reachable and the transcript is user-controlled).
-    let mut child = Command::new("claude")
+    let mut child = Command::new(crate::platform::claude_bin())
         .arg("--print")
         .arg("--allowedTools")
         .arg("")
@@ -1,5 +1,6 @@
 use axum::response::sse::{Event, Sse};
 use axum::response::IntoResponse;
+use axum::extract::State;
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use futures::stream::Stream;
@@ -8,15 +9,20 @@ use hyper::header;
 use serde::Deserialize;
 use serde_json::json;
 use std::convert::Infallible;
+use std::path::Path;
 use std::process::Stdio;
+use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::mpsc;
 use tokio_stream::wrappers::LinesStream;
 use tokio_stream::StreamExt;
 use tower_http::cors::{Any, CorsLayer};
 
 use super::middleware;
 use super::AppState;
+use crate::tasks::executors::sandbox::{self, SandboxExecutor};
+use crate::tasks::executors::{Executor, LineSink};
 
 pub fn build_router(state: AppState) -> Router {
     let health_routes = Router::new().route(
@@ -31,14 +37,19 @@ pub fn build_router(state: AppState) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(vec![header::CONTENT_TYPE, header::AUTHORIZATION]);
+        .allow_headers(vec![
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            header::HeaderName::from_static("x-cthulu-token"),
+        ]);
 
     Router::new()
         .nest("/health", health_routes)
         .route("/claude", post(run_claude))
         .nest("/api", api_router())
         .fallback(not_found)
-        .with_state(state)
+        .with_state(state.clone())
+        .layer(axum::middleware::from_fn_with_state(state, middleware::reject_writes))
         .layer(cors)
         .layer(axum::middleware::from_fn(middleware::strip_trailing_slash))
         .layer(axum::middleware::from_fn(
@@ -52,11 +63,23 @@ fn api_router() -> Router<AppState> {
         .merge(super::agents::router())
         .merge(super::prompts::router())
         .merge(super::templates::router())
+        .merge(super::tokens::router())
         .merge(super::auth::router())
         .merge(super::scheduler::router())
+        .merge(super::subscriptions::router())
+        .merge(super::search::router())
+        .merge(super::secrets::router())
+        .merge(super::ask::router())
+        .merge(super::audio::router())
         .merge(super::changes::router())
         .merge(super::hooks::router())
+        .merge(super::intake::router())
+        .merge(super::metrics::router())
+        .merge(super::egress::router())
+        .merge(super::harness::router())
+        .merge(super::maintenance::router())
         .merge(super::dashboard::router())
+        .merge(super::webhooks::router())
 }
 
 async fn not_found(req: axum::extract::Request) -> impl IntoResponse {
@@ -74,14 +97,48 @@ pub struct ClaudeRequest {
 
 #[tracing::instrument(skip_all, fields(prompt))]
 pub async fn run_claude(
+    State(state): State<AppState>,
     Json(body): Json<ClaudeRequest>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     tracing::info!(prompt = %body.prompt, "spawning claude process");
 
+    let sync_repo = body.working_dir.is_some();
     let working_dir = body.working_dir.unwrap_or_else(|| ".".to_string());
+    let provider = state.sandbox_provider.clone();
 
     let stream = async_stream::stream! {
-        let mut child = match Command::new("claude")
+        // Claude runs straight on the host only under the host sandbox
+        // backend; elsewhere its stream-json events come back as `event`
+        // events and the answer as data
+        if !sandbox::runs_on_host(&*provider) {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let sink: LineSink = Arc::new(move |line| {
+                let _ = tx.send(line);
+            });
+            let mut executor = SandboxExecutor::new(provider, vec![], None);
+            if sync_repo {
+                executor = executor.with_repo_sync();
+            }
+            let run = tokio::spawn(async move {
+                executor.execute_streaming(&body.prompt, Path::new(&working_dir), Some(sink)).await
+            });
+            while let Some(line) = rx.recv().await {
+                yield Ok(Event::default().event("event").data(line));
+            }
+            match run.await {
+                Ok(Ok(result)) => {
+                    for line in result.text.lines() {
+                        yield Ok(Event::default().data(line));
+                    }
+                    yield Ok(Event::default().event("done").data("exit: 0"));
+                }
+                Ok(Err(e)) => yield Ok(Event::default().event("done").data(format!("error: {e:#}"))),
+                Err(e) => yield Ok(Event::default().event("done").data(format!("error: {e}"))),
+            }
+            return;
+        }
+
+        let mut child = match Command::new(crate::platform::claude_bin())
             .arg("--print")
             .arg("--dangerously-skip-permissions")
             .arg(&body.prompt)
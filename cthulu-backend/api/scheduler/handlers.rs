@@ -1,22 +1,39 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::Json;
 use hyper::StatusCode;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::api::AppState;
+use crate::flows::pauses::PauseStore;
+use crate::flows::history::MAX_RUNS_PER_FLOW;
+use crate::flows::misfire::Misfire;
+use crate::flows::scheduler::{cron_fields, cron_timezone, next_cron_runs};
 
 use super::repository::SchedulerRepository;
 
-/// GET /flows/{id}/schedule — compute next run time for a flow's trigger
+/// Most fire times `GET /flows/{id}/schedule` lists.
+const MAX_PREVIEW_RUNS: usize = 100;
+
+#[derive(Deserialize)]
+pub(crate) struct ScheduleQuery {
+    /// How many upcoming fire times to list (default 5).
+    count: Option<usize>,
+}
+
+/// GET /flows/{id}/schedule?count=N — a flow's trigger schedule: for cron,
+/// the parsed expression, the last run and the next N fire times in the
+/// trigger's timezone
 pub(crate) async fn get_schedule(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    Query(query): Query<ScheduleQuery>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let repo = SchedulerRepository::new(state.flow_repo.clone(), state.scheduler.clone());
     let flow = repo.get_flow(&id).await.ok_or_else(|| {
         (StatusCode::NOT_FOUND, Json(json!({ "error": "flow not found" })))
     })?;
+    let count = query.count.unwrap_or(5).clamp(1, MAX_PREVIEW_RUNS);
 
     let trigger_node = flow.nodes.iter().find(|n| n.node_type == crate::flows::NodeType::Trigger);
 
@@ -47,30 +64,48 @@ pub(crate) async fn get_schedule(
                 })));
             }
 
+            let timezone = match cron_timezone(&trigger.config) {
+                Ok(tz) => tz,
+                Err(e) => {
+                    return Ok(Json(json!({
+                        "flow_id": id,
+                        "trigger_kind": "cron",
+                        "schedule": schedule,
+                        "next_run": null,
+                        "error": format!("{e}"),
+                    })));
+                }
+            };
+
             match croner::Cron::new(schedule).parse() {
                 Ok(cron) => {
-                    let now = chrono::Utc::now();
-                    let next = cron.find_next_occurrence(&now, false).ok();
-                    let next_runs: Vec<String> = {
-                        let mut runs = Vec::new();
-                        let mut cursor = now;
-                        for _ in 0..5 {
-                            if let Ok(n) = cron.find_next_occurrence(&cursor, false) {
-                                runs.push(n.to_rfc3339());
-                                cursor = n + chrono::Duration::seconds(1);
-                            } else {
-                                break;
-                            }
-                        }
-                        runs
-                    };
+                    let next_runs: Vec<String> = next_cron_runs(&cron, timezone, chrono::Utc::now(), count)
+                        .iter()
+                        .map(|n| n.to_rfc3339())
+                        .collect();
+                    let last_run = state
+                        .flow_repo
+                        .get_runs(&id, MAX_RUNS_PER_FLOW)
+                        .await
+                        .into_iter()
+                        .find(|r| !r.dry_run)
+                        .map(|r| json!({
+                            "run_id": r.id,
+                            "started_at": r.started_at.with_timezone(&timezone).to_rfc3339(),
+                            "status": r.status,
+                        }));
 
                     Ok(Json(json!({
                         "flow_id": id,
                         "trigger_kind": "cron",
                         "enabled": flow.enabled,
+                        "paused_since": PauseStore::new(&state.data_dir).paused_since(&id),
                         "schedule": schedule,
-                        "next_run": next.map(|n| n.to_rfc3339()),
+                        "fields": cron_fields(schedule),
+                        "timezone": timezone.name(),
+                        "misfire": Misfire::from_trigger(&trigger.config).ok().map(|m| m.as_str()),
+                        "last_run": last_run,
+                        "next_run": next_runs.first(),
                         "next_runs": next_runs,
                     })))
                 }
@@ -79,19 +114,20 @@ pub(crate) async fn get_schedule(
                         "flow_id": id,
                         "trigger_kind": "cron",
                         "schedule": schedule,
+                        "timezone": timezone.name(),
                         "next_run": null,
                         "error": format!("invalid cron: {e}"),
                     })))
                 }
             }
         }
-        "github-pr" => {
+        kind @ ("github-pr" | "github-push" | "github-issue" | "github-release") => {
             let poll_interval = trigger.config.get("poll_interval")
                 .and_then(|v| v.as_u64())
                 .unwrap_or(60);
             Ok(Json(json!({
                 "flow_id": id,
-                "trigger_kind": "github-pr",
+                "trigger_kind": kind,
                 "enabled": flow.enabled,
                 "poll_interval_secs": poll_interval,
                 "next_run": null,
@@ -115,6 +151,7 @@ pub(crate) async fn scheduler_status(
     let repo = SchedulerRepository::new(state.flow_repo.clone(), state.scheduler.clone());
     let active_ids = repo.active_flow_ids().await;
     let flows = repo.list_flows().await;
+    let pauses = PauseStore::new(&state.data_dir);
 
     let flow_statuses: Vec<Value> = flows.iter().map(|f| {
         let is_active = active_ids.contains(&f.id);
@@ -123,19 +160,84 @@ pub(crate) async fn scheduler_status(
             "name": f.name,
             "enabled": f.enabled,
             "scheduler_active": is_active,
+            "paused_since": pauses.paused_since(&f.id),
         })
     }).collect();
 
     Json(json!({
         "active_count": active_ids.len(),
         "total_flows": flows.len(),
+        "all_cron_paused_since": pauses.all_paused_since(),
         "flows": flow_statuses,
     }))
 }
 
+fn pause_error(e: anyhow::Error) -> (StatusCode, Json<Value>) {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("{e:#}") })))
+}
+
+/// Fails unless flow `id` exists with a cron schedule to pause.
+async fn scheduled_flow(state: &AppState, id: &str) -> Result<(), (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(id).await.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, Json(json!({ "error": "flow not found" })))
+    })?;
+    let scheduled = flow
+        .nodes
+        .iter()
+        .any(|n| n.node_type == crate::flows::NodeType::Trigger && n.kind == "cron");
+    if !scheduled {
+        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "flow has no cron schedule" }))));
+    }
+    Ok(())
+}
+
+/// POST /flows/{id}/pause — skip the flow's cron fires until resumed,
+/// without editing the flow; manual runs still work
+pub(crate) async fn pause_flow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    scheduled_flow(&state, &id).await?;
+    let since = PauseStore::new(&state.data_dir).pause(&id).map_err(pause_error)?;
+    tracing::info!(flow_id = %id, "Schedule paused");
+    Ok(Json(json!({ "flow_id": id, "paused_since": since })))
+}
+
+/// POST /flows/{id}/resume — let the flow's cron fire again
+pub(crate) async fn resume_flow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    scheduled_flow(&state, &id).await?;
+    let pauses = PauseStore::new(&state.data_dir);
+    pauses.resume(&id).map_err(pause_error)?;
+    tracing::info!(flow_id = %id, "Schedule resumed");
+    Ok(Json(json!({ "flow_id": id, "paused_since": pauses.paused_since(&id) })))
+}
+
+/// POST /schedules/cron/pause — skip every flow's cron fires until resumed.
+/// Only cron: unlike maintenance mode (`/admin/maintenance`), runs, pollers
+/// and webhooks carry on, and the pause is saved across restarts.
+pub(crate) async fn pause_all_cron(State(state): State<AppState>) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let since = PauseStore::new(&state.data_dir).pause_all().map_err(pause_error)?;
+    tracing::info!("All cron schedules paused");
+    Ok(Json(json!({ "all_cron_paused_since": since })))
+}
+
+/// POST /schedules/cron/resume — end `/schedules/cron/pause`; flows paused
+/// one by one stay paused
+pub(crate) async fn resume_all_cron(State(state): State<AppState>) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    PauseStore::new(&state.data_dir).resume_all().map_err(pause_error)?;
+    tracing::info!("All cron schedules resumed");
+    Ok(Json(json!({ "all_cron_paused_since": null })))
+}
+
 #[derive(Deserialize)]
 pub(crate) struct ValidateCronRequest {
     expression: String,
+    /// IANA timezone the expression is evaluated in; UTC when omitted.
+    #[serde(default)]
+    timezone: Option<String>,
 }
 
 /// POST /validate/cron — validate a cron expression and return next 5 fire times
@@ -152,24 +254,29 @@ pub(crate) async fn validate_cron(
         }));
     }
 
+    let timezone = match cron_timezone(&json!({ "timezone": body.timezone })) {
+        Ok(tz) => tz,
+        Err(e) => {
+            return Json(json!({
+                "valid": false,
+                "expression": expr,
+                "error": format!("{e}"),
+                "next_runs": [],
+            }));
+        }
+    };
+
     match croner::Cron::new(expr).parse() {
         Ok(cron) => {
-            let now = chrono::Utc::now();
-            let mut next_runs = Vec::new();
-            let mut cursor = now;
-            for _ in 0..5 {
-                match cron.find_next_occurrence(&cursor, false) {
-                    Ok(n) => {
-                        next_runs.push(n.to_rfc3339());
-                        cursor = n + chrono::Duration::seconds(1);
-                    }
-                    Err(_) => break,
-                }
-            }
+            let next_runs: Vec<String> = next_cron_runs(&cron, timezone, chrono::Utc::now(), 5)
+                .iter()
+                .map(|n| n.to_rfc3339())
+                .collect();
 
             Json(json!({
                 "valid": true,
                 "expression": expr,
+                "timezone": timezone.name(),
                 "next_runs": next_runs,
             }))
         }
@@ -9,6 +9,10 @@ use crate::api::AppState;
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/flows/{id}/schedule", get(handlers::get_schedule))
+        .route("/flows/{id}/pause", post(handlers::pause_flow))
+        .route("/flows/{id}/resume", post(handlers::resume_flow))
+        .route("/schedules/cron/pause", post(handlers::pause_all_cron))
+        .route("/schedules/cron/resume", post(handlers::resume_all_cron))
         .route("/scheduler/status", get(handlers::scheduler_status))
         .route("/validate/cron", post(handlers::validate_cron))
 }
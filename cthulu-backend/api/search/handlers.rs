@@ -0,0 +1,47 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::AppState;
+use crate::tasks::archive::{ArchiveStore, DEFAULT_SEARCH_LIMIT};
+
+#[derive(Deserialize)]
+pub(crate) struct SearchQuery {
+    #[serde(default)]
+    q: String,
+    flow_id: Option<String>,
+    limit: Option<usize>,
+}
+
+/// GET /search?q= — full-text search over items archived by sources with
+/// `archive: true`, best matches first.
+pub(crate) async fn search(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if query.q.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "q is required" })),
+        ));
+    }
+
+    let store = ArchiveStore::new(&state.data_dir);
+    let results = store
+        .search(
+            &query.q,
+            query.flow_id.as_deref(),
+            query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        )
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("search failed: {e}") })),
+            )
+        })?;
+
+    Ok(Json(json!({ "query": query.q, "results": results })))
+}
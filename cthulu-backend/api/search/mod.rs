@@ -0,0 +1,10 @@
+pub mod handlers;
+
+use axum::routing::get;
+use axum::Router;
+
+use crate::api::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/search", get(handlers::search))
+}
@@ -0,0 +1,80 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::AppState;
+use crate::secrets::{SCHEME, SecretStore};
+
+/// GET /secrets — stored secrets' names, never their values.
+pub(crate) async fn list_secrets(
+    State(state): State<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let store = SecretStore::new(&state.data_dir);
+    let secrets = store.list().map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to read secrets: {e}") })),
+        )
+    })?;
+    Ok(Json(json!({ "secrets": secrets })))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PutSecretRequest {
+    value: String,
+}
+
+/// PUT /secrets/{name} — store or replace a secret, for configs to use as
+/// `secret://{name}` wherever they'd name an env var.
+pub(crate) async fn put_secret(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(body): Json<PutSecretRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if !SecretStore::is_valid_name(&name) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "secret names are letters, digits, '-', '_' and '.'" })),
+        ));
+    }
+    if body.value.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "value is required" })),
+        ));
+    }
+
+    let store = SecretStore::new(&state.data_dir);
+    let secret = store.put_secret(&name, &body.value).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to save secret: {e}") })),
+        )
+    })?;
+    Ok(Json(json!({
+        "name": secret.name,
+        "updated_at": secret.updated_at,
+        "reference": format!("{SCHEME}{}", secret.name),
+    })))
+}
+
+/// DELETE /secrets/{name}
+pub(crate) async fn delete_secret(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let store = SecretStore::new(&state.data_dir);
+    match store.delete_secret(&name) {
+        Ok(true) => Ok(Json(json!({ "deleted": true }))),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "secret not found" })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to delete secret: {e}") })),
+        )),
+    }
+}
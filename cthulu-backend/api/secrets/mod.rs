@@ -0,0 +1,15 @@
+pub mod handlers;
+
+use axum::routing::{get, put};
+use axum::Router;
+
+use crate::api::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/secrets", get(handlers::list_secrets))
+        .route(
+            "/secrets/{name}",
+            put(handlers::put_secret).delete(handlers::delete_secret),
+        )
+}
@@ -0,0 +1,131 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use chrono::Utc;
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::flows::subscriptions::{Delivery, Subscription, SubscriptionStore};
+
+/// GET /flows/{id}/subscriptions
+pub(crate) async fn list_subscriptions(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Json<Value> {
+    let store = SubscriptionStore::new(&state.data_dir);
+    Json(json!({ "subscriptions": store.list(&id) }))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CreateSubscriptionRequest {
+    subscriber: String,
+    delivery: Delivery,
+    #[serde(default)]
+    deliver_at: Option<String>,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    interests: Vec<String>,
+}
+
+/// POST /flows/{id}/subscriptions — subscribe someone to the flow's output.
+pub(crate) async fn create_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<CreateSubscriptionRequest>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    if state.flow_repo.get_flow(&id).await.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        ));
+    }
+
+    let subscription = Subscription {
+        id: Uuid::new_v4().to_string(),
+        flow_id: id,
+        subscriber: body.subscriber,
+        delivery: body.delivery,
+        deliver_at: body.deliver_at.filter(|s| !s.trim().is_empty()),
+        timezone: body.timezone.filter(|s| !s.trim().is_empty()),
+        interests: body.interests,
+        enabled: true,
+        created_at: Utc::now(),
+    };
+    subscription.validate().map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("{e}") })),
+        )
+    })?;
+
+    let store = SubscriptionStore::new(&state.data_dir);
+    store.save(subscription.clone()).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to save subscription: {e}") })),
+        )
+    })?;
+
+    Ok((StatusCode::CREATED, Json(json!(subscription))))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct UpdateInterestsRequest {
+    interests: Vec<String>,
+}
+
+/// PUT /flows/{id}/subscriptions/{subscription_id}/interests — replace the
+/// subscriber's interest profile.
+pub(crate) async fn update_interests(
+    State(state): State<AppState>,
+    Path((id, subscription_id)): Path<(String, String)>,
+    Json(body): Json<UpdateInterestsRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let store = SubscriptionStore::new(&state.data_dir);
+    let mut subscription = store
+        .list(&id)
+        .into_iter()
+        .find(|s| s.id == subscription_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "subscription not found" })),
+            )
+        })?;
+    subscription.interests = body
+        .interests
+        .into_iter()
+        .map(|i| i.trim().to_string())
+        .filter(|i| !i.is_empty())
+        .collect();
+
+    store.save(subscription.clone()).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to save subscription: {e}") })),
+        )
+    })?;
+    Ok(Json(json!(subscription)))
+}
+
+/// DELETE /flows/{id}/subscriptions/{subscription_id}
+pub(crate) async fn delete_subscription(
+    State(state): State<AppState>,
+    Path((id, subscription_id)): Path<(String, String)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let store = SubscriptionStore::new(&state.data_dir);
+    match store.delete(&id, &subscription_id) {
+        Ok(true) => Ok(Json(json!({ "deleted": true }))),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "subscription not found" })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to delete subscription: {e}") })),
+        )),
+    }
+}
@@ -0,0 +1,22 @@
+pub mod handlers;
+
+use axum::routing::{delete, get, put};
+use axum::Router;
+
+use crate::api::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/flows/{id}/subscriptions",
+            get(handlers::list_subscriptions).post(handlers::create_subscription),
+        )
+        .route(
+            "/flows/{id}/subscriptions/{subscription_id}",
+            delete(handlers::delete_subscription),
+        )
+        .route(
+            "/flows/{id}/subscriptions/{subscription_id}/interests",
+            put(handlers::update_interests),
+        )
+}
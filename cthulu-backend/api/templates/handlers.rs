@@ -1,9 +1,10 @@
 /// REST endpoints for the template gallery.
 ///
 /// GET  /api/templates                         — list all templates (metadata + raw YAML)
+/// POST /api/templates                         — save an existing flow to the gallery
 /// GET  /api/templates/{cat}/{slug}             — get raw YAML for a single template
 /// POST /api/templates/{cat}/{slug}/import      — parse YAML → Flow, save, return Flow
-/// POST /api/templates/import-yaml             — parse raw YAML body → Flow, save, return Flow
+/// POST /api/templates/import-yaml             — parse raw YAML body (one flow or a bundle) → Flows, save, return them
 /// POST /api/templates/import-github           — fetch all workflow YAMLs from a GitHub repo,
 ///                                               import each one, return array of imported Flows
 use axum::extract::{Path, State};
@@ -16,7 +17,17 @@ use uuid::Uuid;
 
 use crate::agents::Agent;
 use crate::api::AppState;
+use crate::flows::branch::BRANCH_KIND;
+use crate::flows::loops::LOOP_KIND;
+use crate::flows::subflow::SUBFLOW_KIND;
+use crate::flows::transform::TRANSFORM_KIND;
+use crate::flows::wait::WAIT_KIND;
+use crate::flows::aggregate::AGGREGATE_KIND;
 use crate::flows::{Flow, NodeType};
+use crate::tasks::executors::ollama::OLLAMA_KIND;
+use crate::tasks::executors::openai::OPENAI_KIND;
+use crate::tasks::feedback::FEEDBACK_RANK_KIND;
+use crate::tasks::trending::TRENDING_KIND;
 use crate::templates;
 
 use super::repository::TemplateRepository;
@@ -34,7 +45,18 @@ async fn provision_agents_for_executors(
     state: &AppState,
 ) {
     for node in flow.nodes.iter_mut() {
-        if node.node_type != NodeType::Executor {
+        if node.node_type != NodeType::Executor
+            || node.kind == FEEDBACK_RANK_KIND
+            || node.kind == TRENDING_KIND
+            || node.kind == TRANSFORM_KIND
+            || node.kind == BRANCH_KIND
+            || node.kind == LOOP_KIND
+            || node.kind == SUBFLOW_KIND
+            || node.kind == WAIT_KIND
+            || node.kind == AGGREGATE_KIND
+            || node.kind == OPENAI_KIND
+            || node.kind == OLLAMA_KIND
+        {
             continue;
         }
         // Skip if already has a valid agent_id
@@ -211,7 +233,9 @@ pub(crate) struct ImportGithubBody {
 
 /// POST /api/templates/import-yaml
 /// Body: `{ "yaml": "<raw YAML string>" }`
-/// Parses the YAML as a workflow, saves it as a new disabled Flow, returns the Flow.
+/// Parses the YAML as a workflow, or a `---`-separated bundle of them (as
+/// `GET /api/flows/export` writes), saves each as a new disabled Flow, and
+/// returns them.
 pub(crate) async fn import_yaml(
     State(state): State<AppState>,
     Json(body): Json<ImportYamlBody>,
@@ -226,29 +250,148 @@ pub(crate) async fn import_yaml(
             .into_response();
     }
 
-    let mut flow = match templates::parse_template_yaml(&body.yaml) {
-        Ok(f) => f,
+    let flows = match templates::parse_template_bundle(&body.yaml) {
+        Ok(flows) if !flows.is_empty() => flows,
+        Ok(_) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": "no workflows in the YAML" })),
+            )
+                .into_response();
+        }
         Err(e) => {
             return (
                 StatusCode::UNPROCESSABLE_ENTITY,
-                Json(json!({ "error": format!("failed to parse YAML: {e}") })),
+                Json(json!({ "error": format!("failed to parse YAML: {e:#}") })),
             )
                 .into_response();
         }
     };
 
-    // Auto-create agents for executor nodes missing agent_id
-    provision_agents_for_executors(&mut flow, &state).await;
+    let mut imported = Vec::with_capacity(flows.len());
+    for mut flow in flows {
+        // Auto-create agents for executor nodes missing agent_id
+        provision_agents_for_executors(&mut flow, &state).await;
 
-    match repo.save_imported_flow(flow.clone()).await {
-        Ok(_) => {
-            let _ = state.scheduler.restart_flow(&flow.id).await;
-            tracing::info!(flow_id = %flow.id, flow_name = %flow.name, "imported flow from uploaded YAML");
-            Json(json!({ "flows": [flow] })).into_response()
+        if let Err(e) = repo.save_imported_flow(flow.clone()).await {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("failed to save flow: {e}"), "flows": imported })),
+            )
+                .into_response();
+        }
+        let _ = state.scheduler.restart_flow(&flow.id).await;
+        tracing::info!(flow_id = %flow.id, flow_name = %flow.name, "imported flow from uploaded YAML");
+        imported.push(flow);
+    }
+    Json(json!({ "flows": imported })).into_response()
+}
+
+/// Request body for POST /api/templates
+#[derive(Deserialize)]
+pub(crate) struct CreateTemplateBody {
+    pub flow_id: String,
+    pub category: String,
+    /// Defaults to the flow's name, lowercased and hyphenated.
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub estimated_cost: Option<String>,
+    /// Replace an existing template with the same category and slug.
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// Lowercase letters, digits and hyphens — safe as a path component.
+fn is_valid_slug(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with('-')
+        && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn slugify(name: &str) -> String {
+    name.to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// POST /api/templates
+/// Body: `{ "flow_id": "...", "category": "research", "slug"?, "title"?, "description"?, "tags"?, "icon"?, "estimated_cost"?, "overwrite"? }`
+/// Saves an existing flow to the gallery as `workflows/{category}/{slug}.yaml`.
+/// Credentials in node config become `*_env` references (or are removed);
+/// the response lists each one as `scrubbed` alongside the new template.
+pub(crate) async fn create_template(
+    State(state): State<AppState>,
+    Json(body): Json<CreateTemplateBody>,
+) -> impl IntoResponse {
+    let repo = TemplateRepository::new(state.flow_repo.clone(), state.static_dir.clone());
+
+    let Some(flow) = repo.get_flow(&body.flow_id).await else {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "flow not found" }))).into_response();
+    };
+    let slug = body.slug.unwrap_or_else(|| slugify(&flow.name));
+    if !is_valid_slug(&body.category) || !is_valid_slug(&slug) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "category and slug must be lowercase letters, digits and hyphens" })),
+        )
+            .into_response();
+    }
+
+    let meta = templates::TemplateMeta {
+        title: body.title.or_else(|| Some(flow.name.clone())),
+        description: body.description.or_else(|| Some(flow.description.clone()).filter(|d| !d.is_empty())),
+        tags: body.tags,
+        estimated_cost: body.estimated_cost,
+        icon: body.icon,
+    };
+    let (yaml, scrubbed) = match templates::flow_to_template_yaml(&flow, meta) {
+        Ok(converted) => converted,
+        Err(e) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": format!("flow can't be saved as a template: {e}") })),
+            )
+                .into_response();
+        }
+    };
+
+    let path = match repo.save_template(&body.category, &slug, &yaml, body.overwrite) {
+        Ok(path) => path,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({ "error": format!("template already exists: {}/{slug}", body.category) })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("failed to write template: {e}") })),
+            )
+                .into_response();
+        }
+    };
+
+    match templates::load_template_file(&path, &body.category, &slug) {
+        Ok(template) => {
+            tracing::info!(flow_id = %flow.id, template = %format!("{}/{slug}", body.category), scrubbed = scrubbed.len(), "saved flow as template");
+            (StatusCode::CREATED, Json(json!({ "template": template, "scrubbed": scrubbed }))).into_response()
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": format!("failed to save flow: {e}") })),
+            Json(json!({ "error": format!("saved template doesn't load: {e}") })),
         )
             .into_response(),
     }
@@ -8,7 +8,7 @@ use crate::api::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/templates", get(handlers::list_templates))
+        .route("/templates", get(handlers::list_templates).post(handlers::create_template))
         .route("/templates/import-yaml", post(handlers::import_yaml))
         .route("/templates/import-github", post(handlers::import_github))
         .route("/templates/{category}/{slug}", get(handlers::get_template_yaml))
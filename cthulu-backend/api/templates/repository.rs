@@ -1,8 +1,10 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::egress::AuditedSend;
 use crate::flows::repository::FlowRepository;
 use crate::flows::Flow;
+use crate::identity::{Integration, user_agent};
 use crate::templates;
 
 pub struct TemplateRepository {
@@ -28,6 +30,29 @@ impl TemplateRepository {
         std::fs::read_to_string(file_path)
     }
 
+    pub async fn get_flow(&self, id: &str) -> Option<Flow> {
+        self.flow_repo.get_flow(id).await
+    }
+
+    /// Write a contributed template to `workflows/{category}/{slug}.yaml`,
+    /// failing with `AlreadyExists` unless `overwrite` is set.
+    pub fn save_template(
+        &self,
+        category: &str,
+        slug: &str,
+        yaml: &str,
+        overwrite: bool,
+    ) -> Result<PathBuf, std::io::Error> {
+        let dir = self.static_dir.join("workflows").join(category);
+        let file_path = dir.join(format!("{slug}.yaml"));
+        if !overwrite && file_path.exists() {
+            return Err(std::io::ErrorKind::AlreadyExists.into());
+        }
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(&file_path, yaml)?;
+        Ok(file_path)
+    }
+
     pub async fn save_imported_flow(&self, flow: Flow) -> anyhow::Result<()> {
         self.flow_repo.save_flow(flow).await
     }
@@ -51,9 +76,9 @@ pub async fn fetch_github_yaml_files(
 
     let resp = client
         .get(&api_url)
-        .header("User-Agent", "cthulu-studio/1.0")
+        .header("User-Agent", user_agent(Integration::Templates))
         .header("Accept", "application/vnd.github.v3+json")
-        .send()
+        .send_audited()
         .await
         .map_err(|e| format!("GitHub API request failed: {e}"))?;
 
@@ -81,8 +106,8 @@ pub async fn fetch_github_yaml_files(
         {
             match client
                 .get(download_url)
-                .header("User-Agent", "cthulu-studio/1.0")
-                .send()
+                .header("User-Agent", user_agent(Integration::Templates))
+                .send_audited()
                 .await
             {
                 Ok(file_resp) if file_resp.status().is_success() => {
@@ -0,0 +1,141 @@
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::Json;
+use hyper::{HeaderMap, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::AppState;
+use crate::api::flows::handlers::{body_inputs, start_run};
+use crate::flows::tokens::TokenStore;
+
+/// GET /flows/{id}/tokens
+pub(crate) async fn list_tokens(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Json<Value> {
+    let store = TokenStore::new(&state.data_dir);
+    let tokens: Vec<Value> = store.list(&id).iter().map(|t| t.to_json()).collect();
+    Json(json!({ "tokens": tokens }))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CreateTokenRequest {
+    name: String,
+}
+
+/// POST /flows/{id}/tokens — mint a token that can only trigger this flow.
+/// The secret is in this response and nowhere else.
+pub(crate) async fn create_token(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<CreateTokenRequest>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    if state.flow_repo.get_flow(&id).await.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        ));
+    }
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "name is required" })),
+        ));
+    }
+
+    let store = TokenStore::new(&state.data_dir);
+    let (token, secret) = store.create(&id, name).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to save token: {e}") })),
+        )
+    })?;
+
+    let mut response = token.to_json();
+    response["token"] = json!(secret);
+    response["trigger_path"] = json!(format!("/api/trigger/{secret}"));
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// DELETE /flows/{id}/tokens/{token_id}
+pub(crate) async fn delete_token(
+    State(state): State<AppState>,
+    Path((id, token_id)): Path<(String, String)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let store = TokenStore::new(&state.data_dir);
+    match store.delete(&id, &token_id) {
+        Ok(true) => Ok(Json(json!({ "deleted": true }))),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "token not found" })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to delete token: {e}") })),
+        )),
+    }
+}
+
+/// POST /trigger — run the flow of the token in `Authorization: Bearer` or
+/// `X-Cthulu-Token`. The body, if any, is the run's inputs: a JSON object
+/// (bare or under `inputs`) or form fields, so Shortcuts, IFTTT and Zapier
+/// webhooks work as-is.
+pub(crate) async fn trigger(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let secret = header_token(&headers).unwrap_or_default();
+    trigger_with(&state, &secret, &headers, &body).await
+}
+
+/// POST /trigger/{token} — the same, for callers that can't set headers.
+/// The path is redacted before it reaches traces.
+pub(crate) async fn trigger_by_path(
+    State(state): State<AppState>,
+    Path(secret): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    trigger_with(&state, &secret, &headers, &body).await
+}
+
+/// The token in `Authorization: Bearer ...` or `X-Cthulu-Token`.
+fn header_token(headers: &HeaderMap) -> Option<String> {
+    let bearer = headers
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let custom = headers.get("x-cthulu-token").and_then(|v| v.to_str().ok());
+    bearer.or(custom).map(|t| t.trim().to_string())
+}
+
+async fn trigger_with(
+    state: &AppState,
+    secret: &str,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let unauthorized = || {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid trigger token" })),
+        )
+    };
+    let token = TokenStore::new(&state.data_dir)
+        .redeem(secret)
+        .ok_or_else(unauthorized)?;
+    // A token outliving its flow is as good as revoked
+    let flow = state
+        .flow_repo
+        .get_flow(&token.flow_id)
+        .await
+        .ok_or_else(unauthorized)?;
+
+    let provided = body_inputs(&flow, headers, body)?;
+
+    tracing::info!(flow = %flow.name, token = %token.name, "Flow triggered by token");
+    start_run(state, flow, &provided).await
+}
@@ -0,0 +1,17 @@
+pub mod handlers;
+
+use axum::routing::{delete, get, post};
+use axum::Router;
+
+use crate::api::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/flows/{id}/tokens",
+            get(handlers::list_tokens).post(handlers::create_token),
+        )
+        .route("/flows/{id}/tokens/{token_id}", delete(handlers::delete_token))
+        .route("/trigger", post(handlers::trigger))
+        .route("/trigger/{token}", post(handlers::trigger_by_path))
+}
@@ -0,0 +1,235 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::Json;
+use chrono::Utc;
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::AppState;
+use crate::egress::AuditedSend;
+use crate::github::webhook::{self, IssueCommentEvent, IssuesEvent, PullRequestEvent, PushEvent, ReleaseEvent};
+use crate::tasks::feedback::FeedbackStore;
+use crate::tasks::sinks::slack::feedback as slack_feedback;
+
+/// POST /webhooks/github — receive GitHub deliveries signed with
+/// `GITHUB_WEBHOOK_SECRET`. `pull_request` events and `/cthulu <command>`
+/// PR comments (`issue_comment`) are run in the background by matching
+/// github-pr flows, `push` events by github-push flows, newly opened
+/// `issues` by github-issue flows and published releases (`release`) by
+/// github-release flows; other events are acknowledged and ignored.
+pub(crate) async fn github_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let Some(secret) = state.github_webhook_secret.as_deref() else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "GitHub webhooks are disabled (set GITHUB_WEBHOOK_SECRET)" })),
+        ));
+    };
+    let signature = headers
+        .get(webhook::SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok());
+    if !webhook::verify_signature(secret.as_bytes(), &body, signature) {
+        tracing::warn!("Rejected GitHub webhook with invalid signature");
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid webhook signature" })),
+        ));
+    }
+
+    let event = headers
+        .get(webhook::EVENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    match event {
+        "ping" => Ok((StatusCode::OK, Json(json!({ "status": "pong" })))),
+        "pull_request" => {
+            let event: PullRequestEvent = serde_json::from_slice(&body).map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid pull_request payload: {e}") })),
+                )
+            })?;
+            tracing::info!(
+                repo = %event.repository.full_name,
+                pr = event.pull_request.number,
+                action = %event.action,
+                "Received pull_request webhook"
+            );
+
+            let scheduler = state.scheduler.clone();
+            tokio::spawn(async move {
+                scheduler.handle_pull_request_event(event).await;
+            });
+            Ok((StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))))
+        }
+        "issue_comment" => {
+            let event: IssueCommentEvent = serde_json::from_slice(&body).map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid issue_comment payload: {e}") })),
+                )
+            })?;
+            let Some(command) = event.command() else {
+                return Ok((StatusCode::OK, Json(json!({ "status": "ignored" }))));
+            };
+            tracing::info!(
+                repo = %event.repository.full_name,
+                pr = event.issue.number,
+                command = %command.name,
+                user = %event.comment.author(),
+                "Received PR comment command"
+            );
+
+            let scheduler = state.scheduler.clone();
+            tokio::spawn(async move {
+                scheduler.handle_issue_comment_event(event).await;
+            });
+            Ok((StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))))
+        }
+        "push" => {
+            let event: PushEvent = serde_json::from_slice(&body).map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid push payload: {e}") })),
+                )
+            })?;
+            tracing::info!(
+                repo = %event.repository.full_name,
+                git_ref = %event.git_ref,
+                after = %event.after,
+                "Received push webhook"
+            );
+
+            let scheduler = state.scheduler.clone();
+            tokio::spawn(async move {
+                scheduler.handle_push_event(event).await;
+            });
+            Ok((StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))))
+        }
+        "issues" => {
+            let event: IssuesEvent = serde_json::from_slice(&body).map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid issues payload: {e}") })),
+                )
+            })?;
+            if !event.is_opened() {
+                return Ok((StatusCode::OK, Json(json!({ "status": "ignored" }))));
+            }
+            tracing::info!(
+                repo = %event.repository.full_name,
+                issue = event.issue.number,
+                "Received issue opened webhook"
+            );
+
+            let scheduler = state.scheduler.clone();
+            tokio::spawn(async move {
+                scheduler.handle_issues_event(event).await;
+            });
+            Ok((StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))))
+        }
+        "release" => {
+            let event: ReleaseEvent = serde_json::from_slice(&body).map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid release payload: {e}") })),
+                )
+            })?;
+            if !event.is_published() {
+                return Ok((StatusCode::OK, Json(json!({ "status": "ignored" }))));
+            }
+            tracing::info!(
+                repo = %event.repository.full_name,
+                tag = %event.release.tag_name,
+                "Received release published webhook"
+            );
+
+            let scheduler = state.scheduler.clone();
+            tokio::spawn(async move {
+                scheduler.handle_release_event(event).await;
+            });
+            Ok((StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))))
+        }
+        other => {
+            tracing::debug!(event = %other, "Ignoring GitHub webhook event");
+            Ok((StatusCode::OK, Json(json!({ "status": "ignored" }))))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SlackInteraction {
+    payload: String,
+}
+
+/// POST /webhooks/slack/actions — Slack interactivity requests signed with
+/// `SLACK_SIGNING_SECRET`. Records 👍/👎 clicks on feedback digests against
+/// the flow's delivered items; other interactions are acknowledged and ignored.
+pub(crate) async fn slack_actions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let Some(secret) = state.slack_signing_secret.as_deref() else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Slack actions are disabled (set SLACK_SIGNING_SECRET)" })),
+        ));
+    };
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    if !slack_feedback::verify_signature(
+        secret.as_bytes(),
+        header(slack_feedback::TIMESTAMP_HEADER),
+        &body,
+        header(slack_feedback::SIGNATURE_HEADER),
+        Utc::now().timestamp(),
+    ) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid signature" })),
+        ));
+    }
+
+    let payload: Value = serde_urlencoded::from_bytes::<SlackInteraction>(&body)
+        .ok()
+        .and_then(|form| serde_json::from_str(&form.payload).ok())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid interaction payload" })),
+            )
+        })?;
+
+    let store = FeedbackStore::new(&state.data_dir);
+    let mut recorded = Vec::new();
+    for action in slack_feedback::parse_actions(&payload) {
+        match store.record_vote(&action.flow_id, &action.item_key, &action.user_id, action.vote) {
+            Ok(Some(item)) => {
+                tracing::info!(flow_id = %action.flow_id, vote = ?action.vote, item = %item.title, "Recorded digest feedback");
+                recorded.push(item.title);
+            }
+            Ok(None) => tracing::debug!(flow_id = %action.flow_id, "Feedback for unknown or expired item"),
+            Err(e) => tracing::error!(flow_id = %action.flow_id, error = %e, "Failed to record feedback"),
+        }
+    }
+
+    // Let the voter know it landed, without touching the shared message
+    if let (Some(title), Some(response_url)) = (recorded.first(), payload["response_url"].as_str()) {
+        let http_client = state.http_client.clone();
+        let response_url = response_url.to_string();
+        let text = format!("Thanks — noted your feedback on _{title}_.");
+        tokio::spawn(async move {
+            let ack = json!({ "response_type": "ephemeral", "replace_original": false, "text": text });
+            if let Err(e) = http_client.post(&response_url).json(&ack).send_audited().await {
+                tracing::warn!(error = %e, "Failed to acknowledge Slack feedback");
+            }
+        });
+    }
+
+    Ok((StatusCode::OK, Json(json!({ "recorded": recorded.len() }))))
+}
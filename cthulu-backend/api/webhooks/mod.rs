@@ -0,0 +1,12 @@
+pub mod handlers;
+
+use axum::routing::post;
+use axum::Router;
+
+use crate::api::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/webhooks/github", post(handlers::github_webhook))
+        .route("/webhooks/slack/actions", post(handlers::slack_actions))
+}
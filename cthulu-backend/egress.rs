@@ -0,0 +1,152 @@
+//! Audit log of outbound HTTP requests: the last `CAPACITY` requests made
+//! with `send_audited`, with the flow and node that made them, for
+//! `GET /api/admin/egress`. Paths are kept without their query strings,
+//! which often carry API keys. In test harness mode, requests are answered
+//! by `harness` mocks here instead of going out.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::harness::{self, Intercepted};
+
+/// Requests kept; the oldest are dropped first.
+const CAPACITY: usize = 2000;
+
+static LOG: Mutex<VecDeque<EgressRecord>> = Mutex::new(VecDeque::new());
+
+tokio::task_local! {
+    static ORIGIN: Origin;
+}
+
+/// The flow node a request was made for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub flow_id: String,
+    pub node_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EgressRecord {
+    pub at: DateTime<Utc>,
+    pub method: String,
+    pub host: String,
+    pub path: String,
+    /// `None` when no response came back.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub flow_id: Option<String>,
+    pub node_id: Option<String>,
+}
+
+/// Attribute the requests `work` makes (on this task) to `origin`.
+pub async fn scope<F: Future>(origin: Origin, work: F) -> F::Output {
+    ORIGIN.scope(origin, work).await
+}
+
+/// `RequestBuilder::send`, recorded in the audit log.
+pub trait AuditedSend {
+    fn send_audited(self) -> impl Future<Output = reqwest::Result<reqwest::Response>> + Send;
+}
+
+impl AuditedSend for reqwest::RequestBuilder {
+    async fn send_audited(self) -> reqwest::Result<reqwest::Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+        let method = request.method().to_string();
+        let url = request.url().clone();
+        let started = Instant::now();
+        let origin = ORIGIN.try_with(Origin::clone).ok();
+        let result = match harness::current() {
+            Some(harness) => match harness.intercept(request, origin.as_ref()) {
+                Intercepted::Answered(response) => Ok(response),
+                Intercepted::Send(request) => client.execute(request).await,
+            },
+            None => client.execute(request).await,
+        };
+        record(EgressRecord {
+            at: Utc::now(),
+            method,
+            host: url.host_str().unwrap_or_default().to_string(),
+            path: url.path().to_string(),
+            status: result.as_ref().ok().map(|r| r.status().as_u16()),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            duration_ms: started.elapsed().as_millis() as u64,
+            flow_id: origin.as_ref().map(|o| o.flow_id.clone()),
+            node_id: origin.map(|o| o.node_id),
+        });
+        result
+    }
+}
+
+fn record(entry: EgressRecord) {
+    let mut log = LOG.lock().unwrap();
+    if log.len() == CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+/// Filters for `recent`; unset ones match everything.
+#[derive(Debug, Default)]
+pub struct EgressQuery {
+    pub flow_id: Option<String>,
+    pub node_id: Option<String>,
+    pub host: Option<String>,
+}
+
+impl EgressQuery {
+    fn matches(&self, entry: &EgressRecord) -> bool {
+        let field = |filter: &Option<String>, value: Option<&str>| {
+            filter.as_deref().is_none_or(|f| value == Some(f))
+        };
+        field(&self.flow_id, entry.flow_id.as_deref())
+            && field(&self.node_id, entry.node_id.as_deref())
+            && field(&self.host, Some(&entry.host))
+    }
+}
+
+/// Logged requests matching `query`, newest first.
+pub fn recent(query: &EgressQuery) -> Vec<EgressRecord> {
+    let log = LOG.lock().unwrap();
+    log.iter().rev().filter(|e| query.matches(e)).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_send_audited_records_origin() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .await;
+        });
+
+        let origin = Origin { flow_id: "egress-test".into(), node_id: "rss".into() };
+        let request = reqwest::Client::new().get(format!("http://{addr}/feed.xml?key=secret"));
+        let response = scope(origin, request.send_audited()).await.unwrap();
+        assert_eq!(response.status(), 404);
+
+        let query = EgressQuery { flow_id: Some("egress-test".into()), ..Default::default() };
+        let logged = recent(&query);
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].method, "GET");
+        assert_eq!(logged[0].host, "127.0.0.1");
+        assert_eq!(logged[0].path, "/feed.xml");
+        assert_eq!(logged[0].status, Some(404));
+        assert_eq!(logged[0].node_id.as_deref(), Some("rss"));
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::flows::Node;
+use crate::flows::graph::NodeOutput;
+use crate::json_file;
+use crate::tasks::sources::ContentItem;
+
+/// Executor kind that collects its input across runs and only passes it on
+/// once `release_count` items are held, or `release_after_secs` have passed
+/// since the first — so hourly webhook events become one daily digest. Runs
+/// that only add to the batch stop there: the nodes after it are skipped.
+pub const AGGREGATE_KIND: &str = "aggregate";
+
+/// Serializes read-modify-write of the batch file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// When an aggregate node lets its batch go.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub release_count: Option<usize>,
+    /// Counted from the first held item; checked when the node next runs.
+    pub release_after: Option<Duration>,
+}
+
+impl Aggregate {
+    pub fn for_node(node: &Node) -> Result<Self> {
+        let config = &node.config;
+        let positive = |key: &str| -> Result<Option<u64>> {
+            match &config[key] {
+                Value::Null => Ok(None),
+                value => Ok(Some(
+                    value.as_u64().filter(|n| *n >= 1).with_context(|| format!("{key} must be a whole number of at least 1"))?,
+                )),
+            }
+        };
+        let aggregate = Self {
+            release_count: positive("release_count")?.map(|n| n as usize),
+            release_after: positive("release_after_secs")?.map(Duration::from_secs),
+        };
+        if aggregate.release_count.is_none() && aggregate.release_after.is_none() {
+            anyhow::bail!("aggregate node needs release_count, release_after_secs or both");
+        }
+        Ok(aggregate)
+    }
+
+    /// Whether a batch of `count` items, the first held at `since`, is due.
+    pub fn due(&self, count: usize, since: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let full = self.release_count.is_some_and(|n| count >= n);
+        let old = self
+            .release_after
+            .and_then(|after| chrono::Duration::from_std(after).ok())
+            .is_some_and(|after| now - since >= after);
+        full || old
+    }
+}
+
+/// What an aggregate node did with its input.
+#[derive(Debug, Clone)]
+pub enum Collected {
+    /// Added to the batch, which now has this many items.
+    Held(usize),
+    /// The batch was due; here it is, oldest first.
+    Released(Vec<ContentItem>),
+}
+
+/// The items an aggregate node holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Batch {
+    flow_id: String,
+    node_id: String,
+    since: DateTime<Utc>,
+    items: Vec<ContentItem>,
+}
+
+/// `input` as items to hold: items as they are, and a trigger's context or
+/// an executor's text as one item each, titled with `label` unless the
+/// context has a `title`.
+pub fn items_of(input: NodeOutput, label: &str, now: DateTime<Utc>) -> Vec<ContentItem> {
+    let item = |title: String, url: String, summary: String| ContentItem {
+        title,
+        url,
+        summary,
+        published: Some(now),
+        image_url: None,
+        full_summary: true,
+    };
+    match input {
+        NodeOutput::Items(items) => items,
+        NodeOutput::Context(mut map) => {
+            let title = map.remove("title").unwrap_or_else(|| label.to_string());
+            let url = map.remove("url").unwrap_or_default();
+            let mut fields: Vec<_> = map.into_iter().collect();
+            fields.sort();
+            let summary = fields.iter().map(|(k, v)| format!("{k}: {v}")).collect::<Vec<_>>().join("\n");
+            vec![item(title, url, summary)]
+        }
+        NodeOutput::Text(text, _) if !text.trim().is_empty() => vec![item(label.to_string(), String::new(), text)],
+        _ => Vec::new(),
+    }
+}
+
+/// Batches of aggregate nodes between runs (`~/.cthulu/state/aggregates.json`).
+#[derive(Clone)]
+pub struct AggregateStore {
+    path: PathBuf,
+}
+
+impl AggregateStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("state").join("aggregates.json") }
+    }
+
+    fn load(&self) -> Vec<Batch> {
+        json_file::load_or_default(&self.path)
+    }
+
+    fn write(&self, batches: &[Batch]) -> Result<()> {
+        json_file::write(&self.path, batches)
+    }
+
+    /// Add `items` to the node's batch, and take the whole batch if that
+    /// makes it due.
+    pub fn collect(
+        &self,
+        flow_id: &str,
+        node_id: &str,
+        items: Vec<ContentItem>,
+        aggregate: &Aggregate,
+        now: DateTime<Utc>,
+    ) -> Result<Collected> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let index = match all.iter().position(|b| b.flow_id == flow_id && b.node_id == node_id) {
+            Some(index) => index,
+            None => {
+                all.push(Batch { flow_id: flow_id.to_string(), node_id: node_id.to_string(), since: now, items: Vec::new() });
+                all.len() - 1
+            }
+        };
+        all[index].items.extend(items);
+        let batch = &all[index];
+        let collected = if aggregate.due(batch.items.len(), batch.since, now) {
+            Collected::Released(all.remove(index).items)
+        } else {
+            Collected::Held(batch.items.len())
+        };
+        self.write(&all)?;
+        Ok(collected)
+    }
+
+    /// Drop every batch of `flow_id` (the flow was deleted).
+    pub fn delete_flow(&self, flow_id: &str) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let before = all.len();
+        all.retain(|b| b.flow_id != flow_id);
+        if all.len() == before {
+            return Ok(());
+        }
+        self.write(&all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::NodeType;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn node(config: Value) -> Node {
+        Node {
+            id: "a".into(),
+            node_type: NodeType::Executor,
+            kind: AGGREGATE_KIND.into(),
+            config,
+            position: crate::flows::Position { x: 0.0, y: 0.0 },
+            label: "Daily batch".into(),
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_aggregate_config() {
+        assert!(Aggregate::for_node(&node(json!({}))).is_err());
+        assert!(Aggregate::for_node(&node(json!({ "release_count": 0 }))).is_err());
+        let daily = Aggregate::for_node(&node(json!({ "release_after_secs": 86400 }))).unwrap();
+        let since = at("2026-03-10T08:00:00Z");
+        assert!(!daily.due(100, since, at("2026-03-11T07:59:00Z")));
+        assert!(daily.due(1, since, at("2026-03-11T08:00:00Z")));
+    }
+
+    #[test]
+    fn test_collect_until_due() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AggregateStore::new(dir.path());
+        let aggregate = Aggregate::for_node(&node(json!({ "release_count": 3 }))).unwrap();
+        let now = at("2026-03-10T08:00:00Z");
+        let event = |n: &str| {
+            let context = HashMap::from([("title".to_string(), format!("Deploy {n}")), ("env".to_string(), "prod".to_string())]);
+            items_of(NodeOutput::Context(context), "Daily batch", now)
+        };
+
+        assert!(matches!(store.collect("f", "a", event("1"), &aggregate, now).unwrap(), Collected::Held(1)));
+        assert!(matches!(store.collect("f", "a", event("2"), &aggregate, now).unwrap(), Collected::Held(2)));
+        // Another node's batch is separate
+        assert!(matches!(store.collect("f", "b", event("x"), &aggregate, now).unwrap(), Collected::Held(1)));
+
+        let Collected::Released(items) = AggregateStore::new(dir.path()).collect("f", "a", event("3"), &aggregate, now).unwrap() else {
+            panic!("expected the batch to be released");
+        };
+        let titles: Vec<&str> = items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, ["Deploy 1", "Deploy 2", "Deploy 3"]);
+        assert_eq!(items[0].summary, "env: prod");
+        assert!(matches!(store.collect("f", "a", event("4"), &aggregate, now).unwrap(), Collected::Held(1)));
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::flows::{Edge, Flow, Node};
+use crate::json_file;
+
+/// Serializes read-modify-write of the pending changes file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// An edit to a flow: the fields `PUT /api/flows/{id}` sets, each left as
+/// it is when absent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowChanges {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<Vec<Node>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edges: Option<Vec<Edge>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owners: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_approval: Option<bool>,
+}
+
+impl FlowChanges {
+    /// Write the changes into `flow`, bumping its version.
+    pub fn apply(self, flow: &mut Flow) {
+        if let Some(name) = self.name {
+            flow.name = name;
+        }
+        if let Some(description) = self.description {
+            flow.description = description;
+        }
+        if let Some(enabled) = self.enabled {
+            flow.enabled = enabled;
+        }
+        if let Some(nodes) = self.nodes {
+            flow.nodes = nodes;
+        }
+        if let Some(edges) = self.edges {
+            flow.edges = edges;
+        }
+        if let Some(owners) = self.owners {
+            flow.owners = owners;
+        }
+        if let Some(require_approval) = self.require_approval {
+            flow.require_approval = require_approval;
+        }
+        flow.version += 1;
+        flow.updated_at = Utc::now();
+    }
+}
+
+/// Whether `user` is one of the flow's owners.
+pub fn is_owner(flow: &Flow, user: Option<&str>) -> bool {
+    user.is_some_and(|user| flow.owners.iter().any(|owner| owner == user))
+}
+
+/// Whether an edit by `user` has to wait for an owner. A flow without
+/// owners has nobody to approve, so its edits always go straight through.
+pub fn needs_approval(flow: &Flow, user: Option<&str>) -> bool {
+    flow.require_approval && !flow.owners.is_empty() && !is_owner(flow, user)
+}
+
+/// A non-owner's edit waiting for an owner to approve or reject it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingChange {
+    pub id: String,
+    pub flow_id: String,
+    /// Who made the edit, if they said.
+    pub author: Option<String>,
+    /// The flow's version the edit was made against; approving fails once
+    /// the flow has moved on.
+    pub base_version: u64,
+    pub changes: FlowChanges,
+    pub created_at: DateTime<Utc>,
+}
+
+/// File-backed pending changes (`~/.cthulu/state/pending-changes.json`).
+#[derive(Clone)]
+pub struct ChangeStore {
+    path: PathBuf,
+}
+
+impl ChangeStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("state").join("pending-changes.json") }
+    }
+
+    fn load(&self) -> Vec<PendingChange> {
+        json_file::load_or_default(&self.path)
+    }
+
+    fn write(&self, changes: &[PendingChange]) -> Result<()> {
+        json_file::write(&self.path, changes)
+    }
+
+    /// The flow's pending changes, oldest first.
+    pub fn list(&self, flow_id: &str) -> Vec<PendingChange> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        self.load().into_iter().filter(|c| c.flow_id == flow_id).collect()
+    }
+
+    /// Hold `changes` to `flow` by `author` for approval.
+    pub fn propose(&self, flow: &Flow, author: Option<&str>, changes: FlowChanges) -> Result<PendingChange> {
+        let change = PendingChange {
+            id: uuid::Uuid::new_v4().to_string(),
+            flow_id: flow.id.clone(),
+            author: author.map(String::from),
+            base_version: flow.version,
+            changes,
+            created_at: Utc::now(),
+        };
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        all.push(change.clone());
+        self.write(&all)?;
+        Ok(change)
+    }
+
+    /// Remove a pending change, to apply or drop it.
+    pub fn take(&self, flow_id: &str, id: &str) -> Result<Option<PendingChange>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let Some(index) = all.iter().position(|c| c.flow_id == flow_id && c.id == id) else {
+            return Ok(None);
+        };
+        let change = all.remove(index);
+        self.write(&all)?;
+        Ok(Some(change))
+    }
+
+    /// Drop every pending change to `flow_id` (the flow was deleted).
+    pub fn delete_flow(&self, flow_id: &str) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let before = all.len();
+        all.retain(|c| c.flow_id != flow_id);
+        if all.len() == before {
+            return Ok(());
+        }
+        self.write(&all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(owners: &[&str], require_approval: bool) -> Flow {
+        let mut flow: Flow = serde_json::from_value(serde_json::json!({
+            "id": "f",
+            "name": "Digest",
+            "nodes": [],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        flow.owners = owners.iter().map(|o| o.to_string()).collect();
+        flow.require_approval = require_approval;
+        flow
+    }
+
+    #[test]
+    fn test_needs_approval() {
+        let guarded = flow(&["ana"], true);
+        assert!(!needs_approval(&guarded, Some("ana")));
+        assert!(needs_approval(&guarded, Some("bo")));
+        assert!(needs_approval(&guarded, None));
+        assert!(!needs_approval(&flow(&["ana"], false), Some("bo")));
+        // Nobody could approve
+        assert!(!needs_approval(&flow(&[], true), Some("bo")));
+    }
+
+    #[test]
+    fn test_propose_and_take() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ChangeStore::new(dir.path());
+        let mut flow = flow(&["ana"], true);
+        let changes = FlowChanges { name: Some("Morning digest".into()), ..Default::default() };
+        let pending = store.propose(&flow, Some("bo"), changes).unwrap();
+        assert_eq!(store.list("f").len(), 1);
+        assert!(store.list("other").is_empty());
+
+        let taken = ChangeStore::new(dir.path()).take("f", &pending.id).unwrap().unwrap();
+        assert_eq!((taken.author.as_deref(), taken.base_version), (Some("bo"), 0));
+        taken.changes.apply(&mut flow);
+        assert_eq!((flow.name.as_str(), flow.version), ("Morning digest", 1));
+        assert!(store.take("f", &pending.id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+use crate::flows::graph::NodeOutput;
+use crate::flows::{Edge, EdgeCondition, Node};
+
+/// Executor kind that routes its input instead of transforming it: the
+/// input goes down the out-edges labeled with whether `condition` held
+/// (`true` or `false`), and nodes only reachable the other way are skipped.
+pub const BRANCH_KIND: &str = "branch";
+
+/// A branch node's `condition`, e.g.
+/// `count > 0 and (text contains "outage" or text matches "(?i)sev[12]")`.
+///
+/// Operands are `count` (upstream items), `text` (the input as text), any
+/// other name (a context variable, empty if unset), or a quoted string or
+/// number literal. Comparisons are `==` `!=` `<` `<=` `>` `>=` (numeric when
+/// both sides are numbers), `contains` (case-insensitive) and `matches` (a
+/// regex); they combine with `and`, `or`, `not` and parentheses.
+#[derive(Debug)]
+pub struct Condition(Expr);
+
+#[derive(Debug)]
+enum Expr {
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Operand, Op, Operand),
+    Matches(Operand, Regex),
+}
+
+#[derive(Debug)]
+enum Operand {
+    Count,
+    Text,
+    Var(String),
+    Literal(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Str(String),
+    Num(String),
+    Op(Op),
+    Matches,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Condition {
+    pub fn for_node(node: &Node) -> Result<Self> {
+        let source = node.config["condition"]
+            .as_str()
+            .filter(|s| !s.trim().is_empty())
+            .context("branch node needs a condition")?;
+        Self::parse(source).with_context(|| format!("invalid condition '{source}'"))
+    }
+
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            bail!("unexpected {token:?}");
+        }
+        Ok(Self(expr))
+    }
+
+    /// Whether the condition holds for `input`, looking up names in `vars`
+    /// and then the input's own context.
+    pub fn evaluate(&self, input: &NodeOutput, vars: &HashMap<String, String>) -> Result<bool> {
+        let mut vars = vars.clone();
+        if let Some(context) = input.as_context() {
+            vars.extend(context.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        let text = input.as_text();
+        let scope = Scope { count: input.as_items().len(), text: &text, vars: &vars };
+        scope.eval(&self.0)
+    }
+}
+
+/// Whether data flows along `edge` this run. Out of a branch (one of
+/// `outcomes`), a labeled edge is only taken when its label is the outcome.
+pub fn edge_taken(edge: &Edge, outcomes: &HashMap<String, bool>) -> bool {
+    match (outcomes.get(&edge.source), edge.label.as_deref()) {
+        (Some(outcome), Some(label)) => label.trim().eq_ignore_ascii_case(&outcome.to_string()),
+        _ => true,
+    }
+}
+
+/// What `edge` carries from its source's `output`, or `None` when its
+/// `condition` doesn't hold. A failed source sends `failure()` (what broke,
+/// as text) along `on_failure` edges, and its failure along plain ones.
+pub fn carried(edge: &Edge, output: &NodeOutput, failure: impl FnOnce() -> String) -> Option<NodeOutput> {
+    let failed = matches!(output, NodeOutput::Failed);
+    match edge.condition {
+        None => Some(output.clone()),
+        Some(EdgeCondition::OnSuccess) => (!failed).then(|| output.clone()),
+        Some(EdgeCondition::OnFailure) => failed.then(|| NodeOutput::Text(failure(), None)),
+    }
+}
+
+struct Scope<'a> {
+    count: usize,
+    text: &'a str,
+    vars: &'a HashMap<String, String>,
+}
+
+impl Scope<'_> {
+    fn eval(&self, expr: &Expr) -> Result<bool> {
+        Ok(match expr {
+            Expr::Not(e) => !self.eval(e)?,
+            Expr::And(a, b) => self.eval(a)? && self.eval(b)?,
+            Expr::Or(a, b) => self.eval(a)? || self.eval(b)?,
+            Expr::Matches(operand, re) => re.is_match(&self.value(operand)),
+            Expr::Compare(left, op, right) => {
+                let (left, right) = (self.value(left), self.value(right));
+                let numbers = left.trim().parse::<f64>().ok().zip(right.trim().parse::<f64>().ok());
+                match (op, numbers) {
+                    (Op::Contains, _) => left.to_lowercase().contains(&right.to_lowercase()),
+                    (Op::Eq, Some((l, r))) => l == r,
+                    (Op::Ne, Some((l, r))) => l != r,
+                    (Op::Eq, None) => left == right,
+                    (Op::Ne, None) => left != right,
+                    (Op::Lt, Some((l, r))) => l < r,
+                    (Op::Le, Some((l, r))) => l <= r,
+                    (Op::Gt, Some((l, r))) => l > r,
+                    (Op::Ge, Some((l, r))) => l >= r,
+                    (_, None) => bail!("can't compare '{left}' and '{right}': not numbers"),
+                }
+            }
+        })
+    }
+
+    fn value(&self, operand: &Operand) -> String {
+        match operand {
+            Operand::Count => self.count.to_string(),
+            Operand::Text => self.text.to_string(),
+            Operand::Var(name) => self.vars.get(name).cloned().unwrap_or_default(),
+            Operand::Literal(s) => s.clone(),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '"' || c == '\'' {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    Some('\\') => s.push(chars.next().context("unterminated string")?),
+                    Some(ch) if ch == c => break,
+                    Some(ch) => s.push(ch),
+                    None => bail!("unterminated string"),
+                }
+            }
+            tokens.push(Token::Str(s));
+            continue;
+        }
+        if c.is_ascii_digit() || c == '-' {
+            let mut s = String::new();
+            while let Some(&ch) = chars.peek()
+                && (ch.is_ascii_digit() || ch == '.' || (ch == '-' && s.is_empty()))
+            {
+                s.push(ch);
+                chars.next();
+            }
+            s.parse::<f64>().map_err(|_| anyhow::anyhow!("bad number '{s}'"))?;
+            tokens.push(Token::Num(s));
+            continue;
+        }
+        if c.is_alphabetic() || c == '_' {
+            let mut s = String::new();
+            while let Some(&ch) = chars.peek()
+                && (ch.is_alphanumeric() || ch == '_' || ch == '.')
+            {
+                s.push(ch);
+                chars.next();
+            }
+            tokens.push(match s.as_str() {
+                "and" => Token::And,
+                "or" => Token::Or,
+                "not" => Token::Not,
+                "contains" => Token::Op(Op::Contains),
+                "matches" => Token::Matches,
+                _ => Token::Name(s),
+            });
+            continue;
+        }
+        chars.next();
+        let next_is_eq = chars.peek() == Some(&'=');
+        let token = match (c, next_is_eq) {
+            ('(', _) => Token::LParen,
+            (')', _) => Token::RParen,
+            ('=', true) => Token::Op(Op::Eq),
+            ('!', true) => Token::Op(Op::Ne),
+            ('<', true) => Token::Op(Op::Le),
+            ('>', true) => Token::Op(Op::Ge),
+            ('<', false) => Token::Op(Op::Lt),
+            ('>', false) => Token::Op(Op::Gt),
+            _ => bail!("unexpected '{c}'"),
+        };
+        if next_is_eq && matches!(token, Token::Op(_)) {
+            chars.next();
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent, loosest-binding first: `or`, `and`, `not`, then a
+/// parenthesized expression or a single comparison.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.not()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => bail!("missing ')'"),
+                }
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.operand()?;
+        match self.next() {
+            Some(Token::Op(op)) => Ok(Expr::Compare(left, op, self.operand()?)),
+            Some(Token::Matches) => match self.next() {
+                Some(Token::Str(pattern)) => Ok(Expr::Matches(left, Regex::new(&pattern)?)),
+                _ => bail!("matches needs a quoted regex"),
+            },
+            Some(token) => bail!("expected a comparison, got {token:?}"),
+            None => bail!("expected a comparison"),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(match name.as_str() {
+                "count" => Operand::Count,
+                "text" => Operand::Text,
+                _ => Operand::Var(name),
+            }),
+            Some(Token::Str(s) | Token::Num(s)) => Ok(Operand::Literal(s)),
+            Some(token) => bail!("expected a value, got {token:?}"),
+            None => bail!("expected a value"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::sources::ContentItem;
+
+    fn holds(condition: &str, input: &NodeOutput) -> bool {
+        let vars = HashMap::from([("risk_level".to_string(), "high".to_string())]);
+        Condition::parse(condition).unwrap().evaluate(input, &vars).unwrap()
+    }
+
+    fn items(n: usize) -> NodeOutput {
+        NodeOutput::Items(
+            (0..n)
+                .map(|i| ContentItem {
+                    title: format!("Item {i}"),
+                    url: String::new(),
+                    summary: String::new(),
+                    published: None,
+                    image_url: None,
+                    full_summary: false,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_conditions() {
+        let text = NodeOutput::Text("Major OUTAGE in us-east. Score: 7".into(), None);
+        assert!(holds(r#"text contains "outage""#, &text));
+        assert!(!holds(r#"text contains "resolved""#, &text));
+        assert!(holds(r#"text matches "Score: [5-9]""#, &text));
+        assert!(holds(r#"risk_level == "high" and not text contains 'resolved'"#, &text));
+        assert!(holds(r#"risk_level == "low" or (count == 0 and text != "")"#, &text));
+        assert!(!holds("unset_var != ''", &text));
+
+        assert!(holds("count > 3", &items(5)));
+        assert!(!holds("count >= 3", &items(2)));
+        assert!(holds("count == 2.0", &items(2)));
+        assert!(holds(r#"text contains "item 1""#, &items(2)));
+
+        // Numeric text output, e.g. a scoring executor
+        assert!(holds("text >= 7", &NodeOutput::Text(" 8\n".into(), None)));
+    }
+
+    #[test]
+    fn test_condition_errors() {
+        for bad in ["", "count >", "count > 1 and", "(count > 1", "text matches \"(\"", "text ~ 1", "'open"] {
+            assert!(Condition::parse(bad).is_err(), "{bad}");
+        }
+        let not_numbers = Condition::parse("text > 3").unwrap();
+        assert!(not_numbers.evaluate(&NodeOutput::Text("abc".into(), None), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_edge_taken() {
+        let edge = |source: &str, label: Option<&str>| Edge {
+            id: "e".into(),
+            source: source.into(),
+            target: "t".into(),
+            label: label.map(String::from),
+            condition: None,
+        };
+        let outcomes = HashMap::from([("b".to_string(), true)]);
+        assert!(edge_taken(&edge("b", Some("true")), &outcomes));
+        assert!(!edge_taken(&edge("b", Some("false")), &outcomes));
+        assert!(edge_taken(&edge("b", None), &outcomes));
+        // Labels on edges out of other nodes don't route anything
+        assert!(edge_taken(&edge("x", Some("false")), &outcomes));
+    }
+
+    #[test]
+    fn test_carried_by_condition() {
+        let edge = |condition| Edge {
+            id: "e".into(),
+            source: "s".into(),
+            target: "t".into(),
+            label: None,
+            condition,
+        };
+        let ok = NodeOutput::Text("Summary".into(), None);
+        let broke = || "Fetch failed in Digest: timed out".to_string();
+
+        assert!(matches!(carried(&edge(None), &NodeOutput::Failed, broke), Some(NodeOutput::Failed)));
+        assert_eq!(carried(&edge(Some(EdgeCondition::OnSuccess)), &ok, broke).unwrap().as_text(), "Summary");
+        assert!(carried(&edge(Some(EdgeCondition::OnSuccess)), &NodeOutput::Failed, broke).is_none());
+        assert!(carried(&edge(Some(EdgeCondition::OnFailure)), &ok, broke).is_none());
+        let alert = carried(&edge(Some(EdgeCondition::OnFailure)), &NodeOutput::Failed, broke).unwrap();
+        assert_eq!(alert.as_text(), "Fetch failed in Digest: timed out");
+    }
+}
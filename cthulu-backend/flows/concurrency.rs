@@ -0,0 +1,390 @@
+//! How much runs at once, and what goes first. A trigger's
+//! `max_concurrent_runs` caps the flow's own runs and
+//! `CTHULU_MAX_CONCURRENT_RUNS` all top-level runs, the rest queuing for a
+//! slot; `CTHULU_MAX_CONCURRENT_EXECUTORS` caps executor calls across every
+//! flow, so ten PRs arriving together don't start ten `claude` processes.
+//!
+//! Queues are ordered by the run's `priority` (higher first, then in
+//! arrival order), so a production PR review gets the next slot ahead of a
+//! digest that has been waiting longer.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::flows::branch::Condition;
+use crate::flows::graph::NodeOutput;
+use crate::flows::{Flow, NodeType};
+
+/// Executor calls at once when `CTHULU_MAX_CONCURRENT_EXECUTORS` is unset.
+pub const DEFAULT_MAX_CONCURRENT_EXECUTORS: usize = 4;
+
+/// Runs waiting for a slot when `CTHULU_RUN_QUEUE_SIZE` is unset.
+pub const DEFAULT_RUN_QUEUE_SIZE: usize = 100;
+
+static RUNS: LazyLock<RunSlots> = LazyLock::new(RunSlots::default);
+
+static ALL_RUNS: LazyLock<Option<Arc<Gate>>> = LazyLock::new(|| {
+    let limit = limit_from_env("CTHULU_MAX_CONCURRENT_RUNS", None)?;
+    Some(Gate::new(limit, Some(run_queue_size())))
+});
+
+static EXECUTORS: LazyLock<Option<Arc<Gate>>> = LazyLock::new(|| {
+    let limit = limit_from_env("CTHULU_MAX_CONCURRENT_EXECUTORS", Some(DEFAULT_MAX_CONCURRENT_EXECUTORS))?;
+    Some(Gate::new(limit, None))
+});
+
+fn limit_from_env(name: &str, default: Option<usize>) -> Option<usize> {
+    let value = std::env::var(name).ok();
+    parse_limit(value.as_deref(), default).unwrap_or_else(|| {
+        tracing::warn!("{name} isn't a number, using the default");
+        default
+    })
+}
+
+/// `0` lifts the limit and unset keeps `default`; `None` when `value`
+/// isn't a number.
+fn parse_limit(value: Option<&str>, default: Option<usize>) -> Option<Option<usize>> {
+    match value.map(|v| v.trim().parse::<usize>()) {
+        Some(Ok(0)) => Some(None),
+        Some(Ok(limit)) => Some(Some(limit)),
+        Some(Err(_)) => None,
+        None => Some(default),
+    }
+}
+
+fn run_queue_size() -> usize {
+    match std::env::var("CTHULU_RUN_QUEUE_SIZE").ok().map(|v| v.trim().parse::<usize>()) {
+        Some(Ok(size)) => size,
+        Some(Err(_)) => {
+            tracing::warn!("CTHULU_RUN_QUEUE_SIZE isn't a number, using the default");
+            DEFAULT_RUN_QUEUE_SIZE
+        }
+        None => DEFAULT_RUN_QUEUE_SIZE,
+    }
+}
+
+fn trigger(flow: &Flow) -> Option<&crate::flows::Node> {
+    flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger)
+}
+
+/// The trigger's `max_concurrent_runs`, if it sets one.
+pub fn max_concurrent_runs(flow: &Flow) -> Result<Option<usize>> {
+    let Some(trigger) = trigger(flow) else {
+        return Ok(None);
+    };
+    match &trigger.config["max_concurrent_runs"] {
+        Value::Null => Ok(None),
+        value => match value.as_u64().filter(|n| *n > 0) {
+            Some(limit) => Ok(Some(limit as usize)),
+            None => bail!("max_concurrent_runs must be a whole number of at least 1"),
+        },
+    }
+}
+
+/// The priority of a run started with the event's `vars`: that of the
+/// first of the trigger's `priority_when` rules whose `condition` holds,
+/// else the trigger's `priority`, else 0. Higher goes first.
+pub fn priority(flow: &Flow, vars: &HashMap<String, String>) -> Result<i64> {
+    let Some(trigger) = trigger(flow) else {
+        return Ok(0);
+    };
+    let config = &trigger.config;
+    for rule in config["priority_when"].as_array().map(Vec::as_slice).unwrap_or_default() {
+        let source = rule["condition"].as_str().context("priority_when rules need a condition")?;
+        let condition = Condition::parse(source).with_context(|| format!("invalid priority_when condition '{source}'"))?;
+        if condition.evaluate(&NodeOutput::Empty, vars)? {
+            return priority_value(&rule["priority"]);
+        }
+    }
+    match &config["priority"] {
+        Value::Null => Ok(0),
+        value => priority_value(value),
+    }
+}
+
+fn priority_value(value: &Value) -> Result<i64> {
+    value.as_i64().context("priority must be a whole number")
+}
+
+/// Slots for at most `limit` holders at once, handed out by priority. When
+/// `bound` holders are already waiting, the lowest-priority one is turned
+/// away.
+struct Gate {
+    limit: usize,
+    bound: Option<usize>,
+    state: Mutex<GateState>,
+}
+
+#[derive(Default)]
+struct GateState {
+    taken: usize,
+    waiting: BinaryHeap<Waiter>,
+    arrivals: u64,
+}
+
+struct Waiter {
+    priority: i64,
+    arrival: u64,
+    slot: oneshot::Sender<Slot>,
+}
+
+impl Ord for Waiter {
+    /// Higher priority first, then earlier arrival.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority).then(other.arrival.cmp(&self.arrival))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+/// Held until dropped, when it goes to the next waiter in line.
+pub struct Slot {
+    gate: Option<Arc<Gate>>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if let Some(gate) = self.gate.take() {
+            gate.release();
+        }
+    }
+}
+
+/// What `Gate::try_acquire` did, for the caller to log.
+enum Acquired {
+    Now(Slot),
+    Queued(oneshot::Receiver<Slot>),
+}
+
+impl Gate {
+    fn new(limit: usize, bound: Option<usize>) -> Arc<Self> {
+        Arc::new(Self { limit, bound, state: Mutex::default() })
+    }
+
+    fn try_acquire(self: &Arc<Self>, priority: i64) -> Result<Acquired> {
+        let mut state = self.state.lock().unwrap();
+        if state.taken < self.limit && state.waiting.is_empty() {
+            state.taken += 1;
+            return Ok(Acquired::Now(Slot { gate: Some(self.clone()) }));
+        }
+        // Callers that stopped waiting don't hold a place
+        state.waiting.retain(|w| !w.slot.is_closed());
+        if let Some(bound) = self.bound
+            && state.waiting.len() >= bound
+        {
+            let lowest = state.waiting.iter().min().map(|w| (w.priority, w.arrival));
+            match lowest {
+                // Its sender dropped, the turned-away waiter gets an error
+                Some((lowest, arrival)) if lowest < priority => {
+                    state.waiting.retain(|w| w.arrival != arrival);
+                }
+                _ => bail!("run queue is full ({bound} runs waiting)"),
+            }
+        }
+        let (tx, rx) = oneshot::channel();
+        let arrival = state.arrivals;
+        state.arrivals += 1;
+        state.waiting.push(Waiter { priority, arrival, slot: tx });
+        Ok(Acquired::Queued(rx))
+    }
+
+    /// Hand a freed slot to the first waiter still waiting.
+    fn release(self: Arc<Self>) {
+        loop {
+            let mut state = self.state.lock().unwrap();
+            let Some(waiter) = state.waiting.pop() else {
+                state.taken -= 1;
+                return;
+            };
+            drop(state);
+            match waiter.slot.send(Slot { gate: Some(self.clone()) }) {
+                Ok(()) => return,
+                // Gone; the slot is still ours to hand on
+                Err(mut slot) => slot.gate = None,
+            }
+        }
+    }
+}
+
+async fn wait_for(queued: oneshot::Receiver<Slot>) -> Result<Slot> {
+    queued.await.map_err(|_| anyhow::anyhow!("turned away from the full run queue by higher-priority runs"))
+}
+
+/// Per-flow run slots, sized by each flow's `max_concurrent_runs`.
+#[derive(Default)]
+struct RunSlots {
+    flows: Mutex<HashMap<String, (usize, Arc<Gate>)>>,
+}
+
+impl RunSlots {
+    /// The flow's slots. A changed limit gets fresh ones; runs holding the
+    /// old ones finish against those.
+    fn gate(&self, flow_id: &str, limit: usize) -> Arc<Gate> {
+        let mut flows = self.flows.lock().unwrap();
+        match flows.get(flow_id) {
+            Some((current, gate)) if *current == limit => gate.clone(),
+            _ => {
+                let gate = Gate::new(limit, Some(run_queue_size()));
+                flows.insert(flow_id.to_string(), (limit, gate.clone()));
+                gate
+            }
+        }
+    }
+
+    async fn acquire(&self, flow: &Flow, limit: usize, priority: i64) -> Result<Slot> {
+        match self.gate(&flow.id, limit).try_acquire(priority)? {
+            Acquired::Now(slot) => Ok(slot),
+            Acquired::Queued(queued) => {
+                tracing::info!(flow = %flow.name, max_concurrent_runs = limit, priority, "Run queued until another finishes");
+                wait_for(queued).await
+            }
+        }
+    }
+}
+
+/// A top-level run's slots: the flow's own and the server-wide one.
+pub struct RunSlot {
+    _flow: Option<Slot>,
+    _all: Option<Slot>,
+}
+
+/// Wait for one of the flow's run slots, then for one of the server's,
+/// held until the `RunSlot` drops. Errors when a queue is full of runs of
+/// at least `priority`.
+pub async fn run_slot(flow: &Flow, priority: i64) -> Result<RunSlot> {
+    let _flow = match max_concurrent_runs(flow)? {
+        Some(limit) => Some(RUNS.acquire(flow, limit, priority).await?),
+        None => None,
+    };
+    let _all = match ALL_RUNS.as_ref() {
+        Some(gate) => Some(match gate.try_acquire(priority)? {
+            Acquired::Now(slot) => slot,
+            Acquired::Queued(queued) => {
+                tracing::info!(flow = %flow.name, priority, "Run queued behind other flows' runs");
+                wait_for(queued).await?
+            }
+        }),
+        None => None,
+    };
+    Ok(RunSlot { _flow, _all })
+}
+
+/// Wait for an executor slot, in `priority` order, held until it drops.
+/// `None` when executors aren't limited.
+pub async fn executor_slot(priority: i64) -> Option<Slot> {
+    let gate = EXECUTORS.as_ref()?;
+    match gate.try_acquire(priority).expect("the executor queue is unbounded") {
+        Acquired::Now(slot) => Some(slot),
+        Acquired::Queued(queued) => Some(queued.await.expect("executor slots are never dropped from the queue")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn flow(trigger_config: Value) -> Flow {
+        serde_json::from_value(json!({
+            "id": "f",
+            "name": "PR review",
+            "nodes": [{
+                "id": "t", "node_type": "trigger", "kind": "github-pr", "config": trigger_config,
+                "position": { "x": 0, "y": 0 }, "label": "PRs",
+            }],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(max_concurrent_runs(&flow(json!({}))).unwrap(), None);
+        assert_eq!(max_concurrent_runs(&flow(json!({ "max_concurrent_runs": 2 }))).unwrap(), Some(2));
+        assert!(max_concurrent_runs(&flow(json!({ "max_concurrent_runs": 0 }))).is_err());
+        assert!(max_concurrent_runs(&flow(json!({ "max_concurrent_runs": "two" }))).is_err());
+
+        let default = Some(DEFAULT_MAX_CONCURRENT_EXECUTORS);
+        assert_eq!(parse_limit(None, default), Some(default));
+        assert_eq!(parse_limit(Some("8"), default), Some(Some(8)));
+        assert_eq!(parse_limit(Some("0"), default), Some(None));
+        assert_eq!(parse_limit(Some("many"), default), None);
+    }
+
+    #[test]
+    fn test_priority() {
+        let vars = |base: &str| HashMap::from([("base_ref".to_string(), base.to_string())]);
+        assert_eq!(priority(&flow(json!({})), &vars("main")).unwrap(), 0);
+
+        let config = json!({
+            "priority": -1,
+            "priority_when": [{ "condition": "base_ref == \"main\"", "priority": 10 }],
+        });
+        assert_eq!(priority(&flow(config.clone()), &vars("main")).unwrap(), 10);
+        assert_eq!(priority(&flow(config), &vars("feature")).unwrap(), -1);
+        assert!(priority(&flow(json!({ "priority": "high" })), &vars("main")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_runs_queue_for_a_slot() {
+        let slots = RunSlots::default();
+        let flow = flow(json!({ "max_concurrent_runs": 1 }));
+        let first = slots.acquire(&flow, 1, 0).await.unwrap();
+
+        let second = slots.acquire(&flow, 1, 0);
+        tokio::pin!(second);
+        assert!(futures::poll!(&mut second).is_pending());
+        drop(first);
+        let second = second.await.unwrap();
+
+        // Raising the limit doesn't wait on runs holding the old slots
+        let third = slots.acquire(&flow, 2, 0).await.unwrap();
+        drop((second, third));
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_goes_first() {
+        let gate = Gate::new(1, Some(2));
+        let Ok(Acquired::Now(running)) = gate.try_acquire(0) else {
+            panic!("the gate starts open");
+        };
+        let queue = |priority| match gate.try_acquire(priority) {
+            Ok(Acquired::Queued(queued)) => queued,
+            _ => panic!("the slot is taken"),
+        };
+        let digest = queue(0);
+        let mut review = queue(5);
+
+        // The queue is full: lower priority is turned away, higher bumps the digest
+        assert!(gate.try_acquire(0).is_err());
+        let urgent = queue(9);
+        assert!(wait_for(digest).await.is_err());
+
+        drop(running);
+        let slot = urgent.await.unwrap();
+        assert!(review.try_recv().is_err());
+        drop(slot);
+        let slot = review.await.unwrap();
+        drop(slot);
+        assert_eq!(gate.state.lock().unwrap().taken, 0);
+    }
+}
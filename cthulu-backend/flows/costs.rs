@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, Days, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::flows::{Flow, Node, NodeType, Position};
+use crate::json_file;
+
+/// Days of spend kept per flow.
+const KEEP_DAYS: u64 = 400;
+
+/// Serializes read-modify-write of the ledger file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Daily spend per flow, by UTC day.
+type Ledger = HashMap<String, BTreeMap<NaiveDate, f64>>;
+
+/// File-backed running total of what each flow's executors have cost
+/// (`~/.cthulu/costs.json`), so flow budgets outlive the run history.
+#[derive(Clone)]
+pub struct CostLedger {
+    path: PathBuf,
+}
+
+/// A flow's spend so far in the current day, week and month, and over the
+/// ledger's lifetime.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CostSummary {
+    pub today: f64,
+    pub week: f64,
+    pub month: f64,
+    pub total: f64,
+}
+
+impl CostLedger {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("costs.json") }
+    }
+
+    /// The ledger. A file that doesn't parse is an error, never an empty
+    /// ledger: budgets would otherwise start over at nothing spent.
+    fn load(&self) -> Result<Ledger> {
+        json_file::load(&self.path)
+    }
+
+    fn write(&self, ledger: &Ledger) -> Result<()> {
+        json_file::write(&self.path, ledger)
+    }
+
+    /// Add `usd` to `flow_id`'s spend on `day`.
+    pub fn add(&self, flow_id: &str, day: NaiveDate, usd: f64) -> Result<()> {
+        if usd <= 0.0 {
+            return Ok(());
+        }
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut ledger = self.load()?;
+        let days = ledger.entry(flow_id.to_string()).or_default();
+        *days.entry(day).or_default() += usd;
+        if let Some(cutoff) = day.checked_sub_days(Days::new(KEEP_DAYS)) {
+            days.retain(|d, _| *d >= cutoff);
+        }
+        self.write(&ledger)
+    }
+
+    /// What `flow_id` has spent from `since` on.
+    pub fn spent_since(&self, flow_id: &str, since: NaiveDate) -> Result<f64> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        Ok(self
+            .load()?
+            .get(flow_id)
+            .map_or(0.0, |days| days.range(since..).map(|(_, usd)| usd).sum()))
+    }
+
+    pub fn summary(&self, flow_id: &str, today: NaiveDate) -> Result<CostSummary> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let Some(days) = self.load()?.remove(flow_id) else {
+            return Ok(CostSummary::default());
+        };
+        let since = |period: BudgetPeriod| -> f64 {
+            days.range(period.start(today)..).map(|(_, usd)| usd).sum()
+        };
+        Ok(CostSummary {
+            today: since(BudgetPeriod::Day),
+            week: since(BudgetPeriod::Week),
+            month: since(BudgetPeriod::Month),
+            total: days.values().sum(),
+        })
+    }
+
+    /// Forget a deleted flow's spend.
+    pub fn delete_flow(&self, flow_id: &str) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut ledger = self.load()?;
+        if ledger.remove(flow_id).is_some() {
+            self.write(&ledger)?;
+        }
+        Ok(())
+    }
+}
+
+/// The stretch of time a flow budget covers, in UTC calendar terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Day,
+    /// Monday to Sunday.
+    Week,
+    Month,
+}
+
+impl BudgetPeriod {
+    /// First day of the period `today` falls in.
+    pub fn start(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => today,
+            Self::Week => today - Days::new(today.weekday().num_days_from_monday().into()),
+            Self::Month => today.with_day(1).unwrap_or(today),
+        }
+    }
+}
+
+/// A trigger's spending limits: `max_cost_usd` per run, `max_flow_cost_usd`
+/// per `cost_period` (day, week or month; default month), and a
+/// `cost_alert` sink (`{ kind, config }`) told when either is hit.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    pub max_run_usd: Option<f64>,
+    pub max_flow_usd: Option<f64>,
+    pub period: BudgetPeriod,
+    pub alert: Option<Node>,
+}
+
+impl Budget {
+    pub fn for_flow(flow: &Flow) -> Result<Self> {
+        match flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) {
+            Some(trigger) => Self::from_trigger(&trigger.config),
+            None => Self::from_trigger(&Value::Null),
+        }
+    }
+
+    pub fn from_trigger(config: &Value) -> Result<Self> {
+        let limit = |key: &str| -> Result<Option<f64>> {
+            match &config[key] {
+                Value::Null => Ok(None),
+                value => value
+                    .as_f64()
+                    .filter(|usd| *usd > 0.0)
+                    .map(Some)
+                    .with_context(|| format!("{key} must be a positive number")),
+            }
+        };
+        let period = match config["cost_period"].as_str().unwrap_or("month") {
+            "day" => BudgetPeriod::Day,
+            "week" => BudgetPeriod::Week,
+            "month" => BudgetPeriod::Month,
+            other => anyhow::bail!("cost_period must be day, week or month, got '{other}'"),
+        };
+        let alert = match &config["cost_alert"] {
+            Value::Null => None,
+            alert => {
+                let kind = alert["kind"]
+                    .as_str()
+                    .filter(|k| !k.is_empty())
+                    .context("cost_alert requires a sink kind, e.g. slack")?;
+                Some(Node {
+                    id: "cost-alert".to_string(),
+                    node_type: NodeType::Sink,
+                    kind: kind.to_string(),
+                    config: alert["config"].clone(),
+                    position: Position { x: 0.0, y: 0.0 },
+                    label: "Cost alert".to_string(),
+                })
+            }
+        };
+        Ok(Self {
+            max_run_usd: limit("max_cost_usd")?,
+            max_flow_usd: limit("max_flow_cost_usd")?,
+            period,
+            alert,
+        })
+    }
+
+    /// Why a run can't start, when the flow has already spent its budget
+    /// for the period.
+    pub fn exhausted(&self, flow_usd: f64) -> Option<String> {
+        let max = self.max_flow_usd.filter(|max| flow_usd >= *max)?;
+        Some(format!(
+            "flow budget of ${max:.2} per {} already spent (${flow_usd:.2})",
+            self.period_name()
+        ))
+    }
+
+    /// Why a run must stop, once it (or the flow over the period, this run
+    /// included) has cost more than allowed.
+    pub fn exceeded(&self, run_usd: f64, flow_usd: f64) -> Option<String> {
+        if let Some(max) = self.max_run_usd.filter(|max| run_usd > *max) {
+            return Some(format!("run cost ${run_usd:.2} exceeded max_cost_usd ${max:.2}"));
+        }
+        let max = self.max_flow_usd.filter(|max| flow_usd > *max)?;
+        Some(format!(
+            "flow cost ${flow_usd:.2} this {} exceeded max_flow_cost_usd ${max:.2}",
+            self.period_name()
+        ))
+    }
+
+    fn period_name(&self) -> &'static str {
+        match self.period {
+            BudgetPeriod::Day => "day",
+            BudgetPeriod::Week => "week",
+            BudgetPeriod::Month => "month",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn day(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ledger_accumulates_per_flow_and_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = CostLedger::new(dir.path());
+        ledger.add("f", day("2026-09-28"), 1.0).unwrap();
+        ledger.add("f", day("2026-10-12"), 0.5).unwrap();
+        ledger.add("f", day("2026-10-14"), 0.25).unwrap();
+        ledger.add("f", day("2026-10-14"), 0.25).unwrap();
+        ledger.add("other", day("2026-10-14"), 9.0).unwrap();
+
+        // 2026-10-14 is a Wednesday
+        let summary = ledger.summary("f", day("2026-10-14")).unwrap();
+        assert_eq!(summary, CostSummary { today: 0.5, week: 1.0, month: 1.0, total: 2.0 });
+        assert_eq!(ledger.spent_since("f", day("2026-10-13")).unwrap(), 0.5);
+
+        ledger.delete_flow("f").unwrap();
+        assert_eq!(ledger.summary("f", day("2026-10-14")).unwrap(), CostSummary::default());
+        assert_eq!(ledger.spent_since("other", day("2026-10-01")).unwrap(), 9.0);
+    }
+
+    #[test]
+    fn test_corrupt_ledger_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = CostLedger::new(dir.path());
+        std::fs::write(&ledger.path, "{").unwrap();
+
+        assert!(ledger.spent_since("f", day("2026-10-01")).is_err());
+        assert!(ledger.summary("f", day("2026-10-14")).is_err());
+        assert!(ledger.add("f", day("2026-10-14"), 1.0).is_err());
+        assert_eq!(std::fs::read_to_string(&ledger.path).unwrap(), "{");
+    }
+
+    #[test]
+    fn test_budget_limits() {
+        let budget = Budget::from_trigger(&json!({})).unwrap();
+        assert_eq!(budget.exceeded(100.0, 1000.0), None);
+        assert_eq!(budget.exhausted(1000.0), None);
+
+        let budget = Budget::from_trigger(&json!({
+            "max_cost_usd": 0.5,
+            "max_flow_cost_usd": 10,
+            "cost_period": "week",
+            "cost_alert": { "kind": "slack", "config": { "webhook_url_env": "SLACK_WEBHOOK" } }
+        }))
+        .unwrap();
+        assert_eq!(budget.period.start(day("2026-10-18")), day("2026-10-12"));
+        assert_eq!(budget.alert.as_ref().unwrap().kind, "slack");
+        assert_eq!(budget.exceeded(0.5, 5.0), None);
+        assert!(budget.exceeded(0.51, 5.0).unwrap().contains("max_cost_usd"));
+        assert!(budget.exceeded(0.2, 10.1).unwrap().contains("max_flow_cost_usd"));
+        assert_eq!(budget.exhausted(9.99), None);
+        assert!(budget.exhausted(10.0).is_some());
+
+        assert!(Budget::from_trigger(&json!({ "max_cost_usd": -1 })).is_err());
+        assert!(Budget::from_trigger(&json!({ "cost_period": "year" })).is_err());
+        assert!(Budget::from_trigger(&json!({ "cost_alert": {} })).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::{Mutex, Notify};
+
+use crate::flows::{Flow, NodeType};
+
+/// Batch size at which a window is flushed early when `max_batch` is unset.
+pub const DEFAULT_MAX_BATCH: usize = 50;
+
+/// A trigger's `debounce_secs` / `max_batch` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebounceConfig {
+    pub window: Duration,
+    pub max_batch: usize,
+}
+
+impl DebounceConfig {
+    /// The debounce settings on `flow`'s trigger, or `None` when it runs once
+    /// per event (`debounce_secs` unset or 0).
+    pub fn for_flow(flow: &Flow) -> Option<Self> {
+        let trigger = flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger)?;
+        Self::from_trigger(&trigger.config)
+    }
+
+    pub fn from_trigger(config: &Value) -> Option<Self> {
+        let secs = config["debounce_secs"].as_u64().filter(|s| *s > 0)?;
+        let max_batch = config["max_batch"]
+            .as_u64()
+            .filter(|n| *n > 0)
+            .map_or(DEFAULT_MAX_BATCH, |n| n as usize);
+        Some(Self { window: Duration::from_secs(secs), max_batch })
+    }
+}
+
+struct Batch {
+    events: Arc<std::sync::Mutex<Vec<HashMap<String, String>>>>,
+    full: Arc<Notify>,
+}
+
+/// Coalesces trigger events per key (a flow id) into one run per window.
+///
+/// The first event opens a window; later events join it until `window` has
+/// elapsed since the first or `max_batch` events are queued, then the batch
+/// runs once with [`coalesce`]d context and the next event opens a new window.
+#[derive(Clone, Default)]
+pub struct Coalescer {
+    batches: Arc<Mutex<HashMap<String, Batch>>>,
+}
+
+impl Coalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `context` under `key` and return the number of events now in the
+    /// window. `run` is called with the coalesced context when the window
+    /// closes; only the closure passed with a window's first event is used.
+    pub async fn submit<F, Fut>(
+        &self,
+        key: &str,
+        context: HashMap<String, String>,
+        config: DebounceConfig,
+        run: F,
+    ) -> usize
+    where
+        F: FnOnce(HashMap<String, String>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut batches = self.batches.lock().await;
+        let batch = batches.entry(key.to_string()).or_insert_with(|| {
+            let batch = Batch {
+                events: Arc::new(std::sync::Mutex::new(Vec::new())),
+                full: Arc::new(Notify::new()),
+            };
+            tokio::spawn(flush_after(
+                self.batches.clone(),
+                key.to_string(),
+                batch.events.clone(),
+                batch.full.clone(),
+                config.window,
+                run,
+            ));
+            batch
+        });
+
+        let size = {
+            let mut events = batch.events.lock().unwrap();
+            events.push(context);
+            events.len()
+        };
+        if size >= config.max_batch {
+            // Close the window now; the next event opens a fresh one.
+            batch.full.notify_one();
+            batches.remove(key);
+        }
+        size
+    }
+}
+
+async fn flush_after<F, Fut>(
+    batches: Arc<Mutex<HashMap<String, Batch>>>,
+    key: String,
+    events: Arc<std::sync::Mutex<Vec<HashMap<String, String>>>>,
+    full: Arc<Notify>,
+    window: Duration,
+    run: F,
+) where
+    F: FnOnce(HashMap<String, String>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    tokio::select! {
+        _ = tokio::time::sleep(window) => {}
+        _ = full.notified() => {}
+    }
+    {
+        let mut batches = batches.lock().await;
+        // Unless the batch already filled up and a newer window took its place
+        if batches.get(&key).is_some_and(|b| Arc::ptr_eq(&b.events, &events)) {
+            batches.remove(&key);
+        }
+    }
+    let events = std::mem::take(&mut *events.lock().unwrap());
+    tracing::info!(key = %key, events = events.len(), "Debounce window closed, running batch");
+    run(coalesce(events)).await;
+}
+
+/// One run's context for a batch of events: the latest event's variables
+/// (so single-event prompts keep working), `{{event_count}}`, and `{{events}}`
+/// — a JSON array of every event's variables, oldest first.
+pub fn coalesce(events: Vec<HashMap<String, String>>) -> HashMap<String, String> {
+    let mut context = events.last().cloned().unwrap_or_default();
+    context.insert("event_count".to_string(), events.len().to_string());
+    context.insert(
+        "events".to_string(),
+        serde_json::to_string_pretty(&events).unwrap_or_default(),
+    );
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(n: u32) -> HashMap<String, String> {
+        HashMap::from([("issue_number".to_string(), n.to_string())])
+    }
+
+    #[test]
+    fn test_from_trigger() {
+        assert_eq!(DebounceConfig::from_trigger(&serde_json::json!({})), None);
+        assert_eq!(DebounceConfig::from_trigger(&serde_json::json!({ "debounce_secs": 0 })), None);
+        assert_eq!(
+            DebounceConfig::from_trigger(&serde_json::json!({ "debounce_secs": 30 })),
+            Some(DebounceConfig { window: Duration::from_secs(30), max_batch: DEFAULT_MAX_BATCH })
+        );
+        assert_eq!(
+            DebounceConfig::from_trigger(&serde_json::json!({ "debounce_secs": 30, "max_batch": 5 }))
+                .unwrap()
+                .max_batch,
+            5
+        );
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let context = coalesce(vec![event(1), event(2)]);
+        assert_eq!(context["issue_number"], "2");
+        assert_eq!(context["event_count"], "2");
+        let events: Vec<HashMap<String, String>> = serde_json::from_str(&context["events"]).unwrap();
+        assert_eq!(events, vec![event(1), event(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_submit_coalesces_window_and_flushes_full_batches() {
+        let coalescer = Coalescer::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let config = DebounceConfig { window: Duration::from_millis(100), max_batch: 3 };
+        let submit = |n: u32| {
+            let tx = tx.clone();
+            coalescer.submit("flow", event(n), config, move |ctx| async move {
+                tx.send(ctx).unwrap();
+            })
+        };
+
+        assert_eq!(submit(1).await, 1);
+        assert_eq!(submit(2).await, 2);
+        // Third event fills the batch and runs it without waiting for the window
+        assert_eq!(submit(3).await, 3);
+        let full = tokio::time::timeout(Duration::from_millis(50), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(full["event_count"], "3");
+
+        // A new window opens and closes on its timer
+        assert_eq!(submit(4).await, 1);
+        let timed = rx.recv().await.unwrap();
+        assert_eq!(timed["event_count"], "1");
+        assert_eq!(timed["issue_number"], "4");
+    }
+}
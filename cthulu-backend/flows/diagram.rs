@@ -0,0 +1,194 @@
+//! A flow's graph as a Mermaid or Graphviz DOT diagram, to embed in design
+//! docs and review in PRs as text.
+
+use super::branch::BRANCH_KIND;
+use super::{Edge, EdgeCondition, Flow, Node, NodeType};
+
+/// Output format of `GET /api/flows/{id}/graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Mermaid,
+    Dot,
+}
+
+impl Format {
+    /// `mermaid` (the default) or `dot`.
+    pub fn parse(format: Option<&str>) -> Option<Self> {
+        match format.unwrap_or("mermaid") {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" => Some(Self::Dot),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Mermaid => "text/plain; charset=utf-8",
+            Self::Dot => "text/vnd.graphviz; charset=utf-8",
+        }
+    }
+}
+
+pub fn render(flow: &Flow, format: Format) -> String {
+    match format {
+        Format::Mermaid => mermaid(flow),
+        Format::Dot => dot(flow),
+    }
+}
+
+fn title(flow: &Flow) -> String {
+    match flow.enabled {
+        true => flow.name.clone(),
+        false => format!("{} (disabled)", flow.name),
+    }
+}
+
+/// What an edge is labeled with: its branch outcome or `each`, and when
+/// it's only taken on its source's success or failure.
+fn edge_label(edge: &Edge) -> Option<String> {
+    let condition = edge.condition.map(|c| match c {
+        EdgeCondition::OnSuccess => "on success",
+        EdgeCondition::OnFailure => "on failure",
+    });
+    match (edge.label.as_deref(), condition) {
+        (Some(label), Some(condition)) => Some(format!("{label}, {condition}")),
+        (Some(label), None) => Some(label.to_string()),
+        (None, Some(condition)) => Some(condition.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// Node ids are arbitrary strings, so diagrams name nodes by position.
+fn node_index(flow: &Flow, id: &str) -> Option<usize> {
+    flow.nodes.iter().position(|n| n.id == id)
+}
+
+fn mermaid(flow: &Flow) -> String {
+    let escape = |s: &str| s.replace('"', "#quot;").replace('\n', " ");
+    let mut out = format!("---\ntitle: {}\n---\nflowchart LR\n", escape(&title(flow)));
+    for (i, node) in flow.nodes.iter().enumerate() {
+        let text = format!("\"{}<br/><small>{}</small>\"", escape(&node.label), escape(&node.kind));
+        let shape = match (node.node_type, node.kind.as_str()) {
+            (NodeType::Trigger, _) => format!("([{text}])"),
+            (NodeType::Source, _) => format!("[({text})]"),
+            (NodeType::Executor, BRANCH_KIND) => format!("{{{text}}}"),
+            (NodeType::Executor, _) => format!("[{text}]"),
+            (NodeType::Sink, _) => format!(">{text}]"),
+        };
+        out.push_str(&format!("    n{i}{shape}\n"));
+    }
+    for edge in &flow.edges {
+        let (Some(source), Some(target)) = (node_index(flow, &edge.source), node_index(flow, &edge.target)) else {
+            continue;
+        };
+        let arrow = match edge.condition {
+            Some(EdgeCondition::OnFailure) => "-.->",
+            _ => "-->",
+        };
+        match edge_label(edge) {
+            Some(label) => out.push_str(&format!("    n{source} {arrow}|\"{}\"| n{target}\n", escape(&label))),
+            None => out.push_str(&format!("    n{source} {arrow} n{target}\n")),
+        }
+    }
+    if !flow.enabled && !flow.nodes.is_empty() {
+        let ids: Vec<String> = (0..flow.nodes.len()).map(|i| format!("n{i}")).collect();
+        out.push_str("    classDef disabled fill:#f4f4f4,stroke:#aaa,color:#888,stroke-dasharray:4\n");
+        out.push_str(&format!("    class {} disabled\n", ids.join(",")));
+    }
+    out
+}
+
+fn dot_shape(node: &Node) -> &'static str {
+    match (node.node_type, node.kind.as_str()) {
+        (NodeType::Trigger, _) => "oval",
+        (NodeType::Source, _) => "cylinder",
+        (NodeType::Executor, BRANCH_KIND) => "diamond",
+        (NodeType::Executor, _) => "box",
+        (NodeType::Sink, _) => "invhouse",
+    }
+}
+
+fn dot(flow: &Flow) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"));
+    let mut out = format!("digraph {} {{\n", quote(&flow.id));
+    out.push_str(&format!("    label={};\n    labelloc=t;\n    rankdir=LR;\n", quote(&title(flow))));
+    match flow.enabled {
+        true => out.push_str("    node [fontname=\"Helvetica\"];\n"),
+        false => out.push_str("    node [fontname=\"Helvetica\", style=dashed, color=gray60, fontcolor=gray50];\n"),
+    }
+    out.push_str("    edge [fontname=\"Helvetica\", fontsize=10];\n");
+    for (i, node) in flow.nodes.iter().enumerate() {
+        let label = format!("{}\n{}", node.label, node.kind);
+        out.push_str(&format!("    n{i} [label={}, shape={}];\n", quote(&label), dot_shape(node)));
+    }
+    for edge in &flow.edges {
+        let (Some(source), Some(target)) = (node_index(flow, &edge.source), node_index(flow, &edge.target)) else {
+            continue;
+        };
+        let mut attrs = Vec::new();
+        if let Some(label) = edge_label(edge) {
+            attrs.push(format!("label={}", quote(&label)));
+        }
+        if edge.condition == Some(EdgeCondition::OnFailure) {
+            attrs.push("style=dashed".to_string());
+        }
+        match attrs.is_empty() {
+            true => out.push_str(&format!("    n{source} -> n{target};\n")),
+            false => out.push_str(&format!("    n{source} -> n{target} [{}];\n", attrs.join(", "))),
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(enabled: bool) -> Flow {
+        serde_json::from_value(serde_json::json!({
+            "id": "f1",
+            "name": "PR \"review\"",
+            "enabled": enabled,
+            "nodes": [
+                { "id": "t", "node_type": "trigger", "kind": "github-pr", "config": {}, "position": { "x": 0, "y": 0 }, "label": "PRs" },
+                { "id": "big", "node_type": "executor", "kind": "branch", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Large diff?" },
+                { "id": "rev", "node_type": "executor", "kind": "claude-code", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Review" },
+                { "id": "slack", "node_type": "sink", "kind": "slack", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Alert" }
+            ],
+            "edges": [
+                { "id": "e1", "source": "t", "target": "big" },
+                { "id": "e2", "source": "big", "target": "rev", "label": "true" },
+                { "id": "e3", "source": "rev", "target": "slack", "condition": "on_failure" }
+            ],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let diagram = render(&flow(true), Format::Mermaid);
+        assert!(diagram.starts_with("---\ntitle: PR #quot;review#quot;\n---\nflowchart LR\n"));
+        assert!(diagram.contains("    n0([\"PRs<br/><small>github-pr</small>\"])\n"));
+        assert!(diagram.contains("    n1{\"Large diff?<br/><small>branch</small>\"}\n"));
+        assert!(diagram.contains("    n1 -->|\"true\"| n2\n"));
+        assert!(diagram.contains("    n2 -.->|\"on failure\"| n3\n"));
+        assert!(!diagram.contains("disabled"));
+
+        let diagram = render(&flow(false), Format::Mermaid);
+        assert!(diagram.contains("(disabled)") && diagram.contains("    class n0,n1,n2,n3 disabled\n"));
+    }
+
+    #[test]
+    fn test_dot() {
+        let diagram = render(&flow(false), Format::Dot);
+        assert!(diagram.starts_with("digraph \"f1\" {\n    label=\"PR \\\"review\\\" (disabled)\";\n"));
+        assert!(diagram.contains("style=dashed, color=gray60"));
+        assert!(diagram.contains("    n3 [label=\"Alert\\nslack\", shape=invhouse];\n"));
+        assert!(diagram.contains("    n0 -> n1;\n"));
+        assert!(diagram.contains("    n2 -> n3 [label=\"on failure\", style=dashed];\n"));
+        assert!(diagram.ends_with("}\n"));
+    }
+}
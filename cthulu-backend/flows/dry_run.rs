@@ -0,0 +1,160 @@
+//! Dry runs (`POST /flows/{id}/run` with `dry_run: true`): sources and
+//! transforms run for real, executors echo their rendered prompt instead of
+//! running a model, and sinks hand what they would have delivered to the
+//! run's `DryRun` instead of Slack, Notion or email.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::flows::Node;
+use crate::flows::graph::NodeOutput;
+
+tokio::task_local! {
+    static SCOPED: Arc<DryRun>;
+}
+
+/// What one sink would have delivered.
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub node_id: String,
+    pub label: String,
+    /// The sink's kind, e.g. `slack` or `notion`.
+    pub kind: String,
+    pub text: String,
+    /// How many items the sink got, when it got items rather than text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<usize>,
+    pub at: DateTime<Utc>,
+}
+
+/// The deliveries captured in one dry run, subflows' included.
+#[derive(Debug, Default)]
+pub struct DryRun {
+    deliveries: Mutex<Vec<Delivery>>,
+}
+
+impl DryRun {
+    /// Capture what sink `node` would have delivered from `input`.
+    pub fn capture(&self, node: &Node, input: &NodeOutput) {
+        let items = match input {
+            NodeOutput::Items(items) => Some(items.len()),
+            _ => None,
+        };
+        tracing::info!(node = %node.label, "Dry run, captured the delivery");
+        self.deliveries.lock().unwrap().push(Delivery {
+            node_id: node.id.clone(),
+            label: node.label.clone(),
+            kind: node.kind.clone(),
+            text: input.as_text(),
+            items,
+            at: Utc::now(),
+        });
+    }
+
+    /// Deliveries in the order the sinks ran.
+    pub fn deliveries(&self) -> Vec<Delivery> {
+        self.deliveries.lock().unwrap().clone()
+    }
+}
+
+/// The dry run `scope`d to this task, if any.
+pub fn current() -> Option<Arc<DryRun>> {
+    SCOPED.try_with(Arc::clone).ok()
+}
+
+/// Run `work` as a dry run capturing into `dry_run` (when given) — how
+/// runs carry theirs into their node tasks.
+pub async fn scope<F: Future>(dry_run: Option<Arc<DryRun>>, work: F) -> F::Output {
+    match dry_run {
+        Some(dry_run) => SCOPED.scope(dry_run, work).await,
+        None => work.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::flows::Flow;
+    use crate::flows::file_repository::FileFlowRepository;
+    use crate::flows::history::RunStatus;
+    use crate::flows::repository::FlowRepository;
+    use crate::flows::runner::FlowRunner;
+
+    fn flow() -> Flow {
+        let node = |id: &str, node_type: &str, kind: &str, config: Value| {
+            json!({
+                "id": id, "node_type": node_type, "kind": kind, "config": config,
+                "position": { "x": 0, "y": 0 }, "label": id,
+            })
+        };
+        serde_json::from_value(json!({
+            "id": "dry-flow",
+            "name": "Standup",
+            "nodes": [
+                node("trigger", "trigger", "manual", json!({})),
+                node("write", "executor", "claude-code", json!({ "prompt": "Standup notes for {{team}}" })),
+                node("post", "sink", "slack", json!({ "webhook_url": "https://hooks.slack.com/services/T0/B0/x" })),
+            ],
+            "edges": [
+                { "id": "e1", "source": "trigger", "target": "write" },
+                { "id": "e2", "source": "write", "target": "post" },
+            ],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn runner() -> FlowRunner {
+        FlowRunner {
+            http_client: Arc::new(reqwest::Client::new()),
+            github_client: None,
+            events_tx: None,
+            sandbox_provider: None,
+            agent_repo: None,
+            session_bridge: None,
+            executor_cache: None,
+            source_cache: None,
+            prompt_repo: None,
+            seen_store: None,
+            archive: None,
+            subscriptions: None,
+            feedback: None,
+            trending: None,
+            vectors: None,
+            audio: None,
+            costs: None,
+            waits: None,
+            aggregates: None,
+            run_outputs: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sinks_are_captured() {
+        let dry_run = Arc::new(DryRun::default());
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        let context = HashMap::from([("team".to_string(), "payments".to_string())]);
+
+        let run = scope(Some(dry_run.clone()), runner().execute(&flow(), &repo, Some(context)))
+            .await
+            .unwrap();
+        assert_eq!(run.status, RunStatus::Success, "{:?}", run.error);
+        assert!(run.dry_run);
+
+        // The executor echoed its prompt, which went nowhere but the capture
+        let deliveries = dry_run.deliveries();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!((deliveries[0].node_id.as_str(), deliveries[0].kind.as_str()), ("post", "slack"));
+        assert!(deliveries[0].text.contains("Standup notes for payments"));
+        assert_eq!(repo.get_runs("dry-flow", 10).await[0].id, run.id);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::flows::Node;
+use crate::flows::history::{FlowRun, RunStatus};
+
+/// `prompt_ab` on an executor node: send `b_percent`% of runs to prompt `b`
+/// and the rest to `a`. Both are `prompt_ref`s, usually two versions of the
+/// same library prompt (`pr-review@2` vs `pr-review@3`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PromptSplit {
+    pub a: String,
+    pub b: String,
+    pub b_percent: u8,
+}
+
+impl PromptSplit {
+    pub fn from_node(node: &Node) -> Option<Self> {
+        let ab = &node.config["prompt_ab"];
+        let a = ab["a"].as_str().filter(|s| !s.is_empty())?;
+        let b = ab["b"].as_str().filter(|s| !s.is_empty())?;
+        Some(Self {
+            a: a.to_string(),
+            b: b.to_string(),
+            b_percent: ab["b_percent"].as_u64().unwrap_or(50).min(100) as u8,
+        })
+    }
+
+    /// The variant for `run_id`. Deterministic, so the runner (which records
+    /// it) and the executor (which renders it) agree without sharing state.
+    pub fn choose(&self, run_id: &str) -> &str {
+        if bucket(run_id) < self.b_percent {
+            &self.b
+        } else {
+            &self.a
+        }
+    }
+}
+
+/// Map a run id uniformly onto 0..100.
+fn bucket(run_id: &str) -> u8 {
+    let digest = Sha256::digest(run_id.as_bytes());
+    (u16::from_be_bytes([digest[0], digest[1]]) % 100) as u8
+}
+
+#[derive(Debug, Serialize)]
+pub struct VariantStats {
+    pub prompt_ref: String,
+    pub runs: usize,
+    pub successes: usize,
+    pub success_rate: f64,
+    pub avg_cost_usd: Option<f64>,
+    pub avg_duration_secs: Option<f64>,
+    pub rated_runs: usize,
+    pub avg_rating: Option<f64>,
+}
+
+/// Aggregate finished runs of `node_id` by the prompt variant they used.
+pub fn variant_stats(runs: &[FlowRun], node_id: &str) -> Vec<VariantStats> {
+    #[derive(Default)]
+    struct Acc {
+        runs: usize,
+        successes: usize,
+        costs: Vec<f64>,
+        durations: Vec<f64>,
+        ratings: Vec<f64>,
+    }
+
+    let mut by_variant: BTreeMap<&str, Acc> = BTreeMap::new();
+    for run in runs {
+        let Some(nr) = run.node_runs.iter().find(|nr| nr.node_id == node_id) else {
+            continue;
+        };
+        let (Some(variant), Some(finished_at)) = (nr.prompt_variant.as_deref(), nr.finished_at)
+        else {
+            continue;
+        };
+
+        let acc = by_variant.entry(variant).or_default();
+        acc.runs += 1;
+        if nr.status == RunStatus::Success {
+            acc.successes += 1;
+        }
+        if let Some(cost) = nr.cost_usd {
+            acc.costs.push(cost);
+        }
+        acc.durations
+            .push((finished_at - nr.started_at).num_milliseconds() as f64 / 1000.0);
+        if let Some(rating) = run.rating {
+            acc.ratings.push(rating as f64);
+        }
+    }
+
+    by_variant
+        .into_iter()
+        .map(|(variant, acc)| VariantStats {
+            prompt_ref: variant.to_string(),
+            runs: acc.runs,
+            successes: acc.successes,
+            success_rate: acc.successes as f64 / acc.runs as f64,
+            avg_cost_usd: mean(&acc.costs),
+            avg_duration_secs: mean(&acc.durations),
+            rated_runs: acc.ratings.len(),
+            avg_rating: mean(&acc.ratings),
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::NodeRun;
+    use crate::flows::{NodeType, Position};
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn executor(config: serde_json::Value) -> Node {
+        Node {
+            id: "e1".to_string(),
+            node_type: NodeType::Executor,
+            kind: "claude-code".to_string(),
+            config,
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Review".to_string(),
+        }
+    }
+
+    fn run(id: &str, variant: &str, status: RunStatus, cost: f64, rating: Option<u8>) -> FlowRun {
+        let started_at = Utc::now();
+        FlowRun {
+            id: id.to_string(),
+            flow_id: "f1".to_string(),
+            status,
+            started_at,
+            finished_at: Some(started_at),
+            node_runs: vec![NodeRun {
+                node_id: "e1".to_string(),
+                status,
+                started_at,
+                finished_at: Some(started_at + chrono::Duration::seconds(10)),
+                output_preview: None,
+                prompt_variant: Some(variant.to_string()),
+                cost_usd: Some(cost),
+                attempts: Vec::new(),
+                level: None,
+                ready_at: None,
+            }],
+            error: None,
+            output: None,
+            rating,
+            chat: None,
+            cost_usd: Some(cost),
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
+        }
+    }
+
+    #[test]
+    fn test_split_from_node() {
+        let node = executor(json!({ "prompt_ab": { "a": "review@1", "b": "review@2", "b_percent": 20 } }));
+        let split = PromptSplit::from_node(&node).unwrap();
+        assert_eq!(split.b_percent, 20);
+        assert!(PromptSplit::from_node(&executor(json!({ "prompt": "x" }))).is_none());
+        assert!(PromptSplit::from_node(&executor(json!({ "prompt_ab": { "a": "review@1" } }))).is_none());
+    }
+
+    #[test]
+    fn test_choose_is_deterministic_and_respects_split() {
+        let mut split = PromptSplit {
+            a: "a".to_string(),
+            b: "b".to_string(),
+            b_percent: 30,
+        };
+        let ids: Vec<String> = (0..2000).map(|i| format!("run-{i}")).collect();
+        let to_b = ids.iter().filter(|id| split.choose(id) == "b").count();
+        assert!((450..750).contains(&to_b), "got {to_b} of 2000");
+        assert_eq!(split.choose("run-7"), split.choose("run-7"));
+
+        split.b_percent = 0;
+        assert!(ids.iter().all(|id| split.choose(id) == "a"));
+        split.b_percent = 100;
+        assert!(ids.iter().all(|id| split.choose(id) == "b"));
+    }
+
+    #[test]
+    fn test_variant_stats() {
+        let runs = vec![
+            run("r1", "review@1", RunStatus::Success, 0.10, Some(3)),
+            run("r2", "review@1", RunStatus::Failed, 0.30, None),
+            run("r3", "review@2", RunStatus::Success, 0.05, Some(5)),
+        ];
+        let stats = variant_stats(&runs, "e1");
+        assert_eq!(stats.len(), 2);
+
+        let v1 = &stats[0];
+        assert_eq!(v1.prompt_ref, "review@1");
+        assert_eq!((v1.runs, v1.successes), (2, 1));
+        assert_eq!(v1.success_rate, 0.5);
+        assert!((v1.avg_cost_usd.unwrap() - 0.20).abs() < 1e-9);
+        assert_eq!(v1.avg_duration_secs, Some(10.0));
+        assert_eq!((v1.rated_runs, v1.avg_rating), (1, Some(3.0)));
+
+        assert_eq!(stats[1].avg_rating, Some(5.0));
+        assert!(variant_stats(&runs, "other").is_empty());
+    }
+}
@@ -7,9 +7,10 @@ use async_trait::async_trait;
 use chrono::Utc;
 use tokio::sync::RwLock;
 
-use super::Flow;
-use super::history::{FlowRun, NodeRun, RunStatus, MAX_RUNS_PER_FLOW};
-use super::repository::FlowRepository;
+use super::{Flow, NodeType};
+use super::history::{ChatMessage, FlowRun, NodeRun, RunChat, RunPage, RunQuery, RunStatus, MAX_RUNS_PER_FLOW};
+use super::repository::{FlowRepository, StaleRevision};
+use crate::tasks::executors::models::ModelAttempt;
 
 pub struct FileFlowRepository {
     base_dir: PathBuf,
@@ -42,6 +43,11 @@ impl FileFlowRepository {
         self.runs_dir().join(flow_id).join(format!("{run_id}.json"))
     }
 
+    /// Bytes the run's record takes on disk.
+    pub fn run_bytes(&self, flow_id: &str, run_id: &str) -> u64 {
+        std::fs::metadata(self.run_file(flow_id, run_id)).map_or(0, |m| m.len())
+    }
+
     pub fn attachments_dir(&self, flow_id: &str, node_id: &str) -> PathBuf {
         self.base_dir.join("attachments").join(flow_id).join(node_id)
     }
@@ -135,6 +141,15 @@ impl FlowRepository for FileFlowRepository {
     }
 
     async fn save_flow(&self, flow: Flow) -> Result<()> {
+        // Held through the write, so concurrent edits of one revision can't
+        // both pass the check
+        let mut flows = self.flows.write().await;
+        if let Some(current) = flows.get(&flow.id)
+            && flow.version <= current.version
+        {
+            return Err(StaleRevision { current: current.clone() }.into());
+        }
+
         let dir = self.flows_dir();
         std::fs::create_dir_all(&dir)
             .with_context(|| format!("failed to create flows dir: {}", dir.display()))?;
@@ -150,7 +165,7 @@ impl FlowRepository for FileFlowRepository {
         std::fs::rename(&tmp_path, &path)
             .with_context(|| format!("failed to rename flow file: {}", path.display()))?;
 
-        self.flows.write().await.insert(flow.id.clone(), flow);
+        flows.insert(flow.id.clone(), flow);
         Ok(())
     }
 
@@ -201,6 +216,23 @@ impl FlowRepository for FileFlowRepository {
             .unwrap_or_default()
     }
 
+    async fn query_runs(&self, query: &RunQuery) -> RunPage {
+        let flows = self.flows.read().await;
+        let runs = self.runs.read().await;
+        let trigger_kind = |flow_id: &str| {
+            let flow = flows.get(flow_id)?;
+            let trigger = flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger)?;
+            Some(trigger.kind.as_str())
+        };
+        let matched = runs
+            .iter()
+            .filter(|(flow_id, _)| query.flow_id.as_ref().is_none_or(|id| id == *flow_id))
+            .flat_map(|(flow_id, queue)| queue.iter().filter(|run| query.matches(run, trigger_kind(flow_id))))
+            .cloned()
+            .collect();
+        query.page(matched)
+    }
+
     async fn complete_run(
         &self,
         flow_id: &str,
@@ -216,13 +248,70 @@ impl FlowRepository for FileFlowRepository {
         .await
     }
 
+    async fn set_run_output(&self, flow_id: &str, run_id: &str, output: String) -> Result<()> {
+        self.mutate_run(flow_id, run_id, |r| {
+            r.output = Some(output);
+        })
+        .await
+    }
+
+    async fn set_run_rating(&self, flow_id: &str, run_id: &str, rating: u8) -> Result<()> {
+        self.mutate_run(flow_id, run_id, |r| {
+            r.rating = Some(rating);
+        })
+        .await
+    }
+
+    async fn set_run_replica(&self, flow_id: &str, run_id: &str, replica: Option<String>) -> Result<()> {
+        self.mutate_run(flow_id, run_id, |r| {
+            r.replica = replica;
+        })
+        .await
+    }
+
+    async fn delete_run(&self, flow_id: &str, run_id: &str) -> Result<bool> {
+        let mut runs = self.runs.write().await;
+        let Some(queue) = runs.get_mut(flow_id) else {
+            return Ok(false);
+        };
+        let before = queue.len();
+        queue.retain(|r| r.id != run_id);
+        if queue.len() == before {
+            return Ok(false);
+        }
+        let path = self.run_file(flow_id, run_id);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("failed to delete run file: {}", path.display()))?;
+        }
+        Ok(true)
+    }
+
+    async fn append_run_chat(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        session_id: &str,
+        messages: Vec<ChatMessage>,
+    ) -> Result<()> {
+        self.mutate_run(flow_id, run_id, |r| {
+            r.chat
+                .get_or_insert_with(|| RunChat { session_id: session_id.to_string(), messages: Vec::new() })
+                .messages
+                .extend(messages);
+        })
+        .await
+    }
+
     async fn push_node_run(
         &self,
         flow_id: &str,
         run_id: &str,
         node_run: NodeRun,
     ) -> Result<()> {
+        // A resumed run starts its unfinished nodes over
         self.mutate_run(flow_id, run_id, |r| {
+            r.node_runs.retain(|nr| nr.node_id != node_run.node_id);
             r.node_runs.push(node_run);
         })
         .await
@@ -247,6 +336,48 @@ impl FlowRepository for FileFlowRepository {
         .await
     }
 
+    async fn set_node_run_cost(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        cost_usd: f64,
+    ) -> Result<()> {
+        let node_id = node_id.to_string();
+        self.mutate_run(flow_id, run_id, |r| {
+            if let Some(nr) = r.node_runs.iter_mut().find(|nr| nr.node_id == node_id) {
+                nr.cost_usd = Some(cost_usd);
+            }
+            r.cost_usd = Some(r.node_runs.iter().filter_map(|nr| nr.cost_usd).sum());
+        })
+        .await
+    }
+
+    async fn set_node_run_attempts(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        attempts: Vec<ModelAttempt>,
+    ) -> Result<()> {
+        let node_id = node_id.to_string();
+        self.mutate_run(flow_id, run_id, |r| {
+            if let Some(nr) = r.node_runs.iter_mut().find(|nr| nr.node_id == node_id) {
+                nr.attempts = attempts;
+            }
+        })
+        .await
+    }
+
+    async fn reload_flow(&self, id: &str) -> bool {
+        let filename = format!("{id}.json");
+        if self.reload_file(&filename).await.is_some() {
+            return true;
+        }
+        self.evict_file(&filename).await;
+        false
+    }
+
     async fn load_all(&self) -> Result<()> {
         // Load flows
         let flows_dir = self.flows_dir();
@@ -342,6 +473,7 @@ impl FlowRepository for FileFlowRepository {
 mod tests {
     use super::*;
     use crate::flows::{Node, NodeType, Position};
+    use crate::flows::history::{ChatRole, RunCursor};
     use tempfile::tempdir;
 
     fn test_flow(id: &str, name: &str) -> Flow {
@@ -360,6 +492,8 @@ mod tests {
             }],
             edges: vec![],
             version: 0,
+            owners: vec![],
+            require_approval: false,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -374,6 +508,14 @@ mod tests {
             finished_at: None,
             node_runs: vec![],
             error: None,
+            output: None,
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
         }
     }
 
@@ -428,6 +570,86 @@ mod tests {
         assert!(!dir.path().join("runs").join("f1").exists());
     }
 
+    #[tokio::test]
+    async fn test_stale_save_rejected() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        repo.save_flow(test_flow("f1", "Flow 1")).await.unwrap();
+
+        // Two edits of revision 0: the first wins, the second is refused
+        let mut first = repo.get_flow("f1").await.unwrap();
+        let mut second = first.clone();
+        first.name = "From tab A".into();
+        first.version += 1;
+        second.name = "From tab B".into();
+        second.version += 1;
+        repo.save_flow(first).await.unwrap();
+        let err = repo.save_flow(second).await.unwrap_err();
+        let stale = err.downcast_ref::<StaleRevision>().unwrap();
+        assert_eq!((stale.current.name.as_str(), stale.current.version), ("From tab A", 1));
+        assert_eq!(repo.get_flow("f1").await.unwrap().name, "From tab A");
+    }
+
+    #[tokio::test]
+    async fn test_delete_run() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        repo.add_run(test_run("f1", "r1")).await.unwrap();
+        repo.add_run(test_run("f1", "r2")).await.unwrap();
+        assert!(repo.run_bytes("f1", "r1") > 0);
+
+        assert!(repo.delete_run("f1", "r1").await.unwrap());
+        assert!(!repo.delete_run("f1", "r1").await.unwrap());
+        let runs = repo.get_runs("f1", 100).await;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, "r2");
+        assert!(!dir.path().join("runs").join("f1").join("r1.json").exists());
+        assert_eq!(repo.run_bytes("f1", "r1"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_runs() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        repo.save_flow(test_flow("f1", "Flow 1")).await.unwrap();
+
+        let start = Utc::now() - chrono::Duration::hours(1);
+        for i in 0..5 {
+            let mut run = test_run(if i == 4 { "f2" } else { "f1" }, &format!("r{i}"));
+            run.started_at = start + chrono::Duration::minutes(i);
+            run.status = if i % 2 == 0 { RunStatus::Failed } else { RunStatus::Success };
+            repo.add_run(run).await.unwrap();
+        }
+        let ids = |page: &RunPage| page.runs.iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+
+        // Newest first across flows, two at a time
+        let mut query = RunQuery { limit: 2, ..RunQuery::default() };
+        let page = repo.query_runs(&query).await;
+        assert_eq!(ids(&page), ["r4", "r3"]);
+        query.cursor = RunCursor::parse(page.next_cursor.as_deref().unwrap());
+        let page = repo.query_runs(&query).await;
+        assert_eq!(ids(&page), ["r2", "r1"]);
+        query.cursor = RunCursor::parse(page.next_cursor.as_deref().unwrap());
+        let page = repo.query_runs(&query).await;
+        assert_eq!((ids(&page), page.next_cursor), (vec!["r0".to_string()], None));
+
+        // f2 has no flow, so no trigger kind to match
+        let query = RunQuery {
+            statuses: vec![RunStatus::Failed],
+            trigger: Some("cron".to_string()),
+            since: Some(start + chrono::Duration::minutes(1)),
+            oldest_first: true,
+            ..RunQuery::default()
+        };
+        assert_eq!(ids(&repo.query_runs(&query).await), ["r2"]);
+        let query = RunQuery { flow_id: Some("f2".to_string()), ..RunQuery::default() };
+        assert_eq!(ids(&repo.query_runs(&query).await), ["r4"]);
+    }
+
     // ── Run persistence ──────────────────────────────────────────
 
     #[tokio::test]
@@ -526,6 +748,47 @@ mod tests {
         assert_eq!(runs[0].status, RunStatus::Success);
     }
 
+    #[tokio::test]
+    async fn test_set_run_output() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        repo.add_run(test_run("f1", "r1")).await.unwrap();
+        repo.set_run_output("f1", "r1", "## Digest".to_string()).await.unwrap();
+        repo.set_run_rating("f1", "r1", 4).await.unwrap();
+
+        drop(repo);
+        let repo2 = FileFlowRepository::new(dir.path().to_path_buf());
+        repo2.load_all().await.unwrap();
+        let runs = repo2.get_runs("f1", 10).await;
+        assert_eq!(runs[0].output.as_deref(), Some("## Digest"));
+        assert_eq!(runs[0].rating, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_append_run_chat() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        repo.add_run(test_run("f1", "r1")).await.unwrap();
+        let message = |role, text: &str| ChatMessage { role, text: text.to_string(), at: Utc::now() };
+        repo.append_run_chat("f1", "r1", "s1", vec![message(ChatRole::User, "Why?"), message(ChatRole::Assistant, "Because.")])
+            .await
+            .unwrap();
+        // The session is kept from the first exchange
+        repo.append_run_chat("f1", "r1", "s2", vec![message(ChatRole::User, "Sure?")]).await.unwrap();
+
+        drop(repo);
+        let repo2 = FileFlowRepository::new(dir.path().to_path_buf());
+        repo2.load_all().await.unwrap();
+        let chat = repo2.get_runs("f1", 10).await[0].chat.clone().unwrap();
+        assert_eq!(chat.session_id, "s1");
+        assert_eq!(chat.messages.len(), 3);
+        assert_eq!(chat.messages[1].role, ChatRole::Assistant);
+    }
+
     #[tokio::test]
     async fn test_push_node_run() {
         let dir = tempdir().unwrap();
@@ -540,6 +803,11 @@ mod tests {
             started_at: Utc::now(),
             finished_at: None,
             output_preview: None,
+            prompt_variant: None,
+            cost_usd: None,
+            attempts: Vec::new(),
+            level: None,
+            ready_at: None,
         };
         repo.push_node_run("f1", "r1", nr).await.unwrap();
 
@@ -562,12 +830,18 @@ mod tests {
             started_at: Utc::now(),
             finished_at: None,
             output_preview: None,
+            prompt_variant: None,
+            cost_usd: None,
+            attempts: Vec::new(),
+            level: None,
+            ready_at: None,
         };
         repo.push_node_run("f1", "r1", nr).await.unwrap();
         repo
             .complete_node_run("f1", "r1", "n1", RunStatus::Success, Some("done".to_string()))
             .await
             .unwrap();
+        repo.set_node_run_cost("f1", "r1", "n1", 0.12).await.unwrap();
 
         let runs = repo.get_runs("f1", 10).await;
         assert_eq!(runs[0].node_runs[0].status, RunStatus::Success);
@@ -583,6 +857,7 @@ mod tests {
         repo2.load_all().await.unwrap();
         let runs = repo2.get_runs("f1", 10).await;
         assert_eq!(runs[0].node_runs[0].status, RunStatus::Success);
+        assert_eq!(runs[0].node_runs[0].cost_usd, Some(0.12));
     }
 
     #[tokio::test]
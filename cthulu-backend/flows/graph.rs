@@ -1,10 +1,12 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
+use serde::{Deserialize, Serialize};
+
 use crate::tasks::executors::ExecutionResult;
 use crate::tasks::sources::ContentItem;
 
 /// Unified output type for all node types in the DAG.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NodeOutput {
     /// Content items from sources/filters.
     Items(Vec<ContentItem>),
@@ -222,6 +224,72 @@ pub fn compute_levels(
     levels
 }
 
+/// The nodes each `for-each` node runs per item, in `sorted` order and keyed
+/// by the loop node: everything reachable through its `each` edges. They
+/// are left out of the run's levels. Body nodes may only take input from
+/// their loop and each other, nodes after the loop can't also read from its
+/// body, and loops don't nest.
+pub fn loop_bodies(
+    nodes: &[crate::flows::Node],
+    edges: &[crate::flows::Edge],
+    sorted: &[String],
+) -> anyhow::Result<HashMap<String, Vec<String>>> {
+    use crate::flows::loops::{LOOP_KIND, is_each_edge};
+
+    let (children, _) = build_adjacency(nodes, edges);
+    let labels: HashMap<&str, &str> = nodes.iter().map(|n| (n.id.as_str(), n.label.as_str())).collect();
+    let edges: Vec<_> = edges
+        .iter()
+        .filter(|e| labels.contains_key(e.source.as_str()) && labels.contains_key(e.target.as_str()))
+        .collect();
+
+    let mut bodies = HashMap::new();
+    for node in nodes.iter().filter(|n| n.kind == LOOP_KIND) {
+        let mut body: HashSet<&str> = HashSet::new();
+        let mut stack: Vec<&str> = edges
+            .iter()
+            .filter(|e| is_each_edge(e, &node.id))
+            .map(|e| e.target.as_str())
+            .collect();
+        while let Some(id) = stack.pop() {
+            if body.insert(id) {
+                stack.extend(children.get(id).into_iter().flatten().map(String::as_str));
+            }
+        }
+        if body.is_empty() {
+            continue;
+        }
+
+        for edge in &edges {
+            let to = body.contains(edge.target.as_str());
+            if to && !body.contains(edge.source.as_str()) && edge.source != node.id {
+                anyhow::bail!(
+                    "'{}' in the loop body of '{}' takes input from '{}' outside it",
+                    labels[edge.target.as_str()],
+                    node.label,
+                    labels[edge.source.as_str()]
+                );
+            }
+            // Everything reachable from the body is in it, so a node reading
+            // the loop's merged output and the body shows up as both
+            if to && edge.source == node.id && !is_each_edge(edge, &node.id) {
+                anyhow::bail!(
+                    "'{}' reads both the output of loop '{}' and nodes inside its loop body",
+                    labels[edge.target.as_str()],
+                    node.label
+                );
+            }
+        }
+        if let Some(inner) = nodes.iter().find(|n| n.kind == LOOP_KIND && body.contains(n.id.as_str())) {
+            anyhow::bail!("loop '{}' is inside the loop body of '{}'; loops can't nest", inner.label, node.label);
+        }
+
+        let ordered = sorted.iter().filter(|id| body.contains(id.as_str())).cloned().collect();
+        bodies.insert(node.id.clone(), ordered);
+    }
+    Ok(bodies)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,6 +311,8 @@ mod tests {
             id: format!("{source}->{target}"),
             source: source.to_string(),
             target: target.to_string(),
+            label: None,
+            condition: None,
         }
     }
 
@@ -388,6 +458,7 @@ mod tests {
             summary: String::new(),
             published: None,
             image_url: None,
+            full_summary: false,
         };
         let item2 = ContentItem {
             title: "B".to_string(),
@@ -395,6 +466,7 @@ mod tests {
             summary: String::new(),
             published: None,
             image_url: None,
+            full_summary: false,
         };
 
         let result = NodeOutput::merge(vec![
@@ -476,4 +548,36 @@ mod tests {
         assert_eq!(parents["b"], vec!["a"]);
         assert_eq!(parents["c"], vec!["b"]);
     }
+
+    #[test]
+    fn test_loop_bodies() {
+        let mut each = make_node("each", NodeType::Executor);
+        each.kind = crate::flows::loops::LOOP_KIND.to_string();
+        let nodes = vec![
+            make_node("s", NodeType::Source),
+            each,
+            make_node("classify", NodeType::Executor),
+            make_node("post", NodeType::Sink),
+            make_node("digest", NodeType::Sink),
+        ];
+        let into_body = Edge { label: Some("each".into()), ..make_edge("each", "classify") };
+        let mut edges = vec![
+            make_edge("s", "each"),
+            into_body,
+            make_edge("classify", "post"),
+            make_edge("each", "digest"),
+        ];
+        let sorted = topo_sort(&nodes, &edges).unwrap();
+
+        let bodies = loop_bodies(&nodes, &edges, &sorted).unwrap();
+        assert_eq!(bodies["each"], vec!["classify", "post"]);
+
+        // Reading a body node from outside the loop
+        edges.push(make_edge("classify", "digest"));
+        assert!(loop_bodies(&nodes, &edges, &sorted).is_err());
+        edges.pop();
+        // Feeding a body node from outside the loop
+        edges.push(make_edge("s", "classify"));
+        assert!(loop_bodies(&nodes, &edges, &sorted).is_err());
+    }
 }
@@ -1,6 +1,10 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::tasks::executors::models::ModelAttempt;
+
 pub const MAX_RUNS_PER_FLOW: usize = 100;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -9,6 +13,8 @@ pub enum RunStatus {
     Running,
     Success,
     Failed,
+    /// The server stopped while the run (or node) was still going.
+    Interrupted,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +26,54 @@ pub struct FlowRun {
     pub finished_at: Option<DateTime<Utc>>,
     pub node_runs: Vec<NodeRun>,
     pub error: Option<String>,
+    /// Full text produced by the flow's executor nodes, for the outputs feed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Manual 1–5 quality score, used to compare prompt A/B variants.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+    /// Follow-up conversation about the run's output (`POST /api/flows/{id}/chat`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat: Option<RunChat>,
+    /// Total cost of the run's executor nodes so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// The context the run was started with, kept only when its trigger
+    /// sets `on_interrupt: rerun`, so a run cut short can start over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<HashMap<String, String>>,
+    /// The interrupted run this one restarts, or the failed run it resumes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<String>,
+    /// Run with executors echoing their prompts and sinks captured, not
+    /// delivered; doesn't count as the flow's last run.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// The replica running it, when clustered, so a new scheduling replica
+    /// leaves the runs of replicas still up alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replica: Option<String>,
+}
+
+/// A Claude session seeded with a run's output, resumed for each follow-up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunChat {
+    pub session_id: String,
+    pub messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub text: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatRole {
+    User,
+    Assistant,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,4 +83,114 @@ pub struct NodeRun {
     pub started_at: DateTime<Utc>,
     pub finished_at: Option<DateTime<Utc>>,
     pub output_preview: Option<String>,
+    /// `prompt_ref` used by an executor node running a `prompt_ab` split.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_variant: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// Each model an executor with `fallback_models` tried, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<ModelAttempt>,
+    /// Index of the run level the node ran in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<usize>,
+    /// When the node's last input was ready; it waited from then until
+    /// `started_at` for a free slot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ready_at: Option<DateTime<Utc>>,
+}
+
+/// Which runs to list, in what order, from where.
+#[derive(Debug, Clone)]
+pub struct RunQuery {
+    pub flow_id: Option<String>,
+    /// Any of these; every status when empty.
+    pub statuses: Vec<RunStatus>,
+    /// Started at or after.
+    pub since: Option<DateTime<Utc>>,
+    /// Started before.
+    pub until: Option<DateTime<Utc>>,
+    /// The kind of the flow's trigger, e.g. `cron` or `github-pr`.
+    pub trigger: Option<String>,
+    pub oldest_first: bool,
+    pub limit: usize,
+    /// `next_cursor` of the page before.
+    pub cursor: Option<RunCursor>,
+}
+
+impl Default for RunQuery {
+    fn default() -> Self {
+        Self {
+            flow_id: None,
+            statuses: Vec::new(),
+            since: None,
+            until: None,
+            trigger: None,
+            oldest_first: false,
+            limit: MAX_RUNS_PER_FLOW,
+            cursor: None,
+        }
+    }
+}
+
+/// Where a page ended: the last run's start and id, which break ties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunCursor {
+    pub started_at: DateTime<Utc>,
+    pub run_id: String,
+}
+
+impl RunCursor {
+    /// `{microseconds}.{run id}`, as `encode` writes it.
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let (micros, run_id) = cursor.split_once('.')?;
+        let started_at = DateTime::from_timestamp_micros(micros.parse().ok()?)?;
+        Some(Self { started_at, run_id: run_id.to_string() })
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}.{}", self.started_at.timestamp_micros(), self.run_id)
+    }
+
+    fn of(run: &FlowRun) -> Self {
+        Self { started_at: run.started_at, run_id: run.id.clone() }
+    }
+}
+
+/// One page of runs, and the cursor for the next if there are more.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunPage {
+    pub runs: Vec<FlowRun>,
+    pub next_cursor: Option<String>,
+}
+
+impl RunQuery {
+    /// Whether `run`, of a flow triggered by `trigger_kind`, passes the filters.
+    pub fn matches(&self, run: &FlowRun, trigger_kind: Option<&str>) -> bool {
+        self.flow_id.as_ref().is_none_or(|id| *id == run.flow_id)
+            && (self.statuses.is_empty() || self.statuses.contains(&run.status))
+            && self.since.is_none_or(|since| run.started_at >= since)
+            && self.until.is_none_or(|until| run.started_at < until)
+            && self.trigger.as_deref().is_none_or(|t| trigger_kind == Some(t))
+    }
+
+    /// Sort the matching `runs` and take the page after the cursor.
+    pub fn page(&self, mut runs: Vec<FlowRun>) -> RunPage {
+        let key = |run: &FlowRun| (run.started_at, run.id.clone());
+        runs.sort_by_key(key);
+        if !self.oldest_first {
+            runs.reverse();
+        }
+        if let Some(cursor) = &self.cursor {
+            let after = (cursor.started_at, cursor.run_id.clone());
+            runs.retain(|run| match self.oldest_first {
+                true => key(run) > after,
+                false => key(run) < after,
+            });
+        }
+        let more = runs.len() > self.limit;
+        runs.truncate(self.limit);
+        let next_cursor = runs.last().filter(|_| more).map(|run| RunCursor::of(run).encode());
+        RunPage { runs, next_cursor }
+    }
 }
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::flows::{Flow, NodeType};
+
+/// A run parameter declared under the trigger's `inputs` config, e.g.
+/// `{ name: topic, type: string, required: true }`. Values reach executor
+/// prompts as `{{name}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputSpec {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: InputType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Allowed values for a `string` input.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputType {
+    #[default]
+    String,
+    Number,
+    Boolean,
+}
+
+/// The inputs declared on `flow`'s trigger node (empty if none).
+pub fn declared_inputs(flow: &Flow) -> Result<Vec<InputSpec>> {
+    let Some(trigger) = flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) else {
+        return Ok(Vec::new());
+    };
+    match &trigger.config["inputs"] {
+        Value::Null => Ok(Vec::new()),
+        inputs => serde_json::from_value(inputs.clone()).context("invalid trigger 'inputs'"),
+    }
+}
+
+/// Check `provided` against `specs` and return the prompt variables for the
+/// run, with defaults filled in. Collects every problem rather than stopping
+/// at the first, so a form can show them all at once.
+pub fn resolve_inputs(
+    specs: &[InputSpec],
+    provided: &serde_json::Map<String, Value>,
+) -> std::result::Result<HashMap<String, String>, Vec<String>> {
+    let mut errors: Vec<String> = provided
+        .keys()
+        .filter(|name| !specs.iter().any(|s| &s.name == *name))
+        .map(|name| format!("unknown input '{name}'"))
+        .collect();
+
+    let mut vars = HashMap::new();
+    for spec in specs {
+        let value = match provided.get(&spec.name).filter(|v| !v.is_null()) {
+            Some(value) => value,
+            None => match &spec.default {
+                Some(default) => default,
+                None if spec.required => {
+                    errors.push(format!("missing required input '{}'", spec.name));
+                    continue;
+                }
+                None => continue,
+            },
+        };
+        match to_var(spec, value) {
+            Ok(var) => {
+                vars.insert(spec.name.clone(), var);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if errors.is_empty() { Ok(vars) } else { Err(errors) }
+}
+
+/// Prompt variables from the declared defaults alone, for runs started
+/// without inputs (cron, webhooks, the plain trigger endpoint).
+pub fn default_vars(flow: &Flow) -> HashMap<String, String> {
+    let Ok(specs) = declared_inputs(flow) else {
+        return HashMap::new();
+    };
+    specs
+        .iter()
+        .filter_map(|spec| {
+            let var = to_var(spec, spec.default.as_ref()?).ok()?;
+            Some((spec.name.clone(), var))
+        })
+        .collect()
+}
+
+/// Typed values from form fields (which are all strings), for
+/// [`resolve_inputs`]. Values that don't parse as their declared type stay
+/// strings so the type error is reported there.
+pub fn from_form(specs: &[InputSpec], fields: Vec<(String, String)>) -> serde_json::Map<String, Value> {
+    fields
+        .into_iter()
+        .map(|(name, raw)| {
+            let kind = specs.iter().find(|s| s.name == name).map(|s| s.kind);
+            let value = match kind {
+                Some(InputType::Number) => raw
+                    .trim()
+                    .parse::<serde_json::Number>()
+                    .map(Value::Number)
+                    .unwrap_or(Value::String(raw)),
+                Some(InputType::Boolean) => match raw.trim().to_ascii_lowercase().as_str() {
+                    "true" | "on" | "1" | "yes" => Value::Bool(true),
+                    "false" | "off" | "0" | "no" => Value::Bool(false),
+                    _ => Value::String(raw),
+                },
+                _ => Value::String(raw),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+fn to_var(spec: &InputSpec, value: &Value) -> std::result::Result<String, String> {
+    let var = match (spec.kind, value) {
+        (InputType::String, Value::String(s)) => s.clone(),
+        (InputType::Number, Value::Number(n)) => n.to_string(),
+        (InputType::Boolean, Value::Bool(b)) => b.to_string(),
+        (kind, _) => {
+            return Err(format!("input '{}' must be a {}", spec.name, type_name(kind)));
+        }
+    };
+    if !spec.options.is_empty() && !spec.options.contains(&var) {
+        return Err(format!(
+            "input '{}' must be one of: {}",
+            spec.name,
+            spec.options.join(", ")
+        ));
+    }
+    Ok(var)
+}
+
+fn type_name(kind: InputType) -> &'static str {
+    match kind {
+        InputType::String => "string",
+        InputType::Number => "number",
+        InputType::Boolean => "boolean",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn specs() -> Vec<InputSpec> {
+        serde_json::from_value(json!([
+            { "name": "topic", "required": true },
+            { "name": "depth", "type": "string", "options": ["brief", "deep"], "default": "brief" },
+            { "name": "limit", "type": "number" },
+            { "name": "dry_run", "type": "boolean", "default": false }
+        ]))
+        .unwrap()
+    }
+
+    fn map(value: Value) -> serde_json::Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_resolve_inputs_fills_defaults() {
+        let vars = resolve_inputs(&specs(), &map(json!({ "topic": "rust", "limit": 5 }))).unwrap();
+        assert_eq!(vars["topic"], "rust");
+        assert_eq!(vars["depth"], "brief");
+        assert_eq!(vars["limit"], "5");
+        assert_eq!(vars["dry_run"], "false");
+    }
+
+    #[test]
+    fn test_resolve_inputs_reports_every_error() {
+        let errors = resolve_inputs(
+            &specs(),
+            &map(json!({ "depth": "huge", "limit": "five", "colour": "red" })),
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                "unknown input 'colour'",
+                "missing required input 'topic'",
+                "input 'depth' must be one of: brief, deep",
+                "input 'limit' must be a number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_form_parses_declared_types() {
+        let fields = [("topic", "rust"), ("limit", "5"), ("dry_run", "on"), ("depth", "x")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let provided = from_form(&specs(), fields);
+        assert_eq!(provided["limit"], json!(5));
+        assert_eq!(provided["dry_run"], json!(true));
+
+        let errors = resolve_inputs(&specs(), &provided).unwrap_err();
+        assert_eq!(errors, ["input 'depth' must be one of: brief, deep"]);
+
+        let provided = from_form(&specs(), vec![("limit".into(), "many".into())]);
+        assert_eq!(provided["limit"], json!("many"));
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::egress::AuditedSend;
+use crate::flows::{Flow, NodeType};
+
+const DEFAULT_PER_MINUTE: u32 = 5;
+const DEFAULT_PER_HOUR: u32 = 100;
+const DEFAULT_MAX_BYTES: usize = 4096;
+
+/// Form fields the captcha widgets submit their token in (Turnstile,
+/// hCaptcha, reCAPTCHA); JSON bodies use `captcha_token`.
+pub const CAPTCHA_FIELDS: [&str; 4] = [
+    "captcha_token",
+    "cf-turnstile-response",
+    "h-captcha-response",
+    "g-recaptcha-response",
+];
+
+/// A trigger's `public_intake`: lets anyone submit a small payload that
+/// becomes the run's inputs, e.g. a "request a review" form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntakeConfig {
+    /// Submissions per client IP per minute.
+    pub per_minute: u32,
+    /// Submissions from everyone together per hour.
+    pub per_hour: u32,
+    pub max_bytes: usize,
+    pub captcha: Option<Captcha>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptchaProvider {
+    Turnstile,
+    HCaptcha,
+    ReCaptcha,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Captcha {
+    pub provider: CaptchaProvider,
+    /// Env var holding the provider's secret key.
+    pub secret_env: String,
+}
+
+impl IntakeConfig {
+    /// The intake on `flow`'s trigger, or `None` when it has none.
+    pub fn for_flow(flow: &Flow) -> Result<Option<Self>> {
+        match flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) {
+            Some(trigger) => Self::from_trigger(&trigger.config),
+            None => Ok(None),
+        }
+    }
+
+    pub fn from_trigger(config: &Value) -> Result<Option<Self>> {
+        let intake = &config["public_intake"];
+        if intake.is_null() || intake["enabled"] == false {
+            return Ok(None);
+        }
+        let limit = |key: &str, default: u32| -> Result<u32> {
+            match &intake[key] {
+                Value::Null => Ok(default),
+                value => value
+                    .as_u64()
+                    .and_then(|n| u32::try_from(n).ok())
+                    .filter(|n| *n > 0)
+                    .with_context(|| format!("public_intake.{key} must be a positive number")),
+            }
+        };
+
+        let captcha = match &intake["captcha"] {
+            Value::Null => None,
+            captcha => {
+                let provider = match captcha["provider"].as_str().unwrap_or("turnstile") {
+                    "turnstile" => CaptchaProvider::Turnstile,
+                    "hcaptcha" => CaptchaProvider::HCaptcha,
+                    "recaptcha" => CaptchaProvider::ReCaptcha,
+                    other => anyhow::bail!(
+                        "public_intake.captcha.provider must be turnstile, hcaptcha or recaptcha, got '{other}'"
+                    ),
+                };
+                let secret_env = captcha["secret_env"]
+                    .as_str()
+                    .filter(|s| !s.is_empty())
+                    .context("public_intake.captcha requires secret_env")?;
+                Some(Captcha { provider, secret_env: secret_env.to_string() })
+            }
+        };
+
+        Ok(Some(Self {
+            per_minute: limit("per_minute", DEFAULT_PER_MINUTE)?,
+            per_hour: limit("per_hour", DEFAULT_PER_HOUR)?,
+            max_bytes: intake["max_bytes"]
+                .as_u64()
+                .map_or(DEFAULT_MAX_BYTES, |n| n as usize),
+            captcha,
+        }))
+    }
+}
+
+impl Captcha {
+    fn verify_url(&self) -> &'static str {
+        match self.provider {
+            CaptchaProvider::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+            CaptchaProvider::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            CaptchaProvider::ReCaptcha => "https://www.google.com/recaptcha/api/siteverify",
+        }
+    }
+
+    /// Whether the provider accepts `token` as solved by `client_ip`.
+    pub async fn verify(&self, http_client: &reqwest::Client, token: &str, client_ip: &str) -> Result<bool> {
+        let secret = crate::secrets::var(&self.secret_env)
+            .with_context(|| format!("env var {} not set", self.secret_env))?;
+        let response: Value = http_client
+            .post(self.verify_url())
+            .timeout(Duration::from_secs(10))
+            .form(&[("secret", secret.as_str()), ("response", token), ("remoteip", client_ip)])
+            .send_audited()
+            .await
+            .context("captcha verification request failed")?
+            .error_for_status()
+            .context("captcha verification failed")?
+            .json()
+            .await
+            .context("invalid captcha verification response")?;
+        Ok(response["success"] == true)
+    }
+}
+
+/// A key's window and its hits within it.
+type Window = (Duration, VecDeque<Instant>);
+
+/// Sliding-window counts of recent submissions, shared by every intake
+/// endpoint. Kept in memory: a restart forgets them.
+#[derive(Clone, Default)]
+pub struct IntakeLimiter {
+    hits: Arc<Mutex<HashMap<String, Window>>>,
+    /// Reverse proxies whose `X-Forwarded-For` is believed.
+    trusted_proxies: Arc<Vec<IpAddr>>,
+}
+
+impl IntakeLimiter {
+    pub fn from_env() -> Self {
+        Self::from_raw_values(std::env::var("CTHULU_TRUSTED_PROXIES").ok().as_deref())
+    }
+
+    /// Used directly in tests to avoid mutating process-global environment.
+    pub fn from_raw_values(trusted_proxies: Option<&str>) -> Self {
+        let trusted_proxies = trusted_proxies
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+            .filter_map(|ip| match ip.parse() {
+                Ok(ip) => Some(ip),
+                Err(_) => {
+                    tracing::warn!(ip, "Ignoring invalid CTHULU_TRUSTED_PROXIES entry");
+                    None
+                }
+            })
+            .collect();
+        Self { hits: Arc::default(), trusted_proxies: Arc::new(trusted_proxies) }
+    }
+
+    /// The submitting client: the peer address or, when that's a trusted
+    /// proxy, the right-most `X-Forwarded-For` hop that isn't one. Hops
+    /// left of that are whatever the client claimed.
+    pub fn client_ip(&self, forwarded_for: Option<&str>, peer: Option<IpAddr>) -> String {
+        let Some(mut client) = peer else {
+            return "unknown".to_string();
+        };
+        let hops = forwarded_for.unwrap_or_default().split(',').map(str::trim).rev();
+        for hop in hops {
+            if !self.trusted_proxies.contains(&client) {
+                break;
+            }
+            match hop.parse() {
+                Ok(ip) => client = ip,
+                Err(_) => break,
+            }
+        }
+        client.to_string()
+    }
+
+    /// Record a hit against every `(key, limit, window)` if none of them is
+    /// full; otherwise record nothing and return how long until the first
+    /// full one frees up. Keys without a hit in their window are dropped,
+    /// so one-off clients don't pile up.
+    pub fn check(&self, limits: &[(String, u32, Duration)], now: Instant) -> Result<(), Duration> {
+        let mut hits = self.hits.lock().unwrap();
+        hits.retain(|_, (window, times)| {
+            while times.front().is_some_and(|t| now.duration_since(*t) >= *window) {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+        for (key, limit, window) in limits {
+            if let Some((_, times)) = hits.get(key)
+                && times.len() >= *limit as usize
+            {
+                return Err(*window - now.duration_since(times[0]));
+            }
+        }
+        for (key, _, window) in limits {
+            hits.entry(key.clone()).or_insert_with(|| (*window, VecDeque::new())).1.push_back(now);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_intake_config() {
+        assert_eq!(IntakeConfig::from_trigger(&json!({})).unwrap(), None);
+        assert_eq!(
+            IntakeConfig::from_trigger(&json!({ "public_intake": { "enabled": false } })).unwrap(),
+            None
+        );
+
+        let config = IntakeConfig::from_trigger(&json!({ "public_intake": {} })).unwrap().unwrap();
+        assert_eq!(config.per_minute, DEFAULT_PER_MINUTE);
+        assert_eq!(config.captcha, None);
+
+        let config = IntakeConfig::from_trigger(&json!({
+            "public_intake": { "per_minute": 2, "captcha": { "provider": "hcaptcha", "secret_env": "HCAPTCHA_SECRET" } }
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.per_minute, 2);
+        assert_eq!(config.captcha.unwrap().provider, CaptchaProvider::HCaptcha);
+
+        assert!(IntakeConfig::from_trigger(&json!({ "public_intake": { "per_hour": 0 } })).is_err());
+        assert!(IntakeConfig::from_trigger(&json!({ "public_intake": { "captcha": {} } })).is_err());
+    }
+
+    #[test]
+    fn test_limiter_counts_every_key_or_none() {
+        let limiter = IntakeLimiter::default();
+        let start = Instant::now();
+        let minute = Duration::from_secs(60);
+        let limits = |client: &str| {
+            vec![
+                (format!("f:{client}"), 2, minute),
+                ("f".to_string(), 3, Duration::from_secs(3600)),
+            ]
+        };
+
+        assert!(limiter.check(&limits("a"), start).is_ok());
+        assert!(limiter.check(&limits("a"), start).is_ok());
+        // Client a is out for the minute; that attempt doesn't count against the flow
+        let retry = limiter.check(&limits("a"), start + Duration::from_secs(15)).unwrap_err();
+        assert_eq!(retry, Duration::from_secs(45));
+        assert!(limiter.check(&limits("b"), start).is_ok());
+        // ...but the flow's hourly budget is now spent, for everyone
+        assert!(limiter.check(&limits("c"), start).is_err());
+        assert!(limiter.check(&limits("a"), start + minute).is_err());
+
+        // Clients gone quiet are forgotten
+        assert!(limiter.check(&limits("d"), start + Duration::from_secs(3600)).is_ok());
+        assert_eq!(limiter.hits.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        let direct = IntakeLimiter::default();
+        assert_eq!(direct.client_ip(None, Some(peer)), "10.0.0.1");
+        // Not behind a trusted proxy, the header is the client's own say-so
+        assert_eq!(direct.client_ip(Some("203.0.113.7"), Some(peer)), "10.0.0.1");
+        assert_eq!(direct.client_ip(None, None), "unknown");
+
+        let proxied = IntakeLimiter::from_raw_values(Some("10.0.0.1, 10.0.0.2, bogus"));
+        assert_eq!(proxied.trusted_proxies.len(), 2);
+        assert_eq!(proxied.client_ip(Some("203.0.113.7"), Some(peer)), "203.0.113.7");
+        // Spoofed hops left of the real client are ignored
+        assert_eq!(proxied.client_ip(Some("1.2.3.4, 203.0.113.7, 10.0.0.2"), Some(peer)), "203.0.113.7");
+        assert_eq!(proxied.client_ip(None, Some(peer)), "10.0.0.1");
+    }
+}
@@ -0,0 +1,306 @@
+//! Leader election, for running several replicas behind a load balancer.
+//!
+//! With `CLUSTER_DATABASE_URL` set, replicas (sharing `~/.cthulu`) contend
+//! for a lease row in Postgres; the holder is the only one running
+//! triggers, and renews the lease every third of `CLUSTER_LEASE_SECS`. When
+//! it stops — or dies and its lease runs out — another replica takes over,
+//! settling interrupted runs and starting every trigger, so the service can
+//! be redeployed one replica at a time. Every replica serves the API and
+//! webhooks; a trigger edited through a standby replica is passed to the
+//! leader through `cthulu_trigger_changes`, which it polls on every renewal.
+//!
+//! Standby replicas still run the flows started through the API, so each
+//! replica also holds a lease of its own while it's up, and records itself
+//! on the runs it starts. The leader only settles the runs of replicas
+//! whose lease is gone: on taking over, and whenever a replica drops out.
+
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+use super::scheduler::FlowScheduler;
+
+/// The lease the scheduling replica holds.
+const SCHEDULER_LEASE: &str = "scheduler";
+
+/// Prefix of the lease each replica holds while it's up.
+const REPLICA_LEASE_PREFIX: &str = "replica/";
+
+static REPLICA_ID: OnceLock<String> = OnceLock::new();
+
+/// This replica's id, recorded on the runs it starts; `None` unless
+/// clustered.
+pub fn replica_id() -> Option<String> {
+    REPLICA_ID.get().cloned()
+}
+
+const DEFAULT_LEASE_SECS: u64 = 30;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cthulu_leases (
+    name TEXT PRIMARY KEY,
+    holder TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+CREATE TABLE IF NOT EXISTS cthulu_trigger_changes (
+    seq BIGSERIAL PRIMARY KEY,
+    flow_id TEXT NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coordination {
+    pub database_url: String,
+    /// Who this replica is in the lease table.
+    pub replica_id: String,
+    pub lease: Duration,
+}
+
+impl Coordination {
+    /// `None` (a single server) unless `CLUSTER_DATABASE_URL` is set.
+    pub fn from_env() -> Option<Self> {
+        let env = |key: &str| std::env::var(key).ok();
+        Self::from_raw_values(
+            env("CLUSTER_DATABASE_URL").as_deref(),
+            env("CLUSTER_REPLICA_ID").or_else(|| env("HOSTNAME")).as_deref(),
+            env("CLUSTER_LEASE_SECS").as_deref(),
+        )
+    }
+
+    /// Used directly in tests to avoid mutating process-global environment.
+    pub fn from_raw_values(database_url: Option<&str>, replica_id: Option<&str>, lease_secs: Option<&str>) -> Option<Self> {
+        let database_url = database_url.map(str::trim).filter(|u| !u.is_empty())?;
+        let host = replica_id.map(str::trim).filter(|id| !id.is_empty()).unwrap_or("cthulu");
+        let lease_secs = lease_secs
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|n| *n >= 3)
+            .unwrap_or(DEFAULT_LEASE_SECS);
+        Some(Self {
+            database_url: database_url.to_string(),
+            // A restarted replica is someone new, and mustn't pick up its
+            // previous life's lease as though it were still leading
+            replica_id: format!("{host}-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            lease: Duration::from_secs(lease_secs),
+        })
+    }
+}
+
+/// Named, expiring leases in Postgres.
+pub struct LeaseTable {
+    pool: PgPool,
+    holder: String,
+    lease: Duration,
+}
+
+impl LeaseTable {
+    pub async fn connect(coordination: &Coordination) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&coordination.database_url)
+            .await
+            .context("failed to connect to the cluster database")?;
+        sqlx::raw_sql(SCHEMA)
+            .execute(&pool)
+            .await
+            .context("failed to create the lease table")?;
+        let _ = REPLICA_ID.set(coordination.replica_id.clone());
+        Ok(Self { pool, holder: coordination.replica_id.clone(), lease: coordination.lease })
+    }
+
+    /// Take lease `name`, or renew it if already held; `false` while
+    /// another replica holds it.
+    pub async fn try_acquire(&self, name: &str) -> Result<bool> {
+        let held = sqlx::query(
+            "INSERT INTO cthulu_leases (name, holder, expires_at) \
+             VALUES ($1, $2, now() + make_interval(secs => $3)) \
+             ON CONFLICT (name) DO UPDATE SET holder = EXCLUDED.holder, expires_at = EXCLUDED.expires_at \
+             WHERE cthulu_leases.holder = EXCLUDED.holder OR cthulu_leases.expires_at < now() \
+             RETURNING name",
+        )
+        .bind(name)
+        .bind(&self.holder)
+        .bind(self.lease.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await
+        .with_context(|| format!("failed to acquire lease {name}"))?;
+        Ok(held.is_some())
+    }
+
+    /// Give up lease `name`, if held, for another replica to take at once.
+    pub async fn release(&self, name: &str) -> Result<()> {
+        sqlx::query("DELETE FROM cthulu_leases WHERE name = $1 AND holder = $2")
+            .bind(name)
+            .bind(&self.holder)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("failed to release lease {name}"))?;
+        Ok(())
+    }
+
+    /// Hand over scheduling on shutdown, and let the leader settle this
+    /// replica's runs.
+    pub async fn release_scheduler(&self) {
+        if let Err(e) = self.release(SCHEDULER_LEASE).await {
+            tracing::warn!(error = %e, "Failed to release the scheduler lease");
+        }
+        if let Err(e) = self.release(&self.replica_lease()).await {
+            tracing::warn!(error = %e, "Failed to release the replica lease");
+        }
+    }
+
+    fn replica_lease(&self) -> String {
+        format!("{REPLICA_LEASE_PREFIX}{}", self.holder)
+    }
+
+    /// The replicas whose lease hasn't run out, this one included once it
+    /// has renewed its own.
+    pub async fn live_replicas(&self) -> Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT holder FROM cthulu_leases WHERE starts_with(name, $1) AND expires_at >= now() ORDER BY holder",
+        )
+        .bind(REPLICA_LEASE_PREFIX)
+        .fetch_all(&self.pool)
+        .await
+        .context("failed to read replica leases")?;
+        Ok(rows.into_iter().map(|(holder,)| holder).collect())
+    }
+
+    /// Tell the scheduling replica `flow_id`'s trigger changed, or the flow
+    /// was deleted.
+    pub async fn publish_trigger_change(&self, flow_id: &str) -> Result<()> {
+        sqlx::query("INSERT INTO cthulu_trigger_changes (flow_id) VALUES ($1)")
+            .bind(flow_id)
+            .execute(&self.pool)
+            .await
+            .context("failed to publish trigger change")?;
+        // A new leader starts every trigger anyway, so old changes are moot
+        sqlx::query("DELETE FROM cthulu_trigger_changes WHERE changed_at < now() - interval '1 day'")
+            .execute(&self.pool)
+            .await
+            .context("failed to prune trigger changes")?;
+        Ok(())
+    }
+
+    /// The latest published change, 0 before the first.
+    pub async fn latest_trigger_change(&self) -> Result<i64> {
+        let (seq,): (Option<i64>,) = sqlx::query_as("SELECT max(seq) FROM cthulu_trigger_changes")
+            .fetch_one(&self.pool)
+            .await
+            .context("failed to read trigger changes")?;
+        Ok(seq.unwrap_or(0))
+    }
+
+    /// Changes published after `seq`, oldest first, as `(seq, flow_id)`.
+    pub async fn trigger_changes_since(&self, seq: i64) -> Result<Vec<(i64, String)>> {
+        sqlx::query_as("SELECT seq, flow_id FROM cthulu_trigger_changes WHERE seq > $1 ORDER BY seq")
+            .bind(seq)
+            .fetch_all(&self.pool)
+            .await
+            .context("failed to read trigger changes")
+    }
+}
+
+/// Contend for the scheduler lease, starting the triggers on winning it and
+/// stopping them on losing it, and while holding it, restarting triggers
+/// other replicas changed and settling the runs of replicas that dropped
+/// out. A leader that can't reach the database steps down before its lease
+/// runs out.
+pub async fn leader_loop(scheduler: Arc<FlowScheduler>, leases: Arc<LeaseTable>) {
+    let mut tick = tokio::time::interval(leases.lease / 3);
+    let mut renewed: Option<Instant> = None;
+    // The last trigger change applied
+    let mut applied = 0;
+    // The replicas up as of the last tick, while leading
+    let mut live: Vec<String> = Vec::new();
+    loop {
+        tick.tick().await;
+        if let Err(e) = leases.try_acquire(&leases.replica_lease()).await {
+            tracing::warn!(error = %e, "Failed to renew the replica lease");
+        }
+        let leading = renewed.is_some();
+        let held = match leases.try_acquire(SCHEDULER_LEASE).await {
+            Ok(true) => {
+                renewed = Some(Instant::now());
+                true
+            }
+            Ok(false) => false,
+            // Stand down a tick before the lease runs out, when another
+            // replica may take it
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to renew the scheduler lease");
+                renewed.is_some_and(|at| at.elapsed() < leases.lease * 2 / 3)
+            }
+        };
+        match (leading, held) {
+            (false, true) => {
+                tracing::info!(replica = %leases.holder, "Became the scheduling replica");
+                applied = leases.latest_trigger_change().await.unwrap_or(applied);
+                scheduler.set_standby(false);
+                // Other replicas may have edited flows while this one stood by
+                scheduler.reload_flows().await;
+                match leases.live_replicas().await {
+                    Ok(replicas) => {
+                        scheduler.recover_interrupted_runs(&replicas).await;
+                        live = replicas;
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Not settling interrupted runs"),
+                }
+                scheduler.replay_trigger_events().await;
+                scheduler.start_all().await;
+            }
+            (true, false) => {
+                tracing::warn!(replica = %leases.holder, "Lost the scheduler lease, stopping triggers");
+                renewed = None;
+                scheduler.set_standby(true);
+                scheduler.stop_all().await;
+            }
+            (true, true) => {
+                match leases.trigger_changes_since(applied).await {
+                    Ok(changes) if !changes.is_empty() => {
+                        applied = changes.last().map_or(applied, |(seq, _)| *seq);
+                        let mut flow_ids: Vec<String> = changes.into_iter().map(|(_, id)| id).collect();
+                        flow_ids.sort();
+                        flow_ids.dedup();
+                        tracing::info!(flows = ?flow_ids, "Applying trigger changes from other replicas");
+                        scheduler.apply_trigger_changes(&flow_ids).await;
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Failed to read trigger changes"),
+                }
+                match leases.live_replicas().await {
+                    Ok(replicas) => {
+                        let gone: Vec<&String> = live.iter().filter(|r| !replicas.contains(r)).collect();
+                        if !gone.is_empty() {
+                            tracing::info!(replicas = ?gone, "Settling the runs of replicas that dropped out");
+                            scheduler.recover_interrupted_runs(&replicas).await;
+                        }
+                        live = replicas;
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Failed to read replica leases"),
+                }
+            }
+            (false, false) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_raw_values() {
+        assert_eq!(Coordination::from_raw_values(None, Some("web-1"), None), None);
+        assert_eq!(Coordination::from_raw_values(Some(" "), None, None), None);
+
+        let coordination = Coordination::from_raw_values(Some("postgres://db/cthulu"), Some("web-1"), Some("1")).unwrap();
+        assert_eq!(coordination.lease, Duration::from_secs(DEFAULT_LEASE_SECS));
+        assert!(coordination.replica_id.starts_with("web-1-"));
+        let again = Coordination::from_raw_values(Some("postgres://db/cthulu"), Some("web-1"), Some("12")).unwrap();
+        assert_eq!(again.lease, Duration::from_secs(12));
+        assert_ne!(again.replica_id, coordination.replica_id);
+    }
+}
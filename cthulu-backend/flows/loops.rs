@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+
+use crate::flows::branch::{self, BRANCH_KIND, Condition};
+use crate::flows::graph::NodeOutput;
+use crate::flows::processors::NodeDeps;
+use crate::flows::runner::run_node;
+use crate::flows::timeouts::RunLimits;
+use crate::flows::{Edge, EdgeCondition, Flow, Node};
+use crate::tasks::sources::ContentItem;
+
+/// Executor kind that runs a subgraph once per upstream item: the nodes
+/// reached through its out-edges labeled `each`. Its other out-edges get
+/// the iterations' outputs merged back together.
+pub const LOOP_KIND: &str = "for-each";
+
+/// Label of the edges from a loop node into its body.
+pub const EACH_LABEL: &str = "each";
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A loop node's body, ready to run per item.
+#[derive(Clone)]
+pub struct LoopBody {
+    loop_id: String,
+    label: String,
+    /// Body nodes in execution order.
+    nodes: Vec<Node>,
+    /// Edges into the body, from the loop node or other body nodes.
+    edges: Vec<Edge>,
+    /// Body nodes nothing else in the body reads from; their outputs are
+    /// the iteration's.
+    leaves: HashSet<String>,
+    concurrency: usize,
+}
+
+/// What a loop produced, with what its iterations' executors cost.
+pub struct LoopOutput {
+    pub output: NodeOutput,
+    pub cost_usd: f64,
+}
+
+impl LoopBody {
+    /// `body` is the loop's node ids in execution order, from
+    /// `graph::loop_bodies`.
+    pub fn new(flow: &Flow, loop_node: &Node, body: &[String]) -> Result<Self> {
+        let concurrency = match &loop_node.config["concurrency"] {
+            serde_json::Value::Null => DEFAULT_CONCURRENCY,
+            value => value
+                .as_u64()
+                .filter(|n| *n >= 1)
+                .context("concurrency must be a whole number of at least 1")? as usize,
+        };
+        let ids: HashSet<&str> = body.iter().map(String::as_str).collect();
+        let nodes: Vec<Node> = body
+            .iter()
+            .filter_map(|id| flow.nodes.iter().find(|n| &n.id == id).cloned())
+            .collect();
+        let edges: Vec<Edge> = flow
+            .edges
+            .iter()
+            .filter(|e| ids.contains(e.target.as_str()))
+            .filter(|e| ids.contains(e.source.as_str()) || is_each_edge(e, &loop_node.id))
+            .cloned()
+            .collect();
+        if edges.iter().any(|e| e.condition == Some(EdgeCondition::OnFailure)) {
+            anyhow::bail!("on_failure edges can't be inside a for-each body, where a failure ends the iteration");
+        }
+        let leaves = body
+            .iter()
+            .filter(|id| !edges.iter().any(|e| &e.source == *id))
+            .cloned()
+            .collect();
+        Ok(Self {
+            loop_id: loop_node.id.clone(),
+            label: loop_node.label.clone(),
+            nodes,
+            edges,
+            leaves,
+            concurrency,
+        })
+    }
+
+    /// Run the body once per item of `input`, at most `concurrency` at a
+    /// time, and merge what the iterations output. Iterations that fail are
+    /// left out; the loop fails only if every one did.
+    pub async fn run(&self, input: NodeOutput, deps: &NodeDeps, limits: &RunLimits) -> Result<LoopOutput> {
+        use futures::StreamExt;
+
+        let items = input.as_items();
+        if items.is_empty() {
+            tracing::info!(node = %self.label, "No items to loop over");
+            return Ok(LoopOutput { output: NodeOutput::Empty, cost_usd: 0.0 });
+        }
+        tracing::info!(node = %self.label, items = items.len(), concurrency = self.concurrency, "Looping");
+
+        let iterations: Vec<_> = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| self.iteration(i, item, deps, limits))
+            .collect();
+        let results: Vec<(Result<NodeOutput>, f64)> =
+            futures::stream::iter(iterations).buffered(self.concurrency).collect().await;
+
+        let mut outputs = Vec::new();
+        let mut cost_usd = 0.0;
+        let mut first_error = None;
+        for (i, (result, cost)) in results.into_iter().enumerate() {
+            cost_usd += cost;
+            match result {
+                Ok(output) => outputs.push(output),
+                Err(e) => {
+                    tracing::warn!(node = %self.label, item = i + 1, error = %format!("{e:#}"), "Loop iteration failed, leaving it out");
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if outputs.is_empty()
+            && let Some(e) = first_error
+        {
+            return Err(e.context("every loop iteration failed"));
+        }
+        Ok(LoopOutput { output: NodeOutput::merge(outputs), cost_usd })
+    }
+
+    /// Run the body on one item: its leaves' outputs merged, and what the
+    /// iteration's executors cost even if a later node failed.
+    async fn iteration(
+        &self,
+        index: usize,
+        item: ContentItem,
+        deps: &NodeDeps,
+        limits: &RunLimits,
+    ) -> (Result<NodeOutput>, f64) {
+        let mut outputs: HashMap<&str, NodeOutput> =
+            HashMap::from([(self.loop_id.as_str(), NodeOutput::Items(vec![item]))]);
+        let mut outcomes: HashMap<String, bool> = HashMap::new();
+        let mut cost = 0.0;
+
+        for node in &self.nodes {
+            let parent_outputs: Vec<NodeOutput> = self
+                .edges
+                .iter()
+                .filter(|e| e.target == node.id && branch::edge_taken(e, &outcomes))
+                .filter_map(|e| outputs.get(e.source.as_str()).cloned())
+                .collect();
+            // Only reached through an untaken branch
+            if parent_outputs.is_empty() {
+                continue;
+            }
+            let input = NodeOutput::merge(parent_outputs);
+
+            let output = if node.kind == BRANCH_KIND {
+                Condition::for_node(node)
+                    .and_then(|c| c.evaluate(&input, &deps.trigger_vars))
+                    .map(|outcome| {
+                        outcomes.insert(node.id.clone(), outcome);
+                        input
+                    })
+            } else {
+                match limits.for_node(node, Instant::now()) {
+                    Ok(limit) => run_node(node, input, deps, limit).await,
+                    Err(e) => Err(e),
+                }
+            };
+            match output {
+                Ok(output) => {
+                    if let NodeOutput::Text(_, Some(er)) = &output {
+                        cost += er.cost_usd;
+                    }
+                    outputs.insert(&node.id, output);
+                }
+                Err(e) => {
+                    let e = e.context(format!("{} failed on item {}", node.label, index + 1));
+                    return (Err(e), cost);
+                }
+            }
+        }
+
+        let leaves: Vec<NodeOutput> = self
+            .nodes
+            .iter()
+            .filter(|n| self.leaves.contains(&n.id))
+            .filter_map(|n| outputs.remove(n.id.as_str()))
+            .collect();
+        (Ok(NodeOutput::merge(leaves)), cost)
+    }
+}
+
+/// Whether `edge` leads from loop node `loop_id` into its body.
+pub fn is_each_edge(edge: &Edge, loop_id: &str) -> bool {
+    edge.source == loop_id
+        && edge
+            .label
+            .as_deref()
+            .is_some_and(|label| label.trim().eq_ignore_ascii_case(EACH_LABEL))
+}
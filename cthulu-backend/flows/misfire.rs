@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+use serde_json::Value;
+
+use crate::flows::scheduler::next_cron_runs;
+
+/// Most missed fires `backfill_all` runs, the latest ones; a flow down for
+/// months doesn't get months of runs.
+pub const MAX_BACKFILL: usize = 50;
+
+/// What a cron trigger does at startup about fires it missed while the
+/// server was down, from its `misfire`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Misfire {
+    /// Wait for the next fire.
+    Skip,
+    /// Run once now, for the latest missed fire.
+    RunOnce,
+    /// Run once per missed fire, oldest first.
+    BackfillAll,
+}
+
+impl Misfire {
+    pub fn from_trigger(config: &Value) -> Result<Self> {
+        match config["misfire"].as_str() {
+            None | Some("skip") => Ok(Self::Skip),
+            Some("run_once") => Ok(Self::RunOnce),
+            Some("backfill_all") => Ok(Self::BackfillAll),
+            Some(other) => anyhow::bail!("misfire must be \"skip\", \"run_once\" or \"backfill_all\", got '{other}'"),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::RunOnce => "run_once",
+            Self::BackfillAll => "backfill_all",
+        }
+    }
+
+    /// The fire times of `cron` after `since` (the flow's last run) and up
+    /// to `now` to run for.
+    pub fn catch_up(self, cron: &Cron, tz: Tz, since: DateTime<Utc>, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        if self == Self::Skip {
+            return Vec::new();
+        }
+        let keep = if self == Self::RunOnce { 1 } else { MAX_BACKFILL };
+        let mut missed = VecDeque::with_capacity(keep + 1);
+        let mut after = since;
+        while let Some(next) = next_cron_runs(cron, tz, after, 1).pop().map(|t| t.with_timezone(&Utc)) {
+            if next > now {
+                break;
+            }
+            missed.push_back(next);
+            if missed.len() > keep {
+                missed.pop_front();
+            }
+            after = next;
+        }
+        missed.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_from_trigger() {
+        assert_eq!(Misfire::from_trigger(&json!({})).unwrap(), Misfire::Skip);
+        assert_eq!(Misfire::from_trigger(&json!({ "misfire": "backfill_all" })).unwrap(), Misfire::BackfillAll);
+        assert!(Misfire::from_trigger(&json!({ "misfire": "always" })).is_err());
+    }
+
+    #[test]
+    fn test_catch_up() {
+        let hourly = Cron::new("0 * * * *").parse().unwrap();
+        let since = at("2026-03-01T09:00:05Z");
+        let now = at("2026-03-01T12:30:00Z");
+        assert!(Misfire::Skip.catch_up(&hourly, Tz::UTC, since, now).is_empty());
+        assert_eq!(Misfire::RunOnce.catch_up(&hourly, Tz::UTC, since, now), [at("2026-03-01T12:00:00Z")]);
+        assert_eq!(
+            Misfire::BackfillAll.catch_up(&hourly, Tz::UTC, since, now),
+            [at("2026-03-01T10:00:00Z"), at("2026-03-01T11:00:00Z"), at("2026-03-01T12:00:00Z")]
+        );
+
+        // Nothing missed
+        assert!(Misfire::RunOnce.catch_up(&hourly, Tz::UTC, since, at("2026-03-01T09:59:00Z")).is_empty());
+
+        // A long outage backfills only the latest fires
+        let month_later = at("2026-04-01T09:30:00Z");
+        let missed = Misfire::BackfillAll.catch_up(&hourly, Tz::UTC, since, month_later);
+        assert_eq!(missed.len(), MAX_BACKFILL);
+        assert_eq!(missed.last(), Some(&at("2026-04-01T09:00:00Z")));
+    }
+}
@@ -1,12 +1,44 @@
+pub mod aggregate;
+pub mod approvals;
+pub mod branch;
+pub mod concurrency;
+pub mod costs;
+pub mod debounce;
+pub mod diagram;
+pub mod dry_run;
 pub mod events;
+pub mod experiments;
 pub mod file_repository;
 pub mod graph;
 pub mod history;
+pub mod inputs;
+pub mod intake;
+pub mod leader;
+pub mod loops;
+pub mod misfire;
+pub mod pauses;
 pub mod processors;
+pub mod profile;
+pub mod recovery;
+pub mod report;
 pub mod repository;
+pub mod retention;
+pub mod run_index;
+pub mod run_logs;
+pub mod run_outputs;
 pub mod runner;
 pub mod scheduler;
 pub mod session_bridge;
+pub mod stagger;
+pub mod subflow;
+pub mod subscriptions;
+pub mod timeline;
+pub mod timeouts;
+pub mod tokens;
+pub mod transform;
+pub mod trigger_log;
+pub mod wait;
+pub mod window;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -23,6 +55,13 @@ pub struct Flow {
     pub edges: Vec<Edge>,
     #[serde(default)]
     pub version: u64,
+    /// Who may approve changes to the flow.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// Edits by anyone but an owner wait as pending changes until an owner
+    /// approves them (see `approvals`).
+    #[serde(default)]
+    pub require_approval: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -55,6 +94,21 @@ pub struct Edge {
     pub id: String,
     pub source: String,
     pub target: String,
+    /// Out of a `branch` node: the outcome (`true`/`false`) this edge is taken
+    /// on. Out of a `for-each` node: `each` for edges into its loop body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Take this edge only when its source succeeded, or only when it
+    /// failed. Unset, a failed source fails (skips) its targets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<EdgeCondition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeCondition {
+    OnSuccess,
+    OnFailure,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -84,6 +138,8 @@ mod tests {
             }],
             edges: vec![],
             version: 0,
+            owners: vec![],
+            require_approval: false,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::json_file;
+
+/// Serializes read-modify-write of the pauses file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Pauses {
+    /// When every schedule was paused, if they are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    all: Option<DateTime<Utc>>,
+    /// When each paused flow's schedule was paused.
+    #[serde(default)]
+    flows: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// Paused cron schedules (`~/.cthulu/state/paused-schedules.json`), kept
+/// apart from the flows so pausing one isn't an edit. A paused schedule's
+/// fires are skipped; manual runs, webhooks and other triggers still work.
+/// Holding everything (and draining runs) is maintenance mode's job.
+#[derive(Clone)]
+pub struct PauseStore {
+    path: PathBuf,
+}
+
+impl PauseStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("state").join("paused-schedules.json") }
+    }
+
+    fn load(&self) -> Pauses {
+        json_file::load_or_default(&self.path)
+    }
+
+    fn write(&self, pauses: &Pauses) -> Result<()> {
+        json_file::write(&self.path, pauses)
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Pauses)) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut pauses = self.load();
+        change(&mut pauses);
+        self.write(&pauses)
+    }
+
+    /// Since when `flow_id`'s schedule is paused, by itself or with all of
+    /// them; `None` while it fires.
+    pub fn paused_since(&self, flow_id: &str) -> Option<DateTime<Utc>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let pauses = self.load();
+        pauses.flows.get(flow_id).copied().or(pauses.all)
+    }
+
+    /// Since when every cron schedule is paused.
+    pub fn all_paused_since(&self) -> Option<DateTime<Utc>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        self.load().all
+    }
+
+    /// Pause `flow_id`'s schedule (or keep it paused), returning since when.
+    pub fn pause(&self, flow_id: &str) -> Result<DateTime<Utc>> {
+        let mut since = Utc::now();
+        self.update(|pauses| since = *pauses.flows.entry(flow_id.to_string()).or_insert(since))?;
+        Ok(since)
+    }
+
+    /// Let `flow_id`'s schedule fire again (unless all are paused).
+    pub fn resume(&self, flow_id: &str) -> Result<()> {
+        self.update(|pauses| {
+            pauses.flows.remove(flow_id);
+        })
+    }
+
+    /// Pause every schedule, returning since when.
+    pub fn pause_all(&self) -> Result<DateTime<Utc>> {
+        let mut since = Utc::now();
+        self.update(|pauses| since = *pauses.all.get_or_insert(since))?;
+        Ok(since)
+    }
+
+    /// End `pause_all`; schedules paused one by one stay paused.
+    pub fn resume_all(&self) -> Result<()> {
+        self.update(|pauses| pauses.all = None)
+    }
+
+    /// Forget `flow_id` (the flow was deleted).
+    pub fn delete_flow(&self, flow_id: &str) -> Result<()> {
+        self.resume(flow_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PauseStore::new(dir.path());
+        assert_eq!(store.paused_since("a"), None);
+
+        let since = store.pause("a").unwrap();
+        assert_eq!(store.pause("a").unwrap(), since);
+        assert_eq!(store.paused_since("a"), Some(since));
+        assert_eq!(store.paused_since("b"), None);
+
+        let all = store.pause_all().unwrap();
+        assert_eq!(store.paused_since("b"), Some(all));
+        store.resume_all().unwrap();
+        assert_eq!(store.paused_since("b"), None);
+        assert_eq!(store.paused_since("a"), Some(since));
+
+        store.resume("a").unwrap();
+        assert_eq!(store.paused_since("a"), None);
+    }
+}
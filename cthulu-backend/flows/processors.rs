@@ -1,24 +1,56 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 use crate::agents::repository::AgentRepository;
 use crate::api::{FlowSessions, InteractSession};
 use crate::config::{SinkConfig, SourceConfig};
+use crate::flows::branch::BRANCH_KIND;
+use crate::flows::concurrency;
+use crate::flows::dry_run::DryRun;
+use crate::flows::loops::LOOP_KIND;
+use crate::flows::subflow::SUBFLOW_KIND;
+use crate::flows::transform::{TRANSFORM_KIND, Transform};
+use crate::flows::wait::WAIT_KIND;
+use crate::flows::aggregate::AGGREGATE_KIND;
+use crate::flows::experiments::PromptSplit;
 use crate::flows::graph::NodeOutput;
 use crate::flows::session_bridge::{FlowRunMeta, SessionBridge};
+use crate::flows::subscriptions::{PERSONALIZED_DIGEST_KIND, SubscriptionStore};
 use crate::flows::{Node, NodeType};
 use crate::github::client::GithubClient;
+use crate::harness::Harness;
+use crate::prompts::repository::PromptRepository;
+use crate::prompts::resolve_prompt_ref;
 use crate::sandbox::provider::SandboxProvider;
+use crate::tasks::archive::ArchiveStore;
+use crate::tasks::audio::{AUDIO_KIND, AudioStore, DEFAULT_KEEP_EPISODES, TtsProvider, speakable};
 use crate::tasks::context::render_prompt;
-use crate::tasks::executors::{Executor, LineSink};
+use crate::tasks::executors::batching::{self, Batching};
+use crate::tasks::executors::cache::ExecutorCache;
+use crate::tasks::executors::echo::EchoExecutor;
+use crate::tasks::executors::harness::HarnessExecutor;
+use crate::tasks::executors::models::{ModelAttempt, ModelChain, ModelChoice};
+use crate::tasks::executors::{ExecutionResult, Executor, LineSink};
 use crate::tasks::executors::claude_code::ClaudeCodeExecutor;
-use crate::tasks::executors::sandbox::SandboxExecutor;
-use crate::tasks::pipeline::{format_items, resolve_sinks};
-use crate::tasks::sources;
+use crate::tasks::executors::ollama::{self, OLLAMA_KIND};
+use crate::tasks::executors::openai::{OPENAI_KIND, OpenAiExecutor};
+use crate::tasks::executors::sandbox::{self, SandboxAccess, SandboxExecutor};
+use crate::tasks::feedback::{FEEDBACK_RANK_KIND, FeedbackStore};
+use crate::tasks::personalize::personalize;
+use crate::tasks::pipeline::{format_items, resolve_sinks, slack_api_sink};
+use crate::tasks::reports::ReportFormat;
+use crate::tasks::sources::{self, ContentItem};
+use crate::tasks::sources::browser;
+use crate::tasks::sources::cache::SourceCache;
+use crate::tasks::sources::repo_health;
+use crate::tasks::sources::seen::SeenStore;
+use crate::tasks::trending::{TRENDING_KIND, TrendingOptions, TrendingStore};
+use crate::tasks::vectors::{DEFAULT_TOP_K, VECTOR_SEARCH_KIND, VECTOR_STORE_KIND, VectorStore};
 
 /// Dependencies needed by node processors.
 /// Cloneable so it can be shared across parallel tasks.
@@ -35,6 +67,41 @@ pub struct NodeDeps {
     pub run_id: Option<String>,
     /// Flow name (for flow-run session metadata).
     pub flow_name: Option<String>,
+    /// Executor output cache (used only by nodes with `cache_ttl_secs`).
+    pub executor_cache: Option<ExecutorCache>,
+    /// Fetched items cache (used only by sources with `cache_ttl_secs`).
+    pub source_cache: Option<SourceCache>,
+    /// Prompt library for executor nodes that use `prompt_ref`.
+    pub prompt_repo: Option<Arc<dyn PromptRepository>>,
+    /// Cross-run "seen item" state for sources with `dedupe: true`.
+    pub seen_store: Option<SeenStore>,
+    /// Searchable item archive for sources with `archive: true`.
+    pub archive: Option<ArchiveStore>,
+    /// Start of the flow's previous successful run (for "since last run" sources).
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// Variables injected at the trigger (run inputs, PR context). Available to
+    /// every executor prompt, including those fed by sources.
+    pub trigger_vars: HashMap<String, String>,
+    /// Subscribers to this flow, for `personalized-digest` nodes.
+    pub subscriptions: Option<SubscriptionStore>,
+    /// Votes on earlier digests, for Slack `feedback` sinks and
+    /// `feedback-rank` nodes.
+    pub feedback: Option<FeedbackStore>,
+    /// Item history for `trending` nodes.
+    pub trending: Option<TrendingStore>,
+    /// Embedded items for `vector-store` and `vector-search` nodes.
+    pub vectors: Option<VectorStore>,
+    /// Episodes of `audio` sinks' podcast feeds.
+    pub audio: Option<AudioStore>,
+    /// Time limit of the node being processed, so executors can kill their
+    /// own process (or sandbox exec) when it runs out.
+    pub timeout: Option<Duration>,
+    /// Set in test harness mode: executors return its canned output.
+    pub harness: Option<Arc<Harness>>,
+    /// Set in dry runs: executors echo their prompt, sinks deliver to it.
+    pub dry_run: Option<Arc<DryRun>>,
+    /// The run's priority, for its place in the executor queue.
+    pub priority: i64,
 }
 
 /// Process a single node, dispatching by type.
@@ -56,18 +123,68 @@ pub async fn process_node(
 // ── Source Processing ──────────────────────────────────────────────────
 
 async fn process_source(node: &Node, deps: &NodeDeps) -> Result<NodeOutput> {
+    if node.kind == VECTOR_SEARCH_KIND {
+        return search_vectors(node, deps).await;
+    }
     let configs = parse_source_configs(&[node])?;
     if configs.is_empty() {
         // market-data nodes are skipped (handled via template variable)
         return Ok(NodeOutput::Empty);
     }
 
-    let github_token = deps
-        .github_client
-        .as_ref()
-        .and_then(|_| std::env::var("GITHUB_TOKEN").ok());
+    let github_token = match &deps.github_client {
+        Some(client) => client.access_token().await.ok(),
+        None => None,
+    };
 
-    let items = sources::fetch_all(&configs, &deps.http_client, github_token.as_deref()).await;
+    let cache = node.config["cache_ttl_secs"]
+        .as_u64()
+        .filter(|&secs| secs > 0)
+        .zip(deps.source_cache.as_ref())
+        .map(|(secs, cache)| {
+            (cache, SourceCache::key(&node.kind, &node.config, deps.last_run_at), Duration::from_secs(secs))
+        });
+    let cached = match &cache {
+        Some((cache, key, ttl)) => cache.get(key, *ttl).await,
+        None => None,
+    };
+    let items = match cached {
+        Some(items) => {
+            tracing::info!(node = %node.label, items = items.len(), "Source cache hit");
+            items
+        }
+        None => {
+            let items = sources::fetch_all(
+                &configs,
+                &deps.http_client,
+                github_token.as_deref(),
+                deps.sandbox_provider.as_ref(),
+                deps.last_run_at,
+            )
+            .await;
+            // Nothing fetched may just mean the site was down
+            if let Some((cache, key, ttl)) = cache.as_ref().filter(|_| !items.is_empty())
+                && let Err(e) = cache.put(key, *ttl, &items).await
+            {
+                tracing::warn!(node = %node.label, error = %e, "Failed to cache source items");
+            }
+            items
+        }
+    };
+
+    let items = match (&deps.seen_store, &deps.run_id) {
+        (Some(seen), Some(run_id)) if node.config["dedupe"].as_bool() == Some(true) => {
+            seen.filter_unseen(&deps.flow_id, &node.id, run_id, items).await
+        }
+        _ => items,
+    };
+
+    if let (Some(archive), Some(true), None) = (&deps.archive, node.config["archive"].as_bool(), &deps.dry_run) {
+        let flow_name = deps.flow_name.as_deref().unwrap_or_default();
+        if let Err(e) = archive.archive(&deps.flow_id, flow_name, &items).await {
+            tracing::warn!(node = %node.label, error = %e, "Failed to archive items");
+        }
+    }
 
     tracing::debug!(
         node = %node.label,
@@ -78,6 +195,35 @@ async fn process_source(node: &Node, deps: &NodeDeps) -> Result<NodeOutput> {
     Ok(NodeOutput::Items(items))
 }
 
+/// Items from the vector store closest to the node's `query`, rendered with
+/// the trigger's variables (e.g. `{{question}}`), so executors can answer
+/// from what earlier runs collected.
+async fn search_vectors(node: &Node, deps: &NodeDeps) -> Result<NodeOutput> {
+    let store = deps
+        .vectors
+        .as_ref()
+        .context("vector-search requires the vector store")?;
+    let template = node.config["query"]
+        .as_str()
+        .context("vector-search node missing 'query'")?;
+    let query = render_prompt(template, &deps.trigger_vars);
+    if query.trim().is_empty() {
+        tracing::warn!(node = %node.label, "Vector search query is empty, skipping");
+        return Ok(NodeOutput::Items(Vec::new()));
+    }
+    let top_k = node.config["top_k"].as_u64().map_or(DEFAULT_TOP_K, |k| k as usize);
+    let min_score = node.config["min_score"].as_f64();
+
+    let hits = store.search(&deps.http_client, &node.config, &query, top_k).await?;
+    let items: Vec<ContentItem> = hits
+        .into_iter()
+        .filter(|hit| min_score.is_none_or(|min| f64::from(hit.score) >= min))
+        .map(|hit| hit.item)
+        .collect();
+    tracing::debug!(node = %node.label, items = items.len(), "Vector search done");
+    Ok(NodeOutput::Items(items))
+}
+
 // ── Executor Processing ────────────────────────────────────────────────
 
 async fn process_executor(
@@ -85,11 +231,82 @@ async fn process_executor(
     input: NodeOutput,
     deps: &NodeDeps,
 ) -> Result<NodeOutput> {
-    // Build prompt from input
-    let rendered = render_executor_prompt(node, &input, deps).await?;
+    if node.kind == FEEDBACK_RANK_KIND {
+        return rank_by_feedback(node, input, deps);
+    }
+    if node.kind == TRENDING_KIND {
+        return rank_trending(node, input, deps);
+    }
+    if node.kind == TRANSFORM_KIND {
+        return Transform::for_node(node)?.apply(input, &deps.trigger_vars);
+    }
+    // The runner routes on branches and runs loop bodies; run on their own
+    // they pass input through
+    if node.kind == BRANCH_KIND || node.kind == LOOP_KIND {
+        return Ok(input);
+    }
+    // The runner runs subflows, waits and aggregates, in the flow's top level only
+    if node.kind == SUBFLOW_KIND || node.kind == WAIT_KIND || node.kind == AGGREGATE_KIND {
+        anyhow::bail!("{} nodes can't run inside a for-each loop", node.kind);
+    }
+    if !run_when_matches(node, &input) {
+        tracing::info!(node = %node.label, "Skipping executor, run_when not met");
+        return Ok(NodeOutput::Empty);
+    }
 
-    // Resolve agent config
-    let (permissions, append_system_prompt) = resolve_agent_config(node, deps).await?;
+    // `for_each` runs the executor once per upstream item instead, and
+    // `batch_tokens` once per batch of items that fits the budget
+    let for_each = ForEach::from_config(&node.config)?;
+    let batching = Batching::from_config(&node.config)?;
+    if for_each.is_some() && batching.is_some() {
+        bail!("for_each and batch_tokens can't be combined");
+    }
+    let items = input.as_items();
+    let batches = batching.as_ref().map(|b| b.split(&items)).filter(|batches| batches.len() > 1);
+    let prompts = match (for_each, &batches) {
+        (Some(_), _) => {
+            let mut prompts = Vec::with_capacity(items.len());
+            for (i, item) in items.iter().enumerate() {
+                let item_input = NodeOutput::Items(vec![item.clone()]);
+                prompts.push(render_executor_prompt(node, &item_input, deps, &item_vars(i, item)).await?);
+            }
+            prompts
+        }
+        (None, Some(batches)) => {
+            tracing::info!(node = %node.label, items = items.len(), batches = batches.len(), "Splitting items into batches");
+            let mut prompts = Vec::with_capacity(batches.len());
+            for (i, batch) in batches.iter().enumerate() {
+                let batch_input = NodeOutput::Items(batch.clone());
+                let vars = HashMap::from([
+                    ("batch_index".to_string(), (i + 1).to_string()),
+                    ("batch_count".to_string(), batches.len().to_string()),
+                ]);
+                prompts.push(render_executor_prompt(node, &batch_input, deps, &vars).await?);
+            }
+            prompts
+        }
+        (None, None) => vec![render_executor_prompt(node, &input, deps, &HashMap::new()).await?],
+    };
+    if prompts.is_empty() {
+        tracing::info!(node = %node.label, "No items to run the executor on");
+        return Ok(NodeOutput::Empty);
+    }
+
+    // Dispatch on runtime
+    let runtime = node.config["runtime"]
+        .as_str()
+        .unwrap_or(node.kind.as_str());
+
+    // Resolve agent config; API executors have no tools, so the agent is
+    // optional when every model in the chain runs on one
+    let chain = ModelChain::from_config(&node.config)?;
+    let has_agent = node.config["agent_id"].as_str().is_some_and(|s| !s.is_empty());
+    let api_only = chain.runtimes(runtime).all(|r| matches!(r, OPENAI_KIND | OLLAMA_KIND));
+    let (permissions, append_system_prompt) = if (api_only || deps.harness.is_some() || deps.dry_run.is_some()) && !has_agent {
+        (Vec::new(), None)
+    } else {
+        resolve_agent_config(node, deps).await?
+    };
 
     // Resolve working dir
     let working_dir = node.config["working_dir"]
@@ -97,28 +314,22 @@ async fn process_executor(
         .map(PathBuf::from)
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
-    // Dispatch on runtime
-    let runtime = node.config["runtime"]
-        .as_str()
-        .unwrap_or(node.kind.as_str());
-
-    let executor: Box<dyn Executor> = match runtime {
-        "sandbox" => {
-            let provider = deps
-                .sandbox_provider
-                .as_ref()
-                .context("sandbox executor requested but no sandbox provider configured")?;
-            Box::new(SandboxExecutor::new(
-                provider.clone(),
-                permissions.clone(),
-                append_system_prompt,
-            ))
-        }
-        _ => Box::new(ClaudeCodeExecutor::new(
-            permissions.clone(),
-            append_system_prompt,
-        )),
+    let call = ExecutorCall {
+        node,
+        deps,
+        runtime,
+        permissions: &permissions,
+        append_system_prompt: &append_system_prompt,
+        chain,
+        working_dir: &working_dir,
     };
+    if let [rendered] = prompts.as_slice()
+        && for_each.is_none()
+        && let Some(hit) = call.cached(rendered).await
+    {
+        let text = hit.text.clone();
+        return Ok(NodeOutput::Text(text, Some(hit)));
+    }
 
     let perms_display = if permissions.is_empty() {
         "ALL".to_string()
@@ -128,7 +339,8 @@ async fn process_executor(
     tracing::info!(
         executor = %node.kind,
         permissions = %perms_display,
-        input_chars = rendered.len(),
+        input_chars = prompts.iter().map(String::len).sum::<usize>(),
+        calls = prompts.len(),
         "Executing",
     );
 
@@ -137,8 +349,10 @@ async fn process_executor(
         .as_str()
         .filter(|s| !s.is_empty())
         .map(String::from);
+    // A dry run's echoes don't belong in the agent's sessions
+    let session_bridge = deps.session_bridge.clone().filter(|_| deps.dry_run.is_none());
     let line_sink = setup_flow_run_session(
-        &deps.session_bridge,
+        &session_bridge,
         &agent_id,
         &deps.flow_id,
         deps.flow_name.as_deref().unwrap_or("Unknown"),
@@ -149,10 +363,24 @@ async fn process_executor(
     )
     .await;
 
-    let exec_result = executor
-        .execute_streaming(&rendered, &working_dir, line_sink.clone())
-        .await
-        .with_context(|| format!("executor '{}' failed", node.label));
+    let exec_result = match for_each {
+        Some(for_each) => {
+            use futures::StreamExt;
+            let calls: Vec<_> = prompts.iter().map(|rendered| call.run(rendered, line_sink.clone())).collect();
+            let results: Vec<Result<ExecutionResult>> = futures::stream::iter(calls)
+                .buffered(for_each.concurrency)
+                .collect()
+                .await;
+            for_each.aggregate(node, &items, results)
+        }
+        None => match &batching {
+            Some(batching) if prompts.len() > 1 => {
+                call.run_batched(batching, &prompts, line_sink.clone()).await
+            }
+            _ => call.execute(&prompts[0], line_sink.clone()).await,
+        },
+    }
+    .with_context(|| format!("executor '{}' failed", node.label));
 
     // Finalize session regardless of success/failure
     finalize_flow_run_session(
@@ -176,11 +404,389 @@ async fn process_executor(
     Ok(NodeOutput::Text(text, Some(exec_result)))
 }
 
+/// One executor node's settings for running a rendered prompt: through its
+/// response cache, then its model chain.
+struct ExecutorCall<'a> {
+    node: &'a Node,
+    deps: &'a NodeDeps,
+    runtime: &'a str,
+    permissions: &'a [String],
+    append_system_prompt: &'a Option<String>,
+    chain: ModelChain,
+    working_dir: &'a std::path::Path,
+}
+
+impl ExecutorCall<'_> {
+    /// Opt-in response cache keyed by (model, prompt). The agent's system prompt
+    /// is hashed with the rendered prompt so editing the agent invalidates entries.
+    fn cache(&self, rendered: &str) -> Option<(&ExecutorCache, String, Duration)> {
+        // Outputs cached from real runs would leak into harness and dry runs, and back
+        if self.deps.harness.is_some() || self.deps.dry_run.is_some() {
+            return None;
+        }
+        let model = self.node.config["model"].as_str().unwrap_or("default");
+        self.node.config["cache_ttl_secs"]
+            .as_u64()
+            .filter(|&secs| secs > 0)
+            .zip(self.deps.executor_cache.as_ref())
+            .map(|(secs, cache)| {
+                let prompt = format!(
+                    "{}\0{rendered}",
+                    self.append_system_prompt.as_deref().unwrap_or_default()
+                );
+                (cache, ExecutorCache::key(model, &prompt), Duration::from_secs(secs))
+            })
+    }
+
+    async fn cached(&self, rendered: &str) -> Option<ExecutionResult> {
+        let (cache, key, ttl) = self.cache(rendered)?;
+        let hit = cache.get(&key, ttl).await?;
+        tracing::info!(node = %self.node.label, output_chars = hit.text.len(), "Executor cache hit");
+        Some(hit)
+    }
+
+    async fn run(&self, rendered: &str, line_sink: Option<LineSink>) -> Result<ExecutionResult> {
+        match self.cached(rendered).await {
+            Some(hit) => Ok(hit),
+            None => self.execute(rendered, line_sink).await,
+        }
+    }
+
+    async fn execute(&self, rendered: &str, line_sink: Option<LineSink>) -> Result<ExecutionResult> {
+        // Every flow's executor calls share CTHULU_MAX_CONCURRENT_EXECUTORS slots
+        let _slot = concurrency::executor_slot(self.deps.priority).await;
+        let result = execute_chain(
+            self.node,
+            self.runtime,
+            &self.chain,
+            |choice| {
+                build_executor(
+                    choice.runtime.as_deref().unwrap_or(self.runtime),
+                    self.node,
+                    self.deps,
+                    self.permissions,
+                    self.append_system_prompt.clone(),
+                    choice.model.as_deref(),
+                )
+            },
+            rendered,
+            self.working_dir,
+            line_sink,
+        )
+        .await?;
+
+        if let Some((cache, key, ttl)) = self.cache(rendered) {
+            let model = self.node.config["model"].as_str().unwrap_or("default");
+            if let Err(e) = cache.put(&key, ttl, model, &result).await {
+                tracing::warn!(node = %self.node.label, error = %e, "Failed to write executor cache");
+            }
+        }
+        Ok(result)
+    }
+
+    /// Run each batch's prompt, at most `concurrency` at a time, then merge
+    /// their outputs with the reduce call. Any batch failing fails the node,
+    /// since the merged output would silently miss its items.
+    async fn run_batched(
+        &self,
+        batching: &Batching,
+        prompts: &[String],
+        line_sink: Option<LineSink>,
+    ) -> Result<ExecutionResult> {
+        use futures::{StreamExt, TryStreamExt};
+        let calls: Vec<_> = prompts.iter().map(|rendered| self.run(rendered, line_sink.clone())).collect();
+        let partials: Vec<ExecutionResult> = futures::stream::iter(calls)
+            .buffered(batching.concurrency)
+            .enumerate()
+            .map(|(i, result)| result.with_context(|| format!("batch {} of {}", i + 1, prompts.len())))
+            .try_collect()
+            .await?;
+        let reduce = batching.reduce_prompt(&partials, &self.deps.trigger_vars);
+        tracing::info!(node = %self.node.label, batches = partials.len(), input_chars = reduce.len(), "Merging batch outputs");
+        let reduced = self.execute(&reduce, line_sink).await.context("merging batch outputs")?;
+        Ok(batching::combine(partials, reduced))
+    }
+}
+
+/// An executor's `for_each: true`: run once per upstream item, at most
+/// `concurrency` at a time, rather than once on all of them together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ForEach {
+    concurrency: usize,
+    /// Head each output with its item's title and link.
+    item_headers: bool,
+}
+
+impl ForEach {
+    const DEFAULT_CONCURRENCY: usize = 4;
+
+    fn from_config(config: &serde_json::Value) -> Result<Option<Self>> {
+        if config["for_each"].as_bool() != Some(true) {
+            return Ok(None);
+        }
+        let concurrency = match &config["concurrency"] {
+            serde_json::Value::Null => Self::DEFAULT_CONCURRENCY,
+            value => value
+                .as_u64()
+                .filter(|n| *n >= 1)
+                .context("concurrency must be a whole number of at least 1")? as usize,
+        };
+        Ok(Some(Self {
+            concurrency,
+            item_headers: config["item_headers"].as_bool().unwrap_or(true),
+        }))
+    }
+
+    /// Join the outputs in item order, summing cost and turns. Items whose
+    /// run failed are left out; the node fails only if every one did.
+    fn aggregate(
+        &self,
+        node: &Node,
+        items: &[ContentItem],
+        results: Vec<Result<ExecutionResult>>,
+    ) -> Result<ExecutionResult> {
+        let mut sections = Vec::new();
+        let (mut cost_usd, mut num_turns) = (0.0, 0);
+        let mut attempts = Vec::new();
+        let mut first_error = None;
+        for (item, result) in items.iter().zip(results) {
+            match result {
+                Ok(result) => {
+                    cost_usd += result.cost_usd;
+                    num_turns += result.num_turns;
+                    attempts.extend(result.attempts);
+                    sections.push(match self.item_headers {
+                        true if item.url.is_empty() => format!("## {}\n\n{}", item.title, result.text.trim()),
+                        true => format!("## {}\n{}\n\n{}", item.title, item.url, result.text.trim()),
+                        false => result.text.trim().to_string(),
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!(node = %node.label, item = %item.title, error = %e, "Executor failed on item, leaving it out");
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if sections.is_empty()
+            && let Some(e) = first_error
+        {
+            return Err(e.context("executor failed on every item"));
+        }
+        Ok(ExecutionResult {
+            text: sections.join("\n\n"),
+            cost_usd,
+            num_turns,
+            attempts,
+        })
+    }
+}
+
+/// Template variables describing the item a `for_each` call runs on.
+pub(crate) fn item_vars(index: usize, item: &ContentItem) -> HashMap<String, String> {
+    HashMap::from([
+        ("item_index".to_string(), (index + 1).to_string()),
+        ("item_title".to_string(), item.title.clone()),
+        ("item_url".to_string(), item.url.clone()),
+        ("item_summary".to_string(), item.summary.clone()),
+    ])
+}
+
+/// The executor for `runtime`, running `model` instead of its default.
+fn build_executor(
+    runtime: &str,
+    node: &Node,
+    deps: &NodeDeps,
+    permissions: &[String],
+    append_system_prompt: Option<String>,
+    model: Option<&str>,
+) -> Result<Box<dyn Executor>> {
+    if let Some(harness) = &deps.harness {
+        return Ok(Box::new(HarnessExecutor::new(harness.clone(), &deps.flow_id, &node.id, &node.label)));
+    }
+    if deps.dry_run.is_some() {
+        return Ok(Box::new(EchoExecutor));
+    }
+    let mut config = node.config.clone();
+    if let Some(model) = model {
+        config["model"] = serde_json::Value::String(model.to_string());
+    }
+    let access = SandboxAccess::from_config(&node.config["sandbox"])?;
+    if access.is_some() && matches!(runtime, OPENAI_KIND | OLLAMA_KIND) {
+        anyhow::bail!("`sandbox` doesn't apply to {runtime}, which runs no agent in a sandbox");
+    }
+    Ok(match runtime {
+        OPENAI_KIND => Box::new(OpenAiExecutor::from_config(
+            deps.http_client.clone(),
+            &config,
+            append_system_prompt,
+        )?),
+        OLLAMA_KIND => ollama::from_config(deps.http_client.clone(), &config, append_system_prompt)?,
+        "sandbox" => {
+            let provider = deps
+                .sandbox_provider
+                .as_ref()
+                .context("sandbox executor requested but no sandbox provider configured")?;
+            sandbox_executor(provider, node, deps, permissions, append_system_prompt, model, access)
+        }
+        // Claude is spawned straight on the host only when the host is the
+        // sandbox backend anyway, and the node doesn't limit its access
+        _ => match &deps.sandbox_provider {
+            Some(provider) if access.is_some() || !sandbox::runs_on_host(&**provider) => {
+                if access.as_ref().is_some_and(SandboxAccess::restricts_network) && sandbox::runs_on_host(&**provider) {
+                    tracing::warn!(node = %node.label, "The host sandbox backend can't limit network access");
+                }
+                sandbox_executor(provider, node, deps, permissions, append_system_prompt, model, access)
+            }
+            _ if access.is_some() => anyhow::bail!("`sandbox` access needs a sandbox provider"),
+            _ => {
+                let mut executor = ClaudeCodeExecutor::new(permissions.to_vec(), append_system_prompt);
+                if let Some(model) = model {
+                    executor = executor.with_model(model.to_string());
+                }
+                match deps.timeout {
+                    Some(timeout) => Box::new(executor.with_timeout(timeout)),
+                    None => Box::new(executor),
+                }
+            }
+        },
+    })
+}
+
+/// Claude inside a sandbox from `provider`, with the node's `working_dir`
+/// (when it sets one) copied in and its `sandbox` access applied.
+fn sandbox_executor(
+    provider: &Arc<dyn SandboxProvider>,
+    node: &Node,
+    deps: &NodeDeps,
+    permissions: &[String],
+    append_system_prompt: Option<String>,
+    model: Option<&str>,
+    access: Option<SandboxAccess>,
+) -> Box<dyn Executor> {
+    let mut executor = SandboxExecutor::new(provider.clone(), permissions.to_vec(), append_system_prompt);
+    if let Some(model) = model {
+        executor = executor.with_model(model.to_string());
+    }
+    if node.config["working_dir"].as_str().is_some_and(|d| !d.is_empty()) {
+        executor = executor.with_repo_sync();
+    }
+    if let Some(access) = access {
+        executor = executor.with_access(access);
+    }
+    match deps.timeout {
+        Some(timeout) => Box::new(executor.with_timeout(timeout)),
+        None => Box::new(executor),
+    }
+}
+
+/// Run `prompt` on each model of `chain` in turn until one succeeds without
+/// reporting low confidence; the last model's answer stands either way. The
+/// result's cost and turns cover every attempt, and with fallbacks it lists
+/// them. `runtime` is the node's, for models that don't name their own.
+async fn execute_chain(
+    node: &Node,
+    runtime: &str,
+    chain: &ModelChain,
+    build: impl Fn(&ModelChoice) -> Result<Box<dyn Executor>>,
+    prompt: &str,
+    working_dir: &std::path::Path,
+    line_sink: Option<LineSink>,
+) -> Result<ExecutionResult> {
+    let (mut cost_usd, mut num_turns) = (0.0, 0);
+    let mut attempts = Vec::new();
+    let mut models = chain.models.iter().peekable();
+    while let Some(choice) = models.next() {
+        let model_name = choice.label(runtime);
+        let started = Instant::now();
+        let attempt = match build(choice) {
+            Ok(executor) => {
+                let run = executor.execute_streaming(prompt, working_dir, line_sink.clone());
+                match chain.attempt_timeout {
+                    Some(limit) => tokio::time::timeout(limit, run)
+                        .await
+                        .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out after {}s", limit.as_secs()))),
+                    None => run.await,
+                }
+            }
+            Err(e) => Err(e),
+        };
+        if chain.models.len() > 1 {
+            attempts.push(ModelAttempt {
+                model: model_name.clone(),
+                cost_usd: attempt.as_ref().map_or(0.0, |r| r.cost_usd),
+                duration_ms: started.elapsed().as_millis() as u64,
+                error: attempt.as_ref().err().map(|e| format!("{e:#}")),
+            });
+        }
+        let Some(next) = models.peek() else {
+            return attempt.map(|mut result| {
+                result.cost_usd += cost_usd;
+                result.num_turns += num_turns;
+                result.attempts = attempts;
+                result
+            });
+        };
+        let next = next.label(runtime);
+        match attempt {
+            Ok(mut result) => {
+                result.cost_usd += cost_usd;
+                result.num_turns += num_turns;
+                let Some(confidence) = chain.low_confidence(&result.text) else {
+                    result.attempts = attempts;
+                    return Ok(result);
+                };
+                tracing::info!(
+                    node = %node.label,
+                    model = %model_name,
+                    confidence,
+                    next = %next,
+                    "Low confidence, escalating to next model",
+                );
+                if let Some(attempt) = attempts.last_mut() {
+                    attempt.error = Some(format!("low confidence ({confidence})"));
+                }
+                cost_usd = result.cost_usd;
+                num_turns = result.num_turns;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    node = %node.label,
+                    model = %model_name,
+                    next = %next,
+                    error = %e,
+                    "Executor failed, falling back to next model",
+                );
+            }
+        }
+    }
+    bail!("no model to run")
+}
+
+/// Evaluate an executor's optional `run_when` condition against its context
+/// input, e.g. `run_when: { risk_level: [high, medium] }`. Every listed
+/// variable must be present and equal one of its allowed values.
+fn run_when_matches(node: &Node, input: &NodeOutput) -> bool {
+    let Some(conditions) = node.config["run_when"].as_object() else {
+        return true;
+    };
+    let vars = input.as_context();
+    conditions.iter().all(|(var, allowed)| {
+        let Some(value) = vars.and_then(|v| v.get(var)) else {
+            return false;
+        };
+        match allowed {
+            serde_json::Value::Array(options) => options.iter().any(|o| o.as_str() == Some(value)),
+            other => other.as_str() == Some(value),
+        }
+    })
+}
+
 /// Render the prompt for an executor node from its upstream input.
 async fn render_executor_prompt(
     node: &Node,
     input: &NodeOutput,
     deps: &NodeDeps,
+    extra_vars: &HashMap<String, String>,
 ) -> Result<String> {
     // If input is Context (e.g. from GitHub PR trigger), use it as template vars
     let vars = if let Some(ctx) = input.as_context() {
@@ -198,21 +804,20 @@ async fn render_executor_prompt(
         };
         let timestamp = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
 
-        let mut vars = HashMap::new();
+        let mut vars = deps.trigger_vars.clone();
         vars.insert("content".to_string(), content);
         vars.insert("item_count".to_string(), items.len().to_string());
         vars.insert("timestamp".to_string(), timestamp);
         vars
     };
 
-    let prompt_path = node.config["prompt"]
-        .as_str()
-        .context("executor node missing 'prompt' config")?;
+    let prompt_template =
+        executor_prompt_template(node, deps.prompt_repo.as_deref(), deps.run_id.as_deref()).await?;
 
-    let prompt_template = load_prompt_template(prompt_path)?;
+    let mut vars = vars;
+    vars.extend(extra_vars.iter().map(|(k, v)| (k.clone(), v.clone())));
 
     // Fetch market data if needed
-    let mut vars = vars;
     if prompt_template.contains("{{market_data}}") {
         let market_data = match tokio::time::timeout(
             std::time::Duration::from_secs(15),
@@ -266,9 +871,77 @@ async fn resolve_agent_config(
     Ok((agent.permissions.clone(), agent.append_system_prompt.clone()))
 }
 
+/// Re-rank incoming items by the 👍/👎 votes on this flow's earlier digests,
+/// dropping those scoring below `min_score` and keeping at most `limit`.
+fn rank_by_feedback(node: &Node, input: NodeOutput, deps: &NodeDeps) -> Result<NodeOutput> {
+    let store = deps
+        .feedback
+        .as_ref()
+        .context("feedback-rank requires the feedback store")?;
+    let NodeOutput::Items(items) = input else {
+        tracing::warn!(node = %node.label, "feedback-rank received no items, passing input through");
+        return Ok(input);
+    };
+
+    let profile = store.profile(&deps.flow_id);
+    let received = items.len();
+    let mut ranked = profile.rank(items, node.config["min_score"].as_i64());
+    if let Some(limit) = node.config["limit"].as_u64() {
+        ranked.truncate(limit as usize);
+    }
+    tracing::info!(
+        node = %node.label,
+        received,
+        kept = ranked.len(),
+        learned = !profile.is_empty(),
+        "Ranked items by feedback"
+    );
+    Ok(NodeOutput::Items(ranked))
+}
+
+fn rank_trending(node: &Node, input: NodeOutput, deps: &NodeDeps) -> Result<NodeOutput> {
+    let store = deps
+        .trending
+        .as_ref()
+        .context("trending requires the trending store")?;
+    let NodeOutput::Items(items) = input else {
+        tracing::warn!(node = %node.label, "trending received no items, passing input through");
+        return Ok(input);
+    };
+
+    let received = items.len();
+    let options = TrendingOptions::from_config(&node.config);
+    let mut ranked = store.rank(&deps.flow_id, items, &options)?;
+    if let Some(limit) = node.config["limit"].as_u64() {
+        ranked.truncate(limit as usize);
+    }
+    tracing::info!(node = %node.label, received, kept = ranked.len(), "Ranked items by coverage");
+    Ok(NodeOutput::Items(ranked))
+}
+
 // ── Sink Processing ────────────────────────────────────────────────────
 
 async fn process_sink(node: &Node, input: NodeOutput, deps: &NodeDeps) -> Result<NodeOutput> {
+    if let Some(dry_run) = &deps.dry_run {
+        dry_run.capture(node, &input);
+        return Ok(NodeOutput::Empty);
+    }
+    if node.kind == PERSONALIZED_DIGEST_KIND {
+        return deliver_personalized_digests(node, &input, deps);
+    }
+    if node.kind == VECTOR_STORE_KIND {
+        return store_vectors(node, input, deps).await;
+    }
+    if node.kind == AUDIO_KIND {
+        return deliver_audio(node, input, deps).await;
+    }
+    if node.kind == "slack" && node.config["feedback"].as_bool() == Some(true) {
+        if let NodeOutput::Items(items) = &input {
+            return deliver_feedback_digest(node, items, deps).await;
+        }
+        tracing::warn!(node = %node.label, "Slack feedback buttons need items as input, posting text instead");
+    }
+
     let text = input.as_text();
     if text.is_empty() {
         tracing::warn!(node = %node.label, "Sink received empty input, skipping delivery");
@@ -288,6 +961,164 @@ async fn process_sink(node: &Node, input: NodeOutput, deps: &NodeDeps) -> Result
     Ok(NodeOutput::Empty)
 }
 
+/// Embed the incoming items into the node's collection; text from an
+/// executor is stored as one item per run, titled with the flow name and date.
+async fn store_vectors(node: &Node, input: NodeOutput, deps: &NodeDeps) -> Result<NodeOutput> {
+    let store = deps
+        .vectors
+        .as_ref()
+        .context("vector-store requires the vector store")?;
+    let items = match input {
+        NodeOutput::Items(items) => items,
+        other => {
+            let text = other.as_text();
+            if text.trim().is_empty() {
+                Vec::new()
+            } else {
+                let now = Utc::now();
+                let flow_name = deps.flow_name.as_deref().unwrap_or("Flow run");
+                vec![ContentItem {
+                    title: format!("{flow_name} — {}", now.format("%Y-%m-%d %H:%M UTC")),
+                    url: String::new(),
+                    summary: text,
+                    published: Some(now),
+                    image_url: None,
+                    full_summary: true,
+                }]
+            }
+        }
+    };
+    if items.is_empty() {
+        tracing::warn!(node = %node.label, "Vector store received empty input, skipping");
+        return Ok(NodeOutput::Empty);
+    }
+
+    let stored = store
+        .upsert(&deps.http_client, &node.config, items)
+        .await
+        .with_context(|| format!("sink '{}' delivery failed", node.label))?;
+    tracing::info!(node = %node.label, stored, "Items embedded into vector store");
+    Ok(NodeOutput::Empty)
+}
+
+/// Read the digest aloud and deliver the MP3 to Slack (with `bot_token_env`)
+/// and/or the flow's podcast feed (unless `feed: false`).
+async fn deliver_audio(node: &Node, input: NodeOutput, deps: &NodeDeps) -> Result<NodeOutput> {
+    let to_feed = node.config["feed"].as_bool() != Some(false);
+    let token_env = node.config["bot_token_env"].as_str();
+    if !to_feed && token_env.is_none() {
+        bail!("audio sink needs feed: true or a Slack bot_token_env");
+    }
+    let text = speakable(&input.as_text());
+    if text.is_empty() {
+        tracing::warn!(node = %node.label, "Audio sink received empty input, skipping delivery");
+        return Ok(NodeOutput::Empty);
+    }
+
+    let provider = TtsProvider::from_config(&node.config)?;
+    let mp3 = provider
+        .synthesize(&deps.http_client, &text)
+        .await
+        .with_context(|| format!("sink '{}' speech synthesis failed", node.label))?;
+    let flow_name = deps.flow_name.as_deref().unwrap_or("Digest");
+    let title = format!(
+        "{} — {}",
+        node.config["title"].as_str().unwrap_or(flow_name),
+        Utc::now().format("%Y-%m-%d")
+    );
+
+    if to_feed {
+        let store = deps.audio.as_ref().context("audio feed requires the audio store")?;
+        let keep = node.config["keep_episodes"].as_u64().map_or(DEFAULT_KEEP_EPISODES, |n| n as usize);
+        store.add(&deps.flow_id, &title, deps.run_id.as_deref(), &mp3, keep)?;
+    }
+    if let Some(token_env) = token_env {
+        let sink = slack_api_sink(
+            &deps.http_client,
+            token_env,
+            node.config["channel"].as_str(),
+            node.config["user_email"].as_str(),
+        )?;
+        let filename = format!("{}.mp3", title.replace([' ', '/'], "-"));
+        sink.upload_file(&filename, &title, mp3.clone(), node.config["comment"].as_str())
+            .await
+            .with_context(|| format!("sink '{}' delivery failed", node.label))?;
+    }
+
+    tracing::info!(node = %node.label, bytes = mp3.len(), feed = to_feed, "Audio digest delivered");
+    Ok(NodeOutput::Empty)
+}
+
+/// Rank the shared items against each subscriber's interests and queue
+/// their own digest through their subscription's delivery.
+fn deliver_personalized_digests(node: &Node, input: &NodeOutput, deps: &NodeDeps) -> Result<NodeOutput> {
+    let store = deps
+        .subscriptions
+        .as_ref()
+        .context("personalized-digest requires the subscription store")?;
+    let items = input.as_items();
+    if items.is_empty() {
+        tracing::warn!(node = %node.label, "Personalized digest received no items, skipping delivery");
+        return Ok(NodeOutput::Empty);
+    }
+
+    let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+    let min_score = node.config["min_score"].as_u64().unwrap_or(1) as usize;
+    let flow_name = deps.flow_name.as_deref().unwrap_or("Digest");
+    let title = node.config["title"].as_str().unwrap_or(flow_name);
+
+    let mut variants = Vec::new();
+    for sub in store.active(&deps.flow_id) {
+        let picked = personalize(&items, &sub.interests, min_score, limit);
+        if picked.is_empty() {
+            tracing::info!(node = %node.label, subscriber = %sub.subscriber, "No items match interests, skipping");
+            continue;
+        }
+        let text = format!("## {title}\n\n{}", format_items(&picked));
+        variants.push((sub, text));
+    }
+
+    let queued = store.enqueue(flow_name, deps.run_id.as_deref().unwrap_or_default(), variants)?;
+    tracing::info!(node = %node.label, queued, "Queued personalized digests");
+    if queued > 0 {
+        let store = store.clone();
+        let http_client = Arc::clone(&deps.http_client);
+        tokio::spawn(async move { store.deliver_due(&http_client).await });
+    }
+    Ok(NodeOutput::Empty)
+}
+
+/// Post items with 👍/👎 buttons and remember them, so the votes can steer
+/// `feedback-rank` nodes in later runs.
+async fn deliver_feedback_digest(node: &Node, items: &[ContentItem], deps: &NodeDeps) -> Result<NodeOutput> {
+    let store = deps
+        .feedback
+        .as_ref()
+        .context("slack feedback requires the feedback store")?;
+    if items.is_empty() {
+        tracing::warn!(node = %node.label, "Feedback digest received no items, skipping delivery");
+        return Ok(NodeOutput::Empty);
+    }
+    let token_env = node.config["bot_token_env"]
+        .as_str()
+        .context("slack feedback buttons require bot_token_env")?;
+    let sink = slack_api_sink(
+        &deps.http_client,
+        token_env,
+        node.config["channel"].as_str(),
+        node.config["user_email"].as_str(),
+    )?;
+    let title = node.config["title"].as_str().or(deps.flow_name.as_deref());
+
+    sink.deliver_feedback_digest(title, &deps.flow_id, items)
+        .await
+        .with_context(|| format!("sink '{}' delivery failed", node.label))?;
+    store.record_items(&deps.flow_id, items)?;
+
+    tracing::info!(node = %node.label, items = items.len(), "Feedback digest delivered");
+    Ok(NodeOutput::Empty)
+}
+
 // ── Flow-run session helpers ──────────────────────────────────────────
 
 /// Create a flow-run session in the agent's session pool and return a LineSink
@@ -477,7 +1308,12 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                             .collect()
                     })
                     .unwrap_or_default();
-                SourceConfig::WebScrape { url, keywords }
+                let sandboxed = node.config["sandboxed"].as_bool().unwrap_or(false);
+                SourceConfig::WebScrape {
+                    url,
+                    keywords,
+                    sandboxed,
+                }
             }
             "github-merged-prs" => {
                 let repos = node.config["repos"]
@@ -505,6 +1341,19 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                 let date_selector = node.config["date_selector"].as_str().map(String::from);
                 let date_format = node.config["date_format"].as_str().map(String::from);
                 let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                let next_page_selector = node.config["next_page_selector"].as_str().map(String::from);
+                let max_pages = node.config["max_pages"].as_u64().unwrap_or(1) as usize;
+                let cookies_env = node.config["cookies_env"].as_str().map(String::from);
+                let login = match &node.config["login"] {
+                    serde_json::Value::Null => None,
+                    v => Some(
+                        serde_json::from_value(v.clone())
+                            .context("web-scraper node has invalid 'login'")?,
+                    ),
+                };
+                let sandboxed = node.config["sandboxed"].as_bool().unwrap_or(false);
+                let render = node.config["render"].as_bool().unwrap_or(false);
+                let wait_for_selector = node.config["wait_for_selector"].as_str().map(String::from);
                 SourceConfig::WebScraper {
                     url,
                     base_url,
@@ -515,6 +1364,13 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                     date_selector,
                     date_format,
                     limit,
+                    next_page_selector,
+                    max_pages,
+                    cookies_env,
+                    login,
+                    sandboxed,
+                    render,
+                    wait_for_selector,
                 }
             }
             "google-sheets" => {
@@ -534,10 +1390,265 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                     limit,
                 }
             }
+            "weather" => {
+                let latitude = node.config["latitude"]
+                    .as_f64()
+                    .context("weather node missing 'latitude'")?;
+                let longitude = node.config["longitude"]
+                    .as_f64()
+                    .context("weather node missing 'longitude'")?;
+                let location = node.config["location"].as_str().map(String::from);
+                let days = node.config["days"].as_u64().unwrap_or(1) as u32;
+                SourceConfig::Weather {
+                    latitude,
+                    longitude,
+                    location,
+                    days,
+                }
+            }
+            "market-quotes" => {
+                let symbols: Vec<String> = node.config["symbols"]
+                    .as_array()
+                    .context("market-quotes node missing 'symbols'")?
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect();
+                if symbols.is_empty() {
+                    bail!("market-quotes node has no symbols configured");
+                }
+                SourceConfig::MarketQuotes { symbols }
+            }
+            "sql" => {
+                let connection_env = node.config["connection_env"]
+                    .as_str()
+                    .context("sql node missing 'connection_env'")?
+                    .to_string();
+                let query = node.config["query"]
+                    .as_str()
+                    .context("sql node missing 'query'")?
+                    .to_string();
+                let title_column = node.config["title_column"]
+                    .as_str()
+                    .context("sql node missing 'title_column'")?
+                    .to_string();
+                let summary_column = node.config["summary_column"].as_str().map(String::from);
+                SourceConfig::Sql {
+                    connection_env,
+                    query,
+                    title_column,
+                    summary_column,
+                }
+            }
+            "sentry-issues" => {
+                let token_env = node.config["token_env"]
+                    .as_str()
+                    .unwrap_or("SENTRY_AUTH_TOKEN")
+                    .to_string();
+                let organization = node.config["organization"]
+                    .as_str()
+                    .context("sentry-issues node missing 'organization'")?
+                    .to_string();
+                let project = node.config["project"]
+                    .as_str()
+                    .context("sentry-issues node missing 'project'")?
+                    .to_string();
+                let base_url = node.config["base_url"].as_str().map(String::from);
+                let since_hours = node.config["since_hours"].as_u64().unwrap_or(24);
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::SentryIssues {
+                    token_env,
+                    organization,
+                    project,
+                    base_url,
+                    since_hours,
+                    limit,
+                }
+            }
+            "web-interactive" => {
+                let url = node.config["url"]
+                    .as_str()
+                    .context("web-interactive node missing 'url'")?
+                    .to_string();
+                let webdriver_url_env = node.config["webdriver_url_env"]
+                    .as_str()
+                    .unwrap_or("WEBDRIVER_URL")
+                    .to_string();
+                let steps = browser::parse_steps(&node.config["steps"])
+                    .context("web-interactive node has invalid 'steps'")?;
+                let base_url = node.config["base_url"].as_str().map(String::from);
+                let items_selector = node.config["items_selector"]
+                    .as_str()
+                    .context("web-interactive node missing 'items_selector'")?
+                    .to_string();
+                let title_selector = node.config["title_selector"].as_str().map(String::from);
+                let url_selector = node.config["url_selector"].as_str().map(String::from);
+                let summary_selector = node.config["summary_selector"].as_str().map(String::from);
+                let date_selector = node.config["date_selector"].as_str().map(String::from);
+                let date_format = node.config["date_format"].as_str().map(String::from);
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::WebInteractive {
+                    url,
+                    webdriver_url_env,
+                    steps,
+                    base_url,
+                    items_selector,
+                    title_selector,
+                    url_selector,
+                    summary_selector,
+                    date_selector,
+                    date_format,
+                    limit,
+                }
+            }
+            "file-watch" => {
+                let pattern = node.config["pattern"]
+                    .as_str()
+                    .context("file-watch node missing 'pattern'")?
+                    .to_string();
+                let since_hours = node.config["since_hours"].as_u64().unwrap_or(24);
+                let snippet_chars = node.config["snippet_chars"].as_u64().unwrap_or(0) as usize;
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::FileWatch {
+                    pattern,
+                    since_hours,
+                    snippet_chars,
+                    limit,
+                }
+            }
+            "arxiv" => {
+                let categories = string_array(&node.config["categories"]);
+                let keywords = string_array(&node.config["keywords"]);
+                if categories.is_empty() && keywords.is_empty() {
+                    bail!("arxiv node needs 'categories' or 'keywords'");
+                }
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::Arxiv {
+                    categories,
+                    keywords,
+                    limit,
+                }
+            }
+            "stackexchange" => {
+                let tags = string_array(&node.config["tags"]);
+                if tags.is_empty() {
+                    bail!("stackexchange node missing 'tags'");
+                }
+                let site = node.config["site"]
+                    .as_str()
+                    .unwrap_or("stackoverflow")
+                    .to_string();
+                let min_score = node.config["min_score"].as_i64().unwrap_or(0);
+                let min_answers = node.config["min_answers"].as_u64().unwrap_or(0);
+                let unanswered = node.config["unanswered"].as_bool().unwrap_or(false);
+                let since_days = node.config["since_days"].as_u64().unwrap_or(7);
+                let key_env = node.config["key_env"].as_str().map(String::from);
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::StackExchange {
+                    tags,
+                    site,
+                    min_score,
+                    min_answers,
+                    unanswered,
+                    since_days,
+                    key_env,
+                    limit,
+                }
+            }
+            "github-activity" => {
+                let users: Vec<String> = string_array(&node.config["users"]);
+                if users.is_empty() {
+                    bail!("github-activity node missing 'users'");
+                }
+                SourceConfig::GithubActivity {
+                    users,
+                    repos: string_array(&node.config["repos"]),
+                    orgs: string_array(&node.config["orgs"]),
+                    since_hours: node.config["since_hours"].as_u64().unwrap_or(24),
+                }
+            }
+            "github-repo-health" => {
+                let repos = node.config["repos"]
+                    .as_array()
+                    .context("github-repo-health node missing 'repos'")?
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect();
+                let signals: Vec<String> = node.config["signals"]
+                    .as_array()
+                    .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default();
+                for signal in &signals {
+                    repo_health::Signal::parse_name(signal)?;
+                }
+                let stale_days = node.config["stale_days"].as_i64().unwrap_or(30);
+                let response_days = node.config["response_days"].as_i64().unwrap_or(7);
+                SourceConfig::GithubRepoHealth {
+                    repos,
+                    signals,
+                    stale_days,
+                    response_days,
+                }
+            }
+            "ci-report" => {
+                let path = node.config["path"]
+                    .as_str()
+                    .context("ci-report node missing 'path'")?
+                    .to_string();
+                let format = node.config["format"]
+                    .as_str()
+                    .context("ci-report node missing 'format'")?
+                    .to_string();
+                ReportFormat::parse_name(&format)?;
+                let token_env = node.config["token_env"].as_str().map(String::from);
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::CiReport {
+                    path,
+                    format,
+                    token_env,
+                    limit,
+                }
+            }
+            "pocket" => {
+                let consumer_key_env = node.config["consumer_key_env"]
+                    .as_str()
+                    .unwrap_or("POCKET_CONSUMER_KEY")
+                    .to_string();
+                let access_token_env = node.config["access_token_env"]
+                    .as_str()
+                    .unwrap_or("POCKET_ACCESS_TOKEN")
+                    .to_string();
+                let tag = node.config["tag"].as_str().map(String::from);
+                let since_days = node.config["since_days"].as_u64().unwrap_or(7);
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::Pocket {
+                    consumer_key_env,
+                    access_token_env,
+                    tag,
+                    since_days,
+                    limit,
+                }
+            }
+            "readwise" => {
+                let token_env = node.config["token_env"]
+                    .as_str()
+                    .unwrap_or("READWISE_TOKEN")
+                    .to_string();
+                let location = node.config["location"].as_str().map(String::from);
+                let since_days = node.config["since_days"].as_u64().unwrap_or(7);
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                SourceConfig::Readwise {
+                    token_env,
+                    location,
+                    since_days,
+                    limit,
+                }
+            }
             "market-data" => {
                 // Market data is handled specially via template variable
                 continue;
             }
+            // Searches the vector store in process_source
+            VECTOR_SEARCH_KIND => continue,
             other => bail!("unknown source kind: {other}"),
         };
         configs.push(config);
@@ -553,6 +1664,7 @@ pub fn parse_sink_configs(nodes: &[&Node]) -> Result<Vec<SinkConfig>> {
                 webhook_url_env: node.config["webhook_url_env"].as_str().map(String::from),
                 bot_token_env: node.config["bot_token_env"].as_str().map(String::from),
                 channel: node.config["channel"].as_str().map(String::from),
+                user_email: node.config["user_email"].as_str().map(String::from),
             },
             "notion" => SinkConfig::Notion {
                 token_env: node.config["token_env"]
@@ -564,6 +1676,47 @@ pub fn parse_sink_configs(nodes: &[&Node]) -> Result<Vec<SinkConfig>> {
                     .context("notion node missing 'database_id'")?
                     .to_string(),
             },
+            "pocket" => SinkConfig::Pocket {
+                consumer_key_env: node.config["consumer_key_env"]
+                    .as_str()
+                    .unwrap_or("POCKET_CONSUMER_KEY")
+                    .to_string(),
+                access_token_env: node.config["access_token_env"]
+                    .as_str()
+                    .unwrap_or("POCKET_ACCESS_TOKEN")
+                    .to_string(),
+                tags: string_array(&node.config["tags"]),
+            },
+            "email" => {
+                let to = string_array(&node.config["to"]);
+                if to.is_empty() {
+                    bail!("email node missing 'to'");
+                }
+                SinkConfig::Email {
+                    smtp_url_env: node.config["smtp_url_env"]
+                        .as_str()
+                        .map(String::from)
+                        .unwrap_or_else(crate::config::default_smtp_url_env),
+                    from_env: node.config["from_env"]
+                        .as_str()
+                        .map(String::from)
+                        .unwrap_or_else(crate::config::default_smtp_from_env),
+                    to,
+                    subject: node.config["subject"].as_str().map(String::from),
+                }
+            }
+            "readwise" => SinkConfig::Readwise {
+                token_env: node.config["token_env"]
+                    .as_str()
+                    .unwrap_or("READWISE_TOKEN")
+                    .to_string(),
+                tags: string_array(&node.config["tags"]),
+            },
+            "desktop" => SinkConfig::Desktop {
+                title: node.config["title"].as_str().map(String::from),
+                sound: node.config["sound"].as_bool().unwrap_or(false),
+            },
+            PERSONALIZED_DIGEST_KIND | VECTOR_STORE_KIND | AUDIO_KIND => continue,
             other => bail!("unknown sink kind: {other}"),
         };
         configs.push(config);
@@ -571,6 +1724,38 @@ pub fn parse_sink_configs(nodes: &[&Node]) -> Result<Vec<SinkConfig>> {
     Ok(configs)
 }
 
+fn string_array(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+/// The executor's prompt template, in order of precedence: the `prompt_ab`
+/// variant picked for `run_id` (variant `a` outside a run), the library
+/// prompt named by `prompt_ref`, or the inline/file `prompt`.
+pub async fn executor_prompt_template(
+    node: &Node,
+    prompt_repo: Option<&dyn PromptRepository>,
+    run_id: Option<&str>,
+) -> Result<String> {
+    let split = PromptSplit::from_node(node);
+    let reference = match (&split, run_id) {
+        (Some(split), Some(run_id)) => Some(split.choose(run_id)),
+        (Some(split), None) => Some(split.a.as_str()),
+        (None, _) => node.config["prompt_ref"].as_str().filter(|s| !s.is_empty()),
+    };
+    if let Some(reference) = reference {
+        let repo = prompt_repo.context("prompt_ref used but no prompt library is available")?;
+        return resolve_prompt_ref(repo, reference).await;
+    }
+
+    let prompt_path = node.config["prompt"]
+        .as_str()
+        .context("executor node missing 'prompt' or 'prompt_ref' config")?;
+    load_prompt_template(prompt_path)
+}
+
 pub fn load_prompt_template(prompt_path: &str) -> Result<String> {
     if prompt_path.ends_with(".md")
         || prompt_path.ends_with(".txt")
@@ -582,3 +1767,135 @@ pub fn load_prompt_template(prompt_path: &str) -> Result<String> {
         Ok(prompt_path.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::{NodeType, Position};
+
+    fn executor(config: serde_json::Value) -> Node {
+        Node {
+            id: "e1".into(),
+            node_type: NodeType::Executor,
+            kind: "claude-code".into(),
+            config,
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Review".into(),
+        }
+    }
+
+    #[test]
+    fn test_run_when() {
+        let ctx = NodeOutput::Context(HashMap::from([("risk_level".to_string(), "high".to_string())]));
+
+        assert!(run_when_matches(&executor(serde_json::json!({})), &ctx));
+        assert!(run_when_matches(&executor(serde_json::json!({ "run_when": { "risk_level": ["high", "medium"] } })), &ctx));
+        assert!(run_when_matches(&executor(serde_json::json!({ "run_when": { "risk_level": "high" } })), &ctx));
+        assert!(!run_when_matches(&executor(serde_json::json!({ "run_when": { "risk_level": ["low"] } })), &ctx));
+        // Missing variables never match
+        assert!(!run_when_matches(&executor(serde_json::json!({ "run_when": { "review_type": "initial" } })), &ctx));
+        assert!(!run_when_matches(&executor(serde_json::json!({ "run_when": { "risk_level": "high" } })), &NodeOutput::Empty));
+    }
+
+    /// Answers with a fixed reply per model; a missing reply fails.
+    struct Scripted(Option<&'static str>);
+
+    #[async_trait::async_trait]
+    impl Executor for Scripted {
+        async fn execute(&self, _prompt: &str, _working_dir: &std::path::Path) -> Result<ExecutionResult> {
+            let text = self.0.context("model unavailable")?;
+            Ok(ExecutionResult { text: text.to_string(), cost_usd: 0.1, num_turns: 1, attempts: Vec::new() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_chain_escalates() {
+        let node = executor(serde_json::json!({
+            "model": "haiku",
+            "fallback_models": ["sonnet", "openai:gpt-4o"],
+            "min_confidence": 0.7
+        }));
+        let chain = ModelChain::from_config(&node.config).unwrap();
+        let run = |replies: [Option<&'static str>; 3]| {
+            let chain = &chain;
+            let node = &node;
+            async move {
+                let build = |choice: &ModelChoice| -> Result<Box<dyn Executor>> {
+                    let i = ["haiku", "sonnet", "gpt-4o"]
+                        .iter()
+                        .position(|m| Some(*m) == choice.model.as_deref())
+                        .unwrap();
+                    Ok(Box::new(Scripted(replies[i])))
+                };
+                execute_chain(node, "claude-code", chain, build, "p", std::path::Path::new("."), None).await
+            }
+        };
+
+        let result = run([Some("fine. Confidence: 0.9"), None, None]).await.unwrap();
+        assert_eq!(result.text, "fine. Confidence: 0.9");
+        assert_eq!(result.num_turns, 1);
+
+        assert_eq!(result.attempts.len(), 1);
+        assert_eq!(result.attempts[0].model, "claude-code:haiku");
+
+        // haiku fails, sonnet is unsure, gpt-4o answers; every attempt's spend counts
+        let result = run([None, Some("Confidence: 40%"), Some("sure. Confidence: 95%")]).await.unwrap();
+        assert_eq!(result.text, "sure. Confidence: 95%");
+        assert_eq!(result.num_turns, 2);
+        assert!((result.cost_usd - 0.2).abs() < 1e-9);
+        let models: Vec<&str> = result.attempts.iter().map(|a| a.model.as_str()).collect();
+        assert_eq!(models, ["claude-code:haiku", "claude-code:sonnet", "openai:gpt-4o"]);
+        assert!(result.attempts[0].error.is_some());
+        assert_eq!(result.attempts[1].error.as_deref(), Some("low confidence (0.4)"));
+        assert!(result.attempts[2].error.is_none());
+
+        // The last model's answer stands even when unsure
+        let result = run([Some("Confidence: 0.1"), Some("Confidence: 0.2"), Some("Confidence: 0.3")]).await.unwrap();
+        assert_eq!(result.text, "Confidence: 0.3");
+        assert_eq!(result.num_turns, 3);
+
+        assert!(run([None, None, None]).await.is_err());
+    }
+
+    #[test]
+    fn test_for_each_config() {
+        assert_eq!(ForEach::from_config(&serde_json::json!({})).unwrap(), None);
+        let for_each = ForEach::from_config(&serde_json::json!({ "for_each": true })).unwrap().unwrap();
+        assert_eq!(for_each.concurrency, ForEach::DEFAULT_CONCURRENCY);
+        assert!(for_each.item_headers);
+        let for_each = ForEach::from_config(&serde_json::json!({ "for_each": true, "concurrency": 1 })).unwrap();
+        assert_eq!(for_each.unwrap().concurrency, 1);
+        assert!(ForEach::from_config(&serde_json::json!({ "for_each": true, "concurrency": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_for_each_aggregate() {
+        let item = |title: &str, url: &str| ContentItem {
+            title: title.into(),
+            url: url.into(),
+            summary: String::new(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        };
+        let items = [item("A", "https://a"), item("B", ""), item("C", "https://c")];
+        let ok = |text: &str| Ok(ExecutionResult { text: text.into(), cost_usd: 0.1, num_turns: 1, attempts: Vec::new() });
+        let for_each = ForEach { concurrency: 2, item_headers: true };
+        let node = executor(serde_json::json!({}));
+
+        // A failed item is left out; the rest keep their order
+        let result = for_each
+            .aggregate(&node, &items, vec![ok("bug\n"), Err(anyhow::anyhow!("boom")), ok("feature")])
+            .unwrap();
+        assert_eq!(result.text, "## A\nhttps://a\n\nbug\n\n## C\nhttps://c\n\nfeature");
+        assert_eq!(result.num_turns, 2);
+        assert!((result.cost_usd - 0.2).abs() < 1e-9);
+
+        let for_each = ForEach { item_headers: false, ..for_each };
+        let result = for_each.aggregate(&node, &items[1..], vec![ok("x"), ok("y")]).unwrap();
+        assert_eq!(result.text, "x\n\ny");
+
+        let failed = for_each.aggregate(&node, &items[..1], vec![Err(anyhow::anyhow!("boom"))]);
+        assert!(failed.is_err());
+    }
+}
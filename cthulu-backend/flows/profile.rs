@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::flows::Flow;
+use crate::flows::history::{FlowRun, RunStatus};
+
+/// Where a flow's time and money go, over its run history, for
+/// `GET /api/flows/{id}/profile`. Shares are of the time (or cost) summed
+/// over every node run, so nodes running side by side each count in full.
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub runs: usize,
+    pub node_secs: f64,
+    pub cost_usd: f64,
+    /// Slowest first.
+    pub nodes: Vec<NodeProfile>,
+    /// Slowest first.
+    pub kinds: Vec<KindProfile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeProfile {
+    pub node_id: String,
+    pub label: String,
+    pub kind: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub total_secs: f64,
+    pub avg_secs: f64,
+    pub max_secs: f64,
+    pub cost_usd: f64,
+    pub time_share: f64,
+    pub cost_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KindProfile {
+    pub kind: String,
+    pub runs: usize,
+    pub total_secs: f64,
+    pub cost_usd: f64,
+    pub time_share: f64,
+    pub cost_share: f64,
+}
+
+/// Profile `flow` over `runs`; node runs still going are left out.
+pub fn profile(flow: &Flow, runs: &[FlowRun]) -> Profile {
+    let mut nodes: Vec<NodeProfile> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for node_run in runs.iter().flat_map(|run| &run.node_runs) {
+        let Some(finished_at) = node_run.finished_at else {
+            continue;
+        };
+        let i = *index.entry(&node_run.node_id).or_insert_with(|| {
+            let node = flow.nodes.iter().find(|n| n.id == node_run.node_id);
+            nodes.push(NodeProfile {
+                node_id: node_run.node_id.clone(),
+                label: node.map_or_else(|| node_run.node_id.clone(), |n| n.label.clone()),
+                kind: node.map_or_else(|| "removed".to_string(), |n| n.kind.clone()),
+                runs: 0,
+                failures: 0,
+                total_secs: 0.0,
+                avg_secs: 0.0,
+                max_secs: 0.0,
+                cost_usd: 0.0,
+                time_share: 0.0,
+                cost_share: 0.0,
+            });
+            nodes.len() - 1
+        });
+        let secs = (finished_at - node_run.started_at).num_milliseconds().max(0) as f64 / 1000.0;
+        let entry = &mut nodes[i];
+        entry.runs += 1;
+        if node_run.status != RunStatus::Success {
+            entry.failures += 1;
+        }
+        entry.total_secs += secs;
+        entry.max_secs = entry.max_secs.max(secs);
+        entry.cost_usd += node_run.cost_usd.unwrap_or(0.0);
+    }
+
+    let node_secs: f64 = nodes.iter().map(|n| n.total_secs).sum();
+    let cost_usd: f64 = nodes.iter().map(|n| n.cost_usd).sum();
+    let share = |part: f64, whole: f64| if whole > 0.0 { part / whole } else { 0.0 };
+
+    let mut kinds: Vec<KindProfile> = Vec::new();
+    for node in &mut nodes {
+        node.avg_secs = node.total_secs / node.runs as f64;
+        node.time_share = share(node.total_secs, node_secs);
+        node.cost_share = share(node.cost_usd, cost_usd);
+        match kinds.iter_mut().find(|k| k.kind == node.kind) {
+            Some(kind) => {
+                kind.runs += node.runs;
+                kind.total_secs += node.total_secs;
+                kind.cost_usd += node.cost_usd;
+            }
+            None => kinds.push(KindProfile {
+                kind: node.kind.clone(),
+                runs: node.runs,
+                total_secs: node.total_secs,
+                cost_usd: node.cost_usd,
+                time_share: 0.0,
+                cost_share: 0.0,
+            }),
+        }
+    }
+    for kind in &mut kinds {
+        kind.time_share = share(kind.total_secs, node_secs);
+        kind.cost_share = share(kind.cost_usd, cost_usd);
+    }
+    nodes.sort_by(|a, b| b.total_secs.total_cmp(&a.total_secs));
+    kinds.sort_by(|a, b| b.total_secs.total_cmp(&a.total_secs));
+
+    Profile { runs: runs.len(), node_secs, cost_usd, nodes, kinds }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::NodeRun;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_profile_shares() {
+        let flow: Flow = serde_json::from_value(serde_json::json!({
+            "id": "f",
+            "name": "Digest",
+            "nodes": [
+                { "id": "hn", "node_type": "source", "kind": "web-scrape", "config": {}, "position": { "x": 0, "y": 0 }, "label": "HN" },
+                { "id": "blog", "node_type": "source", "kind": "web-scrape", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Blog" },
+                { "id": "sum", "node_type": "executor", "kind": "claude-code", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Summarize" }
+            ],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        let t0 = Utc::now();
+        let node_run = |node_id: &str, secs: i64, cost_usd: Option<f64>, finished: bool| NodeRun {
+            node_id: node_id.into(),
+            status: if finished { RunStatus::Success } else { RunStatus::Running },
+            started_at: t0,
+            finished_at: finished.then(|| t0 + Duration::seconds(secs)),
+            output_preview: None,
+            prompt_variant: None,
+            cost_usd,
+            attempts: Vec::new(),
+            level: None,
+            ready_at: None,
+        };
+        let run = |node_runs: Vec<NodeRun>| FlowRun {
+            id: "r".into(),
+            flow_id: "f".into(),
+            status: RunStatus::Success,
+            started_at: t0,
+            finished_at: None,
+            node_runs,
+            error: None,
+            output: None,
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
+        };
+        let runs = [
+            run(vec![node_run("hn", 60, None, true), node_run("blog", 2, None, true), node_run("sum", 18, Some(0.03), true)]),
+            run(vec![node_run("hn", 100, None, true), node_run("blog", 2, None, true), node_run("sum", 10, Some(0.01), false)]),
+        ];
+
+        let profile = profile(&flow, &runs);
+        assert_eq!(profile.runs, 2);
+        assert_eq!(profile.node_secs, 182.0);
+        let slowest = &profile.nodes[0];
+        assert_eq!((slowest.label.as_str(), slowest.runs, slowest.avg_secs, slowest.max_secs), ("HN", 2, 80.0, 100.0));
+        assert!((slowest.time_share - 160.0 / 182.0).abs() < 1e-9);
+        // The unfinished run of Summarize isn't counted
+        let summarize = profile.nodes.iter().find(|n| n.node_id == "sum").unwrap();
+        assert_eq!((summarize.runs, summarize.cost_share), (1, 1.0));
+        assert_eq!(profile.kinds[0].kind, "web-scrape");
+        assert_eq!(profile.kinds[0].total_secs, 164.0);
+    }
+}
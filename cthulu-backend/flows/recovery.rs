@@ -0,0 +1,399 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::flows::history::{FlowRun, MAX_RUNS_PER_FLOW, RunStatus};
+use crate::flows::repository::FlowRepository;
+use crate::flows::wait::{Checkpoint, WaitStore};
+use crate::flows::{Flow, NodeType};
+use crate::sandbox::provider::SandboxProvider;
+
+/// Error recorded on runs the server stopped in the middle of.
+pub const INTERRUPTED_ERROR: &str = "interrupted: the server stopped before the run finished";
+
+/// Error recorded on runs still going when a shutdown's grace period ran out.
+pub const SHUTDOWN_ERROR: &str = "interrupted: the server shut down before the run finished";
+
+/// Workspace ids of sandboxes that only live for one call — executor runs,
+/// PR checks and sandboxed fetches. Any still around at startup were left
+/// by runs that died with the server.
+const EPHEMERAL_WORKSPACES: [&str; 3] = ["exec-", "pr-check-", "fetch-"];
+
+/// What happens to a flow's runs that the server stopped mid-way, from its
+/// trigger's `on_interrupt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnInterrupt {
+    /// Record the run as interrupted and leave it at that.
+    Mark,
+    /// Also start it over with the same inputs, once — a rerun that is
+    /// itself interrupted is only marked.
+    Rerun,
+}
+
+impl OnInterrupt {
+    pub fn for_flow(flow: &Flow) -> Result<Self> {
+        match flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) {
+            Some(trigger) => Self::from_trigger(&trigger.config),
+            None => Self::from_trigger(&Value::Null),
+        }
+    }
+
+    pub fn from_trigger(config: &Value) -> Result<Self> {
+        match config["on_interrupt"].as_str() {
+            None | Some("mark") => Ok(Self::Mark),
+            Some("rerun") => Ok(Self::Rerun),
+            Some(other) => anyhow::bail!("on_interrupt must be \"mark\" or \"rerun\", got '{other}'"),
+        }
+    }
+}
+
+/// Whether `run` belongs to one of the `live` replicas, which is still
+/// running it.
+fn runs_elsewhere(run: &FlowRun, live: &[String]) -> bool {
+    run.replica.as_ref().is_some_and(|replica| live.contains(replica))
+}
+
+/// Runs the server stopped while they were held at wait nodes, with the
+/// checkpoints to resume them from. Checkpoints are taken out of `waits`;
+/// those of runs no longer running, or whose flow is gone or disabled, are
+/// dropped, leaving the run to be interrupted. Runs of the `live` replicas
+/// are still waiting there, and their checkpoints are left alone.
+pub async fn held_runs(
+    repo: &dyn FlowRepository,
+    waits: &WaitStore,
+    live: &[String],
+) -> Vec<(Flow, FlowRun, Vec<Checkpoint>)> {
+    let mut run_ids: Vec<(String, String)> = waits.list().into_iter().map(|c| (c.run_id, c.flow_id)).collect();
+    run_ids.sort();
+    run_ids.dedup();
+    let mut held = Vec::new();
+    for (run_id, flow_id) in run_ids {
+        let owned_elsewhere = repo
+            .get_runs(&flow_id, MAX_RUNS_PER_FLOW)
+            .await
+            .iter()
+            .any(|r| r.id == run_id && runs_elsewhere(r, live));
+        if owned_elsewhere {
+            continue;
+        }
+        let checkpoints = match waits.take_run(&run_id) {
+            Ok(checkpoints) if !checkpoints.is_empty() => checkpoints,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::warn!(run_id = %run_id, error = %e, "Failed to read wait checkpoints");
+                continue;
+            }
+        };
+        let flow_id = &checkpoints[0].flow_id;
+        let Some(flow) = repo.get_flow(flow_id).await.filter(|f| f.enabled) else {
+            tracing::warn!(run_id = %run_id, "Dropping wait of a deleted or disabled flow");
+            continue;
+        };
+        let run = repo
+            .get_runs(flow_id, MAX_RUNS_PER_FLOW)
+            .await
+            .into_iter()
+            .find(|r| r.id == run_id && r.status == RunStatus::Running);
+        match run {
+            Some(run) => held.push((flow, run, checkpoints)),
+            None => tracing::warn!(run_id = %run_id, "Dropping wait of a run that is no longer running"),
+        }
+    }
+    held
+}
+
+/// Mark every run still `running` in the store as interrupted, along with
+/// its unfinished nodes, except the `held` ones to be resumed and those of
+/// the `live` replicas. Call before anything starts new runs. Returns the
+/// runs to start over, per their flow's `on_interrupt`.
+pub async fn interrupt_stale_runs(repo: &dyn FlowRepository, held: &[String], live: &[String]) -> Vec<(Flow, FlowRun)> {
+    let mut reruns = Vec::new();
+    for flow in repo.list_flows().await {
+        let stale: Vec<FlowRun> = repo
+            .get_runs(&flow.id, MAX_RUNS_PER_FLOW)
+            .await
+            .into_iter()
+            .filter(|run| run.status == RunStatus::Running && !held.contains(&run.id) && !runs_elsewhere(run, live))
+            .collect();
+        for run in stale {
+            if !mark_interrupted(repo, &flow, &run, INTERRUPTED_ERROR).await {
+                continue;
+            }
+            if should_rerun(&flow, &run) {
+                reruns.push((flow.clone(), run));
+            }
+        }
+    }
+    reruns
+}
+
+/// On shutdown, mark the runs in `run_ids` interrupted, with their
+/// unfinished nodes — except those their flow's `on_interrupt` says to start
+/// over, left `running` for the next startup to rerun. Returns how many
+/// were marked.
+pub async fn interrupt_on_shutdown(repo: &dyn FlowRepository, run_ids: &[String]) -> usize {
+    let mut marked = 0;
+    for flow in repo.list_flows().await {
+        let running: Vec<FlowRun> = repo
+            .get_runs(&flow.id, MAX_RUNS_PER_FLOW)
+            .await
+            .into_iter()
+            .filter(|run| run.status == RunStatus::Running && run_ids.contains(&run.id))
+            .collect();
+        for run in running {
+            if !should_rerun(&flow, &run) && mark_interrupted(repo, &flow, &run, SHUTDOWN_ERROR).await {
+                marked += 1;
+            }
+        }
+    }
+    marked
+}
+
+/// Record `run` and its running nodes as interrupted; `false` if the run
+/// couldn't be.
+async fn mark_interrupted(repo: &dyn FlowRepository, flow: &Flow, run: &FlowRun, error: &str) -> bool {
+    for node_run in run.node_runs.iter().filter(|nr| nr.status == RunStatus::Running) {
+        if let Err(e) = repo
+            .complete_node_run(&flow.id, &run.id, &node_run.node_id, RunStatus::Interrupted, None)
+            .await
+        {
+            tracing::warn!(run_id = %run.id, error = %e, "Failed to mark node run interrupted");
+        }
+    }
+    if let Err(e) = repo.complete_run(&flow.id, &run.id, RunStatus::Interrupted, Some(error.to_string())).await {
+        tracing::warn!(run_id = %run.id, error = %e, "Failed to mark run interrupted");
+        return false;
+    }
+    tracing::warn!(flow = %flow.name, run_id = %run.id, "Marked run interrupted");
+    true
+}
+
+fn should_rerun(flow: &Flow, run: &FlowRun) -> bool {
+    let policy = OnInterrupt::for_flow(flow).unwrap_or_else(|e| {
+        tracing::warn!(flow = %flow.name, error = %e, "Invalid on_interrupt, not rerunning");
+        OnInterrupt::Mark
+    });
+    flow.enabled && policy == OnInterrupt::Rerun && run.rerun_of.is_none() && !run.dry_run
+}
+
+/// Destroy sandboxes that interrupted runs left behind, and the diff
+/// directories of interrupted PR reviews.
+pub async fn reap_orphans(provider: &dyn SandboxProvider) {
+    match provider.list().await {
+        Ok(sandboxes) => {
+            for sandbox in sandboxes
+                .iter()
+                .filter(|s| EPHEMERAL_WORKSPACES.iter().any(|p| s.workspace_id.starts_with(p)))
+            {
+                let destroyed = match provider.attach(&sandbox.id).await {
+                    Ok(handle) => handle.destroy().await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                match destroyed {
+                    Ok(()) => tracing::info!(sandbox = %sandbox.id, "Destroyed orphaned sandbox"),
+                    Err(e) => tracing::warn!(sandbox = %sandbox.id, error = %e, "Failed to destroy orphaned sandbox"),
+                }
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "Failed to list sandboxes for orphan cleanup"),
+    }
+
+    let review_dir = crate::tasks::diff::review_dir();
+    if review_dir.exists() {
+        match std::fs::remove_dir_all(&review_dir) {
+            Ok(()) => tracing::info!(path = %review_dir.display(), "Removed leftover review diffs"),
+            Err(e) => tracing::warn!(path = %review_dir.display(), error = %e, "Failed to remove leftover review diffs"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::file_repository::FileFlowRepository;
+    use crate::flows::history::NodeRun;
+    use crate::flows::{Node, Position};
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn flow(id: &str, trigger: Value) -> Flow {
+        Flow {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            enabled: true,
+            nodes: vec![Node {
+                id: "t".to_string(),
+                node_type: NodeType::Trigger,
+                kind: "manual".to_string(),
+                config: trigger,
+                position: Position { x: 0.0, y: 0.0 },
+                label: "Trigger".to_string(),
+            }],
+            edges: vec![],
+            version: 0,
+            owners: vec![],
+            require_approval: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn run(flow_id: &str, id: &str, status: RunStatus, rerun_of: Option<&str>) -> FlowRun {
+        FlowRun {
+            id: id.to_string(),
+            flow_id: flow_id.to_string(),
+            status,
+            started_at: Utc::now(),
+            finished_at: None,
+            node_runs: vec![NodeRun {
+                node_id: "e".to_string(),
+                status,
+                started_at: Utc::now(),
+                finished_at: None,
+                output_preview: None,
+                prompt_variant: None,
+                cost_usd: None,
+                attempts: Vec::new(),
+                level: None,
+                ready_at: None,
+            }],
+            error: None,
+            output: None,
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: rerun_of.map(String::from),
+            dry_run: false,
+            replica: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_stale_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.save_flow(flow("marked", json!({}))).await.unwrap();
+        repo.save_flow(flow("rerun", json!({ "on_interrupt": "rerun" }))).await.unwrap();
+        repo.add_run(run("marked", "m1", RunStatus::Running, None)).await.unwrap();
+        repo.add_run(run("marked", "m2", RunStatus::Success, None)).await.unwrap();
+        repo.add_run(run("rerun", "r1", RunStatus::Running, None)).await.unwrap();
+        repo.add_run(run("rerun", "r2", RunStatus::Running, Some("r0"))).await.unwrap();
+
+        let reruns = interrupt_stale_runs(&repo, &[], &[]).await;
+        let ids: Vec<_> = reruns.iter().map(|(_, run)| run.id.as_str()).collect();
+        assert_eq!(ids, vec!["r1"]);
+
+        let runs = repo.get_runs("marked", 10).await;
+        let m1 = runs.iter().find(|r| r.id == "m1").unwrap();
+        assert_eq!(m1.status, RunStatus::Interrupted);
+        assert_eq!(m1.error.as_deref(), Some(INTERRUPTED_ERROR));
+        assert_eq!(m1.node_runs[0].status, RunStatus::Interrupted);
+        assert_eq!(runs.iter().find(|r| r.id == "m2").unwrap().status, RunStatus::Success);
+
+        // A second startup finds nothing left to do
+        assert!(interrupt_stale_runs(&repo, &[], &[]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_on_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.save_flow(flow("marked", json!({}))).await.unwrap();
+        repo.save_flow(flow("rerun", json!({ "on_interrupt": "rerun" }))).await.unwrap();
+        repo.add_run(run("marked", "m1", RunStatus::Running, None)).await.unwrap();
+        repo.add_run(run("marked", "m2", RunStatus::Running, None)).await.unwrap();
+        repo.add_run(run("rerun", "r1", RunStatus::Running, None)).await.unwrap();
+
+        // m2 is held at a wait, so isn't among those still going
+        let running = ["m1".to_string(), "r1".to_string()];
+        assert_eq!(interrupt_on_shutdown(&repo, &running).await, 1);
+
+        let runs = repo.get_runs("marked", 10).await;
+        let m1 = runs.iter().find(|r| r.id == "m1").unwrap();
+        assert_eq!((m1.status, m1.error.as_deref()), (RunStatus::Interrupted, Some(SHUTDOWN_ERROR)));
+        assert_eq!(m1.node_runs[0].status, RunStatus::Interrupted);
+        assert_eq!(runs.iter().find(|r| r.id == "m2").unwrap().status, RunStatus::Running);
+
+        // Left for the next startup to start over
+        assert_eq!(repo.get_runs("rerun", 10).await[0].status, RunStatus::Running);
+        let reruns = interrupt_stale_runs(&repo, &["m2".to_string()], &[]).await;
+        assert_eq!(reruns.iter().map(|(_, run)| run.id.as_str()).collect::<Vec<_>>(), ["r1"]);
+    }
+
+    #[tokio::test]
+    async fn test_held_runs_resume_instead_of_interrupting() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        let waits = WaitStore::new(dir.path());
+        repo.save_flow(flow("remind", json!({}))).await.unwrap();
+        repo.add_run(run("remind", "w1", RunStatus::Running, None)).await.unwrap();
+        repo.add_run(run("remind", "w2", RunStatus::Failed, None)).await.unwrap();
+        for run_id in ["w1", "w2"] {
+            waits
+                .save(Checkpoint {
+                    flow_id: "remind".into(),
+                    run_id: run_id.into(),
+                    node_id: "wait".into(),
+                    resume_at: Utc::now(),
+                    context: Default::default(),
+                    outputs: Default::default(),
+                    outcomes: Default::default(),
+                })
+                .unwrap();
+        }
+
+        let held = held_runs(&repo, &waits, &[]).await;
+        let ids: Vec<_> = held.iter().map(|(_, run, _)| run.id.clone()).collect();
+        assert_eq!(ids, vec!["w1"]);
+        assert!(waits.list().is_empty());
+
+        interrupt_stale_runs(&repo, &ids, &[]).await;
+        let runs = repo.get_runs("remind", 10).await;
+        assert_eq!(runs.iter().find(|r| r.id == "w1").unwrap().status, RunStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_runs_of_live_replicas_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        let waits = WaitStore::new(dir.path());
+        repo.save_flow(flow("f", json!({}))).await.unwrap();
+        let owned = |id: &str, replica: &str| FlowRun {
+            replica: Some(replica.to_string()),
+            ..run("f", id, RunStatus::Running, None)
+        };
+        repo.add_run(owned("live", "web-1")).await.unwrap();
+        repo.add_run(owned("gone", "web-2")).await.unwrap();
+        repo.add_run(owned("waiting", "web-1")).await.unwrap();
+        waits
+            .save(Checkpoint {
+                flow_id: "f".into(),
+                run_id: "waiting".into(),
+                node_id: "wait".into(),
+                resume_at: Utc::now(),
+                context: Default::default(),
+                outputs: Default::default(),
+                outcomes: Default::default(),
+            })
+            .unwrap();
+
+        let live = ["web-1".to_string()];
+        assert!(held_runs(&repo, &waits, &live).await.is_empty());
+        assert_eq!(waits.list().len(), 1);
+        interrupt_stale_runs(&repo, &[], &live).await;
+
+        let runs = repo.get_runs("f", 10).await;
+        let status = |id: &str| runs.iter().find(|r| r.id == id).unwrap().status;
+        assert_eq!(status("live"), RunStatus::Running);
+        assert_eq!(status("waiting"), RunStatus::Running);
+        assert_eq!(status("gone"), RunStatus::Interrupted);
+    }
+
+    #[test]
+    fn test_on_interrupt() {
+        assert_eq!(OnInterrupt::from_trigger(&json!({})).unwrap(), OnInterrupt::Mark);
+        assert_eq!(OnInterrupt::from_trigger(&json!({ "on_interrupt": "rerun" })).unwrap(), OnInterrupt::Rerun);
+        assert!(OnInterrupt::from_trigger(&json!({ "on_interrupt": "retry" })).is_err());
+    }
+}
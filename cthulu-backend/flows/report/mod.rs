@@ -0,0 +1,534 @@
+pub mod pdf;
+
+use chrono::{DateTime, Utc};
+
+use super::Flow;
+use super::history::{FlowRun, RunStatus};
+use pdf::{Document, Font};
+
+/// Characters of a node's output preview shown in the timeline.
+const PREVIEW_CHARS: usize = 200;
+
+/// Output format of `GET /api/runs/{id}/report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Html,
+    Pdf,
+}
+
+impl Format {
+    /// `html` (the default) or `pdf`.
+    pub fn parse(format: Option<&str>) -> Option<Self> {
+        match format.unwrap_or("html") {
+            "html" => Some(Self::Html),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Html => "text/html; charset=utf-8",
+            Self::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// One block of the run's markdown output.
+#[derive(Debug, PartialEq)]
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    /// A list item with its marker (`•` or `1.`).
+    Item(String, String),
+    Code(String),
+    Rule,
+}
+
+/// One node's part in the run, in execution order.
+struct Step {
+    label: String,
+    kind: String,
+    status: RunStatus,
+    started_at: DateTime<Utc>,
+    duration: Option<chrono::Duration>,
+    cost_usd: Option<f64>,
+    preview: Option<String>,
+}
+
+/// Self-contained HTML page: run summary, the output, then the node timeline.
+pub fn render_html(flow: &Flow, run: &FlowRun) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<table class=\"summary\">\n",
+        title = escape(&title(flow, run)),
+    );
+    for (key, value) in summary(run) {
+        html.push_str(&format!("<tr><th>{key}</th><td>{}</td></tr>\n", escape(&value)));
+    }
+    html.push_str("</table>\n<h2>Output</h2>\n<div class=\"output\">\n");
+    match run.output.as_deref().filter(|o| !o.trim().is_empty()) {
+        Some(output) => {
+            let mut in_list = false;
+            for block in blocks(output) {
+                let is_item = matches!(block, Block::Item(..));
+                if is_item && !in_list {
+                    html.push_str("<ul>\n");
+                } else if !is_item && in_list {
+                    html.push_str("</ul>\n");
+                }
+                in_list = is_item;
+                html.push_str(&match block {
+                    Block::Heading(level, text) => {
+                        let level = (level + 2).min(6);
+                        format!("<h{level}>{}</h{level}>\n", inline_html(&text))
+                    }
+                    Block::Paragraph(text) => format!("<p>{}</p>\n", inline_html(&text)),
+                    Block::Item(marker, text) if marker != "•" => {
+                        format!("<li class=\"numbered\"><span>{marker}</span> {}</li>\n", inline_html(&text))
+                    }
+                    Block::Item(_, text) => format!("<li>{}</li>\n", inline_html(&text)),
+                    Block::Code(code) => format!("<pre><code>{}</code></pre>\n", escape(&code)),
+                    Block::Rule => "<hr>\n".to_string(),
+                });
+            }
+            if in_list {
+                html.push_str("</ul>\n");
+            }
+        }
+        None => html.push_str("<p class=\"muted\">This run produced no output.</p>\n"),
+    }
+    html.push_str(
+        "</div>\n<h2>Timeline</h2>\n<table class=\"timeline\">\n\
+         <tr><th>Node</th><th>Status</th><th>Started</th><th>Duration</th><th>Cost</th><th>Output</th></tr>\n",
+    );
+    for step in timeline(flow, run) {
+        html.push_str(&format!(
+            "<tr><td>{} <span class=\"muted\">{}</span></td><td class=\"{status}\">{status}</td>\
+             <td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&step.label),
+            escape(&step.kind),
+            step.started_at.format("%H:%M:%S"),
+            step.duration.map(format_duration).unwrap_or_default(),
+            step.cost_usd.map(format_cost).unwrap_or_default(),
+            escape(step.preview.as_deref().unwrap_or_default()),
+            status = status_name(step.status),
+        ));
+    }
+    html.push_str(&format!(
+        "</table>\n<p class=\"muted\">Generated by Cthulu on {}</p>\n</body>\n</html>\n",
+        Utc::now().format("%Y-%m-%d %H:%M UTC")
+    ));
+    html
+}
+
+/// The same report as an A4 PDF (plain text; links show their URL).
+pub fn render_pdf(flow: &Flow, run: &FlowRun) -> Vec<u8> {
+    let mut doc = Document::default();
+    doc.text(&title(flow, run), Font::Bold, 16.0, 0.0);
+    doc.gap(6.0);
+    for (key, value) in summary(run) {
+        doc.text(&format!("{key}: {value}"), Font::Regular, 10.0, 0.0);
+    }
+
+    doc.gap(12.0);
+    doc.text("Output", Font::Bold, 13.0, 0.0);
+    doc.gap(4.0);
+    match run.output.as_deref().filter(|o| !o.trim().is_empty()) {
+        Some(output) => {
+            for block in blocks(output) {
+                match block {
+                    Block::Heading(level, text) => {
+                        doc.gap(6.0);
+                        doc.text(&plain(&text), Font::Bold, if level <= 1 { 12.0 } else { 11.0 }, 0.0);
+                    }
+                    Block::Paragraph(text) => {
+                        doc.text(&plain(&text), Font::Regular, 10.0, 0.0);
+                        doc.gap(4.0);
+                    }
+                    Block::Item(marker, text) => {
+                        doc.text(&format!("{marker} {}", plain(&text)), Font::Regular, 10.0, 12.0);
+                    }
+                    Block::Code(code) => {
+                        for line in code.lines() {
+                            doc.text(line, Font::Mono, 9.0, 12.0);
+                        }
+                        doc.gap(4.0);
+                    }
+                    Block::Rule => doc.gap(8.0),
+                }
+            }
+        }
+        None => doc.text("This run produced no output.", Font::Regular, 10.0, 0.0),
+    }
+
+    doc.gap(12.0);
+    doc.text("Timeline", Font::Bold, 13.0, 0.0);
+    doc.gap(4.0);
+    for step in timeline(flow, run) {
+        let mut line = format!(
+            "{} {} ({}) - {}",
+            step.started_at.format("%H:%M:%S"),
+            step.label,
+            step.kind,
+            status_name(step.status)
+        );
+        if let Some(duration) = step.duration {
+            line.push_str(&format!(", {}", format_duration(duration)));
+        }
+        if let Some(cost) = step.cost_usd {
+            line.push_str(&format!(", {}", format_cost(cost)));
+        }
+        doc.text(&line, Font::Regular, 10.0, 0.0);
+        if let Some(preview) = &step.preview {
+            doc.text(preview, Font::Mono, 8.0, 12.0);
+        }
+    }
+    doc.finish()
+}
+
+fn title(flow: &Flow, run: &FlowRun) -> String {
+    format!("{} — run of {}", flow.name, run.started_at.format("%Y-%m-%d %H:%M UTC"))
+}
+
+fn summary(run: &FlowRun) -> Vec<(&'static str, String)> {
+    let mut rows = vec![
+        ("Run", run.id.clone()),
+        ("Status", status_name(run.status).to_string()),
+        ("Started", run.started_at.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+    ];
+    if let Some(finished) = run.finished_at {
+        rows.push(("Finished", finished.format("%Y-%m-%d %H:%M:%S UTC").to_string()));
+        rows.push(("Duration", format_duration(finished - run.started_at)));
+    }
+    let costs: Vec<f64> = run.node_runs.iter().filter_map(|n| n.cost_usd).collect();
+    if !costs.is_empty() {
+        rows.push(("Cost", format_cost(costs.iter().sum())));
+    }
+    if let Some(rating) = run.rating {
+        rows.push(("Rating", format!("{rating}/5")));
+    }
+    if let Some(error) = &run.error {
+        rows.push(("Error", error.clone()));
+    }
+    rows
+}
+
+fn timeline(flow: &Flow, run: &FlowRun) -> Vec<Step> {
+    let mut steps: Vec<Step> = run
+        .node_runs
+        .iter()
+        .map(|node_run| {
+            let node = flow.nodes.iter().find(|n| n.id == node_run.node_id);
+            Step {
+                label: node.map_or_else(|| node_run.node_id.clone(), |n| n.label.clone()),
+                kind: node.map_or_else(|| "removed".to_string(), |n| n.kind.clone()),
+                status: node_run.status,
+                started_at: node_run.started_at,
+                duration: node_run.finished_at.map(|f| f - node_run.started_at),
+                cost_usd: node_run.cost_usd,
+                preview: node_run
+                    .output_preview
+                    .as_deref()
+                    .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|p| !p.is_empty())
+                    .map(|p| truncate(&p, PREVIEW_CHARS)),
+            }
+        })
+        .collect();
+    steps.sort_by_key(|s| s.started_at);
+    steps
+}
+
+fn status_name(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::Running => "running",
+        RunStatus::Success => "success",
+        RunStatus::Failed => "failed",
+        RunStatus::Interrupted => "interrupted",
+    }
+}
+
+fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn format_cost(cost: f64) -> String {
+    format!("${cost:.4}")
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Split markdown into headings, paragraphs, list items, code and rules.
+fn blocks(markdown: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            match code.take() {
+                Some(lines) => blocks.push(Block::Code(lines.join("\n"))),
+                None => {
+                    flush(&mut paragraph, &mut blocks);
+                    code = Some(Vec::new());
+                }
+            }
+            continue;
+        }
+        if let Some(lines) = code.as_mut() {
+            lines.push(line);
+            continue;
+        }
+
+        let ordered = trimmed
+            .split_once(". ")
+            .filter(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if trimmed == "---THREAD---" || (trimmed.len() >= 3 && trimmed.chars().all(|c| matches!(c, '-' | '*' | '_'))) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+        } else if trimmed.starts_with('#') {
+            flush(&mut paragraph, &mut blocks);
+            let level = trimmed.chars().take_while(|&c| c == '#').count();
+            blocks.push(Block::Heading(level, trimmed[level..].trim().to_string()));
+        } else if let Some(text) = ["- ", "* ", "+ "].iter().find_map(|m| trimmed.strip_prefix(m)) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Item("•".to_string(), text.trim().to_string()));
+        } else if let Some((n, text)) = ordered {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Item(format!("{n}."), text.trim().to_string()));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines.join("\n")));
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// Inline markdown as HTML: `**bold**`, `` `code` `` and `[text](url)`.
+fn inline_html(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    let mut bold = false;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            out.push_str(if bold { "</strong>" } else { "<strong>" });
+            bold = !bold;
+            rest = after;
+        } else if let Some((code, after)) = rest.strip_prefix('`').and_then(|r| r.split_once('`')) {
+            out.push_str(&format!("<code>{}</code>", escape(code)));
+            rest = after;
+        } else if let Some((label, url, after)) = link(rest) {
+            out.push_str(&format!("<a href=\"{}\">{}</a>", escape(url), escape(label)));
+            rest = after;
+        } else {
+            out.push_str(&escape(&c.to_string()));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if bold {
+        out.push_str("</strong>");
+    }
+    out
+}
+
+/// Inline markdown as plain text; links keep their URL in parentheses.
+fn plain(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            rest = after;
+        } else if let Some((label, url, after)) = link(rest) {
+            out.push_str(&format!("{label} ({url})"));
+            rest = after;
+        } else {
+            if c != '`' {
+                out.push(c);
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// `[label](url)` at the start of `text`: (label, url, rest).
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let (label, after) = text.strip_prefix('[')?.split_once("](")?;
+    let (url, rest) = after.split_once(')')?;
+    (!label.contains(']') && !url.contains(char::is_whitespace)).then_some((label, url, rest))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body{font-family:-apple-system,'Segoe UI',Helvetica,Arial,sans-serif;max-width:860px;\
+margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.5}\
+h1{font-size:1.6rem;margin-bottom:.5rem}h2{border-bottom:1px solid #d0d7de;padding-bottom:.3rem;margin-top:2rem}\
+table{border-collapse:collapse;width:100%;font-size:.9rem}th,td{text-align:left;padding:.35rem .6rem;\
+border-bottom:1px solid #eaeef2;vertical-align:top}.summary th{width:8rem;color:#57606a}\
+pre{background:#f6f8fa;padding:.8rem;overflow-x:auto}code{font-size:.9em}\
+li.numbered{list-style:none}li.numbered span{margin-left:-1.2rem}\
+.muted{color:#57606a;font-size:.85em}.success{color:#1a7f37}.failed{color:#cf222e}.interrupted{color:#cf222e}.running{color:#9a6700}\
+@media print{body{margin:0;max-width:none}a{color:inherit}}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::NodeRun;
+    use chrono::TimeZone;
+
+    fn flow() -> Flow {
+        serde_json::from_value(serde_json::json!({
+            "id": "f1",
+            "name": "Morning <brief>",
+            "nodes": [
+                { "id": "n1", "node_type": "source", "kind": "rss", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Feeds" },
+                { "id": "n2", "node_type": "executor", "kind": "claude-code", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Summarize" }
+            ],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn run() -> FlowRun {
+        let at = |m, s| Utc.with_ymd_and_hms(2026, 3, 1, 7, m, s).unwrap();
+        FlowRun {
+            id: "r1".to_string(),
+            flow_id: "f1".to_string(),
+            status: RunStatus::Success,
+            started_at: at(0, 0),
+            finished_at: Some(at(1, 30)),
+            node_runs: vec![
+                NodeRun {
+                    node_id: "n2".to_string(),
+                    status: RunStatus::Success,
+                    started_at: at(0, 5),
+                    finished_at: Some(at(1, 30)),
+                    output_preview: Some("## Top stories\n- Rust".to_string()),
+                    prompt_variant: None,
+                    cost_usd: Some(0.0421),
+                    attempts: Vec::new(),
+                    level: None,
+                    ready_at: None,
+                },
+                NodeRun {
+                    node_id: "n1".to_string(),
+                    status: RunStatus::Success,
+                    started_at: at(0, 0),
+                    finished_at: Some(at(0, 4)),
+                    output_preview: None,
+                    prompt_variant: None,
+                    cost_usd: None,
+                    attempts: Vec::new(),
+                    level: None,
+                    ready_at: None,
+                },
+            ],
+            error: None,
+            output: Some("# Top stories\n\nRust **2.0** shipped, see [the post](https://blog.rust-lang.org).\n\n- One\n- Two\n\n```\nlet x = 1 < 2;\n```".to_string()),
+            rating: Some(4),
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
+        }
+    }
+
+    #[test]
+    fn test_blocks() {
+        let parsed = blocks("# Title\nline one\nline two\n\n1. First\n- Item\n---\n```\ncode\n```");
+        assert_eq!(
+            parsed,
+            vec![
+                Block::Heading(1, "Title".to_string()),
+                Block::Paragraph("line one line two".to_string()),
+                Block::Item("1.".to_string(), "First".to_string()),
+                Block::Item("•".to_string(), "Item".to_string()),
+                Block::Rule,
+                Block::Code("code".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline() {
+        let text = "Use **`a<b`** and [docs](https://x.io/?a=1&b=2) [not a link]";
+        assert_eq!(
+            inline_html(text),
+            "Use <strong><code>a&lt;b</code></strong> and <a href=\"https://x.io/?a=1&amp;b=2\">docs</a> [not a link]"
+        );
+        assert_eq!(plain(text), "Use a<b and docs (https://x.io/?a=1&b=2) [not a link]");
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = render_html(&flow(), &run());
+        assert!(html.contains("<h1>Morning &lt;brief&gt; — run of 2026-03-01 07:00 UTC</h1>"));
+        assert!(html.contains("<tr><th>Duration</th><td>1m 30s</td></tr>"));
+        assert!(html.contains("<tr><th>Cost</th><td>$0.0421</td></tr>"));
+        assert!(html.contains("<h3>Top stories</h3>"));
+        assert!(html.contains("<ul>\n<li>One</li>\n<li>Two</li>\n</ul>"));
+        assert!(html.contains("<pre><code>let x = 1 &lt; 2;</code></pre>"));
+        // Timeline in execution order, with node labels
+        let feeds = html.find("<td>Feeds <span").unwrap();
+        let summarize = html.find("<td>Summarize <span").unwrap();
+        assert!(feeds < summarize);
+        assert!(html.contains("<td>## Top stories - Rust</td>"));
+    }
+
+    #[test]
+    fn test_render_pdf() {
+        let pdf = render_pdf(&flow(), &run());
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("(Rust 2.0 shipped, see the post \\(https://blog.rust-lang.org\\).) Tj"));
+        assert!(text.contains("(07:00:05 Summarize \\(claude-code\\) - success, 1m 25s, $0.0421) Tj"));
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Format::parse(None), Some(Format::Html));
+        assert_eq!(Format::parse(Some("pdf")), Some(Format::Pdf));
+        assert_eq!(Format::parse(Some("docx")), None);
+        assert_eq!(format_duration(chrono::Duration::seconds(3725)), "1h 02m");
+    }
+}
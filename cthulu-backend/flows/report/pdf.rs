@@ -0,0 +1,221 @@
+//! Minimal PDF writer: wrapped text in the standard Helvetica and Courier
+//! fonts over as many A4 pages as it takes. No images, no embedded fonts.
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Mono => "F3",
+        }
+    }
+
+    /// Average glyph width as a share of the font size, for wrapping.
+    /// Courier is exact; Helvetica errs wide so lines never overflow.
+    fn char_width(self) -> f32 {
+        match self {
+            Font::Regular => 0.5,
+            Font::Bold => 0.55,
+            Font::Mono => 0.6,
+        }
+    }
+}
+
+/// Lays out lines top to bottom, starting a new page when one fills up.
+pub struct Document {
+    pages: Vec<String>,
+    current: String,
+    y: f32,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self { pages: Vec::new(), current: String::new(), y: PAGE_HEIGHT - MARGIN }
+    }
+}
+
+impl Document {
+    /// Add `text` wrapped to the page width, `indent` points in.
+    pub fn text(&mut self, text: &str, font: Font, size: f32, indent: f32) {
+        let max_chars = ((PAGE_WIDTH - 2.0 * MARGIN - indent) / (size * font.char_width())) as usize;
+        for line in wrap(text, max_chars.max(10)) {
+            self.line(&line, font, size, indent);
+        }
+    }
+
+    /// Vertical space, e.g. between paragraphs.
+    pub fn gap(&mut self, points: f32) {
+        self.y -= points;
+    }
+
+    fn line(&mut self, text: &str, font: Font, size: f32, indent: f32) {
+        let leading = size * 1.4;
+        if self.y - leading < MARGIN {
+            self.pages.push(std::mem::take(&mut self.current));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= leading;
+        self.current.push_str(&format!(
+            "BT /{} {size} Tf {:.1} {:.1} Td ({}) Tj ET\n",
+            font.resource(),
+            MARGIN + indent,
+            self.y,
+            encode(text),
+        ));
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.current.is_empty() || self.pages.is_empty() {
+            self.pages.push(self.current);
+        }
+
+        // 1 catalog, 2 page tree, 3-5 fonts, then a page and its content
+        // stream per page
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|i| 6 + i * 2).collect();
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids.iter().map(|id| format!("{id} 0 R")).collect::<Vec<_>>().join(" "),
+                page_ids.len()
+            ),
+            font_object("Helvetica"),
+            font_object("Helvetica-Bold"),
+            font_object("Courier"),
+        ];
+        for (page, id) in self.pages.iter().zip(&page_ids) {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+                id + 1
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{page}endstream", page.len()));
+        }
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", i + 1).as_bytes());
+        }
+        let xref = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        out.extend_from_slice(
+            format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n", objects.len() + 1).as_bytes(),
+        );
+        out
+    }
+}
+
+fn font_object(name: &str) -> String {
+    format!("<< /Type /Font /Subtype /Type1 /BaseFont /{name} /Encoding /WinAnsiEncoding >>")
+}
+
+/// Greedy word wrap at `max_chars`; words longer than a line are split.
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > max_chars {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..max_chars).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// A PDF string literal body in WinAnsi: Latin-1 as is, common typography
+/// mapped, anything else `?`.
+fn encode(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            '•' => out.push_str("\\225"),
+            '–' => out.push_str("\\226"),
+            '—' => out.push_str("\\227"),
+            '‘' => out.push_str("\\221"),
+            '’' => out.push_str("\\222"),
+            '“' => out.push_str("\\223"),
+            '”' => out.push_str("\\224"),
+            '…' => out.push_str("\\205"),
+            '€' => out.push_str("\\200"),
+            '\t' => out.push_str("    "),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three four", 9), vec!["one two", "three", "four"]);
+        assert_eq!(wrap("abcdefghijkl", 5), vec!["abcde", "fghij", "kl"]);
+        assert_eq!(wrap("", 5), vec![""]);
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("f(x) \\ y"), "f\\(x\\) \\\\ y");
+        assert_eq!(encode("café — ok ✓"), "caf\\351 \\227 ok ?");
+    }
+
+    #[test]
+    fn test_document_paginates_with_valid_xref() {
+        let mut doc = Document::default();
+        for i in 0..120 {
+            doc.text(&format!("Line {i}"), Font::Regular, 10.0, 0.0);
+        }
+        let pdf = doc.finish();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 3"));
+
+        // Every xref entry points at its object
+        let xref_at: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        let xref = String::from_utf8_lossy(&pdf[xref_at..]);
+        let entries: Vec<&str> = xref.lines().skip(3).take_while(|l| l.ends_with(" n ")).collect();
+        for (i, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+    }
+}
@@ -2,19 +2,32 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use super::Flow;
-use super::history::{FlowRun, NodeRun, RunStatus};
+use super::history::{ChatMessage, FlowRun, NodeRun, RunPage, RunQuery, RunStatus};
+use crate::tasks::executors::models::ModelAttempt;
+
+/// Why `save_flow` refused an edit: the stored flow is already at (or past)
+/// the edit's revision, so it was based on one that's since been replaced.
+#[derive(thiserror::Error, Debug)]
+#[error("flow {} is at revision {}, newer than the edit's base", current.id, current.version)]
+pub struct StaleRevision {
+    pub current: Flow,
+}
 
 #[async_trait]
 pub trait FlowRepository: Send + Sync {
     // Flows
     async fn list_flows(&self) -> Vec<Flow>;
     async fn get_flow(&self, id: &str) -> Option<Flow>;
+    /// Create the flow, or replace it with an edit whose `version` is past
+    /// the stored one's; otherwise fails with [`StaleRevision`].
     async fn save_flow(&self, flow: Flow) -> Result<()>;
     async fn delete_flow(&self, id: &str) -> Result<bool>;
 
     // Runs (tightly coupled to flows)
     async fn add_run(&self, run: FlowRun) -> Result<()>;
     async fn get_runs(&self, flow_id: &str, limit: usize) -> Vec<FlowRun>;
+    /// Runs of any flow matching `query`, a page at a time.
+    async fn query_runs(&self, query: &RunQuery) -> RunPage;
     async fn complete_run(
         &self,
         flow_id: &str,
@@ -22,6 +35,21 @@ pub trait FlowRepository: Send + Sync {
         status: RunStatus,
         error: Option<String>,
     ) -> Result<()>;
+    async fn set_run_output(&self, flow_id: &str, run_id: &str, output: String) -> Result<()>;
+    async fn set_run_rating(&self, flow_id: &str, run_id: &str, rating: u8) -> Result<()>;
+    /// Hand a run over to the replica resuming it.
+    async fn set_run_replica(&self, flow_id: &str, run_id: &str, replica: Option<String>) -> Result<()>;
+    /// Drop a run from the history; false if there was no such run.
+    async fn delete_run(&self, flow_id: &str, run_id: &str) -> Result<bool>;
+    /// Add `messages` to the run's chat, starting it under `session_id` if
+    /// it has none.
+    async fn append_run_chat(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        session_id: &str,
+        messages: Vec<ChatMessage>,
+    ) -> Result<()>;
     async fn push_node_run(
         &self,
         flow_id: &str,
@@ -36,7 +64,23 @@ pub trait FlowRepository: Send + Sync {
         status: RunStatus,
         output_preview: Option<String>,
     ) -> Result<()>;
+    async fn set_node_run_cost(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        cost_usd: f64,
+    ) -> Result<()>;
+    async fn set_node_run_attempts(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        attempts: Vec<ModelAttempt>,
+    ) -> Result<()>;
 
     // Lifecycle
     async fn load_all(&self) -> Result<()>;
+    /// Re-read flow `id` as another process left it; `false` if it's gone.
+    async fn reload_flow(&self, id: &str) -> bool;
 }
@@ -0,0 +1,278 @@
+//! Run retention: a background pass that prunes old run records and their
+//! saved node outputs and logs, and expired source and executor cache
+//! entries, so `~/.cthulu` doesn't grow without bound.
+//!
+//! Set with `RETENTION_KEEP_LAST_N_RUNS`, `RETENTION_MAX_AGE_DAYS` and
+//! `RETENTION_MAX_TOTAL_BYTES`; a trigger's `retention` object
+//! (`keep_last_n_runs`, `max_age_days`) overrides the first two for its
+//! flow. Runs still going are never pruned, nor is each flow's latest
+//! successful run, which "since last run" sources start from.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::file_repository::FileFlowRepository;
+use super::history::{MAX_RUNS_PER_FLOW, RunStatus};
+use super::repository::FlowRepository;
+use super::run_logs::RunLogStore;
+use super::run_outputs::RunOutputStore;
+use super::{Flow, NodeType};
+use crate::tasks::executors::cache::ExecutorCache;
+use crate::tasks::sources::cache::SourceCache;
+
+/// Time between passes.
+const GC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retention {
+    pub keep_last_n_runs: Option<usize>,
+    pub max_age_days: Option<u64>,
+    /// Across every flow's runs and their saved outputs.
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Retention {
+    pub fn from_env() -> Self {
+        let env = |key: &str| std::env::var(key).ok();
+        Self::from_raw_values(
+            env("RETENTION_KEEP_LAST_N_RUNS").as_deref(),
+            env("RETENTION_MAX_AGE_DAYS").as_deref(),
+            env("RETENTION_MAX_TOTAL_BYTES").as_deref(),
+        )
+    }
+
+    /// Used directly in tests to avoid mutating process-global environment.
+    pub fn from_raw_values(keep_last_n_runs: Option<&str>, max_age_days: Option<&str>, max_total_bytes: Option<&str>) -> Self {
+        let parse = |v: Option<&str>| v.and_then(|v| v.trim().parse::<u64>().ok()).filter(|n| *n > 0);
+        Self {
+            keep_last_n_runs: parse(keep_last_n_runs).map(|n| n as usize),
+            max_age_days: parse(max_age_days),
+            max_total_bytes: parse(max_total_bytes),
+        }
+    }
+
+    /// This policy with the flow's trigger `retention` laid over it.
+    pub fn for_flow(&self, flow: &Flow) -> Result<Self> {
+        let Some(trigger) = flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) else {
+            return Ok(*self);
+        };
+        let config = &trigger.config["retention"];
+        let limit = |key: &str| -> Result<Option<u64>> {
+            match &config[key] {
+                Value::Null => Ok(None),
+                value => value
+                    .as_u64()
+                    .filter(|n| *n > 0)
+                    .map(Some)
+                    .with_context(|| format!("retention.{key} must be a whole number of at least 1")),
+            }
+        };
+        Ok(Self {
+            keep_last_n_runs: limit("keep_last_n_runs")?.map(|n| n as usize).or(self.keep_last_n_runs),
+            max_age_days: limit("max_age_days")?.or(self.max_age_days),
+            max_total_bytes: self.max_total_bytes,
+        })
+    }
+}
+
+/// A finished run a pass may prune.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub flow_id: String,
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+    /// Its record, saved outputs and log.
+    pub bytes: u64,
+    /// The flow's latest successful run.
+    pub protected: bool,
+}
+
+/// The runs to prune: each flow's (newest first) past its
+/// `keep_last_n_runs` or older than its `max_age_days`, then the oldest
+/// left, across flows, until what remains fits `max_total_bytes`.
+pub fn select(flows: Vec<(Retention, Vec<Candidate>)>, max_total_bytes: Option<u64>, now: DateTime<Utc>) -> Vec<Candidate> {
+    let mut pruned = Vec::new();
+    let mut kept = Vec::new();
+    for (retention, mut runs) in flows {
+        runs.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        let cutoff = retention.max_age_days.map(|days| now - chrono::Duration::days(days as i64));
+        for (i, run) in runs.into_iter().enumerate() {
+            let excess = retention.keep_last_n_runs.is_some_and(|n| i >= n);
+            let expired = cutoff.is_some_and(|cutoff| run.started_at < cutoff);
+            match (excess || expired) && !run.protected {
+                true => pruned.push(run),
+                false => kept.push(run),
+            }
+        }
+    }
+    if let Some(max) = max_total_bytes {
+        let mut total: u64 = kept.iter().map(|r| r.bytes).sum();
+        kept.sort_by_key(|r| r.started_at);
+        for run in kept {
+            if total <= max {
+                break;
+            }
+            if !run.protected {
+                total -= run.bytes;
+                pruned.push(run);
+            }
+        }
+    }
+    pruned
+}
+
+/// One pass: prune what `retention` says to, returning how many runs went.
+pub async fn collect(repo: &FileFlowRepository, outputs: &RunOutputStore, logs: &RunLogStore, retention: &Retention) -> usize {
+    let mut flows = Vec::new();
+    for flow in repo.list_flows().await {
+        let flow_retention = retention.for_flow(&flow).unwrap_or_else(|e| {
+            tracing::warn!(flow = %flow.name, error = %e, "Invalid retention, using the server's");
+            *retention
+        });
+        let runs = repo.get_runs(&flow.id, MAX_RUNS_PER_FLOW).await;
+        let latest_success = runs.iter().find(|r| r.status == RunStatus::Success && !r.dry_run).map(|r| r.id.clone());
+        let candidates = runs
+            .into_iter()
+            .filter(|r| r.status != RunStatus::Running)
+            .map(|r| Candidate {
+                bytes: repo.run_bytes(&flow.id, &r.id) + outputs.bytes(&flow.id, &r.id) + logs.bytes(&flow.id, &r.id),
+                protected: latest_success.as_deref() == Some(r.id.as_str()),
+                flow_id: flow.id.clone(),
+                run_id: r.id,
+                started_at: r.started_at,
+            })
+            .collect();
+        flows.push((flow_retention, candidates));
+    }
+
+    let pruned = select(flows, retention.max_total_bytes, Utc::now());
+    let mut count = 0;
+    for run in &pruned {
+        match repo.delete_run(&run.flow_id, &run.run_id).await {
+            Ok(_) => count += 1,
+            Err(e) => {
+                tracing::warn!(flow_id = %run.flow_id, run_id = %run.run_id, error = %e, "Failed to prune run");
+                continue;
+            }
+        }
+        if let Err(e) = outputs.delete(&run.flow_id, &run.run_id) {
+            tracing::warn!(flow_id = %run.flow_id, run_id = %run.run_id, error = %e, "Failed to prune run outputs");
+        }
+        if let Err(e) = logs.delete(&run.flow_id, &run.run_id) {
+            tracing::warn!(flow_id = %run.flow_id, run_id = %run.run_id, error = %e, "Failed to prune run log");
+        }
+    }
+    if count > 0 {
+        let bytes: u64 = pruned.iter().map(|r| r.bytes).sum();
+        tracing::info!(runs = count, bytes, "Pruned old runs");
+    }
+    count
+}
+
+/// The caches whose expired entries each pass deletes.
+pub struct Caches {
+    pub sources: SourceCache,
+    pub executors: ExecutorCache,
+}
+
+impl Caches {
+    async fn prune(&self) {
+        let entries = self.sources.prune().await + self.executors.prune().await;
+        if entries > 0 {
+            tracing::info!(entries, "Pruned expired cache entries");
+        }
+    }
+}
+
+/// Prune on start and then hourly.
+pub async fn gc_loop(
+    repo: Arc<FileFlowRepository>,
+    outputs: RunOutputStore,
+    logs: RunLogStore,
+    caches: Caches,
+    retention: Retention,
+) {
+    let mut tick = tokio::time::interval(GC_INTERVAL);
+    loop {
+        tick.tick().await;
+        collect(&repo, &outputs, &logs, &retention).await;
+        caches.prune().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn candidate(flow_id: &str, run_id: &str, days_ago: i64, bytes: u64) -> Candidate {
+        Candidate {
+            flow_id: flow_id.into(),
+            run_id: run_id.into(),
+            started_at: now() - chrono::Duration::days(days_ago),
+            bytes,
+            protected: false,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2026-06-01T00:00:00Z".parse().unwrap()
+    }
+
+    fn ids(pruned: &[Candidate]) -> Vec<&str> {
+        pruned.iter().map(|r| r.run_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_from_raw_values() {
+        let retention = Retention::from_raw_values(Some("20"), Some("0"), Some("junk"));
+        assert_eq!(retention.keep_last_n_runs, Some(20));
+        assert_eq!(retention.max_age_days, None);
+        assert_eq!(retention.max_total_bytes, None);
+        assert_eq!(Retention::from_raw_values(None, None, None), Retention::default());
+    }
+
+    #[test]
+    fn test_flow_overrides() {
+        let flow: Flow = serde_json::from_value(json!({
+            "id": "f", "name": "Digest",
+            "nodes": [{
+                "id": "t", "node_type": "trigger", "kind": "cron",
+                "config": { "retention": { "max_age_days": 7 } },
+                "position": { "x": 0, "y": 0 }, "label": "Daily",
+            }],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        let server = Retention { keep_last_n_runs: Some(50), max_age_days: Some(90), max_total_bytes: None };
+        let retention = server.for_flow(&flow).unwrap();
+        assert_eq!((retention.keep_last_n_runs, retention.max_age_days), (Some(50), Some(7)));
+    }
+
+    #[test]
+    fn test_select() {
+        let keep_two = Retention { keep_last_n_runs: Some(2), ..Retention::default() };
+        let runs = vec![candidate("a", "a1", 3, 10), candidate("a", "a2", 2, 10), candidate("a", "a3", 1, 10)];
+        assert_eq!(ids(&select(vec![(keep_two, runs)], None, now())), ["a1"]);
+
+        // The latest success stays, however old
+        let month = Retention { max_age_days: Some(30), ..Retention::default() };
+        let mut latest = candidate("b", "b1", 40, 10);
+        latest.protected = true;
+        let runs = vec![latest, candidate("b", "b2", 45, 10)];
+        assert_eq!(ids(&select(vec![(month, runs)], None, now())), ["b2"]);
+
+        // Over the byte budget, the oldest go first, from any flow
+        let flows = vec![
+            (Retention::default(), vec![candidate("a", "a1", 5, 100), candidate("a", "a2", 1, 100)]),
+            (Retention::default(), vec![candidate("b", "b1", 3, 100)]),
+        ];
+        assert_eq!(ids(&select(flows, Some(150), now())), ["a1", "b1"]);
+    }
+}
@@ -0,0 +1,363 @@
+//! Full-text index over finished runs' node outputs and final text, for
+//! `GET /api/runs/search` — "which run mentioned CVE-2024-1234?" across
+//! every flow's history.
+//!
+//! A SQLite FTS5 database at `~/.cthulu/state/run-index.db`. It's brought up
+//! to date with the run history before each search: runs finished since
+//! are added, and runs retention pruned or whose flow was deleted dropped.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+
+use super::Flow;
+use super::history::{FlowRun, MAX_RUNS_PER_FLOW, RunStatus};
+use super::repository::FlowRepository;
+use super::run_outputs::{RunOutputStore, RunOutputs};
+use crate::tasks::archive::{MAX_SEARCH_LIMIT, match_expression};
+
+/// Words of context on each side of the match in [`RunHit::snippet`].
+const SNIPPET_TOKENS: i64 = 24;
+
+/// Label of a run's final text among its documents.
+const OUTPUT_LABEL: &str = "Output";
+
+/// Indexed runs, one document per node output plus the final text, and an
+/// external-content FTS5 index over the documents kept in sync by triggers.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    run_id TEXT PRIMARY KEY,
+    flow_id TEXT NOT NULL,
+    flow_name TEXT NOT NULL,
+    status TEXT NOT NULL,
+    started_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS documents (
+    id INTEGER PRIMARY KEY,
+    run_id TEXT NOT NULL,
+    node_id TEXT,
+    label TEXT NOT NULL,
+    text TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS documents_run ON documents (run_id);
+CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(
+    label, text, content='documents', content_rowid='id'
+);
+CREATE TRIGGER IF NOT EXISTS documents_fts_insert AFTER INSERT ON documents BEGIN
+    INSERT INTO documents_fts(rowid, label, text) VALUES (new.id, new.label, new.text);
+END;
+CREATE TRIGGER IF NOT EXISTS documents_fts_delete AFTER DELETE ON documents BEGIN
+    INSERT INTO documents_fts(documents_fts, rowid, label, text) VALUES ('delete', old.id, old.label, old.text);
+END;
+";
+
+/// A search hit: the run's best-matching output.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunHit {
+    pub flow_id: String,
+    pub flow_name: String,
+    pub run_id: String,
+    pub status: RunStatus,
+    pub started_at: DateTime<Utc>,
+    /// The node whose output matched; `None` for the run's final text.
+    pub node_id: Option<String>,
+    pub label: String,
+    /// The part of the output around the match.
+    pub snippet: String,
+}
+
+/// One searchable text of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub node_id: Option<String>,
+    pub label: String,
+    pub text: String,
+}
+
+/// What gets indexed of `run`: its final text, and each node's output —
+/// in full when the run's outputs were saved, else its preview.
+pub fn documents(flow: &Flow, run: &FlowRun, saved: Option<&RunOutputs>) -> Vec<Document> {
+    let label = |node_id: &str| {
+        flow.nodes.iter().find(|n| n.id == node_id).map_or_else(|| node_id.to_string(), |n| n.label.clone())
+    };
+    let mut documents: Vec<Document> = run
+        .output
+        .iter()
+        .map(|text| Document { node_id: None, label: OUTPUT_LABEL.to_string(), text: text.clone() })
+        .collect();
+    for node_run in &run.node_runs {
+        let text = match saved.and_then(|s| s.outputs.get(&node_run.node_id)) {
+            Some(output) => output.as_text(),
+            None => node_run.output_preview.clone().unwrap_or_default(),
+        };
+        documents.push(Document { node_id: Some(node_run.node_id.clone()), label: label(&node_run.node_id), text });
+    }
+    documents.retain(|d| !d.text.trim().is_empty());
+    documents
+}
+
+#[derive(Clone)]
+pub struct RunIndex {
+    path: PathBuf,
+}
+
+impl RunIndex {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("state").join("run-index.db") }
+    }
+
+    async fn connect(&self) -> Result<SqlitePool> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let options = SqliteConnectOptions::new()
+            .filename(&self.path)
+            .create_if_missing(true)
+            .busy_timeout(Duration::from_secs(5));
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .with_context(|| format!("failed to open run index: {}", self.path.display()))?;
+        sqlx::raw_sql(SCHEMA)
+            .execute(&pool)
+            .await
+            .context("failed to create run index schema")?;
+        Ok(pool)
+    }
+
+    /// Bring the index up to date with every flow's history: index finished
+    /// runs it doesn't have (dry runs aside) and drop the ones gone from
+    /// the history. Returns how many runs were added.
+    pub async fn sync(&self, repo: &dyn FlowRepository, outputs: &RunOutputStore) -> Result<usize> {
+        let pool = self.connect().await?;
+        let indexed: HashSet<String> = sqlx::query("SELECT run_id FROM runs")
+            .fetch_all(&pool)
+            .await
+            .context("failed to read run index")?
+            .iter()
+            .map(|row| row.get("run_id"))
+            .collect();
+
+        let mut current = HashSet::new();
+        let mut added = 0;
+        for flow in repo.list_flows().await {
+            for run in repo.get_runs(&flow.id, MAX_RUNS_PER_FLOW).await {
+                if run.status == RunStatus::Running || run.dry_run {
+                    continue;
+                }
+                current.insert(run.id.clone());
+                if indexed.contains(&run.id) {
+                    continue;
+                }
+                let saved = outputs.load(&flow.id, &run.id);
+                let documents = documents(&flow, &run, saved.as_ref());
+                if add(&pool, &flow, &run, &documents).await? {
+                    added += 1;
+                }
+            }
+        }
+
+        for run_id in indexed.difference(&current) {
+            let mut tx = pool.begin().await?;
+            sqlx::query("DELETE FROM documents WHERE run_id = ?").bind(run_id).execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM runs WHERE run_id = ?").bind(run_id).execute(&mut *tx).await?;
+            tx.commit().await.context("failed to drop run from index")?;
+        }
+        pool.close().await;
+        Ok(added)
+    }
+
+    /// Runs whose outputs best match `query` (all words in one output;
+    /// `word*` matches a prefix), optionally within one flow, each with its
+    /// best-matching output.
+    pub async fn search(&self, query: &str, flow_id: Option<&str>, limit: usize) -> Result<Vec<RunHit>> {
+        let Some(expr) = match_expression(query, " ") else {
+            return Ok(Vec::new());
+        };
+        let pool = self.connect().await?;
+        // FTS5's snippet() can't share a query with the window function
+        // keeping each run's best hit, hence the materialized CTE.
+        let rows = sqlx::query(
+            "WITH hits AS MATERIALIZED (
+                 SELECT runs.flow_id, runs.flow_name, runs.run_id, runs.status, runs.started_at,
+                        documents.node_id, documents.label,
+                        snippet(documents_fts, 1, '', '', '…', ?) AS snippet,
+                        documents_fts.rank AS rank
+                 FROM documents_fts
+                 JOIN documents ON documents.id = documents_fts.rowid
+                 JOIN runs ON runs.run_id = documents.run_id
+                 WHERE documents_fts MATCH ? AND (? IS NULL OR runs.flow_id = ?)
+             )
+             SELECT * FROM (
+                 SELECT *, row_number() OVER (PARTITION BY run_id ORDER BY rank) AS nth FROM hits
+             )
+             WHERE nth = 1
+             ORDER BY rank
+             LIMIT ?",
+        )
+        .bind(SNIPPET_TOKENS)
+        .bind(expr)
+        .bind(flow_id.map(str::to_string))
+        .bind(flow_id.map(str::to_string))
+        .bind(limit.min(MAX_SEARCH_LIMIT) as i64)
+        .fetch_all(&pool)
+        .await
+        .context("run search failed")?;
+        pool.close().await;
+
+        Ok(rows
+            .iter()
+            .map(|row| RunHit {
+                flow_id: row.get("flow_id"),
+                flow_name: row.get("flow_name"),
+                run_id: row.get("run_id"),
+                status: serde_json::from_value(serde_json::Value::String(row.get("status")))
+                    .unwrap_or(RunStatus::Failed),
+                started_at: DateTime::parse_from_rfc3339(row.get("started_at"))
+                    .map(|t| t.with_timezone(&Utc))
+                    .unwrap_or_default(),
+                node_id: row.get("node_id"),
+                label: row.get("label"),
+                snippet: row.get("snippet"),
+            })
+            .collect())
+    }
+}
+
+/// Index one run, unless a concurrent sync just did. Returns whether it
+/// was added.
+async fn add(pool: &SqlitePool, flow: &Flow, run: &FlowRun, documents: &[Document]) -> Result<bool> {
+    let status = serde_json::to_value(run.status)?.as_str().unwrap_or_default().to_string();
+    let mut tx = pool.begin().await?;
+    let inserted = sqlx::query(
+        "INSERT OR IGNORE INTO runs (run_id, flow_id, flow_name, status, started_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&run.id)
+    .bind(&flow.id)
+    .bind(&flow.name)
+    .bind(status)
+    .bind(run.started_at.to_rfc3339())
+    .execute(&mut *tx)
+    .await
+    .context("failed to index run")?
+    .rows_affected();
+    if inserted == 0 {
+        return Ok(false);
+    }
+    for document in documents {
+        sqlx::query("INSERT INTO documents (run_id, node_id, label, text) VALUES (?, ?, ?, ?)")
+            .bind(&run.id)
+            .bind(&document.node_id)
+            .bind(&document.label)
+            .bind(&document.text)
+            .execute(&mut *tx)
+            .await
+            .context("failed to index run output")?;
+    }
+    tx.commit().await.context("failed to index run")?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::flows::file_repository::FileFlowRepository;
+    use crate::flows::graph::NodeOutput;
+
+    fn flow(id: &str, name: &str) -> Flow {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "nodes": [
+                { "id": "scan", "node_type": "executor", "kind": "claude-code", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Triage advisories" },
+                { "id": "post", "node_type": "sink", "kind": "slack", "config": {}, "position": { "x": 0, "y": 0 }, "label": "Post" },
+            ],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn run(flow_id: &str, id: &str, output: &str, preview: &str) -> FlowRun {
+        serde_json::from_value(json!({
+            "id": id,
+            "flow_id": flow_id,
+            "status": "success",
+            "started_at": "2026-06-01T00:00:00Z",
+            "finished_at": "2026-06-01T00:01:00Z",
+            "node_runs": [{
+                "node_id": "scan", "status": "success",
+                "started_at": "2026-06-01T00:00:00Z", "finished_at": "2026-06-01T00:01:00Z",
+                "output_preview": preview,
+            }],
+            "error": null,
+            "output": output,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_documents() {
+        let run = run("f1", "r1", "Digest", "Patch CVE-2024…");
+        let mut saved = RunOutputs {
+            flow_id: "f1".into(),
+            run_id: "r1".into(),
+            context: HashMap::new(),
+            outputs: HashMap::from([("scan".to_string(), NodeOutput::Text("Patch CVE-2024-3094 now".into(), None))]),
+            outcomes: HashMap::new(),
+            saved_at: Utc::now(),
+        };
+        let docs = documents(&flow("f1", "Security"), &run, Some(&saved));
+        assert_eq!(docs.len(), 2);
+        assert_eq!((docs[0].node_id.as_deref(), docs[0].label.as_str()), (None, OUTPUT_LABEL));
+        assert_eq!((docs[1].label.as_str(), docs[1].text.as_str()), ("Triage advisories", "Patch CVE-2024-3094 now"));
+
+        saved.outputs.clear();
+        assert_eq!(documents(&flow("f1", "Security"), &run, Some(&saved))[1].text, "Patch CVE-2024…");
+    }
+
+    #[tokio::test]
+    async fn test_sync_and_search() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        let outputs = RunOutputStore::new(dir.path());
+        let index = RunIndex::new(dir.path());
+        repo.save_flow(flow("f1", "Security")).await.unwrap();
+        repo.save_flow(flow("f2", "Digest")).await.unwrap();
+        repo.add_run(run("f1", "r1", "Upgrade xz: CVE-2024-3094 is exploited", "CVE-2024-3094 in xz")).await.unwrap();
+        repo.add_run(run("f1", "r2", "Nothing new today", "")).await.unwrap();
+        repo.add_run(run("f2", "r3", "Rust 1.80 ships", "Rust 1.80")).await.unwrap();
+
+        assert_eq!(index.sync(&repo, &outputs).await.unwrap(), 3);
+        assert_eq!(index.sync(&repo, &outputs).await.unwrap(), 0);
+
+        // One hit per run, however many of its outputs match
+        let hits = index.search("CVE-2024-3094", None, 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].run_id.as_str(), hits[0].flow_name.as_str()), ("r1", "Security"));
+        assert!(hits[0].snippet.contains("3094"));
+
+        assert_eq!(index.search("rust", Some("f2"), 10).await.unwrap()[0].run_id, "r3");
+        assert!(index.search("rust", Some("f1"), 10).await.unwrap().is_empty());
+        assert!(index.search("", None, 10).await.unwrap().is_empty());
+
+        // Pruned runs drop out
+        repo.delete_run("f1", "r1").await.unwrap();
+        index.sync(&repo, &outputs).await.unwrap();
+        assert!(index.search("CVE-2024-3094", None, 10).await.unwrap().is_empty());
+    }
+}
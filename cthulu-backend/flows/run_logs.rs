@@ -0,0 +1,307 @@
+//! Per-run logs (`~/.cthulu/state/run-logs/{flow_id}/{run_id}.jsonl`): the
+//! tracing events emitted inside a run's `flow_run` span — node starts and
+//! finishes, executor tool calls, errors — saved as structured lines, so a
+//! failed overnight run can be read back without grepping the server's
+//! stdout. Kept for as many runs per flow as the run history.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, OnceLock};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::flows::history::MAX_RUNS_PER_FLOW;
+
+/// Events kept per run; a chatty executor stops being logged past this.
+pub const MAX_EVENTS_PER_RUN: usize = 5000;
+
+/// The data directory logs are written under, set on start.
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Open `flow_run` spans, by span id, and the run each logs to.
+static RUNS: LazyLock<Mutex<HashMap<Id, Scope>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct Scope {
+    path: PathBuf,
+    written: usize,
+}
+
+pub fn init(data_dir: &Path) {
+    let _ = DATA_DIR.set(data_dir.to_path_buf());
+}
+
+/// Log the events inside `span` to run `run_id` of `flow_id`. A no-op until
+/// `init`, or when the span is disabled.
+pub fn attach(span: &tracing::Span, flow_id: &str, run_id: &str) {
+    let (Some(id), Some(data_dir)) = (span.id(), DATA_DIR.get()) else {
+        return;
+    };
+    let store = RunLogStore::new(data_dir);
+    store.prune(flow_id);
+    let path = store.path(flow_id, run_id);
+    RUNS.lock().unwrap().insert(id, Scope { path, written: 0 });
+}
+
+/// One logged event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunLogEvent {
+    pub at: DateTime<Utc>,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`.
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Label of the node it came from, when it came from one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+impl RunLogEvent {
+    /// Whether it's at `level` or more severe.
+    pub fn at_least(&self, level: Level) -> bool {
+        self.level.parse::<Level>().is_ok_and(|l| l <= level)
+    }
+}
+
+#[derive(Clone)]
+pub struct RunLogStore {
+    dir: PathBuf,
+}
+
+impl RunLogStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { dir: data_dir.join("state").join("run-logs") }
+    }
+
+    fn path(&self, flow_id: &str, run_id: &str) -> PathBuf {
+        self.dir.join(flow_id).join(format!("{run_id}.jsonl"))
+    }
+
+    fn append(path: &Path, event: &RunLogEvent) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// The run's events, oldest first; empty if none were logged.
+    pub fn load(&self, flow_id: &str, run_id: &str) -> Vec<RunLogEvent> {
+        let Ok(content) = std::fs::read_to_string(self.path(flow_id, run_id)) else {
+            return Vec::new();
+        };
+        content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+    }
+
+    /// Bytes the run's log takes on disk.
+    pub fn bytes(&self, flow_id: &str, run_id: &str) -> u64 {
+        std::fs::metadata(self.path(flow_id, run_id)).map_or(0, |m| m.len())
+    }
+
+    /// Drop the flow's oldest logs beyond the history's length, leaving
+    /// room for one more.
+    fn prune(&self, flow_id: &str) {
+        let Ok(entries) = std::fs::read_dir(self.dir.join(flow_id)) else {
+            return;
+        };
+        let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        if files.len() >= MAX_RUNS_PER_FLOW {
+            files.sort();
+            for (_, old) in &files[..=files.len() - MAX_RUNS_PER_FLOW] {
+                let _ = std::fs::remove_file(old);
+            }
+        }
+    }
+
+    /// Drop one run's log (the run was pruned).
+    pub fn delete(&self, flow_id: &str, run_id: &str) -> Result<()> {
+        let path = self.path(flow_id, run_id);
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Drop every run log of `flow_id` (the flow was deleted).
+    pub fn delete_flow(&self, flow_id: &str) -> Result<()> {
+        let flow_dir = self.dir.join(flow_id);
+        if !flow_dir.exists() {
+            return Ok(());
+        }
+        std::fs::remove_dir_all(&flow_dir).with_context(|| format!("failed to remove {}", flow_dir.display()))
+    }
+}
+
+/// The label of the node a `node` span runs.
+struct NodeLabel(String);
+
+/// Collects an event's or span's fields as JSON.
+#[derive(Default)]
+struct Fields {
+    message: Option<String>,
+    fields: Map<String, Value>,
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record_value(field, Value::String(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_value(field, Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_value(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_value(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_value(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_value(field, value.into());
+    }
+}
+
+impl Fields {
+    fn record_value(&mut self, field: &Field, value: Value) {
+        match (field.name(), value) {
+            ("message", Value::String(message)) => self.message = Some(message),
+            (name, value) => {
+                self.fields.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+/// Writes the events inside attached `flow_run` spans to their run's log.
+pub struct RunLogLayer;
+
+impl<S> Layer<S> for RunLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        if attrs.metadata().name() != "node" {
+            return;
+        }
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let (Some(Value::String(label)), Some(span)) = (fields.fields.remove("node"), ctx.span(id)) {
+            span.extensions_mut().insert(NodeLabel(label));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        let mut runs = RUNS.lock().unwrap();
+        let mut node = None;
+        let mut run_span = None;
+        for span in scope {
+            if node.is_none()
+                && let Some(label) = span.extensions().get::<NodeLabel>()
+            {
+                node = Some(label.0.clone());
+            }
+            if runs.contains_key(&span.id()) {
+                run_span = Some(span.id());
+                break;
+            }
+        }
+        let Some(run) = run_span.and_then(|id| runs.get_mut(&id)) else {
+            return;
+        };
+        if run.written > MAX_EVENTS_PER_RUN {
+            return;
+        }
+
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let mut entry = RunLogEvent {
+            at: Utc::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: fields.message.unwrap_or_default(),
+            node: match fields.fields.remove("node") {
+                Some(Value::String(label)) => Some(label),
+                _ => node,
+            },
+            fields: fields.fields,
+        };
+        if run.written == MAX_EVENTS_PER_RUN {
+            entry = RunLogEvent {
+                level: Level::WARN.to_string(),
+                target: module_path!().to_string(),
+                message: format!("Log truncated at {MAX_EVENTS_PER_RUN} events"),
+                node: None,
+                fields: Map::new(),
+                ..entry
+            };
+        }
+        run.written += 1;
+        // Can't log a failure here: it would come straight back
+        let _ = RunLogStore::append(&run.path, &entry);
+    }
+
+    fn on_close(&self, id: Id, _ctx: LayerContext<'_, S>) {
+        RUNS.lock().unwrap().remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_captures_run_events() {
+        let dir = tempfile::tempdir().unwrap();
+        init(dir.path());
+        let subscriber = tracing_subscriber::registry().with(RunLogLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("outside any run");
+            let span = tracing::info_span!("flow_run", flow = "Digest", run = "abc");
+            attach(&span, "f1", "run-1");
+            tracing::info!(parent: &span, nodes = 3, "▶ Started");
+            let _run = span.enter();
+            let node = tracing::info_span!("node", node = "Summarize");
+            node.in_scope(|| tracing::debug!(source = "claude", tool = "WebFetch", "Tool: WebFetch"));
+            tracing::error!(node = "Notify", error = "403", "✗ Node failed");
+        });
+
+        let events = RunLogStore::new(dir.path()).load("f1", "run-1");
+        let messages: Vec<&str> = events.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["▶ Started", "Tool: WebFetch", "✗ Node failed"]);
+        assert_eq!(events[0].fields["nodes"], 3);
+        assert_eq!(events[1].node.as_deref(), Some("Summarize"));
+        assert_eq!(events[1].fields["tool"], "WebFetch");
+        assert_eq!((events[2].node.as_deref(), events[2].level.as_str()), (Some("Notify"), "ERROR"));
+        assert!(events[2].at_least(Level::WARN) && !events[1].at_least(Level::INFO));
+    }
+}
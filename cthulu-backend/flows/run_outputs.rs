@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::flows::graph::NodeOutput;
+use crate::flows::history::{FlowRun, MAX_RUNS_PER_FLOW, RunStatus};
+use crate::flows::wait::Resume;
+
+/// What a run's finished nodes produced, saved level by level, so a run
+/// that failed late can be resumed from where it broke instead of redoing
+/// the scrapes and executor calls before it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunOutputs {
+    pub flow_id: String,
+    pub run_id: String,
+    /// The run's trigger variables.
+    pub context: HashMap<String, String>,
+    /// Outputs of the nodes that succeeded.
+    pub outputs: HashMap<String, NodeOutput>,
+    /// Outcomes of the branches among them.
+    pub outcomes: HashMap<String, bool>,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl RunOutputs {
+    /// The level `run` broke at: the first with a node that didn't succeed.
+    /// `None` when every node that ran succeeded (the run itself failed,
+    /// e.g. on a budget).
+    pub fn failed_level(run: &FlowRun) -> Option<usize> {
+        run.node_runs
+            .iter()
+            .filter(|node_run| node_run.status != RunStatus::Success)
+            .filter_map(|node_run| node_run.level)
+            .min()
+    }
+
+    /// Where resuming `run` picks up: the outputs of the nodes in levels
+    /// before the one it broke at, for the new run to take as done.
+    pub fn resume_point(self, run: &FlowRun) -> (HashMap<String, String>, Resume) {
+        let failed_level = Self::failed_level(run);
+        let levels: HashMap<&str, Option<usize>> =
+            run.node_runs.iter().map(|node_run| (node_run.node_id.as_str(), node_run.level)).collect();
+        // Nodes without a node run (the trigger) come before everything
+        let before_failure = |node_id: &str| match (levels.get(node_id).copied().flatten(), failed_level) {
+            (Some(level), Some(failed)) => level < failed,
+            _ => true,
+        };
+        let outputs: HashMap<String, NodeOutput> = self
+            .outputs
+            .into_iter()
+            .filter(|(node_id, _)| before_failure(node_id))
+            .collect();
+        let outcomes = self
+            .outcomes
+            .into_iter()
+            .filter(|(node_id, _)| outputs.contains_key(node_id))
+            .collect();
+        (self.context, Resume { outputs, outcomes, resume_at: HashMap::new() })
+    }
+}
+
+/// Per-node outputs of recent runs
+/// (`~/.cthulu/state/run-outputs/{flow_id}/{run_id}.json`), kept for as many
+/// runs per flow as the run history.
+#[derive(Clone)]
+pub struct RunOutputStore {
+    dir: PathBuf,
+}
+
+impl RunOutputStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { dir: data_dir.join("state").join("run-outputs") }
+    }
+
+    fn path(&self, flow_id: &str, run_id: &str) -> PathBuf {
+        self.dir.join(flow_id).join(format!("{run_id}.json"))
+    }
+
+    /// Save (or overwrite) a run's outputs, dropping the flow's oldest
+    /// runs' beyond the history's length.
+    pub fn save(&self, saved: &RunOutputs) -> Result<()> {
+        let path = self.path(&saved.flow_id, &saved.run_id);
+        let flow_dir = self.dir.join(&saved.flow_id);
+        std::fs::create_dir_all(&flow_dir)
+            .with_context(|| format!("failed to create {}", flow_dir.display()))?;
+        std::fs::write(&path, serde_json::to_string(saved)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        let mut files: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(&flow_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        if files.len() > MAX_RUNS_PER_FLOW {
+            files.sort();
+            for (_, old) in &files[..files.len() - MAX_RUNS_PER_FLOW] {
+                let _ = std::fs::remove_file(old);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load(&self, flow_id: &str, run_id: &str) -> Option<RunOutputs> {
+        let content = std::fs::read_to_string(self.path(flow_id, run_id)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Bytes the run's saved outputs take on disk.
+    pub fn bytes(&self, flow_id: &str, run_id: &str) -> u64 {
+        std::fs::metadata(self.path(flow_id, run_id)).map_or(0, |m| m.len())
+    }
+
+    /// Drop one run's saved outputs (the run was pruned).
+    pub fn delete(&self, flow_id: &str, run_id: &str) -> Result<()> {
+        let path = self.path(flow_id, run_id);
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Drop every saved run of `flow_id` (the flow was deleted).
+    pub fn delete_flow(&self, flow_id: &str) -> Result<()> {
+        let flow_dir = self.dir.join(flow_id);
+        if !flow_dir.exists() {
+            return Ok(());
+        }
+        std::fs::remove_dir_all(&flow_dir).with_context(|| format!("failed to remove {}", flow_dir.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::NodeRun;
+
+    fn node_run(node_id: &str, level: usize, status: RunStatus) -> NodeRun {
+        NodeRun {
+            node_id: node_id.into(),
+            status,
+            started_at: Utc::now(),
+            finished_at: Some(Utc::now()),
+            output_preview: None,
+            prompt_variant: None,
+            cost_usd: None,
+            attempts: Vec::new(),
+            level: Some(level),
+            ready_at: None,
+        }
+    }
+
+    #[test]
+    fn test_resume_point() {
+        let run = FlowRun {
+            id: "r".into(),
+            flow_id: "f".into(),
+            status: RunStatus::Failed,
+            started_at: Utc::now(),
+            finished_at: None,
+            node_runs: vec![
+                node_run("scrape", 1, RunStatus::Success),
+                node_run("check", 2, RunStatus::Success),
+                node_run("summarize", 3, RunStatus::Success),
+                node_run("review", 3, RunStatus::Failed),
+                node_run("post", 4, RunStatus::Failed),
+            ],
+            error: None,
+            output: None,
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
+        };
+        assert_eq!(RunOutputs::failed_level(&run), Some(3));
+
+        let text = |t: &str| NodeOutput::Text(t.into(), None);
+        let saved = RunOutputs {
+            flow_id: "f".into(),
+            run_id: "r".into(),
+            context: HashMap::from([("topic".to_string(), "rust".to_string())]),
+            outputs: HashMap::from([
+                ("trigger".to_string(), NodeOutput::Context(HashMap::new())),
+                ("scrape".to_string(), text("pages")),
+                ("check".to_string(), text("ok")),
+                ("summarize".to_string(), text("summary")),
+            ]),
+            outcomes: HashMap::from([("check".to_string(), true)]),
+            saved_at: Utc::now(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = RunOutputStore::new(dir.path());
+        store.save(&saved).unwrap();
+        let (context, resume) = store.load("f", "r").unwrap().resume_point(&run);
+        assert_eq!(context["topic"], "rust");
+        let mut reused: Vec<&str> = resume.outputs.keys().map(String::as_str).collect();
+        reused.sort();
+        // The whole failed level runs again, including what succeeded in it
+        assert_eq!(reused, ["check", "scrape", "trigger"]);
+        assert_eq!(resume.outcomes.get("check"), Some(&true));
+
+        store.delete_flow("f").unwrap();
+        assert!(store.load("f", "r").is_none());
+    }
+}
@@ -1,27 +1,71 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use chrono::Utc;
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
 use tracing::Instrument;
 use uuid::Uuid;
 
 use tokio::sync::broadcast;
 
 use crate::agents::repository::AgentRepository;
+use crate::egress::{self, Origin};
+use crate::flows::branch::{self, BRANCH_KIND, Condition};
+use crate::flows::concurrency;
+use crate::flows::dry_run;
+use crate::flows::costs::{Budget, CostLedger};
 use crate::flows::events::{RunEvent, RunEventType};
+use crate::flows::experiments::PromptSplit;
 use crate::flows::graph::{self, NodeOutput};
-use crate::flows::history::{FlowRun, NodeRun, RunStatus};
+use crate::flows::inputs;
+use crate::flows::leader;
+use crate::flows::history::{FlowRun, MAX_RUNS_PER_FLOW, NodeRun, RunStatus};
 use crate::flows::processors::{self, NodeDeps};
+use crate::flows::recovery::OnInterrupt;
 use crate::flows::repository::FlowRepository;
 use crate::flows::session_bridge::SessionBridge;
+use crate::flows::subflow::{self, SUBFLOW_KIND, Subflow};
+use crate::flows::subscriptions::SubscriptionStore;
+use crate::flows::loops::{LOOP_KIND, LoopBody};
+use crate::flows::timeouts::{NodeLimit, OnTimeout, RunLimits, TimedOut};
+use crate::flows::transform::TRANSFORM_KIND;
+use crate::flows::aggregate::{AGGREGATE_KIND, Aggregate, AggregateStore, Collected, items_of};
+use crate::flows::wait::{Checkpoint, Resume, WAIT_KIND, Wait, WaitStore};
+use crate::flows::run_logs;
+use crate::flows::run_outputs::{RunOutputStore, RunOutputs};
 use crate::flows::{Flow, NodeType};
 use crate::github::client::GithubClient;
+use crate::harness;
+use crate::maintenance;
+use crate::prompts::repository::PromptRepository;
 use crate::sandbox::provider::SandboxProvider;
+use crate::tasks::archive::ArchiveStore;
 use crate::tasks::context::render_prompt;
+use crate::tasks::executors::cache::ExecutorCache;
+use crate::tasks::feedback::{FEEDBACK_RANK_KIND, FeedbackStore};
 use crate::tasks::pipeline::format_items;
+use crate::tasks::sources::cache::SourceCache;
+use crate::tasks::sources::seen::SeenStore;
 use crate::tasks::sources::{self, ContentItem};
+use crate::tasks::trending::{TRENDING_KIND, TrendingStore};
+use crate::tasks::audio::AudioStore;
+use crate::tasks::vectors::VectorStore;
+
+/// How long a node that hit its time limit gets to clean up (an executor
+/// killing its process, a sandbox being destroyed) before it is dropped.
+const KILL_GRACE: Duration = Duration::from_secs(10);
+
+/// What a node's work in a level came to: its output, and what loop or
+/// subflow runs inside it cost (executors report their own).
+type NodeResult = Result<(NodeOutput, Option<f64>)>;
+
+/// A node of a level that finished (or panicked), with its time limit.
+type Settled = (String, Option<NodeLimit>, std::result::Result<NodeResult, tokio::task::JoinError>);
 
 /// Data returned by `prepare_session()` — everything needed to start
 /// an interactive Claude Code session for a flow.
@@ -46,6 +90,34 @@ pub struct FlowRunner {
     pub agent_repo: Option<Arc<dyn AgentRepository>>,
     /// Session bridge for routing executor output to agent workspaces.
     pub session_bridge: Option<SessionBridge>,
+    /// Cache for executor nodes that opt in with `cache_ttl_secs`.
+    pub executor_cache: Option<ExecutorCache>,
+    /// Cache for source nodes that opt in with `cache_ttl_secs`.
+    pub source_cache: Option<SourceCache>,
+    /// Prompt library for resolving `prompt_ref` on executor nodes.
+    pub prompt_repo: Option<Arc<dyn PromptRepository>>,
+    /// Cross-run "seen item" state for sources with `dedupe: true`.
+    pub seen_store: Option<SeenStore>,
+    /// Searchable archive of items from sources with `archive: true`.
+    pub archive: Option<ArchiveStore>,
+    /// Per-user subscriptions that get a copy of each successful run's output.
+    pub subscriptions: Option<SubscriptionStore>,
+    /// Items delivered with 👍/👎 buttons and their votes.
+    pub feedback: Option<FeedbackStore>,
+    /// Item history for `trending` nodes.
+    pub trending: Option<TrendingStore>,
+    /// Embedded items for `vector-store` and `vector-search` nodes.
+    pub vectors: Option<VectorStore>,
+    /// Episodes of `audio` sinks' podcast feeds.
+    pub audio: Option<AudioStore>,
+    /// Per-flow spend, for triggers with `max_flow_cost_usd`.
+    pub costs: Option<CostLedger>,
+    /// Checkpoints of runs held at `wait` nodes, to resume after a restart.
+    pub waits: Option<WaitStore>,
+    /// Batches `aggregate` nodes hold between runs.
+    pub aggregates: Option<AggregateStore>,
+    /// Each run's node outputs, to resume it from where it failed.
+    pub run_outputs: Option<RunOutputStore>,
 }
 
 impl FlowRunner {
@@ -78,7 +150,20 @@ impl FlowRunner {
         let executor_node = flow
             .nodes
             .iter()
-            .find(|n| n.node_type == NodeType::Executor)
+            .find(|n| {
+                n.node_type == NodeType::Executor
+                    && ![
+                        FEEDBACK_RANK_KIND,
+                        TRENDING_KIND,
+                        TRANSFORM_KIND,
+                        BRANCH_KIND,
+                        LOOP_KIND,
+                        SUBFLOW_KIND,
+                        WAIT_KIND,
+                        AGGREGATE_KIND,
+                    ]
+                    .contains(&n.kind.as_str())
+            })
             .context("flow has no executor node")?;
 
         let source_nodes: Vec<_> = flow
@@ -116,14 +201,20 @@ impl FlowRunner {
 
         // 1. Fetch sources
         let source_configs = processors::parse_source_configs(&source_nodes)?;
-        let github_token = self
-            .github_client
-            .as_ref()
-            .and_then(|_| std::env::var("GITHUB_TOKEN").ok());
+        let github_token = match &self.github_client {
+            Some(client) => client.access_token().await.ok(),
+            None => None,
+        };
 
         let items: Vec<ContentItem> = if !source_configs.is_empty() {
-            sources::fetch_all(&source_configs, &self.http_client, github_token.as_deref())
-                .await
+            sources::fetch_all(
+                &source_configs,
+                &self.http_client,
+                github_token.as_deref(),
+                self.sandbox_provider.as_ref(),
+                None,
+            )
+            .await
         } else {
             vec![]
         };
@@ -137,11 +228,9 @@ impl FlowRunner {
         vars.insert("item_count".to_string(), items.len().to_string());
         vars.insert("timestamp".to_string(), timestamp);
 
-        let prompt_path = executor_node.config["prompt"]
-            .as_str()
-            .context("executor node missing 'prompt' config")?;
-
-        let prompt_template = processors::load_prompt_template(prompt_path)?;
+        let prompt_template =
+            processors::executor_prompt_template(executor_node, self.prompt_repo.as_deref(), None)
+                .await?;
 
         // Fetch market data if needed
         if prompt_template.contains("{{market_data}}") {
@@ -245,6 +334,7 @@ impl FlowRunner {
         // their own messages. We still resolve it so the UI can show it.
         let prompt = executor_node.config["prompt"]
             .as_str()
+            .or_else(|| executor_node.config["prompt_ref"].as_str())
             .unwrap_or("")
             .to_string();
 
@@ -269,7 +359,67 @@ impl FlowRunner {
         repo: &dyn FlowRepository,
         context: Option<HashMap<String, String>>,
     ) -> Result<FlowRun> {
+        self.execute_rerun(flow, repo, context, None).await
+    }
+
+    /// Execute a flow as a rerun of the run `rerun_of` (one the server was
+    /// stopped in the middle of), or as a fresh run when `None`.
+    pub async fn execute_rerun(
+        &self,
+        flow: &Flow,
+        repo: &dyn FlowRepository,
+        context: Option<HashMap<String, String>>,
+        rerun_of: Option<String>,
+    ) -> Result<FlowRun> {
+        self.run_flow(flow, repo, context, rerun_of, &[], None).await
+    }
+
+    /// Run `flow` again in place of its `failed` run, from the level that
+    /// failed: the nodes before it aren't run again, their `saved` outputs
+    /// are used instead.
+    pub async fn resume_failed(
+        &self,
+        flow: &Flow,
+        repo: &dyn FlowRepository,
+        failed: &FlowRun,
+        saved: RunOutputs,
+    ) -> Result<FlowRun> {
+        let (context, resume) = saved.resume_point(failed);
+        tracing::info!(flow = %flow.name, failed = %failed.id, reused = resume.outputs.len(), "Resuming failed run");
+        self.run_flow(flow, repo, Some(context), Some(failed.id.clone()), &[], Some(resume)).await
+    }
+
+    /// Run `flow`, called as a subflow by the `callers` flows (the nearest
+    /// last) or directly when there are none, going on from `resume` if set.
+    async fn run_flow(
+        &self,
+        flow: &Flow,
+        repo: &dyn FlowRepository,
+        context: Option<HashMap<String, String>>,
+        rerun_of: Option<String>,
+        callers: &[String],
+        resume: Option<Resume>,
+    ) -> Result<FlowRun> {
+        // New runs hold through maintenance mode; a subflow's caller is already going
+        if callers.is_empty() && maintenance::resumed().await {
+            tracing::info!(flow = %flow.name, "Run held through maintenance mode, starting");
+        }
+        // Past the run limits, new runs queue by priority; subflows go with their caller's
+        let _slot = match callers.is_empty() {
+            true => {
+                let mut vars = inputs::default_vars(flow);
+                vars.extend(context.clone().unwrap_or_default());
+                Some(concurrency::run_slot(flow, concurrency::priority(flow, &vars)?).await?)
+            }
+            false => None,
+        };
         let has_context = context.is_some();
+        // Keep the inputs around only when an interrupted run would be
+        // started over with them
+        let inputs = match OnInterrupt::for_flow(flow) {
+            Ok(OnInterrupt::Rerun) => context.clone(),
+            _ => None,
+        };
         let run_id = Uuid::new_v4().to_string();
         let short_id = &run_id[..8];
         let run = FlowRun {
@@ -280,6 +430,14 @@ impl FlowRunner {
             finished_at: None,
             node_runs: vec![],
             error: None,
+            output: None,
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs,
+            rerun_of,
+            dry_run: dry_run::current().is_some(),
+            replica: leader::replica_id(),
         };
         repo.add_run(run.clone()).await?;
 
@@ -287,10 +445,51 @@ impl FlowRunner {
         self.emit(&flow.id, &run_id, None, RunEventType::RunStarted, format!("Flow execution started{ctx_label}"));
 
         let span = tracing::info_span!("flow_run", flow = %flow.name, run = %short_id);
+        run_logs::attach(&span, &flow.id, &run_id);
         tracing::info!(parent: &span, nodes = flow.nodes.len(), edges = flow.edges.len(), "▶ Started{ctx_label}");
+        self.finish_run(flow, repo, run, context, callers, resume, span).await
+    }
 
+    /// Go on with `run`, which the server stopped while it was held at wait
+    /// nodes, from the `checkpoints` they left.
+    pub async fn resume(
+        &self,
+        flow: &Flow,
+        repo: &dyn FlowRepository,
+        run: FlowRun,
+        checkpoints: Vec<Checkpoint>,
+    ) -> Result<FlowRun> {
+        let (context, resume) = Resume::from_checkpoints(checkpoints);
+        let replica = leader::replica_id();
+        if run.replica != replica {
+            repo.set_run_replica(&flow.id, &run.id, replica).await?;
+        }
+        self.emit(&flow.id, &run.id, None, RunEventType::RunStarted, "Flow execution resumed");
+        let span = tracing::info_span!("flow_run", flow = %flow.name, run = %&run.id[..8.min(run.id.len())]);
+        run_logs::attach(&span, &flow.id, &run.id);
+        tracing::info!(parent: &span, waits = resume.resume_at.len(), "▶ Resumed");
+        self.finish_run(flow, repo, run, Some(context), &[], Some(resume), span).await
+    }
+
+    /// Execute the started `run` and record how it ended.
+    #[allow(clippy::too_many_arguments)]
+    async fn finish_run(
+        &self,
+        flow: &Flow,
+        repo: &dyn FlowRepository,
+        run: FlowRun,
+        context: Option<HashMap<String, String>>,
+        callers: &[String],
+        resume: Option<Resume>,
+        span: tracing::Span,
+    ) -> Result<FlowRun> {
+        let run_id = run.id.clone();
+        let _running = maintenance::track(&run_id);
         let start = std::time::Instant::now();
-        let result = self.execute_inner(flow, &run_id, repo, context).instrument(span.clone()).await;
+        let result = self
+            .execute_inner(flow, &run_id, repo, context, callers, resume)
+            .instrument(span.clone())
+            .await;
         let elapsed = start.elapsed();
 
         // Determine final status: if execute_inner returned Ok but any node failed, mark as Failed
@@ -307,6 +506,19 @@ impl FlowRunner {
 
         repo.complete_run(&flow.id, &run_id, final_status, final_error.clone()).await?;
 
+        // Deduped source items only count as seen once a run has used them
+        // (which a dry run hasn't)
+        let dry = dry_run::current().is_some();
+        match (&self.seen_store, final_status) {
+            (Some(seen), RunStatus::Success) if !dry => {
+                if let Err(e) = seen.commit(&run_id).await {
+                    tracing::warn!(parent: &span, error = %e, "Failed to save source dedupe state");
+                }
+            }
+            (Some(seen), _) => seen.discard(&run_id),
+            (None, _) => {}
+        }
+
         match final_status {
             RunStatus::Success => {
                 self.emit(&flow.id, &run_id, None, RunEventType::RunCompleted, format!("Completed in {:.1}s", elapsed.as_secs_f64()));
@@ -326,6 +538,20 @@ impl FlowRunner {
             .find(|r| r.id == run_id)
             .unwrap_or(run);
 
+        // Fan the output out to subscribers; time-shifted ones wait in the outbox
+        if let (Some(store), RunStatus::Success, false) = (&self.subscriptions, final_status, dry) {
+            match store.enqueue_run(flow, &run) {
+                Ok(0) => {}
+                Ok(queued) => {
+                    tracing::info!(parent: &span, queued, "Queued subscription deliveries");
+                    let store = store.clone();
+                    let http_client = Arc::clone(&self.http_client);
+                    tokio::spawn(async move { store.deliver_due(&http_client).await });
+                }
+                Err(e) => tracing::warn!(parent: &span, error = %e, "Failed to queue subscription deliveries"),
+            }
+        }
+
         // If execute_inner itself errored (not just node failures), propagate
         if let Err(e) = result {
             return Err(e);
@@ -348,11 +574,24 @@ impl FlowRunner {
         run_id: &str,
         repo: &dyn FlowRepository,
         context: Option<HashMap<String, String>>,
+        callers: &[String],
+        resume: Option<Resume>,
     ) -> Result<bool> {
+        let limits = RunLimits::for_flow(flow, Instant::now())?;
+        let budget = Budget::for_flow(flow)?;
+
         // Topo sort all nodes
         let sorted = graph::topo_sort(&flow.nodes, &flow.edges)?;
         let (_, parents) = graph::build_adjacency(&flow.nodes, &flow.edges);
-        let levels = graph::compute_levels(&sorted, &parents);
+        // Loop bodies run inside their loop node, once per item
+        let bodies = graph::loop_bodies(&flow.nodes, &flow.edges, &sorted)?;
+        let in_body: HashSet<&str> = bodies.values().flatten().map(String::as_str).collect();
+        let scheduled: Vec<String> = sorted
+            .iter()
+            .filter(|id| !in_body.contains(id.as_str()))
+            .cloned()
+            .collect();
+        let levels = graph::compute_levels(&scheduled, &parents);
 
         // Build node lookup
         let node_map: HashMap<&str, &crate::flows::Node> =
@@ -360,14 +599,35 @@ impl FlowRunner {
 
         // Per-node output storage
         let mut outputs: HashMap<String, NodeOutput> = HashMap::new();
+        // Outcome of each branch node that ran, for routing its out-edges
+        let mut outcomes: HashMap<String, bool> = HashMap::new();
 
-        // Inject context as trigger output if provided (GitHub PR path)
-        if let Some(ctx) = context {
-            if let Some(trigger) = flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) {
-                outputs.insert(trigger.id.clone(), NodeOutput::Context(ctx));
-            }
+        // Defaults of the trigger's declared inputs fill in anything the caller
+        // (run inputs, GitHub PR context) didn't provide.
+        let mut trigger_vars = inputs::default_vars(flow);
+        trigger_vars.extend(context.unwrap_or_default());
+
+        // Inject context as trigger output if there is any
+        if !trigger_vars.is_empty()
+            && let Some(trigger) = flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger)
+        {
+            outputs.insert(trigger.id.clone(), NodeOutput::Context(trigger_vars.clone()));
         }
 
+        // A resumed run goes on from the level it was held at
+        let Resume { outputs: done, outcomes: decided, mut resume_at } = resume.unwrap_or_default();
+        let finished: HashSet<String> = done.keys().cloned().collect();
+        outputs.extend(done);
+        outcomes.extend(decided);
+
+        let last_run_at = repo
+            .get_runs(&flow.id, MAX_RUNS_PER_FLOW)
+            .await
+            .into_iter()
+            .find(|r| r.id != run_id && r.status == RunStatus::Success && !r.dry_run)
+            .map(|r| r.started_at);
+
+        let priority = concurrency::priority(flow, &trigger_vars)?;
         let deps = NodeDeps {
             http_client: Arc::clone(&self.http_client),
             github_client: self.github_client.clone(),
@@ -377,54 +637,124 @@ impl FlowRunner {
             session_bridge: self.session_bridge.clone(),
             run_id: Some(run_id.to_string()),
             flow_name: Some(flow.name.clone()),
+            executor_cache: self.executor_cache.clone(),
+            source_cache: self.source_cache.clone(),
+            prompt_repo: self.prompt_repo.clone(),
+            seen_store: self.seen_store.clone(),
+            archive: self.archive.clone(),
+            last_run_at,
+            trigger_vars,
+            subscriptions: self.subscriptions.clone(),
+            feedback: self.feedback.clone(),
+            trending: self.trending.clone(),
+            vectors: self.vectors.clone(),
+            audio: self.audio.clone(),
+            timeout: None,
+            harness: harness::current(),
+            dry_run: dry_run::current(),
+            priority,
         };
 
+        if let Some(reason) = budget.exhausted(self.flow_spent(&flow.id, &budget)?) {
+            self.alert_cost(&budget, flow, &reason, &deps);
+            anyhow::bail!("{reason}");
+        }
+        let mut run_cost = 0.0;
+        // This run's costs the ledger failed to record
+        let mut unrecorded = 0.0;
+
         let mut any_failed = false;
+        // When each node finished, for when its children's inputs were ready
+        let run_started = Utc::now();
+        let mut finished_at: HashMap<String, chrono::DateTime<Utc>> = HashMap::new();
+        // Each failed node's label and error (for a skipped node, the failure
+        // upstream that skipped it), passed along on_failure edges
+        let mut failures: HashMap<String, (String, String)> = HashMap::new();
+
+        for (level_index, level) in levels.iter().enumerate() {
+            if let Some(timeout) = limits.expired(Instant::now()) {
+                anyhow::bail!("run timed out after {}s", timeout.as_secs());
+            }
 
-        for level in &levels {
             // For nodes within a level that can run in parallel, we collect futures
             // However, since nodes in the same level are independent (no edges between them),
             // we can process them concurrently
-            let mut handles: Vec<(String, tokio::task::JoinHandle<Result<NodeOutput>>)> = Vec::new();
+            let mut handles = Vec::new();
+            // Subflows run here rather than spawned, as they need the repo
+            let mut subflows = Vec::new();
 
             for node_id in level {
                 let node = match node_map.get(node_id.as_str()) {
                     Some(n) => *n,
                     None => continue,
                 };
+                if finished.contains(node_id) {
+                    continue;
+                }
 
                 // Triggers: just mark as Empty if no context was injected
                 if node.node_type == NodeType::Trigger {
                     outputs.entry(node_id.clone()).or_insert(NodeOutput::Empty);
+                    finished_at.insert(node_id.clone(), run_started);
                     continue;
                 }
 
-                // Collect & merge parent outputs
-                let parent_outputs: Vec<NodeOutput> = parents
-                    .get(node_id.as_str())
-                    .map(|pids| {
-                        pids.iter()
-                            .filter_map(|p| outputs.get(p).cloned())
-                            .collect()
+                // Collect & merge parent outputs along taken edges. A node that
+                // only untaken branches (or skipped nodes) lead to is skipped.
+                let incoming: Vec<&crate::flows::Edge> = flow
+                    .edges
+                    .iter()
+                    .filter(|e| e.target == *node_id && node_map.contains_key(e.source.as_str()))
+                    .collect();
+                let parent_outputs: Vec<NodeOutput> = incoming
+                    .iter()
+                    .filter(|e| branch::edge_taken(e, &outcomes))
+                    .filter_map(|e| {
+                        branch::carried(e, outputs.get(&e.source)?, || match failures.get(&e.source) {
+                            Some((label, error)) => format!("{label} failed in {}: {error}", flow.name),
+                            None => format!("{} failed in {}", node_map[e.source.as_str()].label, flow.name),
+                        })
                     })
-                    .unwrap_or_default();
+                    .collect();
+                if !incoming.is_empty() && parent_outputs.is_empty() {
+                    tracing::info!(node = %node.label, "Skipping node — branch not taken");
+                    continue;
+                }
                 let input = NodeOutput::merge(parent_outputs);
 
                 // Skip if any parent failed (propagate failure sentinel)
                 if matches!(input, NodeOutput::Failed) {
+                    // Keep the first failure upstream, for on_failure edges further down
+                    if let Some(failure) = incoming.iter().find_map(|e| failures.get(&e.source).cloned()) {
+                        failures.insert(node_id.clone(), failure);
+                    }
                     outputs.insert(node_id.clone(), NodeOutput::Failed);
                     any_failed = true;
                     tracing::warn!(node = %node.label, "Skipping node — upstream failed");
                     continue;
                 }
 
+                let limit = limits.for_node(node, Instant::now())?;
+
                 // Record node run start
+                let ready_at = incoming
+                    .iter()
+                    .filter_map(|e| finished_at.get(&e.source))
+                    .max()
+                    .copied()
+                    .unwrap_or(run_started);
                 let node_run = NodeRun {
                     node_id: node_id.clone(),
                     status: RunStatus::Running,
                     started_at: Utc::now(),
                     finished_at: None,
                     output_preview: None,
+                    prompt_variant: PromptSplit::from_node(node)
+                        .map(|split| split.choose(run_id).to_string()),
+                    cost_usd: None,
+                    attempts: Vec::new(),
+                    level: Some(level_index),
+                    ready_at: Some(ready_at),
                 };
                 repo.push_node_run(&flow.id, run_id, node_run).await?;
                 self.emit(
@@ -435,20 +765,159 @@ impl FlowRunner {
                     format!("Processing {}...", node.label),
                 );
 
+                // Branches are decided here, so their outcome routes this level's children
+                if node.kind == BRANCH_KIND {
+                    let vars = &deps.trigger_vars;
+                    match Condition::for_node(node).and_then(|c| c.evaluate(&input, vars)) {
+                        Ok(outcome) => {
+                            let preview = format!("Condition {outcome}");
+                            self.emit(&flow.id, run_id, Some(node_id), RunEventType::NodeCompleted, &preview);
+                            tracing::info!(node = %node.label, outcome, "✓ Branch decided");
+                            repo.complete_node_run(&flow.id, run_id, node_id, RunStatus::Success, Some(preview))
+                                .await?;
+                            outcomes.insert(node_id.clone(), outcome);
+                            outputs.insert(node_id.clone(), input);
+                            finished_at.insert(node_id.clone(), Utc::now());
+                        }
+                        Err(e) => {
+                            let err_msg = format!("{e:#}");
+                            self.emit(&flow.id, run_id, Some(node_id), RunEventType::NodeFailed, &err_msg);
+                            tracing::error!(node = %node.label, error = %err_msg, "✗ Node failed");
+                            failures.insert(node_id.clone(), (node.label.clone(), err_msg.clone()));
+                            repo.complete_node_run(&flow.id, run_id, node_id, RunStatus::Failed, Some(err_msg))
+                                .await?;
+                            outputs.insert(node_id.clone(), NodeOutput::Failed);
+                            any_failed = true;
+                        }
+                    }
+                    continue;
+                }
+
+                // Aggregates release their batch here, or hold it and end this path
+                if node.kind == AGGREGATE_KIND {
+                    match self.collect(flow, node, input) {
+                        Ok(Collected::Released(items)) => {
+                            let preview = format!("Released {} items", items.len());
+                            self.emit(&flow.id, run_id, Some(node_id), RunEventType::NodeCompleted, &preview);
+                            tracing::info!(node = %node.label, items = items.len(), "✓ Batch released");
+                            repo.complete_node_run(&flow.id, run_id, node_id, RunStatus::Success, Some(preview))
+                                .await?;
+                            outputs.insert(node_id.clone(), NodeOutput::Items(items));
+                            finished_at.insert(node_id.clone(), Utc::now());
+                        }
+                        Ok(Collected::Held(count)) => {
+                            let preview = format!("Holding {count} items");
+                            self.emit(&flow.id, run_id, Some(node_id), RunEventType::NodeCompleted, &preview);
+                            tracing::info!(node = %node.label, held = count, "Batch held");
+                            repo.complete_node_run(&flow.id, run_id, node_id, RunStatus::Success, Some(preview))
+                                .await?;
+                        }
+                        Err(e) => {
+                            let err_msg = format!("{e:#}");
+                            self.emit(&flow.id, run_id, Some(node_id), RunEventType::NodeFailed, &err_msg);
+                            tracing::error!(node = %node.label, error = %err_msg, "✗ Node failed");
+                            failures.insert(node_id.clone(), (node.label.clone(), err_msg.clone()));
+                            repo.complete_node_run(&flow.id, run_id, node_id, RunStatus::Failed, Some(err_msg))
+                                .await?;
+                            outputs.insert(node_id.clone(), NodeOutput::Failed);
+                            any_failed = true;
+                        }
+                    }
+                    continue;
+                }
+
+                if node.kind == SUBFLOW_KIND {
+                    subflows.push((node, input, limit));
+                    continue;
+                }
+
+                if node.kind == WAIT_KIND {
+                    let until = match resume_at.remove(node_id) {
+                        Some(at) => Ok(at),
+                        None => Wait::for_node(node).map(|wait| wait.resume_at(Utc::now())),
+                    };
+                    let checkpoint = until.map(|until| Checkpoint {
+                        flow_id: flow.id.clone(),
+                        run_id: run_id.to_string(),
+                        node_id: node_id.clone(),
+                        resume_at: until,
+                        context: deps.trigger_vars.clone(),
+                        outputs: outputs.clone(),
+                        outcomes: outcomes.clone(),
+                    });
+                    if let Ok(checkpoint) = &checkpoint {
+                        let until = checkpoint.resume_at;
+                        let message = format!("Waiting until {}", until.format("%Y-%m-%d %H:%M UTC"));
+                        self.emit(&flow.id, run_id, Some(node_id), RunEventType::NodeStarted, &message);
+                        tracing::info!(node = %node.label, until = %until, "Waiting");
+                    }
+                    // Subflow runs aren't resumed on their own, so only the top level saves its waits
+                    let store = self.waits.clone().filter(|_| callers.is_empty());
+                    let handle = tokio::spawn(async move {
+                        wait_out(store, checkpoint?).await?;
+                        Ok((input, None))
+                    });
+                    handles.push((node_id.clone(), limit, handle));
+                    continue;
+                }
+
                 // Spawn task for parallel execution within the level
                 let node_clone = node.clone();
                 let deps_clone = deps.clone();
-                let handle = tokio::spawn(async move {
-                    processors::process_node(&node_clone, input, &deps_clone).await
-                });
-                handles.push((node_id.clone(), handle));
+                let body = bodies
+                    .get(node_id)
+                    .map(|body| LoopBody::new(flow, node, body))
+                    .transpose()?;
+                let limits_clone = limits.clone();
+                let node_span = tracing::info_span!("node", node = %node.label);
+                let handle = tokio::spawn(
+                    async move {
+                        match body {
+                            Some(body) => within(limit, body.run(input, &deps_clone, &limits_clone))
+                                .await
+                                .map(|looped| (looped.output, Some(looped.cost_usd))),
+                            None => run_node(&node_clone, input, &deps_clone, limit)
+                                .await
+                                .map(|output| (output, None)),
+                        }
+                    }
+                    .instrument(node_span),
+                );
+                handles.push((node_id.clone(), limit, handle));
+            }
+            let mut chain = callers.to_vec();
+            chain.push(flow.id.clone());
+            let mut unsettled: Vec<String> = handles.iter().map(|(id, ..)| id.clone()).collect();
+            unsettled.extend(subflows.iter().map(|(node, ..)| node.id.clone()));
+            let aborts: Vec<tokio::task::AbortHandle> =
+                handles.iter().map(|(.., handle)| handle.abort_handle()).collect();
+            let mut pending: FuturesUnordered<BoxFuture<'_, Settled>> = FuturesUnordered::new();
+            for (node_id, limit, handle) in handles {
+                pending.push(Box::pin(async move { (node_id, limit, handle.await) }));
+            }
+            for (node, input, limit) in subflows {
+                let call = within(limit, self.run_subflow(node, input, &deps, repo, &chain));
+                pending.push(Box::pin(async move { (node.id.clone(), limit, Ok(call.await)) }));
             }
 
-            // Await all parallel tasks in this level
-            for (node_id, handle) in handles {
+            // Take the level's nodes in the order they finish, so each run's end
+            // time (and its children's ready time) is when it really finished
+            let mut abort: Option<String> = None;
+            while abort.is_none() {
+                // Left only sleeping out saved waits, the run is safe to stop: it resumes after a restart
+                let saved_waits = callers.is_empty()
+                    && self.waits.is_some()
+                    && unsettled.iter().all(|id| node_map[id.as_str()].kind == WAIT_KIND);
+                let held = saved_waits.then(|| maintenance::hold(run_id));
+                let Some((node_id, limit, joined)) = pending.next().await else {
+                    break;
+                };
+                drop(held);
                 let node = node_map[node_id.as_str()];
-                match handle.await {
-                    Ok(Ok(output)) => {
+                unsettled.retain(|id| *id != node_id);
+                finished_at.insert(node_id.clone(), Utc::now());
+                match joined {
+                    Ok(Ok((output, inner_cost))) => {
                         // Build preview for node run
                         let preview = match &output {
                             NodeOutput::Items(items) => format!("{} items", items.len()),
@@ -490,6 +959,40 @@ impl FlowRunner {
                             Some(preview),
                         )
                         .await?;
+                        if let NodeOutput::Text(_, Some(er)) = &output
+                            && !er.attempts.is_empty()
+                        {
+                            repo.set_node_run_attempts(&flow.id, run_id, &node_id, er.attempts.clone())
+                                .await?;
+                        }
+                        let cost = match &output {
+                            NodeOutput::Text(_, Some(er)) => Some(er.cost_usd),
+                            _ => inner_cost.filter(|cost| *cost > 0.0),
+                        };
+                        if let Some(cost) = cost {
+                            repo.set_node_run_cost(&flow.id, run_id, &node_id, cost)
+                                .await?;
+                            run_cost += cost;
+                            match self.costs.as_ref().map(|costs| costs.add(&flow.id, Utc::now().date_naive(), cost)) {
+                                Some(Ok(())) => {}
+                                Some(Err(e)) => {
+                                    tracing::warn!(error = %e, "Failed to record flow cost");
+                                    unrecorded += cost;
+                                }
+                                None => unrecorded += cost,
+                            }
+                            // Re-read, since the flow's other runs spend from the same budget
+                            let reason = match self.flow_spent(&flow.id, &budget) {
+                                Ok(flow_spent) => budget.exceeded(run_cost, flow_spent + unrecorded),
+                                Err(e) => Some(format!("{e:#}")),
+                            };
+                            if abort.is_none()
+                                && let Some(reason) = reason
+                            {
+                                self.alert_cost(&budget, flow, &reason, &deps);
+                                abort = Some(reason);
+                            }
+                        }
                         outputs.insert(node_id, output);
                     }
                     Ok(Err(e)) => {
@@ -502,6 +1005,18 @@ impl FlowRunner {
                             &err_msg,
                         );
                         tracing::error!(node = %node.label, error = %err_msg, "✗ Node failed");
+                        if let Some(limit) = limit
+                            && limit.on_timeout == OnTimeout::Abort
+                            && e.downcast_ref::<TimedOut>().is_some()
+                        {
+                            abort = Some(match limits.expired(Instant::now()) {
+                                Some(timeout) if limit.run_deadline => {
+                                    format!("run timed out after {}s", timeout.as_secs())
+                                }
+                                _ => format!("{} timed out after {}s", node.label, limit.timeout.as_secs()),
+                            });
+                        }
+                        failures.insert(node_id.clone(), (node.label.clone(), err_msg.clone()));
                         repo.complete_node_run(
                             &flow.id,
                             run_id,
@@ -523,6 +1038,7 @@ impl FlowRunner {
                             &err_msg,
                         );
                         tracing::error!(node = %node.label, error = %err_msg, "✗ Node panicked");
+                        failures.insert(node_id.clone(), (node.label.clone(), err_msg.clone()));
                         repo.complete_node_run(
                             &flow.id,
                             run_id,
@@ -536,12 +1052,186 @@ impl FlowRunner {
                     }
                 }
             }
+            if let Some(store) = &self.run_outputs {
+                let saved = RunOutputs {
+                    flow_id: flow.id.clone(),
+                    run_id: run_id.to_string(),
+                    context: deps.trigger_vars.clone(),
+                    outputs: outputs
+                        .iter()
+                        .filter(|(_, output)| !matches!(output, NodeOutput::Failed))
+                        .map(|(id, output)| (id.clone(), output.clone()))
+                        .collect(),
+                    outcomes: outcomes.clone(),
+                    saved_at: Utc::now(),
+                };
+                if let Err(e) = store.save(&saved) {
+                    tracing::warn!(error = %e, "Failed to save node outputs");
+                }
+            }
+            if let Some(reason) = abort {
+                // Stop what's still running in the level
+                drop(pending);
+                for handle in &aborts {
+                    handle.abort();
+                }
+                let err_msg = format!("aborted: {reason}");
+                for node_id in unsettled {
+                    self.emit(&flow.id, run_id, Some(&node_id), RunEventType::NodeFailed, &err_msg);
+                    repo.complete_node_run(&flow.id, run_id, &node_id, RunStatus::Failed, Some(err_msg.clone()))
+                        .await?;
+                }
+                anyhow::bail!("{reason}");
+            }
+        }
+
+        // Keep the executors' full text so the outputs feed can serve it;
+        // the run itself already succeeded, so a failed save only loses that.
+        if let Some(text) = final_output(&sorted, &node_map, &outputs)
+            && let Err(e) = repo.set_run_output(&flow.id, run_id, text).await
+        {
+            tracing::warn!(run_id, error = %e, "Failed to save run output");
         }
 
         Ok(any_failed)
     }
 }
 
+impl FlowRunner {
+    /// Add `input` to aggregate `node`'s batch, taking the batch if it's due.
+    fn collect(&self, flow: &Flow, node: &crate::flows::Node, input: NodeOutput) -> Result<Collected> {
+        let store = self.aggregates.as_ref().context("aggregate requires the aggregate store")?;
+        let aggregate = Aggregate::for_node(node)?;
+        let now = Utc::now();
+        store.collect(&flow.id, &node.id, items_of(input, &node.label, now), &aggregate, now)
+    }
+
+    /// Run the flow subflow `node` names on `input`, for the `callers`
+    /// flows (this one last): its final output, and what its run cost.
+    fn run_subflow<'a>(
+        &'a self,
+        node: &'a crate::flows::Node,
+        input: NodeOutput,
+        deps: &'a NodeDeps,
+        repo: &'a dyn FlowRepository,
+        callers: &'a [String],
+    ) -> BoxFuture<'a, NodeResult> {
+        Box::pin(async move {
+            let subflow = Subflow::for_node(node)?;
+            subflow.check_callers(callers)?;
+            let flow = repo
+                .get_flow(&subflow.flow_id)
+                .await
+                .with_context(|| format!("subflow {} not found", subflow.flow_id))?;
+            let context = subflow::context(&input, &deps.trigger_vars);
+            tracing::info!(node = %node.label, subflow = %flow.name, "Running subflow");
+            let run = self.run_flow(&flow, repo, Some(context), None, callers, None).await?;
+            if run.status != RunStatus::Success {
+                anyhow::bail!(
+                    "subflow {} failed: {}",
+                    flow.name,
+                    run.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+            let output = run.output.map_or(NodeOutput::Empty, |text| NodeOutput::Text(text, None));
+            Ok((output, run.cost_usd))
+        })
+    }
+
+    /// What the flow has spent this budget period, from the ledger. Only a
+    /// flow budget needs it, and an unreadable ledger fails rather than
+    /// reading as nothing spent.
+    fn flow_spent(&self, flow_id: &str, budget: &Budget) -> Result<f64> {
+        let Some(costs) = self.costs.as_ref().filter(|_| budget.max_flow_usd.is_some()) else {
+            return Ok(0.0);
+        };
+        costs
+            .spent_since(flow_id, budget.period.start(Utc::now().date_naive()))
+            .context("can't check max_flow_cost_usd")
+    }
+
+    /// Tell the flow's `cost_alert` sink, if any, why its run was stopped.
+    fn alert_cost(&self, budget: &Budget, flow: &Flow, reason: &str, deps: &NodeDeps) {
+        let Some(sink) = budget.alert.clone() else {
+            return;
+        };
+        let message = format!("💸 {} stopped: {reason}", flow.name);
+        let deps = deps.clone();
+        tokio::spawn(async move {
+            if let Err(e) = processors::process_node(&sink, NodeOutput::Text(message, None), &deps).await {
+                tracing::warn!(error = %e, "Failed to send cost alert");
+            }
+        });
+    }
+}
+
+/// Hold a run at a wait node until its checkpoint's `resume_at`, keeping
+/// the checkpoint in `store` meanwhile so a restart resumes it.
+async fn wait_out(store: Option<WaitStore>, checkpoint: Checkpoint) -> Result<()> {
+    if let Some(store) = &store {
+        store.save(checkpoint.clone())?;
+    }
+    let remaining = (checkpoint.resume_at - Utc::now()).to_std().unwrap_or_default();
+    tokio::time::sleep(remaining).await;
+    // Waits due during maintenance mode hold until it ends
+    maintenance::resumed().await;
+    if let Some(store) = &store
+        && let Err(e) = store.remove(&checkpoint.run_id, &checkpoint.node_id)
+    {
+        tracing::warn!(error = %e, "Failed to clear wait checkpoint");
+    }
+    Ok(())
+}
+
+/// Run `node` on `input`, failing it once `limit` is up. Its outbound
+/// requests are logged as the node's (and, in test harness mode, answered by
+/// the run's harness).
+pub(crate) async fn run_node(
+    node: &crate::flows::Node,
+    input: NodeOutput,
+    deps: &NodeDeps,
+    limit: Option<NodeLimit>,
+) -> Result<NodeOutput> {
+    let mut deps = deps.clone();
+    deps.timeout = limit.map(|l| l.timeout);
+    let origin = Origin { flow_id: deps.flow_id.clone(), node_id: node.id.clone() };
+    let work = egress::scope(origin, processors::process_node(node, input, &deps));
+    within(limit, harness::scope(deps.harness.clone(), work)).await
+}
+
+/// Await `work`, or fail with [`TimedOut`] once `limit` (plus a grace
+/// period) is up.
+async fn within<T>(limit: Option<NodeLimit>, work: impl Future<Output = Result<T>>) -> Result<T> {
+    match limit {
+        // Dropping the node kills any process it started
+        Some(limit) => tokio::time::timeout(limit.timeout + KILL_GRACE, work)
+            .await
+            .unwrap_or_else(|_| Err(TimedOut(limit.timeout).into())),
+        None => work.await,
+    }
+}
+
+/// Join the text outputs of executor nodes, in execution order.
+fn final_output(
+    sorted: &[String],
+    node_map: &HashMap<&str, &crate::flows::Node>,
+    outputs: &HashMap<String, NodeOutput>,
+) -> Option<String> {
+    let texts: Vec<&str> = sorted
+        .iter()
+        .filter(|id| {
+            node_map
+                .get(id.as_str())
+                .is_some_and(|n| n.node_type == NodeType::Executor && n.kind != BRANCH_KIND)
+        })
+        .filter_map(|id| match outputs.get(id) {
+            Some(NodeOutput::Text(text, _)) if !text.trim().is_empty() => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    (!texts.is_empty()).then(|| texts.join("\n\n---\n\n"))
+}
+
 fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
         s.to_string()
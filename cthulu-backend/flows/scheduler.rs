@@ -1,25 +1,55 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use croner::Cron;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
 
 use crate::agents::repository::AgentRepository;
 use crate::api::FlowSessions;
+use crate::flows::debounce::{Coalescer, DebounceConfig};
 use crate::flows::events::RunEvent;
+use crate::flows::history::MAX_RUNS_PER_FLOW;
+use crate::flows::leader::LeaseTable;
+use crate::flows::misfire::Misfire;
+use crate::flows::pauses::PauseStore;
+use crate::flows::recovery;
 use crate::flows::repository::FlowRepository;
 use crate::flows::runner::FlowRunner;
 use crate::flows::session_bridge::SessionBridge;
-use crate::flows::NodeType;
+use crate::flows::stagger::{self, Stagger};
+use crate::flows::subscriptions::SubscriptionStore;
+use crate::flows::trigger_log::{EventStatus, LoggedEvent, SeenPrs, TriggerEvent, TriggerLog};
+use crate::flows::window::{self, ActiveWindow, Gate, WindowQueue};
+use crate::flows::{Flow, NodeType};
 use crate::github::client::GithubClient;
-use crate::github::models::RepoConfig;
+use crate::github::commands::{self, PrCommand};
+use crate::github::models::{Commit, Issue, PullRequest, Release, RepoConfig};
+use crate::github::repo_context;
+use crate::github::webhook::{IssueCommentEvent, IssuesEvent, PullRequestEvent, PushEvent, ReleaseEvent};
+use crate::maintenance;
+use crate::prompts::repository::PromptRepository;
 use crate::sandbox::provider::SandboxProvider;
-use crate::tasks::diff;
+use crate::tasks::{checks, diff, risk};
+use crate::tasks::executors::cache::ExecutorCache;
+use crate::tasks::sources::cache::SourceCache;
+use crate::tasks::archive::ArchiveStore;
+use crate::tasks::feedback::FeedbackStore;
+use crate::tasks::trending::TrendingStore;
+use crate::flows::costs::CostLedger;
+use crate::flows::aggregate::AggregateStore;
+use crate::flows::wait::WaitStore;
+use crate::flows::run_outputs::RunOutputStore;
+use crate::tasks::audio::AudioStore;
+use crate::tasks::vectors::VectorStore;
+use crate::tasks::sources::seen::SeenStore;
 
 pub struct FlowScheduler {
     flow_repo: Arc<dyn FlowRepository>,
@@ -27,14 +57,28 @@ pub struct FlowScheduler {
     github_client: Option<Arc<dyn GithubClient>>,
     events_tx: broadcast::Sender<RunEvent>,
     handles: Mutex<HashMap<String, JoinHandle<()>>>,
-    seen_prs: Arc<Mutex<HashMap<String, HashMap<u64, String>>>>,
+    seen_prs: Arc<Mutex<SeenPrs>>,
     sandbox_provider: Arc<dyn SandboxProvider>,
     agent_repo: Arc<dyn AgentRepository>,
+    prompt_repo: Arc<dyn PromptRepository>,
     /// Fields needed to construct SessionBridge for flow runs.
     interact_sessions: Arc<tokio::sync::RwLock<HashMap<String, FlowSessions>>>,
     sessions_path: std::path::PathBuf,
     data_dir: std::path::PathBuf,
     session_streams: Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>,
+    /// PR events arrive on `/api/webhooks/github`, so github-pr triggers
+    /// don't poll.
+    github_webhooks: bool,
+    /// Pending event batches of triggers with `debounce_secs` set.
+    coalescer: Coalescer,
+    /// Events held until their trigger's `active_window` opens.
+    window_queue: WindowQueue,
+    /// Another replica schedules (see `leader`), so triggers don't start.
+    standby: AtomicBool,
+    /// Where trigger changes go while on standby, for the leader to apply.
+    cluster: OnceLock<Arc<LeaseTable>>,
+    /// Offsets of cron flows sharing a schedule.
+    stagger: Arc<Stagger>,
 }
 
 impl FlowScheduler {
@@ -45,10 +89,12 @@ impl FlowScheduler {
         events_tx: broadcast::Sender<RunEvent>,
         sandbox_provider: Arc<dyn SandboxProvider>,
         agent_repo: Arc<dyn AgentRepository>,
+        prompt_repo: Arc<dyn PromptRepository>,
         interact_sessions: Arc<tokio::sync::RwLock<HashMap<String, FlowSessions>>>,
         sessions_path: std::path::PathBuf,
         data_dir: std::path::PathBuf,
         session_streams: Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>,
+        github_webhooks: bool,
     ) -> Self {
         Self {
             flow_repo,
@@ -56,13 +102,21 @@ impl FlowScheduler {
             github_client,
             events_tx,
             handles: Mutex::new(HashMap::new()),
-            seen_prs: Arc::new(Mutex::new(HashMap::new())),
+            // PR heads seen before the last restart
+            seen_prs: Arc::new(Mutex::new(TriggerLog::new(&data_dir).seen_prs())),
             sandbox_provider,
             agent_repo,
+            prompt_repo,
             interact_sessions,
             sessions_path,
             data_dir,
             session_streams,
+            github_webhooks,
+            coalescer: Coalescer::new(),
+            window_queue: WindowQueue::new(),
+            standby: AtomicBool::new(false),
+            cluster: OnceLock::new(),
+            stagger: Arc::new(Stagger::from_env()),
         }
     }
 
@@ -75,18 +129,192 @@ impl FlowScheduler {
         }
     }
 
-    pub async fn start_all(&self) {
+    /// A runner with the scheduler's dependencies, for every run it starts,
+    /// trigger loops included.
+    fn build_runner(&self) -> FlowRunner {
+        FlowRunner {
+            http_client: self.http_client.clone(),
+            github_client: self.github_client.clone(),
+            events_tx: Some(self.events_tx.clone()),
+            sandbox_provider: Some(self.sandbox_provider.clone()),
+            agent_repo: Some(self.agent_repo.clone()),
+            session_bridge: Some(self.build_session_bridge()),
+            executor_cache: Some(ExecutorCache::new(&self.data_dir)),
+            source_cache: Some(SourceCache::new(&self.data_dir)),
+            seen_store: Some(SeenStore::new(&self.data_dir)),
+            archive: Some(ArchiveStore::new(&self.data_dir)),
+            subscriptions: Some(SubscriptionStore::new(&self.data_dir)),
+            feedback: Some(FeedbackStore::new(&self.data_dir)),
+            trending: Some(TrendingStore::new(&self.data_dir)),
+            vectors: Some(VectorStore::new(&self.data_dir)),
+            audio: Some(AudioStore::new(&self.data_dir)),
+            costs: Some(CostLedger::new(&self.data_dir)),
+            waits: Some(WaitStore::new(&self.data_dir)),
+            aggregates: Some(AggregateStore::new(&self.data_dir)),
+            run_outputs: Some(RunOutputStore::new(&self.data_dir)),
+            prompt_repo: Some(self.prompt_repo.clone()),
+        }
+    }
+
+    /// Settle what the last process left behind: resume its runs held at
+    /// wait nodes, mark its other unfinished runs interrupted, start over
+    /// the ones whose flow asks for it, and destroy their orphaned
+    /// sandboxes. Call before any trigger starts. Clustered, runs of the
+    /// `live` replicas are theirs to finish, and sandboxes are kept.
+    pub async fn recover_interrupted_runs(&self, live: &[String]) {
+        let held = recovery::held_runs(&*self.flow_repo, &WaitStore::new(&self.data_dir), live).await;
+        let held_ids: Vec<String> = held.iter().map(|(_, run, _)| run.id.clone()).collect();
+        let reruns = recovery::interrupt_stale_runs(&*self.flow_repo, &held_ids, live).await;
+        // Sandboxes don't say whose run they're for, so with other
+        // replicas up they may be in use
+        if live.is_empty() {
+            recovery::reap_orphans(&*self.sandbox_provider).await;
+        }
+        for (flow, run, checkpoints) in held {
+            let runner = self.build_runner();
+            let repo = self.flow_repo.clone();
+            tokio::spawn(async move {
+                tracing::info!(flow = %flow.name, run_id = %run.id, "Resuming run held at a wait");
+                if let Err(e) = runner.resume(&flow, &*repo, run, checkpoints).await {
+                    tracing::error!(flow = %flow.name, error = %e, "Resumed run failed");
+                }
+            });
+        }
+        for (flow, run) in reruns {
+            let runner = self.build_runner();
+            let repo = self.flow_repo.clone();
+            tokio::spawn(async move {
+                tracing::info!(flow = %flow.name, run_id = %run.id, "Rerunning interrupted run");
+                if let Err(e) = runner.execute_rerun(&flow, &*repo, run.inputs, Some(run.id)).await {
+                    tracing::error!(flow = %flow.name, error = %e, "Rerun of interrupted run failed");
+                }
+            });
+        }
+    }
+
+    /// Redo the PR reviews detected before the last stop whose runs never
+    /// started. Reviews whose runs did start are left to run recovery.
+    pub async fn replay_trigger_events(&self) {
+        let log = TriggerLog::new(&self.data_dir);
+        let pending = match log.recover() {
+            Ok(pending) => pending,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to read the trigger event log");
+                return;
+            }
+        };
+        for event in pending {
+            let id = event.id.clone();
+            if !self.replay_review(event, &log).await {
+                log.resume(id).set(EventStatus::Done);
+            }
+        }
+    }
+
+    /// Start the logged review `event` again, unless its flow, repo or PR is
+    /// gone.
+    async fn replay_review(&self, event: TriggerEvent, log: &TriggerLog) -> bool {
+        let Ok(payload) = serde_json::from_value::<ReviewPayload>(event.payload) else {
+            return false;
+        };
+        let Some(flow) = self.flow_repo.get_flow(&event.flow_id).await.filter(|f| f.enabled) else {
+            return false;
+        };
+        let Some(github_client) = self.github_client.clone() else {
+            return false;
+        };
+        let Some(repo) = flow
+            .nodes
+            .iter()
+            .find(|n| n.node_type == NodeType::Trigger && n.kind == "github-pr")
+            .and_then(|trigger| {
+                parse_repo_configs(&trigger.config)
+                    .into_iter()
+                    .find(|r| r.full_name() == payload.repo)
+            })
+        else {
+            return false;
+        };
+        let pr = match github_client.fetch_single_pr(&repo.owner, &repo.repo, payload.pr_number).await {
+            Ok(pr) => pr,
+            Err(e) => {
+                tracing::warn!(repo = %payload.repo, pr = payload.pr_number, error = %e, "Failed to fetch PR to redo its review");
+                return false;
+            }
+        };
+
+        tracing::info!(flow = %flow.name, pr = pr.number, "Redoing PR review detected before the last stop");
+        let runner = self.build_runner();
+        let flow_repo = self.flow_repo.clone();
+        let logged = log.resume(event.id);
+        tokio::spawn(async move {
+            review_pr(runner, &flow, &*flow_repo, &*github_client, &repo, pr, payload.review_type, logged).await;
+        });
+        true
+    }
+
+    pub async fn start_all(self: &Arc<Self>) {
         let flows = self.flow_repo.list_flows().await;
         for flow in flows {
             if flow.enabled {
                 if let Err(e) = self.start_flow(&flow.id).await {
                     tracing::error!(flow = %flow.name, error = %e, "Failed to start flow trigger");
+                    continue;
                 }
+                self.catch_up_missed(&flow).await;
             }
         }
     }
 
-    pub async fn start_flow(&self, flow_id: &str) -> Result<()> {
+    /// Run the fires `flow`'s cron trigger missed while the server was down,
+    /// as its `misfire` says, counting from its last run or, if later, its
+    /// last change (being enabled, say). Each run gets the fire time it
+    /// stands in for as `scheduled_at`.
+    async fn catch_up_missed(&self, flow: &Flow) {
+        let Some(trigger) = flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger && n.kind == "cron") else {
+            return;
+        };
+        if PauseStore::new(&self.data_dir).paused_since(&flow.id).is_some() {
+            return;
+        }
+        let Ok(misfire) = Misfire::from_trigger(&trigger.config) else {
+            return;
+        };
+        let Some(cron) = trigger.config["schedule"].as_str().and_then(|s| Cron::new(s).parse().ok()) else {
+            return;
+        };
+        let Ok(timezone) = cron_timezone(&trigger.config) else {
+            return;
+        };
+        let last_run = self
+            .flow_repo
+            .get_runs(&flow.id, MAX_RUNS_PER_FLOW)
+            .await
+            .into_iter()
+            .find(|r| !r.dry_run)
+            .map(|r| r.started_at);
+        let since = last_run.map_or(flow.updated_at, |at| at.max(flow.updated_at));
+        let missed = misfire.catch_up(&cron, timezone, since, Utc::now());
+        if missed.is_empty() {
+            return;
+        }
+
+        tracing::info!(flow = %flow.name, missed = missed.len(), policy = ?misfire, "Catching up on missed cron fires");
+        let runner = self.build_runner();
+        let flow = flow.clone();
+        let flow_repo = self.flow_repo.clone();
+        tokio::spawn(async move {
+            maintenance::resumed().await;
+            for fire in missed {
+                let context = HashMap::from([("scheduled_at".to_string(), fire.to_rfc3339())]);
+                if let Err(e) = runner.execute(&flow, &*flow_repo, Some(context)).await {
+                    tracing::error!(flow = %flow.name, scheduled_at = %fire, error = %e, "Catch-up run failed");
+                }
+            }
+        });
+    }
+
+    pub async fn start_flow(self: &Arc<Self>, flow_id: &str) -> Result<()> {
         let flow = self
             .flow_repo
             .get_flow(flow_id)
@@ -97,6 +325,10 @@ impl FlowScheduler {
             tracing::debug!(flow = %flow.name, "Flow is disabled, not starting trigger");
             return Ok(());
         }
+        if self.standby.load(Ordering::Relaxed) {
+            tracing::debug!(flow = %flow.name, "Another replica schedules, not starting trigger");
+            return Ok(());
+        }
 
         let trigger_node = match flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) {
             Some(n) => n,
@@ -105,6 +337,7 @@ impl FlowScheduler {
                 return Ok(());
             }
         };
+        ActiveWindow::for_flow(&flow)?;
 
         match trigger_node.kind.as_str() {
             "cron" => {
@@ -112,33 +345,18 @@ impl FlowScheduler {
                     .as_str()
                     .context("cron trigger missing 'schedule'")?
                     .to_string();
+                let timezone = cron_timezone(&trigger_node.config)?;
+                Misfire::from_trigger(&trigger_node.config)?;
+                let jitter_secs = stagger::jitter_secs(&trigger_node.config)?;
+                self.stagger.register(&flow.id, &schedule, timezone);
 
                 let flow_id = flow.id.clone();
                 let flow_name = flow.name.clone();
-                let flow_repo = self.flow_repo.clone();
-                let http_client = self.http_client.clone();
-                let github_client = self.github_client.clone();
-                let events_tx = self.events_tx.clone();
-
-                tracing::info!(flow = %flow.name, schedule = %schedule, "Started cron trigger");
+                tracing::info!(flow = %flow.name, schedule = %schedule, timezone = %timezone, "Started cron trigger");
 
-                let sandbox_provider = self.sandbox_provider.clone();
-                let agent_repo = self.agent_repo.clone();
-                let session_bridge = self.build_session_bridge();
+                let scheduler = self.clone();
                 let handle = tokio::spawn(async move {
-                    cron_loop(
-                        &flow_id,
-                        &flow_name,
-                        &schedule,
-                        flow_repo,
-                        http_client,
-                        github_client,
-                        events_tx,
-                        sandbox_provider,
-                        agent_repo,
-                        session_bridge,
-                    )
-                    .await;
+                    cron_loop(scheduler, &flow_id, &flow_name, &schedule, timezone, jitter_secs).await;
                 });
                 self.handles.lock().await.insert(flow.id.clone(), handle);
             }
@@ -146,39 +364,90 @@ impl FlowScheduler {
                 let github_client = self
                     .github_client
                     .clone()
-                    .context("GitHub PR trigger requires GITHUB_TOKEN")?;
+                    .context("GitHub PR trigger requires GITHUB_TOKEN or GitHub App credentials (GITHUB_APP_ID)")?;
+
+                if self.github_webhooks {
+                    tracing::info!(flow = %flow.name, "GitHub PR trigger receives webhook events, not polling");
+                    return Ok(());
+                }
 
                 let flow_id = flow.id.clone();
                 let flow_name = flow.name.clone();
-                let flow_repo = self.flow_repo.clone();
-                let http_client = self.http_client.clone();
-                let seen_prs = self.seen_prs.clone();
                 let trigger_config = trigger_node.config.clone();
-                let events_tx = self.events_tx.clone();
-
-                let sandbox_provider = self.sandbox_provider.clone();
-                let agent_repo = self.agent_repo.clone();
-                let session_bridge = self.build_session_bridge();
+                let scheduler = self.clone();
                 let handle = tokio::spawn(async move {
-                    github_pr_loop(
-                        &flow_id,
-                        &flow_name,
-                        trigger_config,
-                        flow_repo,
-                        http_client,
-                        github_client,
-                        seen_prs,
-                        events_tx,
-                        sandbox_provider,
-                        agent_repo,
-                        session_bridge,
-                    )
-                    .await;
+                    github_pr_loop(scheduler, &flow_id, &flow_name, trigger_config, github_client).await;
                 });
 
                 tracing::info!(flow = %flow.name, "Started GitHub PR trigger");
                 self.handles.lock().await.insert(flow.id.clone(), handle);
             }
+            "github-push" => {
+                let github_client = self
+                    .github_client
+                    .clone()
+                    .context("GitHub push trigger requires GITHUB_TOKEN or GitHub App credentials (GITHUB_APP_ID)")?;
+
+                if self.github_webhooks {
+                    tracing::info!(flow = %flow.name, "GitHub push trigger receives webhook events, not polling");
+                    return Ok(());
+                }
+
+                let flow_id = flow.id.clone();
+                let flow_name = flow.name.clone();
+                let trigger_config = trigger_node.config.clone();
+                let scheduler = self.clone();
+                let handle = tokio::spawn(async move {
+                    github_push_loop(scheduler, &flow_id, &flow_name, trigger_config, github_client).await;
+                });
+
+                tracing::info!(flow = %flow.name, "Started GitHub push trigger");
+                self.handles.lock().await.insert(flow.id.clone(), handle);
+            }
+            "github-issue" => {
+                let github_client = self
+                    .github_client
+                    .clone()
+                    .context("GitHub issue trigger requires GITHUB_TOKEN or GitHub App credentials (GITHUB_APP_ID)")?;
+
+                if self.github_webhooks {
+                    tracing::info!(flow = %flow.name, "GitHub issue trigger receives webhook events, not polling");
+                    return Ok(());
+                }
+
+                let flow_id = flow.id.clone();
+                let flow_name = flow.name.clone();
+                let trigger_config = trigger_node.config.clone();
+                let scheduler = self.clone();
+                let handle = tokio::spawn(async move {
+                    github_issue_loop(scheduler, &flow_id, &flow_name, trigger_config, github_client).await;
+                });
+
+                tracing::info!(flow = %flow.name, "Started GitHub issue trigger");
+                self.handles.lock().await.insert(flow.id.clone(), handle);
+            }
+            "github-release" => {
+                let github_client = self
+                    .github_client
+                    .clone()
+                    .context("GitHub release trigger requires GITHUB_TOKEN or GitHub App credentials (GITHUB_APP_ID)")?;
+
+                if self.github_webhooks {
+                    tracing::info!(flow = %flow.name, "GitHub release trigger receives webhook events, not polling");
+                    return Ok(());
+                }
+
+                let flow_id = flow.id.clone();
+                let flow_name = flow.name.clone();
+                let trigger_config = trigger_node.config.clone();
+                let scheduler = self.clone();
+                let handle = tokio::spawn(async move {
+                    github_release_loop(scheduler, &flow_id, &flow_name, trigger_config, github_client).await;
+                });
+
+                tracing::info!(flow = %flow.name, "Started GitHub release trigger");
+                self.handles.lock().await.insert(flow.id.clone(), handle);
+            }
             "manual" | "webhook" => {
                 tracing::debug!(
                     flow = %flow.name,
@@ -195,6 +464,12 @@ impl FlowScheduler {
     }
 
     pub async fn stop_flow(&self, flow_id: &str) {
+        self.stop_trigger(flow_id).await;
+        self.forward_to_leader(flow_id).await;
+    }
+
+    async fn stop_trigger(&self, flow_id: &str) {
+        self.stagger.unregister(flow_id);
         let mut handles = self.handles.lock().await;
         if let Some(handle) = handles.remove(flow_id) {
             handle.abort();
@@ -202,8 +477,59 @@ impl FlowScheduler {
         }
     }
 
-    pub async fn restart_flow(&self, flow_id: &str) -> Result<()> {
-        self.stop_flow(flow_id).await;
+    /// Stop every running trigger.
+    pub async fn stop_all(&self) {
+        self.stagger.clear();
+        let mut handles = self.handles.lock().await;
+        for (_, handle) in handles.drain() {
+            handle.abort();
+        }
+    }
+
+    /// Hold triggers while another replica schedules, or let them start.
+    pub fn set_standby(&self, standby: bool) {
+        self.standby.store(standby, Ordering::Relaxed);
+    }
+
+    /// Clustered, pass trigger changes made on standby to the leader.
+    pub fn forward_trigger_changes(&self, leases: Arc<LeaseTable>) {
+        let _ = self.cluster.set(leases);
+    }
+
+    async fn forward_to_leader(&self, flow_id: &str) {
+        if !self.standby.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(leases) = self.cluster.get()
+            && let Err(e) = leases.publish_trigger_change(flow_id).await
+        {
+            tracing::error!(flow_id = %flow_id, error = %e, "Failed to pass trigger change to the scheduling replica");
+        }
+    }
+
+    /// Re-read every flow from disk, for edits made by other replicas.
+    pub async fn reload_flows(&self) {
+        if let Err(e) = self.flow_repo.load_all().await {
+            tracing::error!(error = %e, "Failed to reload flows");
+        }
+    }
+
+    /// Restart the triggers of `flow_ids`, edited (or deleted) through other
+    /// replicas, with the flows as they now are on disk.
+    pub async fn apply_trigger_changes(self: &Arc<Self>, flow_ids: &[String]) {
+        for flow_id in flow_ids {
+            self.stop_trigger(flow_id).await;
+            if self.flow_repo.reload_flow(flow_id).await
+                && let Err(e) = self.start_flow(flow_id).await
+            {
+                tracing::error!(flow_id = %flow_id, error = %e, "Failed to restart changed trigger");
+            }
+        }
+    }
+
+    pub async fn restart_flow(self: &Arc<Self>, flow_id: &str) -> Result<()> {
+        self.stop_trigger(flow_id).await;
+        self.forward_to_leader(flow_id).await;
         self.start_flow(flow_id).await
     }
 
@@ -230,7 +556,7 @@ impl FlowScheduler {
         let github_client = self
             .github_client
             .clone()
-            .context("GITHUB_TOKEN not configured")?;
+            .context("neither GITHUB_TOKEN nor GitHub App credentials (GITHUB_APP_ID) are configured")?;
 
         let trigger_node = flow
             .nodes
@@ -272,6 +598,7 @@ impl FlowScheduler {
             seen.entry(repo_slug.to_string())
                 .or_default()
                 .insert(pr_number, pr.head.sha.clone());
+            save_seen(&TriggerLog::new(&self.data_dir), &seen);
         }
 
         let diff_raw = github_client
@@ -284,46 +611,336 @@ impl FlowScheduler {
         context.insert("diff".to_string(), diff_ctx.text());
         context.insert("pr_number".to_string(), pr.number.to_string());
         context.insert("pr_title".to_string(), pr.title.clone());
-        context.insert("pr_body".to_string(), pr.body.unwrap_or_default());
+        context.insert("pr_body".to_string(), pr.body.clone().unwrap_or_default());
         context.insert("base_ref".to_string(), pr.base.ref_name.clone());
         context.insert("head_ref".to_string(), pr.head.ref_name.clone());
         context.insert("head_sha".to_string(), pr.head.sha.clone());
         context.insert("repo".to_string(), repo_slug.to_string());
         context.insert("local_path".to_string(), local_path.display().to_string());
         context.insert("review_type".to_string(), "initial".to_string());
-
-        let runner = FlowRunner {
-            http_client: self.http_client.clone(),
-            github_client: self.github_client.clone(),
-            events_tx: Some(self.events_tx.clone()),
-            sandbox_provider: Some(self.sandbox_provider.clone()),
-            agent_repo: Some(self.agent_repo.clone()),
-            session_bridge: Some(self.build_session_bridge()),
-        };
-
-        runner
+        add_repo_context(
+            &mut context,
+            &*github_client,
+            &trigger_node.config,
+            owner,
+            repo_name,
+            &pr,
+            &diff_raw,
+        )
+        .await;
+        add_risk_score(&mut context, &trigger_node.config, &diff_raw);
+        add_check_results(
+            &mut context,
+            Some(&*self.sandbox_provider),
+            &trigger_node.config,
+            &local_path,
+            &pr,
+        )
+        .await;
+
+        self.build_runner()
             .execute(&flow, &*self.flow_repo, Some(context))
             .await?;
 
         diff::cleanup(&diff_ctx);
         Ok(())
     }
+
+    /// Review a PR from a `pull_request` webhook delivery with every enabled
+    /// github-pr flow watching its repo. Deliveries share `seen_prs` with
+    /// manual triggers, so redelivered events don't review twice.
+    pub async fn handle_pull_request_event(&self, event: PullRequestEvent) {
+        let Some(github_client) = self.github_client.clone() else {
+            tracing::warn!("Ignoring pull_request webhook: neither GITHUB_TOKEN nor GitHub App credentials are configured");
+            return;
+        };
+        if !event.is_reviewable() {
+            return;
+        }
+        let pr = &event.pull_request;
+        let log = TriggerLog::new(&self.data_dir);
+
+        for (flow, trigger_config, repo) in self.github_flows("github-pr", &event.repository.full_name).await {
+            if trigger_config["auto_review"].as_bool() == Some(false) {
+                continue;
+            }
+            let skip_drafts = trigger_config["skip_drafts"].as_bool().unwrap_or(true);
+            let review_on_push = trigger_config["review_on_push"].as_bool().unwrap_or(false);
+            let review_on_ready = trigger_config["review_on_ready"].as_bool().unwrap_or(true);
+            if (pr.draft && skip_drafts) || (event.is_push() && !review_on_push) {
+                continue;
+            }
+            let became_ready = event.is_ready_for_review() && review_on_ready;
+
+            let (review_type, logged) = {
+                let mut seen = self.seen_prs.lock().await;
+                let seen_map = seen.entry(repo.full_name()).or_default();
+                let Some(review_type) = next_review(seen_map, pr, review_on_push, became_ready) else {
+                    continue;
+                };
+                match claim_review(&log, &flow.id, &repo, pr, &review_type, &seen) {
+                    Some(logged) => (review_type, logged),
+                    None => continue,
+                }
+            };
+
+            let runner = self.build_runner();
+            let flow_repo = self.flow_repo.clone();
+            let github_client = github_client.clone();
+            let (job_flow, pr) = (flow.clone(), pr.clone());
+            self.run_in_window(&flow, async move {
+                review_pr(runner, &job_flow, &*flow_repo, &*github_client, &repo, pr, review_type, logged).await;
+            })
+            .await;
+        }
+    }
+
+    /// Run a maintainer's `/cthulu <command>` PR comment with every enabled
+    /// github-pr flow watching the repo that lists the command in its
+    /// `commands`. Works on PRs already marked as seen, and outside the
+    /// trigger's `active_window` (commands are explicit requests).
+    pub async fn handle_issue_comment_event(&self, event: IssueCommentEvent) {
+        let Some(github_client) = self.github_client.clone() else {
+            tracing::warn!("Ignoring issue_comment webhook: neither GITHUB_TOKEN nor GitHub App credentials are configured");
+            return;
+        };
+        let Some(command) = event.command() else {
+            return;
+        };
+
+        let log = TriggerLog::new(&self.data_dir);
+
+        // Fetched once, by the first flow the command is for
+        let mut fetched: Option<Option<PullRequest>> = None;
+        for (flow, trigger_config, repo) in self.github_flows("github-pr", &event.repository.full_name).await {
+            if !handles_command(&trigger_config, &command.name) {
+                continue;
+            }
+            if fetched.is_none() {
+                fetched = Some(fetch_command_pr(&*github_client, &self.seen_prs, &log, &repo, event.issue.number).await);
+            }
+            let Some(Some(pr)) = fetched.clone() else {
+                continue;
+            };
+            let review_type = ReviewType::Command {
+                command: command.clone(),
+                requested_by: event.comment.author().to_string(),
+                comment_id: event.comment.id,
+            };
+            let Some(logged) = claim_review(&log, &flow.id, &repo, &pr, &review_type, &*self.seen_prs.lock().await)
+            else {
+                continue;
+            };
+            review_pr(
+                self.build_runner(),
+                &flow,
+                &*self.flow_repo,
+                &*github_client,
+                &repo,
+                pr,
+                review_type,
+                logged,
+            )
+            .await;
+        }
+    }
+
+    /// Run every enabled github-push flow watching the pushed branch on the
+    /// commits in a `push` webhook delivery.
+    pub async fn handle_push_event(&self, event: PushEvent) {
+        let Some(github_client) = self.github_client.clone() else {
+            tracing::warn!("Ignoring push webhook: neither GITHUB_TOKEN nor GitHub App credentials are configured");
+            return;
+        };
+        let Some(branch) = event.branch() else {
+            return;
+        };
+        if !event.has_range() {
+            tracing::debug!(branch, "Ignoring push that creates or deletes a branch");
+            return;
+        }
+        let push = PushRange {
+            branch: branch.to_string(),
+            before: event.before.clone(),
+            after: event.after.clone(),
+        };
+
+        for (flow, trigger_config, repo) in self.github_flows("github-push", &event.repository.full_name).await {
+            if !watches_branch(&trigger_config, branch) {
+                continue;
+            }
+            let runner = self.build_runner();
+            let flow_repo = self.flow_repo.clone();
+            let github_client = github_client.clone();
+            let (job_flow, push) = (flow.clone(), push.clone());
+            self.run_in_window(&flow, async move {
+                review_push(runner, &job_flow, &*flow_repo, &*github_client, &repo, &push).await;
+            })
+            .await;
+        }
+    }
+
+    /// Run every enabled github-issue flow watching the repo on an issue from
+    /// an `issues` "opened" webhook delivery.
+    pub async fn handle_issues_event(&self, event: IssuesEvent) {
+        for (flow, trigger_config, repo) in self.github_flows("github-issue", &event.repository.full_name).await {
+            if !issue_matches(&trigger_config, &event.issue) {
+                continue;
+            }
+            let context = issue_context(&repo, &event.issue);
+            let (batch_runner, runner) = (self.build_runner(), self.build_runner());
+            let coalescer = self.coalescer.clone();
+            let flow_repo = self.flow_repo.clone();
+            let (job_flow, issue) = (flow.clone(), event.issue.clone());
+            self.run_in_window(&flow, async move {
+                let Some(context) =
+                    debounce_event(&coalescer, &job_flow, flow_repo.clone(), move || batch_runner, context).await
+                else {
+                    return;
+                };
+                triage_issue(runner, &job_flow, &*flow_repo, &issue, context).await;
+            })
+            .await;
+        }
+    }
+
+    /// Run every enabled github-release flow watching the repo on a release
+    /// from a `release` "published" webhook delivery.
+    pub async fn handle_release_event(&self, event: ReleaseEvent) {
+        for (flow, trigger_config, repo) in self.github_flows("github-release", &event.repository.full_name).await {
+            if !release_matches(&trigger_config, &event.release) {
+                continue;
+            }
+            let runner = self.build_runner();
+            let flow_repo = self.flow_repo.clone();
+            let (job_flow, release) = (flow.clone(), event.release.clone());
+            self.run_in_window(&flow, async move {
+                announce_release(runner, &job_flow, &*flow_repo, &repo, &release).await;
+            })
+            .await;
+        }
+    }
+
+    /// Run `job` for an event on `flow` now if its trigger's `active_window`
+    /// is open (or it has none); otherwise drop it or hold it until the
+    /// window opens, per the window's `outside` setting.
+    pub async fn run_in_window<F>(&self, flow: &Flow, job: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        match window::gate(flow, Utc::now()) {
+            Gate::Open => job.await,
+            Gate::Drop => tracing::info!(flow = %flow.name, "Event outside active window, dropped"),
+            Gate::Queue(open_at) => self.defer_until_open(flow, open_at, job).await,
+        }
+    }
+
+    /// Hold `job` until `open_at`, when `flow`'s active window next opens.
+    pub async fn defer_until_open<F>(&self, flow: &Flow, open_at: DateTime<Utc>, job: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        tracing::info!(flow = %flow.name, open_at = %open_at, "Event outside active window, queued");
+        self.window_queue.defer(&flow.id, open_at, job).await;
+    }
+
+    /// Add an event to `flow`'s pending batch when its trigger sets
+    /// `debounce_secs`, returning `None`. Otherwise hands `context` back for
+    /// the caller to run now.
+    pub async fn debounce_event(
+        &self,
+        flow: &Flow,
+        context: HashMap<String, String>,
+    ) -> Option<HashMap<String, String>> {
+        debounce_event(&self.coalescer, flow, self.flow_repo.clone(), || self.build_runner(), context).await
+    }
+
+    /// Enabled flows with a `kind` trigger (github-pr, github-push, github-issue,
+    /// github-release) watching
+    /// `repo_slug`, with their trigger config and the matching repo entry.
+    async fn github_flows(&self, kind: &str, repo_slug: &str) -> Vec<(Flow, serde_json::Value, RepoConfig)> {
+        let mut matches = Vec::new();
+        for flow in self.flow_repo.list_flows().await {
+            if !flow.enabled {
+                continue;
+            }
+            let Some(trigger_config) = flow
+                .nodes
+                .iter()
+                .find(|n| n.node_type == NodeType::Trigger && n.kind == kind)
+                .map(|n| n.config.clone())
+            else {
+                continue;
+            };
+            let Some(repo) = parse_repo_configs(&trigger_config)
+                .into_iter()
+                .find(|r| r.full_name().eq_ignore_ascii_case(repo_slug))
+            else {
+                continue;
+            };
+            matches.push((flow, trigger_config, repo));
+        }
+        matches
+    }
 }
 
 // ── Cron loop ────────────────────────────────────────────────────
 
+/// The IANA timezone a cron trigger's `schedule` is evaluated in (e.g.
+/// `Europe/London`), from its `timezone` config. Defaults to UTC.
+pub(crate) fn cron_timezone(config: &serde_json::Value) -> Result<Tz> {
+    match config["timezone"].as_str().map(str::trim) {
+        None | Some("") => Ok(Tz::UTC),
+        Some(name) => name
+            .parse::<Tz>()
+            .map_err(|_| anyhow::anyhow!("unknown timezone '{name}' (expected an IANA name like 'Europe/London')")),
+    }
+}
+
+/// Names of a cron expression's fields, by how many it has.
+const CRON_FIELDS_5: [&str; 5] = ["minute", "hour", "day_of_month", "month", "day_of_week"];
+const CRON_FIELDS_6: [&str; 6] = ["second", "minute", "hour", "day_of_month", "month", "day_of_week"];
+
+/// `schedule` split into its named fields, e.g. `{"minute": "0", "hour":
+/// "9", ...}`; `None` for a nickname like `@daily`.
+pub(crate) fn cron_fields(schedule: &str) -> Option<serde_json::Map<String, serde_json::Value>> {
+    let parts: Vec<&str> = schedule.split_whitespace().collect();
+    let names: &[&str] = match parts.len() {
+        5 => &CRON_FIELDS_5,
+        6 => &CRON_FIELDS_6,
+        _ => return None,
+    };
+    Some(names.iter().zip(parts).map(|(name, part)| (name.to_string(), part.into())).collect())
+}
+
+/// The next `count` fire times of `cron` strictly after `after`, as wall-clock
+/// times in `tz`. Croner resolves DST gaps and overlaps, so "0 9 * * 1-5" stays
+/// at 09:00 local time across clock changes.
+pub(crate) fn next_cron_runs(cron: &Cron, tz: Tz, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Tz>> {
+    let mut runs = Vec::with_capacity(count);
+    let mut cursor = after.with_timezone(&tz);
+    while runs.len() < count {
+        match cron.find_next_occurrence(&cursor, false) {
+            Ok(next) => {
+                cursor = next + chrono::Duration::seconds(1);
+                runs.push(next);
+            }
+            Err(_) => break,
+        }
+    }
+    runs
+}
+
 async fn cron_loop(
+    scheduler: Arc<FlowScheduler>,
     flow_id: &str,
     flow_name: &str,
     schedule: &str,
-    flow_repo: Arc<dyn FlowRepository>,
-    http_client: Arc<reqwest::Client>,
-    github_client: Option<Arc<dyn GithubClient>>,
-    events_tx: broadcast::Sender<RunEvent>,
-    sandbox_provider: Arc<dyn SandboxProvider>,
-    agent_repo: Arc<dyn AgentRepository>,
-    session_bridge: SessionBridge,
+    timezone: Tz,
+    jitter_secs: u64,
 ) {
+    let flow_repo = scheduler.flow_repo.clone();
+    let stagger = scheduler.stagger.clone();
     let cron = match Cron::new(schedule).parse() {
         Ok(c) => c,
         Err(e) => {
@@ -332,11 +949,11 @@ async fn cron_loop(
         }
     };
 
-    tracing::info!(flow = %flow_name, schedule = %schedule, "Cron loop started");
+    tracing::info!(flow = %flow_name, schedule = %schedule, timezone = %timezone, "Cron loop started");
 
     loop {
         let now = Utc::now();
-        let next = match cron.find_next_occurrence(&now, false) {
+        let local_next = match cron.find_next_occurrence(&now.with_timezone(&timezone), false) {
             Ok(next) => next,
             Err(e) => {
                 tracing::error!(flow = %flow_name, error = %e, "Failed to compute next cron occurrence");
@@ -345,10 +962,18 @@ async fn cron_loop(
             }
         };
 
+        // Flows sharing the schedule take turns, spread over part of the interval
+        let interval = next_cron_runs(&cron, timezone, local_next.with_timezone(&Utc), 1)
+            .first()
+            .and_then(|after| (*after - local_next).to_std().ok())
+            .unwrap_or_default();
+        let delay = stagger.offset(flow_id, interval) + stagger::jitter(jitter_secs, interval);
+        let next = local_next.with_timezone(&Utc) + chrono::Duration::from_std(delay).unwrap_or_default();
         let duration = (next - now).to_std().unwrap_or(std::time::Duration::from_secs(1));
         tracing::info!(
             flow = %flow_name,
-            next = %next.format("%Y-%m-%d %H:%M:%S UTC"),
+            next = %local_next.format("%Y-%m-%d %H:%M:%S %Z"),
+            delay = format_args!("{:.0}s", delay.as_secs_f64()),
             "Sleeping until next cron fire"
         );
         tokio::time::sleep(duration).await;
@@ -359,6 +984,10 @@ async fn cron_loop(
             let remaining = (next - now_after).to_std().unwrap_or_default();
             tokio::time::sleep(remaining).await;
         }
+        // A fire during maintenance mode runs once it ends
+        if maintenance::resumed().await {
+            tracing::info!(flow = %flow_name, "Cron fire held through maintenance mode");
+        }
 
         // Re-fetch the flow in case it was updated
         let flow = match flow_repo.get_flow(flow_id).await {
@@ -373,14 +1002,25 @@ async fn cron_loop(
             }
         };
 
-        let runner = FlowRunner {
-            http_client: http_client.clone(),
-            github_client: github_client.clone(),
-            events_tx: Some(events_tx.clone()),
-            sandbox_provider: Some(sandbox_provider.clone()),
-            agent_repo: Some(agent_repo.clone()),
-            session_bridge: Some(session_bridge.clone()),
-        };
+        if PauseStore::new(&scheduler.data_dir).paused_since(flow_id).is_some() {
+            tracing::info!(flow = %flow_name, "Cron fire skipped, schedule paused");
+            continue;
+        }
+
+        // Fires while a queueing window is closed collapse into one run when it opens
+        match window::gate(&flow, Utc::now()) {
+            Gate::Open => {}
+            Gate::Drop => {
+                tracing::info!(flow = %flow_name, "Cron fire outside active window, skipped");
+                continue;
+            }
+            Gate::Queue(open_at) => {
+                tracing::info!(flow = %flow_name, open_at = %open_at, "Cron fire outside active window, queued");
+                tokio::time::sleep((open_at - Utc::now()).to_std().unwrap_or_default()).await;
+            }
+        }
+
+        let runner = scheduler.build_runner();
 
         if let Err(e) = runner.execute(&flow, &*flow_repo, None).await {
             tracing::error!(flow = %flow_name, error = %e, "Cron flow execution failed");
@@ -391,22 +1031,19 @@ async fn cron_loop(
 // ── GitHub PR loop ───────────────────────────────────────────────
 
 async fn github_pr_loop(
+    scheduler: Arc<FlowScheduler>,
     flow_id: &str,
     flow_name: &str,
     trigger_config: serde_json::Value,
-    flow_repo: Arc<dyn FlowRepository>,
-    http_client: Arc<reqwest::Client>,
     github_client: Arc<dyn GithubClient>,
-    seen_prs: Arc<Mutex<HashMap<String, HashMap<u64, String>>>>,
-    events_tx: broadcast::Sender<RunEvent>,
-    sandbox_provider: Arc<dyn SandboxProvider>,
-    agent_repo: Arc<dyn AgentRepository>,
-    session_bridge: SessionBridge,
 ) {
+    let flow_repo = scheduler.flow_repo.clone();
+    let seen_prs = scheduler.seen_prs.clone();
     let poll_interval = trigger_config["poll_interval"].as_u64().unwrap_or(60);
     let skip_drafts = trigger_config["skip_drafts"].as_bool().unwrap_or(true);
     let review_on_push = trigger_config["review_on_push"].as_bool().unwrap_or(false);
-    let max_diff_size = trigger_config["max_diff_size"].as_u64().unwrap_or(50_000) as usize;
+    let review_on_ready = trigger_config["review_on_ready"].as_bool().unwrap_or(true);
+    let auto_review = trigger_config["auto_review"].as_bool().unwrap_or(true);
 
     let repos = parse_repo_configs(&trigger_config);
     if repos.is_empty() {
@@ -414,6 +1051,10 @@ async fn github_pr_loop(
         return;
     }
 
+    // Open draft PRs per repo, so a poll can tell when one is marked ready
+    let mut drafts: HashMap<String, HashSet<u64>> = HashMap::new();
+    let trigger_log = TriggerLog::new(&scheduler.data_dir);
+
     // Seed: fetch open PRs and populate seen_prs
     for repo in &repos {
         let max_retries = 10;
@@ -425,7 +1066,23 @@ async fn github_pr_loop(
                 .await
             {
                 Ok(prs) => {
+                    drafts.insert(
+                        repo.full_name(),
+                        prs.iter().filter(|pr| pr.draft).map(|pr| pr.number).collect(),
+                    );
                     let mut seen = seen_prs.lock().await;
+                    // Seen before a restart: PRs opened or pushed to since
+                    // are picked up by the first poll
+                    if let Some(known) = seen.get(&repo.full_name()) {
+                        tracing::info!(
+                            repo = %repo.full_name(),
+                            count = known.len(),
+                            "Resuming with {} known PRs for {}",
+                            known.len(),
+                            repo.full_name()
+                        );
+                        break;
+                    }
                     let pr_shas: HashMap<u64, String> = prs
                         .iter()
                         .filter(|pr| {
@@ -450,6 +1107,7 @@ async fn github_pr_loop(
                         repo.full_name()
                     );
                     seen.insert(repo.full_name(), pr_shas);
+                    save_seen(&trigger_log, &seen);
                     break;
                 }
                 Err(e) => {
@@ -476,199 +1134,1069 @@ async fn github_pr_loop(
         }
     }
 
-    // Filter to only repos that were successfully seeded
-    let seeded_repos: Vec<RepoConfig> = {
-        let seen = seen_prs.lock().await;
-        repos
-            .into_iter()
-            .filter(|r| seen.contains_key(&r.full_name()))
-            .collect()
-    };
+    // Filter to only repos that were successfully seeded
+    let seeded_repos: Vec<RepoConfig> = {
+        let seen = seen_prs.lock().await;
+        repos
+            .into_iter()
+            .filter(|r| seen.contains_key(&r.full_name()))
+            .collect()
+    };
+
+    tracing::info!(
+        flow = %flow_name,
+        repos = seeded_repos.len(),
+        interval = poll_interval,
+        skip_drafts,
+        review_on_push,
+        "Polling {} repos every {}s",
+        seeded_repos.len(),
+        poll_interval
+    );
+
+    let make_runner = || scheduler.build_runner();
+
+    // Comment commands posted before the loop started are ignored; edits
+    // bump a comment's `updated_at`, so remember which ones were handled.
+    let started_at = Utc::now();
+    let mut comments_since = started_at;
+    let mut handled_comments: HashSet<u64> = HashSet::new();
+
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(poll_interval));
+
+    loop {
+        interval.tick().await;
+        // Polls stop for maintenance mode, without a burst of missed ticks after
+        if maintenance::resumed().await {
+            interval.reset();
+        }
+        let tick_started = Utc::now();
+
+        // Check if flow still exists and is enabled
+        let flow = match flow_repo.get_flow(flow_id).await {
+            Some(f) if f.enabled => f,
+            Some(_) => {
+                tracing::info!(flow = %flow_name, "Flow disabled, stopping PR poll loop");
+                return;
+            }
+            None => {
+                tracing::info!(flow = %flow_name, "Flow deleted, stopping PR poll loop");
+                return;
+            }
+        };
+        let gate = window::gate(&flow, Utc::now());
+
+        for repo in &seeded_repos {
+            match github_client
+                .fetch_issue_comments(&repo.owner, &repo.repo, comments_since)
+                .await
+            {
+                Ok(comments) => {
+                    for comment in comments {
+                        if comment.created_at.is_some_and(|t| t < started_at)
+                            || !handled_comments.insert(comment.id)
+                            || !commands::is_maintainer(&comment.author_association)
+                        {
+                            continue;
+                        }
+                        let Some(command) = commands::parse(&comment.body) else {
+                            continue;
+                        };
+                        if !handles_command(&trigger_config, &command.name) {
+                            continue;
+                        }
+                        let Some(pr_number) = comment.issue_number() else {
+                            continue;
+                        };
+                        let Some(pr) =
+                            fetch_command_pr(&*github_client, &seen_prs, &trigger_log, repo, pr_number).await
+                        else {
+                            continue;
+                        };
+                        let review_type = ReviewType::Command {
+                            command,
+                            requested_by: comment.author().to_string(),
+                            comment_id: comment.id,
+                        };
+                        let Some(logged) =
+                            claim_review(&trigger_log, flow_id, repo, &pr, &review_type, &*seen_prs.lock().await)
+                        else {
+                            continue;
+                        };
+                        review_pr(make_runner(), &flow, &*flow_repo, &*github_client, repo, pr, review_type, logged)
+                            .await;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(repo = %repo.full_name(), error = %e, "Failed to fetch PR comments");
+                }
+            }
+
+            // Comment commands run any time; automatic reviews respect the
+            // active window, and while a queueing one is closed new PRs are
+            // left unseen until it opens.
+            if !auto_review || matches!(gate, Gate::Queue(_)) {
+                continue;
+            }
+
+            let prs = match github_client
+                .fetch_open_prs(&repo.owner, &repo.repo)
+                .await
+            {
+                Ok(prs) => prs,
+                Err(e) => {
+                    tracing::error!(repo = %repo.full_name(), error = %e, "Failed to fetch PRs");
+                    continue;
+                }
+            };
+
+            let repo_drafts = drafts.entry(repo.full_name()).or_default();
+            let was_draft = std::mem::take(repo_drafts);
+            repo_drafts.extend(prs.iter().filter(|pr| pr.draft).map(|pr| pr.number));
+
+            for pr in prs {
+                if pr.draft && skip_drafts {
+                    continue;
+                }
+                let became_ready = review_on_ready && !pr.draft && was_draft.contains(&pr.number);
+
+                let mut seen = seen_prs.lock().await;
+                let seen_map = seen.entry(repo.full_name()).or_default();
+                let Some(review_type) = next_review(seen_map, &pr, review_on_push, became_ready) else {
+                    continue;
+                };
+                if gate == Gate::Drop {
+                    save_seen(&trigger_log, &seen);
+                    tracing::info!(flow = %flow_name, pr = pr.number, "PR outside active window, skipped");
+                    continue;
+                }
+                let Some(logged) = claim_review(&trigger_log, flow_id, repo, &pr, &review_type, &seen) else {
+                    continue;
+                };
+                drop(seen);
+
+                review_pr(make_runner(), &flow, &*flow_repo, &*github_client, repo, pr, review_type, logged).await;
+            }
+        }
+        comments_since = tick_started;
+    }
+}
+
+// ── GitHub push loop ─────────────────────────────────────────────
+
+/// Poll the heads of the watched branches and run the flow on the commits
+/// between a branch's previous and new head. Heads are seeded on the first
+/// poll (and when a branch first appears), so earlier history is never
+/// reviewed.
+async fn github_push_loop(
+    scheduler: Arc<FlowScheduler>,
+    flow_id: &str,
+    flow_name: &str,
+    trigger_config: serde_json::Value,
+    github_client: Arc<dyn GithubClient>,
+) {
+    let flow_repo = scheduler.flow_repo.clone();
+    let make_runner = || scheduler.build_runner();
+    let poll_interval = trigger_config["poll_interval"].as_u64().unwrap_or(60);
+    let repos = parse_repo_configs(&trigger_config);
+    if repos.is_empty() {
+        tracing::error!(flow = %flow_name, "No valid repos configured for GitHub push trigger");
+        return;
+    }
+    tracing::info!(
+        flow = %flow_name,
+        repos = repos.len(),
+        branches = ?watched_branches(&trigger_config),
+        interval = poll_interval,
+        "Polling branch heads every {}s",
+        poll_interval
+    );
+
+    // (repo, branch) -> last seen head
+    let mut heads: HashMap<(String, String), String> = HashMap::new();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(poll_interval));
+
+    loop {
+        interval.tick().await;
+        if maintenance::resumed().await {
+            interval.reset();
+        }
+
+        let flow = match flow_repo.get_flow(flow_id).await {
+            Some(f) if f.enabled => f,
+            Some(_) => {
+                tracing::info!(flow = %flow_name, "Flow disabled, stopping push poll loop");
+                return;
+            }
+            None => {
+                tracing::info!(flow = %flow_name, "Flow deleted, stopping push poll loop");
+                return;
+            }
+        };
+        // While a queueing window is closed, heads aren't advanced, so the
+        // first poll after it opens covers every commit pushed meanwhile.
+        let gate = window::gate(&flow, Utc::now());
+        if matches!(gate, Gate::Queue(_)) {
+            continue;
+        }
+
+        for repo in &repos {
+            let repo_branches = match github_client.fetch_branches(&repo.owner, &repo.repo).await {
+                Ok(b) => b,
+                Err(e) => {
+                    tracing::warn!(repo = %repo.full_name(), error = %e, "Failed to fetch branch heads");
+                    continue;
+                }
+            };
+            for branch in repo_branches.into_iter().filter(|b| watches_branch(&trigger_config, &b.name)) {
+                let head = branch.commit.sha;
+                let key = (repo.full_name(), branch.name.clone());
+                let Some(before) = heads.insert(key, head.clone()) else {
+                    continue;
+                };
+                if before == head {
+                    continue;
+                }
+                if gate == Gate::Drop {
+                    tracing::info!(flow = %flow_name, branch = %branch.name, "Push outside active window, skipped");
+                    continue;
+                }
+                let push = PushRange { branch: branch.name, before, after: head };
+                review_push(make_runner(), &flow, &*flow_repo, &*github_client, repo, &push).await;
+            }
+        }
+    }
+}
+
+/// New commits on a branch: `before` (exclusive) to `after` (inclusive).
+#[derive(Clone)]
+struct PushRange {
+    branch: String,
+    before: String,
+    after: String,
+}
+
+/// Run `flow` on a push with `{{commits}}`, `{{commit_shas}}`,
+/// `{{commit_authors}}` and the combined `{{diff}}` as context.
+async fn review_push(
+    runner: FlowRunner,
+    flow: &Flow,
+    flow_repo: &dyn FlowRepository,
+    github_client: &dyn GithubClient,
+    repo: &RepoConfig,
+    push: &PushRange,
+) {
+    let Some(trigger_config) = flow
+        .nodes
+        .iter()
+        .find(|n| n.node_type == NodeType::Trigger)
+        .map(|n| &n.config)
+    else {
+        return;
+    };
+    let max_diff_size = trigger_config["max_diff_size"].as_u64().unwrap_or(50_000) as usize;
+
+    let commits = match github_client
+        .compare_commits(&repo.owner, &repo.repo, &push.before, &push.after)
+        .await
+    {
+        Ok(commits) if commits.is_empty() => return,
+        Ok(commits) => commits,
+        Err(e) => {
+            // e.g. a force push whose old head is gone
+            tracing::error!(repo = %repo.full_name(), branch = %push.branch, error = %e, "Failed to compare push");
+            return;
+        }
+    };
+    tracing::info!(
+        flow = %flow.name,
+        repo = %repo.full_name(),
+        branch = %push.branch,
+        commits = commits.len(),
+        "Push to {} detected: {} new commits",
+        push.branch,
+        commits.len()
+    );
+
+    let diff_raw = match github_client
+        .fetch_compare_diff(&repo.owner, &repo.repo, &push.before, &push.after)
+        .await
+    {
+        Ok(d) => d,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to fetch push diff");
+            return;
+        }
+    };
+    let diff_ctx = match diff::prepare_diff_context(&diff_raw, 0, max_diff_size) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to prepare diff context");
+            return;
+        }
+    };
+
+    let mut context = push_context(repo, push, &commits);
+    context.insert("diff".to_string(), diff_ctx.text());
+    add_risk_score(&mut context, trigger_config, &diff_raw);
+
+    match runner.execute(flow, flow_repo, Some(context)).await {
+        Ok(run) => tracing::info!(flow = %flow.name, branch = %push.branch, run_id = %run.id, "Push review completed"),
+        Err(e) => tracing::error!(flow = %flow.name, branch = %push.branch, error = %e, "Push review failed"),
+    }
+
+    diff::cleanup(&diff_ctx);
+}
+
+fn push_context(repo: &RepoConfig, push: &PushRange, commits: &[Commit]) -> HashMap<String, String> {
+    let mut authors: Vec<&str> = Vec::new();
+    for commit in commits {
+        let author = commit.author_name();
+        if !authors.contains(&author) {
+            authors.push(author);
+        }
+    }
+    let summary: Vec<String> = commits
+        .iter()
+        .map(|c| format!("- {} {} ({})", &c.sha[..7.min(c.sha.len())], c.subject(), c.author_name()))
+        .collect();
+
+    let mut context = HashMap::new();
+    context.insert("repo".to_string(), repo.full_name());
+    context.insert("local_path".to_string(), repo.local_path.display().to_string());
+    context.insert("branch".to_string(), push.branch.clone());
+    context.insert("before_sha".to_string(), push.before.clone());
+    context.insert("after_sha".to_string(), push.after.clone());
+    context.insert("commit_count".to_string(), commits.len().to_string());
+    context.insert(
+        "commit_shas".to_string(),
+        commits.iter().map(|c| c.sha.as_str()).collect::<Vec<_>>().join("\n"),
+    );
+    context.insert("commit_authors".to_string(), authors.join(", "));
+    context.insert("commits".to_string(), summary.join("\n"));
+    context
+}
+
+/// The trigger's `branches` (globs allowed), `["main"]` by default.
+fn watched_branches(trigger_config: &serde_json::Value) -> Vec<String> {
+    trigger_config["branches"]
+        .as_array()
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_else(|| vec!["main".to_string()])
+}
+
+fn watches_branch(trigger_config: &serde_json::Value, branch: &str) -> bool {
+    watched_branches(trigger_config).iter().any(|pattern| {
+        glob::Pattern::new(pattern).is_ok_and(|p| p.matches(branch))
+    })
+}
+
+// ── Debounce ─────────────────────────────────────────────────────
+
+/// Queue `context` into `flow`'s debounce window when its trigger sets
+/// `debounce_secs`; the batch later runs once via a runner from
+/// `make_runner`. Returns `context` untouched when the flow runs per event.
+async fn debounce_event(
+    coalescer: &Coalescer,
+    flow: &Flow,
+    flow_repo: Arc<dyn FlowRepository>,
+    make_runner: impl FnOnce() -> FlowRunner,
+    context: HashMap<String, String>,
+) -> Option<HashMap<String, String>> {
+    let Some(config) = DebounceConfig::for_flow(flow) else {
+        return Some(context);
+    };
+    let runner = make_runner();
+    let batch_flow = flow.clone();
+    let batched = coalescer
+        .submit(&flow.id, context, config, move |context| async move {
+            match runner.execute(&batch_flow, &*flow_repo, Some(context)).await {
+                Ok(run) => tracing::info!(flow = %batch_flow.name, run_id = %run.id, "Batched run completed"),
+                Err(e) => tracing::error!(flow = %batch_flow.name, error = %e, "Batched run failed"),
+            }
+        })
+        .await;
+    tracing::info!(flow = %flow.name, batched, window_secs = config.window.as_secs(), "Event added to debounce window");
+    None
+}
+
+// ── GitHub issue loop ────────────────────────────────────────────
+
+/// Poll the most recently opened issues and run the flow once per new one.
+/// Issues already open on the first poll are seeded as seen, so existing
+/// backlog is never triaged.
+async fn github_issue_loop(
+    scheduler: Arc<FlowScheduler>,
+    flow_id: &str,
+    flow_name: &str,
+    trigger_config: serde_json::Value,
+    github_client: Arc<dyn GithubClient>,
+) {
+    let flow_repo = scheduler.flow_repo.clone();
+    let coalescer = scheduler.coalescer.clone();
+    let make_runner = || scheduler.build_runner();
+    let poll_interval = trigger_config["poll_interval"].as_u64().unwrap_or(60);
+    let repos = parse_repo_configs(&trigger_config);
+    if repos.is_empty() {
+        tracing::error!(flow = %flow_name, "No valid repos configured for GitHub issue trigger");
+        return;
+    }
+    tracing::info!(
+        flow = %flow_name,
+        repos = repos.len(),
+        interval = poll_interval,
+        "Polling new issues every {}s",
+        poll_interval
+    );
+
+    // repo -> issue numbers already seen; absent until the repo's first poll
+    let mut seen: HashMap<String, HashSet<u64>> = HashMap::new();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(poll_interval));
+
+    loop {
+        interval.tick().await;
+        if maintenance::resumed().await {
+            interval.reset();
+        }
+
+        let flow = match flow_repo.get_flow(flow_id).await {
+            Some(f) if f.enabled => f,
+            Some(_) => {
+                tracing::info!(flow = %flow_name, "Flow disabled, stopping issue poll loop");
+                return;
+            }
+            None => {
+                tracing::info!(flow = %flow_name, "Flow deleted, stopping issue poll loop");
+                return;
+            }
+        };
+        // New issues stay unseen while a queueing window is closed
+        let gate = window::gate(&flow, Utc::now());
+        if matches!(gate, Gate::Queue(_)) {
+            continue;
+        }
+
+        for repo in &repos {
+            let issues = match github_client.fetch_recent_issues(&repo.owner, &repo.repo).await {
+                Ok(issues) => issues,
+                Err(e) => {
+                    tracing::warn!(repo = %repo.full_name(), error = %e, "Failed to fetch recent issues");
+                    continue;
+                }
+            };
+            let issues: Vec<Issue> = issues.into_iter().filter(|i| i.pull_request.is_none()).collect();
+            let first_poll = !seen.contains_key(&repo.full_name());
+            let repo_seen = seen.entry(repo.full_name()).or_default();
+            // Oldest first, so several new issues are triaged in the order they were opened
+            for issue in issues.iter().rev() {
+                if !repo_seen.insert(issue.number) || first_poll {
+                    continue;
+                }
+                if !issue_matches(&trigger_config, issue) {
+                    continue;
+                }
+                if gate == Gate::Drop {
+                    tracing::info!(flow = %flow_name, issue = issue.number, "Issue outside active window, skipped");
+                    continue;
+                }
+                let context = issue_context(repo, issue);
+                let Some(context) =
+                    debounce_event(&coalescer, &flow, flow_repo.clone(), &make_runner, context).await
+                else {
+                    continue;
+                };
+                triage_issue(make_runner(), &flow, &*flow_repo, issue, context).await;
+            }
+        }
+    }
+}
+
+/// Whether a new issue passes the trigger's label filters: with
+/// `unlabeled_only` it must have no labels, and it must carry none of
+/// `without_labels` (case-insensitive), e.g. `["triaged"]`.
+fn issue_matches(trigger_config: &serde_json::Value, issue: &Issue) -> bool {
+    if trigger_config["unlabeled_only"].as_bool().unwrap_or(false) && !issue.labels.is_empty() {
+        return false;
+    }
+    let excluded: Vec<&str> = trigger_config["without_labels"]
+        .as_array()
+        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    !issue
+        .labels
+        .iter()
+        .any(|label| excluded.iter().any(|x| x.eq_ignore_ascii_case(&label.name)))
+}
+
+/// Run `flow` on a newly opened issue with its [`issue_context`].
+async fn triage_issue(
+    runner: FlowRunner,
+    flow: &Flow,
+    flow_repo: &dyn FlowRepository,
+    issue: &Issue,
+    context: HashMap<String, String>,
+) {
+    tracing::info!(
+        flow = %flow.name,
+        repo = %context["repo"],
+        issue = issue.number,
+        "New issue #{} detected: {}",
+        issue.number,
+        issue.title
+    );
+    match runner.execute(flow, flow_repo, Some(context)).await {
+        Ok(run) => tracing::info!(flow = %flow.name, issue = issue.number, run_id = %run.id, "Issue triage completed"),
+        Err(e) => tracing::error!(flow = %flow.name, issue = issue.number, error = %e, "Issue triage failed"),
+    }
+}
+
+fn issue_context(repo: &RepoConfig, issue: &Issue) -> HashMap<String, String> {
+    let mut context = HashMap::new();
+    context.insert("repo".to_string(), repo.full_name());
+    context.insert("local_path".to_string(), repo.local_path.display().to_string());
+    context.insert("issue_number".to_string(), issue.number.to_string());
+    context.insert("issue_title".to_string(), issue.title.clone());
+    context.insert("issue_body".to_string(), issue.body.clone().unwrap_or_default());
+    context.insert(
+        "issue_author".to_string(),
+        issue.user.as_ref().map(|u| u.login.clone()).unwrap_or_default(),
+    );
+    context.insert("issue_url".to_string(), issue.html_url.clone());
+    context.insert(
+        "issue_labels".to_string(),
+        issue.labels.iter().map(|l| l.name.as_str()).collect::<Vec<_>>().join(", "),
+    );
+    context
+}
 
+// ── GitHub release loop ──────────────────────────────────────────
+
+/// Poll recent releases and run the flow once per newly published one.
+/// Releases already published on the first poll are seeded as seen; drafts
+/// are skipped until they are published.
+async fn github_release_loop(
+    scheduler: Arc<FlowScheduler>,
+    flow_id: &str,
+    flow_name: &str,
+    trigger_config: serde_json::Value,
+    github_client: Arc<dyn GithubClient>,
+) {
+    let flow_repo = scheduler.flow_repo.clone();
+    let make_runner = || scheduler.build_runner();
+    let poll_interval = trigger_config["poll_interval"].as_u64().unwrap_or(300);
+    let repos = parse_repo_configs(&trigger_config);
+    if repos.is_empty() {
+        tracing::error!(flow = %flow_name, "No valid repos configured for GitHub release trigger");
+        return;
+    }
     tracing::info!(
         flow = %flow_name,
-        repos = seeded_repos.len(),
+        repos = repos.len(),
         interval = poll_interval,
-        skip_drafts,
-        review_on_push,
-        "Polling {} repos every {}s",
-        seeded_repos.len(),
+        "Polling releases every {}s",
         poll_interval
     );
 
-    let mut interval =
-        tokio::time::interval(std::time::Duration::from_secs(poll_interval));
+    // repo -> release IDs already seen; absent until the repo's first poll
+    let mut seen: HashMap<String, HashSet<u64>> = HashMap::new();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(poll_interval));
 
     loop {
         interval.tick().await;
+        if maintenance::resumed().await {
+            interval.reset();
+        }
 
-        // Check if flow still exists and is enabled
         let flow = match flow_repo.get_flow(flow_id).await {
             Some(f) if f.enabled => f,
             Some(_) => {
-                tracing::info!(flow = %flow_name, "Flow disabled, stopping PR poll loop");
+                tracing::info!(flow = %flow_name, "Flow disabled, stopping release poll loop");
                 return;
             }
             None => {
-                tracing::info!(flow = %flow_name, "Flow deleted, stopping PR poll loop");
+                tracing::info!(flow = %flow_name, "Flow deleted, stopping release poll loop");
                 return;
             }
         };
+        // New releases stay unseen while a queueing window is closed
+        let gate = window::gate(&flow, Utc::now());
+        if matches!(gate, Gate::Queue(_)) {
+            continue;
+        }
 
-        for repo in &seeded_repos {
-            let prs = match github_client
-                .fetch_open_prs(&repo.owner, &repo.repo)
-                .await
-            {
-                Ok(prs) => prs,
+        for repo in &repos {
+            let releases = match github_client.fetch_recent_releases(&repo.owner, &repo.repo).await {
+                Ok(releases) => releases,
                 Err(e) => {
-                    tracing::error!(repo = %repo.full_name(), error = %e, "Failed to fetch PRs");
+                    tracing::warn!(repo = %repo.full_name(), error = %e, "Failed to fetch releases");
                     continue;
                 }
             };
-
-            for pr in prs {
-                if pr.draft && skip_drafts {
+            let first_poll = !seen.contains_key(&repo.full_name());
+            let repo_seen = seen.entry(repo.full_name()).or_default();
+            // Oldest first, so several new releases are announced in order
+            for release in releases.iter().rev().filter(|r| !r.draft) {
+                if !repo_seen.insert(release.id) || first_poll {
+                    continue;
+                }
+                if !release_matches(&trigger_config, release) {
                     continue;
                 }
+                if gate == Gate::Drop {
+                    tracing::info!(flow = %flow_name, tag = %release.tag_name, "Release outside active window, skipped");
+                    continue;
+                }
+                announce_release(make_runner(), &flow, &*flow_repo, repo, release).await;
+            }
+        }
+    }
+}
 
-                let review_type = {
-                    let mut seen = seen_prs.lock().await;
-                    let seen_map = seen.entry(repo.full_name()).or_default();
+/// Whether a published release passes the trigger's filters: pre-releases
+/// only with `include_prereleases`, and the tag must match `tag_pattern`
+/// (a glob, e.g. `v*`) when set.
+fn release_matches(trigger_config: &serde_json::Value, release: &Release) -> bool {
+    if release.draft {
+        return false;
+    }
+    if release.prerelease && !trigger_config["include_prereleases"].as_bool().unwrap_or(false) {
+        return false;
+    }
+    match trigger_config["tag_pattern"].as_str() {
+        Some(pattern) => glob::Pattern::new(pattern).is_ok_and(|p| p.matches(&release.tag_name)),
+        None => true,
+    }
+}
 
-                    match seen_map.get(&pr.number) {
-                        None => {
-                            seen_map.insert(pr.number, pr.head.sha.clone());
-                            ReviewType::Initial
-                        }
-                        Some(old_sha) if review_on_push && *old_sha != pr.head.sha => {
-                            let old = old_sha.clone();
-                            seen_map.insert(pr.number, pr.head.sha.clone());
-                            ReviewType::ReReview { previous_sha: old }
-                        }
-                        _ => continue,
-                    }
-                };
+/// Run `flow` on a newly published release with its [`release_context`].
+async fn announce_release(
+    runner: FlowRunner,
+    flow: &Flow,
+    flow_repo: &dyn FlowRepository,
+    repo: &RepoConfig,
+    release: &Release,
+) {
+    tracing::info!(
+        flow = %flow.name,
+        repo = %repo.full_name(),
+        tag = %release.tag_name,
+        "New release {} published",
+        release.tag_name
+    );
+    let context = release_context(repo, release);
+    match runner.execute(flow, flow_repo, Some(context)).await {
+        Ok(run) => tracing::info!(flow = %flow.name, tag = %release.tag_name, run_id = %run.id, "Release run completed"),
+        Err(e) => tracing::error!(flow = %flow.name, tag = %release.tag_name, error = %e, "Release run failed"),
+    }
+}
 
-                tracing::info!(
-                    flow = %flow_name,
-                    repo = %repo.full_name(),
-                    pr = pr.number,
-                    title = %pr.title,
-                    review_type = %review_type,
-                    "PR #{} detected ({}): {}",
-                    pr.number,
-                    review_type,
-                    pr.title
-                );
+fn release_context(repo: &RepoConfig, release: &Release) -> HashMap<String, String> {
+    let mut context = HashMap::new();
+    context.insert("repo".to_string(), repo.full_name());
+    context.insert("local_path".to_string(), repo.local_path.display().to_string());
+    context.insert("release_tag".to_string(), release.tag_name.clone());
+    context.insert("release_name".to_string(), release.title().to_string());
+    context.insert("release_notes".to_string(), release.body.clone().unwrap_or_default());
+    context.insert(
+        "release_author".to_string(),
+        release.author.as_ref().map(|u| u.login.clone()).unwrap_or_default(),
+    );
+    context.insert("release_url".to_string(), release.html_url.clone());
+    context.insert("release_prerelease".to_string(), release.prerelease.to_string());
+    context
+}
 
-                // Post starting comment
-                let start_msg = match &review_type {
-                    ReviewType::Initial => format!(
-                        ":robot: **Cthulu Review Bot** is starting a deep-dive review of this PR...\n\n\
-                         _Reviewing PR #{} — this may take a few minutes._",
-                        pr.number
-                    ),
-                    ReviewType::ReReview { previous_sha } => format!(
-                        ":robot: **Cthulu Review Bot** is re-reviewing this PR after new commits...\n\n\
-                         _Re-reviewing PR #{} (previous HEAD: `{}`, new HEAD: `{}`)_",
-                        pr.number,
-                        &previous_sha[..7.min(previous_sha.len())],
-                        &pr.head.sha[..7.min(pr.head.sha.len())]
-                    ),
-                };
-                if let Err(e) = github_client
-                    .post_comment(&repo.owner, &repo.repo, pr.number, &start_msg)
-                    .await
-                {
-                    tracing::warn!(error = %e, "Failed to post starting comment");
-                }
+// ── PR review ────────────────────────────────────────────────────
+
+/// Review one PR through `flow`: post a starting comment, build the diff and
+/// repo context, and run the flow. Failures are logged rather than returned
+/// so one bad PR doesn't stop the poll loop or a webhook delivery.
+#[allow(clippy::too_many_arguments)]
+async fn review_pr(
+    runner: FlowRunner,
+    flow: &Flow,
+    flow_repo: &dyn FlowRepository,
+    github_client: &dyn GithubClient,
+    repo: &RepoConfig,
+    pr: PullRequest,
+    review_type: ReviewType,
+    logged: LoggedEvent,
+) {
+    let Some(trigger_config) = flow
+        .nodes
+        .iter()
+        .find(|n| n.node_type == NodeType::Trigger)
+        .map(|n| &n.config)
+    else {
+        logged.set(EventStatus::Done);
+        return;
+    };
+    let max_diff_size = trigger_config["max_diff_size"].as_u64().unwrap_or(50_000) as usize;
 
-                // Fetch diff
-                let diff_raw = match github_client
-                    .fetch_pr_diff(&repo.owner, &repo.repo, pr.number)
-                    .await
-                {
-                    Ok(d) => d,
-                    Err(e) => {
-                        tracing::error!(error = %e, "Failed to fetch PR diff");
-                        continue;
-                    }
-                };
+    tracing::info!(
+        flow = %flow.name,
+        repo = %repo.full_name(),
+        pr = pr.number,
+        title = %pr.title,
+        review_type = %review_type,
+        "PR #{} detected ({}): {}",
+        pr.number,
+        review_type,
+        pr.title
+    );
 
-                let diff_ctx = match diff::prepare_diff_context(&diff_raw, pr.number, max_diff_size)
-                {
-                    Ok(ctx) => ctx,
-                    Err(e) => {
-                        tracing::error!(error = %e, "Failed to prepare diff context");
-                        continue;
-                    }
-                };
+    // Post starting comment
+    let start_msg = match &review_type {
+        ReviewType::Initial => format!(
+            ":robot: **Cthulu Review Bot** is starting a deep-dive review of this PR...\n\n\
+             _Reviewing PR #{} — this may take a few minutes._",
+            pr.number
+        ),
+        ReviewType::Ready => format!(
+            ":robot: **Cthulu Review Bot** is reviewing this PR now that it's ready for review...\n\n\
+             _Reviewing PR #{} — this may take a few minutes._",
+            pr.number
+        ),
+        ReviewType::ReReview { previous_sha } => format!(
+            ":robot: **Cthulu Review Bot** is re-reviewing this PR after new commits...\n\n\
+             _Re-reviewing PR #{} (previous HEAD: `{}`, new HEAD: `{}`)_",
+            pr.number,
+            &previous_sha[..7.min(previous_sha.len())],
+            &pr.head.sha[..7.min(pr.head.sha.len())]
+        ),
+        ReviewType::Command { command, requested_by, .. } => format!(
+            ":robot: **Cthulu Review Bot** is running `{} {}` on this PR...\n\n\
+             _Requested by @{requested_by} on PR #{}._",
+            commands::COMMAND_PREFIX,
+            command.name,
+            pr.number
+        ),
+    };
+    if let Err(e) = github_client
+        .post_comment(&repo.owner, &repo.repo, pr.number, &start_msg)
+        .await
+    {
+        tracing::warn!(error = %e, "Failed to post starting comment");
+    }
 
-                // Build context
-                let mut context = HashMap::new();
-                context.insert("diff".to_string(), diff_ctx.text());
-                context.insert("pr_number".to_string(), pr.number.to_string());
-                context.insert("pr_title".to_string(), pr.title.clone());
-                context.insert("pr_body".to_string(), pr.body.clone().unwrap_or_default());
-                context.insert("base_ref".to_string(), pr.base.ref_name.clone());
-                context.insert("head_ref".to_string(), pr.head.ref_name.clone());
-                context.insert("head_sha".to_string(), pr.head.sha.clone());
-                context.insert("repo".to_string(), repo.full_name());
-                context.insert(
-                    "local_path".to_string(),
-                    repo.local_path.display().to_string(),
-                );
-                context.insert("review_type".to_string(), review_type.to_string());
-
-                // Git fetch before review
-                let _ = tokio::process::Command::new("git")
-                    .args(["fetch", "origin"])
-                    .current_dir(&repo.local_path)
-                    .output()
-                    .await;
-
-                let runner = FlowRunner {
-                    http_client: http_client.clone(),
-                    github_client: Some(github_client.clone()),
-                    events_tx: Some(events_tx.clone()),
-                    sandbox_provider: Some(sandbox_provider.clone()),
-                    agent_repo: Some(agent_repo.clone()),
-                    session_bridge: Some(session_bridge.clone()),
-                };
+    // Fetch diff
+    let diff_raw = match github_client
+        .fetch_pr_diff(&repo.owner, &repo.repo, pr.number)
+        .await
+    {
+        Ok(d) => d,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to fetch PR diff");
+            logged.set(EventStatus::Done);
+            return;
+        }
+    };
 
-                match runner
-                    .execute(&flow, &*flow_repo, Some(context))
-                    .await
-                {
-                    Ok(run) => {
-                        tracing::info!(
-                            flow = %flow_name,
-                            repo = %repo.full_name(),
-                            pr = pr.number,
-                            run_id = %run.id,
-                            "PR review completed"
-                        );
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            flow = %flow_name,
-                            repo = %repo.full_name(),
-                            pr = pr.number,
-                            error = %e,
-                            "PR review failed"
-                        );
-                    }
-                }
+    let diff_ctx = match diff::prepare_diff_context(&diff_raw, pr.number, max_diff_size) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to prepare diff context");
+            logged.set(EventStatus::Done);
+            return;
+        }
+    };
 
-                diff::cleanup(&diff_ctx);
-            }
+    // Build context
+    let mut context = HashMap::new();
+    context.insert("diff".to_string(), diff_ctx.text());
+    context.insert("pr_number".to_string(), pr.number.to_string());
+    context.insert("pr_title".to_string(), pr.title.clone());
+    context.insert("pr_body".to_string(), pr.body.clone().unwrap_or_default());
+    context.insert("base_ref".to_string(), pr.base.ref_name.clone());
+    context.insert("head_ref".to_string(), pr.head.ref_name.clone());
+    context.insert("head_sha".to_string(), pr.head.sha.clone());
+    context.insert("repo".to_string(), repo.full_name());
+    context.insert(
+        "local_path".to_string(),
+        repo.local_path.display().to_string(),
+    );
+    context.insert("review_type".to_string(), review_type.to_string());
+    let (command, command_args, requested_by) = match &review_type {
+        ReviewType::Command { command, requested_by, .. } => {
+            (command.name.clone(), command.args.clone(), requested_by.clone())
+        }
+        _ => ("review".to_string(), String::new(), String::new()),
+    };
+    context.insert("command".to_string(), command);
+    context.insert("command_args".to_string(), command_args);
+    context.insert("requested_by".to_string(), requested_by);
+    add_repo_context(
+        &mut context,
+        github_client,
+        trigger_config,
+        &repo.owner,
+        &repo.repo,
+        &pr,
+        &diff_raw,
+    )
+    .await;
+    add_risk_score(&mut context, trigger_config, &diff_raw);
+
+    // Git fetch before review
+    let _ = tokio::process::Command::new("git")
+        .args(["fetch", "origin"])
+        .current_dir(&repo.local_path)
+        .output()
+        .await;
+
+    add_check_results(
+        &mut context,
+        runner.sandbox_provider.as_deref(),
+        trigger_config,
+        &repo.local_path,
+        &pr,
+    )
+    .await;
+
+    logged.set(EventStatus::Started);
+    match runner
+        .execute(flow, flow_repo, Some(context))
+        .await
+    {
+        Ok(run) => {
+            tracing::info!(
+                flow = %flow.name,
+                repo = %repo.full_name(),
+                pr = pr.number,
+                run_id = %run.id,
+                "PR review completed"
+            );
+        }
+        Err(e) => {
+            tracing::error!(
+                flow = %flow.name,
+                repo = %repo.full_name(),
+                pr = pr.number,
+                error = %e,
+                "PR review failed"
+            );
         }
     }
+    logged.set(EventStatus::Done);
+
+    diff::cleanup(&diff_ctx);
 }
 
 // ── Helpers ──────────────────────────────────────────────────────
 
+/// Add `{{languages}}`, `{{codeowners}}`, `{{owner_mentions}}` and
+/// `{{incident_tags}}` to a PR review context, unless the trigger sets
+/// `repo_context: false`.
+///
+/// `mention_owners: false` blanks `{{owner_mentions}}`; with
+/// `request_owner_reviews: true` the code owners are also requested as
+/// reviewers on the PR's first review.
+async fn add_repo_context(
+    context: &mut HashMap<String, String>,
+    github_client: &dyn GithubClient,
+    trigger_config: &serde_json::Value,
+    owner: &str,
+    repo: &str,
+    pr: &PullRequest,
+    diff_raw: &str,
+) {
+    if trigger_config["repo_context"].as_bool() == Some(false) {
+        return;
+    }
+    let incident_label = trigger_config["incident_label"]
+        .as_str()
+        .unwrap_or("incident");
+    let touched: Vec<String> = diff::split_diff_by_file(diff_raw)
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    let repo_ctx = repo_context::gather(
+        github_client,
+        owner,
+        repo,
+        &pr.base.ref_name,
+        &touched,
+        incident_label,
+    )
+    .await;
+    context.extend(repo_ctx.vars);
+
+    if trigger_config["mention_owners"].as_bool() == Some(false) {
+        context.insert("owner_mentions".to_string(), "none".to_string());
+    }
+
+    let first_review = matches!(
+        context.get("review_type").map(String::as_str),
+        Some("initial" | "ready_for_review")
+    );
+    if trigger_config["request_owner_reviews"].as_bool() == Some(true) && first_review {
+        let author = pr.user.as_ref().map(|u| u.login.as_str());
+        let (users, teams) = repo_context::reviewer_request(&repo_ctx.owners, owner, author);
+        if !users.is_empty() || !teams.is_empty() {
+            match github_client
+                .request_reviewers(owner, repo, pr.number, &users, &teams)
+                .await
+            {
+                Ok(()) => tracing::info!(pr = pr.number, users = ?users, teams = ?teams, "Requested code owner reviews"),
+                Err(e) => tracing::warn!(pr = pr.number, error = %e, "Failed to request code owner reviews"),
+            }
+        }
+    }
+}
+
+/// Add `{{risk_score}}`, `{{risk_level}}` and `{{risk_factors}}`, scored
+/// against the trigger's `critical_paths` globs. Executors can branch on
+/// them with `run_when: { risk_level: [high] }`.
+fn add_risk_score(
+    context: &mut HashMap<String, String>,
+    trigger_config: &serde_json::Value,
+    diff_raw: &str,
+) {
+    let critical_paths: Vec<String> = trigger_config["critical_paths"]
+        .as_array()
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    let report = risk::assess(&diff::split_diff_by_file(diff_raw), &critical_paths);
+    tracing::info!(score = report.score, level = report.level(), "Assessed PR risk");
+    context.extend(report.vars());
+}
+
+/// Decide whether `pr` needs a review, recording its head SHA as seen.
+/// A PR that `became_ready` (moved out of draft) always gets a
+/// ready-for-review review, even if it was reviewed as a draft. Otherwise
+/// unseen PRs get an initial review; with `review_on_push`, seen PRs whose
+/// head moved get a re-review.
+fn next_review(
+    seen_map: &mut HashMap<u64, String>,
+    pr: &PullRequest,
+    review_on_push: bool,
+    became_ready: bool,
+) -> Option<ReviewType> {
+    if became_ready {
+        seen_map.insert(pr.number, pr.head.sha.clone());
+        return Some(ReviewType::Ready);
+    }
+    match seen_map.get(&pr.number) {
+        None => {
+            seen_map.insert(pr.number, pr.head.sha.clone());
+            Some(ReviewType::Initial)
+        }
+        Some(old_sha) if review_on_push && *old_sha != pr.head.sha => {
+            let previous_sha = old_sha.clone();
+            seen_map.insert(pr.number, pr.head.sha.clone());
+            Some(ReviewType::ReReview { previous_sha })
+        }
+        _ => None,
+    }
+}
+
+/// The log entry of a PR review. Reviews of the same head (or the same
+/// command comment) share an id, so each runs once however often it's
+/// detected.
+fn review_event(flow_id: &str, repo: &RepoConfig, pr: &PullRequest, review_type: &ReviewType) -> TriggerEvent {
+    let id = match review_type {
+        ReviewType::Command { comment_id, .. } => format!("pr-comment:{flow_id}:{}:{comment_id}", repo.full_name()),
+        _ => format!("pr:{flow_id}:{}#{}@{}:{review_type}", repo.full_name(), pr.number, pr.head.sha),
+    };
+    let payload = ReviewPayload {
+        repo: repo.full_name(),
+        pr_number: pr.number,
+        review_type: review_type.clone(),
+    };
+    TriggerEvent::new(flow_id, id, serde_json::to_value(payload).unwrap_or_default())
+}
+
+/// Log the review before running it and save `seen`. `None` when it was
+/// logged before — a redelivered webhook, or a PR detected again after a
+/// restart.
+fn claim_review(
+    log: &TriggerLog,
+    flow_id: &str,
+    repo: &RepoConfig,
+    pr: &PullRequest,
+    review_type: &ReviewType,
+    seen: &SeenPrs,
+) -> Option<LoggedEvent> {
+    let event = review_event(flow_id, repo, pr, review_type);
+    let id = event.id.clone();
+    let claimed = match log.claim(event) {
+        Ok(Some(event)) => Some(event),
+        Ok(None) => {
+            tracing::info!(repo = %repo.full_name(), pr = pr.number, "PR review already logged, skipping");
+            None
+        }
+        Err(e) => {
+            tracing::warn!(event = %id, error = %e, "Failed to log PR review, running it anyway");
+            Some(log.resume(id))
+        }
+    };
+    save_seen(log, seen);
+    claimed
+}
+
+fn save_seen(log: &TriggerLog, seen: &SeenPrs) {
+    if let Err(e) = log.save_seen_prs(seen) {
+        tracing::warn!(error = %e, "Failed to save seen PRs");
+    }
+}
+
+/// Run the trigger's `pre_review.command` at the PR head and add
+/// `{{test_status}}` and `{{test_results}}`, so the review is grounded in
+/// actual results. A no-op without `pre_review`.
+async fn add_check_results(
+    context: &mut HashMap<String, String>,
+    sandbox_provider: Option<&dyn SandboxProvider>,
+    trigger_config: &serde_json::Value,
+    repo_path: &std::path::Path,
+    pr: &PullRequest,
+) {
+    let Some(config) = checks::CheckConfig::from_trigger(trigger_config) else {
+        return;
+    };
+    let Some(provider) = sandbox_provider else {
+        tracing::warn!(pr = pr.number, "Skipping pre-review check: no sandbox provider");
+        return;
+    };
+    let report = checks::run(provider, repo_path, pr, &config).await;
+    context.extend(report.vars());
+}
+
+/// Whether a github-pr flow answers `/cthulu <name>`: its trigger's
+/// `commands` list, `["review"]` by default.
+fn handles_command(trigger_config: &serde_json::Value, name: &str) -> bool {
+    match trigger_config["commands"].as_array() {
+        Some(names) => names.iter().any(|n| n.as_str() == Some(name)),
+        None => name == "review",
+    }
+}
+
+/// Fetch the PR a command comment was posted on and record its head as
+/// seen, so the poll loop doesn't review the same commit again.
+async fn fetch_command_pr(
+    github_client: &dyn GithubClient,
+    seen_prs: &Mutex<SeenPrs>,
+    log: &TriggerLog,
+    repo: &RepoConfig,
+    pr_number: u64,
+) -> Option<PullRequest> {
+    match github_client.fetch_single_pr(&repo.owner, &repo.repo, pr_number).await {
+        Ok(pr) => {
+            let mut seen = seen_prs.lock().await;
+            seen.entry(repo.full_name())
+                .or_default()
+                .insert(pr.number, pr.head.sha.clone());
+            save_seen(log, &seen);
+            Some(pr)
+        }
+        Err(e) => {
+            // Commands on plain issues land here too
+            tracing::warn!(repo = %repo.full_name(), number = pr_number, error = %e, "Failed to fetch PR for command");
+            None
+        }
+    }
+}
+
 fn parse_repo_configs(trigger_config: &serde_json::Value) -> Vec<RepoConfig> {
     trigger_config["repos"]
         .as_array()
@@ -690,16 +2218,33 @@ fn parse_repo_configs(trigger_config: &serde_json::Value) -> Vec<RepoConfig> {
         .unwrap_or_default()
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 enum ReviewType {
     Initial,
+    /// The PR moved from draft to ready for review.
+    Ready,
     ReReview { previous_sha: String },
+    /// Requested with a `/cthulu <command>` PR comment.
+    Command { command: PrCommand, requested_by: String, comment_id: u64 },
+}
+
+/// What a logged PR review is redone with, if the server stops before its
+/// run starts.
+#[derive(Serialize, Deserialize)]
+struct ReviewPayload {
+    repo: String,
+    pr_number: u64,
+    review_type: ReviewType,
 }
 
 impl std::fmt::Display for ReviewType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ReviewType::Initial => write!(f, "initial"),
+            ReviewType::Ready => write!(f, "ready_for_review"),
             ReviewType::ReReview { .. } => write!(f, "re-review"),
+            ReviewType::Command { .. } => write!(f, "command"),
         }
     }
 }
@@ -707,15 +2252,18 @@ impl std::fmt::Display for ReviewType {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::github::models::{PrRef, PullRequest};
+    use crate::github::models::{Label, PrRef, PrUser, PullRequest};
     use crate::tasks::context::render_prompt;
     use std::sync::Mutex as StdMutex;
 
     // --- Mock GithubClient ---
+    type ReviewRequest = (u64, Vec<String>, Vec<String>);
+
     #[allow(dead_code)]
     struct MockGithubClient {
         prs: StdMutex<Vec<PullRequest>>,
         comments_posted: StdMutex<Vec<(String, u64, String)>>,
+        reviewers_requested: StdMutex<Vec<ReviewRequest>>,
         diff: String,
     }
 
@@ -725,6 +2273,7 @@ mod tests {
             Self {
                 prs: StdMutex::new(prs),
                 comments_posted: StdMutex::new(Vec::new()),
+                reviewers_requested: StdMutex::new(Vec::new()),
                 diff: diff.to_string(),
             }
         }
@@ -749,6 +2298,87 @@ mod tests {
             ));
             Ok(())
         }
+        async fn fetch_languages(
+            &self,
+            _owner: &str,
+            _repo: &str,
+        ) -> anyhow::Result<std::collections::BTreeMap<String, u64>> {
+            Ok([("Rust".to_string(), 900), ("Shell".to_string(), 100)].into())
+        }
+        async fn fetch_file(&self, _owner: &str, _repo: &str, path: &str, _ref: &str) -> anyhow::Result<Option<String>> {
+            Ok((path == ".github/CODEOWNERS").then(|| "/src/ @org/backend @alice @author\n".to_string()))
+        }
+        async fn request_reviewers(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            pr: u64,
+            reviewers: &[String],
+            team_reviewers: &[String],
+        ) -> anyhow::Result<()> {
+            self.reviewers_requested
+                .lock()
+                .unwrap()
+                .push((pr, reviewers.to_vec(), team_reviewers.to_vec()));
+            Ok(())
+        }
+        async fn fetch_labeled_issues(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            label: &str,
+            _since: chrono::DateTime<Utc>,
+        ) -> anyhow::Result<Vec<crate::github::models::Issue>> {
+            let labels = [label, "payments"]
+                .into_iter()
+                .map(|name| crate::github::models::Label { name: name.to_string() })
+                .collect();
+            Ok(vec![crate::github::models::Issue {
+                labels,
+                ..Default::default()
+            }])
+        }
+        async fn fetch_open_issues(&self, _owner: &str, _repo: &str) -> anyhow::Result<Vec<crate::github::models::Issue>> {
+            Ok(vec![])
+        }
+        async fn fetch_recent_issues(&self, _owner: &str, _repo: &str) -> anyhow::Result<Vec<crate::github::models::Issue>> {
+            Ok(vec![])
+        }
+        async fn fetch_recent_releases(&self, _owner: &str, _repo: &str) -> anyhow::Result<Vec<Release>> {
+            Ok(vec![])
+        }
+        async fn fetch_branches(&self, _owner: &str, _repo: &str) -> anyhow::Result<Vec<crate::github::models::Branch>> {
+            Ok(vec![])
+        }
+        async fn fetch_issue_comments(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            _since: chrono::DateTime<chrono::Utc>,
+        ) -> anyhow::Result<Vec<crate::github::models::IssueComment>> {
+            Ok(vec![])
+        }
+        async fn compare_commits(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            _base: &str,
+            _head: &str,
+        ) -> anyhow::Result<Vec<crate::github::models::Commit>> {
+            Ok(vec![])
+        }
+        async fn fetch_compare_diff(&self, _owner: &str, _repo: &str, _base: &str, _head: &str) -> anyhow::Result<String> {
+            Ok(String::new())
+        }
+        async fn fetch_last_commit(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            _ref: Option<&str>,
+            _path: Option<&str>,
+        ) -> anyhow::Result<Option<crate::github::models::Commit>> {
+            Ok(None)
+        }
     }
 
     fn make_pr(number: u64, title: &str) -> PullRequest {
@@ -760,11 +2390,18 @@ mod tests {
             head: PrRef {
                 sha: format!("sha-{number}"),
                 ref_name: "feature-branch".to_string(),
+                repo: None,
             },
             base: PrRef {
                 sha: "def456".to_string(),
                 ref_name: "main".to_string(),
+                repo: None,
             },
+            user: Some(PrUser {
+                login: "author".to_string(),
+            }),
+            created_at: None,
+            author_association: None,
         }
     }
 
@@ -780,9 +2417,52 @@ mod tests {
         pr
     }
 
+    #[tokio::test]
+    async fn test_add_repo_context() {
+        let client = MockGithubClient::new(vec![], "");
+        let pr = make_pr(1, "Change");
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n+fn x() {}\n";
+
+        let mut context = HashMap::new();
+        let config = serde_json::json!({});
+        add_repo_context(&mut context, &client, &config, "org", "r", &pr, diff).await;
+        assert_eq!(context["languages"], "Rust 90.0%, Shell 10.0%");
+        assert_eq!(context["codeowners"], "- @org/backend @alice @author: src/lib.rs");
+        assert_eq!(context["owner_mentions"], "@org/backend @alice @author");
+        assert_eq!(context["incident_tags"], "payments (1)");
+        assert!(client.reviewers_requested.lock().unwrap().is_empty());
+
+        let mut context = HashMap::new();
+        let config = serde_json::json!({ "repo_context": false });
+        add_repo_context(&mut context, &client, &config, "org", "r", &pr, diff).await;
+        assert!(context.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_repo_context_requests_owner_reviews() {
+        let client = MockGithubClient::new(vec![], "");
+        let pr = make_pr(7, "Change");
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n+fn x() {}\n";
+        let config = serde_json::json!({ "request_owner_reviews": true, "mention_owners": false });
+
+        let mut context = HashMap::from([("review_type".to_string(), "initial".to_string())]);
+        add_repo_context(&mut context, &client, &config, "org", "r", &pr, diff).await;
+        assert_eq!(context["owner_mentions"], "none");
+        assert_eq!(
+            *client.reviewers_requested.lock().unwrap(),
+            vec![(7, vec!["alice".to_string()], vec!["backend".to_string()])]
+        );
+
+        // Re-reviews don't re-request
+        let mut context = HashMap::from([("review_type".to_string(), "re-review".to_string())]);
+        add_repo_context(&mut context, &client, &config, "org", "r", &pr, diff).await;
+        assert_eq!(client.reviewers_requested.lock().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_review_type_display() {
         assert_eq!(ReviewType::Initial.to_string(), "initial");
+        assert_eq!(ReviewType::Ready.to_string(), "ready_for_review");
         assert_eq!(
             ReviewType::ReReview {
                 previous_sha: "abc".to_string()
@@ -852,6 +2532,202 @@ mod tests {
         assert_eq!(stored_sha, &pr.head.sha);
     }
 
+    #[test]
+    fn test_push_context() {
+        let commit = |sha: &str, message: &str, login: Option<&str>, name: &str| {
+            serde_json::from_value::<Commit>(serde_json::json!({
+                "sha": sha,
+                "commit": {
+                    "message": message,
+                    "author": { "name": name, "date": "2024-05-01T10:00:00Z" },
+                    "committer": { "name": name, "date": "2024-05-01T10:00:00Z" }
+                },
+                "author": login.map(|l| serde_json::json!({ "login": l }))
+            }))
+            .unwrap()
+        };
+        let commits = vec![
+            commit("aaaaaaaaaa", "Fix parser\n\nLonger body", Some("alice"), "Alice A"),
+            commit("bbbbbbbbbb", "Bump deps", None, "Bob B"),
+            commit("cccccccccc", "Tidy", Some("alice"), "Alice A"),
+        ];
+        let repo = RepoConfig {
+            owner: "owner".into(),
+            repo: "repo".into(),
+            local_path: PathBuf::from("/src/repo"),
+        };
+        let push = PushRange { branch: "main".into(), before: "0123".into(), after: "cccccccccc".into() };
+
+        let ctx = push_context(&repo, &push, &commits);
+        assert_eq!(ctx["commit_count"], "3");
+        assert_eq!(ctx["commit_authors"], "alice, Bob B");
+        assert_eq!(ctx["commit_shas"], "aaaaaaaaaa\nbbbbbbbbbb\ncccccccccc");
+        assert_eq!(ctx["commits"].lines().next(), Some("- aaaaaaa Fix parser (alice)"));
+        assert_eq!(ctx["branch"], "main");
+        assert_eq!(ctx["repo"], "owner/repo");
+    }
+
+    #[test]
+    fn test_watches_branch() {
+        let default = serde_json::json!({});
+        assert!(watches_branch(&default, "main"));
+        assert!(!watches_branch(&default, "feature/x"));
+
+        let config = serde_json::json!({ "branches": ["main", "release/*"] });
+        assert!(watches_branch(&config, "release/1.2"));
+        assert!(!watches_branch(&config, "develop"));
+    }
+
+    fn make_issue(number: u64, labels: &[&str]) -> Issue {
+        Issue {
+            number,
+            title: "Crash on startup".to_string(),
+            body: Some("Steps to reproduce".to_string()),
+            html_url: format!("https://github.com/owner/repo/issues/{number}"),
+            user: Some(PrUser { login: "bob".to_string() }),
+            labels: labels.iter().map(|l| Label { name: l.to_string() }).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_issue_matches() {
+        let unlabeled = make_issue(1, &[]);
+        let triaged = make_issue(2, &["bug", "Triaged"]);
+
+        let default = serde_json::json!({});
+        assert!(issue_matches(&default, &unlabeled));
+        assert!(issue_matches(&default, &triaged));
+
+        let config = serde_json::json!({ "without_labels": ["triaged"] });
+        assert!(issue_matches(&config, &unlabeled));
+        assert!(!issue_matches(&config, &triaged));
+        assert!(issue_matches(&config, &make_issue(3, &["bug"])));
+
+        let config = serde_json::json!({ "unlabeled_only": true });
+        assert!(issue_matches(&config, &unlabeled));
+        assert!(!issue_matches(&config, &make_issue(3, &["bug"])));
+    }
+
+    #[test]
+    fn test_issue_context() {
+        let repo = RepoConfig {
+            owner: "owner".into(),
+            repo: "repo".into(),
+            local_path: PathBuf::from("/src/repo"),
+        };
+        let ctx = issue_context(&repo, &make_issue(31, &["bug", "ui"]));
+        assert_eq!(ctx["repo"], "owner/repo");
+        assert_eq!(ctx["issue_number"], "31");
+        assert_eq!(ctx["issue_title"], "Crash on startup");
+        assert_eq!(ctx["issue_body"], "Steps to reproduce");
+        assert_eq!(ctx["issue_author"], "bob");
+        assert_eq!(ctx["issue_url"], "https://github.com/owner/repo/issues/31");
+        assert_eq!(ctx["issue_labels"], "bug, ui");
+    }
+
+    fn make_release(tag: &str, prerelease: bool) -> Release {
+        Release {
+            id: 7,
+            tag_name: tag.to_string(),
+            name: Some(format!("Release {tag}")),
+            body: Some("- Faster builds".to_string()),
+            html_url: format!("https://github.com/owner/repo/releases/tag/{tag}"),
+            prerelease,
+            author: Some(PrUser { login: "alice".to_string() }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_release_matches() {
+        let config = serde_json::json!({});
+        assert!(release_matches(&config, &make_release("v1.0.0", false)));
+        assert!(!release_matches(&config, &make_release("v1.1.0-rc.1", true)));
+        assert!(!release_matches(&config, &Release { draft: true, ..make_release("v1.0.0", false) }));
+
+        let config = serde_json::json!({ "include_prereleases": true, "tag_pattern": "v*" });
+        assert!(release_matches(&config, &make_release("v1.1.0-rc.1", true)));
+        assert!(!release_matches(&config, &make_release("nightly-2026-01-01", false)));
+    }
+
+    #[test]
+    fn test_release_context() {
+        let repo = RepoConfig {
+            owner: "owner".into(),
+            repo: "repo".into(),
+            local_path: PathBuf::from("/src/repo"),
+        };
+        let ctx = release_context(&repo, &make_release("v1.2.0", false));
+        assert_eq!(ctx["repo"], "owner/repo");
+        assert_eq!(ctx["release_tag"], "v1.2.0");
+        assert_eq!(ctx["release_name"], "Release v1.2.0");
+        assert_eq!(ctx["release_notes"], "- Faster builds");
+        assert_eq!(ctx["release_author"], "alice");
+        assert_eq!(ctx["release_url"], "https://github.com/owner/repo/releases/tag/v1.2.0");
+        assert_eq!(ctx["release_prerelease"], "false");
+    }
+
+    #[test]
+    fn test_handles_command() {
+        let default = serde_json::json!({ "repos": [] });
+        assert!(handles_command(&default, "review"));
+        assert!(!handles_command(&default, "explain"));
+
+        let custom = serde_json::json!({ "commands": ["explain", "security-scan"] });
+        assert!(handles_command(&custom, "security-scan"));
+        assert!(!handles_command(&custom, "review"));
+    }
+
+    #[test]
+    fn test_next_review() {
+        let mut seen = HashMap::new();
+        let pr = make_pr_with_sha(1, "Feature", "aaa111");
+        assert!(matches!(next_review(&mut seen, &pr, true, false), Some(ReviewType::Initial)));
+        // Redelivered event for the same head
+        assert!(next_review(&mut seen, &pr, true, false).is_none());
+
+        let pushed = make_pr_with_sha(1, "Feature", "bbb222");
+        assert!(next_review(&mut seen, &pushed, false, false).is_none());
+        match next_review(&mut seen, &pushed, true, false) {
+            Some(ReviewType::ReReview { previous_sha }) => assert_eq!(previous_sha, "aaa111"),
+            _ => panic!("expected a re-review"),
+        }
+        assert_eq!(seen[&1], "bbb222");
+
+        // Marked ready after a review as a draft: reviewed again
+        assert!(matches!(next_review(&mut seen, &pushed, false, true), Some(ReviewType::Ready)));
+        let ready = make_pr_with_sha(2, "Was a draft", "ccc333");
+        assert!(matches!(next_review(&mut seen, &ready, false, true), Some(ReviewType::Ready)));
+        assert_eq!(seen[&2], "ccc333");
+    }
+
+    #[test]
+    fn test_claim_review_once_per_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = TriggerLog::new(dir.path());
+        let repo = RepoConfig {
+            owner: "owner".into(),
+            repo: "repo".into(),
+            local_path: PathBuf::from("/src/repo"),
+        };
+        let seen = SeenPrs::from([("owner/repo".to_string(), HashMap::from([(1, "aaa111".to_string())]))]);
+        let pr = make_pr_with_sha(1, "Feature", "aaa111");
+
+        assert!(claim_review(&log, "f", &repo, &pr, &ReviewType::Initial, &seen).is_some());
+        // Redelivered, or detected again after a restart
+        assert!(claim_review(&log, "f", &repo, &pr, &ReviewType::Initial, &seen).is_none());
+        // Another flow, or a different review of the same head
+        assert!(claim_review(&log, "g", &repo, &pr, &ReviewType::Initial, &seen).is_some());
+        assert!(claim_review(&log, "f", &repo, &pr, &ReviewType::Ready, &seen).is_some());
+        assert_eq!(log.seen_prs(), seen);
+
+        let pending = log.recover().unwrap();
+        let payload: ReviewPayload = serde_json::from_value(pending[0].payload.clone()).unwrap();
+        assert_eq!((payload.repo.as_str(), payload.pr_number), ("owner/repo", 1));
+        assert!(matches!(payload.review_type, ReviewType::Initial));
+    }
+
     #[test]
     fn test_review_type_context_in_prompt_rendering() {
         let template = "Review type: {{review_type}}, PR #{{pr_number}}";
@@ -891,4 +2767,34 @@ mod tests {
         }
         assert_eq!(seen["owner/repo"][&42], "abc123def456");
     }
+
+    #[test]
+    fn test_cron_timezone() {
+        assert_eq!(cron_timezone(&serde_json::json!({})).unwrap(), Tz::UTC);
+        assert_eq!(
+            cron_timezone(&serde_json::json!({"timezone": "Europe/London"})).unwrap(),
+            Tz::Europe__London
+        );
+        assert!(cron_timezone(&serde_json::json!({"timezone": "Mars/Olympus"})).is_err());
+    }
+
+    #[test]
+    fn test_cron_fields() {
+        let fields = cron_fields("0 9 * * 1-5").unwrap();
+        assert_eq!((fields["hour"].as_str(), fields["day_of_week"].as_str()), (Some("9"), Some("1-5")));
+        assert_eq!(cron_fields("30 0 9 * * *").unwrap()["second"], "30");
+        assert_eq!(cron_fields("@daily"), None);
+    }
+
+    #[test]
+    fn test_next_cron_runs_follow_local_time_across_dst() {
+        // UK clocks go forward on Sunday 2025-03-30.
+        let cron = Cron::new("0 9 * * 1-5").parse().unwrap();
+        let after = DateTime::parse_from_rfc3339("2025-03-27T12:00:00Z").unwrap().with_timezone(&Utc);
+        let runs: Vec<String> = next_cron_runs(&cron, Tz::Europe__London, after, 2)
+            .iter()
+            .map(|r| r.with_timezone(&Utc).to_rfc3339())
+            .collect();
+        assert_eq!(runs, ["2025-03-28T09:00:00+00:00", "2025-03-31T08:00:00+00:00"]);
+    }
 }
@@ -0,0 +1,118 @@
+//! Spreading out cron fires, so twenty "every hour" flows don't all hit
+//! GitHub and Claude at :00. Flows sharing a schedule are staggered evenly
+//! across `CTHULU_CRON_STAGGER_SECS` (default 300; 0 turns it off), in flow
+//! id order, and a trigger's `jitter_secs` adds a random delay on top.
+//! Neither pushes a fire past half the time to the next one.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono_tz::Tz;
+use serde_json::Value;
+
+const DEFAULT_STAGGER_SECS: u64 = 300;
+
+/// The cron schedules of the running triggers, to stagger flows sharing one.
+pub struct Stagger {
+    window: Duration,
+    /// Schedule (expression and timezone) of each flow.
+    schedules: Mutex<BTreeMap<String, String>>,
+}
+
+impl Stagger {
+    pub fn from_env() -> Self {
+        Self::from_raw_values(std::env::var("CTHULU_CRON_STAGGER_SECS").ok().as_deref())
+    }
+
+    /// Used directly in tests to avoid mutating process-global environment.
+    pub fn from_raw_values(window_secs: Option<&str>) -> Self {
+        let window_secs = window_secs
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_STAGGER_SECS);
+        Self { window: Duration::from_secs(window_secs), schedules: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn register(&self, flow_id: &str, schedule: &str, timezone: Tz) {
+        let key = format!("{} {}", schedule.split_whitespace().collect::<Vec<_>>().join(" "), timezone.name());
+        self.schedules.lock().unwrap().insert(flow_id.to_string(), key);
+    }
+
+    pub fn unregister(&self, flow_id: &str) {
+        self.schedules.lock().unwrap().remove(flow_id);
+    }
+
+    pub fn clear(&self) {
+        self.schedules.lock().unwrap().clear();
+    }
+
+    /// How long after each fire `flow_id` runs, given `interval` between
+    /// its schedule's fires: its slot among the flows sharing it.
+    pub fn offset(&self, flow_id: &str, interval: Duration) -> Duration {
+        let schedules = self.schedules.lock().unwrap();
+        let Some(key) = schedules.get(flow_id) else {
+            return Duration::ZERO;
+        };
+        let sharing: Vec<&String> = schedules.iter().filter(|(_, k)| *k == key).map(|(id, _)| id).collect();
+        let slot = sharing.iter().position(|id| *id == flow_id).unwrap_or(0);
+        self.window.min(interval / 2) * slot as u32 / sharing.len() as u32
+    }
+}
+
+/// A trigger's `jitter_secs`: the most a fire is randomly delayed by.
+pub fn jitter_secs(config: &Value) -> Result<u64> {
+    match &config["jitter_secs"] {
+        Value::Null => Ok(0),
+        value => value.as_u64().context("jitter_secs must be a whole number of seconds"),
+    }
+}
+
+/// A random delay of up to `max_secs`, but under half of `interval`.
+pub fn jitter(max_secs: u64, interval: Duration) -> Duration {
+    let max = Duration::from_secs(max_secs).min(interval / 2);
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    let millis = uuid::Uuid::new_v4().as_u128() % max.as_millis();
+    Duration::from_millis(millis as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_offsets() {
+        let stagger = Stagger::from_raw_values(None);
+        stagger.register("a", "0 * * * *", Tz::UTC);
+        stagger.register("b", "0  *  * * *", Tz::UTC);
+        stagger.register("c", "0 * * * *", Tz::UTC);
+        stagger.register("d", "0 * * * *", Tz::Europe__London);
+        let offsets: Vec<u64> = ["a", "b", "c", "d", "unknown"].iter().map(|id| stagger.offset(id, HOUR).as_secs()).collect();
+        assert_eq!(offsets, [0, 100, 200, 0, 0]);
+
+        // Never past half the interval
+        assert_eq!(stagger.offset("c", Duration::from_secs(60)).as_secs(), 20);
+
+        stagger.unregister("a");
+        assert_eq!(stagger.offset("c", HOUR).as_secs(), 150);
+
+        let off = Stagger::from_raw_values(Some("0"));
+        off.register("a", "0 * * * *", Tz::UTC);
+        off.register("b", "0 * * * *", Tz::UTC);
+        assert_eq!(off.offset("b", HOUR), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_jitter() {
+        assert_eq!(jitter_secs(&json!({})).unwrap(), 0);
+        assert_eq!(jitter_secs(&json!({ "jitter_secs": 120 })).unwrap(), 120);
+        assert!(jitter_secs(&json!({ "jitter_secs": "2m" })).is_err());
+        assert!(jitter(120, Duration::from_secs(60)) < Duration::from_secs(30));
+        assert_eq!(jitter(0, HOUR), Duration::ZERO);
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+
+use crate::flows::Node;
+use crate::flows::graph::NodeOutput;
+use crate::tasks::pipeline::format_items;
+
+/// Executor kind that runs another stored flow on its input and passes on
+/// that run's final output, so a shared fragment ("post to Slack in our
+/// house format") is kept in one flow instead of copied into many.
+pub const SUBFLOW_KIND: &str = "subflow";
+
+/// Flows deep a chain of subflow calls may go, counting the first caller.
+const MAX_DEPTH: usize = 5;
+
+/// A subflow node's config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subflow {
+    pub flow_id: String,
+}
+
+impl Subflow {
+    pub fn for_node(node: &Node) -> Result<Self> {
+        let flow_id = node.config["flow_id"]
+            .as_str()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .context("subflow node needs a flow_id")?;
+        Ok(Self { flow_id: flow_id.to_string() })
+    }
+
+    /// Refuse a call that would run a flow already running further up
+    /// `callers` (the calling flow last), or go more than `MAX_DEPTH` deep.
+    pub fn check_callers(&self, callers: &[String]) -> Result<()> {
+        if callers.contains(&self.flow_id) {
+            anyhow::bail!("subflow cycle: {} → {}", callers.join(" → "), self.flow_id);
+        }
+        if callers.len() >= MAX_DEPTH {
+            anyhow::bail!("subflows nest more than {MAX_DEPTH} flows deep");
+        }
+        Ok(())
+    }
+}
+
+/// The context the called flow runs with: the caller's variables, and its
+/// input as `{{content}}` (or, when the input is a trigger's context, those
+/// variables too).
+pub fn context(input: &NodeOutput, vars: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut context = vars.clone();
+    match input {
+        NodeOutput::Context(map) => context.extend(map.clone()),
+        NodeOutput::Items(items) => {
+            context.insert("content".to_string(), format_items(items));
+            context.insert("item_count".to_string(), items.len().to_string());
+        }
+        other => {
+            context.insert("content".to_string(), other.as_text());
+        }
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::NodeType;
+    use serde_json::json;
+
+    fn node(config: serde_json::Value) -> Node {
+        Node {
+            id: "s".into(),
+            node_type: NodeType::Executor,
+            kind: SUBFLOW_KIND.into(),
+            config,
+            position: crate::flows::Position { x: 0.0, y: 0.0 },
+            label: "Post to Slack".into(),
+        }
+    }
+
+    #[test]
+    fn test_subflow_callers() {
+        assert!(Subflow::for_node(&node(json!({}))).is_err());
+        let subflow = Subflow::for_node(&node(json!({ "flow_id": "slack-post" }))).unwrap();
+
+        assert!(subflow.check_callers(&["digest".into()]).is_ok());
+        let err = subflow.check_callers(&["slack-post".into(), "digest".into()]).unwrap_err();
+        assert_eq!(err.to_string(), "subflow cycle: slack-post → digest → slack-post");
+        let deep: Vec<String> = (0..MAX_DEPTH).map(|i| format!("f{i}")).collect();
+        assert!(subflow.check_callers(&deep).is_err());
+    }
+
+    #[test]
+    fn test_subflow_context() {
+        let vars = HashMap::from([("team".to_string(), "infra".to_string())]);
+        let context = context(&NodeOutput::Text("Summary".into(), None), &vars);
+        assert_eq!(context["content"], "Summary");
+        assert_eq!(context["team"], "infra");
+    }
+}
@@ -0,0 +1,497 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::config::SinkConfig;
+use crate::flows::Flow;
+use crate::flows::history::FlowRun;
+use crate::json_file;
+use crate::tasks::pipeline::resolve_sinks;
+
+/// Sink kind that sends each subscriber their own variant of the digest.
+pub const PERSONALIZED_DIGEST_KIND: &str = "personalized-digest";
+
+/// Serializes read-modify-write of the subscription files; runners, API
+/// handlers and the delivery loop each hold their own `SubscriptionStore`.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// One person's subscription to a flow's output. The flow runs once; every
+/// subscriber gets that run's output through their own delivery channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    pub id: String,
+    pub flow_id: String,
+    /// Who this is for (a name or email), for display.
+    pub subscriber: String,
+    pub delivery: Delivery,
+    /// Local time of day (`HH:MM`) to deliver at; right after the run when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deliver_at: Option<String>,
+    /// IANA timezone for `deliver_at`. Defaults to UTC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Interest keywords a `personalized-digest` node ranks items by.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interests: Vec<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Where a subscriber's copy goes. Slack deliveries use the server's
+/// `SLACK_BOT_TOKEN`; email uses `SMTP_URL` / `SMTP_FROM`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Delivery {
+    SlackChannel { channel: String },
+    SlackDm { user_email: String },
+    Email { to: String },
+}
+
+impl Delivery {
+    fn sink_config(&self, flow_name: &str) -> SinkConfig {
+        match self {
+            Delivery::SlackChannel { channel } => SinkConfig::Slack {
+                webhook_url_env: None,
+                bot_token_env: Some("SLACK_BOT_TOKEN".to_string()),
+                channel: Some(channel.clone()),
+                user_email: None,
+            },
+            Delivery::SlackDm { user_email } => SinkConfig::Slack {
+                webhook_url_env: None,
+                bot_token_env: Some("SLACK_BOT_TOKEN".to_string()),
+                channel: None,
+                user_email: Some(user_email.clone()),
+            },
+            Delivery::Email { to } => SinkConfig::Email {
+                smtp_url_env: crate::config::default_smtp_url_env(),
+                from_env: crate::config::default_smtp_from_env(),
+                to: vec![to.clone()],
+                subject: Some(flow_name.to_string()),
+            },
+        }
+    }
+}
+
+impl Subscription {
+    /// Reject subscriptions that could never be delivered.
+    pub fn validate(&self) -> Result<()> {
+        if self.subscriber.trim().is_empty() {
+            anyhow::bail!("subscriber is required");
+        }
+        let target = match &self.delivery {
+            Delivery::SlackChannel { channel } => channel,
+            Delivery::SlackDm { user_email } => user_email,
+            Delivery::Email { to } => to,
+        };
+        if target.trim().is_empty() {
+            anyhow::bail!("delivery target is required");
+        }
+        self.next_delivery(Utc::now()).map(|_| ())
+    }
+
+    /// When output produced at `now` should reach this subscriber: `now`, or
+    /// the next `deliver_at` in the subscriber's timezone.
+    pub fn next_delivery(&self, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        let Some(deliver_at) = &self.deliver_at else {
+            return Ok(now);
+        };
+        let time = NaiveTime::parse_from_str(deliver_at, "%H:%M")
+            .with_context(|| format!("invalid deliver_at '{deliver_at}' (expected HH:MM)"))?;
+        let tz: Tz = match self.timezone.as_deref() {
+            None | Some("") => Tz::UTC,
+            Some(name) => name.parse().map_err(|_| anyhow::anyhow!("unknown timezone '{name}'"))?,
+        };
+
+        let today = now.with_timezone(&tz).date_naive();
+        for date in [today, today + Days::new(1)] {
+            // A time skipped by a DST jump delivers an hour later.
+            let local = date.and_time(time);
+            let Some(at) = tz
+                .from_local_datetime(&local)
+                .earliest()
+                .or_else(|| tz.from_local_datetime(&(local + chrono::Duration::hours(1))).earliest())
+            else {
+                continue;
+            };
+            let at = at.with_timezone(&Utc);
+            if at >= now {
+                return Ok(at);
+            }
+        }
+        anyhow::bail!("could not schedule delivery at {deliver_at}")
+    }
+}
+
+/// Deliveries that fail this many times are dropped.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry of a failed delivery; doubled each time.
+const RETRY_BACKOFF: chrono::Duration = chrono::Duration::minutes(5);
+
+/// A run's output waiting for a subscriber's delivery time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingDelivery {
+    subscription_id: String,
+    flow_name: String,
+    run_id: String,
+    text: String,
+    due: DateTime<Utc>,
+    /// Failed attempts so far; `due` is pushed back after each.
+    #[serde(default)]
+    attempts: u32,
+}
+
+impl PendingDelivery {
+    fn is(&self, other: &PendingDelivery) -> bool {
+        self.subscription_id == other.subscription_id && self.run_id == other.run_id
+    }
+}
+
+/// File-backed subscriptions (`~/.cthulu/subscriptions.json`) and their
+/// outbox of time-shifted deliveries (`~/.cthulu/state/subscription-outbox.json`).
+#[derive(Clone)]
+pub struct SubscriptionStore {
+    subscriptions_path: PathBuf,
+    outbox_path: PathBuf,
+}
+
+impl SubscriptionStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            subscriptions_path: data_dir.join("subscriptions.json"),
+            outbox_path: data_dir.join("state").join("subscription-outbox.json"),
+        }
+    }
+
+    fn load<T: for<'de> Deserialize<'de>>(path: &Path) -> Vec<T> {
+        json_file::load_or_default(path)
+    }
+
+    fn write<T: Serialize>(path: &Path, entries: &[T]) -> Result<()> {
+        json_file::write(path, entries)
+    }
+
+    pub fn list(&self, flow_id: &str) -> Vec<Subscription> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        Self::load::<Subscription>(&self.subscriptions_path)
+            .into_iter()
+            .filter(|s| s.flow_id == flow_id)
+            .collect()
+    }
+
+    /// Insert or replace (by id).
+    pub fn save(&self, subscription: Subscription) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all: Vec<Subscription> = Self::load(&self.subscriptions_path);
+        match all.iter_mut().find(|s| s.id == subscription.id) {
+            Some(existing) => *existing = subscription,
+            None => all.push(subscription),
+        }
+        Self::write(&self.subscriptions_path, &all)
+    }
+
+    pub fn delete(&self, flow_id: &str, id: &str) -> Result<bool> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all: Vec<Subscription> = Self::load(&self.subscriptions_path);
+        let before = all.len();
+        all.retain(|s| !(s.flow_id == flow_id && s.id == id));
+        if all.len() == before {
+            return Ok(false);
+        }
+        Self::write(&self.subscriptions_path, &all)?;
+        Ok(true)
+    }
+
+    /// Enabled subscriptions to `flow_id`.
+    pub fn active(&self, flow_id: &str) -> Vec<Subscription> {
+        self.list(flow_id).into_iter().filter(|s| s.enabled).collect()
+    }
+
+    /// Queue `run`'s output for every enabled subscriber of `flow`. Returns
+    /// how many deliveries were queued. Flows with a `personalized-digest`
+    /// node are skipped: that node already queued each subscriber's variant.
+    pub fn enqueue_run(&self, flow: &Flow, run: &FlowRun) -> Result<usize> {
+        let Some(text) = run.output.as_deref().filter(|t| !t.trim().is_empty()) else {
+            return Ok(0);
+        };
+        if flow.nodes.iter().any(|n| n.kind == PERSONALIZED_DIGEST_KIND) {
+            return Ok(0);
+        }
+        let variants: Vec<(Subscription, String)> = self
+            .active(&flow.id)
+            .into_iter()
+            .map(|sub| (sub, text.to_string()))
+            .collect();
+        self.enqueue(&flow.name, &run.id, variants)
+    }
+
+    /// Queue one text per subscriber, each due at the subscriber's next
+    /// delivery time. Returns how many deliveries were queued.
+    pub fn enqueue(
+        &self,
+        flow_name: &str,
+        run_id: &str,
+        variants: Vec<(Subscription, String)>,
+    ) -> Result<usize> {
+        let now = Utc::now();
+        let mut pending = Vec::new();
+        for (sub, text) in variants {
+            let due = match sub.next_delivery(now) {
+                Ok(due) => due,
+                Err(e) => {
+                    tracing::warn!(subscription = %sub.id, error = %e, "Skipping misconfigured subscription");
+                    continue;
+                }
+            };
+            pending.push(PendingDelivery {
+                subscription_id: sub.id,
+                flow_name: flow_name.to_string(),
+                run_id: run_id.to_string(),
+                text,
+                due,
+                attempts: 0,
+            });
+        }
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut outbox: Vec<PendingDelivery> = Self::load(&self.outbox_path);
+        let queued = pending.len();
+        outbox.extend(pending);
+        Self::write(&self.outbox_path, &outbox)?;
+        Ok(queued)
+    }
+
+    /// The deliveries due at `now`, paired with their subscriptions. They
+    /// stay in the outbox until [`Self::settle`]; ones whose subscription
+    /// was deleted or disabled are dropped.
+    fn due(&self, now: DateTime<Utc>) -> Result<Vec<(Subscription, PendingDelivery)>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut outbox: Vec<PendingDelivery> = Self::load(&self.outbox_path);
+        if !outbox.iter().any(|d| d.due <= now) {
+            return Ok(Vec::new());
+        }
+
+        let subscriptions: Vec<Subscription> = Self::load(&self.subscriptions_path);
+        let mut due = Vec::new();
+        let before = outbox.len();
+        outbox.retain(|d| {
+            if d.due > now {
+                return true;
+            }
+            match subscriptions.iter().find(|s| s.id == d.subscription_id && s.enabled) {
+                Some(sub) => {
+                    due.push((sub.clone(), d.clone()));
+                    true
+                }
+                None => false,
+            }
+        });
+        if outbox.len() != before {
+            Self::write(&self.outbox_path, &outbox)?;
+        }
+        Ok(due)
+    }
+
+    /// Record how delivering `pending` went: delivered ones leave the
+    /// outbox, failed ones are retried with backoff until `MAX_ATTEMPTS`.
+    fn settle(&self, pending: &PendingDelivery, delivered: bool, now: DateTime<Utc>) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut outbox: Vec<PendingDelivery> = Self::load(&self.outbox_path);
+        let Some(index) = outbox.iter().position(|d| d.is(pending)) else {
+            return Ok(());
+        };
+        let attempts = outbox[index].attempts + 1;
+        if delivered || attempts >= MAX_ATTEMPTS {
+            outbox.remove(index);
+        } else {
+            outbox[index].attempts = attempts;
+            outbox[index].due = now + RETRY_BACKOFF * 2i32.pow(attempts - 1);
+        }
+        Self::write(&self.outbox_path, &outbox)
+    }
+
+    /// Deliver everything due now. A failed delivery stays queued and is
+    /// retried with backoff, up to `MAX_ATTEMPTS` times, without holding up
+    /// the others.
+    pub async fn deliver_due(&self, http_client: &Arc<reqwest::Client>) {
+        let due = match self.due(Utc::now()) {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to read subscription outbox");
+                return;
+            }
+        };
+        for (sub, pending) in due {
+            let config = sub.delivery.sink_config(&pending.flow_name);
+            let result = match resolve_sinks(std::slice::from_ref(&config), http_client) {
+                Ok(sinks) => match sinks.first() {
+                    Some(sink) => sink.deliver(&pending.text).await,
+                    None => Ok(()),
+                },
+                Err(e) => Err(e),
+            };
+            match &result {
+                Ok(()) => tracing::info!(
+                    subscription = %sub.id,
+                    subscriber = %sub.subscriber,
+                    run_id = %pending.run_id,
+                    "Delivered subscription"
+                ),
+                Err(e) => tracing::error!(
+                    subscription = %sub.id,
+                    subscriber = %sub.subscriber,
+                    run_id = %pending.run_id,
+                    attempt = pending.attempts + 1,
+                    error = %e,
+                    "Subscription delivery failed"
+                ),
+            }
+            if let Err(e) = self.settle(&pending, result.is_ok(), Utc::now()) {
+                tracing::error!(subscription = %sub.id, error = %e, "Failed to update subscription outbox");
+            }
+        }
+    }
+}
+
+/// Deliver time-shifted subscription output as it comes due.
+pub async fn delivery_loop(store: SubscriptionStore, http_client: Arc<reqwest::Client>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        store.deliver_due(&http_client).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::RunStatus;
+    use tempfile::tempdir;
+
+    fn subscription(id: &str, deliver_at: Option<&str>, timezone: Option<&str>) -> Subscription {
+        Subscription {
+            id: id.to_string(),
+            flow_id: "flow-1".to_string(),
+            subscriber: "alice".to_string(),
+            delivery: Delivery::SlackDm { user_email: "alice@example.com".to_string() },
+            deliver_at: deliver_at.map(String::from),
+            timezone: timezone.map(String::from),
+            interests: vec![],
+            enabled: true,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_next_delivery() {
+        let now = at("2025-06-02T10:00:00Z");
+        assert_eq!(subscription("s", None, None).next_delivery(now).unwrap(), now);
+        // 08:00 in London (BST) already passed today, so tomorrow 07:00 UTC.
+        assert_eq!(
+            subscription("s", Some("08:00"), Some("Europe/London")).next_delivery(now).unwrap(),
+            at("2025-06-03T07:00:00Z")
+        );
+        assert_eq!(
+            subscription("s", Some("18:30"), None).next_delivery(now).unwrap(),
+            at("2025-06-02T18:30:00Z")
+        );
+        assert!(subscription("s", Some("8am"), None).validate().is_err());
+        assert!(subscription("s", Some("08:00"), Some("Nowhere/Land")).validate().is_err());
+    }
+
+    #[test]
+    fn test_delivery_deserializes_by_kind() {
+        let delivery: Delivery = serde_json::from_str(r#"{"kind":"email","to":"bob@example.com"}"#).unwrap();
+        assert_eq!(delivery, Delivery::Email { to: "bob@example.com".to_string() });
+    }
+
+    #[test]
+    fn test_enqueue_and_settle() {
+        let dir = tempdir().unwrap();
+        let store = SubscriptionStore::new(dir.path());
+        store.save(subscription("now", None, None)).unwrap();
+        store.save(subscription("later", Some("23:59"), Some("Pacific/Kiritimati"))).unwrap();
+        let mut paused = subscription("paused", None, None);
+        paused.enabled = false;
+        store.save(paused).unwrap();
+        assert_eq!(store.list("flow-1").len(), 3);
+
+        let flow: Flow = serde_json::from_value(serde_json::json!({
+            "id": "flow-1", "name": "Brief", "nodes": [], "edges": [],
+            "created_at": "2025-01-01T00:00:00Z", "updated_at": "2025-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        let run = FlowRun {
+            id: "run-1".to_string(),
+            flow_id: "flow-1".to_string(),
+            status: RunStatus::Success,
+            started_at: Utc::now(),
+            finished_at: None,
+            node_runs: vec![],
+            error: None,
+            output: Some("## Brief\nhello".to_string()),
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
+        };
+        assert_eq!(store.enqueue_run(&flow, &run).unwrap(), 2);
+
+        let now = Utc::now();
+        let due = store.due(now).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0.id, "now");
+        assert_eq!(due[0].1.text, "## Brief\nhello");
+
+        // Still queued until it's delivered
+        assert_eq!(store.due(now).unwrap().len(), 1);
+        store.settle(&due[0].1, true, now).unwrap();
+        assert!(store.due(now).unwrap().is_empty());
+
+        assert!(store.delete("flow-1", "later").unwrap());
+        assert!(store.due(now + chrono::Duration::days(2)).unwrap().is_empty());
+        let outbox: Vec<PendingDelivery> = SubscriptionStore::load(&store.outbox_path);
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn test_failed_delivery_is_retried_with_backoff() {
+        let dir = tempdir().unwrap();
+        let store = SubscriptionStore::new(dir.path());
+        let sub = subscription("now", None, None);
+        store.save(sub.clone()).unwrap();
+        store.enqueue("Brief", "run-1", vec![(sub, "hello".to_string())]).unwrap();
+
+        let mut now = Utc::now();
+        for attempt in 1..MAX_ATTEMPTS {
+            let due = store.due(now).unwrap();
+            assert_eq!(due.len(), 1);
+            assert_eq!(due[0].1.attempts, attempt - 1);
+            store.settle(&due[0].1, false, now).unwrap();
+            assert!(store.due(now).unwrap().is_empty());
+            now += RETRY_BACKOFF * 2i32.pow(attempt - 1);
+        }
+        let due = store.due(now).unwrap();
+        store.settle(&due[0].1, false, now).unwrap();
+        assert!(store.due(now + chrono::Duration::days(1)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::flows::Flow;
+use crate::flows::history::{FlowRun, RunStatus};
+
+/// A run laid out Gantt-style for `GET /api/runs/{id}/timeline`: every
+/// node's span and each parallel level's, in milliseconds from the run's
+/// start, so the UI can show where the time went.
+#[derive(Debug, Clone, Serialize)]
+pub struct Timeline {
+    pub run_id: String,
+    pub flow_id: String,
+    pub status: RunStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: i64,
+    pub levels: Vec<LevelSpan>,
+    pub nodes: Vec<NodeSpan>,
+}
+
+/// The nodes of one level, which ran side by side, and from when the first
+/// started to when the last finished.
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelSpan {
+    pub level: usize,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub node_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeSpan {
+    pub node_id: String,
+    pub label: String,
+    pub kind: String,
+    /// `None` for runs recorded before levels were.
+    pub level: Option<usize>,
+    pub status: RunStatus,
+    pub start_ms: i64,
+    /// `now` for a node still running.
+    pub end_ms: i64,
+    pub duration_ms: i64,
+    /// From when its inputs were ready to when it started: time spent
+    /// waiting for a free slot.
+    pub queue_ms: i64,
+    pub cost_usd: Option<f64>,
+}
+
+/// `run`'s timeline; spans still open end at `now`.
+pub fn timeline(flow: &Flow, run: &FlowRun, now: DateTime<Utc>) -> Timeline {
+    let offset = |at: DateTime<Utc>| (at - run.started_at).num_milliseconds().max(0);
+    let run_end = run.finished_at.unwrap_or(now);
+
+    let nodes: Vec<NodeSpan> = run
+        .node_runs
+        .iter()
+        .map(|node_run| {
+            let node = flow.nodes.iter().find(|n| n.id == node_run.node_id);
+            let start_ms = offset(node_run.started_at);
+            let end_ms = offset(node_run.finished_at.unwrap_or(run_end)).max(start_ms);
+            NodeSpan {
+                node_id: node_run.node_id.clone(),
+                label: node.map_or_else(|| node_run.node_id.clone(), |n| n.label.clone()),
+                kind: node.map_or_else(|| "removed".to_string(), |n| n.kind.clone()),
+                level: node_run.level,
+                status: node_run.status,
+                start_ms,
+                end_ms,
+                duration_ms: end_ms - start_ms,
+                queue_ms: node_run
+                    .ready_at
+                    .map_or(0, |ready| (node_run.started_at - ready).num_milliseconds().max(0)),
+                cost_usd: node_run.cost_usd,
+            }
+        })
+        .collect();
+
+    let mut levels: Vec<LevelSpan> = Vec::new();
+    for span in &nodes {
+        let Some(level) = span.level else { continue };
+        match levels.iter_mut().find(|l| l.level == level) {
+            Some(existing) => {
+                existing.start_ms = existing.start_ms.min(span.start_ms);
+                existing.end_ms = existing.end_ms.max(span.end_ms);
+                existing.node_ids.push(span.node_id.clone());
+            }
+            None => levels.push(LevelSpan {
+                level,
+                start_ms: span.start_ms,
+                end_ms: span.end_ms,
+                node_ids: vec![span.node_id.clone()],
+            }),
+        }
+    }
+    levels.sort_by_key(|l| l.level);
+
+    Timeline {
+        run_id: run.id.clone(),
+        flow_id: run.flow_id.clone(),
+        status: run.status,
+        started_at: run.started_at,
+        finished_at: run.finished_at,
+        duration_ms: offset(run_end),
+        levels,
+        nodes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::NodeRun;
+    use chrono::Duration;
+
+    fn node_run(node_id: &str, level: usize, ready: i64, start: i64, end: Option<i64>, t0: DateTime<Utc>) -> NodeRun {
+        NodeRun {
+            node_id: node_id.into(),
+            status: if end.is_some() { RunStatus::Success } else { RunStatus::Running },
+            started_at: t0 + Duration::milliseconds(start),
+            finished_at: end.map(|end| t0 + Duration::milliseconds(end)),
+            output_preview: None,
+            prompt_variant: None,
+            cost_usd: None,
+            attempts: Vec::new(),
+            level: Some(level),
+            ready_at: Some(t0 + Duration::milliseconds(ready)),
+        }
+    }
+
+    #[test]
+    fn test_timeline_spans() {
+        let t0 = Utc::now();
+        let flow: Flow = serde_json::from_value(serde_json::json!({
+            "id": "f",
+            "name": "Digest",
+            "description": "",
+            "enabled": true,
+            "nodes": [],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        let run = FlowRun {
+            id: "r".into(),
+            flow_id: "f".into(),
+            status: RunStatus::Running,
+            started_at: t0,
+            finished_at: None,
+            node_runs: vec![
+                node_run("hn", 1, 0, 5, Some(1200), t0),
+                node_run("rss", 1, 0, 10, Some(400), t0),
+                // Queued 300ms behind the slowest source
+                node_run("summarize", 2, 1200, 1500, None, t0),
+            ],
+            error: None,
+            output: None,
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
+        };
+
+        let timeline = timeline(&flow, &run, t0 + Duration::milliseconds(2000));
+        assert_eq!(timeline.duration_ms, 2000);
+        assert_eq!(timeline.levels.len(), 2);
+        assert_eq!((timeline.levels[0].start_ms, timeline.levels[0].end_ms), (5, 1200));
+        assert_eq!(timeline.levels[0].node_ids, ["hn", "rss"]);
+        let summarize = &timeline.nodes[2];
+        assert_eq!(summarize.label, "summarize");
+        assert_eq!(summarize.queue_ms, 300);
+        assert_eq!((summarize.end_ms, summarize.duration_ms), (2000, 500));
+    }
+}
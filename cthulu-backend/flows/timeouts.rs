@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::flows::{Flow, Node, NodeType};
+
+/// What a run does when a node runs out of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnTimeout {
+    /// Fail the node (and skip what depends on it); independent branches
+    /// carry on.
+    Continue,
+    /// Fail the node and stop the run, killing whatever is still running.
+    Abort,
+}
+
+/// A run's time limits, from its trigger: `timeout_secs` for the whole run,
+/// `node_timeout_secs` as every node's default and `on_timeout`. Nodes may
+/// set their own `timeout_secs` and `on_timeout`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunLimits {
+    deadline: Option<Instant>,
+    run_timeout: Option<Duration>,
+    node_timeout: Option<Duration>,
+    on_timeout: OnTimeout,
+}
+
+/// How long one node may run, and what happens if it doesn't finish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeLimit {
+    pub timeout: Duration,
+    /// The limit is what's left of the run's, not the node's own.
+    pub run_deadline: bool,
+    pub on_timeout: OnTimeout,
+}
+
+impl RunLimits {
+    /// The limits of a run of `flow` starting at `started`.
+    pub fn for_flow(flow: &Flow, started: Instant) -> Result<Self> {
+        match flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) {
+            Some(trigger) => Self::from_trigger(&trigger.config, started),
+            None => Self::from_trigger(&Value::Null, started),
+        }
+    }
+
+    pub fn from_trigger(config: &Value, started: Instant) -> Result<Self> {
+        let run_timeout = secs(config, "timeout_secs");
+        Ok(Self {
+            deadline: run_timeout.map(|t| started + t),
+            run_timeout,
+            node_timeout: secs(config, "node_timeout_secs"),
+            on_timeout: parse_on_timeout(config)?.unwrap_or(OnTimeout::Continue),
+        })
+    }
+
+    /// The run's own limit, once it has passed `now`.
+    pub fn expired(&self, now: Instant) -> Option<Duration> {
+        self.deadline
+            .filter(|deadline| now >= *deadline)
+            .and(self.run_timeout)
+    }
+
+    /// The limit for `node` starting at `now`: the shorter of its own
+    /// (or the flow's default) and what's left of the run. Running out of
+    /// run always aborts.
+    pub fn for_node(&self, node: &Node, now: Instant) -> Result<Option<NodeLimit>> {
+        let own = NodeLimit {
+            timeout: Duration::ZERO,
+            run_deadline: false,
+            on_timeout: parse_on_timeout(&node.config)?.unwrap_or(self.on_timeout),
+        };
+        let remaining = self.deadline.map(|deadline| NodeLimit {
+            timeout: deadline.saturating_duration_since(now),
+            run_deadline: true,
+            on_timeout: OnTimeout::Abort,
+        });
+        let own = secs(&node.config, "timeout_secs")
+            .or(self.node_timeout)
+            .map(|timeout| NodeLimit { timeout, ..own });
+
+        Ok(match (own, remaining) {
+            (Some(own), Some(remaining)) if remaining.timeout < own.timeout => Some(remaining),
+            (Some(own), _) => Some(own),
+            (None, remaining) => remaining,
+        })
+    }
+}
+
+/// The error of a node stopped for running out of time, so the run can
+/// tell it from the node failing by itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut(pub Duration);
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "timed out after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+fn secs(config: &Value, key: &str) -> Option<Duration> {
+    config[key]
+        .as_u64()
+        .filter(|s| *s > 0)
+        .map(Duration::from_secs)
+}
+
+fn parse_on_timeout(config: &Value) -> Result<Option<OnTimeout>> {
+    match config["on_timeout"].as_str() {
+        None => Ok(None),
+        Some("continue") => Ok(Some(OnTimeout::Continue)),
+        Some("abort") => Ok(Some(OnTimeout::Abort)),
+        Some(other) => anyhow::bail!("on_timeout must be \"continue\" or \"abort\", got '{other}'"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::Position;
+    use serde_json::json;
+
+    fn node(node_type: NodeType, config: Value) -> Node {
+        Node {
+            id: "n".to_string(),
+            node_type,
+            kind: "claude-code".to_string(),
+            config,
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Node".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_no_limits_by_default() {
+        let now = Instant::now();
+        let limits = RunLimits::from_trigger(&json!({}), now).unwrap();
+        assert_eq!(limits.for_node(&node(NodeType::Executor, json!({})), now).unwrap(), None);
+        assert_eq!(limits.expired(now + Duration::from_secs(86_400)), None);
+    }
+
+    #[test]
+    fn test_node_limit_is_the_tighter_of_node_and_run() {
+        let start = Instant::now();
+        let trigger = json!({ "timeout_secs": 600, "node_timeout_secs": 300, "on_timeout": "abort" });
+        let limits = RunLimits::from_trigger(&trigger, start).unwrap();
+
+        let default = limits.for_node(&node(NodeType::Sink, json!({})), start).unwrap().unwrap();
+        assert_eq!(default.timeout, Duration::from_secs(300));
+        assert!(!default.run_deadline);
+        assert_eq!(default.on_timeout, OnTimeout::Abort);
+
+        let own = node(NodeType::Executor, json!({ "timeout_secs": 60, "on_timeout": "continue" }));
+        let limit = limits.for_node(&own, start).unwrap().unwrap();
+        assert_eq!(limit.timeout, Duration::from_secs(60));
+        assert_eq!(limit.on_timeout, OnTimeout::Continue);
+
+        // Late in the run, what's left of it wins and always aborts
+        let late = start + Duration::from_secs(580);
+        let limit = limits.for_node(&own, late).unwrap().unwrap();
+        assert_eq!(limit.timeout, Duration::from_secs(20));
+        assert!(limit.run_deadline);
+        assert_eq!(limit.on_timeout, OnTimeout::Abort);
+
+        assert_eq!(limits.expired(late), None);
+        assert_eq!(limits.expired(start + Duration::from_secs(600)), Some(Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_invalid_on_timeout() {
+        assert!(RunLimits::from_trigger(&json!({ "on_timeout": "retry" }), Instant::now()).is_err());
+    }
+
+    #[test]
+    fn test_timed_out_is_told_apart() {
+        let err: anyhow::Error = TimedOut(Duration::from_secs(30)).into();
+        assert_eq!(format!("{err:#}"), "timed out after 30s");
+        assert!(err.downcast_ref::<TimedOut>().is_some());
+        assert!(anyhow::anyhow!("timed out after 30s").downcast_ref::<TimedOut>().is_none());
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::json_file;
+
+/// Prefix of every trigger token secret, so a leaked one is recognizable.
+const SECRET_PREFIX: &str = "ctht_";
+
+/// Serializes read-modify-write of the token file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A single-purpose token that can only trigger one flow, for embedding in
+/// Shortcuts/IFTTT/Zapier webhooks. Only the secret's hash is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerToken {
+    pub id: String,
+    pub flow_id: String,
+    /// What it's for, e.g. "iPhone shortcut".
+    pub name: String,
+    /// The secret's first characters, to tell tokens apart.
+    pub hint: String,
+    /// SHA-256 of the secret; never returned by the API.
+    sha256: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl TriggerToken {
+    /// The token as the API shows it: everything but the hash.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "flow_id": self.flow_id,
+            "name": self.name,
+            "hint": self.hint,
+            "created_at": self.created_at,
+            "last_used_at": self.last_used_at,
+        })
+    }
+}
+
+/// File-backed trigger tokens (`~/.cthulu/trigger-tokens.json`).
+#[derive(Clone)]
+pub struct TokenStore {
+    path: PathBuf,
+}
+
+impl TokenStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("trigger-tokens.json") }
+    }
+
+    fn load(&self) -> Vec<TriggerToken> {
+        json_file::load_or_default(&self.path)
+    }
+
+    fn write(&self, tokens: Vec<TriggerToken>) -> Result<()> {
+        json_file::write(&self.path, &tokens)
+    }
+
+    pub fn list(&self, flow_id: &str) -> Vec<TriggerToken> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        self.load().into_iter().filter(|t| t.flow_id == flow_id).collect()
+    }
+
+    /// Mint a token for `flow_id`. The secret is returned only here.
+    pub fn create(&self, flow_id: &str, name: &str) -> Result<(TriggerToken, String)> {
+        let secret = format!(
+            "{SECRET_PREFIX}{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        let token = TriggerToken {
+            id: uuid::Uuid::new_v4().to_string(),
+            flow_id: flow_id.to_string(),
+            name: name.to_string(),
+            hint: secret[..SECRET_PREFIX.len() + 4].to_string(),
+            sha256: hash(&secret),
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        all.push(token.clone());
+        self.write(all)?;
+        Ok((token, secret))
+    }
+
+    pub fn delete(&self, flow_id: &str, id: &str) -> Result<bool> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let before = all.len();
+        all.retain(|t| !(t.flow_id == flow_id && t.id == id));
+        if all.len() == before {
+            return Ok(false);
+        }
+        self.write(all)?;
+        Ok(true)
+    }
+
+    /// The token `secret` belongs to, stamping its last use.
+    pub fn redeem(&self, secret: &str) -> Option<TriggerToken> {
+        if !secret.starts_with(SECRET_PREFIX) {
+            return None;
+        }
+        let sha256 = hash(secret);
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let token = all.iter_mut().find(|t| t.sha256 == sha256)?;
+        token.last_used_at = Some(Utc::now());
+        let token = token.clone();
+        if let Err(e) = self.write(all) {
+            tracing::warn!(token = %token.id, error = %e, "Failed to record trigger token use");
+        }
+        Some(token)
+    }
+
+    /// Drop every token of a deleted flow.
+    pub fn delete_flow(&self, flow_id: &str) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let before = all.len();
+        all.retain(|t| t.flow_id != flow_id);
+        if all.len() != before {
+            self.write(all)?;
+        }
+        Ok(())
+    }
+}
+
+fn hash(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_only_redeem_their_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TokenStore::new(dir.path());
+        let (token, secret) = store.create("flow-1", "iPhone shortcut").unwrap();
+        assert!(secret.starts_with("ctht_"));
+        assert_eq!(token.hint, &secret[..9]);
+
+        // The secret itself is never written, nor listed
+        let file = std::fs::read_to_string(dir.path().join("trigger-tokens.json")).unwrap();
+        assert!(!file.contains(&secret));
+        assert!(store.list("flow-1")[0].to_json().get("sha256").is_none());
+
+        let redeemed = store.redeem(&secret).unwrap();
+        assert_eq!(redeemed.flow_id, "flow-1");
+        assert!(store.list("flow-1")[0].last_used_at.is_some());
+        assert!(store.redeem("ctht_wrong").is_none());
+        assert!(store.redeem(&secret[5..]).is_none());
+
+        assert!(!store.delete("flow-2", &token.id).unwrap());
+        assert!(store.delete("flow-1", &token.id).unwrap());
+        assert!(store.redeem(&secret).is_none());
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use crate::flows::Node;
+use crate::flows::graph::NodeOutput;
+use crate::flows::processors::item_vars;
+use crate::tasks::context::render_prompt;
+use crate::tasks::pipeline::format_items;
+
+/// Executor kind that reshapes its input without an LLM: pull a field out
+/// of JSON, rename context keys, cap and shorten items, or render a
+/// template. The steps that are configured run in that order.
+pub const TRANSFORM_KIND: &str = "transform";
+
+/// A transform node's config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transform {
+    /// Path into the input parsed as JSON, e.g. `.data.items[].title`.
+    pub extract: Option<Path>,
+    /// Context keys to rename, old to new.
+    pub rename: HashMap<String, String>,
+    /// Items to keep, from the first.
+    pub limit: Option<usize>,
+    /// Characters to cut item summaries (or text) to.
+    pub truncate: Option<usize>,
+    /// Rendered per item, the results one per line.
+    pub item_template: Option<String>,
+    /// Rendered once, with `{{content}}` the input so far.
+    pub template: Option<String>,
+}
+
+/// A jq-style path: `.field`, `[n]` and `[]` (every element) steps, with
+/// an optional leading `$` as in JSONPath.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path(Vec<Step>);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Field(String),
+    Index(usize),
+    Each,
+}
+
+impl Path {
+    pub fn parse(path: &str) -> Result<Self> {
+        let mut steps = Vec::new();
+        let mut rest = path.trim().strip_prefix('$').unwrap_or(path.trim());
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('[') {
+                let (inner, after) = after.split_once(']').with_context(|| format!("unclosed [ in path '{path}'"))?;
+                steps.push(match inner.trim() {
+                    "" | "*" => Step::Each,
+                    index => Step::Index(index.parse().with_context(|| format!("invalid index [{index}] in path '{path}'"))?),
+                });
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                if end > 0 {
+                    steps.push(Step::Field(after[..end].to_string()));
+                }
+                rest = &after[end..];
+            } else {
+                bail!("invalid path '{path}' (expected steps like .field, [0] or [])");
+            }
+        }
+        Ok(Self(steps))
+    }
+
+    /// The values at this path in `value`; `[]` steps fan out, and missing
+    /// fields give nothing.
+    fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![value];
+        for step in &self.0 {
+            current = current
+                .into_iter()
+                .flat_map(|value| match step {
+                    Step::Field(name) => value.get(name).into_iter().collect::<Vec<_>>(),
+                    Step::Index(i) => value.get(i).into_iter().collect(),
+                    Step::Each => match value {
+                        Value::Array(values) => values.iter().collect(),
+                        Value::Object(map) => map.values().collect(),
+                        _ => Vec::new(),
+                    },
+                })
+                .collect();
+        }
+        current
+    }
+}
+
+impl Transform {
+    pub fn for_node(node: &Node) -> Result<Self> {
+        let config = &node.config;
+        let count = |key: &str| -> Result<Option<usize>> {
+            match &config[key] {
+                Value::Null => Ok(None),
+                value => Ok(Some(value.as_u64().with_context(|| format!("{key} must be a whole number"))? as usize)),
+            }
+        };
+        let rename = match &config["rename"] {
+            Value::Null => HashMap::new(),
+            Value::Object(map) => map
+                .iter()
+                .map(|(from, to)| {
+                    let to = to.as_str().with_context(|| format!("rename of '{from}' must be a string"))?;
+                    Ok((from.clone(), to.to_string()))
+                })
+                .collect::<Result<_>>()?,
+            _ => bail!("rename must map old context keys to new ones"),
+        };
+        let transform = Self {
+            extract: config["extract"].as_str().map(Path::parse).transpose()?,
+            rename,
+            limit: count("limit")?,
+            truncate: count("truncate")?,
+            item_template: config["item_template"].as_str().map(String::from),
+            template: config["template"].as_str().map(String::from),
+        };
+        if transform == Self::default() {
+            bail!("transform node needs at least one of extract, rename, limit, truncate, item_template or template");
+        }
+        Ok(transform)
+    }
+
+    /// Reshape `input`; `vars` are the run's trigger variables, for templates.
+    pub fn apply(&self, input: NodeOutput, vars: &HashMap<String, String>) -> Result<NodeOutput> {
+        let mut output = input;
+
+        if let Some(path) = &self.extract {
+            let json: Value = match &output {
+                NodeOutput::Context(map) => serde_json::to_value(map)?,
+                other => serde_json::from_str(other.as_text().trim()).context("extract needs JSON input")?,
+            };
+            let lines: Vec<String> = path.select(&json).into_iter().map(plain).collect();
+            output = NodeOutput::Text(lines.join("\n"), None);
+        }
+
+        if !self.rename.is_empty() {
+            let NodeOutput::Context(map) = output else {
+                bail!("rename needs context input");
+            };
+            output = NodeOutput::Context(
+                map.into_iter()
+                    .map(|(key, value)| (self.rename.get(&key).cloned().unwrap_or(key), value))
+                    .collect(),
+            );
+        }
+
+        if let NodeOutput::Items(items) = &mut output {
+            if let Some(limit) = self.limit {
+                items.truncate(limit);
+            }
+            if let Some(max) = self.truncate {
+                for item in items.iter_mut() {
+                    item.summary = shorten(&item.summary, max);
+                    item.full_summary = true;
+                }
+            }
+        } else if let (Some(max), NodeOutput::Text(text, _)) = (self.truncate, &output) {
+            output = NodeOutput::Text(shorten(text, max), None);
+        }
+
+        if let Some(template) = &self.item_template {
+            let items = output.as_items();
+            let lines: Vec<String> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let mut template_vars = vars.clone();
+                    template_vars.extend(item_vars(i, item));
+                    render_prompt(template, &template_vars)
+                })
+                .collect();
+            output = NodeOutput::Text(lines.join("\n"), None);
+        }
+
+        if let Some(template) = &self.template {
+            let mut template_vars = vars.clone();
+            match &output {
+                NodeOutput::Context(map) => template_vars.extend(map.clone()),
+                NodeOutput::Items(items) => {
+                    template_vars.insert("content".to_string(), format_items(items));
+                    template_vars.insert("item_count".to_string(), items.len().to_string());
+                }
+                other => {
+                    template_vars.insert("content".to_string(), other.as_text());
+                }
+            }
+            output = NodeOutput::Text(render_prompt(template, &template_vars), None);
+        }
+
+        // Upstream executors' cost is theirs, not counted again here
+        if let NodeOutput::Text(text, Some(_)) = output {
+            output = NodeOutput::Text(text, None);
+        }
+        Ok(output)
+    }
+}
+
+/// A JSON value as text: strings without quotes, anything else as JSON.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// `text` cut to at most `max` characters, ending in "…" when cut.
+fn shorten(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::NodeType;
+    use crate::tasks::sources::ContentItem;
+    use serde_json::json;
+
+    fn transform(config: Value) -> Result<Transform> {
+        Transform::for_node(&Node {
+            id: "x".into(),
+            node_type: NodeType::Executor,
+            kind: TRANSFORM_KIND.into(),
+            config,
+            position: crate::flows::Position { x: 0.0, y: 0.0 },
+            label: "Reshape".into(),
+        })
+    }
+
+    fn item(title: &str, summary: &str) -> ContentItem {
+        ContentItem {
+            title: title.into(),
+            url: format!("https://example.com/{title}"),
+            summary: summary.into(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        }
+    }
+
+    #[test]
+    fn test_transform_config() {
+        assert!(transform(json!({})).is_err());
+        assert!(transform(json!({ "extract": "data" })).is_err());
+        assert!(transform(json!({ "limit": "ten" })).is_err());
+        assert_eq!(
+            Path::parse("$.data.items[0].tags[]").unwrap(),
+            Path(vec![
+                Step::Field("data".into()),
+                Step::Field("items".into()),
+                Step::Index(0),
+                Step::Field("tags".into()),
+                Step::Each,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_extract() {
+        let input = NodeOutput::Text(
+            r#"{"data": {"items": [{"title": "A", "n": 1}, {"title": "B", "n": 2}]}}"#.into(),
+            None,
+        );
+        let vars = HashMap::new();
+        let titles = transform(json!({ "extract": ".data.items[].title" })).unwrap();
+        assert_eq!(titles.apply(input.clone(), &vars).unwrap().as_text(), "A\nB");
+        let first = transform(json!({ "extract": ".data.items[1]" })).unwrap();
+        assert_eq!(first.apply(input, &vars).unwrap().as_text(), r#"{"n":2,"title":"B"}"#);
+
+        let not_json = NodeOutput::Text("plain text".into(), None);
+        assert!(titles.apply(not_json, &vars).is_err());
+    }
+
+    #[test]
+    fn test_rename_and_template() {
+        let input = NodeOutput::Context(HashMap::from([
+            ("pr_title".to_string(), "Fix login".to_string()),
+            ("repo".to_string(), "acme/web".to_string()),
+        ]));
+        let vars = HashMap::new();
+        let renamed = transform(json!({ "rename": { "pr_title": "title" } })).unwrap().apply(input.clone(), &vars).unwrap();
+        assert_eq!(renamed.as_context().unwrap()["title"], "Fix login");
+
+        let both = transform(json!({ "rename": { "pr_title": "title" }, "template": "{{repo}}: {{title}}" })).unwrap();
+        assert_eq!(both.apply(input, &vars).unwrap().as_text(), "acme/web: Fix login");
+    }
+
+    #[test]
+    fn test_items() {
+        let items = NodeOutput::Items(vec![item("a", "first summary"), item("b", "second"), item("c", "third")]);
+        let vars = HashMap::from([("team".to_string(), "infra".to_string())]);
+        let t = transform(json!({
+            "limit": 2,
+            "truncate": 6,
+            "item_template": "{{item_index}}. [{{item_title}}]({{item_url}}) {{item_summary}} ({{team}})"
+        }))
+        .unwrap();
+        assert_eq!(
+            t.apply(items.clone(), &vars).unwrap().as_text(),
+            "1. [a](https://example.com/a) first… (infra)\n2. [b](https://example.com/b) second (infra)"
+        );
+
+        let counted = transform(json!({ "limit": 1, "template": "{{item_count}} new" })).unwrap();
+        assert_eq!(counted.apply(items, &vars).unwrap().as_text(), "1 new");
+    }
+}
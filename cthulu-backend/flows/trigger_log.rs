@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::json_file;
+
+/// Events kept in the log; the oldest finished ones are dropped first.
+const MAX_EVENTS: usize = 5000;
+
+/// Serializes read-modify-write of the log file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Head SHA last seen per PR number, by repo (`owner/repo`).
+pub type SeenPrs = HashMap<String, HashMap<u64, String>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventStatus {
+    /// Detected, but its run hasn't been created yet.
+    Pending,
+    /// Handed to the runner; from here run recovery takes care of it.
+    Started,
+    Done,
+}
+
+/// Something a trigger detected — a new PR head, a command comment — that
+/// should run its flow exactly once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerEvent {
+    /// Idempotency key: the same whenever the same thing is detected again.
+    pub id: String,
+    pub flow_id: String,
+    /// What the event is redone with if the server stops before its run
+    /// starts.
+    pub payload: Value,
+    pub status: EventStatus,
+    pub received_at: DateTime<Utc>,
+}
+
+impl TriggerEvent {
+    pub fn new(flow_id: &str, id: String, payload: Value) -> Self {
+        Self {
+            id,
+            flow_id: flow_id.to_string(),
+            payload,
+            status: EventStatus::Pending,
+            received_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct LogFile {
+    #[serde(default)]
+    events: Vec<TriggerEvent>,
+    #[serde(default)]
+    seen_prs: SeenPrs,
+}
+
+/// File-backed log of trigger events (`~/.cthulu/trigger_events.json`),
+/// written before their runs start. Redelivered or re-detected events are
+/// recognised by id and skipped, and ones the server stopped before running
+/// are redone at the next startup. Also keeps the PR heads the github-pr
+/// trigger has seen, so a restart neither reviews old PRs again nor misses
+/// ones opened while it was down.
+#[derive(Clone)]
+pub struct TriggerLog {
+    path: PathBuf,
+}
+
+/// An event claimed in the log, whose status follows its run.
+pub struct LoggedEvent {
+    log: TriggerLog,
+    pub id: String,
+}
+
+impl LoggedEvent {
+    pub fn set(&self, status: EventStatus) {
+        if let Err(e) = self.log.set_status(&self.id, status) {
+            tracing::warn!(event = %self.id, error = %e, "Failed to update trigger event");
+        }
+    }
+}
+
+impl TriggerLog {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("trigger_events.json") }
+    }
+
+    fn load(&self) -> LogFile {
+        json_file::load_or_default(&self.path)
+    }
+
+    fn write(&self, file: &mut LogFile) -> Result<()> {
+        let excess = file.events.len().saturating_sub(MAX_EVENTS);
+        if excess > 0 {
+            let mut dropped = 0;
+            file.events.retain(|e| {
+                let drop = dropped < excess && e.status == EventStatus::Done;
+                dropped += usize::from(drop);
+                !drop
+            });
+        }
+        json_file::write(&self.path, file)
+    }
+
+    /// Record `event` as pending, or `None` when its id was logged before.
+    pub fn claim(&self, event: TriggerEvent) -> Result<Option<LoggedEvent>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut file = self.load();
+        if file.events.iter().any(|e| e.id == event.id) {
+            return Ok(None);
+        }
+        let id = event.id.clone();
+        file.events.push(event);
+        self.write(&mut file)?;
+        Ok(Some(self.resume(id)))
+    }
+
+    /// A handle on an event already in the log.
+    pub fn resume(&self, id: String) -> LoggedEvent {
+        LoggedEvent { log: self.clone(), id }
+    }
+
+    pub fn set_status(&self, id: &str, status: EventStatus) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut file = self.load();
+        let Some(event) = file.events.iter_mut().find(|e| e.id == id) else {
+            return Ok(());
+        };
+        event.status = status;
+        self.write(&mut file)
+    }
+
+    /// At startup: events whose runs started are done (run recovery handles
+    /// those runs); return the ones that never got a run, to redo.
+    pub fn recover(&self) -> Result<Vec<TriggerEvent>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut file = self.load();
+        let mut changed = false;
+        for event in file.events.iter_mut().filter(|e| e.status == EventStatus::Started) {
+            event.status = EventStatus::Done;
+            changed = true;
+        }
+        if changed {
+            self.write(&mut file)?;
+        }
+        Ok(file
+            .events
+            .into_iter()
+            .filter(|e| e.status == EventStatus::Pending)
+            .collect())
+    }
+
+    pub fn seen_prs(&self) -> SeenPrs {
+        let _guard = FILE_LOCK.lock().unwrap();
+        self.load().seen_prs
+    }
+
+    pub fn save_seen_prs(&self, seen: &SeenPrs) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut file = self.load();
+        file.seen_prs = seen.clone();
+        self.write(&mut file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_claim_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = TriggerLog::new(dir.path());
+
+        let event = log.claim(TriggerEvent::new("f", "pr:1@abc".into(), json!({ "pr": 1 }))).unwrap();
+        assert!(event.is_some());
+        // Redelivered, or detected again after a restart
+        assert!(log.claim(TriggerEvent::new("f", "pr:1@abc".into(), json!({}))).unwrap().is_none());
+        assert!(log.claim(TriggerEvent::new("f", "pr:1@def".into(), json!({}))).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_recover() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = TriggerLog::new(dir.path());
+        let detected = log.claim(TriggerEvent::new("f", "a".into(), json!({ "pr": 1 }))).unwrap().unwrap();
+        let started = log.claim(TriggerEvent::new("f", "b".into(), json!({}))).unwrap().unwrap();
+        started.set(EventStatus::Started);
+        let done = log.claim(TriggerEvent::new("f", "c".into(), json!({}))).unwrap().unwrap();
+        done.set(EventStatus::Done);
+
+        let pending = log.recover().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, detected.id);
+        assert_eq!(pending[0].payload, json!({ "pr": 1 }));
+        assert_eq!(log.load().events[1].status, EventStatus::Done);
+    }
+
+    #[test]
+    fn test_seen_prs_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = TriggerLog::new(dir.path());
+        assert!(log.seen_prs().is_empty());
+
+        let seen = SeenPrs::from([("o/r".to_string(), HashMap::from([(7, "abc".to_string())]))]);
+        log.save_seen_prs(&seen).unwrap();
+        log.claim(TriggerEvent::new("f", "a".into(), json!({}))).unwrap();
+        assert_eq!(TriggerLog::new(dir.path()).seen_prs(), seen);
+    }
+
+    #[test]
+    fn test_log_is_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = TriggerLog::new(dir.path());
+        let mut file = LogFile::default();
+        for i in 0..MAX_EVENTS + 10 {
+            let mut event = TriggerEvent::new("f", i.to_string(), Value::Null);
+            // Unfinished events are never dropped
+            if i != 0 {
+                event.status = EventStatus::Done;
+            }
+            file.events.push(event);
+        }
+        log.write(&mut file).unwrap();
+
+        let events = log.load().events;
+        assert_eq!(events.len(), MAX_EVENTS);
+        assert_eq!(events[0].id, "0");
+        assert_eq!(events[1].id, "11");
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::flows::Node;
+use crate::flows::graph::NodeOutput;
+use crate::flows::scheduler::cron_timezone;
+use crate::json_file;
+
+/// Executor kind that holds its run for `wait_secs`, or until the next
+/// `until` wall-clock time, then passes its input on — for flows like "post
+/// a reminder, wait a day, check again, escalate". Pending waits are saved,
+/// so a restart resumes them rather than interrupting the run.
+pub const WAIT_KIND: &str = "wait";
+
+/// Serializes read-modify-write of the checkpoint file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// How long a wait node holds its run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Wait {
+    For(Duration),
+    /// The next time it's `HH:MM` in the timezone.
+    Until(NaiveTime, Tz),
+    At(DateTime<Utc>),
+}
+
+impl Wait {
+    pub fn for_node(node: &Node) -> Result<Self> {
+        let config = &node.config;
+        match (&config["wait_secs"], config["until"].as_str().map(str::trim)) {
+            (serde_json::Value::Null, Some(until)) => {
+                if let Ok(at) = DateTime::parse_from_rfc3339(until) {
+                    return Ok(Self::At(at.with_timezone(&Utc)));
+                }
+                let time = NaiveTime::parse_from_str(until, "%H:%M")
+                    .with_context(|| format!("invalid until '{until}' (expected HH:MM or an RFC 3339 time)"))?;
+                Ok(Self::Until(time, cron_timezone(config)?))
+            }
+            (secs, None) if !secs.is_null() => {
+                let secs = secs.as_u64().context("wait_secs must be a whole number of seconds")?;
+                Ok(Self::For(Duration::from_secs(secs)))
+            }
+            _ => anyhow::bail!("wait node needs one of wait_secs or until"),
+        }
+    }
+
+    /// When a wait starting at `now` is over.
+    pub fn resume_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::For(duration) => chrono::Duration::from_std(*duration)
+                .ok()
+                .and_then(|duration| now.checked_add_signed(duration))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            Self::At(at) => *at,
+            Self::Until(time, tz) => {
+                let today = now.with_timezone(tz).date_naive();
+                (0..=2)
+                    .filter_map(|days| {
+                        // A time skipped by a DST jump resumes an hour later
+                        let local = (today + Days::new(days)).and_time(*time);
+                        tz.from_local_datetime(&local)
+                            .earliest()
+                            .or_else(|| tz.from_local_datetime(&(local + chrono::Duration::hours(1))).earliest())
+                    })
+                    .map(|at| at.with_timezone(&Utc))
+                    .find(|at| *at > now)
+                    .unwrap_or(now)
+            }
+        }
+    }
+}
+
+/// A run held at a wait node: what it needs to go on from there after a
+/// restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub flow_id: String,
+    pub run_id: String,
+    pub node_id: String,
+    pub resume_at: DateTime<Utc>,
+    /// The run's trigger variables.
+    pub context: HashMap<String, String>,
+    /// Outputs of the nodes that finished before the wait's level.
+    pub outputs: HashMap<String, NodeOutput>,
+    /// Outcomes of the branches among them.
+    pub outcomes: HashMap<String, bool>,
+}
+
+/// Where a resumed run picks up: the finished nodes' outputs, and when each
+/// of its pending waits is over.
+#[derive(Debug, Default)]
+pub struct Resume {
+    pub outputs: HashMap<String, NodeOutput>,
+    pub outcomes: HashMap<String, bool>,
+    pub resume_at: HashMap<String, DateTime<Utc>>,
+}
+
+impl Resume {
+    /// The run's context and resume point from its checkpoints (one per
+    /// wait in the level it stopped at).
+    pub fn from_checkpoints(checkpoints: Vec<Checkpoint>) -> (HashMap<String, String>, Self) {
+        let mut context = HashMap::new();
+        let mut resume = Self::default();
+        for checkpoint in checkpoints {
+            context = checkpoint.context;
+            resume.outputs.extend(checkpoint.outputs);
+            resume.outcomes.extend(checkpoint.outcomes);
+            resume.resume_at.insert(checkpoint.node_id, checkpoint.resume_at);
+        }
+        (context, resume)
+    }
+}
+
+/// Checkpoints of pending waits (`~/.cthulu/state/waits.json`).
+#[derive(Clone)]
+pub struct WaitStore {
+    path: PathBuf,
+}
+
+impl WaitStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("state").join("waits.json") }
+    }
+
+    fn load(&self) -> Vec<Checkpoint> {
+        json_file::load_or_default(&self.path)
+    }
+
+    fn write(&self, checkpoints: &[Checkpoint]) -> Result<()> {
+        json_file::write(&self.path, checkpoints)
+    }
+
+    pub fn list(&self) -> Vec<Checkpoint> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        self.load()
+    }
+
+    /// Insert or replace (by run and node).
+    pub fn save(&self, checkpoint: Checkpoint) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        all.retain(|c| !(c.run_id == checkpoint.run_id && c.node_id == checkpoint.node_id));
+        all.push(checkpoint);
+        self.write(&all)
+    }
+
+    pub fn remove(&self, run_id: &str, node_id: &str) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load();
+        let before = all.len();
+        all.retain(|c| !(c.run_id == run_id && c.node_id == node_id));
+        if all.len() == before {
+            return Ok(());
+        }
+        self.write(&all)
+    }
+
+    /// Remove and return every checkpoint of `run_id`.
+    pub fn take_run(&self, run_id: &str) -> Result<Vec<Checkpoint>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let (taken, kept): (Vec<_>, Vec<_>) = self.load().into_iter().partition(|c| c.run_id == run_id);
+        if !taken.is_empty() {
+            self.write(&kept)?;
+        }
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::NodeType;
+    use serde_json::json;
+
+    fn node(config: serde_json::Value) -> Node {
+        Node {
+            id: "w".into(),
+            node_type: NodeType::Executor,
+            kind: WAIT_KIND.into(),
+            config,
+            position: crate::flows::Position { x: 0.0, y: 0.0 },
+            label: "Wait a day".into(),
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_wait_config() {
+        let now = at("2026-03-10T12:00:00Z");
+        let wait = Wait::for_node(&node(json!({ "wait_secs": 86400 }))).unwrap();
+        assert_eq!(wait.resume_at(now), at("2026-03-11T12:00:00Z"));
+
+        // Already past 09:00 in London, so tomorrow's
+        let wait = Wait::for_node(&node(json!({ "until": "09:00", "timezone": "Europe/London" }))).unwrap();
+        assert_eq!(wait.resume_at(now), at("2026-03-11T09:00:00Z"));
+        let wait = Wait::for_node(&node(json!({ "until": "18:30" }))).unwrap();
+        assert_eq!(wait.resume_at(now), at("2026-03-10T18:30:00Z"));
+
+        let wait = Wait::for_node(&node(json!({ "until": "2026-04-01T08:00:00+02:00" }))).unwrap();
+        assert_eq!(wait.resume_at(now), at("2026-04-01T06:00:00Z"));
+
+        assert!(Wait::for_node(&node(json!({}))).is_err());
+        assert!(Wait::for_node(&node(json!({ "wait_secs": 60, "until": "09:00" }))).is_err());
+        assert!(Wait::for_node(&node(json!({ "until": "9am" }))).is_err());
+    }
+
+    #[test]
+    fn test_checkpoints_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = WaitStore::new(dir.path());
+        let checkpoint = |run_id: &str, node_id: &str| Checkpoint {
+            flow_id: "f".into(),
+            run_id: run_id.into(),
+            node_id: node_id.into(),
+            resume_at: at("2026-03-11T12:00:00Z"),
+            context: HashMap::from([("team".to_string(), "infra".to_string())]),
+            outputs: HashMap::from([("post".to_string(), NodeOutput::Text("Reminder sent".into(), None))]),
+            outcomes: HashMap::new(),
+        };
+        store.save(checkpoint("r1", "w1")).unwrap();
+        store.save(checkpoint("r1", "w2")).unwrap();
+        store.save(checkpoint("r2", "w1")).unwrap();
+        store.remove("r2", "w1").unwrap();
+
+        let taken = WaitStore::new(dir.path()).take_run("r1").unwrap();
+        let (context, resume) = Resume::from_checkpoints(taken);
+        assert_eq!(context["team"], "infra");
+        assert_eq!(resume.resume_at.len(), 2);
+        assert_eq!(resume.outputs["post"].as_text(), "Reminder sent");
+        assert!(store.list().is_empty());
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Days, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::flows::{Flow, NodeType};
+
+const ALL_DAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// What happens to an event that arrives while the window is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutsideWindow {
+    Drop,
+    /// Hold it and run when the window next opens.
+    Queue,
+}
+
+/// A trigger's `active_window`: the days and local time range it may start
+/// runs in.
+///
+/// `start` to `end` wraps midnight when `end` is not after `start` (e.g.
+/// 22:00–06:00 counts the early hours towards the day the window opened);
+/// `start == end` means the whole day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveWindow {
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub timezone: Tz,
+    pub outside: OutsideWindow,
+}
+
+/// Whether an event arriving now may run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gate {
+    Open,
+    Drop,
+    /// Closed; run at this time instead.
+    Queue(DateTime<Utc>),
+}
+
+impl ActiveWindow {
+    /// The window on `flow`'s trigger, or `None` when it has none.
+    pub fn for_flow(flow: &Flow) -> Result<Option<Self>> {
+        match flow.nodes.iter().find(|n| n.node_type == NodeType::Trigger) {
+            Some(trigger) => Self::from_trigger(&trigger.config),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse `active_window` from a trigger config. `timezone` falls back to
+    /// the trigger's own (cron triggers), then UTC.
+    pub fn from_trigger(config: &Value) -> Result<Option<Self>> {
+        let window = &config["active_window"];
+        if window.is_null() {
+            return Ok(None);
+        }
+
+        let days = match window["days"].as_array() {
+            None => ALL_DAYS.to_vec(),
+            Some(entries) => {
+                let mut days = Vec::new();
+                for entry in entries {
+                    let entry = entry.as_str().context("active_window.days entries must be strings")?;
+                    for day in parse_days(entry)? {
+                        if !days.contains(&day) {
+                            days.push(day);
+                        }
+                    }
+                }
+                days
+            }
+        };
+        if days.is_empty() {
+            anyhow::bail!("active_window.days must name at least one day");
+        }
+
+        let start = parse_time(window["start"].as_str().unwrap_or("00:00"))?;
+        let end = parse_time(window["end"].as_str().unwrap_or("00:00"))?;
+
+        let timezone = match window["timezone"]
+            .as_str()
+            .or(config["timezone"].as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            None => Tz::UTC,
+            Some(name) => name
+                .parse()
+                .map_err(|_| anyhow::anyhow!("unknown timezone '{name}' (expected an IANA name like 'Europe/London')"))?,
+        };
+
+        let outside = match window["outside"].as_str().unwrap_or("drop") {
+            "drop" => OutsideWindow::Drop,
+            "queue" => OutsideWindow::Queue,
+            other => anyhow::bail!("active_window.outside must be \"drop\" or \"queue\", got '{other}'"),
+        };
+
+        Ok(Some(Self { days, start, end, timezone, outside }))
+    }
+
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&self.timezone);
+        let (day, time) = (local.weekday(), local.time());
+        if self.start == self.end {
+            self.days.contains(&day)
+        } else if self.start < self.end {
+            self.days.contains(&day) && time >= self.start && time < self.end
+        } else {
+            (self.days.contains(&day) && time >= self.start)
+                || (self.days.contains(&day.pred()) && time < self.end)
+        }
+    }
+
+    /// `after` if the window is open then, else when it next opens.
+    pub fn next_open(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        if self.is_open(after) {
+            return after;
+        }
+        let opens_at = if self.start == self.end { NaiveTime::MIN } else { self.start };
+        let today = after.with_timezone(&self.timezone).date_naive();
+        (0..=7)
+            .map(|offset| today + Days::new(offset))
+            .filter(|date| self.days.contains(&date.weekday()))
+            .filter_map(|date| {
+                // An opening skipped by a DST jump moves an hour later.
+                let local = date.and_time(opens_at);
+                self.timezone
+                    .from_local_datetime(&local)
+                    .earliest()
+                    .or_else(|| self.timezone.from_local_datetime(&(local + chrono::Duration::hours(1))).earliest())
+            })
+            .map(|at| at.with_timezone(&Utc))
+            .find(|at| *at > after)
+            .unwrap_or(after)
+    }
+
+    pub fn gate(&self, now: DateTime<Utc>) -> Gate {
+        if self.is_open(now) {
+            return Gate::Open;
+        }
+        match self.outside {
+            OutsideWindow::Drop => Gate::Drop,
+            OutsideWindow::Queue => Gate::Queue(self.next_open(now)),
+        }
+    }
+}
+
+/// The [`Gate`] for an event on `flow` at `now`. Flows without a window are
+/// always open; an invalid window is ignored (and reported when the trigger
+/// starts).
+pub fn gate(flow: &Flow, now: DateTime<Utc>) -> Gate {
+    match ActiveWindow::for_flow(flow) {
+        Ok(Some(window)) => window.gate(now),
+        Ok(None) => Gate::Open,
+        Err(e) => {
+            tracing::warn!(flow = %flow.name, error = %e, "Ignoring invalid active_window");
+            Gate::Open
+        }
+    }
+}
+
+/// `mon`, `Monday`, or a range like `mon-fri` (wrapping, so `fri-mon` works).
+fn parse_days(entry: &str) -> Result<Vec<Weekday>> {
+    let parse = |s: &str| {
+        s.trim()
+            .parse::<Weekday>()
+            .map_err(|_| anyhow::anyhow!("invalid day '{s}' in active_window.days"))
+    };
+    match entry.split_once('-') {
+        None => Ok(vec![parse(entry)?]),
+        Some((from, to)) => {
+            let (mut day, to) = (parse(from)?, parse(to)?);
+            let mut days = vec![day];
+            while day != to {
+                day = day.succ();
+                days.push(day);
+            }
+            Ok(days)
+        }
+    }
+}
+
+/// `HH:MM`; `24:00` is accepted as an end-of-day `end`.
+fn parse_time(s: &str) -> Result<NaiveTime> {
+    if s.trim() == "24:00" {
+        return Ok(NaiveTime::MIN);
+    }
+    NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .with_context(|| format!("invalid active_window time '{s}' (expected HH:MM)"))
+}
+
+/// Runs held outside their flow's window, started when it opens. Each key (a
+/// flow id) runs its held jobs one at a time.
+#[derive(Clone, Default)]
+pub struct WindowQueue {
+    lanes: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+}
+
+impl WindowQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `job` at `open_at`, after any of `key`'s held jobs already running.
+    pub async fn defer<F>(&self, key: &str, open_at: DateTime<Utc>, job: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let lane = self.lanes.lock().await.entry(key.to_string()).or_default().clone();
+        tokio::spawn(async move {
+            let wait = (open_at - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            let _turn = lane.lock().await;
+            job.await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn window(config: Value) -> ActiveWindow {
+        ActiveWindow::from_trigger(&json!({ "active_window": config })).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_from_trigger() {
+        assert_eq!(ActiveWindow::from_trigger(&json!({})).unwrap(), None);
+
+        let w = window(json!({ "days": ["mon-fri"], "start": "09:00", "end": "17:30", "outside": "queue" }));
+        assert_eq!(w.days, ALL_DAYS[..5].to_vec());
+        assert_eq!(w.end, NaiveTime::from_hms_opt(17, 30, 0).unwrap());
+        assert_eq!(w.timezone, Tz::UTC);
+        assert_eq!(w.outside, OutsideWindow::Queue);
+
+        // Falls back to the cron trigger's timezone; ranges wrap
+        let w = ActiveWindow::from_trigger(&json!({
+            "timezone": "Europe/London",
+            "active_window": { "days": ["fri-mon", "sat"] }
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(w.timezone, chrono_tz::Europe::London);
+        assert_eq!(w.days, vec![Weekday::Fri, Weekday::Sat, Weekday::Sun, Weekday::Mon]);
+        assert_eq!(w.outside, OutsideWindow::Drop);
+
+        for bad in [
+            json!({ "days": [] }),
+            json!({ "days": ["someday"] }),
+            json!({ "start": "9am" }),
+            json!({ "timezone": "Mars/Base" }),
+            json!({ "outside": "later" }),
+        ] {
+            assert!(ActiveWindow::from_trigger(&json!({ "active_window": bad })).is_err());
+        }
+    }
+
+    #[test]
+    fn test_business_hours() {
+        let w = window(json!({ "days": ["mon-fri"], "start": "09:00", "end": "18:00", "timezone": "America/New_York", "outside": "queue" }));
+        // Friday 2026-01-09 12:00 EST
+        assert_eq!(w.gate(at("2026-01-09T17:00:00Z")), Gate::Open);
+        // Friday 18:30 EST -> Monday 09:00 EST
+        assert_eq!(w.gate(at("2026-01-09T23:30:00Z")), Gate::Queue(at("2026-01-12T14:00:00Z")));
+        // Monday 08:00 EST -> 09:00 the same day
+        assert_eq!(w.next_open(at("2026-01-12T13:00:00Z")), at("2026-01-12T14:00:00Z"));
+
+        let dropping = ActiveWindow { outside: OutsideWindow::Drop, ..w };
+        assert_eq!(dropping.gate(at("2026-01-10T17:00:00Z")), Gate::Drop);
+    }
+
+    #[test]
+    fn test_overnight_and_all_day_windows() {
+        // Friday night into Saturday morning counts as Friday's window
+        let w = window(json!({ "days": ["fri"], "start": "22:00", "end": "06:00" }));
+        assert!(w.is_open(at("2026-01-09T23:00:00Z")));
+        assert!(w.is_open(at("2026-01-10T05:59:00Z")));
+        assert!(!w.is_open(at("2026-01-10T06:00:00Z")));
+        assert!(!w.is_open(at("2026-01-08T23:00:00Z")));
+        assert_eq!(w.next_open(at("2026-01-10T12:00:00Z")), at("2026-01-16T22:00:00Z"));
+
+        let weekends = window(json!({ "days": ["sat", "sun"] }));
+        assert!(weekends.is_open(at("2026-01-10T00:00:00Z")));
+        assert!(!weekends.is_open(at("2026-01-09T23:59:00Z")));
+        assert_eq!(weekends.next_open(at("2026-01-09T12:00:00Z")), at("2026-01-10T00:00:00Z"));
+    }
+
+    #[tokio::test]
+    async fn test_defer_runs_held_jobs_in_turn() {
+        let queue = WindowQueue::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let open_at = Utc::now() + chrono::Duration::milliseconds(50);
+        for n in 0..3 {
+            let tx = tx.clone();
+            queue
+                .defer("flow", open_at, async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    tx.send(n).unwrap();
+                })
+                .await;
+        }
+        assert!(rx.try_recv().is_err());
+
+        let mut ran = Vec::new();
+        for _ in 0..3 {
+            ran.push(rx.recv().await.unwrap());
+        }
+        ran.sort();
+        assert_eq!(ran, [0, 1, 2]);
+    }
+}
@@ -1,11 +1,25 @@
+use std::collections::BTreeMap;
+
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use reqwest::Client;
+use chrono::{DateTime, Utc};
+use reqwest::header::LINK;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::egress::AuditedSend;
+use crate::identity::{Integration, user_agent};
+
+use super::app::AppAuth;
+use super::models::{Branch, Commit, Comparison, Issue, IssueComment, PullRequest, Release};
 
-use super::models::PullRequest;
+pub(crate) const GITHUB_API: &str = "https://api.github.com";
 
-const USER_AGENT: &str = "cthulu-bot";
-const GITHUB_API: &str = "https://api.github.com";
+/// Items per page of a list call (GitHub's maximum).
+const PER_PAGE: &str = "100";
+
+/// Pages a list call fetches at most unless `GITHUB_MAX_PAGES` says otherwise.
+const DEFAULT_MAX_PAGES: usize = 10;
 
 #[async_trait]
 pub trait GithubClient: Send + Sync {
@@ -13,46 +27,165 @@ pub trait GithubClient: Send + Sync {
     async fn fetch_single_pr(&self, owner: &str, repo: &str, pr_number: u64) -> Result<PullRequest>;
     async fn fetch_pr_diff(&self, owner: &str, repo: &str, pr_number: u64) -> Result<String>;
     async fn post_comment(&self, owner: &str, repo: &str, pr_number: u64, body: &str) -> Result<()>;
+    /// Bytes of code per language, as reported by GitHub linguist.
+    async fn fetch_languages(&self, owner: &str, repo: &str) -> Result<BTreeMap<String, u64>>;
+    /// Raw contents of `path` at `git_ref`, or `None` if the file doesn't exist.
+    async fn fetch_file(&self, owner: &str, repo: &str, path: &str, git_ref: &str) -> Result<Option<String>>;
+    /// Ask users (logins) and teams (slugs) to review a PR.
+    async fn request_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        reviewers: &[String],
+        team_reviewers: &[String],
+    ) -> Result<()>;
+    /// Issues (open or closed) carrying `label`, updated since `since`.
+    async fn fetch_labeled_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        label: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Issue>>;
+    /// Open issues, oldest first. Includes PRs (`Issue::pull_request` set).
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>>;
+    /// The most recently opened open issues, newest first. Includes PRs.
+    async fn fetch_recent_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>>;
+    /// The most recent releases, newest first. Includes drafts when the token
+    /// has push access.
+    async fn fetch_recent_releases(&self, owner: &str, repo: &str) -> Result<Vec<Release>>;
+    async fn fetch_branches(&self, owner: &str, repo: &str) -> Result<Vec<Branch>>;
+    /// Comments on all issues and PRs in the repo updated since `since`,
+    /// oldest first.
+    async fn fetch_issue_comments(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<IssueComment>>;
+    /// Latest commit on `git_ref` (default branch when `None`), optionally
+    /// only among those touching `path`.
+    async fn fetch_last_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        git_ref: Option<&str>,
+        path: Option<&str>,
+    ) -> Result<Option<Commit>>;
+    /// Commits in `head` that aren't in `base`, oldest first.
+    async fn compare_commits(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<Vec<Commit>>;
+    /// Unified diff between `base` and `head`.
+    async fn fetch_compare_diff(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<String>;
+    /// A token for GitHub calls made without the client, by sources and
+    /// the flow runner.
+    async fn access_token(&self) -> Result<String> {
+        anyhow::bail!("this GitHub client has no access token")
+    }
+}
+
+/// How requests are authenticated.
+enum Auth {
+    /// A personal access token.
+    Token(String),
+    /// Installation tokens of a GitHub App.
+    App(Box<AppAuth>),
 }
 
 pub struct HttpGithubClient {
     client: Client,
-    token: String,
+    auth: Auth,
+    max_pages: usize,
 }
 
 impl HttpGithubClient {
     pub fn new(client: Client, token: String) -> Self {
-        Self { client, token }
+        Self { client, auth: Auth::Token(token), max_pages: max_pages_from_env() }
+    }
+
+    /// Authenticate as an installation of a GitHub App.
+    pub fn with_app(client: Client, app: AppAuth) -> Self {
+        Self { client, auth: Auth::App(Box::new(app)), max_pages: max_pages_from_env() }
+    }
+
+    async fn token(&self) -> Result<String> {
+        match &self.auth {
+            Auth::Token(token) => Ok(token.clone()),
+            Auth::App(app) => app.token().await,
+        }
+    }
+
+    /// Every page of a list endpoint, following the `Link` header's `next`
+    /// for up to `max_pages` pages. `items` takes the list out of a page.
+    async fn fetch_pages<P: DeserializeOwned, T>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+        what: &str,
+        items: fn(P) -> Vec<T>,
+    ) -> Result<Vec<T>> {
+        let mut all = Vec::new();
+        let mut request = self.client.get(url).query(query).query(&[("per_page", PER_PAGE)]);
+        for page in 1.. {
+            let resp = request
+                .bearer_auth(self.token().await?)
+                .header("User-Agent", user_agent(Integration::Github))
+                .header("Accept", "application/vnd.github+json")
+                .send_audited()
+                .await
+                .with_context(|| format!("failed to fetch {what}"))?;
+
+            let status = resp.status();
+            if !status.is_success() {
+                let body = resp.text().await.unwrap_or_default();
+                anyhow::bail!("GitHub API error {status} fetching {what}: {body}");
+            }
+
+            let next = resp.headers().get(LINK).and_then(|v| v.to_str().ok()).and_then(next_link);
+            let body: P = resp.json().await.with_context(|| format!("failed to parse {what}"))?;
+            all.extend(items(body));
+            let Some(next) = next else {
+                break;
+            };
+            if page >= self.max_pages {
+                tracing::warn!(pages = page, items = all.len(), "Stopped fetching {what} at GITHUB_MAX_PAGES");
+                break;
+            }
+            request = self.client.get(next);
+        }
+        Ok(all)
     }
 }
 
+/// The `rel="next"` URL of a `Link` header.
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// Most pages a list call fetches, from `GITHUB_MAX_PAGES`.
+fn max_pages_from_env() -> usize {
+    parse_max_pages(std::env::var("GITHUB_MAX_PAGES").ok().as_deref())
+}
+
+fn parse_max_pages(value: Option<&str>) -> usize {
+    value
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_MAX_PAGES)
+}
+
 #[async_trait]
 impl GithubClient for HttpGithubClient {
     async fn fetch_open_prs(&self, owner: &str, repo: &str) -> Result<Vec<PullRequest>> {
         let url = format!("{GITHUB_API}/repos/{owner}/{repo}/pulls");
-        let resp = self
-            .client
-            .get(&url)
-            .query(&[
-                ("state", "open"),
-                ("sort", "created"),
-                ("direction", "desc"),
-            ])
-            .bearer_auth(&self.token)
-            .header("User-Agent", USER_AGENT)
-            .header("Accept", "application/vnd.github+json")
-            .send()
-            .await
-            .context("failed to fetch open PRs")?;
-
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GitHub API error {status} fetching PRs for {owner}/{repo}: {body}");
-        }
-
-        let prs: Vec<PullRequest> = resp.json().await.context("failed to parse PR list")?;
-        Ok(prs)
+        let query = [("state", "open"), ("sort", "created"), ("direction", "desc")];
+        self.fetch_pages(&url, &query, &format!("PRs for {owner}/{repo}"), |prs| prs).await
     }
 
     async fn fetch_single_pr(&self, owner: &str, repo: &str, pr_number: u64) -> Result<PullRequest> {
@@ -60,10 +193,10 @@ impl GithubClient for HttpGithubClient {
         let resp = self
             .client
             .get(&url)
-            .bearer_auth(&self.token)
-            .header("User-Agent", USER_AGENT)
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
             .header("Accept", "application/vnd.github+json")
-            .send()
+            .send_audited()
             .await
             .context("failed to fetch PR")?;
 
@@ -81,10 +214,10 @@ impl GithubClient for HttpGithubClient {
         let resp = self
             .client
             .get(&url)
-            .bearer_auth(&self.token)
-            .header("User-Agent", USER_AGENT)
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
             .header("Accept", "application/vnd.github.v3.diff")
-            .send()
+            .send_audited()
             .await
             .context("failed to fetch PR diff")?;
 
@@ -104,11 +237,11 @@ impl GithubClient for HttpGithubClient {
         let resp = self
             .client
             .post(&url)
-            .bearer_auth(&self.token)
-            .header("User-Agent", USER_AGENT)
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
             .header("Accept", "application/vnd.github+json")
             .json(&payload)
-            .send()
+            .send_audited()
             .await
             .context("failed to post comment")?;
 
@@ -120,4 +253,261 @@ impl GithubClient for HttpGithubClient {
 
         Ok(())
     }
+
+    async fn request_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        reviewers: &[String],
+        team_reviewers: &[String],
+    ) -> Result<()> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/pulls/{pr_number}/requested_reviewers");
+        let payload = serde_json::json!({
+            "reviewers": reviewers,
+            "team_reviewers": team_reviewers,
+        });
+
+        let resp = self
+            .client
+            .post(&url)
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
+            .header("Accept", "application/vnd.github+json")
+            .json(&payload)
+            .send_audited()
+            .await
+            .context("failed to request reviewers")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API error {status} requesting reviewers on PR #{pr_number}: {body}");
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_languages(&self, owner: &str, repo: &str) -> Result<BTreeMap<String, u64>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/languages");
+        let resp = self
+            .client
+            .get(&url)
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
+            .header("Accept", "application/vnd.github+json")
+            .send_audited()
+            .await
+            .context("failed to fetch repo languages")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API error {status} fetching languages for {owner}/{repo}: {body}");
+        }
+
+        resp.json().await.context("failed to parse repo languages")
+    }
+
+    async fn fetch_file(&self, owner: &str, repo: &str, path: &str, git_ref: &str) -> Result<Option<String>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/contents/{path}");
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("ref", git_ref)])
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
+            .header("Accept", "application/vnd.github.raw+json")
+            .send_audited()
+            .await
+            .with_context(|| format!("failed to fetch {path}"))?;
+
+        let status = resp.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API error {status} fetching {path} from {owner}/{repo}: {body}");
+        }
+
+        resp.text().await.map(Some).context("failed to read file body")
+    }
+
+    async fn fetch_labeled_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        label: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Issue>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/issues");
+        let since = since.to_rfc3339();
+        let query = [("labels", label), ("state", "all"), ("since", since.as_str())];
+        self.fetch_pages(&url, &query, &format!("'{label}' issues for {owner}/{repo}"), |issues| issues)
+            .await
+    }
+
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/issues");
+        let query = [("state", "open"), ("sort", "created"), ("direction", "asc")];
+        self.fetch_pages(&url, &query, &format!("issues for {owner}/{repo}"), |issues| issues).await
+    }
+
+    async fn fetch_recent_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/issues");
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[
+                ("state", "open"),
+                ("sort", "created"),
+                ("direction", "desc"),
+                ("per_page", "30"),
+            ])
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
+            .header("Accept", "application/vnd.github+json")
+            .send_audited()
+            .await
+            .context("failed to fetch recent issues")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API error {status} fetching recent issues for {owner}/{repo}: {body}");
+        }
+
+        resp.json().await.context("failed to parse issue list")
+    }
+
+    async fn fetch_recent_releases(&self, owner: &str, repo: &str) -> Result<Vec<Release>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/releases");
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("per_page", "20")])
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
+            .header("Accept", "application/vnd.github+json")
+            .send_audited()
+            .await
+            .context("failed to fetch releases")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API error {status} fetching releases for {owner}/{repo}: {body}");
+        }
+
+        resp.json().await.context("failed to parse release list")
+    }
+
+    async fn fetch_branches(&self, owner: &str, repo: &str) -> Result<Vec<Branch>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/branches");
+        self.fetch_pages(&url, &[], &format!("branches for {owner}/{repo}"), |branches| branches).await
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<IssueComment>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/issues/comments");
+        let since = since.to_rfc3339();
+        let query = [("since", since.as_str()), ("sort", "created"), ("direction", "asc")];
+        self.fetch_pages(&url, &query, &format!("comments for {owner}/{repo}"), |comments| comments).await
+    }
+
+    async fn fetch_last_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        git_ref: Option<&str>,
+        path: Option<&str>,
+    ) -> Result<Option<Commit>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/commits");
+        let mut query = vec![("per_page", "1")];
+        if let Some(git_ref) = git_ref {
+            query.push(("sha", git_ref));
+        }
+        if let Some(path) = path {
+            query.push(("path", path));
+        }
+        let resp = self
+            .client
+            .get(&url)
+            .query(&query)
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
+            .header("Accept", "application/vnd.github+json")
+            .send_audited()
+            .await
+            .context("failed to fetch commits")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API error {status} fetching commits for {owner}/{repo}: {body}");
+        }
+
+        let commits: Vec<Commit> = resp.json().await.context("failed to parse commit list")?;
+        Ok(commits.into_iter().next())
+    }
+
+    async fn compare_commits(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<Vec<Commit>> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/compare/{base}...{head}");
+        let what = format!("commits {base}...{head} in {owner}/{repo}");
+        self.fetch_pages(&url, &[], &what, |comparison: Comparison| comparison.commits).await
+    }
+
+    async fn fetch_compare_diff(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<String> {
+        let url = format!("{GITHUB_API}/repos/{owner}/{repo}/compare/{base}...{head}");
+        let resp = self
+            .client
+            .get(&url)
+            .bearer_auth(self.token().await?)
+            .header("User-Agent", user_agent(Integration::Github))
+            .header("Accept", "application/vnd.github.v3.diff")
+            .send_audited()
+            .await
+            .context("failed to fetch compare diff")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API error {status} fetching diff {base}...{head} in {owner}/{repo}: {body}");
+        }
+
+        resp.text().await.context("failed to read diff body")
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        self.token().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_link() {
+        let header = r#"<https://api.github.com/repositories/1/pulls?state=open&page=2>; rel="next", <https://api.github.com/repositories/1/pulls?state=open&page=5>; rel="last""#;
+        assert_eq!(
+            next_link(header).as_deref(),
+            Some("https://api.github.com/repositories/1/pulls?state=open&page=2")
+        );
+        let last_page = r#"<https://api.github.com/repositories/1/pulls?page=1>; rel="first", <https://api.github.com/repositories/1/pulls?page=4>; rel="prev""#;
+        assert_eq!(next_link(last_page), None);
+    }
+
+    #[test]
+    fn test_parse_max_pages() {
+        assert_eq!(parse_max_pages(None), DEFAULT_MAX_PAGES);
+        assert_eq!(parse_max_pages(Some(" 3 ")), 3);
+        assert_eq!(parse_max_pages(Some("0")), DEFAULT_MAX_PAGES);
+        assert_eq!(parse_max_pages(Some("all")), DEFAULT_MAX_PAGES);
+    }
 }
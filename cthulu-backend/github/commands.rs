@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Prefix for commands maintainers post as PR comments.
+pub const COMMAND_PREFIX: &str = "/cthulu";
+
+/// A `/cthulu <name> [args]` command parsed from a PR comment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrCommand {
+    /// Lowercased command word, e.g. `review`, `explain`, `security-scan`.
+    pub name: String,
+    /// Rest of the command line, if any.
+    pub args: String,
+}
+
+/// Find the first line of `body` that is a `/cthulu <name>` command.
+/// Quoted lines (`> /cthulu review`) are ignored so replies don't re-trigger.
+pub fn parse(body: &str) -> Option<PrCommand> {
+    body.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(COMMAND_PREFIX)?;
+        // `/cthulu-bot` is not a command
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let mut words = rest.split_whitespace();
+        let name = words.next()?.to_lowercase();
+        Some(PrCommand {
+            name,
+            args: words.collect::<Vec<_>>().join(" "),
+        })
+    })
+}
+
+/// Only people with write access to the repo may trigger runs.
+pub fn is_maintainer(author_association: &str) -> bool {
+    matches!(author_association, "OWNER" | "MEMBER" | "COLLABORATOR")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("/cthulu review"),
+            Some(PrCommand { name: "review".into(), args: String::new() })
+        );
+        assert_eq!(
+            parse("Thanks!\n  /cthulu Security-Scan  src/auth  \nmore text"),
+            Some(PrCommand { name: "security-scan".into(), args: "src/auth".into() })
+        );
+        assert_eq!(parse("/cthulu"), None);
+        assert_eq!(parse("/cthulu-bot review"), None);
+        assert_eq!(parse("> /cthulu review\nagreed"), None);
+        assert_eq!(parse("please run /cthulu review"), None);
+    }
+
+    #[test]
+    fn test_is_maintainer() {
+        assert!(is_maintainer("OWNER"));
+        assert!(is_maintainer("COLLABORATOR"));
+        assert!(!is_maintainer("CONTRIBUTOR"));
+        assert!(!is_maintainer("NONE"));
+    }
+}
@@ -1,2 +1,6 @@
+pub mod app;
 pub mod client;
+pub mod commands;
 pub mod models;
+pub mod repo_context;
+pub mod webhook;
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use std::path::PathBuf;
 
@@ -24,6 +25,115 @@ pub struct PullRequest {
     pub draft: bool,
     pub head: PrRef,
     pub base: PrRef,
+    #[serde(default)]
+    pub user: Option<PrUser>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The author's relation to the repo: `OWNER`, `MEMBER`, `COLLABORATOR`,
+    /// `CONTRIBUTOR`, `NONE`...
+    #[serde(default)]
+    pub author_association: Option<String>,
+}
+
+impl PullRequest {
+    /// Whether the head branch lives in the base repo (not a fork) and the
+    /// author is an owner, member or collaborator. Unknown counts as no.
+    pub fn by_maintainer(&self) -> bool {
+        let same_repo = match (&self.head.repo, &self.base.repo) {
+            (Some(head), Some(base)) => head.full_name.eq_ignore_ascii_case(&base.full_name),
+            _ => false,
+        };
+        same_repo
+            && matches!(self.author_association.as_deref(), Some("OWNER" | "MEMBER" | "COLLABORATOR"))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrUser {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Issue {
+    #[serde(default)]
+    pub number: u64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub user: Option<PrUser>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    /// Comment count.
+    #[serde(default)]
+    pub comments: u64,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Present when the "issue" is actually a pull request.
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Release {
+    pub id: u64,
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Release notes (markdown).
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub author: Option<PrUser>,
+}
+
+impl Release {
+    /// The release title, falling back to the tag.
+    pub fn title(&self) -> &str {
+        self.name.as_deref().filter(|n| !n.trim().is_empty()).unwrap_or(&self.tag_name)
+    }
+}
+
+/// A comment on an issue or PR conversation.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IssueComment {
+    pub id: u64,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub user: Option<PrUser>,
+    /// `OWNER`, `MEMBER`, `COLLABORATOR`, `CONTRIBUTOR`, `NONE`, ...
+    #[serde(default)]
+    pub author_association: String,
+    /// API URL of the issue/PR, ending in its number.
+    #[serde(default)]
+    pub issue_url: String,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl IssueComment {
+    pub fn issue_number(&self) -> Option<u64> {
+        self.issue_url.rsplit('/').next()?.parse().ok()
+    }
+
+    pub fn author(&self) -> &str {
+        self.user.as_ref().map(|u| u.login.as_str()).unwrap_or("unknown")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Label {
+    pub name: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -31,4 +141,79 @@ pub struct PrRef {
     pub sha: String,
     #[serde(rename = "ref")]
     pub ref_name: String,
+    /// The repo the ref lives in; `None` once a fork is deleted.
+    #[serde(default)]
+    pub repo: Option<PrRepo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrRepo {
+    pub full_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Branch {
+    pub name: String,
+    pub commit: BranchCommit,
+    #[serde(default)]
+    pub protected: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BranchCommit {
+    pub sha: String,
+}
+
+/// An entry from the commits list or compare APIs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Commit {
+    pub sha: String,
+    pub commit: CommitDetail,
+    /// The GitHub account of the author, when the email maps to one.
+    #[serde(default)]
+    pub author: Option<PrUser>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitDetail {
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub author: Option<GitActor>,
+    pub committer: Option<GitActor>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitActor {
+    #[serde(default)]
+    pub name: String,
+    pub date: DateTime<Utc>,
+}
+
+impl Commit {
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        self.commit.committer.as_ref().map(|c| c.date)
+    }
+
+    /// GitHub login if known, else the git author name.
+    pub fn author_name(&self) -> &str {
+        match (&self.author, &self.commit.author) {
+            (Some(user), _) => &user.login,
+            (None, Some(actor)) => &actor.name,
+            (None, None) => "unknown",
+        }
+    }
+
+    /// First line of the commit message.
+    pub fn subject(&self) -> &str {
+        self.commit.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// Response of the compare API (`base...head`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct Comparison {
+    /// Commits reachable from head but not base, oldest first.
+    #[serde(default)]
+    pub commits: Vec<Commit>,
 }
@@ -0,0 +1,321 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Utc;
+use glob::{MatchOptions, Pattern};
+
+use super::client::GithubClient;
+
+/// Where GitHub looks for CODEOWNERS, in priority order.
+const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// How far back to look for incident issues.
+const INCIDENT_LOOKBACK_DAYS: i64 = 30;
+
+/// Languages listed in `{{languages}}`; the long tail is dropped.
+const MAX_LANGUAGES: usize = 6;
+
+pub struct RepoContext {
+    /// Template variables for the review prompt.
+    pub vars: HashMap<String, String>,
+    /// CODEOWNERS owners of the touched files (`@user`, `@org/team`), deduped.
+    pub owners: Vec<String>,
+}
+
+/// Repo metadata for review prompts, as template variables:
+///
+/// - `{{languages}}` — language breakdown, e.g. `Rust 71.2%, TypeScript 24.0%`
+/// - `{{codeowners}}` — CODEOWNERS entries covering the touched files
+/// - `{{owner_mentions}}` — those owners as a space-separated mention list
+/// - `{{incident_tags}}` — labels on recent `incident_label` issues, by frequency
+///
+/// Each lookup is best-effort: failures are logged and yield a placeholder so
+/// a flaky API call never blocks a review.
+pub async fn gather(
+    client: &dyn GithubClient,
+    owner: &str,
+    repo: &str,
+    base_ref: &str,
+    touched_files: &[String],
+    incident_label: &str,
+) -> RepoContext {
+    let (languages, codeowners, incidents) = tokio::join!(
+        client.fetch_languages(owner, repo),
+        fetch_codeowners(client, owner, repo, base_ref),
+        client.fetch_labeled_issues(
+            owner,
+            repo,
+            incident_label,
+            Utc::now() - chrono::Duration::days(INCIDENT_LOOKBACK_DAYS),
+        ),
+    );
+
+    let languages = languages
+        .map(|l| format_languages(&l))
+        .unwrap_or_else(|e| {
+            tracing::warn!(repo = %format!("{owner}/{repo}"), error = %e, "Failed to fetch repo languages");
+            String::new()
+        });
+
+    let rules = codeowners.map(|text| parse_codeowners(&text)).unwrap_or_default();
+    let owners = owners_of(&rules, touched_files);
+    let codeowners = format_codeowners(&rules, touched_files);
+
+    let incident_tags = incidents
+        .map(|issues| {
+            let labels = issues
+                .iter()
+                .flat_map(|i| i.labels.iter().map(|l| l.name.as_str()));
+            format_incident_tags(labels, incident_label)
+        })
+        .unwrap_or_else(|e| {
+            tracing::warn!(repo = %format!("{owner}/{repo}"), error = %e, "Failed to fetch incident issues");
+            String::new()
+        });
+
+    let or_none = |s: String| if s.is_empty() { "none".to_string() } else { s };
+    let vars = HashMap::from([
+        ("languages".to_string(), or_none(languages)),
+        ("codeowners".to_string(), or_none(codeowners)),
+        ("owner_mentions".to_string(), or_none(owners.join(" "))),
+        ("incident_tags".to_string(), or_none(incident_tags)),
+    ]);
+    RepoContext { vars, owners }
+}
+
+/// First CODEOWNERS file found at `base_ref`, if any.
+async fn fetch_codeowners(
+    client: &dyn GithubClient,
+    owner: &str,
+    repo: &str,
+    base_ref: &str,
+) -> Option<String> {
+    for path in CODEOWNERS_PATHS {
+        match client.fetch_file(owner, repo, path, base_ref).await {
+            Ok(Some(text)) => return Some(text),
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(path, error = %e, "Failed to fetch CODEOWNERS");
+                return None;
+            }
+        }
+    }
+    None
+}
+
+fn format_languages(bytes: &BTreeMap<String, u64>) -> String {
+    let total: u64 = bytes.values().sum();
+    if total == 0 {
+        return String::new();
+    }
+    let mut langs: Vec<(&String, &u64)> = bytes.iter().collect();
+    langs.sort_by_key(|(_, b)| std::cmp::Reverse(**b));
+    langs
+        .into_iter()
+        .take(MAX_LANGUAGES)
+        .map(|(name, b)| format!("{name} {:.1}%", *b as f64 * 100.0 / total as f64))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+struct OwnerRule {
+    globs: Vec<Pattern>,
+    owners: Vec<String>,
+}
+
+fn parse_codeowners(text: &str) -> Vec<OwnerRule> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let globs = codeowners_globs(parts.next()?);
+            let owners: Vec<String> = parts.map(String::from).collect();
+            (!globs.is_empty()).then_some(OwnerRule { globs, owners })
+        })
+        .collect()
+}
+
+/// Translate a gitignore-style CODEOWNERS pattern into globs over repo paths.
+/// Unanchored patterns without a slash match at any depth, and every pattern
+/// also covers everything beneath a matching directory.
+fn codeowners_globs(pattern: &str) -> Vec<Pattern> {
+    let anchored = pattern.starts_with('/') || pattern.trim_end_matches('/').contains('/');
+    let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+    let base = if anchored || trimmed.starts_with("**") {
+        trimmed.to_string()
+    } else {
+        format!("**/{trimmed}")
+    };
+    [base.clone(), format!("{base}/**")]
+        .iter()
+        .filter_map(|p| Pattern::new(p).ok())
+        .collect()
+}
+
+/// Owners of `path`; the last matching rule wins, as on GitHub.
+fn owners_for<'a>(rules: &'a [OwnerRule], path: &str) -> Option<&'a OwnerRule> {
+    let opts = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.globs.iter().any(|g| g.matches_with(path, opts)))
+}
+
+/// Every owner of any touched file, in first-seen order.
+fn owners_of(rules: &[OwnerRule], touched_files: &[String]) -> Vec<String> {
+    let mut owners: Vec<String> = Vec::new();
+    for rule in touched_files.iter().filter_map(|path| owners_for(rules, path)) {
+        for o in &rule.owners {
+            if !owners.contains(o) {
+                owners.push(o.clone());
+            }
+        }
+    }
+    owners
+}
+
+/// Split CODEOWNERS owners into (user logins, team slugs) for a review
+/// request. Teams must belong to `org`; emails and the PR author are skipped
+/// since GitHub can't request them.
+pub fn reviewer_request(owners: &[String], org: &str, author: Option<&str>) -> (Vec<String>, Vec<String>) {
+    let mut users = Vec::new();
+    let mut teams = Vec::new();
+    for owner in owners {
+        let Some(name) = owner.strip_prefix('@') else {
+            continue;
+        };
+        match name.split_once('/') {
+            Some((team_org, slug)) if team_org.eq_ignore_ascii_case(org) => teams.push(slug.to_string()),
+            Some(_) => {}
+            None if author.is_some_and(|a| a.eq_ignore_ascii_case(name)) => {}
+            None => users.push(name.to_string()),
+        }
+    }
+    (users, teams)
+}
+
+/// One line per owner set, listing the touched files it covers.
+fn format_codeowners(rules: &[OwnerRule], touched_files: &[String]) -> String {
+    let mut by_owners: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for path in touched_files {
+        let owners = match owners_for(rules, path) {
+            Some(rule) if !rule.owners.is_empty() => rule.owners.join(" "),
+            _ => "(no owner)".to_string(),
+        };
+        by_owners.entry(owners).or_default().push(path);
+    }
+    if by_owners.keys().all(|o| o == "(no owner)") {
+        return String::new();
+    }
+    by_owners
+        .into_iter()
+        .map(|(owners, files)| format!("- {owners}: {}", files.join(", ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_incident_tags<'a>(labels: impl Iterator<Item = &'a str>, incident_label: &str) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for label in labels.filter(|l| !l.eq_ignore_ascii_case(incident_label)) {
+        *counts.entry(label).or_default() += 1;
+    }
+    let mut tags: Vec<(&str, usize)> = counts.into_iter().collect();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    tags.into_iter()
+        .map(|(tag, n)| format!("{tag} ({n})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEOWNERS: &str = "\
+# Default owners
+*                    @org/platform
+*.ts                 @org/frontend
+/cthulu-backend/     @org/backend   # Rust service
+docs/                @alice
+/scripts/deploy.sh
+";
+
+    fn owners(path: &str) -> Option<String> {
+        let rules = parse_codeowners(CODEOWNERS);
+        owners_for(&rules, path).map(|r| r.owners.join(" "))
+    }
+
+    #[test]
+    fn test_codeowners_matching() {
+        assert_eq!(owners("README.md").as_deref(), Some("@org/platform"));
+        assert_eq!(owners("cthulu-studio/src/App.ts").as_deref(), Some("@org/frontend"));
+        assert_eq!(owners("cthulu-backend/flows/runner.rs").as_deref(), Some("@org/backend"));
+        assert_eq!(owners("docs/guide/intro.md").as_deref(), Some("@alice"));
+        // An entry with no owners un-assigns the path
+        assert_eq!(owners("scripts/deploy.sh").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_format_codeowners_groups_by_owner() {
+        let rules = parse_codeowners(CODEOWNERS);
+        let files = vec![
+            "cthulu-backend/main.rs".to_string(),
+            "cthulu-backend/config.rs".to_string(),
+            "scripts/deploy.sh".to_string(),
+            "docs/a.md".to_string(),
+        ];
+        assert_eq!(
+            format_codeowners(&rules, &files),
+            "- (no owner): scripts/deploy.sh\n\
+             - @alice: docs/a.md\n\
+             - @org/backend: cthulu-backend/main.rs, cthulu-backend/config.rs"
+        );
+        assert_eq!(format_codeowners(&[], &files), "");
+    }
+
+    #[test]
+    fn test_owners_of_touched_files() {
+        let rules = parse_codeowners(CODEOWNERS);
+        let files = vec![
+            "docs/a.md".to_string(),
+            "cthulu-backend/main.rs".to_string(),
+            "docs/b.md".to_string(),
+            "scripts/deploy.sh".to_string(),
+        ];
+        assert_eq!(owners_of(&rules, &files), vec!["@alice", "@org/backend"]);
+    }
+
+    #[test]
+    fn test_reviewer_request() {
+        let owners: Vec<String> = ["@alice", "@org/backend", "@other-org/team", "dev@example.com", "@bob"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (users, teams) = reviewer_request(&owners, "org", Some("Bob"));
+        assert_eq!(users, vec!["alice"]);
+        assert_eq!(teams, vec!["backend"]);
+    }
+
+    #[test]
+    fn test_format_languages() {
+        let bytes = BTreeMap::from([
+            ("Rust".to_string(), 750),
+            ("TypeScript".to_string(), 200),
+            ("Shell".to_string(), 50),
+        ]);
+        assert_eq!(format_languages(&bytes), "Rust 75.0%, TypeScript 20.0%, Shell 5.0%");
+        assert_eq!(format_languages(&BTreeMap::new()), "");
+    }
+
+    #[test]
+    fn test_format_incident_tags() {
+        let labels = ["incident", "payments", "sev2", "incident", "payments", "auth"];
+        assert_eq!(
+            format_incident_tags(labels.into_iter(), "incident"),
+            "payments (2), auth (1), sev2 (1)"
+        );
+    }
+}
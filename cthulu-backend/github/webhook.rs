@@ -0,0 +1,316 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use super::commands::{self, PrCommand};
+use super::models::{Issue, IssueComment, PullRequest, Release};
+
+/// Header carrying the HMAC-SHA256 of the raw body, as `sha256=<hex>`.
+pub const SIGNATURE_HEADER: &str = "x-hub-signature-256";
+/// Header naming the event type (`pull_request`, `issue_comment`, `push`, ...).
+pub const EVENT_HEADER: &str = "x-github-event";
+
+/// Check a delivery's `X-Hub-Signature-256` against the shared secret.
+/// The comparison is constant-time.
+pub fn verify_signature(secret: &[u8], body: &[u8], signature: Option<&str>) -> bool {
+    let Some(sig) = signature.and_then(|s| s.strip_prefix("sha256=")) else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(sig) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullRequestEvent {
+    pub action: String,
+    pub pull_request: PullRequest,
+    pub repository: EventRepo,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventRepo {
+    /// `owner/repo`
+    pub full_name: String,
+}
+
+impl PullRequestEvent {
+    /// Actions that introduce a PR for review, or new commits to one.
+    pub fn is_reviewable(&self) -> bool {
+        matches!(
+            self.action.as_str(),
+            "opened" | "reopened" | "ready_for_review" | "synchronize"
+        )
+    }
+
+    pub fn is_push(&self) -> bool {
+        self.action == "synchronize"
+    }
+
+    /// A draft PR was marked ready for review.
+    pub fn is_ready_for_review(&self) -> bool {
+        self.action == "ready_for_review"
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IssueCommentEvent {
+    pub action: String,
+    pub issue: Issue,
+    pub comment: IssueComment,
+    pub repository: EventRepo,
+}
+
+impl IssueCommentEvent {
+    /// The `/cthulu` command in a newly posted PR comment by a maintainer.
+    pub fn command(&self) -> Option<PrCommand> {
+        if self.action != "created"
+            || self.issue.pull_request.is_none()
+            || !commands::is_maintainer(&self.comment.author_association)
+        {
+            return None;
+        }
+        commands::parse(&self.comment.body)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IssuesEvent {
+    pub action: String,
+    pub issue: Issue,
+    pub repository: EventRepo,
+}
+
+impl IssuesEvent {
+    /// A newly opened issue (the `issues` event never carries PRs).
+    pub fn is_opened(&self) -> bool {
+        self.action == "opened"
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseEvent {
+    pub action: String,
+    pub release: Release,
+    pub repository: EventRepo,
+}
+
+impl ReleaseEvent {
+    /// A release just made public (pre-releases included; drafts never
+    /// send `published`).
+    pub fn is_published(&self) -> bool {
+        self.action == "published"
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushEvent {
+    /// Full ref, e.g. `refs/heads/main`.
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// Previous head; all zeros when the branch was just created.
+    pub before: String,
+    pub after: String,
+    #[serde(default)]
+    pub deleted: bool,
+    pub repository: EventRepo,
+}
+
+impl PushEvent {
+    /// The pushed branch, or `None` for tags.
+    pub fn branch(&self) -> Option<&str> {
+        self.git_ref.strip_prefix("refs/heads/")
+    }
+
+    /// Whether there's a `before...after` range to diff: not a branch
+    /// creation or deletion.
+    pub fn has_range(&self) -> bool {
+        !self.deleted && !is_null_sha(&self.before) && !is_null_sha(&self.after)
+    }
+}
+
+fn is_null_sha(sha: &str) -> bool {
+    sha.chars().all(|c| c == '0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        // Example from GitHub's "Validating webhook deliveries" docs
+        assert!(verify_signature(
+            b"It's a Secret to Everybody",
+            b"Hello, World!",
+            Some("sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"),
+        ));
+
+        let body = br#"{"action":"opened"}"#;
+        let sig = sign(b"secret", body);
+        assert!(verify_signature(b"secret", body, Some(&sig)));
+        assert!(!verify_signature(b"other", body, Some(&sig)));
+        assert!(!verify_signature(b"secret", b"tampered", Some(&sig)));
+        assert!(!verify_signature(b"secret", body, Some(sig.trim_start_matches("sha256="))));
+        assert!(!verify_signature(b"secret", body, Some("sha256=not-hex")));
+        assert!(!verify_signature(b"secret", body, None));
+    }
+
+    #[test]
+    fn test_parse_pull_request_event() {
+        let event: PullRequestEvent = serde_json::from_str(
+            r#"{
+                "action": "synchronize",
+                "number": 7,
+                "pull_request": {
+                    "number": 7,
+                    "title": "Fix parser",
+                    "body": null,
+                    "draft": false,
+                    "head": { "sha": "abc123", "ref": "fix-parser" },
+                    "base": { "sha": "def456", "ref": "main" },
+                    "user": { "login": "alice" }
+                },
+                "repository": { "full_name": "owner/repo", "name": "repo" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(event.repository.full_name, "owner/repo");
+        assert_eq!(event.pull_request.head.sha, "abc123");
+        assert!(event.is_reviewable());
+        assert!(event.is_push());
+        assert!(!event.is_ready_for_review());
+
+        let ready = PullRequestEvent { action: "ready_for_review".into(), ..event.clone() };
+        assert!(ready.is_reviewable());
+        assert!(ready.is_ready_for_review());
+
+        let closed = PullRequestEvent { action: "closed".into(), ..event };
+        assert!(!closed.is_reviewable());
+    }
+
+    #[test]
+    fn test_parse_push_event() {
+        let event: PushEvent = serde_json::from_str(
+            r#"{
+                "ref": "refs/heads/main",
+                "before": "1111111111111111111111111111111111111111",
+                "after": "2222222222222222222222222222222222222222",
+                "created": false,
+                "deleted": false,
+                "commits": [],
+                "repository": { "full_name": "owner/repo" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(event.branch(), Some("main"));
+        assert!(event.has_range());
+
+        let created = PushEvent { before: "0".repeat(40), ..event.clone() };
+        assert!(!created.has_range());
+        let tag = PushEvent { git_ref: "refs/tags/v1.0".into(), ..event };
+        assert_eq!(tag.branch(), None);
+    }
+
+    #[test]
+    fn test_parse_issues_event() {
+        let event: IssuesEvent = serde_json::from_str(
+            r#"{
+                "action": "opened",
+                "issue": {
+                    "number": 31,
+                    "title": "Crash on startup",
+                    "body": "Steps to reproduce...",
+                    "html_url": "https://github.com/owner/repo/issues/31",
+                    "user": { "login": "bob" },
+                    "labels": [{ "name": "bug" }]
+                },
+                "repository": { "full_name": "owner/repo" }
+            }"#,
+        )
+        .unwrap();
+        assert!(event.is_opened());
+        assert_eq!(event.issue.user.as_ref().unwrap().login, "bob");
+        assert_eq!(event.issue.body.as_deref(), Some("Steps to reproduce..."));
+
+        let labeled = IssuesEvent { action: "labeled".into(), ..event };
+        assert!(!labeled.is_opened());
+    }
+
+    #[test]
+    fn test_parse_release_event() {
+        let event: ReleaseEvent = serde_json::from_str(
+            r#"{
+                "action": "published",
+                "release": {
+                    "id": 101,
+                    "tag_name": "v1.2.0",
+                    "name": "",
+                    "body": "- Faster builds",
+                    "html_url": "https://github.com/owner/repo/releases/tag/v1.2.0",
+                    "draft": false,
+                    "prerelease": false,
+                    "author": { "login": "alice" },
+                    "published_at": "2026-01-05T10:00:00Z"
+                },
+                "repository": { "full_name": "owner/repo" }
+            }"#,
+        )
+        .unwrap();
+        assert!(event.is_published());
+        assert_eq!(event.release.title(), "v1.2.0");
+        assert_eq!(event.release.author.as_ref().unwrap().login, "alice");
+
+        let edited = ReleaseEvent { action: "edited".into(), ..event };
+        assert!(!edited.is_published());
+    }
+
+    #[test]
+    fn test_issue_comment_command() {
+        let event: IssueCommentEvent = serde_json::from_str(
+            r#"{
+                "action": "created",
+                "issue": {
+                    "number": 12,
+                    "title": "Add caching",
+                    "html_url": "https://github.com/owner/repo/pull/12",
+                    "pull_request": { "url": "https://api.github.com/repos/owner/repo/pulls/12" }
+                },
+                "comment": {
+                    "id": 99,
+                    "body": "/cthulu explain",
+                    "user": { "login": "alice" },
+                    "author_association": "MEMBER",
+                    "issue_url": "https://api.github.com/repos/owner/repo/issues/12"
+                },
+                "repository": { "full_name": "owner/repo" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(event.command().unwrap().name, "explain");
+        assert_eq!(event.comment.issue_number(), Some(12));
+
+        let mut outsider = event.clone();
+        outsider.comment.author_association = "NONE".into();
+        assert_eq!(outsider.command(), None);
+
+        let mut edited = event.clone();
+        edited.action = "edited".into();
+        assert_eq!(edited.command(), None);
+
+        let mut on_issue = event;
+        on_issue.issue.pull_request = None;
+        assert_eq!(on_issue.command(), None);
+    }
+}
@@ -0,0 +1,458 @@
+//! Test harness mode (`cthulu serve --test-harness [FIXTURES]`): outbound
+//! requests are answered by configured mocks instead of reaching GitHub,
+//! Slack or a model API, and executors return canned output instead of
+//! running a model, so whole flows run the same way every time in CI.
+//!
+//! Requests to loopback addresses still go out, so a test can stand up mock
+//! servers of its own (and `forward_to` them). Everything else without a
+//! mock gets a 502, never the real service.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::{Context, Result, bail};
+use axum::http::{self, StatusCode, header};
+use chrono::{DateTime, Utc};
+use reqwest::ResponseBuilderExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::egress::Origin;
+
+/// Requests and prompts kept; the oldest are dropped first.
+const CAPACITY: usize = 2000;
+
+static GLOBAL: OnceLock<Arc<Harness>> = OnceLock::new();
+
+tokio::task_local! {
+    static SCOPED: Arc<Harness>;
+}
+
+/// Answers for Slack's webhooks and Web API, which every digest flow posts
+/// to; tried after the fixtures' own mocks.
+fn default_mocks() -> Vec<Mock> {
+    vec![
+        Mock { status: 200, body: json!("ok"), ..Mock::new("hooks.slack.com") },
+        Mock {
+            status: 200,
+            body: json!({ "ok": true, "channel": "C0HARNESS", "ts": "1700000000.000100" }),
+            ..Mock::new("slack.com")
+        },
+    ]
+}
+
+/// The fixtures file: YAML (or JSON) with the mocks and executor outputs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HarnessConfig {
+    #[serde(default)]
+    pub mocks: Vec<Mock>,
+    #[serde(default)]
+    pub executors: Vec<ExecutorFixture>,
+}
+
+/// A canned answer for requests to one host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mock {
+    /// e.g. `api.github.com`.
+    pub host: String,
+    /// Every method when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Path prefix; every path when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default = "default_status")]
+    pub status: u16,
+    /// JSON, or a string sent as it is.
+    #[serde(default)]
+    pub body: Value,
+    /// Send the request on to this server (e.g. `http://127.0.0.1:9000`)
+    /// instead of answering it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_to: Option<String>,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+impl Mock {
+    fn new(host: &str) -> Self {
+        Self { host: host.to_string(), method: None, path: None, status: 200, body: Value::Null, forward_to: None }
+    }
+
+    fn matches(&self, method: &str, url: &reqwest::Url) -> bool {
+        url.host_str().is_some_and(|host| host.eq_ignore_ascii_case(&self.host))
+            && self.method.as_deref().is_none_or(|m| m.eq_ignore_ascii_case(method))
+            && self.path.as_deref().is_none_or(|p| url.path().starts_with(p))
+    }
+
+    fn response(&self, url: &reqwest::Url) -> reqwest::Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        respond(url, status, &self.body)
+    }
+}
+
+/// What an executor returns for the prompts it matches. Unmatched prompts
+/// are echoed back as the output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutorFixture {
+    /// Node id or label; every node when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Only prompts containing this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_contains: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Fail the call with this error instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub cost_usd: f64,
+}
+
+impl HarnessConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read harness fixtures: {}", path.display()))?;
+        let config: Self = serde_yaml::from_str(&content)
+            .with_context(|| format!("invalid harness fixtures: {}", path.display()))?;
+        for mock in &config.mocks {
+            if StatusCode::from_u16(mock.status).is_err() {
+                bail!("mock for {} has an invalid status {}", mock.host, mock.status);
+            }
+            if let Some(target) = &mock.forward_to {
+                reqwest::Url::parse(target)
+                    .with_context(|| format!("mock for {} forwards to an invalid URL", mock.host))?;
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// How the harness handled an outbound request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Handling {
+    Mocked,
+    Forwarded,
+    /// To a loopback address, sent as it is.
+    Loopback,
+    /// No mock matched; answered with a 502.
+    Refused,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HarnessRequest {
+    pub at: DateTime<Utc>,
+    pub method: String,
+    /// Without the query string.
+    pub url: String,
+    pub body: Option<String>,
+    pub handling: Handling,
+    pub flow_id: Option<String>,
+    pub node_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HarnessPrompt {
+    pub at: DateTime<Utc>,
+    pub flow_id: String,
+    pub node_id: String,
+    pub prompt: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+}
+
+/// An outbound request after the harness has seen it.
+pub enum Intercepted {
+    Answered(reqwest::Response),
+    Send(reqwest::Request),
+}
+
+pub struct Harness {
+    config: HarnessConfig,
+    requests: Mutex<VecDeque<HarnessRequest>>,
+    prompts: Mutex<VecDeque<HarnessPrompt>>,
+}
+
+impl Harness {
+    pub fn new(mut config: HarnessConfig) -> Self {
+        config.mocks.extend(default_mocks());
+        Self { config, requests: Mutex::default(), prompts: Mutex::default() }
+    }
+
+    pub fn config(&self) -> &HarnessConfig {
+        &self.config
+    }
+
+    /// Answer `request` from the mocks, or hand it back to be sent when it
+    /// goes to loopback or a mock forwards it.
+    pub fn intercept(&self, mut request: reqwest::Request, origin: Option<&Origin>) -> Intercepted {
+        let method = request.method().to_string();
+        let url = request.url().clone();
+        let mock = self.config.mocks.iter().find(|mock| mock.matches(&method, &url));
+        let (handling, intercepted) = match mock {
+            Some(Mock { forward_to: Some(target), .. }) => {
+                forward(&mut request, target);
+                (Handling::Forwarded, None)
+            }
+            Some(mock) => (Handling::Mocked, Some(mock.response(&url))),
+            None if is_loopback(&url) => (Handling::Loopback, None),
+            None => {
+                tracing::warn!(%method, host = url.host_str().unwrap_or_default(), path = url.path(), "Test harness: no mock for request");
+                let error = format!("no test harness mock for {method} {}{}", url.host_str().unwrap_or_default(), url.path());
+                (Handling::Refused, Some(respond(&url, StatusCode::BAD_GATEWAY, &json!({ "error": error }))))
+            }
+        };
+
+        let mut logged = url;
+        logged.set_query(None);
+        push(&self.requests, HarnessRequest {
+            at: Utc::now(),
+            method,
+            url: logged.to_string(),
+            body: request.body().and_then(|b| b.as_bytes()).map(|b| String::from_utf8_lossy(b).into_owned()),
+            handling,
+            flow_id: origin.map(|o| o.flow_id.clone()),
+            node_id: origin.map(|o| o.node_id.clone()),
+        });
+        match intercepted {
+            Some(response) => Intercepted::Answered(response),
+            None => Intercepted::Send(request),
+        }
+    }
+
+    /// The canned output for an executor node's prompt (see
+    /// `ExecutorFixture`), recorded for `GET /api/admin/harness`.
+    pub fn execute(&self, flow_id: &str, node_id: &str, label: &str, prompt: &str) -> Result<(String, f64)> {
+        let fixture = self.config.executors.iter().find(|f| {
+            f.node.as_deref().is_none_or(|n| n == node_id || n == label)
+                && f.prompt_contains.as_deref().is_none_or(|p| prompt.contains(p))
+        });
+        let result = match fixture {
+            Some(ExecutorFixture { error: Some(error), .. }) => Err(error.clone()),
+            Some(ExecutorFixture { output: Some(output), cost_usd, .. }) => Ok((output.clone(), *cost_usd)),
+            Some(fixture) => Ok((prompt.to_string(), fixture.cost_usd)),
+            None => Ok((prompt.to_string(), 0.0)),
+        };
+        push(&self.prompts, HarnessPrompt {
+            at: Utc::now(),
+            flow_id: flow_id.to_string(),
+            node_id: node_id.to_string(),
+            prompt: prompt.to_string(),
+            output: result.as_ref().ok().map(|(output, _)| output.clone()),
+            error: result.as_ref().err().cloned(),
+        });
+        result.map_err(|e| anyhow::anyhow!("{e}"))
+    }
+
+    /// Recorded requests, oldest first, of `flow_id` when given.
+    pub fn requests(&self, flow_id: Option<&str>) -> Vec<HarnessRequest> {
+        let requests = self.requests.lock().unwrap();
+        requests.iter().filter(|r| flow_id.is_none_or(|f| r.flow_id.as_deref() == Some(f))).cloned().collect()
+    }
+
+    /// Recorded executor prompts, oldest first, of `flow_id` when given.
+    pub fn prompts(&self, flow_id: Option<&str>) -> Vec<HarnessPrompt> {
+        let prompts = self.prompts.lock().unwrap();
+        prompts.iter().filter(|p| flow_id.is_none_or(|f| p.flow_id == f)).cloned().collect()
+    }
+
+    /// Forget what was recorded, between test cases.
+    pub fn clear(&self) {
+        self.requests.lock().unwrap().clear();
+        self.prompts.lock().unwrap().clear();
+    }
+}
+
+fn push<T>(log: &Mutex<VecDeque<T>>, entry: T) {
+    let mut log = log.lock().unwrap();
+    if log.len() == CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+fn respond(url: &reqwest::Url, status: StatusCode, body: &Value) -> reqwest::Response {
+    let (content_type, body) = match body {
+        Value::Null => ("text/plain; charset=utf-8", String::new()),
+        Value::String(text) => ("text/plain; charset=utf-8", text.clone()),
+        json => ("application/json", json.to_string()),
+    };
+    let response = http::Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .url(url.clone())
+        .body(body)
+        .expect("status and headers are valid");
+    reqwest::Response::from(response)
+}
+
+/// Point `request` at `target`, keeping its path and query.
+fn forward(request: &mut reqwest::Request, target: &str) {
+    let Ok(target) = reqwest::Url::parse(target) else {
+        return;
+    };
+    let url = request.url_mut();
+    let _ = url.set_scheme(target.scheme());
+    let _ = url.set_host(target.host_str());
+    let _ = url.set_port(target.port());
+}
+
+fn is_loopback(url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host.trim_matches(['[', ']']).parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Turn test harness mode on for the whole process.
+pub fn install(config: HarnessConfig) -> Arc<Harness> {
+    GLOBAL.get_or_init(|| Arc::new(Harness::new(config))).clone()
+}
+
+/// The harness in effect here: `scope`d to this task, or installed for the
+/// process. `None` outside test harness mode.
+pub fn current() -> Option<Arc<Harness>> {
+    SCOPED.try_with(Arc::clone).ok().or_else(|| GLOBAL.get().cloned())
+}
+
+/// Run `work` under `harness` (when given), without installing it for the
+/// whole process — how flow runs carry theirs into their node tasks, and
+/// how tests use one.
+pub async fn scope<F: Future>(harness: Option<Arc<Harness>>, work: F) -> F::Output {
+    match harness {
+        Some(harness) => SCOPED.scope(harness, work).await,
+        None => work.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::flows::file_repository::FileFlowRepository;
+    use crate::flows::history::RunStatus;
+    use crate::flows::runner::FlowRunner;
+    use crate::flows::Flow;
+
+    const FIXTURES: &str = r#"
+mocks:
+  - host: feeds.example.com
+    path: /rss
+    body: |
+      <rss version="2.0"><channel><title>Launches</title>
+        <item><title>Rust 2.0 released</title><link>https://example.com/rust</link>
+          <description>The next edition of Rust is out, with a new borrow checker and faster builds.</description></item>
+      </channel></rss>
+  - host: example.com
+    body: "<html><body><p>Rust 2.0 is out.</p></body></html>"
+executors:
+  - node: Summarize
+    prompt_contains: Rust 2.0
+    output: "One launch today: Rust 2.0."
+"#;
+
+    fn flow() -> Flow {
+        let node = |id: &str, node_type: &str, kind: &str, config: Value| {
+            json!({
+                "id": id, "node_type": node_type, "kind": kind, "config": config,
+                "position": { "x": 0, "y": 0 }, "label": if id == "sum" { "Summarize" } else { id },
+            })
+        };
+        serde_json::from_value(json!({
+            "id": "harness-flow",
+            "name": "Launch digest",
+            "nodes": [
+                node("trigger", "trigger", "manual", json!({})),
+                node("feed", "source", "rss", json!({ "url": "https://feeds.example.com/rss" })),
+                node("sum", "executor", "claude-code", json!({ "prompt": "Summarize:\n{{content}}" })),
+            ],
+            "edges": [
+                { "id": "e1", "source": "trigger", "target": "feed" },
+                { "id": "e2", "source": "feed", "target": "sum" },
+            ],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn runner() -> FlowRunner {
+        FlowRunner {
+            http_client: Arc::new(reqwest::Client::new()),
+            github_client: None,
+            events_tx: None,
+            sandbox_provider: None,
+            agent_repo: None,
+            session_bridge: None,
+            executor_cache: None,
+            source_cache: None,
+            prompt_repo: None,
+            seen_store: None,
+            archive: None,
+            subscriptions: None,
+            feedback: None,
+            trending: None,
+            vectors: None,
+            audio: None,
+            costs: None,
+            waits: None,
+            aggregates: None,
+            run_outputs: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flow_runs_against_mocks() {
+        let config: HarnessConfig = serde_yaml::from_str(FIXTURES).unwrap();
+        let harness = Arc::new(Harness::new(config));
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+
+        let run = scope(Some(harness.clone()), runner().execute(&flow(), &repo, Some(HashMap::new())))
+            .await
+            .unwrap();
+        assert_eq!(run.status, RunStatus::Success, "{:?}", run.error);
+
+        let requests = harness.requests(Some("harness-flow"));
+        // The feed, then the item's page
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, "https://feeds.example.com/rss");
+        assert!(requests.iter().all(|r| r.handling == Handling::Mocked && r.node_id.as_deref() == Some("feed")));
+
+        let prompts = harness.prompts(Some("harness-flow"));
+        assert_eq!(prompts.len(), 1);
+        assert!(prompts[0].prompt.contains("Rust 2.0 released"));
+        assert_eq!(prompts[0].output.as_deref(), Some("One launch today: Rust 2.0."));
+    }
+
+    #[tokio::test]
+    async fn test_unmocked_requests_are_refused() {
+        let harness = Arc::new(Harness::new(HarnessConfig::default()));
+        let client = reqwest::Client::new();
+        let intercept = |url: &str| harness.intercept(client.post(url).body("hi").build().unwrap(), None);
+
+        let Intercepted::Answered(response) = intercept("https://api.github.com/repos/a/b/pulls?token=x") else {
+            panic!("should not reach GitHub");
+        };
+        assert_eq!(response.status(), 502);
+        let Intercepted::Answered(response) = intercept("https://hooks.slack.com/services/T0/B0/x") else {
+            panic!("Slack webhooks are mocked by default");
+        };
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert!(matches!(intercept("http://127.0.0.1:11434/api/chat"), Intercepted::Send(_)));
+
+        let requests = harness.requests(None);
+        let handled: Vec<Handling> = requests.iter().map(|r| r.handling).collect();
+        assert_eq!(handled, [Handling::Refused, Handling::Mocked, Handling::Loopback]);
+        assert_eq!(requests[0].url, "https://api.github.com/repos/a/b/pulls");
+        assert_eq!(requests[1].body.as_deref(), Some("hi"));
+    }
+}
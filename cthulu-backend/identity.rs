@@ -0,0 +1,96 @@
+//! The User-Agent cthulu identifies itself with, per integration. Some APIs
+//! and sites require naming the operator, so each can be overridden, with
+//! an optional contact URL appended the way crawlers do:
+//! `CTHULU_USER_AGENT_GITHUB=acme-digest/1.0` plus
+//! `CTHULU_CONTACT_URL=https://acme.dev/bot` sends
+//! `acme-digest/1.0 (+https://acme.dev/bot)`. `CTHULU_USER_AGENT` and
+//! `CTHULU_CONTACT_URL` apply to every integration without its own.
+
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integration {
+    /// The GitHub API.
+    Github,
+    /// Fetching flow templates from GitHub.
+    Templates,
+    /// Market data APIs.
+    Market,
+    /// Everything else: feeds, pages, webhooks and sinks.
+    Http,
+}
+
+impl Integration {
+    const ALL: [Self; 4] = [Self::Github, Self::Templates, Self::Market, Self::Http];
+
+    fn env_suffix(self) -> &'static str {
+        match self {
+            Self::Github => "GITHUB",
+            Self::Templates => "TEMPLATES",
+            Self::Market => "MARKET",
+            Self::Http => "HTTP",
+        }
+    }
+
+    fn default_product(self) -> &'static str {
+        match self {
+            Self::Templates => "cthulu-studio/1.0",
+            Self::Github | Self::Market | Self::Http => "cthulu-bot",
+        }
+    }
+}
+
+/// The User-Agent for `integration`, read from the environment once.
+pub fn user_agent(integration: Integration) -> &'static str {
+    static AGENTS: OnceLock<Vec<String>> = OnceLock::new();
+    let agents = AGENTS.get_or_init(|| {
+        Integration::ALL
+            .iter()
+            .map(|&i| resolve(i, |key| std::env::var(key).ok()))
+            .collect()
+    });
+    let index = Integration::ALL.iter().position(|&i| i == integration).unwrap();
+    &agents[index]
+}
+
+fn resolve(integration: Integration, env: impl Fn(&str) -> Option<String>) -> String {
+    let var = |key: &str| env(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let setting = |name: &str| var(&format!("{name}_{}", integration.env_suffix())).or_else(|| var(name));
+    let product = setting("CTHULU_USER_AGENT").unwrap_or_else(|| integration.default_product().to_string());
+    match setting("CTHULU_CONTACT_URL") {
+        Some(url) => format!("{product} (+{url})"),
+        None => product,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve_with(integration: Integration, vars: &[(&str, &str)]) -> String {
+        let vars: HashMap<String, String> =
+            vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        resolve(integration, |key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn test_user_agent_defaults() {
+        assert_eq!(resolve_with(Integration::Github, &[]), "cthulu-bot");
+        assert_eq!(resolve_with(Integration::Templates, &[]), "cthulu-studio/1.0");
+    }
+
+    #[test]
+    fn test_user_agent_overrides() {
+        let vars = [
+            ("CTHULU_USER_AGENT", "acme-digest/1.0"),
+            ("CTHULU_USER_AGENT_MARKET", "acme-markets/2.0"),
+            ("CTHULU_CONTACT_URL", "https://acme.dev/bot"),
+            ("CTHULU_CONTACT_URL_GITHUB", " "),
+        ];
+        assert_eq!(resolve_with(Integration::Http, &vars), "acme-digest/1.0 (+https://acme.dev/bot)");
+        assert_eq!(resolve_with(Integration::Market, &vars), "acme-markets/2.0 (+https://acme.dev/bot)");
+        // A blank override falls back to the shared setting
+        assert_eq!(resolve_with(Integration::Github, &vars), "acme-digest/1.0 (+https://acme.dev/bot)");
+    }
+}
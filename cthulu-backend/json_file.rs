@@ -0,0 +1,127 @@
+//! The small JSON files the stores under `~/.cthulu` keep their state in.
+//! Writes go to a temp file that is renamed over the old one, so a crash
+//! mid-write leaves the previous contents rather than half a file. A file
+//! that doesn't parse is never read as empty and then overwritten: `load`
+//! refuses it, and `load_or_default` moves it aside first.
+//!
+//! Each store still serializes its own read-modify-write with a lock.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// `path`'s contents; `T::default()` if there's no file yet.
+pub fn load<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    serde_json::from_str(&content).with_context(|| format!("{} is corrupt", path.display()))
+}
+
+/// [`load`] for stores that carry on empty: a file that can't be read is
+/// logged and, if it doesn't parse, renamed to `<name>.corrupt` so the next
+/// write doesn't destroy it.
+pub fn load_or_default<T: DeserializeOwned + Default>(path: &Path) -> T {
+    load(path).unwrap_or_else(|e| {
+        let aside = corrupt_path(path);
+        if path.exists() && std::fs::rename(path, &aside).is_ok() {
+            tracing::error!(error = %e, moved_to = %aside.display(), "Starting over from an unreadable state file");
+        } else {
+            tracing::error!(error = %e, "Failed to load state file");
+        }
+        T::default()
+    })
+}
+
+/// Replace `path` with `value`, creating its directory if needed.
+pub fn write<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    write_file(path, &serde_json::to_vec_pretty(value)?, false)
+}
+
+/// [`write`] for files only the owner may read; the temp file is created
+/// with mode 0600, so the contents are never world-readable.
+pub fn write_private<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    write_file(path, &serde_json::to_vec_pretty(value)?, true)
+}
+
+fn write_file(path: &Path, content: &[u8], private: bool) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let tmp_path = tmp_path(path);
+    let _ = std::fs::remove_file(&tmp_path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    let mut file = options
+        .open(&tmp_path)
+        .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+    file.write_all(content)
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path).with_context(|| format!("failed to rename {}", path.display()))
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".tmp")
+}
+
+fn corrupt_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".corrupt")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("things.json");
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), Vec::<u32>::new());
+
+        write(&path, &[1, 2, 3]).unwrap();
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), [1, 2, 3]);
+        assert!(!tmp_path(&path).exists());
+    }
+
+    #[test]
+    fn test_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("things.json");
+        std::fs::write(&path, "[1, 2").unwrap();
+        assert!(load::<Vec<u32>>(&path).is_err());
+
+        assert_eq!(load_or_default::<Vec<u32>>(&path), Vec::<u32>::new());
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(corrupt_path(&path)).unwrap(), "[1, 2");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secret.json");
+        write_private(&path, "x").unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+}
@@ -1,12 +1,24 @@
+#![recursion_limit = "256"]
+
 mod agent_sdk;
 mod agents;
 mod config;
+mod egress;
 mod flows;
 mod git;
 mod github;
+mod harness;
+mod identity;
+mod json_file;
+mod maintenance;
+mod monitor;
 mod prompts;
+mod platform;
 mod sandbox;
+mod secrets;
 mod api;
+mod service;
+mod systemd;
 mod tasks;
 mod templates;
 mod watcher;
@@ -18,10 +30,11 @@ use clap::Parser;
 use dotenvy::dotenv;
 use sentry::integrations::tower::{NewSentryLayer, SentryHttpLayer};
 use std::error::Error;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpListener;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::{EnvFilter, Layer};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
@@ -46,6 +59,15 @@ enum Cli {
         /// Start with all flow triggers disabled
         #[arg(long)]
         start_disabled: bool,
+        /// Answer outbound requests with mocks and executors with canned
+        /// output, for testing flows end to end, with an optional fixtures file
+        #[arg(long, value_name = "FIXTURES")]
+        test_harness: Option<Option<PathBuf>>,
+    },
+    /// Install or remove cthulu as a login service for the current user
+    Service {
+        #[command(subcommand)]
+        action: service::ServiceAction,
     },
 }
 
@@ -58,34 +80,43 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = std::env::args().collect();
     let cli = if args.len() <= 1 {
         // No subcommand given, default to serve
-        Cli::Serve { start_disabled: false }
+        Cli::Serve { start_disabled: false, test_harness: None }
     } else {
         Cli::parse()
     };
 
     match cli {
-        Cli::Serve { start_disabled } => run_server(start_disabled).await,
+        Cli::Serve { start_disabled, test_harness } => run_server(start_disabled, test_harness).await,
+        Cli::Service { action } => Ok(service::run(action)?),
     }
 }
 
-async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
+async fn run_server(start_disabled: bool, test_harness: Option<Option<PathBuf>>) -> Result<(), Box<dyn Error>> {
     let config = config::Config::from_env();
 
     let filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("cthulu=info,tower_http=warn,hyper=warn"));
 
+    // Run logs keep executors' debug events (tool calls) whatever RUST_LOG says
+    let run_log_filter = tracing_subscriber::filter::Targets::new().with_target("cthulu", tracing::Level::DEBUG);
+
     tracing_subscriber::registry()
-        .with(filter)
-        .with(tracing_tree::HierarchicalLayer::new(2).with_targets(true).with_bracketed_fields(false))
-        .with(sentry::integrations::tracing::layer().event_filter(
-            |metadata| match *metadata.level() {
-                tracing::Level::ERROR => sentry::integrations::tracing::EventFilter::Event,
-                tracing::Level::WARN | tracing::Level::INFO => {
-                    sentry::integrations::tracing::EventFilter::Breadcrumb
-                }
-                _ => sentry::integrations::tracing::EventFilter::Ignore,
-            },
-        ))
+        .with(
+            tracing_tree::HierarchicalLayer::new(2)
+                .with_targets(true)
+                .with_bracketed_fields(false)
+                .and_then(sentry::integrations::tracing::layer().event_filter(
+                    |metadata| match *metadata.level() {
+                        tracing::Level::ERROR => sentry::integrations::tracing::EventFilter::Event,
+                        tracing::Level::WARN | tracing::Level::INFO => {
+                            sentry::integrations::tracing::EventFilter::Breadcrumb
+                        }
+                        _ => sentry::integrations::tracing::EventFilter::Ignore,
+                    },
+                ))
+                .with_filter(filter),
+        )
+        .with(flows::run_logs::RunLogLayer.with_filter(run_log_filter))
         .init();
 
     let _guard = sentry::init((
@@ -102,23 +133,55 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
 
     let http_client = Arc::new(
         reqwest::Client::builder()
+            .user_agent(identity::user_agent(identity::Integration::Http))
             .connect_timeout(Duration::from_secs(10))
             .timeout(Duration::from_secs(30))
             .build()
             .context("failed to build HTTP client")?,
     );
 
-    let github_client: Option<Arc<dyn GithubClient>> = std::env::var("GITHUB_TOKEN")
-        .ok()
-        .filter(|t| !t.is_empty())
-        .map(|token| {
-            Arc::new(HttpGithubClient::new((*http_client).clone(), token)) as Arc<dyn GithubClient>
-        });
+    if let Some(fixtures) = test_harness {
+        let config = match fixtures {
+            Some(path) => harness::HarnessConfig::load(&path)?,
+            None => harness::HarnessConfig::default(),
+        };
+        let harness = harness::install(config);
+        tracing::warn!(
+            mocks = harness.config().mocks.len(),
+            executor_fixtures = harness.config().executors.len(),
+            "Test harness mode: outbound requests are mocked and executors return canned output"
+        );
+    }
+
+    // In test harness mode GitHub is mocked, so any token will do. A GitHub
+    // App, when configured, takes the place of GITHUB_TOKEN
+    let github_app = match harness::current() {
+        Some(_) => None,
+        None => github::app::AppConfig::from_env()?,
+    };
+    let github_client: Option<Arc<dyn GithubClient>> = match github_app {
+        Some(config) => {
+            let app = github::app::AppAuth::new((*http_client).clone(), &config)?;
+            tracing::info!(app_id = %config.app_id, installation = config.installation_id, "Authenticating to GitHub as an app");
+            Some(Arc::new(HttpGithubClient::with_app((*http_client).clone(), app)))
+        }
+        None => {
+            let github_token = match harness::current() {
+                Some(_) => Some("test-harness".to_string()),
+                None => std::env::var("GITHUB_TOKEN").ok(),
+            };
+            github_token.filter(|t| !t.is_empty()).map(|token| {
+                Arc::new(HttpGithubClient::new((*http_client).clone(), token)) as Arc<dyn GithubClient>
+            })
+        }
+    };
 
     // Initialize data directory
     let base_dir = dirs::home_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join(".cthulu");
+    secrets::init(&base_dir);
+    flows::run_logs::init(&base_dir);
 
     // Initialize flow repository (flows + runs)
     // Keep concrete Arc for the file watcher, upcast to trait object for AppState.
@@ -178,35 +241,12 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
     let sessions_path = base_dir.join("sessions.yaml");
     let persisted_sessions = api::load_sessions(&sessions_path);
 
-    // Read OAuth token: macOS Keychain first, then CLAUDE_CODE_OAUTH_TOKEN env
-    let oauth_token: Option<String> = {
-        // Try macOS Keychain
-        let keychain_result = std::process::Command::new("security")
-            .args(["find-generic-password", "-s", "Claude Code-credentials", "-w"])
-            .output();
-        match keychain_result {
-            Ok(output) if output.status.success() => {
-                let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                // Parse JSON to extract accessToken
-                if let Ok(v) = serde_json::from_str::<serde_json::Value>(&raw) {
-                    let token = v["claudeAiOauth"]["accessToken"].as_str().map(String::from);
-                    if token.is_some() {
-                        tracing::info!("OAuth token loaded from macOS Keychain");
-                    }
-                    token
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
-        .or_else(|| {
-            std::env::var("CLAUDE_CODE_OAUTH_TOKEN").ok().map(|t| {
-                tracing::info!("OAuth token loaded from CLAUDE_CODE_OAUTH_TOKEN env");
-                t
-            })
-        })
-    };
+    // Read OAuth token: the Claude CLI's login (Keychain or credentials file),
+    // then CLAUDE_CODE_OAUTH_TOKEN env
+    let oauth_token = api::auth::repository::read_oauth_token();
+    if oauth_token.is_some() {
+        tracing::info!("OAuth token loaded");
+    }
 
     // Initialize sandbox provider (before scheduler, so scheduler can use it)
     //
@@ -297,6 +337,9 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
     // Interact sessions (shared between scheduler and AppState)
     let interact_sessions = Arc::new(tokio::sync::RwLock::new(persisted_sessions));
 
+    // With a webhook secret, GitHub pushes PR events instead of being polled.
+    let github_webhook_secret = std::env::var("GITHUB_WEBHOOK_SECRET").ok().filter(|s| !s.is_empty());
+
     // Create and start the flow scheduler
     let scheduler = Arc::new(FlowScheduler::new(
         flow_repo.clone(),
@@ -305,25 +348,75 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
         events_tx.clone(),
         sandbox_provider.clone(),
         agent_repo.clone(),
+        prompt_repo.clone(),
         interact_sessions.clone(),
         sessions_path.clone(),
         base_dir.clone(),
         session_streams.clone(),
+        github_webhook_secret.is_some(),
     ));
+    // CTHULU_READ_ONLY=paused also holds triggers, as maintenance mode
+    // does, with no way to end it short of a restart
+    let read_only = std::env::var("CTHULU_READ_ONLY").unwrap_or_default();
+    let read_only_paused = read_only == "paused";
+    let read_only = read_only_paused || matches!(read_only.as_str(), "1" | "true");
+    if read_only_paused {
+        tracing::info!("Read-only with scheduling paused (CTHULU_READ_ONLY=paused)");
+        maintenance::enable();
+    } else if read_only {
+        tracing::info!("Read-only: mutating API requests are refused (CTHULU_READ_ONLY)");
+    }
+    // Clustered, only the replica holding the scheduler lease runs triggers
+    // and settles interrupted runs
+    let leases = match flows::leader::Coordination::from_env() {
+        Some(coordination) => {
+            tracing::info!(replica = %coordination.replica_id, "Clustered: scheduling while holding the scheduler lease");
+            scheduler.set_standby(true);
+            let leases = Arc::new(flows::leader::LeaseTable::connect(&coordination).await?);
+            scheduler.forward_trigger_changes(leases.clone());
+            Some(leases)
+        }
+        None => {
+            scheduler.recover_interrupted_runs(&[]).await;
+            scheduler.replay_trigger_events().await;
+            None
+        }
+    };
     if start_disabled {
         tracing::info!("Starting with all flow triggers disabled (--start-disabled)");
         let flows = flow_repo.list_flows().await;
         for mut flow in flows {
             if flow.enabled {
                 flow.enabled = false;
+                flow.version += 1;
                 if let Err(e) = flow_repo.save_flow(flow).await {
                     tracing::warn!(error = %e, "Failed to disable flow");
                 }
             }
         }
-    } else {
+    } else if leases.is_none() {
         scheduler.start_all().await;
     }
+    if let Some(leases) = &leases {
+        tokio::spawn(flows::leader::leader_loop(scheduler.clone(), leases.clone()));
+    }
+
+    // Time-shifted subscription deliveries outlive the runs that queued them
+    tokio::spawn(flows::subscriptions::delivery_loop(
+        flows::subscriptions::SubscriptionStore::new(&base_dir),
+        http_client.clone(),
+    ));
+    tokio::spawn(monitor::monitor_loop(monitor::Thresholds::from_env(), http_client.clone()));
+    tokio::spawn(flows::retention::gc_loop(
+        file_flow_repo.clone(),
+        flows::run_outputs::RunOutputStore::new(&base_dir),
+        flows::run_logs::RunLogStore::new(&base_dir),
+        flows::retention::Caches {
+            sources: tasks::sources::cache::SourceCache::new(&base_dir),
+            executors: tasks::executors::cache::ExecutorCache::new(&base_dir),
+        },
+        flows::retention::Retention::from_env(),
+    ));
 
     // Resolve static/ directory: prefer CTHULU_STATIC_DIR env var,
     // then look relative to the current working directory (repo root during dev),
@@ -369,6 +462,11 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
         pending_permissions: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
         global_hook_tx: Arc::new(tokio::sync::broadcast::channel::<String>(256).0),
         server_port: config.port,
+        feed_token: std::env::var("CTHULU_FEED_TOKEN").ok().filter(|t| !t.is_empty()),
+        github_webhook_secret,
+        slack_signing_secret: std::env::var("SLACK_SIGNING_SECRET").ok().filter(|s| !s.is_empty()),
+        intake_limiter: flows::intake::IntakeLimiter::from_env(),
+        read_only,
     };
 
     // Start file change watcher (keeps caches in sync with external edits)
@@ -383,21 +481,60 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
 
     let live_processes = app_state.live_processes.clone();
     let sdk_sessions = app_state.sdk_sessions.clone();
+    let flow_repo = app_state.flow_repo.clone();
 
     let app = api::create_app(app_state)
         .layer(SentryHttpLayer::new().enable_transaction())
         .layer(NewSentryLayer::<Request<Body>>::new_from_top());
 
     let port = config.port;
-    let addr = format!("0.0.0.0:{port}");
-    let listener = TcpListener::bind(&addr).await?;
-    println!("Listening on http://{addr}");
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+    // Under systemd socket activation the socket outlives restarts, so
+    // connections queue instead of being refused while we come back up
+    let listener = match systemd::activated_listener()? {
+        Some(listener) => TcpListener::from_std(listener)?,
+        None => TcpListener::bind(format!("0.0.0.0:{port}")).await?,
+    };
+    println!("Listening on http://{}", listener.local_addr()?);
+    systemd::notify("READY=1");
+    if let Some(interval) = systemd::watchdog_interval() {
+        systemd::spawn_watchdog(interval);
+    }
+    // On a signal, triggers hold and new runs are refused (maintenance mode)
+    // while the running ones get up to CTHULU_SHUTDOWN_GRACE_SECS to finish
+    let grace = std::env::var("CTHULU_SHUTDOWN_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_SHUTDOWN_GRACE_SECS);
+    // Peer addresses rate-limit the public intake endpoint
+    axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            systemd::notify("STOPPING=1");
+            tracing::info!(grace_secs = grace, "shutting down: draining running flows");
+            let status = maintenance::drain(Duration::from_secs(grace)).await;
+            if !status.drained {
+                tracing::warn!(running = status.running.len(), "Runs still going after the shutdown grace period");
+            }
+        })
         .await?;
+    if let Some(leases) = &leases {
+        leases.release_scheduler().await;
+    }
+
+    // Runs still going die with the process; record them as interrupted
+    // before their executors are killed under them
+    let running = maintenance::status().running;
+    if !running.is_empty() {
+        let marked = flows::recovery::interrupt_on_shutdown(&*flow_repo, &running).await;
+        tracing::warn!(marked, "shutting down: marked unfinished runs interrupted");
+    }
 
     // Server has stopped — kill all child processes then exit.
     tracing::info!("shutting down: killing child processes");
+    let killed = platform::kill_armed_trees();
+    if killed > 0 {
+        tracing::info!(killed, "shutting down: killed executor processes");
+    }
     {
         let mut pool = live_processes.lock().await;
         for (key, mut proc) in pool.drain() {
@@ -420,6 +557,9 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// How long a shutdown waits for running flows by default.
+const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 20;
+
 /// Wait for Ctrl+C or SIGTERM to initiate graceful shutdown.
 async fn shutdown_signal() {
     let ctrl_c = tokio::signal::ctrl_c();
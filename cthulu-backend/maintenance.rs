@@ -0,0 +1,217 @@
+//! Maintenance mode (`POST /api/admin/maintenance`): while it's on, cron
+//! fires, pollers and incoming events hold until it ends instead of
+//! starting runs, and the runs already going drain. Runs only sleeping out
+//! a saved `wait` count as drained — they resume after a restart.
+//!
+//! It's the switch for stopping everything for a while and lives in memory.
+//! To skip cron fires alone, and keep skipping them across restarts, pause
+//! the schedules instead (`flows::pauses`).
+
+use std::collections::BTreeSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::watch;
+
+static MAINTENANCE: LazyLock<Maintenance> = LazyLock::new(Maintenance::new);
+
+#[derive(Default)]
+struct State {
+    since: Option<DateTime<Utc>>,
+    drained_at: Option<DateTime<Utc>>,
+    running: BTreeSet<String>,
+    held: BTreeSet<String>,
+}
+
+impl State {
+    fn drained(&self) -> bool {
+        self.running.iter().all(|run_id| self.held.contains(run_id))
+    }
+
+    /// Note when maintenance mode's runs have all drained.
+    fn settle(&mut self) {
+        if self.since.is_some() && self.drained_at.is_none() && self.drained() {
+            self.drained_at = Some(Utc::now());
+            tracing::info!("Maintenance: running work drained");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Status {
+    pub enabled: bool,
+    pub since: Option<DateTime<Utc>>,
+    /// Runs still executing.
+    pub running: Vec<String>,
+    /// Runs only sleeping out a saved wait.
+    pub held: Vec<String>,
+    pub drained: bool,
+    pub drained_at: Option<DateTime<Utc>>,
+}
+
+struct Maintenance {
+    /// Whether it's on, for the tasks holding until it ends.
+    enabled: watch::Sender<bool>,
+    state: Mutex<State>,
+}
+
+impl Maintenance {
+    fn new() -> Self {
+        Self { enabled: watch::Sender::new(false), state: Mutex::new(State::default()) }
+    }
+
+    fn status(&self) -> Status {
+        let state = self.state.lock().unwrap();
+        Status {
+            enabled: state.since.is_some(),
+            since: state.since,
+            running: state.running.difference(&state.held).cloned().collect(),
+            held: state.held.iter().cloned().collect(),
+            drained: state.drained(),
+            drained_at: state.drained_at,
+        }
+    }
+
+    fn enable(&self) -> Status {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.since.is_none() {
+                state.since = Some(Utc::now());
+                tracing::info!(running = state.running.len(), "Maintenance mode on, draining");
+                state.settle();
+            }
+        }
+        self.enabled.send_replace(true);
+        self.status()
+    }
+
+    fn disable(&self) -> Status {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.since.take().is_some() {
+                tracing::info!("Maintenance mode off, resuming");
+            }
+            state.drained_at = None;
+        }
+        self.enabled.send_replace(false);
+        self.status()
+    }
+
+    async fn resumed(&self) -> bool {
+        let mut enabled = self.enabled.subscribe();
+        if !*enabled.borrow_and_update() {
+            return false;
+        }
+        let _ = enabled.wait_for(|on| !on).await;
+        true
+    }
+
+    fn track(&self, run_id: &str) -> Running<'_> {
+        self.state.lock().unwrap().running.insert(run_id.to_string());
+        Running(self, run_id.to_string())
+    }
+
+    fn hold(&self, run_id: &str) -> Held<'_> {
+        let mut state = self.state.lock().unwrap();
+        state.held.insert(run_id.to_string());
+        state.settle();
+        Held(self, run_id.to_string())
+    }
+}
+
+/// Marks a run as executing until dropped.
+pub struct Running<'a>(&'a Maintenance, String);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.running.remove(&self.1);
+        state.held.remove(&self.1);
+        state.settle();
+    }
+}
+
+/// Marks a run as only sleeping out saved waits until dropped.
+pub struct Held<'a>(&'a Maintenance, String);
+
+impl Drop for Held<'_> {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().held.remove(&self.1);
+    }
+}
+
+pub fn status() -> Status {
+    MAINTENANCE.status()
+}
+
+/// Turn maintenance mode on (or keep it on).
+pub fn enable() -> Status {
+    MAINTENANCE.enable()
+}
+
+/// Turn maintenance mode off, letting held triggers and events go.
+pub fn disable() -> Status {
+    MAINTENANCE.disable()
+}
+
+/// Turn maintenance mode on and wait up to `wait` for running work to drain.
+pub async fn drain(wait: Duration) -> Status {
+    let mut status = MAINTENANCE.enable();
+    let deadline = tokio::time::Instant::now() + wait;
+    while !status.drained && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        status = MAINTENANCE.status();
+    }
+    status
+}
+
+pub fn enabled() -> bool {
+    *MAINTENANCE.enabled.borrow()
+}
+
+/// Wait for maintenance mode to end; `true` when it was on.
+pub async fn resumed() -> bool {
+    MAINTENANCE.resumed().await
+}
+
+pub fn track(run_id: &str) -> Running<'static> {
+    MAINTENANCE.track(run_id)
+}
+
+pub fn hold(run_id: &str) -> Held<'static> {
+    MAINTENANCE.hold(run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_and_resume() {
+        let maintenance = Maintenance::new();
+        let run = maintenance.track("run");
+        let waiting = maintenance.track("wait");
+        let status = maintenance.enable();
+        assert!(status.enabled);
+        assert!(!status.drained);
+        assert_eq!(status.running, ["run", "wait"]);
+
+        let held = maintenance.hold("wait");
+        drop(run);
+        let status = maintenance.status();
+        assert!(status.drained && status.drained_at.is_some());
+        assert_eq!(status.held, ["wait"]);
+
+        let resumer = maintenance.resumed();
+        tokio::pin!(resumer);
+        assert!(futures::poll!(&mut resumer).is_pending());
+        maintenance.disable();
+        assert!(resumer.await);
+        assert!(!maintenance.resumed().await);
+        drop(held);
+        drop(waiting);
+        assert!(maintenance.status().running.is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+//! Self-monitoring: the server's memory, open files, async tasks and child
+//! processes, served at `/api/metrics` and checked against `MONITOR_MAX_*`
+//! limits, with an alert through `MONITOR_ALERT_SINK` when one is crossed.
+//! Leaked `claude` children show up here as a growing child count.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::config::SinkConfig;
+use crate::tasks::pipeline::resolve_sinks;
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// One sample of the server's resource use. Counts the platform can't
+/// report (e.g. file descriptors on Windows) are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResourceUsage {
+    pub rss_bytes: Option<u64>,
+    pub open_fds: Option<u64>,
+    /// Tasks alive on the tokio runtime.
+    pub tokio_tasks: u64,
+    /// Processes descended from this one (claude CLIs and their tools).
+    pub child_processes: Option<u64>,
+}
+
+impl ResourceUsage {
+    pub fn sample() -> Self {
+        let (rss_bytes, child_processes) = process_stats();
+        Self {
+            rss_bytes,
+            open_fds: open_fds(),
+            tokio_tasks: tokio::runtime::Handle::try_current()
+                .map_or(0, |rt| rt.metrics().num_alive_tasks() as u64),
+            child_processes,
+        }
+    }
+
+    /// Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, value: Option<u64>| {
+            if let Some(value) = value {
+                out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"));
+            }
+        };
+        gauge("cthulu_resident_memory_bytes", "Resident set size of the server process.", self.rss_bytes);
+        gauge("cthulu_open_fds", "Open file descriptors of the server process.", self.open_fds);
+        gauge("cthulu_tokio_tasks", "Tasks alive on the async runtime.", Some(self.tokio_tasks));
+        gauge("cthulu_child_processes", "Processes descended from the server.", self.child_processes);
+        out
+    }
+}
+
+/// The `MONITOR_MAX_*` limits; unset ones aren't checked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Thresholds {
+    pub max_rss_bytes: Option<u64>,
+    pub max_fds: Option<u64>,
+    pub max_tasks: Option<u64>,
+    pub max_children: Option<u64>,
+}
+
+impl Thresholds {
+    pub fn from_env() -> Self {
+        let env = |key: &str| std::env::var(key).ok();
+        Self::from_raw_values(
+            env("MONITOR_MAX_RSS_MB").as_deref(),
+            env("MONITOR_MAX_FDS").as_deref(),
+            env("MONITOR_MAX_TASKS").as_deref(),
+            env("MONITOR_MAX_CHILDREN").as_deref(),
+        )
+    }
+
+    /// Used directly in tests to avoid mutating process-global environment.
+    pub fn from_raw_values(
+        rss_mb: Option<&str>,
+        fds: Option<&str>,
+        tasks: Option<&str>,
+        children: Option<&str>,
+    ) -> Self {
+        let parse = |v: Option<&str>| v.and_then(|v| v.trim().parse::<u64>().ok()).filter(|n| *n > 0);
+        Self {
+            max_rss_bytes: parse(rss_mb).map(|mb| mb * 1024 * 1024),
+            max_fds: parse(fds),
+            max_tasks: parse(tasks),
+            max_children: parse(children),
+        }
+    }
+
+    /// Each limit `usage` is over, by name, with a line describing it.
+    pub fn breaches(&self, usage: &ResourceUsage) -> Vec<(&'static str, String)> {
+        let mut breaches = Vec::new();
+        if let (Some(rss), Some(max)) = (usage.rss_bytes, self.max_rss_bytes)
+            && rss > max
+        {
+            let mb = |b: u64| b / (1024 * 1024);
+            breaches.push(("rss", format!("memory {} MB (limit {} MB)", mb(rss), mb(max))));
+        }
+        let mut check = |name: &'static str, label: &str, value: Option<u64>, max: Option<u64>| {
+            if let (Some(value), Some(max)) = (value, max)
+                && value > max
+            {
+                breaches.push((name, format!("{label} {value} (limit {max})")));
+            }
+        };
+        check("fds", "open file descriptors", usage.open_fds, self.max_fds);
+        check("tasks", "async tasks", Some(usage.tokio_tasks), self.max_tasks);
+        check("children", "child processes", usage.child_processes, self.max_children);
+        breaches
+    }
+}
+
+/// Sample every `MONITOR_INTERVAL_SECS` (default 60) and alert once per
+/// crossed limit, again only after it has recovered.
+pub async fn monitor_loop(thresholds: Thresholds, http_client: Arc<reqwest::Client>) {
+    let interval = std::env::var("MONITOR_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|s| *s > 0)
+        .map_or(DEFAULT_INTERVAL, Duration::from_secs);
+    let sink = match alert_sink() {
+        Ok(sink) => sink,
+        Err(e) => {
+            tracing::warn!(error = %e, "Invalid MONITOR_ALERT_SINK, resource alerts will only be logged");
+            None
+        }
+    };
+
+    let mut active: BTreeSet<&'static str> = BTreeSet::new();
+    let mut tick = tokio::time::interval(interval);
+    loop {
+        tick.tick().await;
+        let usage = tokio::task::spawn_blocking(ResourceUsage::sample)
+            .await
+            .unwrap_or_default();
+        let breaches = thresholds.breaches(&usage);
+
+        let recovered: Vec<_> = active
+            .iter()
+            .filter(|name| !breaches.iter().any(|(b, _)| b == *name))
+            .copied()
+            .collect();
+        for name in recovered {
+            tracing::info!(resource = name, "Resource usage back under its limit");
+            active.remove(name);
+        }
+
+        let new: Vec<&String> = breaches
+            .iter()
+            .filter(|(name, _)| active.insert(name))
+            .map(|(_, line)| line)
+            .collect();
+        if new.is_empty() {
+            continue;
+        }
+        let text = format!(
+            "⚠️ cthulu resource limit exceeded\n{}",
+            new.iter().map(|line| format!("• {line}")).collect::<Vec<_>>().join("\n")
+        );
+        tracing::warn!(usage = ?usage, "{text}");
+        if let Some(config) = &sink {
+            let delivered = match resolve_sinks(std::slice::from_ref(config), &http_client) {
+                Ok(sinks) => futures::future::try_join_all(sinks.iter().map(|s| s.deliver(&text)))
+                    .await
+                    .map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = delivered {
+                tracing::warn!(error = %e, "Failed to deliver resource alert");
+            }
+        }
+    }
+}
+
+/// `MONITOR_ALERT_SINK`: a sink config as JSON, e.g.
+/// `{"type": "slack", "webhook_url_env": "SLACK_WEBHOOK_URL"}`.
+fn alert_sink() -> Result<Option<SinkConfig>> {
+    match std::env::var("MONITOR_ALERT_SINK") {
+        Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+            .map(Some)
+            .context("MONITOR_ALERT_SINK must be a sink config as JSON"),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(unix)]
+fn open_fds() -> Option<u64> {
+    let dir = if cfg!(target_os = "linux") { "/proc/self/fd" } else { "/dev/fd" };
+    // Less the one reading the directory
+    Some(std::fs::read_dir(dir).ok()?.count().saturating_sub(1) as u64)
+}
+
+#[cfg(not(unix))]
+fn open_fds() -> Option<u64> {
+    None
+}
+
+/// Resident memory and descendant count.
+#[cfg(target_os = "linux")]
+fn process_stats() -> (Option<u64>, Option<u64>) {
+    let rss = std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| status_rss(&status));
+    let parents = std::fs::read_dir("/proc").ok().map(|entries| {
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+                let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+                Some((pid, stat_ppid(&stat)?))
+            })
+            .collect::<HashMap<_, _>>()
+    });
+    (rss, parents.map(|parents| descendants(&parents, std::process::id())))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_stats() -> (Option<u64>, Option<u64>) {
+    let Ok(output) = std::process::Command::new("ps").args(["-A", "-o", "pid=,ppid=,rss="]).output() else {
+        return (None, None);
+    };
+    let table = parse_ps(&String::from_utf8_lossy(&output.stdout));
+    let pid = std::process::id();
+    let rss = table.iter().find(|(p, _, _)| *p == pid).map(|(_, _, kb)| kb * 1024);
+    let parents = table.iter().map(|(pid, ppid, _)| (*pid, *ppid)).collect();
+    (rss, Some(descendants(&parents, pid)))
+}
+
+#[cfg(not(unix))]
+fn process_stats() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+/// `VmRSS` from `/proc/<pid>/status`, in bytes.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn status_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// The parent pid in `/proc/<pid>/stat`; the command name before it may
+/// itself contain spaces and parentheses.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn stat_ppid(stat: &str) -> Option<u32> {
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    after_comm.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Rows of `ps -o pid=,ppid=,rss=`.
+#[cfg_attr(any(target_os = "linux", not(unix)), allow(dead_code))]
+fn parse_ps(output: &str) -> Vec<(u32, u32, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split_whitespace().map(|c| c.parse::<u64>().ok());
+            Some((cols.next()?? as u32, cols.next()?? as u32, cols.next()??))
+        })
+        .collect()
+}
+
+/// How many processes descend from `root`, given each one's parent.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn descendants(parents: &HashMap<u32, u32>, root: u32) -> u64 {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (pid, ppid) in parents {
+        if pid != ppid {
+            children.entry(*ppid).or_default().push(*pid);
+        }
+    }
+    let mut stack = vec![root];
+    let mut count = 0;
+    while let Some(pid) = stack.pop() {
+        if let Some(kids) = children.get(&pid) {
+            count += kids.len() as u64;
+            stack.extend(kids);
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proc_parsing() {
+        let status = "Name:\tcthulu\nVmPeak:\t  900 kB\nVmRSS:\t  51200 kB\nThreads:\t12\n";
+        assert_eq!(status_rss(status), Some(51200 * 1024));
+        assert_eq!(stat_ppid("4242 (claude (node)) S 100 4242 4242 0 -1"), Some(100));
+        assert_eq!(
+            parse_ps("  100     1  2048\n  200   100   512\nbad line\n"),
+            vec![(100, 1, 2048), (200, 100, 512)]
+        );
+    }
+
+    #[test]
+    fn test_descendants() {
+        // 100 → 200 → {300, 301}; 400 belongs to someone else
+        let parents = HashMap::from([(100, 1), (200, 100), (300, 200), (301, 200), (400, 1)]);
+        assert_eq!(descendants(&parents, 100), 3);
+        assert_eq!(descendants(&parents, 300), 0);
+    }
+
+    #[test]
+    fn test_breaches() {
+        let thresholds = Thresholds::from_raw_values(Some("512"), None, Some("x"), Some("4"));
+        assert_eq!(thresholds.max_tasks, None);
+        let usage = ResourceUsage {
+            rss_bytes: Some(600 * 1024 * 1024),
+            open_fds: Some(10_000),
+            tokio_tasks: 50_000,
+            child_processes: Some(4),
+        };
+        let breaches = thresholds.breaches(&usage);
+        assert_eq!(breaches, vec![("rss", "memory 600 MB (limit 512 MB)".to_string())]);
+
+        let usage = ResourceUsage { child_processes: Some(9), ..usage };
+        let names: Vec<_> = thresholds.breaches(&usage).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["rss", "children"]);
+    }
+
+    #[test]
+    fn test_prometheus_skips_unknown_values() {
+        let usage = ResourceUsage { rss_bytes: Some(1024), open_fds: None, tokio_tasks: 3, child_processes: Some(0) };
+        let text = usage.to_prometheus();
+        assert!(text.contains("cthulu_resident_memory_bytes 1024\n"));
+        assert!(text.contains("# TYPE cthulu_tokio_tasks gauge\ncthulu_tokio_tasks 3\n"));
+        assert!(!text.contains("cthulu_open_fds"));
+    }
+}
@@ -0,0 +1,147 @@
+//! What differs between macOS, Linux and Windows: finding the Claude CLI and
+//! its credentials, running shell commands, and stopping a process together
+//! with everything it started.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// `CREATE_NEW_PROCESS_GROUP`, so a console Ctrl+C doesn't reach children
+/// and `taskkill /T` can find the whole tree.
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+/// The Claude CLI to run: `CLAUDE_BIN` if set, else `claude` — `claude.cmd`
+/// on Windows, where npm installs a shim and bare names only resolve `.exe`.
+pub fn claude_bin() -> String {
+    std::env::var("CLAUDE_BIN")
+        .ok()
+        .filter(|bin| !bin.is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "claude.cmd" } else { "claude" }.to_string())
+}
+
+/// Start `cmd` in its own process group so [`kill_tree`] also stops the
+/// tools and shells it spawns.
+pub fn isolate(cmd: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
+/// Best-effort termination of `pid` and its descendants.
+pub fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    {
+        // The group only exists for processes started with `isolate`
+        let _ = std::process::Command::new("kill")
+            .args(["-TERM", "--", &format!("-{pid}")])
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        let _ = std::process::Command::new("kill")
+            .args(["-TERM", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .spawn();
+    }
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .spawn();
+    }
+}
+
+/// Process trees a [`KillTreeOnDrop`] is armed for, to stop on shutdown.
+static ARMED: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/// Kills a process tree when dropped, unless disarmed — for children whose
+/// caller may give up on them mid-run (timeouts, aborted runs).
+pub struct KillTreeOnDrop(Option<u32>);
+
+impl KillTreeOnDrop {
+    pub fn new(pid: Option<u32>) -> Self {
+        if let Some(pid) = pid {
+            ARMED.lock().unwrap().insert(pid);
+        }
+        Self(pid)
+    }
+
+    /// The process finished on its own; leave it be.
+    pub fn disarm(&mut self) {
+        if let Some(pid) = self.0.take() {
+            ARMED.lock().unwrap().remove(&pid);
+        }
+    }
+}
+
+impl Drop for KillTreeOnDrop {
+    fn drop(&mut self) {
+        if let Some(pid) = self.0.take() {
+            ARMED.lock().unwrap().remove(&pid);
+            kill_tree(pid);
+        }
+    }
+}
+
+/// Kill every process tree still armed — the server is exiting, and drops
+/// won't run. Returns how many there were.
+pub fn kill_armed_trees() -> usize {
+    let pids = std::mem::take(&mut *ARMED.lock().unwrap());
+    for &pid in &pids {
+        kill_tree(pid);
+    }
+    pids.len()
+}
+
+/// The argv running `script` in the platform's shell.
+pub fn shell(script: &str) -> Vec<String> {
+    if cfg!(windows) {
+        vec!["cmd".to_string(), "/C".to_string(), script.to_string()]
+    } else {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+}
+
+/// `pattern` with a leading `~/` (or `~\` on Windows) made absolute.
+pub fn expand_home(pattern: &str) -> String {
+    let rest = pattern
+        .strip_prefix("~/")
+        .or_else(|| pattern.strip_prefix("~\\").filter(|_| cfg!(windows)));
+    match (rest, dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).display().to_string(),
+        _ => pattern.to_string(),
+    }
+}
+
+/// The Claude CLI's stored login (`{"claudeAiOauth": {...}}`): the macOS
+/// Keychain, else `~/.claude/.credentials.json`, where it lives on Linux
+/// and Windows (and on macOS when the Keychain is locked or unavailable).
+pub fn claude_credentials() -> Option<String> {
+    #[cfg(target_os = "macos")]
+    if let Some(raw) = keychain_credentials() {
+        return Some(raw);
+    }
+    let raw = std::fs::read_to_string(credentials_file()?).ok()?;
+    let raw = raw.trim();
+    (!raw.is_empty()).then(|| raw.to_string())
+}
+
+fn credentials_file() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("CLAUDE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".claude")))?;
+    Some(config_dir.join(".credentials.json"))
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_credentials() -> Option<String> {
+    let output = std::process::Command::new("security")
+        .args(["find-generic-password", "-s", "Claude Code-credentials", "-w"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if raw.is_empty() { None } else { Some(raw) }
+}
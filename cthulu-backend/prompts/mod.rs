@@ -1,9 +1,12 @@
 pub mod file_repository;
 pub mod repository;
 
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use repository::PromptRepository;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SavedPrompt {
     pub id: String,
@@ -12,4 +15,143 @@ pub struct SavedPrompt {
     pub source_flow_name: String,
     pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
+    /// Current version of `summary`; bumped every time the body changes.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Previous bodies, oldest first, so nodes can pin `prompt_ref: "id@N"`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<PromptVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVersion {
+    pub version: u32,
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl SavedPrompt {
+    /// Replace the prompt body, archiving the current one as a past version.
+    /// A no-op when the body is unchanged.
+    pub fn set_summary(&mut self, summary: String) {
+        if summary == self.summary {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.summary, summary);
+        self.history.push(PromptVersion {
+            version: self.version,
+            summary: previous,
+            created_at: Utc::now(),
+        });
+        self.version += 1;
+    }
+
+    /// Body at `version`, or the current body when `None`.
+    pub fn summary_at(&self, version: Option<u32>) -> Option<&str> {
+        match version {
+            None => Some(&self.summary),
+            Some(v) if v == self.version => Some(&self.summary),
+            Some(v) => self
+                .history
+                .iter()
+                .find(|h| h.version == v)
+                .map(|h| h.summary.as_str()),
+        }
+    }
+}
+
+/// Resolve a node's `prompt_ref` to the prompt body.
+///
+/// The reference is a prompt id or exact title, optionally pinned to a
+/// version with `@N` (e.g. `pr-review@3`); unpinned refs track the latest.
+pub async fn resolve_prompt_ref(repo: &dyn PromptRepository, reference: &str) -> Result<String> {
+    let (name, version) = match reference.rsplit_once('@') {
+        Some((name, v)) => {
+            let v = v
+                .parse::<u32>()
+                .with_context(|| format!("invalid version in prompt_ref '{reference}'"))?;
+            (name, Some(v))
+        }
+        None => (reference, None),
+    };
+
+    let prompt = match repo.get_prompt(name).await {
+        Some(p) => p,
+        None => repo
+            .list_prompts()
+            .await
+            .into_iter()
+            .find(|p| p.title == name)
+            .with_context(|| format!("prompt_ref '{name}' not found in prompt library"))?,
+    };
+
+    prompt
+        .summary_at(version)
+        .map(String::from)
+        .with_context(|| format!("prompt '{name}' has no version {}", version.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompts::file_repository::FilePromptRepository;
+    use tempfile::tempdir;
+
+    fn prompt(id: &str, title: &str, summary: &str) -> SavedPrompt {
+        SavedPrompt {
+            id: id.to_string(),
+            title: title.to_string(),
+            summary: summary.to_string(),
+            source_flow_name: String::new(),
+            tags: vec![],
+            created_at: Utc::now(),
+            version: 1,
+            history: vec![],
+        }
+    }
+
+    #[test]
+    fn test_set_summary_versions_body() {
+        let mut p = prompt("p1", "Review", "v1 body");
+        p.set_summary("v1 body".to_string());
+        assert_eq!(p.version, 1);
+
+        p.set_summary("v2 body".to_string());
+        p.set_summary("v3 body".to_string());
+        assert_eq!(p.version, 3);
+        assert_eq!(p.summary_at(None), Some("v3 body"));
+        assert_eq!(p.summary_at(Some(3)), Some("v3 body"));
+        assert_eq!(p.summary_at(Some(1)), Some("v1 body"));
+        assert_eq!(p.summary_at(Some(2)), Some("v2 body"));
+        assert_eq!(p.summary_at(Some(4)), None);
+    }
+
+    #[test]
+    fn test_legacy_prompt_defaults_to_version_one() {
+        let json = r#"{"id":"p1","title":"t","summary":"s","source_flow_name":"",
+            "tags":[],"created_at":"2024-06-01T00:00:00Z"}"#;
+        let p: SavedPrompt = serde_json::from_str(json).unwrap();
+        assert_eq!(p.version, 1);
+        assert!(p.history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_prompt_ref() {
+        let dir = tempdir().unwrap();
+        let repo = FilePromptRepository::new(dir.path());
+        let mut p = prompt("abc-123", "pr-review", "old instructions");
+        p.set_summary("new instructions".to_string());
+        repo.save_prompt(p).await.unwrap();
+
+        assert_eq!(resolve_prompt_ref(&repo, "abc-123").await.unwrap(), "new instructions");
+        assert_eq!(resolve_prompt_ref(&repo, "pr-review").await.unwrap(), "new instructions");
+        assert_eq!(resolve_prompt_ref(&repo, "pr-review@1").await.unwrap(), "old instructions");
+        assert!(resolve_prompt_ref(&repo, "pr-review@9").await.is_err());
+        assert!(resolve_prompt_ref(&repo, "pr-review@x").await.is_err());
+        assert!(resolve_prompt_ref(&repo, "missing").await.is_err());
+    }
 }
@@ -83,6 +83,7 @@ impl SandboxProvider for DangerousHostProvider {
             supervisor: Arc::new(supervisor),
             metadata,
             env: spec.env,
+            read_only: spec.filesystem.read_only_workspace,
         }))
     }
 
@@ -112,6 +113,7 @@ impl SandboxProvider for DangerousHostProvider {
             supervisor: Arc::new(supervisor),
             metadata,
             env: BTreeMap::new(),
+            read_only: false,
         }))
     }
 
@@ -142,6 +144,22 @@ struct DangerousHandle {
     metadata: SandboxMetadata,
     /// Per-sandbox env vars (from SandboxSpec.env).
     env: BTreeMap<String, String>,
+    /// Seal the workspace before the first exec (from
+    /// `FilesystemSpec.read_only_workspace`).
+    read_only: bool,
+}
+
+impl DangerousHandle {
+    fn prepare_exec(&self, req: &mut ExecRequest) -> Result<(), SandboxError> {
+        // Merge sandbox-level env into request env
+        for (k, v) in &self.env {
+            req.env.entry(k.clone()).or_insert_with(|| v.clone());
+        }
+        if self.read_only {
+            self.jail.seal()?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -175,10 +193,7 @@ impl SandboxHandle for DangerousHandle {
             command = ?req.command,
             "executing command in DangerousHost sandbox"
         );
-        // Merge sandbox-level env into request env
-        for (k, v) in &self.env {
-            req.env.entry(k.clone()).or_insert_with(|| v.clone());
-        }
+        self.prepare_exec(&mut req)?;
         self.supervisor.exec(&req, self.jail.root()).await
     }
 
@@ -186,9 +201,7 @@ impl SandboxHandle for DangerousHandle {
         &self,
         mut req: ExecRequest,
     ) -> Result<Box<dyn ExecStream + Send + Unpin>, SandboxError> {
-        for (k, v) in &self.env {
-            req.env.entry(k.clone()).or_insert_with(|| v.clone());
-        }
+        self.prepare_exec(&mut req)?;
         let stream = self.supervisor.exec_stream(&req, self.jail.root()).await?;
         Ok(Box::new(stream))
     }
@@ -383,6 +396,43 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn read_only_workspace_sealed_on_exec() {
+        let (tmp, provider) = test_provider().await;
+        let mut spec = base_spec("ro-ws");
+        spec.filesystem.read_only_workspace = true;
+        let handle = provider.provision(spec).await.unwrap();
+
+        handle
+            .put_file(PutFileRequest {
+                path: "/notes.txt".into(),
+                bytes: b"before".to_vec(),
+                create_parents: false,
+                mode: None,
+            })
+            .await
+            .unwrap();
+        let notes = tmp.path().join("sandboxes/ro-ws/notes.txt");
+        assert!(!std::fs::metadata(&notes).unwrap().permissions().readonly());
+
+        handle
+            .exec(ExecRequest {
+                command: vec!["echo".into(), "hi".into()],
+                cwd: None,
+                env: BTreeMap::new(),
+                stdin: None,
+                timeout: Some(std::time::Duration::from_secs(5)),
+                tty: false,
+                detach: false,
+            })
+            .await
+            .unwrap();
+        assert!(std::fs::metadata(&notes).unwrap().permissions().readonly());
+
+        handle.destroy().await.unwrap();
+        assert!(!notes.exists());
+    }
+
     #[tokio::test]
     async fn unsupported_operations() {
         let (_tmp, provider) = test_provider().await;
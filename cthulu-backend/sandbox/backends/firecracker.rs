@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -160,8 +160,16 @@ impl SandboxProvider for FirecrackerProvider {
         // 3. Allocate network
         let network = self.net_allocator.allocate(&vm_id);
 
-        // Set up TAP device on the host
+        // Set up TAP device on the host, firewalled to the spec's policy
+        // before the guest boots
         net::setup_tap(self.transport.as_ref(), &network).await?;
+        let allowlist = match spec.network.mode {
+            NetworkMode::Allowlist => net::resolve_allowlist(&spec.network.allowed_hosts).await,
+            NetworkMode::AllowAll | NetworkMode::Disabled => Vec::new(),
+        };
+        if spec.network.mode != NetworkMode::AllowAll {
+            net::restrict_tap(self.transport.as_ref(), &network.tap_name, &allowlist).await?;
+        }
 
         // 4. Generate SSH key for this VM (create in vm_state_dir)
         let ssh_key_path = vm_state_dir.join("id_rsa");
@@ -255,8 +263,14 @@ impl SandboxProvider for FirecrackerProvider {
                 ))
             })?;
 
-        // Set up guest networking (default route + DNS)
-        net::setup_guest_network(&guest_agent, &network.host_ip).await?;
+        // Set up guest networking as far as the spec's policy allows. The
+        // TAP itself stays up either way: it's how we reach the guest, and
+        // the host firewall keeps the guest from reaching out.
+        match spec.network.mode {
+            NetworkMode::AllowAll => net::setup_guest_network(&guest_agent, &network.host_ip).await?,
+            NetworkMode::Allowlist => net::setup_guest_allowlist(&guest_agent, &network.host_ip, &allowlist).await?,
+            NetworkMode::Disabled => tracing::info!(vm_id = %vm_id, "guest networking disabled"),
+        }
 
         // 8. Initialize snapshot store
         let snapshot_store = SnapshotStore::new(&vm_state_dir)?;
@@ -289,6 +303,8 @@ impl SandboxProvider for FirecrackerProvider {
             transport: self.transport.clone(),
             ssh_key_path,
             _ssh_pub_key_path: ssh_pub_key_path,
+            read_only: spec.filesystem.read_only_workspace,
+            sealed: AtomicBool::new(false),
         }))
     }
 
@@ -377,6 +393,37 @@ struct FirecrackerHandle {
     transport: Arc<dyn HostTransport>,
     ssh_key_path: PathBuf,
     _ssh_pub_key_path: PathBuf,
+    /// Remount the workspace read-only before the first exec (from
+    /// `FilesystemSpec.read_only_workspace`).
+    read_only: bool,
+    sealed: AtomicBool,
+}
+
+impl FirecrackerHandle {
+    /// Bind-mount the workspace over itself read-only, so execs can read the
+    /// files put there but not change them.
+    async fn seal_workspace(&self) -> Result<(), SandboxError> {
+        let req = ExecRequest {
+            command: vec![
+                "mkdir -p /workspace && mount --bind /workspace /workspace && mount -o remount,bind,ro /workspace"
+                    .into(),
+            ],
+            cwd: None,
+            env: BTreeMap::new(),
+            stdin: None,
+            timeout: Some(Duration::from_secs(10)),
+            tty: false,
+            detach: false,
+        };
+        let result = self.guest_agent.exec(&req).await?;
+        if result.exit_code != Some(0) {
+            return Err(SandboxError::Exec(format!(
+                "failed to make the workspace read-only: {}",
+                String::from_utf8_lossy(&result.stderr)
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -418,6 +465,9 @@ impl SandboxHandle for FirecrackerHandle {
             "executing command in firecracker VM"
         );
 
+        if self.read_only && !self.sealed.swap(true, Ordering::SeqCst) {
+            self.seal_workspace().await?;
+        }
         self.guest_agent.exec(&req).await
     }
 
@@ -6,7 +6,10 @@
 //! - IP address assignment (host side)
 //! - Guest IP derivation from MAC address
 //! - NAT/masquerade rules for internet access
+//! - Host firewall rules limiting a TAP to allowlisted hosts
+//! - Guest routes limited to allowlisted hosts
 
+use std::net::Ipv4Addr;
 use std::sync::atomic::{AtomicU16, Ordering};
 
 use crate::sandbox::error::SandboxError;
@@ -226,12 +229,142 @@ pub async fn setup_guest_network(
     Ok(())
 }
 
+/// Resolve allowlisted hosts to their IPv4 addresses, on the host. Hosts
+/// that don't resolve are left out.
+pub async fn resolve_allowlist(hosts: &[String]) -> Vec<(String, Vec<Ipv4Addr>)> {
+    let mut resolved = Vec::new();
+    for host in hosts {
+        let addrs: Vec<Ipv4Addr> = match tokio::net::lookup_host((host.as_str(), 443)).await {
+            Ok(addrs) => addrs
+                .filter_map(|a| match a.ip() {
+                    std::net::IpAddr::V4(ip) => Some(ip),
+                    std::net::IpAddr::V6(_) => None,
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!(host = %host, error = %e, "allowlisted host did not resolve, leaving it out");
+                continue;
+            }
+        };
+        resolved.push((host.clone(), addrs));
+    }
+    resolved
+}
+
+/// Limit what a VM can reach through its TAP, from the host where the
+/// guest can't undo it: a per-TAP chain, jumped to from `FORWARD` and
+/// `INPUT`, that lets replies back in, lets the guest open connections to
+/// the `resolved` addresses and drops everything else. With nothing
+/// resolved (networking disabled) the guest reaches nothing; the host can
+/// still connect to it over SSH.
+pub async fn restrict_tap(
+    transport: &dyn HostTransport,
+    tap_name: &str,
+    resolved: &[(String, Vec<Ipv4Addr>)],
+) -> Result<(), SandboxError> {
+    remove_tap_firewall(transport, tap_name).await;
+    for rule in tap_firewall_rules(tap_name, resolved) {
+        let args: Vec<&str> = rule.iter().map(String::as_str).collect();
+        transport
+            .run_cmd_sudo(&args)
+            .await?
+            .check()
+            .map_err(|e| SandboxError::Provision(format!("TAP firewall setup failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// The `iptables` invocations [`restrict_tap`] runs, in order. The chain
+/// is filled before anything jumps to it.
+fn tap_firewall_rules(tap_name: &str, resolved: &[(String, Vec<Ipv4Addr>)]) -> Vec<Vec<String>> {
+    let chain = tap_name;
+    let rule = |args: &[&str]| -> Vec<String> {
+        std::iter::once("iptables").chain(args.iter().copied()).map(String::from).collect()
+    };
+    let mut rules = vec![
+        rule(&["-N", chain]),
+        rule(&["-A", chain, "-m", "conntrack", "--ctstate", "ESTABLISHED,RELATED", "-j", "ACCEPT"]),
+    ];
+    for addr in resolved.iter().flat_map(|(_, addrs)| addrs) {
+        let dest = addr.to_string();
+        rules.push(rule(&["-A", chain, "-i", tap_name, "-d", &dest, "-j", "ACCEPT"]));
+    }
+    rules.push(rule(&["-A", chain, "-j", "DROP"]));
+    for (table_chain, direction) in TAP_JUMPS {
+        rules.push(rule(&["-I", table_chain, direction, tap_name, "-j", chain]));
+    }
+    rules
+}
+
+/// Where [`restrict_tap`] jumps to a TAP's chain from.
+const TAP_JUMPS: [(&str, &str); 3] = [("FORWARD", "-i"), ("FORWARD", "-o"), ("INPUT", "-i")];
+
+/// Undo [`restrict_tap`]; a TAP that was never restricted is fine.
+async fn remove_tap_firewall(transport: &dyn HostTransport, tap_name: &str) {
+    for (table_chain, direction) in TAP_JUMPS {
+        let _ = transport
+            .run_cmd_sudo(&["iptables", "-D", table_chain, direction, tap_name, "-j", tap_name])
+            .await;
+    }
+    let _ = transport.run_cmd_sudo(&["iptables", "-F", tap_name]).await;
+    let _ = transport.run_cmd_sudo(&["iptables", "-X", tap_name]).await;
+}
+
+/// Configure guest networking that only reaches the `resolved` hosts: a
+/// route per address and `/etc/hosts` entries in place of DNS. This only
+/// spares the guest pointless connection attempts; [`restrict_tap`] is
+/// what keeps it from reaching anything else.
+pub async fn setup_guest_allowlist(
+    guest_agent: &dyn super::guest_agent::GuestAgent,
+    host_ip: &str,
+    resolved: &[(String, Vec<Ipv4Addr>)],
+) -> Result<(), SandboxError> {
+    use crate::sandbox::types::ExecRequest;
+    use std::collections::BTreeMap;
+
+    let req = ExecRequest {
+        command: vec![allowlist_script(host_ip, resolved)],
+        cwd: None,
+        env: BTreeMap::new(),
+        stdin: None,
+        timeout: Some(std::time::Duration::from_secs(10)),
+        tty: false,
+        detach: false,
+    };
+    let result = guest_agent.exec(&req).await?;
+    if result.exit_code != Some(0) {
+        return Err(SandboxError::Provision(format!(
+            "allowlist network setup failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+    Ok(())
+}
+
+/// Shell script adding the routes and `/etc/hosts` entries for `resolved`.
+fn allowlist_script(host_ip: &str, resolved: &[(String, Vec<Ipv4Addr>)]) -> String {
+    let mut lines = Vec::new();
+    for (host, addrs) in resolved {
+        for addr in addrs {
+            lines.push(format!("ip route replace {addr}/32 via {host_ip} dev eth0"));
+        }
+        if let Some(first) = addrs.first() {
+            lines.push(format!("echo '{first} {host}' >> /etc/hosts"));
+        }
+    }
+    if lines.is_empty() {
+        return "true".into();
+    }
+    lines.join(" && ")
+}
+
 /// Tear down TAP device for a VM.
 pub async fn teardown_tap(
     transport: &dyn HostTransport,
     tap_name: &str,
 ) -> Result<(), SandboxError> {
     tracing::info!(tap = %tap_name, "tearing down TAP device");
+    remove_tap_firewall(transport, tap_name).await;
     let _ = transport
         .run_cmd_sudo(&["ip", "link", "del", tap_name])
         .await;
@@ -288,6 +421,45 @@ mod tests {
         assert_eq!(sanitize_iface_id("a".repeat(20).as_str()), "a".repeat(15));
     }
 
+    #[test]
+    fn allowlist_script_routes_each_address() {
+        let resolved = vec![
+            ("api.github.com".to_string(), vec![Ipv4Addr::new(140, 82, 112, 5), Ipv4Addr::new(140, 82, 112, 6)]),
+            ("gone.example".to_string(), vec![]),
+        ];
+        assert_eq!(
+            allowlist_script("172.16.0.1", &resolved),
+            "ip route replace 140.82.112.5/32 via 172.16.0.1 dev eth0 && \
+             ip route replace 140.82.112.6/32 via 172.16.0.1 dev eth0 && \
+             echo '140.82.112.5 api.github.com' >> /etc/hosts"
+        );
+        assert_eq!(allowlist_script("172.16.0.1", &[]), "true");
+    }
+
+    #[test]
+    fn tap_firewall_drops_all_but_allowlisted() {
+        let resolved = vec![("api.github.com".to_string(), vec![Ipv4Addr::new(140, 82, 112, 5)])];
+        let rules: Vec<String> = tap_firewall_rules("fc-tap0", &resolved)
+            .iter()
+            .map(|rule| rule.join(" "))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                "iptables -N fc-tap0",
+                "iptables -A fc-tap0 -m conntrack --ctstate ESTABLISHED,RELATED -j ACCEPT",
+                "iptables -A fc-tap0 -i fc-tap0 -d 140.82.112.5 -j ACCEPT",
+                "iptables -A fc-tap0 -j DROP",
+                "iptables -I FORWARD -i fc-tap0 -j fc-tap0",
+                "iptables -I FORWARD -o fc-tap0 -j fc-tap0",
+                "iptables -I INPUT -i fc-tap0 -j fc-tap0",
+            ]
+        );
+
+        let disabled = tap_firewall_rules("fc-tap0", &[]);
+        assert!(!disabled.iter().any(|rule| rule.contains(&"-d".to_string())));
+    }
+
     #[test]
     fn vm_network_config_matches() {
         let alloc = NetworkAllocator::new(0);
@@ -9,6 +9,7 @@
 
 use std::path::{Path, PathBuf};
 
+use crate::egress::AuditedSend;
 use crate::sandbox::error::SandboxError;
 use crate::sandbox::firecracker::host_transport::HostTransport;
 
@@ -128,7 +129,7 @@ impl FirecrackerVmApi {
                     .put(&url)
                     .header("Content-Type", "application/json")
                     .body(body.to_string())
-                    .send()
+                    .send_audited()
                     .await
                     .map_err(|e| {
                         SandboxError::Backend(format!("FC API PUT {endpoint} failed: {e}"))
@@ -186,7 +187,7 @@ impl FirecrackerVmApi {
                     .header("Accept", "application/json")
                     .header("Content-Type", "application/json")
                     .body(body.to_string())
-                    .send()
+                    .send_audited()
                     .await
                     .map_err(|e| {
                         SandboxError::Backend(format!("FC API PATCH {endpoint} failed: {e}"))
@@ -243,7 +244,7 @@ impl FirecrackerVmApi {
                 let url = format!("{base_url}{endpoint}");
                 let resp = client
                     .get(&url)
-                    .send()
+                    .send_audited()
                     .await
                     .map_err(|e| {
                         SandboxError::Backend(format!("FC API GET {endpoint} failed: {e}"))
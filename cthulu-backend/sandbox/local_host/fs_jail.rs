@@ -1,4 +1,5 @@
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::sandbox::error::SandboxError;
 use crate::sandbox::types::{DirEntry, GetFileRequest, GetFileResponse, PutFileRequest};
@@ -10,6 +11,8 @@ use crate::sandbox::types::{DirEntry, GetFileRequest, GetFileResponse, PutFileRe
 /// `DangerousHost` backend.
 pub struct FsJail {
     root: PathBuf,
+    /// Whether `seal` has made the workspace read-only.
+    sealed: AtomicBool,
 }
 
 impl FsJail {
@@ -21,7 +24,7 @@ impl FsJail {
                 root.display()
             ))
         })?;
-        Ok(Self { root })
+        Ok(Self { root, sealed: AtomicBool::new(false) })
     }
 
     /// Attach to an existing workspace directory.
@@ -32,7 +35,7 @@ impl FsJail {
                 root.display()
             )));
         }
-        Ok(Self { root })
+        Ok(Self { root, sealed: AtomicBool::new(false) })
     }
 
     pub fn root(&self) -> &Path {
@@ -140,8 +143,21 @@ impl FsJail {
         Ok(())
     }
 
+    /// Make everything in the workspace read-only, once. Processes run as
+    /// the same user, so this stops accidental writes rather than a
+    /// determined one.
+    pub fn seal(&self) -> Result<(), SandboxError> {
+        if self.sealed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        set_read_only(&self.root, true)
+    }
+
     /// Remove the entire workspace directory.
     pub fn destroy(&self) -> Result<(), SandboxError> {
+        if self.sealed.load(Ordering::SeqCst) {
+            set_read_only(&self.root, false)?;
+        }
         if self.root.exists() {
             std::fs::remove_dir_all(&self.root)?;
         }
@@ -149,6 +165,45 @@ impl FsJail {
     }
 }
 
+/// Set or clear the read-only flag on `path` and everything under it.
+/// Directories are handled after their contents on the way in, and before
+/// them on the way out, so the walk can always reach every entry.
+fn set_read_only(path: &Path, read_only: bool) -> Result<(), SandboxError> {
+    let meta = std::fs::symlink_metadata(path)?;
+    if meta.file_type().is_symlink() {
+        return Ok(());
+    }
+    let permissions = writable(meta.permissions(), !read_only);
+    if meta.is_dir() {
+        if !read_only {
+            std::fs::set_permissions(path, permissions.clone())?;
+        }
+        for entry in std::fs::read_dir(path)? {
+            set_read_only(&entry?.path(), read_only)?;
+        }
+        if read_only {
+            std::fs::set_permissions(path, permissions)?;
+        }
+    } else {
+        std::fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+/// `permissions` with the write bits cleared, or the owner's set again.
+#[cfg(unix)]
+fn writable(permissions: std::fs::Permissions, writable: bool) -> std::fs::Permissions {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = permissions.mode();
+    std::fs::Permissions::from_mode(if writable { mode | 0o200 } else { mode & !0o222 })
+}
+
+#[cfg(not(unix))]
+fn writable(mut permissions: std::fs::Permissions, writable: bool) -> std::fs::Permissions {
+    permissions.set_readonly(!writable);
+    permissions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,6 +341,29 @@ mod tests {
             .is_err());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn seal_makes_workspace_read_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws_path = tmp.path().join("ws");
+        let jail = FsJail::create(ws_path.clone()).unwrap();
+        jail.put_file(&PutFileRequest {
+            path: "/src/lib.rs".into(),
+            bytes: b"// lib".to_vec(),
+            create_parents: true,
+            mode: None,
+        })
+        .unwrap();
+
+        jail.seal().unwrap();
+        let meta = std::fs::metadata(ws_path.join("src/lib.rs")).unwrap();
+        assert!(meta.permissions().readonly());
+        assert!(std::fs::metadata(ws_path.join("src")).unwrap().permissions().readonly());
+
+        jail.destroy().unwrap();
+        assert!(!ws_path.exists());
+    }
+
     #[test]
     fn destroy_removes_workspace() {
         let tmp = tempfile::tempdir().unwrap();
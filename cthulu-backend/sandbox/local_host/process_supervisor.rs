@@ -64,6 +64,8 @@ impl ProcessSupervisor {
         cmd.stdin(if req.stdin.is_some() { Stdio::piped() } else { Stdio::null() });
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
+        // Callers that stop waiting (e.g. a run hitting its time limit) kill it
+        cmd.kill_on_drop(true);
 
         let mut child = cmd.spawn().map_err(|e| SandboxError::Exec(format!("spawn failed: {e}")))?;
 
@@ -217,14 +219,15 @@ impl ProcessSupervisor {
             }
         });
 
-        Ok(ProcessExecStream { rx, stdin })
+        Ok(ProcessExecStream { rx, stdin: Some(stdin) })
     }
 }
 
 /// ExecStream implementation backed by a child process.
 pub struct ProcessExecStream {
     rx: mpsc::UnboundedReceiver<ExecEvent>,
-    stdin: tokio::process::ChildStdin,
+    /// Taken (and so dropped, sending EOF) by `close_stdin`.
+    stdin: Option<tokio::process::ChildStdin>,
 }
 
 #[async_trait::async_trait]
@@ -234,11 +237,15 @@ impl ExecStream for ProcessExecStream {
     }
 
     async fn write_stdin(&mut self, data: &[u8]) -> Result<(), SandboxError> {
-        self.stdin
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| SandboxError::Exec("stdin already closed".into()))?;
+        stdin
             .write_all(data)
             .await
             .map_err(|e| SandboxError::Exec(format!("write stdin: {e}")))?;
-        self.stdin
+        stdin
             .flush()
             .await
             .map_err(|e| SandboxError::Exec(format!("flush stdin: {e}")))?;
@@ -246,10 +253,12 @@ impl ExecStream for ProcessExecStream {
     }
 
     async fn close_stdin(&mut self) -> Result<(), SandboxError> {
-        self.stdin
-            .shutdown()
-            .await
-            .map_err(|e| SandboxError::Exec(format!("close stdin: {e}")))?;
+        if let Some(mut stdin) = self.stdin.take() {
+            stdin
+                .shutdown()
+                .await
+                .map_err(|e| SandboxError::Exec(format!("close stdin: {e}")))?;
+        }
         Ok(())
     }
 }
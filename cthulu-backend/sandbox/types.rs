@@ -59,6 +59,9 @@ pub struct FilesystemSpec {
     pub tmp_path: String,
     pub persistent_workspace: bool,
     pub size_mb_hint: Option<u32>,
+    /// Execs can read the workspace but not change it. Files can still be
+    /// put into it until the first exec.
+    pub read_only_workspace: bool,
 }
 
 impl Default for FilesystemSpec {
@@ -69,6 +72,7 @@ impl Default for FilesystemSpec {
             tmp_path: "/tmp".into(),
             persistent_workspace: false,
             size_mb_hint: None,
+            read_only_workspace: false,
         }
     }
 }
@@ -130,6 +134,13 @@ impl NetworkPolicy {
             allow_loopback: true,
         }
     }
+
+    pub fn allow_all() -> Self {
+        Self {
+            mode: NetworkMode::AllowAll,
+            ..Self::default_safe()
+        }
+    }
 }
 
 // ── Capabilities (reported by handle) ───────────────────────────────
@@ -488,6 +499,7 @@ mod tests {
                 tmp_path: "/tmp".into(),
                 persistent_workspace: true,
                 size_mb_hint: Some(4096),
+                read_only_workspace: false,
             },
             resources: ResourceHints {
                 vcpu: Some(2),
@@ -0,0 +1,267 @@
+//! Encrypted secrets (`~/.cthulu/secrets.json`), so tokens needn't be
+//! scattered across the host environment. Wherever a node config names an
+//! env var for a credential (`bot_token_env`, `api_key_env`, ...), it can
+//! name a stored secret instead: `bot_token_env: secret://slack-bot`.
+//!
+//! Values are sealed with AES-256-GCM under a master key: the SHA-256 of
+//! `CTHULU_SECRETS_KEY`, or on macOS of the Keychain's `cthulu-secrets`
+//! password when that's unset. The API lists secret names, never values.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// How a config refers to a stored secret rather than an env var.
+pub const SCHEME: &str = "secret://";
+
+/// Serializes read-modify-write of the secrets file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// The data directory `var` reads `secret://` references from, set on start.
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// The master key, looked up once.
+static MASTER_KEY: OnceLock<Option<[u8; 32]>> = OnceLock::new();
+
+pub fn init(data_dir: &Path) {
+    let _ = DATA_DIR.set(data_dir.to_path_buf());
+}
+
+/// `std::env::var`, except that `secret://name` reads the secret store.
+pub fn var(name: &str) -> Result<String> {
+    match name.strip_prefix(SCHEME) {
+        Some(secret) => {
+            let data_dir = DATA_DIR.get().context("the secret store isn't set up")?;
+            SecretStore::new(data_dir)
+                .get_secret(secret)?
+                .with_context(|| format!("no secret named {secret}"))
+        }
+        None => Ok(std::env::var(name)?),
+    }
+}
+
+fn master_key() -> Option<[u8; 32]> {
+    *MASTER_KEY.get_or_init(|| {
+        let raw = std::env::var("CTHULU_SECRETS_KEY")
+            .ok()
+            .filter(|k| !k.trim().is_empty())
+            .or_else(keychain_key)?;
+        Some(Sha256::digest(raw.trim().as_bytes()).into())
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_key() -> Option<String> {
+    let output = std::process::Command::new("security")
+        .args(["find-generic-password", "-s", "cthulu-secrets", "-w"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if raw.is_empty() { None } else { Some(raw) }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keychain_key() -> Option<String> {
+    None
+}
+
+/// A secret as the API shows it: everything but the value.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretInfo {
+    pub name: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    /// Hex of the nonce.
+    nonce: String,
+    /// Hex of the ciphertext and tag.
+    value: String,
+    updated_at: DateTime<Utc>,
+}
+
+/// File-backed secrets, each value encrypted under the master key with its
+/// name as associated data, so values can't be swapped between names.
+#[derive(Clone)]
+pub struct SecretStore {
+    path: PathBuf,
+    key: Option<[u8; 32]>,
+}
+
+impl SecretStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self::with_key(data_dir, master_key())
+    }
+
+    /// Used directly in tests to avoid mutating process-global environment.
+    pub fn with_key(data_dir: &Path, key: Option<[u8; 32]>) -> Self {
+        Self { path: data_dir.join("secrets.json"), key }
+    }
+
+    /// Letters, digits, `-`, `_` and `.`, as they appear after `secret://`.
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= 128
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    }
+
+    fn cipher(&self) -> Result<LessSafeKey> {
+        let key = self.key.context("no master key: set CTHULU_SECRETS_KEY")?;
+        let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| anyhow!("invalid master key"))?;
+        Ok(LessSafeKey::new(key))
+    }
+
+    /// The sealed secrets. A file that doesn't parse is an error rather
+    /// than an empty store, so the next write can't replace every secret.
+    fn load(&self) -> Result<BTreeMap<String, Sealed>> {
+        crate::json_file::load(&self.path)
+    }
+
+    fn write(&self, secrets: &BTreeMap<String, Sealed>) -> Result<()> {
+        crate::json_file::write_private(&self.path, secrets)
+    }
+
+    pub fn list(&self) -> Result<Vec<SecretInfo>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        Ok(self
+            .load()?
+            .into_iter()
+            .map(|(name, sealed)| SecretInfo { name, updated_at: sealed.updated_at })
+            .collect())
+    }
+
+    /// Store (or replace) secret `name`.
+    pub fn put_secret(&self, name: &str, value: &str) -> Result<SecretInfo> {
+        if !Self::is_valid_name(name) {
+            bail!("secret names are letters, digits, '-', '_' and '.'");
+        }
+        let cipher = self.cipher()?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut nonce).map_err(|_| anyhow!("no randomness for a nonce"))?;
+        let mut sealed = value.as_bytes().to_vec();
+        cipher
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(name.as_bytes()), &mut sealed)
+            .map_err(|_| anyhow!("failed to encrypt secret"))?;
+
+        let updated_at = Utc::now();
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load()?;
+        all.insert(
+            name.to_string(),
+            Sealed { nonce: hex::encode(nonce), value: hex::encode(sealed), updated_at },
+        );
+        self.write(&all)?;
+        Ok(SecretInfo { name: name.to_string(), updated_at })
+    }
+
+    /// Secret `name`'s value; `None` if there's no such secret.
+    pub fn get_secret(&self, name: &str) -> Result<Option<String>> {
+        let Some(sealed) = ({
+            let _guard = FILE_LOCK.lock().unwrap();
+            self.load()?.remove(name)
+        }) else {
+            return Ok(None);
+        };
+        let cipher = self.cipher()?;
+        let nonce: [u8; NONCE_LEN] = hex::decode(&sealed.nonce)
+            .ok()
+            .and_then(|n| n.try_into().ok())
+            .with_context(|| format!("secret {name} is corrupt"))?;
+        let mut value = hex::decode(&sealed.value).with_context(|| format!("secret {name} is corrupt"))?;
+        let plain = cipher
+            .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::from(name.as_bytes()), &mut value)
+            .map_err(|_| anyhow!("secret {name} doesn't decrypt: wrong master key?"))?;
+        Ok(Some(String::from_utf8(plain.to_vec()).context("secret isn't UTF-8")?))
+    }
+
+    pub fn delete_secret(&self, name: &str) -> Result<bool> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let mut all = self.load()?;
+        if all.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.write(&all)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_and_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::with_key(dir.path(), Some([7; 32]));
+        store.put_secret("slack-bot", "xoxb-123").unwrap();
+        assert_eq!(store.get_secret("slack-bot").unwrap().as_deref(), Some("xoxb-123"));
+        assert_eq!(store.get_secret("missing").unwrap(), None);
+        assert_eq!(store.list().unwrap().iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["slack-bot"]);
+
+        // Only ciphertext on disk
+        let raw = std::fs::read_to_string(dir.path().join("secrets.json")).unwrap();
+        assert!(!raw.contains("xoxb-123"));
+
+        // A different key can't read it, and no key can't either
+        let wrong = SecretStore::with_key(dir.path(), Some([8; 32]));
+        assert!(wrong.get_secret("slack-bot").is_err());
+        assert!(SecretStore::with_key(dir.path(), None).get_secret("slack-bot").is_err());
+
+        assert!(store.put_secret("bad name", "x").is_err());
+        assert!(store.delete_secret("slack-bot").unwrap());
+        assert!(!store.delete_secret("slack-bot").unwrap());
+    }
+
+    #[test]
+    fn test_values_are_bound_to_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::with_key(dir.path(), Some([7; 32]));
+        store.put_secret("a", "alpha").unwrap();
+        store.put_secret("b", "beta").unwrap();
+
+        let path = dir.path().join("secrets.json");
+        let mut all: BTreeMap<String, Sealed> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let a = all["a"].clone();
+        all.insert("b".into(), a);
+        std::fs::write(&path, serde_json::to_string(&all).unwrap()).unwrap();
+        assert!(store.get_secret("b").is_err());
+    }
+
+    #[test]
+    fn test_corrupt_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::with_key(dir.path(), Some([7; 32]));
+        store.put_secret("a", "alpha").unwrap();
+
+        let path = dir.path().join("secrets.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(store.list().is_err());
+        assert!(store.get_secret("a").is_err());
+        assert!(store.put_secret("b", "beta").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{ not json");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretStore::with_key(dir.path(), Some([7; 32]));
+        store.put_secret("a", "alpha").unwrap();
+        let mode = std::fs::metadata(dir.path().join("secrets.json")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(!dir.path().join("secrets.json.tmp").exists());
+    }
+}
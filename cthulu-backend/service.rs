@@ -0,0 +1,264 @@
+//! `cthulu service install|uninstall`: start the server at login as the
+//! current user, so it keeps the user's Claude login and PATH. A systemd
+//! user unit on Linux, a launchd agent on macOS, a logon scheduled task on
+//! Windows. The working directory is where `install` was run, so its
+//! `.env` is picked up.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+const NAME: &str = "cthulu";
+#[cfg(target_os = "macos")]
+const LAUNCHD_LABEL: &str = "com.cthulu.server";
+
+#[derive(clap::Subcommand)]
+pub enum ServiceAction {
+    /// Register the server to start at login, and start it now
+    Install,
+    /// Stop the server and remove the registration
+    Uninstall,
+}
+
+pub fn run(action: ServiceAction) -> Result<()> {
+    match action {
+        ServiceAction::Install => {
+            let exe = std::env::current_exe().context("can't locate the cthulu executable")?;
+            let dir = std::env::current_dir().context("can't read the working directory")?;
+            install(&exe, &dir)
+        }
+        ServiceAction::Uninstall => uninstall(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn unit_path() -> Result<std::path::PathBuf> {
+    let config = dirs::config_dir().context("no config directory")?;
+    Ok(config.join("systemd/user").join(format!("{NAME}.service")))
+}
+
+#[cfg(target_os = "linux")]
+fn install(exe: &Path, dir: &Path) -> Result<()> {
+    let path = unit_path()?;
+    let unit = systemd_unit(exe, dir, &std::env::var("PATH").unwrap_or_default());
+    write(&path, &unit)?;
+    run_cmd("systemctl", &["--user", "daemon-reload"])?;
+    run_cmd("systemctl", &["--user", "enable", "--now", &format!("{NAME}.service")])?;
+    println!("Installed {}", path.display());
+    println!("To keep it running after logout: loginctl enable-linger $USER");
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn uninstall() -> Result<()> {
+    let path = unit_path()?;
+    let _ = run_cmd("systemctl", &["--user", "disable", "--now", &format!("{NAME}.service")]);
+    remove(&path)?;
+    run_cmd("systemctl", &["--user", "daemon-reload"])
+}
+
+#[cfg(target_os = "macos")]
+fn plist_path() -> Result<std::path::PathBuf> {
+    let home = dirs::home_dir().context("no home directory")?;
+    Ok(home.join("Library/LaunchAgents").join(format!("{LAUNCHD_LABEL}.plist")))
+}
+
+#[cfg(target_os = "macos")]
+fn install(exe: &Path, dir: &Path) -> Result<()> {
+    let path = plist_path()?;
+    let home = dirs::home_dir().context("no home directory")?;
+    let log = home.join(".cthulu/logs/service.log");
+    std::fs::create_dir_all(log.parent().unwrap())?;
+    let plist = launchd_plist(LAUNCHD_LABEL, exe, dir, &log, &std::env::var("PATH").unwrap_or_default());
+    write(&path, &plist)?;
+    let _ = run_cmd("launchctl", &["unload", &path.display().to_string()]);
+    run_cmd("launchctl", &["load", "-w", &path.display().to_string()])?;
+    println!("Installed {} (logs: {})", path.display(), log.display());
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn uninstall() -> Result<()> {
+    let path = plist_path()?;
+    let _ = run_cmd("launchctl", &["unload", "-w", &path.display().to_string()]);
+    remove(&path)
+}
+
+#[cfg(windows)]
+fn install(exe: &Path, dir: &Path) -> Result<()> {
+    let task = schtasks_command(exe, dir);
+    run_cmd(
+        "schtasks",
+        &["/Create", "/F", "/TN", NAME, "/SC", "ONLOGON", "/RL", "LIMITED", "/TR", &task],
+    )?;
+    run_cmd("schtasks", &["/Run", "/TN", NAME])?;
+    println!("Installed scheduled task '{NAME}' (runs at logon)");
+    Ok(())
+}
+
+#[cfg(windows)]
+fn uninstall() -> Result<()> {
+    let _ = run_cmd("schtasks", &["/End", "/TN", NAME]);
+    run_cmd("schtasks", &["/Delete", "/F", "/TN", NAME])
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn install(_exe: &Path, _dir: &Path) -> Result<()> {
+    anyhow::bail!("service install is supported on Linux, macOS and Windows")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn uninstall() -> Result<()> {
+    anyhow::bail!("service uninstall is supported on Linux, macOS and Windows")
+}
+
+/// A systemd user unit running `exe serve` from `dir`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn systemd_unit(exe: &Path, dir: &Path, path_env: &str) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Cthulu flow runner\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=notify\n\
+         WatchdogSec=120\n\
+         WorkingDirectory={dir}\n\
+         ExecStart=\"{exe}\" serve\n\
+         Environment=\"PATH={path_env}\"\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        dir = dir.display(),
+        exe = exe.display(),
+    )
+}
+
+/// A launchd agent running `exe serve` from `dir`, kept alive.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn launchd_plist(label: &str, exe: &Path, dir: &Path, log: &Path, path_env: &str) -> String {
+    let xml = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{exe}</string>
+        <string>serve</string>
+    </array>
+    <key>WorkingDirectory</key>
+    <string>{dir}</string>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>PATH</key>
+        <string>{path_env}</string>
+    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = xml(label),
+        exe = xml(&exe.display().to_string()),
+        dir = xml(&dir.display().to_string()),
+        path_env = xml(path_env),
+        log = xml(&log.display().to_string()),
+    )
+}
+
+/// The scheduled task's command line: `exe serve` from `dir`, in a
+/// minimized console (scheduled tasks have no working-directory setting).
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn schtasks_command(exe: &Path, dir: &Path) -> String {
+    format!(
+        r#"cmd /C cd /d "{}" && start "{NAME}" /min "{}" serve"#,
+        dir.display(),
+        exe.display()
+    )
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn write(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn remove(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => {
+            println!("Removed {}", path.display());
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("failed to remove {}", path.display())),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn run_cmd(program: &str, args: &[&str]) -> Result<()> {
+    let status = std::process::Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("failed to run {program}"))?;
+    if !status.success() {
+        anyhow::bail!("{program} {} failed with {status}", args.join(" "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_unit() {
+        let unit = systemd_unit(Path::new("/opt/cthulu/cthulu"), Path::new("/srv/bot"), "/usr/bin:/bin");
+        assert!(unit.contains("ExecStart=\"/opt/cthulu/cthulu\" serve\n"));
+        assert!(unit.contains("WorkingDirectory=/srv/bot\n"));
+        assert!(unit.contains("Environment=\"PATH=/usr/bin:/bin\"\n"));
+        assert!(unit.contains("Type=notify\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+    }
+
+    #[test]
+    fn test_launchd_plist_escapes_paths() {
+        let plist = launchd_plist(
+            "com.cthulu.server",
+            Path::new("/Users/me/R&D/cthulu"),
+            Path::new("/Users/me/R&D"),
+            Path::new("/Users/me/.cthulu/logs/service.log"),
+            "/usr/bin",
+        );
+        assert!(plist.contains("<string>/Users/me/R&amp;D/cthulu</string>"));
+        assert!(plist.contains("<key>KeepAlive</key>"));
+    }
+
+    #[test]
+    fn test_schtasks_command() {
+        let cmd = schtasks_command(Path::new(r"C:\Tools\cthulu.exe"), Path::new(r"C:\Users\me\bot"));
+        assert_eq!(
+            cmd,
+            r#"cmd /C cd /d "C:\Users\me\bot" && start "cthulu" /min "C:\Tools\cthulu.exe" serve"#
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! systemd integration, all no-ops elsewhere: take over a listening socket
+//! passed by socket activation (so restarts don't drop connections), report
+//! readiness with `sd_notify`, and ping the watchdog so a hung server is
+//! restarted.
+
+use std::time::Duration;
+
+/// First fd systemd passes (`SD_LISTEN_FDS_START`).
+#[cfg(unix)]
+const LISTEN_FDS_START: i32 = 3;
+
+/// The socket systemd opened for us (a `.socket` unit's `ListenStream=`),
+/// if it started this process by socket activation.
+#[cfg(unix)]
+pub fn activated_listener() -> std::io::Result<Option<std::net::TcpListener>> {
+    use std::os::fd::FromRawFd;
+
+    let env = |key: &str| std::env::var(key).ok();
+    let Some(fd) = activated_fd(env("LISTEN_PID").as_deref(), env("LISTEN_FDS").as_deref(), std::process::id())
+    else {
+        return Ok(None);
+    };
+    // SAFETY: LISTEN_PID names this process, so systemd handed it `fd`, open
+    // and unowned; nothing else in the process takes it over.
+    let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+    listener.set_nonblocking(true)?;
+    Ok(Some(listener))
+}
+
+#[cfg(not(unix))]
+pub fn activated_listener() -> std::io::Result<Option<std::net::TcpListener>> {
+    Ok(None)
+}
+
+/// The fd to listen on, given `LISTEN_PID` and `LISTEN_FDS`. They are
+/// inherited by children, so only count when addressed to `pid`.
+#[cfg(unix)]
+fn activated_fd(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> Option<i32> {
+    let for_us = listen_pid?.parse::<u32>().ok()? == pid;
+    let count: i32 = listen_fds?.parse().ok()?;
+    if count > 1 {
+        tracing::warn!(count, "systemd passed several sockets, using the first");
+    }
+    (for_us && count >= 1).then_some(LISTEN_FDS_START)
+}
+
+/// Send `state` (e.g. `READY=1`) to the service manager, if `NOTIFY_SOCKET`
+/// says there is one listening.
+pub fn notify(state: &str) {
+    #[cfg(target_os = "linux")]
+    if let Ok(path) = std::env::var("NOTIFY_SOCKET")
+        && let Err(e) = send_notify(&path, state)
+    {
+        tracing::warn!(error = %e, "sd_notify failed");
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = state;
+}
+
+#[cfg(target_os = "linux")]
+fn send_notify(path: &str, state: &str) -> std::io::Result<()> {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+    let socket = UnixDatagram::unbound()?;
+    let addr = match path.strip_prefix('@') {
+        Some(name) => SocketAddr::from_abstract_name(name)?,
+        None => SocketAddr::from_pathname(path)?,
+    };
+    socket.send_to_addr(state.as_bytes(), &addr)?;
+    Ok(())
+}
+
+/// How often to ping the watchdog: half its `WatchdogSec=`, when the
+/// watchdog is on for this process.
+pub fn watchdog_interval() -> Option<Duration> {
+    let env = |key: &str| std::env::var(key).ok();
+    parse_watchdog(env("WATCHDOG_USEC").as_deref(), env("WATCHDOG_PID").as_deref(), std::process::id())
+}
+
+fn parse_watchdog(usec: Option<&str>, watchdog_pid: Option<&str>, pid: u32) -> Option<Duration> {
+    if let Some(watchdog_pid) = watchdog_pid
+        && watchdog_pid.parse::<u32>().ok()? != pid
+    {
+        return None;
+    }
+    let usec: u64 = usec?.parse().ok().filter(|u| *u > 0)?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+/// Ping the watchdog every `interval` from the async runtime, so a runtime
+/// that stops making progress stops pinging and gets restarted.
+pub fn spawn_watchdog(interval: Duration) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(interval);
+        loop {
+            tick.tick().await;
+            notify("WATCHDOG=1");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_activated_fd() {
+        assert_eq!(activated_fd(Some("42"), Some("1"), 42), Some(3));
+        assert_eq!(activated_fd(Some("42"), Some("2"), 42), Some(3));
+        // Inherited from a parent that was activated
+        assert_eq!(activated_fd(Some("41"), Some("1"), 42), None);
+        assert_eq!(activated_fd(Some("42"), Some("0"), 42), None);
+        assert_eq!(activated_fd(None, Some("1"), 42), None);
+    }
+
+    #[test]
+    fn test_watchdog_interval() {
+        assert_eq!(parse_watchdog(Some("30000000"), None, 42), Some(Duration::from_secs(15)));
+        assert_eq!(parse_watchdog(Some("30000000"), Some("42"), 42), Some(Duration::from_secs(15)));
+        assert_eq!(parse_watchdog(Some("30000000"), Some("7"), 42), None);
+        assert_eq!(parse_watchdog(Some("0"), None, 42), None);
+        assert_eq!(parse_watchdog(None, None, 42), None);
+    }
+}
@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::{QueryBuilder, Row, Sqlite};
+
+use super::sources::ContentItem;
+use super::sources::seen::SeenStore;
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Rows per INSERT, well under SQLite's bound-parameter limit.
+const INSERT_CHUNK: usize = 500;
+
+/// Words of context on each side of the match in [`ArchivedItem::snippet`].
+const SNIPPET_TOKENS: i64 = 24;
+
+/// Items plus an external-content FTS5 index over their titles and summaries,
+/// kept in sync by an insert trigger (items are never updated or deleted).
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS items (
+    id INTEGER PRIMARY KEY,
+    flow_id TEXT NOT NULL,
+    flow_name TEXT NOT NULL,
+    item_key TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    published TEXT,
+    archived_at TEXT NOT NULL,
+    UNIQUE (flow_id, item_key)
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS items_fts USING fts5(
+    title, summary, content='items', content_rowid='id'
+);
+CREATE TRIGGER IF NOT EXISTS items_fts_insert AFTER INSERT ON items BEGIN
+    INSERT INTO items_fts(rowid, title, summary) VALUES (new.id, new.title, new.summary);
+END;
+";
+
+/// A search hit.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedItem {
+    pub flow_id: String,
+    pub flow_name: String,
+    pub title: String,
+    pub url: String,
+    /// The part of the summary around the match.
+    pub snippet: String,
+    pub published: Option<DateTime<Utc>>,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Full-text searchable archive of every item fetched by sources with
+/// `archive: true`, across all flows.
+///
+/// A SQLite database at `~/.cthulu/state/archive.db`.
+#[derive(Clone)]
+pub struct ArchiveStore {
+    path: PathBuf,
+}
+
+impl ArchiveStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("state").join("archive.db") }
+    }
+
+    async fn connect(&self) -> Result<SqlitePool> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let options = SqliteConnectOptions::new()
+            .filename(&self.path)
+            .create_if_missing(true)
+            .busy_timeout(Duration::from_secs(5));
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .with_context(|| format!("failed to open archive: {}", self.path.display()))?;
+        sqlx::raw_sql(SCHEMA)
+            .execute(&pool)
+            .await
+            .context("failed to create archive schema")?;
+        Ok(pool)
+    }
+
+    /// Add `items` fetched by `flow_id`, skipping ones it archived before
+    /// (same URL, or title when there is none). Returns how many were new.
+    pub async fn archive(&self, flow_id: &str, flow_name: &str, items: &[ContentItem]) -> Result<u64> {
+        if items.is_empty() {
+            return Ok(0);
+        }
+        let pool = self.connect().await?;
+        let archived_at = Utc::now().to_rfc3339();
+        let mut added = 0;
+        for chunk in items.chunks(INSERT_CHUNK) {
+            let mut insert: QueryBuilder<Sqlite> = QueryBuilder::new(
+                "INSERT OR IGNORE INTO items \
+                 (flow_id, flow_name, item_key, title, url, summary, published, archived_at) ",
+            );
+            insert.push_values(chunk, |mut row, item| {
+                row.push_bind(flow_id.to_string())
+                    .push_bind(flow_name.to_string())
+                    .push_bind(SeenStore::key(item))
+                    .push_bind(item.title.clone())
+                    .push_bind(item.url.clone())
+                    .push_bind(item.summary.clone())
+                    .push_bind(item.published.map(|p| p.to_rfc3339()))
+                    .push_bind(archived_at.clone());
+            });
+            added += insert
+                .build()
+                .execute(&pool)
+                .await
+                .context("failed to archive items")?
+                .rows_affected();
+        }
+        pool.close().await;
+        Ok(added)
+    }
+
+    /// Best matches for `query` (all words must match; `word*` matches a
+    /// prefix), optionally within one flow.
+    pub async fn search(&self, query: &str, flow_id: Option<&str>, limit: usize) -> Result<Vec<ArchivedItem>> {
+        match match_expression(query, " ") {
+            Some(expr) => self.run_match(expr, flow_id, limit).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Like [`search`](Self::search), but items matching any of the words
+    /// count, ranked by how well they match — for retrieval from a
+    /// natural-language question.
+    pub async fn search_any(&self, query: &str, flow_id: Option<&str>, limit: usize) -> Result<Vec<ArchivedItem>> {
+        match match_expression(query, " OR ") {
+            Some(expr) => self.run_match(expr, flow_id, limit).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn run_match(&self, expr: String, flow_id: Option<&str>, limit: usize) -> Result<Vec<ArchivedItem>> {
+        let pool = self.connect().await?;
+        let rows = sqlx::query(
+            "SELECT items.flow_id, items.flow_name, items.title, items.url,
+                    snippet(items_fts, 1, '', '', '…', ?) AS snippet,
+                    items.published, items.archived_at
+             FROM items_fts JOIN items ON items.id = items_fts.rowid
+             WHERE items_fts MATCH ? AND (? IS NULL OR items.flow_id = ?)
+             ORDER BY bm25(items_fts)
+             LIMIT ?",
+        )
+        .bind(SNIPPET_TOKENS)
+        .bind(expr)
+        .bind(flow_id.map(str::to_string))
+        .bind(flow_id.map(str::to_string))
+        .bind(limit.min(MAX_SEARCH_LIMIT) as i64)
+        .fetch_all(&pool)
+        .await
+        .context("archive search failed")?;
+        pool.close().await;
+
+        Ok(rows
+            .iter()
+            .map(|row| ArchivedItem {
+                flow_id: row.get("flow_id"),
+                flow_name: row.get("flow_name"),
+                title: row.get("title"),
+                url: row.get("url"),
+                snippet: row.get("snippet"),
+                published: row.get::<Option<String>, _>("published").as_deref().and_then(parse_time),
+                archived_at: parse_time(row.get("archived_at")).unwrap_or_default(),
+            })
+            .collect())
+    }
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// User input as an FTS5 query: each word quoted (so punctuation and FTS
+/// operators are taken literally), joined by `separator` (`" "` requires
+/// all, `" OR "` any). `None` if there are no words.
+pub(crate) fn match_expression(query: &str, separator: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter_map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) => (stem, "*"),
+                None => (word, ""),
+            };
+            let word = word.replace('"', "");
+            (!word.is_empty()).then(|| format!("\"{word}\"{prefix}"))
+        })
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(separator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(title: &str, url: &str, summary: &str) -> ContentItem {
+        ContentItem {
+            title: title.to_string(),
+            url: url.to_string(),
+            summary: summary.to_string(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        }
+    }
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(match_expression("rust async", " ").as_deref(), Some("\"rust\" \"async\""));
+        assert_eq!(match_expression("compil* \"OR\" -x", " ").as_deref(), Some("\"compil\"* \"OR\" \"-x\""));
+        assert_eq!(match_expression("rust async", " OR ").as_deref(), Some("\"rust\" OR \"async\""));
+        assert_eq!(match_expression("  \"\" ", " "), None);
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_search() {
+        let dir = tempdir().unwrap();
+        let store = ArchiveStore::new(dir.path());
+        let items = vec![
+            item("Rust 2.0 released", "https://example.com/rust", "The compiler gets faster builds."),
+            item("Go generics deep dive", "https://example.com/go", "Type parameters explained."),
+        ];
+        assert_eq!(store.archive("f1", "Morning Brief", &items).await.unwrap(), 2);
+        // Already archived for this flow
+        assert_eq!(store.archive("f1", "Morning Brief", &items[..1]).await.unwrap(), 0);
+        assert_eq!(store.archive("f2", "Weekly", &items[..1]).await.unwrap(), 1);
+
+        let hits = store.search("faster compiler", None, 10).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].title, "Rust 2.0 released");
+        assert!(hits[0].snippet.contains("compiler"));
+
+        let hits = store.search("generic*", Some("f1"), 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].flow_name, "Morning Brief");
+        assert!(store.search("rust", Some("f3"), 10).await.unwrap().is_empty());
+        assert!(store.search("", None, 10).await.unwrap().is_empty());
+
+        assert!(store.search("rust generics", None, 10).await.unwrap().is_empty());
+        assert_eq!(store.search_any("rust generics", Some("f1"), 10).await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::archive::ArchivedItem;
+use crate::flows::Flow;
+use crate::flows::history::{FlowRun, RunStatus};
+
+pub const DEFAULT_MAX_RUNS: usize = 5;
+pub const DEFAULT_MAX_ITEMS: usize = 10;
+
+/// Characters of a run's output included in the prompt; long digests are
+/// cut rather than crowding out the other sources.
+const RUN_EXCERPT_CHARS: usize = 3_000;
+
+/// Words too common to say anything about which run answers a question.
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "was", "were", "what", "when", "where", "which", "who", "why", "how",
+    "did", "does", "has", "have", "had", "with", "about", "from", "that", "this", "these", "those",
+    "any", "all", "our", "there", "their", "they", "you", "can", "could", "would", "should", "been",
+    "into", "over", "last", "week", "weeks", "month", "months", "day", "days", "tell", "say", "said",
+];
+
+/// Where a piece of context came from, returned with the answer so each
+/// `[n]` marker links back to a run or an archived item.
+#[derive(Debug, Clone, Serialize)]
+pub struct Citation {
+    /// The `[n]` marker the answer uses for this source.
+    pub index: usize,
+    pub kind: CitationKind,
+    pub flow_id: String,
+    pub flow_name: String,
+    pub title: String,
+    /// `GET /api/flows/{id}/runs/{run_id}` for runs, the item's URL for items.
+    pub link: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    pub date: DateTime<Utc>,
+    /// Whether the answer actually cites it.
+    pub cited: bool,
+    #[serde(skip)]
+    excerpt: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CitationKind {
+    Run,
+    Item,
+}
+
+/// Lowercase words of 3+ characters in `question`, minus stop words.
+pub fn terms(question: &str) -> HashSet<String> {
+    question
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// The `limit` successful runs whose output shares the most question terms,
+/// most recent first among equals. Runs sharing none are left out.
+pub fn relevant_runs<'a>(
+    history: &'a [(Flow, Vec<FlowRun>)],
+    question: &str,
+    limit: usize,
+) -> Vec<(&'a Flow, &'a FlowRun)> {
+    let terms = terms(question);
+    let mut scored: Vec<(usize, &Flow, &FlowRun)> = history
+        .iter()
+        .flat_map(|(flow, runs)| runs.iter().map(move |run| (flow, run)))
+        .filter(|(_, run)| run.status == RunStatus::Success)
+        .filter_map(|(flow, run)| {
+            let output = run.output.as_deref()?.to_lowercase();
+            let score = terms.iter().filter(|t| output.contains(t.as_str())).count();
+            (score > 0).then_some((score, flow, run))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.2.started_at.cmp(&a.2.started_at)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, flow, run)| (flow, run))
+        .collect()
+}
+
+/// Number the retrieved runs, then items, as the answer's sources.
+pub fn citations(runs: &[(&Flow, &FlowRun)], items: &[ArchivedItem]) -> Vec<Citation> {
+    let runs = runs.iter().map(|(flow, run)| {
+        let date = run.finished_at.unwrap_or(run.started_at);
+        Citation {
+            index: 0,
+            kind: CitationKind::Run,
+            flow_id: flow.id.clone(),
+            flow_name: flow.name.clone(),
+            title: format!("{} — {}", flow.name, date.format("%Y-%m-%d %H:%M UTC")),
+            link: format!("/api/flows/{}/runs/{}", flow.id, run.id),
+            run_id: Some(run.id.clone()),
+            date,
+            cited: false,
+            excerpt: run.output.as_deref().unwrap_or_default().chars().take(RUN_EXCERPT_CHARS).collect(),
+        }
+    });
+    let items = items.iter().map(|item| Citation {
+        index: 0,
+        kind: CitationKind::Item,
+        flow_id: item.flow_id.clone(),
+        flow_name: item.flow_name.clone(),
+        title: item.title.clone(),
+        link: item.url.clone(),
+        run_id: None,
+        date: item.published.unwrap_or(item.archived_at),
+        cited: false,
+        excerpt: item.snippet.clone(),
+    });
+    runs.chain(items)
+        .enumerate()
+        .map(|(i, citation)| Citation { index: i + 1, ..citation })
+        .collect()
+}
+
+/// The executor prompt: numbered sources, then the question, asking for
+/// `[n]` markers on every claim.
+pub fn build_prompt(question: &str, sources: &[Citation]) -> String {
+    let mut prompt = String::from(
+        "Answer the question using only the sources below, collected by earlier automated \
+         flow runs. Cite every claim with the source's number in square brackets, e.g. [2]. \
+         If the sources don't answer the question, say so.\n\n## Sources\n\n",
+    );
+    for source in sources {
+        let kind = match source.kind {
+            CitationKind::Run => "run output",
+            CitationKind::Item => "item",
+        };
+        prompt.push_str(&format!(
+            "[{}] {} ({kind}, flow \"{}\", {})\n{}\n\n",
+            source.index,
+            source.title,
+            source.flow_name,
+            source.date.format("%Y-%m-%d"),
+            source.excerpt.trim(),
+        ));
+    }
+    prompt.push_str(&format!("## Question\n\n{question}\n"));
+    prompt
+}
+
+/// Mark the sources the answer refers to with `[n]` (or `[n, m]`).
+pub fn mark_cited(answer: &str, sources: &mut [Citation]) {
+    let mut cited = HashSet::new();
+    for group in answer.split('[').skip(1).filter_map(|rest| rest.split_once(']')) {
+        cited.extend(group.0.split(',').filter_map(|n| n.trim().parse::<usize>().ok()));
+    }
+    for source in sources {
+        source.cited = cited.contains(&source.index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn flow(id: &str) -> Flow {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": format!("Flow {id}"),
+            "nodes": [],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn run(id: &str, day: u32, status: RunStatus, output: &str) -> FlowRun {
+        FlowRun {
+            id: id.to_string(),
+            flow_id: "f".to_string(),
+            status,
+            started_at: Utc.with_ymd_and_hms(2026, 3, day, 9, 0, 0).unwrap(),
+            finished_at: None,
+            node_runs: Vec::new(),
+            error: None,
+            output: Some(output.to_string()),
+            rating: None,
+            chat: None,
+            cost_usd: None,
+            inputs: None,
+            rerun_of: None,
+            dry_run: false,
+            replica: None,
+        }
+    }
+
+    #[test]
+    fn test_relevant_runs_rank_by_shared_terms_then_recency() {
+        let history = vec![(
+            flow("f"),
+            vec![
+                run("old", 1, RunStatus::Success, "Postgres vacuum tuning and Postgres indexes"),
+                run("new", 5, RunStatus::Success, "Postgres release notes"),
+                run("best", 2, RunStatus::Success, "Postgres indexes got faster"),
+                run("failed", 6, RunStatus::Failed, "Postgres indexes"),
+                run("unrelated", 7, RunStatus::Success, "Kubernetes upgrade"),
+            ],
+        )];
+        let ranked = relevant_runs(&history, "What did we learn about Postgres indexes?", 10);
+        let ids: Vec<&str> = ranked.iter().map(|(_, r)| r.id.as_str()).collect();
+        assert_eq!(ids, ["best", "old", "new"]);
+        assert_eq!(relevant_runs(&history, "what about it?", 10).len(), 0);
+    }
+
+    #[test]
+    fn test_citations_prompt_and_marking() {
+        let history = vec![(flow("f"), vec![run("r1", 1, RunStatus::Success, "Rust 2.0 shipped.")])];
+        let runs = relevant_runs(&history, "rust", 5);
+        let items = vec![ArchivedItem {
+            flow_id: "g".to_string(),
+            flow_name: "Flow g".to_string(),
+            title: "Rust blog".to_string(),
+            url: "https://blog.rust-lang.org".to_string(),
+            snippet: "Rust 2.0 is out".to_string(),
+            published: None,
+            archived_at: Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap(),
+        }];
+        let mut sources = citations(&runs, &items);
+        assert_eq!(sources[0].link, "/api/flows/f/runs/r1");
+        assert_eq!(sources[1].index, 2);
+        assert_eq!(sources[1].link, "https://blog.rust-lang.org");
+
+        let prompt = build_prompt("When did Rust 2.0 ship?", &sources);
+        assert!(prompt.contains("[1] Flow f — 2026-03-01 09:00 UTC (run output, flow \"Flow f\", 2026-03-01)\nRust 2.0 shipped."));
+        assert!(prompt.contains("[2] Rust blog (item"));
+        assert!(prompt.ends_with("## Question\n\nWhen did Rust 2.0 ship?\n"));
+
+        mark_cited("It shipped on March 1 [2]. See also [link].", &mut sources);
+        assert!(!sources[0].cited);
+        assert!(sources[1].cited);
+        mark_cited("Both agree [1, 2].", &mut sources);
+        assert!(sources.iter().all(|s| s.cited));
+    }
+}
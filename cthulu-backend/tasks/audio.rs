@@ -0,0 +1,480 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::egress::AuditedSend;
+use crate::json_file;
+
+/// Sink kind that reads the digest aloud into an MP3, for the Slack channel
+/// and/or the flow's podcast feed.
+pub const AUDIO_KIND: &str = "audio";
+
+/// Episodes kept per flow; older files are deleted.
+pub const DEFAULT_KEEP_EPISODES: usize = 30;
+
+const DEFAULT_OPENAI_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini-tts";
+const DEFAULT_OPENAI_VOICE: &str = "alloy";
+
+/// Longest input `/audio/speech` accepts; longer digests are read in parts
+/// and the MP3s joined (MP3 frames concatenate cleanly).
+const OPENAI_MAX_CHARS: usize = 4_096;
+
+const SYNTHESIS_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Serializes read-modify-write of episode lists between concurrent runs.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// One rendered digest in a flow's feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Episode {
+    pub id: String,
+    pub title: String,
+    /// MP3 file name inside the flow's audio directory.
+    pub file: String,
+    pub bytes: u64,
+    pub published_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+}
+
+/// MP3s and episode lists under `~/.cthulu/audio/<flow_id>/`, served as a
+/// podcast feed by `GET /api/audio/{flow_id}/feed.xml`.
+#[derive(Clone)]
+pub struct AudioStore {
+    dir: PathBuf,
+}
+
+impl AudioStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { dir: data_dir.join("audio") }
+    }
+
+    fn flow_dir(&self, flow_id: &str) -> PathBuf {
+        self.dir.join(flow_id)
+    }
+
+    /// The flow's episodes, newest first.
+    pub fn episodes(&self, flow_id: &str) -> Vec<Episode> {
+        json_file::load_or_default(&self.flow_dir(flow_id).join("episodes.json"))
+    }
+
+    /// Save `mp3` as a new episode, dropping all but the newest `keep`.
+    pub fn add(
+        &self,
+        flow_id: &str,
+        title: &str,
+        run_id: Option<&str>,
+        mp3: &[u8],
+        keep: usize,
+    ) -> Result<Episode> {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = self.flow_dir(flow_id);
+        std::fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+        let now = Utc::now();
+        let id = uuid::Uuid::new_v4().to_string();
+        let episode = Episode {
+            file: format!("{}-{}.mp3", now.format("%Y%m%d-%H%M%S"), &id[..8]),
+            id,
+            title: title.to_string(),
+            bytes: mp3.len() as u64,
+            published_at: now,
+            run_id: run_id.map(str::to_string),
+        };
+        std::fs::write(dir.join(&episode.file), mp3).context("failed to write episode audio")?;
+
+        let mut episodes = self.episodes(flow_id);
+        episodes.insert(0, episode.clone());
+        for old in episodes.drain(keep.max(1).min(episodes.len())..) {
+            let _ = std::fs::remove_file(dir.join(&old.file));
+        }
+        json_file::write(&dir.join("episodes.json"), &episodes)?;
+        Ok(episode)
+    }
+
+    /// Path of an episode's MP3, only for files the flow's list names.
+    pub fn episode_path(&self, flow_id: &str, file: &str) -> Option<PathBuf> {
+        self.episodes(flow_id)
+            .iter()
+            .any(|e| e.file == file)
+            .then(|| self.flow_dir(flow_id).join(file))
+    }
+}
+
+/// Text-to-speech backend, from an `audio` node's `provider` config.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TtsProvider {
+    /// `POST {base_url}/audio/speech`: OpenAI or any compatible server.
+    OpenAi {
+        base_url: String,
+        api_key: Option<String>,
+        model: String,
+        voice: String,
+        /// Speaking style, for models that take it (`gpt-4o-mini-tts`).
+        instructions: Option<String>,
+    },
+    /// The local `piper` binary, its WAV converted with `ffmpeg`.
+    Piper {
+        bin: String,
+        /// Path to the `.onnx` voice.
+        voice_model: String,
+        ffmpeg: String,
+    },
+}
+
+impl TtsProvider {
+    pub fn from_config(config: &Value) -> Result<Self> {
+        match config["provider"].as_str().unwrap_or("openai") {
+            "openai" => {
+                let key_env = config["api_key_env"].as_str().unwrap_or("OPENAI_API_KEY");
+                let api_key = crate::secrets::var(key_env).ok().filter(|k| !k.is_empty());
+                let base_url = config["base_url"].as_str().unwrap_or(DEFAULT_OPENAI_URL);
+                if api_key.is_none() && base_url == DEFAULT_OPENAI_URL {
+                    anyhow::bail!("audio provider openai requires env var {key_env}");
+                }
+                Ok(Self::OpenAi {
+                    base_url: base_url.trim_end_matches('/').to_string(),
+                    api_key,
+                    model: config["model"].as_str().unwrap_or(DEFAULT_OPENAI_MODEL).to_string(),
+                    voice: config["voice"].as_str().unwrap_or(DEFAULT_OPENAI_VOICE).to_string(),
+                    instructions: config["instructions"].as_str().map(str::to_string),
+                })
+            }
+            "piper" => Ok(Self::Piper {
+                bin: config["piper_bin"].as_str().unwrap_or("piper").to_string(),
+                voice_model: config["voice_model"]
+                    .as_str()
+                    .context("audio provider piper requires voice_model (path to an .onnx voice)")?
+                    .to_string(),
+                ffmpeg: config["ffmpeg_bin"].as_str().unwrap_or("ffmpeg").to_string(),
+            }),
+            other => anyhow::bail!("unknown audio provider '{other}' (expected openai or piper)"),
+        }
+    }
+
+    /// Read `text` aloud as MP3 bytes.
+    pub async fn synthesize(&self, http_client: &reqwest::Client, text: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::OpenAi { base_url, api_key, model, voice, instructions } => {
+                let mut mp3 = Vec::new();
+                for part in chunks(text, OPENAI_MAX_CHARS) {
+                    let mut body = json!({
+                        "model": model,
+                        "voice": voice,
+                        "input": part,
+                        "response_format": "mp3",
+                    });
+                    if let Some(instructions) = instructions {
+                        body["instructions"] = json!(instructions);
+                    }
+                    let mut request = http_client
+                        .post(format!("{base_url}/audio/speech"))
+                        .timeout(SYNTHESIS_TIMEOUT)
+                        .json(&body);
+                    if let Some(key) = api_key {
+                        request = request.bearer_auth(key);
+                    }
+                    let response = request.send_audited().await.context("speech request failed")?;
+                    let status = response.status();
+                    if !status.is_success() {
+                        let body = response.text().await.unwrap_or_default();
+                        anyhow::bail!("speech API returned {status}: {body}");
+                    }
+                    mp3.extend_from_slice(&response.bytes().await.context("failed to read speech audio")?);
+                }
+                Ok(mp3)
+            }
+            Self::Piper { bin, voice_model, ffmpeg } => {
+                let dir = std::env::temp_dir().join(format!("cthulu-tts-{}", uuid::Uuid::new_v4()));
+                std::fs::create_dir_all(&dir)?;
+                let result = piper_to_mp3(bin, voice_model, ffmpeg, text, &dir).await;
+                let _ = std::fs::remove_dir_all(&dir);
+                result
+            }
+        }
+    }
+}
+
+async fn piper_to_mp3(bin: &str, voice_model: &str, ffmpeg: &str, text: &str, dir: &Path) -> Result<Vec<u8>> {
+    let wav = dir.join("digest.wav");
+    let mp3 = dir.join("digest.mp3");
+
+    let mut child = Command::new(bin)
+        .arg("--model")
+        .arg(voice_model)
+        .arg("--output_file")
+        .arg(&wav)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to spawn {bin}"))?;
+    let mut stdin = child.stdin.take().context("piper stdin not piped")?;
+    stdin.write_all(text.as_bytes()).await?;
+    drop(stdin);
+    let output = tokio::time::timeout(SYNTHESIS_TIMEOUT, child.wait_with_output())
+        .await
+        .context("piper timed out")??;
+    if !output.status.success() {
+        anyhow::bail!("piper failed ({}): {}", output.status, String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    let output = Command::new(ffmpeg)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&wav)
+        .args(["-codec:a", "libmp3lame", "-q:a", "4"])
+        .arg(&mp3)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("failed to spawn {ffmpeg}"))?;
+    if !output.status.success() {
+        anyhow::bail!("ffmpeg failed ({}): {}", output.status, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    std::fs::read(&mp3).context("failed to read converted MP3")
+}
+
+/// Markdown turned into something worth hearing: no markup, link text
+/// without URLs, one paragraph per heading or list item.
+pub fn speakable(markdown: &str) -> String {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_code = false;
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        let rule = !line.is_empty() && line.chars().all(|c| matches!(c, '-' | '*' | '_' | '='));
+        if in_code || rule || line == "---THREAD---" {
+            continue;
+        }
+        let heading = line.starts_with('#');
+        let list_item = line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ")
+            || line.split_once(". ").is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        if line.is_empty() || heading || list_item {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            if line.is_empty() {
+                continue;
+            }
+        }
+        let text = line
+            .trim_start_matches('#')
+            .trim_start_matches(['-', '*', '+', '>'])
+            .trim();
+        let text = match text.split_once(". ") {
+            Some((n, rest)) if list_item && n.chars().all(|c| c.is_ascii_digit()) => rest,
+            _ => text,
+        };
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&strip_inline(text));
+        if heading {
+            // A heading reads as its own sentence
+            if !current.ends_with(['.', '!', '?', ':']) {
+                current.push('.');
+            }
+            paragraphs.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs.retain(|p| !p.trim().is_empty());
+    paragraphs.join("\n\n")
+}
+
+/// Drop bold/italic and code markers, keep link text, drop bare URLs.
+fn strip_inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.split_once("](").and_then(|(label, tail)| tail.split_once(')').map(|(_, t)| (label, t))) {
+            Some((label, tail)) => {
+                out.push_str(label);
+                rest = tail;
+            }
+            None => {
+                out.push('[');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    let out: String = out.chars().filter(|c| !matches!(c, '*' | '`')).collect();
+    out.split_whitespace()
+        .filter(|w| !w.starts_with("http://") && !w.starts_with("https://") && !w.starts_with("<http"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split on paragraph, then sentence, boundaries into parts of at most
+/// `max` characters.
+fn chunks(text: &str, max: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let pieces = text
+        .split("\n\n")
+        .flat_map(|p| p.split_inclusive(". "))
+        .flat_map(|s| {
+            let chars: Vec<char> = s.chars().collect();
+            chars.chunks(max).map(|c| c.iter().collect::<String>()).collect::<Vec<_>>()
+        });
+    for piece in pieces {
+        if !current.is_empty() && current.chars().count() + piece.chars().count() + 1 > max {
+            parts.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() && !current.ends_with(' ') {
+            current.push(' ');
+        }
+        current.push_str(&piece);
+    }
+    if !current.trim().is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// RSS 2.0 podcast feed of a flow's episodes. `base_url` is where the API
+/// is reachable from the listener's app, e.g. `https://cthulu.example.com`.
+pub fn feed_xml(flow_id: &str, flow_name: &str, base_url: &str, episodes: &[Episode]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">\n\
+         <channel>\n\
+         <title>{}</title>\n\
+         <link>{base_url}/api/audio/{flow_id}/feed.xml</link>\n\
+         <description>Audio digests from the Cthulu flow {}</description>\n\
+         <language>en</language>\n\
+         <itunes:author>Cthulu</itunes:author>\n",
+        xml_escape(flow_name),
+        xml_escape(flow_name),
+    );
+    for episode in episodes {
+        xml.push_str(&format!(
+            "<item>\n\
+             <title>{}</title>\n\
+             <guid isPermaLink=\"false\">{}</guid>\n\
+             <pubDate>{}</pubDate>\n\
+             <enclosure url=\"{base_url}/api/audio/{flow_id}/episodes/{}\" length=\"{}\" type=\"audio/mpeg\"/>\n\
+             </item>\n",
+            xml_escape(&episode.title),
+            episode.id,
+            episode.published_at.to_rfc2822(),
+            episode.file,
+            episode.bytes,
+        ));
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speakable() {
+        let markdown = "# Morning brief\n\n\
+            Two things **matter** today:\n\n\
+            - [Rust 2.0](https://blog.rust-lang.org) shipped\n\
+            - Postgres `18` is out, see https://postgresql.org\n\
+            1. Numbered item\n\n\
+            ```\nlet x = 1;\n```\n\n---\n";
+        assert_eq!(
+            speakable(markdown),
+            "Morning brief.\n\nTwo things matter today:\n\nRust 2.0 shipped\n\n\
+             Postgres 18 is out, see\n\nNumbered item"
+        );
+    }
+
+    #[test]
+    fn test_chunks_respect_limit_and_sentences() {
+        let text = "First sentence. Second sentence.\n\nThird one here.";
+        assert_eq!(chunks(text, 100), vec!["First sentence. Second sentence. Third one here."]);
+        let parts = chunks(text, 20);
+        assert_eq!(parts, vec!["First sentence. ", "Second sentence.", "Third one here."]);
+        assert!(chunks(&"x".repeat(50), 20).iter().all(|p| p.chars().count() <= 20));
+    }
+
+    #[test]
+    fn test_store_keeps_newest_episodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AudioStore::new(dir.path());
+        let first = store.add("flow-1", "Day 1", Some("r1"), b"one", 2).unwrap();
+        store.add("flow-1", "Day 2", None, b"two", 2).unwrap();
+        let third = store.add("flow-1", "Day 3", None, b"three", 2).unwrap();
+
+        let episodes = store.episodes("flow-1");
+        let titles: Vec<&str> = episodes.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["Day 3", "Day 2"]);
+        assert_eq!(episodes[0].bytes, 5);
+        assert!(store.episode_path("flow-1", &first.file).is_none());
+        assert!(!dir.path().join("audio/flow-1").join(&first.file).exists());
+        assert!(store.episode_path("flow-1", &third.file).unwrap().exists());
+        assert!(store.episode_path("flow-1", "../../secrets").is_none());
+    }
+
+    #[test]
+    fn test_feed_xml() {
+        let episode = Episode {
+            id: "e1".to_string(),
+            title: "Brief & news".to_string(),
+            file: "20260301-070000-abcd1234.mp3".to_string(),
+            bytes: 1234,
+            published_at: "2026-03-01T07:00:00Z".parse().unwrap(),
+            run_id: None,
+        };
+        let xml = feed_xml("f1", "Morning <brief>", "https://cthulu.example.com", &[episode]);
+        assert!(xml.contains("<title>Morning &lt;brief&gt;</title>"));
+        assert!(xml.contains("<title>Brief &amp; news</title>"));
+        assert!(xml.contains(
+            "<enclosure url=\"https://cthulu.example.com/api/audio/f1/episodes/20260301-070000-abcd1234.mp3\" length=\"1234\" type=\"audio/mpeg\"/>"
+        ));
+        assert!(xml.contains("<pubDate>Sun, 1 Mar 2026 07:00:00 +0000</pubDate>"));
+    }
+
+    #[test]
+    fn test_provider_from_config() {
+        let piper = TtsProvider::from_config(&json!({ "provider": "piper", "voice_model": "/voices/en.onnx" })).unwrap();
+        assert_eq!(
+            piper,
+            TtsProvider::Piper {
+                bin: "piper".to_string(),
+                voice_model: "/voices/en.onnx".to_string(),
+                ffmpeg: "ffmpeg".to_string(),
+            }
+        );
+        assert!(TtsProvider::from_config(&json!({ "provider": "piper" })).is_err());
+        assert!(TtsProvider::from_config(&json!({ "provider": "espeak" })).is_err());
+        // A local OpenAI-compatible server needs no key
+        let local = json!({ "base_url": "http://localhost:8880/v1", "api_key_env": "CTHULU_TEST_UNSET_TTS_KEY" });
+        assert!(matches!(
+            TtsProvider::from_config(&local).unwrap(),
+            TtsProvider::OpenAi { api_key: None, .. }
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Entries written before they recorded their TTL are pruned at this age.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// A directory of `{key}.json` entries that expire after a TTL, shared by
+/// the source and executor caches. The TTL is the node's, given on each
+/// read, so changing it keeps the entries; an entry read after it expired
+/// is deleted, and [`TtlCache::prune`] removes the ones nothing reads again.
+pub struct TtlCache<T> {
+    dir: PathBuf,
+    entry: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for TtlCache<T> {
+    fn clone(&self) -> Self {
+        Self { dir: self.dir.clone(), entry: PhantomData }
+    }
+}
+
+/// What's on disk: `T`'s fields next to when it was cached.
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    created_at: DateTime<Utc>,
+    /// The TTL the entry was written under, for pruning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl_secs: Option<u64>,
+    #[serde(flatten)]
+    value: T,
+}
+
+/// Just the part of an entry pruning looks at.
+#[derive(Deserialize)]
+struct Stamp {
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    ttl_secs: Option<u64>,
+}
+
+fn expired(created_at: DateTime<Utc>, ttl: Duration, now: DateTime<Utc>) -> bool {
+    now.signed_duration_since(created_at).to_std().is_ok_and(|age| age > ttl)
+}
+
+impl<T: Serialize + DeserializeOwned> TtlCache<T> {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, entry: PhantomData }
+    }
+
+    pub(crate) fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    /// The entry for `key` if present and younger than `ttl`.
+    pub async fn get(&self, key: &str, ttl: Duration) -> Option<T> {
+        let path = self.path(key);
+        let content = tokio::fs::read_to_string(&path).await.ok()?;
+        let entry: Entry<T> = serde_json::from_str(&content).ok()?;
+        if expired(entry.created_at, ttl, Utc::now()) {
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+        Some(entry.value)
+    }
+
+    pub async fn put(&self, key: &str, ttl: Duration, value: T) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("failed to create cache dir: {}", self.dir.display()))?;
+        let entry = Entry { created_at: Utc::now(), ttl_secs: Some(ttl.as_secs()), value };
+        let path = self.path(key);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string_pretty(&entry)?)
+            .await
+            .with_context(|| format!("failed to write cache entry: {}", tmp_path.display()))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .with_context(|| format!("failed to rename cache entry: {}", path.display()))
+    }
+
+    /// Delete every entry past the TTL it was written under, and ones that
+    /// don't parse. Returns how many went.
+    pub async fn prune(&self) -> usize {
+        prune_dir(&self.dir, Utc::now()).await
+    }
+}
+
+async fn prune_dir(dir: &Path, now: DateTime<Utc>) -> usize {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return 0;
+    };
+    let mut removed = 0;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let stale = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str::<Stamp>(&content).map_or(true, |stamp| {
+                let ttl = stamp.ttl_secs.map_or(DEFAULT_MAX_AGE, Duration::from_secs);
+                expired(stamp.created_at, ttl, now)
+            }),
+            Err(_) => continue,
+        };
+        if stale && tokio::fs::remove_file(&path).await.is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Value {
+        text: String,
+    }
+
+    fn value(text: &str) -> Value {
+        Value { text: text.to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_is_deleted_on_read() {
+        let dir = tempdir().unwrap();
+        let cache: TtlCache<Value> = TtlCache::new(dir.path().to_path_buf());
+        cache.put("k", Duration::from_secs(60), value("v")).await.unwrap();
+        assert_eq!(cache.get("k", Duration::from_secs(60)).await, Some(value("v")));
+
+        let path = cache.path("k");
+        let entry = Entry { created_at: Utc::now() - chrono::Duration::hours(2), ttl_secs: Some(60), value: value("v") };
+        std::fs::write(&path, serde_json::to_string(&entry).unwrap()).unwrap();
+        assert!(cache.get("k", Duration::from_secs(3 * 3600)).await.is_some());
+        assert!(cache.get("k", Duration::from_secs(3600)).await.is_none());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_prune() {
+        let dir = tempdir().unwrap();
+        let cache: TtlCache<Value> = TtlCache::new(dir.path().to_path_buf());
+        let write = |key: &str, hours_ago: i64, ttl_secs: Option<u64>| {
+            let entry = Entry { created_at: Utc::now() - chrono::Duration::hours(hours_ago), ttl_secs, value: value(key) };
+            std::fs::write(cache.path(key), serde_json::to_string(&entry).unwrap()).unwrap();
+        };
+        write("fresh", 1, Some(7200));
+        write("stale", 3, Some(7200));
+        write("untimed", 24, None);
+        write("ancient", 24 * 31, None);
+        std::fs::write(cache.path("corrupt"), "{").unwrap();
+
+        assert_eq!(cache.prune().await, 3);
+        let mut left: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["fresh.json", "untimed.json"]);
+    }
+}
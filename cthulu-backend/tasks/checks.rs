@@ -0,0 +1,493 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::github::models::PullRequest;
+use crate::sandbox::handle::SandboxHandle;
+use crate::sandbox::provider::SandboxProvider;
+use crate::sandbox::types::*;
+use crate::tasks::executors::sandbox::runs_on_host;
+use crate::tasks::reports::{self, ReportFormat};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+/// Failures usually surface at the end of the output, so the tail is kept.
+const DEFAULT_MAX_OUTPUT_CHARS: usize = 8_000;
+
+/// Archive of the PR head, extracted into `SOURCE_DIR` inside the sandbox.
+const ARCHIVE_PATH: &str = "pr-source.tar";
+const SOURCE_DIR: &str = "pr-source";
+
+/// Report artifacts larger than this are skipped.
+const MAX_REPORT_BYTES: usize = 5 * 1024 * 1024;
+/// Files or findings listed per report in `{{ci_reports}}`.
+const REPORT_ENTRIES: usize = 10;
+
+/// A github-pr trigger's `pre_review` config: a test/lint command run in a
+/// sandbox at the PR head before the review.
+#[derive(Debug, Clone)]
+pub struct CheckConfig {
+    pub command: String,
+    pub timeout: Duration,
+    pub max_output_chars: usize,
+    /// PR code reaching the network can exfiltrate whatever the sandbox
+    /// sees, so this is opt-in.
+    pub network: bool,
+    /// Also run for PRs from forks or by non-maintainers, who can put any
+    /// command in the repo's scripts.
+    pub allow_untrusted: bool,
+    /// Artifacts the command writes (path relative to the repo root, and
+    /// format), summarized into `{{ci_reports}}`.
+    pub reports: Vec<(String, ReportFormat)>,
+}
+
+impl CheckConfig {
+    /// `None` when the trigger has no `pre_review.command`.
+    pub fn from_trigger(trigger_config: &serde_json::Value) -> Option<Self> {
+        let cfg = &trigger_config["pre_review"];
+        let command = cfg["command"].as_str().filter(|c| !c.trim().is_empty())?;
+        Some(Self {
+            command: command.to_string(),
+            timeout: Duration::from_secs(cfg["timeout_secs"].as_u64().unwrap_or(DEFAULT_TIMEOUT_SECS)),
+            max_output_chars: cfg["max_output_chars"]
+                .as_u64()
+                .map(|n| n as usize)
+                .unwrap_or(DEFAULT_MAX_OUTPUT_CHARS),
+            network: cfg["network"].as_bool().unwrap_or(false),
+            allow_untrusted: cfg["allow_untrusted"].as_bool().unwrap_or(false),
+            reports: cfg["reports"]
+                .as_array()
+                .map(|arr| arr.iter().filter_map(parse_report_spec).collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    TimedOut,
+    /// The check couldn't be run (checkout or sandbox failure).
+    Error,
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStatus::Passed => write!(f, "passed"),
+            CheckStatus::Failed => write!(f, "failed"),
+            CheckStatus::TimedOut => write!(f, "timed_out"),
+            CheckStatus::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    pub command: String,
+    pub status: CheckStatus,
+    pub exit_code: Option<i32>,
+    /// Tail of combined stdout/stderr (or the error, for `Error`).
+    pub output: String,
+    /// Summaries of the configured report artifacts.
+    pub reports: Vec<String>,
+}
+
+impl CheckReport {
+    fn error(config: &CheckConfig, err: &anyhow::Error) -> Self {
+        Self {
+            command: config.command.clone(),
+            status: CheckStatus::Error,
+            exit_code: None,
+            output: format!("{err:#}"),
+            reports: Vec::new(),
+        }
+    }
+
+    /// `{{test_status}}`, `{{test_results}}` and, when reports are configured,
+    /// `{{ci_reports}}`. Output is only included when the check didn't pass —
+    /// that's what the review needs to act on.
+    pub fn vars(&self) -> HashMap<String, String> {
+        let results = match self.status {
+            CheckStatus::Passed => format!("`{}` passed.", self.command),
+            CheckStatus::Failed => format!(
+                "`{}` failed (exit code {}). Output (tail):\n```\n{}\n```",
+                self.command,
+                self.exit_code.map_or("unknown".to_string(), |c| c.to_string()),
+                self.output
+            ),
+            CheckStatus::TimedOut => format!(
+                "`{}` timed out. Output before the timeout (tail):\n```\n{}\n```",
+                self.command, self.output
+            ),
+            CheckStatus::Error => format!("`{}` could not be run: {}", self.command, self.output),
+        };
+        let mut vars = HashMap::from([
+            ("test_status".to_string(), self.status.to_string()),
+            ("test_results".to_string(), results),
+        ]);
+        if !self.reports.is_empty() {
+            vars.insert("ci_reports".to_string(), self.reports.join("\n\n"));
+        }
+        vars
+    }
+}
+
+/// Check out the PR head from the local clone at `repo_path`, copy it into
+/// a fresh sandbox, and run `config.command` there with the platform shell
+/// (`sh -c`, or `cmd /C` on Windows). Refused on the host provider, and for
+/// PRs from forks or non-maintainers unless `allow_untrusted` is set.
+///
+/// Never fails: problems before the command runs are reported as
+/// `CheckStatus::Error` so the review still goes ahead.
+pub async fn run(
+    provider: &dyn SandboxProvider,
+    repo_path: &Path,
+    pr: &PullRequest,
+    config: &CheckConfig,
+) -> CheckReport {
+    if runs_on_host(provider) {
+        let err = anyhow::anyhow!("refusing to run PR code on the host; configure a sandbox provider");
+        tracing::warn!(pr = pr.number, "Skipping pre-review check: {err}");
+        return CheckReport::error(config, &err);
+    }
+    if !config.allow_untrusted && !pr.by_maintainer() {
+        let err = anyhow::anyhow!("skipped for a PR from a fork or a non-maintainer (see pre_review.allow_untrusted)");
+        tracing::info!(pr = pr.number, "Skipping pre-review check: {err}");
+        return CheckReport::error(config, &err);
+    }
+    run_in_fresh_sandbox(provider, repo_path, pr.number, &pr.head.sha, config).await
+}
+
+async fn run_in_fresh_sandbox(
+    provider: &dyn SandboxProvider,
+    repo_path: &Path,
+    pr_number: u64,
+    head_sha: &str,
+    config: &CheckConfig,
+) -> CheckReport {
+    let archive = match archive_head(repo_path, pr_number, head_sha).await {
+        Ok(bytes) => bytes,
+        Err(e) => return CheckReport::error(config, &e),
+    };
+
+    let spec = SandboxSpec {
+        workspace_id: format!("pr-check-{pr_number}-{}", uuid::Uuid::new_v4()),
+        profile: SandboxProfile::FullDev,
+        filesystem: FilesystemSpec::default(),
+        resources: ResourceHints::default(),
+        env: BTreeMap::new(),
+        mounts: vec![],
+        network: if config.network {
+            NetworkPolicy {
+                mode: NetworkMode::AllowAll,
+                ..NetworkPolicy::default_safe()
+            }
+        } else {
+            NetworkPolicy::default_safe()
+        },
+        lifecycle: LifecyclePolicy::default(),
+        labels: BTreeMap::from([("purpose".into(), "pr-check".into())]),
+    };
+    let handle = match provider.provision(spec).await {
+        Ok(h) => h,
+        Err(e) => return CheckReport::error(config, &anyhow::anyhow!("sandbox provision failed: {e}")),
+    };
+
+    let report = run_in_sandbox(&*handle, archive, config)
+        .await
+        .unwrap_or_else(|e| CheckReport::error(config, &e));
+    // Each check gets a fresh sandbox, so always tear it down.
+    let _ = handle.destroy().await;
+
+    tracing::info!(
+        pr = pr_number,
+        command = %config.command,
+        status = %report.status,
+        "Pre-review check finished"
+    );
+    report
+}
+
+async fn run_in_sandbox(
+    handle: &dyn SandboxHandle,
+    archive: Vec<u8>,
+    config: &CheckConfig,
+) -> Result<CheckReport> {
+    handle
+        .put_file(PutFileRequest {
+            path: ARCHIVE_PATH.to_string(),
+            bytes: archive,
+            create_parents: true,
+            mode: None,
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to copy PR source into sandbox: {e}"))?;
+
+    let extract = handle
+        .exec(shell(
+            format!("mkdir -p {SOURCE_DIR} && tar -xf {ARCHIVE_PATH} -C {SOURCE_DIR} && rm {ARCHIVE_PATH}"),
+            Duration::from_secs(120),
+        ))
+        .await
+        .map_err(|e| anyhow::anyhow!("sandbox exec failed: {e}"))?;
+    if extract.exit_code != Some(0) {
+        anyhow::bail!(
+            "failed to extract PR source: {}",
+            String::from_utf8_lossy(&extract.stderr).trim()
+        );
+    }
+
+    let result = handle
+        .exec(shell(format!("cd {SOURCE_DIR} && {}", config.command), config.timeout))
+        .await
+        .map_err(|e| anyhow::anyhow!("sandbox exec failed: {e}"))?;
+
+    let status = match (result.timed_out, result.exit_code) {
+        (true, _) => CheckStatus::TimedOut,
+        (false, Some(0)) => CheckStatus::Passed,
+        _ => CheckStatus::Failed,
+    };
+    let mut output = String::from_utf8_lossy(&result.stdout).into_owned();
+    output.push_str(&String::from_utf8_lossy(&result.stderr));
+
+    let mut reports = Vec::new();
+    for (path, format) in &config.reports {
+        reports.push(read_report(handle, path, *format).await);
+    }
+
+    Ok(CheckReport {
+        command: config.command.clone(),
+        status,
+        exit_code: result.exit_code,
+        output: tail(output.trim(), config.max_output_chars),
+        reports,
+    })
+}
+
+/// `{ path, format }` from `pre_review.reports`; bad entries are skipped.
+fn parse_report_spec(spec: &serde_json::Value) -> Option<(String, ReportFormat)> {
+    let path = spec["path"].as_str()?;
+    match ReportFormat::parse_name(spec["format"].as_str().unwrap_or_default()) {
+        Ok(format) => Some((path.trim_start_matches('/').to_string(), format)),
+        Err(e) => {
+            tracing::warn!(path, error = %e, "Ignoring pre_review report");
+            None
+        }
+    }
+}
+
+/// Summary of one report artifact, or a note on why it's missing.
+async fn read_report(handle: &dyn SandboxHandle, path: &str, format: ReportFormat) -> String {
+    let file = handle
+        .get_file(GetFileRequest {
+            path: format!("{SOURCE_DIR}/{path}"),
+            max_bytes: Some(MAX_REPORT_BYTES),
+        })
+        .await;
+    let parsed = match file {
+        Ok(resp) if resp.truncated => Err(anyhow::anyhow!("larger than {MAX_REPORT_BYTES} bytes")),
+        Ok(resp) => reports::parse(format, &String::from_utf8_lossy(&resp.bytes)),
+        Err(e) => Err(anyhow::anyhow!("not found ({e})")),
+    };
+    match parsed {
+        Ok(report) => format!("`{path}`: {}", report.summary(format, REPORT_ENTRIES)),
+        Err(e) => format!("`{path}`: could not be read: {e:#}"),
+    }
+}
+
+/// Tar of the tree at `head_sha`. Fetches the PR's head ref first so heads
+/// from forks are available locally.
+async fn archive_head(repo_path: &Path, pr_number: u64, head_sha: &str) -> Result<Vec<u8>> {
+    let fetch = tokio::process::Command::new("git")
+        .args(["fetch", "--quiet", "origin", &format!("pull/{pr_number}/head")])
+        .current_dir(repo_path)
+        .output()
+        .await
+        .context("failed to run git fetch")?;
+    if !fetch.status.success() {
+        tracing::warn!(
+            pr = pr_number,
+            stderr = %String::from_utf8_lossy(&fetch.stderr).trim(),
+            "Failed to fetch PR head, trying the local object store"
+        );
+    }
+
+    let archive = tokio::process::Command::new("git")
+        .args(["archive", "--format=tar", head_sha])
+        .current_dir(repo_path)
+        .output()
+        .await
+        .context("failed to run git archive")?;
+    if !archive.status.success() {
+        anyhow::bail!(
+            "git archive of {head_sha} failed in {}: {}",
+            repo_path.display(),
+            String::from_utf8_lossy(&archive.stderr).trim()
+        );
+    }
+    Ok(archive.stdout)
+}
+
+fn shell(script: String, timeout: Duration) -> ExecRequest {
+    ExecRequest {
+        command: crate::platform::shell(&script),
+        cwd: None,
+        env: BTreeMap::new(),
+        stdin: None,
+        timeout: Some(timeout),
+        tty: false,
+        detach: false,
+    }
+}
+
+/// Last `max_chars` characters of `text`, starting at a line boundary when
+/// one is nearby.
+fn tail(text: &str, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+    let start = text.char_indices().nth(total - max_chars).map_or(0, |(i, _)| i);
+    let cut = &text[start..];
+    let cut = cut.split_once('\n').map_or(cut, |(_, rest)| rest);
+    format!("… (truncated)\n{cut}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::backends::dangerous::DangerousHostProvider;
+
+    fn config(command: &str) -> CheckConfig {
+        CheckConfig::from_trigger(&serde_json::json!({
+            "pre_review": { "command": command, "timeout_secs": 30, "network": false }
+        }))
+        .unwrap()
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let out = std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "git {args:?}: {}", String::from_utf8_lossy(&out.stderr));
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_from_trigger() {
+        assert!(CheckConfig::from_trigger(&serde_json::json!({})).is_none());
+        assert!(CheckConfig::from_trigger(&serde_json::json!({ "pre_review": { "command": " " } })).is_none());
+
+        let cfg = CheckConfig::from_trigger(&serde_json::json!({ "pre_review": { "command": "cargo test" } })).unwrap();
+        assert_eq!(cfg.timeout, Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+        assert_eq!(cfg.max_output_chars, DEFAULT_MAX_OUTPUT_CHARS);
+        assert!(!cfg.network && !cfg.allow_untrusted);
+        assert!(cfg.reports.is_empty());
+
+        let cfg = CheckConfig::from_trigger(&serde_json::json!({ "pre_review": {
+            "command": "cargo llvm-cov --json --output-path cov.json",
+            "reports": [{ "path": "/cov.json", "format": "llvm-cov" }, { "path": "x.xml", "format": "cobertura" }]
+        } }))
+        .unwrap();
+        assert_eq!(cfg.reports, vec![("cov.json".to_string(), ReportFormat::LlvmCov)]);
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("short", 10), "short");
+        assert_eq!(tail("line one\nline two\nline three", 12), "… (truncated)\nline three");
+    }
+
+    #[test]
+    fn test_vars() {
+        let report = CheckReport {
+            command: "make lint".into(),
+            status: CheckStatus::Failed,
+            exit_code: Some(2),
+            output: "src/a.rs:1: unused import".into(),
+            reports: Vec::new(),
+        };
+        let vars = report.vars();
+        assert_eq!(vars["test_status"], "failed");
+        assert_eq!(
+            vars["test_results"],
+            "`make lint` failed (exit code 2). Output (tail):\n```\nsrc/a.rs:1: unused import\n```"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_at_pr_head() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "--quiet"]);
+        std::fs::write(repo.join("check.sh"), "echo checking; exit 0\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "--quiet", "-m", "base"]);
+        std::fs::write(repo.join("check.sh"), "echo 'assertion failed: left == right'; exit 3\n").unwrap();
+        git(&repo, &["commit", "--quiet", "-am", "break it"]);
+        let head = git(&repo, &["rev-parse", "HEAD"]);
+        let base = git(&repo, &["rev-parse", "HEAD~1"]);
+
+        let provider = DangerousHostProvider::new(DangerousConfig {
+            root_dir: tmp.path().join("sandboxes"),
+            ..DangerousConfig::default()
+        })
+        .unwrap();
+
+        let failed = run_in_fresh_sandbox(&provider, &repo, 1, &head, &config("sh check.sh")).await;
+        assert_eq!(failed.status, CheckStatus::Failed);
+        assert_eq!(failed.exit_code, Some(3));
+        assert_eq!(failed.output, "assertion failed: left == right");
+
+        let passed = run_in_fresh_sandbox(&provider, &repo, 1, &base, &config("sh check.sh")).await;
+        assert_eq!(passed.status, CheckStatus::Passed);
+
+        let mut with_reports = config("printf 'SF:src/lib.rs\\nLF:4\\nLH:1\\nend_of_record\\n' > lcov.info");
+        with_reports.reports = vec![
+            ("lcov.info".to_string(), ReportFormat::Lcov),
+            ("eslint.json".to_string(), ReportFormat::Eslint),
+        ];
+        let report = run_in_fresh_sandbox(&provider, &repo, 1, &base, &with_reports).await;
+        assert_eq!(report.status, CheckStatus::Passed);
+        assert_eq!(report.reports[0], "`lcov.info`: Coverage: 25.0% of lines (1/4)\n- src/lib.rs — 25.0% (1/4)");
+        assert!(report.reports[1].starts_with("`eslint.json`: could not be read: not found"));
+        assert!(report.vars()["ci_reports"].contains("25.0%"));
+
+        let missing = run_in_fresh_sandbox(&provider, &repo, 1, "0000000000000000000000000000000000000000", &config("true")).await;
+        assert_eq!(missing.status, CheckStatus::Error);
+
+        // Never on the host, even for a maintainer's PR
+        let pr: PullRequest = serde_json::from_value(serde_json::json!({
+            "number": 1, "title": "t", "author_association": "OWNER",
+            "head": { "sha": head, "ref": "fix", "repo": { "full_name": "o/r" } },
+            "base": { "sha": base, "ref": "main", "repo": { "full_name": "o/r" } },
+        }))
+        .unwrap();
+        assert!(pr.by_maintainer());
+        let refused = run(&provider, &repo, &pr, &config("touch ran")).await;
+        assert_eq!(refused.status, CheckStatus::Error);
+        assert!(refused.output.contains("on the host"));
+    }
+
+    #[test]
+    fn test_by_maintainer() {
+        let pr = |association: &str, head_repo: &str| -> PullRequest {
+            serde_json::from_value(serde_json::json!({
+                "number": 1, "title": "t", "author_association": association,
+                "head": { "sha": "a", "ref": "fix", "repo": { "full_name": head_repo } },
+                "base": { "sha": "b", "ref": "main", "repo": { "full_name": "o/r" } },
+            }))
+            .unwrap()
+        };
+        assert!(pr("MEMBER", "o/r").by_maintainer());
+        assert!(!pr("MEMBER", "fork/r").by_maintainer());
+        assert!(!pr("CONTRIBUTOR", "o/r").by_maintainer());
+    }
+}
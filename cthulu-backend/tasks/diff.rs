@@ -98,7 +98,7 @@ pub fn prepare_diff_context(
 
     let file_diffs = split_diff_by_file(diff);
     let run_id = uuid::Uuid::new_v4();
-    let dir = std::env::temp_dir().join("cthulu-review").join(format!("{pr_number}-{run_id}"));
+    let dir = review_dir().join(format!("{pr_number}-{run_id}"));
 
     std::fs::create_dir_all(&dir)
         .with_context(|| format!("failed to create diff dir: {}", dir.display()))?;
@@ -110,7 +110,7 @@ pub fn prepare_diff_context(
     let mut manifest = String::new();
     writeln!(
         manifest,
-        "This PR diff is too large to include inline ({} chars, {} files). \
+        "This diff is too large to include inline ({} chars, {} files). \
          The diff has been split into per-file chunks.",
         diff.len(),
         file_diffs.len()
@@ -160,6 +160,11 @@ pub fn prepare_diff_context(
     Ok(DiffContext::Chunked { manifest, dir })
 }
 
+/// Where chunked diffs are written, one directory per review.
+pub fn review_dir() -> PathBuf {
+    std::env::temp_dir().join("cthulu-review")
+}
+
 pub fn cleanup(ctx: &DiffContext) {
     if let DiffContext::Chunked { dir, .. } = ctx {
         let _ = std::fs::remove_dir_all(dir);
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use super::ExecutionResult;
+use crate::tasks::context::render_prompt;
+use crate::tasks::pipeline::format_items;
+use crate::tasks::sources::ContentItem;
+
+/// Merges the batches' outputs when the node has no `reduce_prompt`.
+const DEFAULT_REDUCE_PROMPT: &str = "The input was too long for one pass, so it was split into \
+{{batch_count}} parts and each was handled separately. Combine the partial results below into \
+one response in the same format, as if the whole input had been handled at once: merge \
+duplicates, keep the most important points, and don't mention the parts.\n\n{{content}}";
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// An executor's `batch_tokens`: when its items would make a longer prompt,
+/// split them into calls of at most that many (estimated) tokens each, then
+/// merge the partial outputs with one more call on `reduce_prompt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batching {
+    pub max_tokens: usize,
+    pub reduce_prompt: Option<String>,
+    pub concurrency: usize,
+}
+
+impl Batching {
+    pub fn from_config(config: &Value) -> Result<Option<Self>> {
+        let max_tokens = match &config["batch_tokens"] {
+            Value::Null => return Ok(None),
+            value => value
+                .as_u64()
+                .filter(|n| *n >= 1)
+                .context("batch_tokens must be a whole number of at least 1")? as usize,
+        };
+        let concurrency = match &config["concurrency"] {
+            Value::Null => DEFAULT_CONCURRENCY,
+            value => value
+                .as_u64()
+                .filter(|n| *n >= 1)
+                .context("concurrency must be a whole number of at least 1")? as usize,
+        };
+        Ok(Some(Self {
+            max_tokens,
+            reduce_prompt: config["reduce_prompt"].as_str().map(String::from),
+            concurrency,
+        }))
+    }
+
+    /// `items` in order, split so each batch's formatted text fits the
+    /// budget. An item too big for it on its own gets a batch to itself.
+    pub fn split(&self, items: &[ContentItem]) -> Vec<Vec<ContentItem>> {
+        let mut batches = Vec::new();
+        let mut batch: Vec<ContentItem> = Vec::new();
+        let mut tokens = 0;
+        for item in items {
+            let item_tokens = estimate_tokens(&format_items(std::slice::from_ref(item)));
+            if !batch.is_empty() && tokens + item_tokens > self.max_tokens {
+                batches.push(std::mem::take(&mut batch));
+                tokens = 0;
+            }
+            tokens += item_tokens;
+            batch.push(item.clone());
+        }
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+        batches
+    }
+
+    /// The prompt for the final call, on the batches' outputs in order.
+    pub fn reduce_prompt(&self, partials: &[ExecutionResult], vars: &HashMap<String, String>) -> String {
+        let content = partials
+            .iter()
+            .enumerate()
+            .map(|(i, result)| format!("## Part {} of {}\n\n{}", i + 1, partials.len(), result.text.trim()))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let mut vars = vars.clone();
+        vars.insert("content".to_string(), content);
+        vars.insert("batch_count".to_string(), partials.len().to_string());
+        render_prompt(self.reduce_prompt.as_deref().unwrap_or(DEFAULT_REDUCE_PROMPT), &vars)
+    }
+}
+
+/// Rough token count for budgeting: about four characters each in English.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// The reduce call's result, with the cost, turns and attempts of the
+/// batches that fed it.
+pub fn combine(partials: Vec<ExecutionResult>, mut reduced: ExecutionResult) -> ExecutionResult {
+    let mut attempts = Vec::new();
+    for partial in partials {
+        reduced.cost_usd += partial.cost_usd;
+        reduced.num_turns += partial.num_turns;
+        attempts.extend(partial.attempts);
+    }
+    attempts.append(&mut reduced.attempts);
+    reduced.attempts = attempts;
+    reduced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(summary: &str) -> ContentItem {
+        ContentItem {
+            title: "T".into(),
+            url: String::new(),
+            summary: summary.into(),
+            published: None,
+            image_url: None,
+            full_summary: true,
+        }
+    }
+
+    fn result(text: &str, cost_usd: f64) -> ExecutionResult {
+        ExecutionResult { text: text.into(), cost_usd, num_turns: 1, attempts: Vec::new() }
+    }
+
+    #[test]
+    fn test_batching_config() {
+        assert_eq!(Batching::from_config(&json!({})).unwrap(), None);
+        let batching = Batching::from_config(&json!({ "batch_tokens": 4000 })).unwrap().unwrap();
+        assert_eq!(batching.max_tokens, 4000);
+        assert_eq!(batching.concurrency, DEFAULT_CONCURRENCY);
+        assert!(Batching::from_config(&json!({ "batch_tokens": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_split_by_tokens() {
+        let items = [item(&"a".repeat(400)), item(&"b".repeat(400)), item(&"c".repeat(2000)), item("d")];
+        let per_item = estimate_tokens(&format_items(&items[..1]));
+        let batching = Batching { max_tokens: per_item * 2, reduce_prompt: None, concurrency: 1 };
+        let sizes: Vec<usize> = batching.split(&items).iter().map(Vec::len).collect();
+        // The oversized item goes alone; order is kept
+        assert_eq!(sizes, [2, 1, 1]);
+        assert_eq!(batching.split(&items).concat().len(), items.len());
+    }
+
+    #[test]
+    fn test_reduce() {
+        let batching = Batching { max_tokens: 100, reduce_prompt: Some("Merge for {{team}}:\n{{content}}".into()), concurrency: 1 };
+        let partials = vec![result("first", 0.1), result("second", 0.2)];
+        let vars = HashMap::from([("team".to_string(), "infra".to_string())]);
+        assert_eq!(
+            batching.reduce_prompt(&partials, &vars),
+            "Merge for infra:\n## Part 1 of 2\n\nfirst\n\n## Part 2 of 2\n\nsecond"
+        );
+
+        let combined = combine(partials, result("merged", 0.05));
+        assert_eq!(combined.text, "merged");
+        assert_eq!(combined.num_turns, 3);
+        assert!((combined.cost_usd - 0.35).abs() < 1e-9);
+    }
+}
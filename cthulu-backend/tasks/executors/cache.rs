@@ -0,0 +1,116 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::ExecutionResult;
+use crate::tasks::cache::TtlCache;
+
+/// On-disk cache of executor outputs, keyed by a hash of (model, prompt).
+///
+/// Opt-in per node via `cache_ttl_secs`. Entries live under
+/// `~/.cthulu/cache/executor/{key}.json` and expire after the node's TTL.
+#[derive(Clone)]
+pub struct ExecutorCache {
+    entries: TtlCache<CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    model: String,
+    text: String,
+    cost_usd: f64,
+    num_turns: u64,
+}
+
+impl ExecutorCache {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            entries: TtlCache::new(data_dir.join("cache").join("executor")),
+        }
+    }
+
+    pub fn key(model: &str, prompt: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_bytes());
+        hasher.update([0]);
+        hasher.update(prompt.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Return the cached result if present and younger than `ttl`.
+    /// Cost is reported as zero since nothing was spent.
+    pub async fn get(&self, key: &str, ttl: Duration) -> Option<ExecutionResult> {
+        let entry = self.entries.get(key, ttl).await?;
+        Some(ExecutionResult {
+            text: entry.text,
+            cost_usd: 0.0,
+            num_turns: entry.num_turns,
+            attempts: Vec::new(),
+        })
+    }
+
+    pub async fn put(&self, key: &str, ttl: Duration, model: &str, result: &ExecutionResult) -> Result<()> {
+        let entry = CacheEntry {
+            model: model.to_string(),
+            text: result.text.clone(),
+            cost_usd: result.cost_usd,
+            num_turns: result.num_turns,
+        };
+        self.entries.put(key, ttl, entry).await
+    }
+
+    /// Delete expired entries, returning how many went.
+    pub async fn prune(&self) -> usize {
+        self.entries.prune().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn result(text: &str) -> ExecutionResult {
+        ExecutionResult {
+            text: text.to_string(),
+            cost_usd: 0.25,
+            num_turns: 3,
+            attempts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_key_depends_on_model_and_prompt() {
+        let a = ExecutorCache::key("sonnet", "summarize");
+        assert_eq!(a, ExecutorCache::key("sonnet", "summarize"));
+        assert_ne!(a, ExecutorCache::key("opus", "summarize"));
+        assert_ne!(a, ExecutorCache::key("sonnet", "summarize!"));
+        assert_eq!(a.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_put_then_get() {
+        let dir = tempdir().unwrap();
+        let cache = ExecutorCache::new(dir.path());
+        let key = ExecutorCache::key("default", "prompt");
+        let ttl = Duration::from_secs(60);
+
+        assert!(cache.get(&key, ttl).await.is_none());
+        cache.put(&key, ttl, "default", &result("digest")).await.unwrap();
+
+        let hit = cache.get(&key, ttl).await.unwrap();
+        assert_eq!(hit.text, "digest");
+        assert_eq!(hit.num_turns, 3);
+        assert_eq!(hit.cost_usd, 0.0);
+
+        // Entries written before the shared cache still read
+        let old = serde_json::json!({
+            "created_at": chrono::Utc::now(), "model": "default", "text": "old", "cost_usd": 0.1, "num_turns": 1
+        });
+        std::fs::write(cache.entries.path(&key), old.to_string()).unwrap();
+        assert_eq!(cache.get(&key, ttl).await.unwrap().text, "old");
+    }
+}
@@ -6,19 +6,51 @@ use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use tokio::time::timeout;
+use tracing::Instrument;
 
 use super::{ExecutionResult, Executor, LineSink};
+use crate::platform;
 
 const PROCESS_TIMEOUT: Duration = Duration::from_secs(15 * 60);
 
 pub struct ClaudeCodeExecutor {
     permissions: Vec<String>,
     append_system_prompt: Option<String>,
+    session: Option<ClaudeSession>,
+    model: Option<String>,
+    timeout: Duration,
+}
+
+/// A Claude session to keep across executions, so follow-up prompts see
+/// the earlier conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClaudeSession {
+    /// Start a session with this ID.
+    New(String),
+    /// Continue the session with this ID.
+    Resume(String),
 }
 
 impl ClaudeCodeExecutor {
     pub fn new(permissions: Vec<String>, append_system_prompt: Option<String>) -> Self {
-        Self { permissions, append_system_prompt }
+        Self { permissions, append_system_prompt, session: None, model: None, timeout: PROCESS_TIMEOUT }
+    }
+
+    pub fn with_session(mut self, session: ClaudeSession) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Run `model` (`--model`) instead of the CLI's default.
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Kill the process after `timeout` instead of the default 15 minutes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
     }
 
     pub fn build_args(&self) -> Vec<String> {
@@ -29,6 +61,11 @@ impl ClaudeCodeExecutor {
             "stream-json".to_string(),
         ];
 
+        if let Some(model) = &self.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+
         if let Some(prompt) = &self.append_system_prompt {
             args.push("--append-system-prompt".to_string());
             args.push(prompt.clone());
@@ -41,6 +78,18 @@ impl ClaudeCodeExecutor {
             args.push(self.permissions.join(","));
         }
 
+        match &self.session {
+            Some(ClaudeSession::New(id)) => {
+                args.push("--session-id".to_string());
+                args.push(id.clone());
+            }
+            Some(ClaudeSession::Resume(id)) => {
+                args.push("--resume".to_string());
+                args.push(id.clone());
+            }
+            None => {}
+        }
+
         args.push("-".to_string()); // read from stdin
         args
     }
@@ -60,16 +109,20 @@ impl Executor for ClaudeCodeExecutor {
     ) -> Result<ExecutionResult> {
         let args = self.build_args();
 
-        let mut child = Command::new("claude")
-            .args(&args)
+        let mut cmd = Command::new(platform::claude_bin());
+        cmd.args(&args)
             .current_dir(working_dir)
             .env_remove("CLAUDECODE")
             .env("CLAUDECODE", "")
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .spawn()
-            .context("failed to spawn claude process")?;
+            // A run that gives up on this node (or the whole run) drops us
+            .kill_on_drop(true);
+        platform::isolate(&mut cmd);
+        let mut child = cmd.spawn().context("failed to spawn claude process")?;
+        // ...and the tools claude started go with it
+        let mut tree = platform::KillTreeOnDrop::new(child.id());
 
         // Write prompt to stdin
         {
@@ -91,7 +144,7 @@ impl Executor for ClaudeCodeExecutor {
                     tracing::debug!(source = "claude-stderr", "{}", line);
                 }
             }
-        });
+        }.in_current_span());
 
         // Stream stdout JSON events to tracing, capture result
         let stdout = child.stdout.take().expect("stdout piped");
@@ -189,21 +242,23 @@ impl Executor for ClaudeCodeExecutor {
             }
 
             (result_text, total_cost, total_turns)
-        });
+        }.in_current_span());
 
-        let status = match timeout(PROCESS_TIMEOUT, child.wait()).await {
+        let status = match timeout(self.timeout, child.wait()).await {
             Ok(result) => result.context("failed to wait on claude")?,
             Err(_elapsed) => {
                 tracing::error!(
                     "claude process timed out after {}s, killing",
-                    PROCESS_TIMEOUT.as_secs()
+                    self.timeout.as_secs()
                 );
+                drop(tree);
                 let _ = child.kill().await;
                 stderr_handle.abort();
                 stdout_handle.abort();
-                anyhow::bail!("claude process timed out after {}s", PROCESS_TIMEOUT.as_secs());
+                anyhow::bail!("claude process timed out after {}s", self.timeout.as_secs());
             }
         };
+        tree.disarm();
         let _ = stderr_handle.await;
         let (result_text, cost_usd, num_turns) = stdout_handle
             .await
@@ -217,6 +272,7 @@ impl Executor for ClaudeCodeExecutor {
             text: result_text.unwrap_or_default(),
             cost_usd,
             num_turns,
+            attempts: Vec::new(),
         })
     }
 }
@@ -270,4 +326,36 @@ mod tests {
         let fmt_idx = args.iter().position(|a| a == "--output-format").unwrap();
         assert_eq!(args[fmt_idx + 1], "stream-json");
     }
+
+    #[test]
+    fn test_build_args_session() {
+        let args = ClaudeCodeExecutor::new(vec![], None).build_args();
+        assert!(!args.contains(&"--session-id".to_string()));
+        assert!(!args.contains(&"--resume".to_string()));
+
+        let args = ClaudeCodeExecutor::new(vec![], None)
+            .with_session(ClaudeSession::New("abc".to_string()))
+            .build_args();
+        let idx = args.iter().position(|a| a == "--session-id").unwrap();
+        assert_eq!(args[idx + 1], "abc");
+
+        let args = ClaudeCodeExecutor::new(vec![], None)
+            .with_session(ClaudeSession::Resume("abc".to_string()))
+            .build_args();
+        let idx = args.iter().position(|a| a == "--resume").unwrap();
+        assert_eq!(args[idx + 1], "abc");
+        assert_eq!(args.last().unwrap(), "-");
+    }
+
+    #[test]
+    fn test_build_args_model() {
+        let args = ClaudeCodeExecutor::new(vec![], None).build_args();
+        assert!(!args.contains(&"--model".to_string()));
+
+        let args = ClaudeCodeExecutor::new(vec![], None)
+            .with_model("haiku".to_string())
+            .build_args();
+        let idx = args.iter().position(|a| a == "--model").unwrap();
+        assert_eq!(args[idx + 1], "haiku");
+    }
 }
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+
+use super::{ExecutionResult, Executor};
+
+/// Stands in for every runtime in dry runs: returns the rendered prompt as
+/// the output, without running a model.
+pub struct EchoExecutor;
+
+#[async_trait]
+impl Executor for EchoExecutor {
+    async fn execute(&self, prompt: &str, _working_dir: &Path) -> Result<ExecutionResult> {
+        Ok(ExecutionResult {
+            text: prompt.to_string(),
+            cost_usd: 0.0,
+            num_turns: 0,
+            attempts: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::{ExecutionResult, Executor};
+use crate::harness::Harness;
+
+/// Stands in for every runtime in test harness mode: returns the fixtures'
+/// output for the node's prompt, or the prompt itself, without running a
+/// model.
+pub struct HarnessExecutor {
+    harness: Arc<Harness>,
+    flow_id: String,
+    node_id: String,
+    label: String,
+}
+
+impl HarnessExecutor {
+    pub fn new(harness: Arc<Harness>, flow_id: &str, node_id: &str, label: &str) -> Self {
+        Self {
+            harness,
+            flow_id: flow_id.to_string(),
+            node_id: node_id.to_string(),
+            label: label.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for HarnessExecutor {
+    async fn execute(&self, prompt: &str, _working_dir: &Path) -> Result<ExecutionResult> {
+        let (text, cost_usd) = self.harness.execute(&self.flow_id, &self.node_id, &self.label, prompt)?;
+        Ok(ExecutionResult {
+            text,
+            cost_usd,
+            num_turns: 1,
+            attempts: Vec::new(),
+        })
+    }
+}
@@ -1,16 +1,28 @@
+pub mod batching;
+pub mod cache;
 pub mod claude_code;
+pub mod echo;
+pub mod harness;
+pub mod models;
+pub mod ollama;
+pub mod openai;
 pub mod sandbox;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+use models::ModelAttempt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub text: String,
     pub cost_usd: f64,
     pub num_turns: u64,
+    /// Each model tried, when the node has fallbacks.
+    pub attempts: Vec<ModelAttempt>,
 }
 
 /// Callback that receives each stdout line from the executor process.
@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::ollama::OLLAMA_KIND;
+use super::openai::OPENAI_KIND;
+
+/// Runtimes a `fallback_models` entry can switch to with a `runtime:`
+/// prefix, e.g. `openai:gpt-4o-mini`.
+const RUNTIMES: [&str; 4] = ["claude-code", "sandbox", OPENAI_KIND, OLLAMA_KIND];
+
+/// The models an executor node tries, in order: `model` (the executor's
+/// default when unset), then each of `fallback_models`. The next one runs
+/// when the previous fails (including rate limits and, with
+/// `attempt_timeout_secs`, running too long) or, with `min_confidence`,
+/// reports a confidence below it. A fallback can be on another provider,
+/// so a flow keeps working through an outage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelChain {
+    pub models: Vec<ModelChoice>,
+    pub min_confidence: Option<f64>,
+    /// How long each attempt gets before the next model is tried.
+    pub attempt_timeout: Option<Duration>,
+}
+
+/// One model in a chain, on the node's own runtime unless `runtime` says
+/// otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelChoice {
+    pub runtime: Option<String>,
+    pub model: Option<String>,
+}
+
+impl ModelChoice {
+    fn parse(entry: &str) -> Self {
+        match entry.split_once(':') {
+            Some((runtime, model)) if RUNTIMES.contains(&runtime) => Self {
+                runtime: Some(runtime.to_string()),
+                model: Some(model.to_string()).filter(|m| !m.is_empty()),
+            },
+            // Ollama tags (`llama3.1:8b`) have colons too
+            _ => Self { runtime: None, model: Some(entry.to_string()) },
+        }
+    }
+
+    /// `runtime:model` for logs and run history, with the node's runtime
+    /// and the executor's default filled in.
+    pub fn label(&self, default_runtime: &str) -> String {
+        format!(
+            "{}:{}",
+            self.runtime.as_deref().unwrap_or(default_runtime),
+            self.model.as_deref().unwrap_or("default")
+        )
+    }
+}
+
+/// One model's go at a prompt, kept with the node run so fallbacks and
+/// what each cost show in its history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelAttempt {
+    /// `runtime:model`.
+    pub model: String,
+    pub cost_usd: f64,
+    pub duration_ms: u64,
+    /// Why the next model was tried, if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ModelChain {
+    pub fn from_config(config: &Value) -> Result<Self> {
+        let mut models = vec![ModelChoice {
+            runtime: None,
+            model: config["model"].as_str().filter(|m| !m.is_empty()).map(String::from),
+        }];
+        match &config["fallback_models"] {
+            Value::Null => {}
+            Value::Array(fallbacks) => {
+                for model in fallbacks {
+                    let model = model
+                        .as_str()
+                        .filter(|m| !m.is_empty())
+                        .context("fallback_models must be a list of model names")?;
+                    models.push(ModelChoice::parse(model));
+                }
+            }
+            _ => anyhow::bail!("fallback_models must be a list of model names"),
+        }
+        let min_confidence = match &config["min_confidence"] {
+            Value::Null => None,
+            value => Some(
+                value
+                    .as_f64()
+                    .filter(|c| (0.0..=1.0).contains(c))
+                    .context("min_confidence must be a number between 0 and 1")?,
+            ),
+        };
+        let attempt_timeout = match &config["attempt_timeout_secs"] {
+            Value::Null => None,
+            value => Some(Duration::from_secs(
+                value
+                    .as_u64()
+                    .filter(|secs| *secs > 0)
+                    .context("attempt_timeout_secs must be a whole number of seconds")?,
+            )),
+        };
+        Ok(Self { models, min_confidence, attempt_timeout })
+    }
+
+    /// Every runtime the chain runs on, starting with the node's own.
+    pub fn runtimes<'a>(&'a self, default_runtime: &'a str) -> impl Iterator<Item = &'a str> {
+        self.models
+            .iter()
+            .map(move |choice| choice.runtime.as_deref().unwrap_or(default_runtime))
+    }
+
+    /// The confidence `text` reports, when it is below `min_confidence`.
+    /// Output that reports none is taken at its word.
+    pub fn low_confidence(&self, text: &str) -> Option<f64> {
+        let min = self.min_confidence?;
+        confidence(text).filter(|c| *c < min)
+    }
+}
+
+/// The last `confidence` the output states — `"confidence": 0.4` in JSON,
+/// or `Confidence: 40%` in prose — as a fraction.
+pub fn confidence(text: &str) -> Option<f64> {
+    let lower = text.to_lowercase();
+    lower.rmatch_indices("confidence").find_map(|(i, word)| {
+        let rest = lower[i + word.len()..].trim_start_matches(['"', '\'', '*', '_', ' ', '\t']);
+        let rest = rest.strip_prefix([':', '='])?.trim_start_matches([' ', '\t', '"', '*']);
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let value: f64 = rest[..end].trim_end_matches('.').parse().ok()?;
+        let percent = rest[end..].starts_with('%') || value > 1.0;
+        let value = if percent { value / 100.0 } else { value };
+        (0.0..=1.0).contains(&value).then_some(value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_chain_from_config() {
+        let chain = ModelChain::from_config(&json!({})).unwrap();
+        assert_eq!(chain.models, vec![ModelChoice { runtime: None, model: None }]);
+
+        let chain = ModelChain::from_config(&json!({
+            "model": "haiku",
+            "fallback_models": ["sonnet", "openai:gpt-4o-mini", "llama3.1:8b"],
+            "min_confidence": 0.7,
+            "attempt_timeout_secs": 120
+        }))
+        .unwrap();
+        let labels: Vec<String> = chain.models.iter().map(|m| m.label("claude-code")).collect();
+        assert_eq!(
+            labels,
+            ["claude-code:haiku", "claude-code:sonnet", "openai:gpt-4o-mini", "claude-code:llama3.1:8b"]
+        );
+        assert_eq!(chain.runtimes("claude-code").collect::<Vec<_>>()[2], "openai");
+        assert_eq!(chain.attempt_timeout, Some(Duration::from_secs(120)));
+        assert_eq!(chain.low_confidence("Confidence: 0.9"), None);
+        assert_eq!(chain.low_confidence("Confidence: 0.5"), Some(0.5));
+        assert_eq!(chain.low_confidence("no score here"), None);
+
+        assert!(ModelChain::from_config(&json!({ "fallback_models": "opus" })).is_err());
+        assert!(ModelChain::from_config(&json!({ "min_confidence": 70 })).is_err());
+        assert!(ModelChain::from_config(&json!({ "attempt_timeout_secs": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_confidence_formats() {
+        assert_eq!(confidence(r#"{"verdict": "approve", "confidence": 0.35}"#), Some(0.35));
+        assert_eq!(confidence("**Confidence:** 80%"), Some(0.8));
+        assert_eq!(confidence("Confidence = 65"), Some(0.65));
+        // The final statement wins
+        assert_eq!(confidence("confidence: 0.2 at first\n\nFinal confidence: 0.9."), Some(0.9));
+        assert_eq!(confidence("I have low confidence in this"), None);
+    }
+}
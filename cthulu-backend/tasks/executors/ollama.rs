@@ -0,0 +1,225 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::openai::{self, OpenAiExecutor};
+use super::{ExecutionResult, Executor};
+use crate::egress::AuditedSend;
+
+/// Executor kind that calls a local model server (Ollama, or any
+/// OpenAI-compatible one) instead of the `claude` CLI.
+pub const OLLAMA_KIND: &str = "ollama";
+
+/// Used when neither the node's `base_url` nor `OLLAMA_HOST` is set.
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Local models on modest hardware are slow; match the CLI's budget.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// One prompt in, one completion out against a self-hosted model. Always
+/// reports zero cost.
+///
+/// Node config: `model` (required), `temperature`, `max_tokens`, `num_ctx`,
+/// `system_prompt`, `base_url`, and `api`: `ollama` for Ollama's native
+/// `/api/chat` (the default) or `openai` for an OpenAI-compatible server
+/// (llama.cpp, vLLM, LM Studio, or Ollama's own `/v1`), with an optional
+/// `api_key_env`.
+pub struct OllamaExecutor {
+    http_client: Arc<reqwest::Client>,
+    base_url: String,
+    model: String,
+    temperature: Option<f64>,
+    max_tokens: Option<u64>,
+    num_ctx: Option<u64>,
+    system_prompt: Option<String>,
+}
+
+/// Build the executor for an `ollama` node: native, or an
+/// [`OpenAiExecutor`] pointed at the local server.
+pub fn from_config(
+    http_client: Arc<reqwest::Client>,
+    config: &Value,
+    append_system_prompt: Option<String>,
+) -> Result<Box<dyn Executor>> {
+    let model = config["model"]
+        .as_str()
+        .filter(|m| !m.is_empty())
+        .context("ollama executor requires a model, e.g. llama3.1")?;
+    let base_url = config["base_url"]
+        .as_str()
+        .map(str::to_string)
+        .or_else(|| std::env::var("OLLAMA_HOST").ok().filter(|h| !h.is_empty()))
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    let base_url = with_scheme(base_url.trim_end_matches('/'));
+
+    match config["api"].as_str().unwrap_or("ollama") {
+        "ollama" => Ok(Box::new(OllamaExecutor {
+            http_client,
+            base_url,
+            model: model.to_string(),
+            temperature: config["temperature"].as_f64(),
+            max_tokens: config["max_tokens"].as_u64(),
+            num_ctx: config["num_ctx"].as_u64(),
+            system_prompt: openai::system_prompt(config, append_system_prompt),
+        })),
+        "openai" => {
+            let api_key = config["api_key_env"]
+                .as_str()
+                .map(|env| crate::secrets::var(env).with_context(|| format!("env var {env} not set")))
+                .transpose()?;
+            // Ollama serves the OpenAI API under /v1; other servers are
+            // configured with their full base URL
+            let base_url = if config["base_url"].is_string() {
+                base_url
+            } else {
+                format!("{base_url}/v1")
+            };
+            let config = json!({
+                "model": model,
+                "temperature": config["temperature"],
+                "max_tokens": config["max_tokens"],
+                "system_prompt": config["system_prompt"],
+            });
+            Ok(Box::new(OpenAiExecutor::compatible(
+                http_client,
+                &base_url,
+                api_key,
+                &config,
+                append_system_prompt,
+            )?))
+        }
+        other => anyhow::bail!("unknown ollama api '{other}' (expected ollama or openai)"),
+    }
+}
+
+/// `OLLAMA_HOST` is often just `host:port`.
+fn with_scheme(url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        url.to_string()
+    } else {
+        format!("http://{url}")
+    }
+}
+
+impl OllamaExecutor {
+    fn request_body(&self, prompt: &str) -> Value {
+        let mut messages = Vec::new();
+        if let Some(system) = &self.system_prompt {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": prompt }));
+
+        let mut options = serde_json::Map::new();
+        if let Some(t) = self.temperature {
+            options.insert("temperature".into(), json!(t));
+        }
+        if let Some(max) = self.max_tokens {
+            options.insert("num_predict".into(), json!(max));
+        }
+        if let Some(ctx) = self.num_ctx {
+            options.insert("num_ctx".into(), json!(ctx));
+        }
+        json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": false,
+            "options": options,
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for OllamaExecutor {
+    async fn execute(&self, prompt: &str, _working_dir: &Path) -> Result<ExecutionResult> {
+        let response = self
+            .http_client
+            .post(format!("{}/api/chat", self.base_url))
+            .timeout(REQUEST_TIMEOUT)
+            .json(&self.request_body(prompt))
+            .send_audited()
+            .await
+            .with_context(|| format!("ollama request to {} failed (is the server running?)", self.base_url))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("ollama returned {status}: {body}");
+        }
+        let body: Value = response.json().await.context("invalid ollama response")?;
+        let (text, input_tokens, output_tokens) = parse_response(&body)?;
+
+        tracing::debug!(model = %self.model, input_tokens, output_tokens, "Ollama completion finished");
+        Ok(ExecutionResult {
+            text,
+            cost_usd: 0.0,
+            num_turns: 1,
+            attempts: Vec::new(),
+        })
+    }
+}
+
+/// The completion text and (input, output) token counts.
+fn parse_response(body: &Value) -> Result<(String, u64, u64)> {
+    if let Some(error) = body["error"].as_str() {
+        anyhow::bail!("ollama error: {error}");
+    }
+    let text = body["message"]["content"]
+        .as_str()
+        .context("ollama response has no message content")?;
+    Ok((
+        text.to_string(),
+        body["prompt_eval_count"].as_u64().unwrap_or(0),
+        body["eval_count"].as_u64().unwrap_or(0),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_body() {
+        let executor = OllamaExecutor {
+            http_client: Arc::new(reqwest::Client::new()),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            model: "llama3.1".to_string(),
+            temperature: Some(0.2),
+            max_tokens: Some(500),
+            num_ctx: None,
+            system_prompt: Some("Be brief.".to_string()),
+        };
+        let body = executor.request_body("Summarize");
+        assert_eq!(body["model"], "llama3.1");
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["messages"][0]["content"], "Be brief.");
+        assert_eq!(body["messages"][1]["content"], "Summarize");
+        assert_eq!(body["options"], json!({ "temperature": 0.2, "num_predict": 500 }));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = json!({
+            "model": "llama3.1",
+            "message": { "role": "assistant", "content": "Done." },
+            "done": true,
+            "prompt_eval_count": 120,
+            "eval_count": 8
+        });
+        assert_eq!(parse_response(&body).unwrap(), ("Done.".to_string(), 120, 8));
+        assert!(parse_response(&json!({ "error": "model 'x' not found" })).is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let client = Arc::new(reqwest::Client::new());
+        assert!(from_config(client.clone(), &json!({}), None).is_err());
+        assert!(from_config(client.clone(), &json!({ "model": "llama3.1" }), None).is_ok());
+        assert!(from_config(client.clone(), &json!({ "model": "llama3.1", "api": "openai" }), None).is_ok());
+        assert!(from_config(client, &json!({ "model": "llama3.1", "api": "grpc" }), None).is_err());
+        assert_eq!(with_scheme("0.0.0.0:11434"), "http://0.0.0.0:11434");
+        assert_eq!(with_scheme("https://gpu-box:11434"), "https://gpu-box:11434");
+    }
+}
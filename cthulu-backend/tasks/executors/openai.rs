@@ -0,0 +1,282 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::{ExecutionResult, Executor};
+use crate::egress::AuditedSend;
+
+/// Executor kind that calls the OpenAI API directly instead of the `claude` CLI.
+pub const OPENAI_KIND: &str = "openai";
+
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// USD per million input and output tokens, matched by model prefix
+/// (longest first). Models not listed report no cost.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("o4-mini", 1.10, 4.40),
+    ("o3", 2.00, 8.00),
+];
+
+/// Which OpenAI endpoint to call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Api {
+    /// `POST /chat/completions`, also served by most OpenAI-compatible
+    /// gateways.
+    Chat,
+    /// `POST /responses`.
+    Responses,
+}
+
+/// One prompt in, one completion out: no tools, no agent loop.
+///
+/// Node config: `model`, `temperature`, `max_tokens`, `api` (`chat` or
+/// `responses`), `base_url`, `api_key_env` and `system_prompt`.
+pub struct OpenAiExecutor {
+    http_client: Arc<reqwest::Client>,
+    base_url: String,
+    api_key: Option<String>,
+    api: Api,
+    model: String,
+    temperature: Option<f64>,
+    max_tokens: Option<u64>,
+    system_prompt: Option<String>,
+}
+
+impl OpenAiExecutor {
+    /// Build from an executor node's config. The agent's system prompt (if
+    /// any) is appended to the node's `system_prompt`.
+    pub fn from_config(
+        http_client: Arc<reqwest::Client>,
+        config: &Value,
+        append_system_prompt: Option<String>,
+    ) -> Result<Self> {
+        let key_env = config["api_key_env"].as_str().unwrap_or(DEFAULT_API_KEY_ENV);
+        let api_key = crate::secrets::var(key_env).with_context(|| format!("env var {key_env} not set"))?;
+        let base_url = config["base_url"].as_str().unwrap_or(DEFAULT_BASE_URL);
+        Self::compatible(http_client, base_url, Some(api_key), config, append_system_prompt)
+    }
+
+    /// Any OpenAI-compatible endpoint at `base_url` (local servers usually
+    /// need no `api_key`), configured from the same node keys.
+    pub fn compatible(
+        http_client: Arc<reqwest::Client>,
+        base_url: &str,
+        api_key: Option<String>,
+        config: &Value,
+        append_system_prompt: Option<String>,
+    ) -> Result<Self> {
+        let api = match config["api"].as_str().unwrap_or("chat") {
+            "chat" => Api::Chat,
+            "responses" => Api::Responses,
+            other => anyhow::bail!("unknown openai api '{other}' (expected chat or responses)"),
+        };
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            api,
+            model: config["model"].as_str().unwrap_or(DEFAULT_MODEL).to_string(),
+            temperature: config["temperature"].as_f64(),
+            max_tokens: config["max_tokens"].as_u64(),
+            system_prompt: system_prompt(config, append_system_prompt),
+        })
+    }
+
+    fn request_body(&self, prompt: &str) -> (&'static str, Value) {
+        match self.api {
+            Api::Chat => {
+                let mut messages = Vec::new();
+                if let Some(system) = &self.system_prompt {
+                    messages.push(json!({ "role": "system", "content": system }));
+                }
+                messages.push(json!({ "role": "user", "content": prompt }));
+                let mut body = json!({ "model": self.model, "messages": messages });
+                if let Some(t) = self.temperature {
+                    body["temperature"] = json!(t);
+                }
+                if let Some(max) = self.max_tokens {
+                    body["max_completion_tokens"] = json!(max);
+                }
+                ("/chat/completions", body)
+            }
+            Api::Responses => {
+                let mut body = json!({ "model": self.model, "input": prompt });
+                if let Some(system) = &self.system_prompt {
+                    body["instructions"] = json!(system);
+                }
+                if let Some(t) = self.temperature {
+                    body["temperature"] = json!(t);
+                }
+                if let Some(max) = self.max_tokens {
+                    body["max_output_tokens"] = json!(max);
+                }
+                ("/responses", body)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for OpenAiExecutor {
+    async fn execute(&self, prompt: &str, _working_dir: &Path) -> Result<ExecutionResult> {
+        let (path, body) = self.request_body(prompt);
+        let mut request = self
+            .http_client
+            .post(format!("{}{path}", self.base_url))
+            .timeout(REQUEST_TIMEOUT)
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send_audited().await.context("openai request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("openai API returned {status}: {body}");
+        }
+        let body: Value = response.json().await.context("invalid openai response")?;
+        let (text, input_tokens, output_tokens) = parse_response(self.api, &body)?;
+
+        tracing::debug!(model = %self.model, input_tokens, output_tokens, "OpenAI completion finished");
+        Ok(ExecutionResult {
+            text,
+            cost_usd: cost(&self.model, input_tokens, output_tokens),
+            num_turns: 1,
+            attempts: Vec::new(),
+        })
+    }
+}
+
+/// The node's `system_prompt` followed by the agent's, if either is set.
+pub(super) fn system_prompt(config: &Value, append_system_prompt: Option<String>) -> Option<String> {
+    [config["system_prompt"].as_str().map(str::to_string), append_system_prompt]
+        .into_iter()
+        .flatten()
+        .filter(|p| !p.trim().is_empty())
+        .reduce(|a, b| format!("{a}\n\n{b}"))
+}
+
+/// The completion text and (input, output) token counts.
+fn parse_response(api: Api, body: &Value) -> Result<(String, u64, u64)> {
+    let usage = &body["usage"];
+    match api {
+        Api::Chat => {
+            let text = body["choices"][0]["message"]["content"]
+                .as_str()
+                .context("openai response has no message content")?;
+            Ok((
+                text.to_string(),
+                usage["prompt_tokens"].as_u64().unwrap_or(0),
+                usage["completion_tokens"].as_u64().unwrap_or(0),
+            ))
+        }
+        Api::Responses => {
+            let text: Vec<&str> = body["output"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|o| o["type"] == "message")
+                .flat_map(|o| o["content"].as_array().into_iter().flatten())
+                .filter(|c| c["type"] == "output_text")
+                .filter_map(|c| c["text"].as_str())
+                .collect();
+            if text.is_empty() {
+                anyhow::bail!("openai response has no output text");
+            }
+            Ok((
+                text.concat(),
+                usage["input_tokens"].as_u64().unwrap_or(0),
+                usage["output_tokens"].as_u64().unwrap_or(0),
+            ))
+        }
+    }
+}
+
+fn cost(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    PRICES
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map_or(0.0, |(_, input, output)| {
+            (input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn executor(api: Api) -> OpenAiExecutor {
+        OpenAiExecutor {
+            http_client: Arc::new(reqwest::Client::new()),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: Some("sk-test".to_string()),
+            api,
+            model: "gpt-4o-mini".to_string(),
+            temperature: Some(0.2),
+            max_tokens: Some(500),
+            system_prompt: Some("Be brief.".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_request_body() {
+        let (path, body) = executor(Api::Chat).request_body("Summarize");
+        assert_eq!(path, "/chat/completions");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "Summarize");
+        assert_eq!(body["max_completion_tokens"], 500);
+        assert_eq!(body["temperature"], 0.2);
+
+        let (path, body) = executor(Api::Responses).request_body("Summarize");
+        assert_eq!(path, "/responses");
+        assert_eq!(body["instructions"], "Be brief.");
+        assert_eq!(body["input"], "Summarize");
+        assert_eq!(body["max_output_tokens"], 500);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let chat = json!({
+            "choices": [{ "message": { "role": "assistant", "content": "Done." } }],
+            "usage": { "prompt_tokens": 1000, "completion_tokens": 200 }
+        });
+        assert_eq!(parse_response(Api::Chat, &chat).unwrap(), ("Done.".to_string(), 1000, 200));
+
+        let responses = json!({
+            "output": [
+                { "type": "reasoning", "summary": [] },
+                { "type": "message", "content": [{ "type": "output_text", "text": "Done." }] }
+            ],
+            "usage": { "input_tokens": 10, "output_tokens": 2 }
+        });
+        assert_eq!(parse_response(Api::Responses, &responses).unwrap(), ("Done.".to_string(), 10, 2));
+        assert!(parse_response(Api::Responses, &json!({ "output": [] })).is_err());
+    }
+
+    #[test]
+    fn test_cost_matches_longest_prefix() {
+        assert!((cost("gpt-4o-mini-2024-07-18", 1_000_000, 1_000_000) - 0.75).abs() < 1e-9);
+        assert!((cost("gpt-4o", 1_000_000, 0) - 2.50).abs() < 1e-9);
+        assert_eq!(cost("llama3", 1_000_000, 1_000_000), 0.0);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = json!({ "api_key_env": "CTHULU_TEST_UNSET_OPENAI_KEY" });
+        assert!(OpenAiExecutor::from_config(Arc::new(reqwest::Client::new()), &config, None).is_err());
+    }
+}
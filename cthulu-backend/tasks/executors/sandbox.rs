@@ -1,29 +1,130 @@
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use tokio::time::timeout;
 
-use super::{ExecutionResult, Executor};
+use super::{ExecutionResult, Executor, LineSink};
+use crate::sandbox::error::SandboxError;
+use crate::sandbox::handle::SandboxHandle;
 use crate::sandbox::provider::SandboxProvider;
 use crate::sandbox::types::*;
 
 const PROCESS_TIMEOUT: Duration = Duration::from_secs(15 * 60);
 
+/// Directories not copied into the sandbox: history and build output the
+/// agent doesn't need, and that would dwarf the sources.
+const SYNC_SKIP_DIRS: [&str; 3] = [".git", "target", "node_modules"];
+
+/// Largest working directory copied into a sandbox.
+const MAX_SYNC_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Reachable whatever a node's network access, or Claude couldn't run.
+const MODEL_API_HOST: &str = "api.anthropic.com";
+
+/// A node's `sandbox` config: what the agent in its sandbox may touch.
+///
+/// ```yaml
+/// sandbox:
+///   filesystem: read-only     # or workspace (the default)
+///   network: [api.github.com] # or off, or all (the default)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxAccess {
+    /// The workspace, and the repo copied into it, can't be changed.
+    pub read_only: bool,
+    pub network: NetworkAccess,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkAccess {
+    All,
+    /// Only the model API.
+    Off,
+    /// These hosts and the model API.
+    Hosts(Vec<String>),
+}
+
+impl SandboxAccess {
+    /// `None` when the node has no `sandbox` config.
+    pub fn from_config(config: &serde_json::Value) -> Result<Option<Self>> {
+        if config.is_null() {
+            return Ok(None);
+        }
+        let read_only = match &config["filesystem"] {
+            serde_json::Value::Null => false,
+            serde_json::Value::String(s) if s == "workspace" => false,
+            serde_json::Value::String(s) if s == "read-only" => true,
+            _ => anyhow::bail!("sandbox.filesystem must be \"read-only\" or \"workspace\""),
+        };
+        let network = match &config["network"] {
+            serde_json::Value::Null => NetworkAccess::All,
+            serde_json::Value::String(s) if s == "all" => NetworkAccess::All,
+            serde_json::Value::String(s) if s == "off" => NetworkAccess::Off,
+            serde_json::Value::Array(hosts) => NetworkAccess::Hosts(
+                hosts
+                    .iter()
+                    .map(|h| {
+                        h.as_str()
+                            .map(str::trim)
+                            .filter(|h| is_hostname(h))
+                            .map(str::to_string)
+                            .with_context(|| format!("sandbox.network host {h} is not a hostname"))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            _ => anyhow::bail!("sandbox.network must be \"off\", \"all\" or a list of hosts"),
+        };
+        Ok(Some(Self { read_only, network }))
+    }
+
+    pub fn restricts_network(&self) -> bool {
+        self.network != NetworkAccess::All
+    }
+
+    fn apply(&self, spec: &mut SandboxSpec) {
+        spec.filesystem.read_only_workspace = self.read_only;
+        let allowlist = |mut allowed_hosts: Vec<String>| {
+            allowed_hosts.push(MODEL_API_HOST.to_string());
+            NetworkPolicy {
+                mode: NetworkMode::Allowlist,
+                allowed_hosts,
+                ..NetworkPolicy::default_safe()
+            }
+        };
+        spec.network = match &self.network {
+            NetworkAccess::All => NetworkPolicy::allow_all(),
+            NetworkAccess::Off => allowlist(Vec::new()),
+            NetworkAccess::Hosts(hosts) => allowlist(hosts.clone()),
+        };
+    }
+}
+
+fn is_hostname(host: &str) -> bool {
+    !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
 /// Executor that runs Claude CLI inside a sandbox.
 ///
 /// Bridges the existing `Executor` trait (used by `FlowRunner`) to the
 /// `SandboxProvider` / `SandboxHandle` API. Each `execute()` call:
-/// 1. Provisions a sandbox (or reuses one for the workspace)
-/// 2. Runs `claude` CLI inside it with the same args as `ClaudeCodeExecutor`
-/// 3. Parses the stream-json output for cost/turns/result
-/// 4. Returns `ExecutionResult`
+/// 1. Provisions a fresh sandbox
+/// 2. With `with_repo_sync`, copies the working directory into it
+/// 3. Runs `claude` CLI inside it with the same args as `ClaudeCodeExecutor`,
+///    streaming its stdout lines to the line sink
+/// 4. Parses the stream-json output for cost/turns/result
+/// 5. Destroys the sandbox and returns `ExecutionResult`
 pub struct SandboxExecutor {
     provider: Arc<dyn SandboxProvider>,
     permissions: Vec<String>,
     append_system_prompt: Option<String>,
+    model: Option<String>,
+    timeout: Duration,
+    sync_repo: bool,
+    access: Option<SandboxAccess>,
 }
 
 impl SandboxExecutor {
@@ -36,9 +137,41 @@ impl SandboxExecutor {
             provider,
             permissions,
             append_system_prompt,
+            model: None,
+            timeout: PROCESS_TIMEOUT,
+            sync_repo: false,
+            access: None,
         }
     }
 
+    /// Run `model` (`--model`) instead of the CLI's default.
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Kill the exec (and destroy the sandbox) after `timeout` instead of
+    /// the default 15 minutes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Copy the working directory into the sandbox before running, so the
+    /// agent works on the repo without reaching the host's copy. Its edits
+    /// stay in the sandbox.
+    pub fn with_repo_sync(mut self) -> Self {
+        self.sync_repo = true;
+        self
+    }
+
+    /// Provision the sandbox with the node's filesystem and network access
+    /// instead of a writable workspace and open network.
+    pub fn with_access(mut self, access: SandboxAccess) -> Self {
+        self.access = Some(access);
+        self
+    }
+
     fn build_claude_args(&self) -> Vec<String> {
         let mut args = vec![
             "claude".to_string(),
@@ -48,6 +181,11 @@ impl SandboxExecutor {
             "stream-json".to_string(),
         ];
 
+        if let Some(model) = &self.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+
         if let Some(prompt) = &self.append_system_prompt {
             args.push("--append-system-prompt".to_string());
             args.push(prompt.clone());
@@ -68,23 +206,37 @@ impl SandboxExecutor {
 #[async_trait]
 impl Executor for SandboxExecutor {
     async fn execute(&self, prompt: &str, working_dir: &Path) -> Result<ExecutionResult> {
-        // Use working_dir's last component as workspace_id
-        let workspace_id = working_dir
+        self.execute_streaming(prompt, working_dir, None).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        line_sink: Option<LineSink>,
+    ) -> Result<ExecutionResult> {
+        // A fresh workspace per call, named after working_dir's last component.
+        // The `exec-` prefix lets startup reap ones a crash left behind.
+        let dir_name = working_dir
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "default".to_string());
+        let workspace_id = format!("exec-{dir_name}-{}", uuid::Uuid::new_v4());
 
-        let spec = SandboxSpec {
+        let mut spec = SandboxSpec {
             workspace_id,
             profile: SandboxProfile::Base,
             filesystem: FilesystemSpec::default(),
             resources: ResourceHints::default(),
             env: BTreeMap::new(),
             mounts: vec![],
-            network: NetworkPolicy::default_safe(),
+            network: NetworkPolicy::allow_all(),
             lifecycle: LifecyclePolicy::default(),
             labels: BTreeMap::from([("executor".into(), "sandbox".into())]),
         };
+        if let Some(access) = &self.access {
+            access.apply(&mut spec);
+        }
 
         let handle = self
             .provider
@@ -92,38 +244,63 @@ impl Executor for SandboxExecutor {
             .await
             .map_err(|e| anyhow::anyhow!("sandbox provision failed: {e}"))?;
 
-        let args = self.build_claude_args();
+        let result = self.run_in(&*handle, prompt, working_dir, line_sink).await;
+
+        // Destroy the sandbox to release resources (workspace dirs, VM state, TAP devices).
+        // Each execute() provisions a fresh sandbox, so there's nothing to preserve.
+        if let Err(e) = handle.destroy().await {
+            tracing::warn!(sandbox = %handle.id(), error = %e, "Failed to destroy sandbox");
+        }
+        result
+    }
+}
+
+impl SandboxExecutor {
+    async fn run_in(
+        &self,
+        handle: &dyn SandboxHandle,
+        prompt: &str,
+        working_dir: &Path,
+        line_sink: Option<LineSink>,
+    ) -> Result<ExecutionResult> {
+        if self.sync_repo {
+            let files = sync_repo(handle, working_dir).await?;
+            tracing::info!(sandbox = %handle.id(), files, dir = %working_dir.display(), "Synced repo into sandbox");
+        }
 
+        // The host backend starts execs in the workspace already; the others
+        // start in the user's home
+        let cwd = match handle.backend_kind() {
+            SandboxBackendKind::DangerousHost => None,
+            _ => Some(FilesystemSpec::default().workspace_path),
+        };
         let exec_req = ExecRequest {
-            command: args,
-            cwd: None,
-            env: BTreeMap::new(),
+            command: self.build_claude_args(),
+            cwd,
+            env: claude_env(),
             stdin: Some(prompt.as_bytes().to_vec()),
-            timeout: Some(PROCESS_TIMEOUT),
+            timeout: Some(self.timeout),
             tty: false,
             detach: false,
         };
 
-        let result = handle
-            .exec(exec_req)
+        let output = exec_lines(handle, exec_req, line_sink.as_ref())
             .await
             .map_err(|e| anyhow::anyhow!("sandbox exec failed: {e}"))?;
 
-        if result.timed_out {
-            let _ = handle.destroy().await;
+        if output.timed_out {
             anyhow::bail!(
                 "claude process timed out after {}s",
-                PROCESS_TIMEOUT.as_secs()
+                self.timeout.as_secs()
             );
         }
 
         // Parse stream-json output (same logic as ClaudeCodeExecutor)
-        let stdout_str = String::from_utf8_lossy(&result.stdout);
         let mut result_text: Option<String> = None;
         let mut total_cost: f64 = 0.0;
         let mut total_turns: u64 = 0;
 
-        for line in stdout_str.lines() {
+        for line in output.stdout.lines() {
             if line.is_empty() {
                 continue;
             }
@@ -150,29 +327,183 @@ impl Executor for SandboxExecutor {
             }
         }
 
-        if let Some(code) = result.exit_code {
-            if code != 0 {
-                let stderr_str = String::from_utf8_lossy(&result.stderr);
-                let _ = handle.destroy().await;
-                anyhow::bail!("claude exited with code {code}: {stderr_str}");
-            }
+        if let Some(code) = output.exit_code
+            && code != 0
+        {
+            anyhow::bail!("claude exited with code {code}: {}", output.stderr);
         }
 
-        // Destroy the sandbox to release resources (workspace dirs, VM state, TAP devices).
-        // Each execute() provisions a fresh sandbox, so there's nothing to preserve.
-        let _ = handle.destroy().await;
-
         Ok(ExecutionResult {
             text: result_text.unwrap_or_default(),
             cost_usd: total_cost,
             num_turns: total_turns,
+            attempts: Vec::new(),
         })
     }
 }
 
+/// Whether `provider` is the host itself — the only case where Claude may
+/// be spawned directly rather than inside a sandbox.
+pub fn runs_on_host(provider: &dyn SandboxProvider) -> bool {
+    provider.info().kind == SandboxBackendKind::DangerousHost
+}
+
+/// Claude's login, passed in since a sandbox can't read the host's.
+fn claude_env() -> BTreeMap<String, String> {
+    let mut env = BTreeMap::new();
+    if let Some(token) = crate::api::auth::repository::read_oauth_token() {
+        env.insert("CLAUDE_CODE_OAUTH_TOKEN".to_string(), token);
+    }
+    if let Ok(key) = std::env::var("ANTHROPIC_API_KEY")
+        && !key.is_empty()
+    {
+        env.insert("ANTHROPIC_API_KEY".to_string(), key);
+    }
+    env
+}
+
+/// What an exec printed and how it ended.
+#[derive(Debug, Default)]
+struct ExecOutput {
+    stdout: String,
+    stderr: String,
+    exit_code: Option<i32>,
+    timed_out: bool,
+}
+
+/// Run `req`, handing each stdout line to `line_sink` as it arrives. Backends
+/// that can't stream run it to completion and hand the lines over after.
+async fn exec_lines(
+    handle: &dyn SandboxHandle,
+    req: ExecRequest,
+    line_sink: Option<&LineSink>,
+) -> Result<ExecOutput, SandboxError> {
+    let emit = |stdout: &str| {
+        if let Some(sink) = line_sink {
+            stdout.lines().filter(|l| !l.is_empty()).for_each(|l| sink(l.to_string()));
+        }
+    };
+
+    let mut stream = match handle.exec_stream(req.clone()).await {
+        Ok(stream) => stream,
+        Err(SandboxError::Unsupported(_)) => {
+            let result = handle.exec(req).await?;
+            let stdout = String::from_utf8_lossy(&result.stdout).into_owned();
+            emit(&stdout);
+            return Ok(ExecOutput {
+                stdout,
+                stderr: String::from_utf8_lossy(&result.stderr).into_owned(),
+                exit_code: result.exit_code,
+                timed_out: result.timed_out,
+            });
+        }
+        Err(e) => return Err(e),
+    };
+
+    if let Some(stdin) = &req.stdin {
+        stream.write_stdin(stdin).await?;
+    }
+    stream.close_stdin().await?;
+
+    let mut output = ExecOutput::default();
+    let mut partial = Vec::new();
+    let collect = async {
+        while let Some(event) = stream.next_event().await? {
+            match event {
+                ExecEvent::Stdout(bytes) => {
+                    partial.extend_from_slice(&bytes);
+                    while let Some(end) = partial.iter().position(|b| *b == b'\n') {
+                        let line: Vec<u8> = partial.drain(..=end).collect();
+                        let line = String::from_utf8_lossy(&line);
+                        emit(&line);
+                        output.stdout.push_str(&line);
+                    }
+                }
+                ExecEvent::Stderr(bytes) => output.stderr.push_str(&String::from_utf8_lossy(&bytes)),
+                ExecEvent::Exit { code } => {
+                    output.exit_code = Some(code);
+                    break;
+                }
+                ExecEvent::Status(_) => {}
+            }
+        }
+        let rest = String::from_utf8_lossy(&partial).into_owned();
+        emit(&rest);
+        output.stdout.push_str(&rest);
+        Ok::<_, SandboxError>(())
+    };
+    match timeout(req.timeout.unwrap_or(PROCESS_TIMEOUT), collect).await {
+        Ok(result) => result?,
+        Err(_elapsed) => output.timed_out = true,
+    }
+    Ok(output)
+}
+
+/// Copy the files under `dir` into the sandbox's workspace, keeping their
+/// paths and modes, and return how many there were. Skips `SYNC_SKIP_DIRS`.
+async fn sync_repo(handle: &dyn SandboxHandle, dir: &Path) -> Result<usize> {
+    let files = repo_files(dir)?;
+    for (relative, path) in &files {
+        let bytes = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+            Some(std::fs::metadata(path)?.permissions().mode() & 0o777)
+        };
+        #[cfg(not(unix))]
+        let mode = None;
+        handle
+            .put_file(PutFileRequest {
+                path: relative.clone(),
+                bytes,
+                create_parents: true,
+                mode,
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to copy {relative} into the sandbox: {e}"))?;
+    }
+    Ok(files.len())
+}
+
+/// The regular files under `dir` as (slash-separated relative path, path),
+/// refusing a tree over `MAX_SYNC_BYTES`.
+fn repo_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    let mut total: u64 = 0;
+    let mut pending = vec![(String::new(), dir.to_path_buf())];
+    while let Some((prefix, current)) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .with_context(|| format!("failed to read {}", current.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = if prefix.is_empty() { name.clone() } else { format!("{prefix}/{name}") };
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if !SYNC_SKIP_DIRS.contains(&name.as_str()) {
+                    pending.push((relative, entry.path()));
+                }
+            } else if file_type.is_file() {
+                total += entry.metadata()?.len();
+                if total > MAX_SYNC_BYTES {
+                    anyhow::bail!(
+                        "{} is over {} MB, too big to copy into a sandbox",
+                        dir.display(),
+                        MAX_SYNC_BYTES / (1024 * 1024)
+                    );
+                }
+                files.push((relative, entry.path()));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sandbox::backends::dangerous::DangerousHostProvider;
 
     #[test]
     fn build_args_with_permissions() {
@@ -227,4 +558,105 @@ mod tests {
         let args = executor.build_claude_args();
         assert_eq!(args.last().unwrap(), "-");
     }
+
+    #[test]
+    fn sandbox_access_from_config() {
+        assert_eq!(SandboxAccess::from_config(&serde_json::Value::Null).unwrap(), None);
+
+        let access = SandboxAccess::from_config(&serde_json::json!({
+            "filesystem": "read-only",
+            "network": ["api.github.com"],
+        }))
+        .unwrap()
+        .unwrap();
+        assert!(access.read_only);
+        let mut spec = spec("access");
+        access.apply(&mut spec);
+        assert!(spec.filesystem.read_only_workspace);
+        assert_eq!(spec.network.mode, NetworkMode::Allowlist);
+        assert_eq!(spec.network.allowed_hosts, vec!["api.github.com", MODEL_API_HOST]);
+
+        let open = SandboxAccess::from_config(&serde_json::json!({})).unwrap().unwrap();
+        assert!(!open.read_only && !open.restricts_network());
+        assert!(SandboxAccess::from_config(&serde_json::json!({ "network": "none" })).is_err());
+        assert!(SandboxAccess::from_config(&serde_json::json!({ "network": ["a.com; rm -rf /"] })).is_err());
+    }
+
+    fn host_provider(root: &Path) -> DangerousHostProvider {
+        DangerousHostProvider::new(DangerousConfig {
+            root_dir: root.to_path_buf(),
+            ..DangerousConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn spec(workspace_id: &str) -> SandboxSpec {
+        SandboxSpec {
+            workspace_id: workspace_id.into(),
+            profile: SandboxProfile::Base,
+            filesystem: FilesystemSpec::default(),
+            resources: ResourceHints::default(),
+            env: BTreeMap::new(),
+            mounts: vec![],
+            network: NetworkPolicy::default_safe(),
+            lifecycle: LifecyclePolicy::default(),
+            labels: BTreeMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_repo_copies_sources_only() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join("src")).unwrap();
+        std::fs::create_dir_all(repo.path().join(".git")).unwrap();
+        std::fs::create_dir_all(repo.path().join("target/debug")).unwrap();
+        std::fs::write(repo.path().join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(repo.path().join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo.path().join(".git/HEAD"), "ref").unwrap();
+        std::fs::write(repo.path().join("target/debug/app"), "bin").unwrap();
+
+        let files: Vec<_> = repo_files(repo.path()).unwrap().into_iter().map(|(rel, _)| rel).collect();
+        assert_eq!(files, vec!["Cargo.toml", "src/main.rs"]);
+
+        let root = tempfile::tempdir().unwrap();
+        let handle = host_provider(root.path()).provision(spec("sync")).await.unwrap();
+        assert_eq!(sync_repo(&*handle, repo.path()).await.unwrap(), 2);
+        let copied = handle
+            .get_file(GetFileRequest { path: "src/main.rs".into(), max_bytes: None })
+            .await
+            .unwrap();
+        assert_eq!(copied.bytes, b"fn main() {}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn exec_lines_streams_stdout() {
+        let root = tempfile::tempdir().unwrap();
+        let handle = host_provider(root.path()).provision(spec("stream")).await.unwrap();
+        let lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = lines.clone();
+        let sink: LineSink = Arc::new(move |line| seen.lock().unwrap().push(line));
+
+        let output = exec_lines(
+            &*handle,
+            ExecRequest {
+                command: vec!["sh".into(), "-c".into(), "cat; echo second; echo oops >&2; exit 3".into()],
+                cwd: None,
+                env: BTreeMap::new(),
+                stdin: Some(b"first\n".to_vec()),
+                timeout: Some(Duration::from_secs(10)),
+                tty: false,
+                detach: false,
+            },
+            Some(&sink),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(*lines.lock().unwrap(), vec!["first", "second"]);
+        assert_eq!(output.stdout, "first\nsecond\n");
+        assert_eq!(output.stderr, "oops\n");
+        assert_eq!(output.exit_code, Some(3));
+        assert!(!output.timed_out);
+    }
 }
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::json_file;
+use super::sources::seen::SeenStore;
+use super::sources::{ContentItem, title_terms, url_domain};
+
+/// Executor kind that re-ranks (and optionally drops) items using the votes
+/// collected on earlier digests.
+pub const FEEDBACK_RANK_KIND: &str = "feedback-rank";
+
+/// Delivered items (and their votes) older than this are forgotten.
+const FEEDBACK_RETENTION_DAYS: i64 = 90;
+
+/// A vote on an item counts this much more when the same item comes back
+/// than the terms and domain it shares with other items.
+const ITEM_WEIGHT: i64 = 3;
+
+/// Serializes read-modify-write of feedback files between concurrent runs and
+/// the Slack actions endpoint.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Vote {
+    Up,
+    Down,
+}
+
+impl Vote {
+    fn value(self) -> i64 {
+        match self {
+            Vote::Up => 1,
+            Vote::Down => -1,
+        }
+    }
+}
+
+/// An item sent out with feedback buttons, and the votes it has received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackItem {
+    pub title: String,
+    pub url: String,
+    pub delivered_at: DateTime<Utc>,
+    /// Latest vote per voter (Slack user ID).
+    #[serde(default)]
+    pub votes: HashMap<String, Vote>,
+}
+
+impl FeedbackItem {
+    fn net_votes(&self) -> i64 {
+        self.votes.values().map(|v| v.value()).sum()
+    }
+}
+
+/// Per-flow record of items delivered with 👍/👎 buttons, keyed like
+/// [`SeenStore`] (hash of the URL, or title when there is none).
+///
+/// State lives under `~/.cthulu/state/feedback/{flow_id}.json`.
+#[derive(Clone)]
+pub struct FeedbackStore {
+    dir: PathBuf,
+}
+
+impl FeedbackStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { dir: data_dir.join("state").join("feedback") }
+    }
+
+    pub fn key(item: &ContentItem) -> String {
+        SeenStore::key(item)
+    }
+
+    fn path(&self, flow_id: &str) -> PathBuf {
+        self.dir.join(format!("{flow_id}.json"))
+    }
+
+    fn load(path: &Path) -> HashMap<String, FeedbackItem> {
+        json_file::load_or_default(path)
+    }
+
+    fn write(path: &Path, items: &HashMap<String, FeedbackItem>) -> Result<()> {
+        json_file::write(path, items)
+    }
+
+    /// Remember items just delivered with feedback buttons so later votes can
+    /// be attributed. Items already known keep their votes.
+    pub fn record_items(&self, flow_id: &str, items: &[ContentItem]) -> Result<()> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let path = self.path(flow_id);
+        let mut known = Self::load(&path);
+
+        let now = Utc::now();
+        let cutoff = now - chrono::Duration::days(FEEDBACK_RETENTION_DAYS);
+        known.retain(|_, item| item.delivered_at > cutoff);
+        for item in items {
+            known.entry(Self::key(item)).or_insert_with(|| FeedbackItem {
+                title: item.title.clone(),
+                url: item.url.clone(),
+                delivered_at: now,
+                votes: HashMap::new(),
+            });
+        }
+        Self::write(&path, &known)
+    }
+
+    /// Record `voter`'s vote on a delivered item, replacing any earlier one.
+    /// Returns the item, or `None` if it isn't known (expired or never sent).
+    pub fn record_vote(&self, flow_id: &str, key: &str, voter: &str, vote: Vote) -> Result<Option<FeedbackItem>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let path = self.path(flow_id);
+        let mut known = Self::load(&path);
+        let Some(item) = known.get_mut(key) else {
+            return Ok(None);
+        };
+        item.votes.insert(voter.to_string(), vote);
+        let item = item.clone();
+        Self::write(&path, &known)?;
+        Ok(Some(item))
+    }
+
+    /// What the votes on `flow_id`'s past items say about new ones.
+    pub fn profile(&self, flow_id: &str) -> FeedbackProfile {
+        let _guard = FILE_LOCK.lock().unwrap();
+        FeedbackProfile::from_items(Self::load(&self.path(flow_id)))
+    }
+}
+
+/// Net votes learned from past items: per item, per title term and per
+/// source domain.
+#[derive(Debug, Default)]
+pub struct FeedbackProfile {
+    items: HashMap<String, i64>,
+    terms: HashMap<String, i64>,
+    domains: HashMap<String, i64>,
+}
+
+impl FeedbackProfile {
+    fn from_items(items: HashMap<String, FeedbackItem>) -> Self {
+        let mut profile = Self::default();
+        for (key, item) in items {
+            let net = item.net_votes();
+            if net == 0 {
+                continue;
+            }
+            profile.items.insert(key, net);
+            for term in title_terms(&item.title) {
+                *profile.terms.entry(term).or_default() += net;
+            }
+            if let Some(domain) = url_domain(&item.url) {
+                *profile.domains.entry(domain).or_default() += net;
+            }
+        }
+        profile
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Positive for items like those voted up, negative for those like the
+    /// ones voted down, 0 when nothing is known.
+    pub fn score(&self, item: &ContentItem) -> i64 {
+        let direct = self.items.get(&FeedbackStore::key(item)).copied().unwrap_or(0) * ITEM_WEIGHT;
+        let terms: i64 = title_terms(&item.title)
+            .iter()
+            .filter_map(|t| self.terms.get(t))
+            .sum();
+        let domain = url_domain(&item.url)
+            .and_then(|d| self.domains.get(&d).copied())
+            .unwrap_or(0);
+        direct + terms + domain
+    }
+
+    /// Items best-scored first (ties keep source order), dropping those
+    /// scoring below `min_score`.
+    pub fn rank(&self, items: Vec<ContentItem>, min_score: Option<i64>) -> Vec<ContentItem> {
+        let mut scored: Vec<(i64, ContentItem)> = items
+            .into_iter()
+            .map(|item| (self.score(&item), item))
+            .filter(|(score, _)| min_score.is_none_or(|min| *score >= min))
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(title: &str, url: &str) -> ContentItem {
+        ContentItem {
+            title: title.to_string(),
+            url: url.to_string(),
+            summary: String::new(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        }
+    }
+
+    fn titles(items: &[ContentItem]) -> Vec<&str> {
+        items.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_votes_are_recorded_per_voter() {
+        let dir = tempdir().unwrap();
+        let store = FeedbackStore::new(dir.path());
+        let rust = item("Rust async traits", "https://blog.rust-lang.org/async");
+        store.record_items("f1", std::slice::from_ref(&rust)).unwrap();
+
+        let key = FeedbackStore::key(&rust);
+        store.record_vote("f1", &key, "U1", Vote::Down).unwrap();
+        store.record_vote("f1", &key, "U1", Vote::Up).unwrap();
+        let voted = store.record_vote("f1", &key, "U2", Vote::Up).unwrap().unwrap();
+        assert_eq!(voted.net_votes(), 2);
+
+        assert!(store.record_vote("f1", "unknown", "U1", Vote::Up).unwrap().is_none());
+        assert!(store.record_vote("f2", &key, "U1", Vote::Up).unwrap().is_none());
+
+        // Re-delivering keeps the votes
+        store.record_items("f1", &[rust]).unwrap();
+        assert_eq!(store.profile("f1").items[&key], 2);
+    }
+
+    #[test]
+    fn test_profile_ranks_similar_items() {
+        let dir = tempdir().unwrap();
+        let store = FeedbackStore::new(dir.path());
+        let liked = item("Rust compiler performance", "https://blog.rust-lang.org/perf");
+        let disliked = item("Celebrity gossip roundup", "https://www.gossip.example/today");
+        store.record_items("f1", &[liked.clone(), disliked.clone()]).unwrap();
+        store.record_vote("f1", &FeedbackStore::key(&liked), "U1", Vote::Up).unwrap();
+        store.record_vote("f1", &FeedbackStore::key(&disliked), "U1", Vote::Down).unwrap();
+
+        let profile = store.profile("f1");
+        let candidates = vec![
+            item("More gossip", "https://gossip.example/more"),
+            item("Weather today", "https://weather.example/"),
+            item("Rust performance tips", "https://example.com/rust"),
+        ];
+        assert_eq!(
+            titles(&profile.rank(candidates.clone(), None)),
+            ["Rust performance tips", "Weather today", "More gossip"]
+        );
+        assert_eq!(titles(&profile.rank(candidates, Some(0))), ["Rust performance tips", "Weather today"]);
+
+        // A voted item coming back counts its own votes most
+        assert_eq!(profile.score(&liked), ITEM_WEIGHT + 3 + 1);
+    }
+
+    #[test]
+    fn test_empty_profile_keeps_order() {
+        let profile = FeedbackProfile::default();
+        let items = vec![item("b", "https://b.example"), item("a", "https://a.example")];
+        assert!(profile.is_empty());
+        assert_eq!(titles(&profile.rank(items, Some(0))), ["b", "a"]);
+    }
+}
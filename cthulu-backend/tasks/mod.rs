@@ -1,6 +1,17 @@
+pub mod archive;
+pub mod ask;
+pub mod audio;
+pub mod cache;
+pub mod checks;
 pub mod context;
 pub mod diff;
 pub mod executors;
+pub mod feedback;
+pub mod personalize;
 pub mod pipeline;
+pub mod reports;
+pub mod risk;
 pub mod sinks;
 pub mod sources;
+pub mod trending;
+pub mod vectors;
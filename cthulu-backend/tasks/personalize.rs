@@ -0,0 +1,90 @@
+use super::sources::ContentItem;
+
+/// Title matches count double: a keyword in the headline is a stronger signal
+/// than one buried in the summary.
+const TITLE_WEIGHT: usize = 2;
+const SUMMARY_WEIGHT: usize = 1;
+
+/// How well `item` matches an interest profile: the weighted number of
+/// interest keywords (case-insensitive substrings) in its title and summary.
+pub fn score_item(item: &ContentItem, interests: &[String]) -> usize {
+    let title = item.title.to_lowercase();
+    let summary = item.summary.to_lowercase();
+    interests
+        .iter()
+        .map(|i| i.trim().to_lowercase())
+        .filter(|i| !i.is_empty())
+        .map(|interest| {
+            let mut score = 0;
+            if title.contains(&interest) {
+                score += TITLE_WEIGHT;
+            }
+            if summary.contains(&interest) {
+                score += SUMMARY_WEIGHT;
+            }
+            score
+        })
+        .sum()
+}
+
+/// One subscriber's cut of the shared items: those scoring at least
+/// `min_score`, best first (ties keep source order), capped at `limit`.
+/// An empty profile keeps the shared order.
+pub fn personalize(
+    items: &[ContentItem],
+    interests: &[String],
+    min_score: usize,
+    limit: usize,
+) -> Vec<ContentItem> {
+    if interests.iter().all(|i| i.trim().is_empty()) {
+        return items.iter().take(limit).cloned().collect();
+    }
+    let mut scored: Vec<(usize, &ContentItem)> = items
+        .iter()
+        .map(|item| (score_item(item, interests), item))
+        .filter(|(score, _)| *score >= min_score)
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().take(limit).map(|(_, item)| item.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, summary: &str) -> ContentItem {
+        ContentItem {
+            title: title.to_string(),
+            url: format!("https://example.com/{}", title.replace(' ', "-")),
+            summary: summary.to_string(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        }
+    }
+
+    #[test]
+    fn test_score_item() {
+        let interests = vec!["Rust".to_string(), "wasm".to_string(), " ".to_string()];
+        assert_eq!(score_item(&item("Rust 2.0 released", "WASM target improved"), &interests), 3);
+        assert_eq!(score_item(&item("Go news", "nothing relevant"), &interests), 0);
+    }
+
+    #[test]
+    fn test_personalize_filters_and_reranks() {
+        let items = vec![
+            item("Go generics", "types"),
+            item("Kubernetes update", "mentions rust once"),
+            item("Rust async", "rust runtime news"),
+        ];
+        let interests = vec!["rust".to_string()];
+
+        let picked = personalize(&items, &interests, 1, 10);
+        let titles: Vec<&str> = picked.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, ["Rust async", "Kubernetes update"]);
+
+        assert_eq!(personalize(&items, &interests, 0, 10).len(), 3);
+        assert_eq!(personalize(&items, &interests, 1, 1).len(), 1);
+        assert_eq!(personalize(&items, &[], 1, 2)[0].title, "Go generics");
+    }
+}
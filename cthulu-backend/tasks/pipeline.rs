@@ -4,8 +4,11 @@ use anyhow::{Context, Result};
 
 use crate::config::SinkConfig;
 use crate::tasks::sinks::Sink;
+use crate::tasks::sinks::desktop::DesktopSink;
+use crate::tasks::sinks::email::EmailSink;
 use crate::tasks::sinks::notion::NotionSink;
-use crate::tasks::sinks::slack::{SlackApiSink, SlackWebhookSink};
+use crate::tasks::sinks::read_later::{PocketSink, ReadwiseSink};
+use crate::tasks::sinks::slack::{SlackApiSink, SlackTarget, SlackWebhookSink};
 use crate::tasks::sources::ContentItem;
 
 pub fn resolve_sinks(
@@ -20,21 +23,17 @@ pub fn resolve_sinks(
                 webhook_url_env,
                 bot_token_env,
                 channel,
+                user_email,
             } => {
                 if let Some(token_env) = bot_token_env {
-                    let bot_token = std::env::var(token_env).with_context(|| {
-                        format!("sink requires env var {token_env} but it is not set")
-                    })?;
-                    let channel = channel.as_ref().with_context(|| {
-                        "slack bot_token_env requires a channel to be set"
-                    })?;
-                    sinks.push(Arc::new(SlackApiSink::new(
-                        Arc::clone(http_client),
-                        bot_token,
-                        channel.clone(),
-                    )));
+                    sinks.push(Arc::new(slack_api_sink(
+                        http_client,
+                        token_env,
+                        channel.as_deref(),
+                        user_email.as_deref(),
+                    )?));
                 } else if let Some(webhook_env) = webhook_url_env {
-                    let webhook_url = std::env::var(webhook_env).with_context(|| {
+                    let webhook_url = crate::secrets::var(webhook_env).with_context(|| {
                         format!("sink requires env var {webhook_env} but it is not set")
                     })?;
                     sinks.push(Arc::new(SlackWebhookSink::new(
@@ -45,11 +44,28 @@ pub fn resolve_sinks(
                     anyhow::bail!("slack sink requires either webhook_url_env or bot_token_env");
                 }
             }
+            SinkConfig::Email {
+                smtp_url_env,
+                from_env,
+                to,
+                subject,
+            } => {
+                let smtp_url = crate::secrets::var(smtp_url_env).with_context(|| {
+                    format!("sink requires env var {smtp_url_env} but it is not set")
+                })?;
+                let from = crate::secrets::var(from_env).with_context(|| {
+                    format!("sink requires env var {from_env} but it is not set")
+                })?;
+                if to.is_empty() {
+                    anyhow::bail!("email sink requires at least one 'to' address");
+                }
+                sinks.push(Arc::new(EmailSink::new(smtp_url, from, to.clone(), subject.clone())));
+            }
             SinkConfig::Notion {
                 token_env,
                 database_id,
             } => {
-                let token = std::env::var(token_env).with_context(|| {
+                let token = crate::secrets::var(token_env).with_context(|| {
                     format!("sink requires env var {token_env} but it is not set")
                 })?;
                 sinks.push(Arc::new(NotionSink::new(
@@ -58,12 +74,60 @@ pub fn resolve_sinks(
                     database_id.clone(),
                 )));
             }
+            SinkConfig::Pocket {
+                consumer_key_env,
+                access_token_env,
+                tags,
+            } => {
+                let consumer_key = crate::secrets::var(consumer_key_env).with_context(|| {
+                    format!("sink requires env var {consumer_key_env} but it is not set")
+                })?;
+                let access_token = crate::secrets::var(access_token_env).with_context(|| {
+                    format!("sink requires env var {access_token_env} but it is not set")
+                })?;
+                sinks.push(Arc::new(PocketSink::new(
+                    Arc::clone(http_client),
+                    consumer_key,
+                    access_token,
+                    tags.clone(),
+                )));
+            }
+            SinkConfig::Readwise { token_env, tags } => {
+                let token = crate::secrets::var(token_env).with_context(|| {
+                    format!("sink requires env var {token_env} but it is not set")
+                })?;
+                sinks.push(Arc::new(ReadwiseSink::new(
+                    Arc::clone(http_client),
+                    token,
+                    tags.clone(),
+                )));
+            }
+            SinkConfig::Desktop { title, sound } => {
+                sinks.push(Arc::new(DesktopSink::new(title.clone(), *sound)));
+            }
         }
     }
 
     Ok(sinks)
 }
 
+/// A bot-token Slack sink posting to `user_email`'s DM if set, else `channel`.
+pub fn slack_api_sink(
+    http_client: &Arc<reqwest::Client>,
+    token_env: &str,
+    channel: Option<&str>,
+    user_email: Option<&str>,
+) -> Result<SlackApiSink> {
+    let bot_token = crate::secrets::var(token_env)
+        .with_context(|| format!("sink requires env var {token_env} but it is not set"))?;
+    let target = match (channel, user_email) {
+        (_, Some(email)) => SlackTarget::DirectMessage { user_email: email.to_string() },
+        (Some(channel), None) => SlackTarget::Channel(channel.to_string()),
+        (None, None) => anyhow::bail!("slack bot_token_env requires a channel or user_email to be set"),
+    };
+    Ok(SlackApiSink::new(Arc::clone(http_client), bot_token, target))
+}
+
 pub fn format_items(items: &[ContentItem]) -> String {
     if items.is_empty() {
         return "No items fetched.".to_string();
@@ -78,7 +142,7 @@ pub fn format_items(items: &[ContentItem]) -> String {
                 .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
                 .unwrap_or_else(|| "unknown date".to_string());
 
-            let summary_short = if item.summary.len() > 500 {
+            let summary_short = if !item.full_summary && item.summary.len() > 500 {
                 let mut end = 500;
                 while !item.summary.is_char_boundary(end) {
                     end -= 1;
@@ -112,6 +176,23 @@ pub fn format_items(items: &[ContentItem]) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slack_bot_sink_needs_channel_or_user_email() {
+        let http_client = Arc::new(reqwest::Client::new());
+        // Any env var that is always set works as a stand-in token.
+        let slack = |channel: Option<&str>, user_email: Option<&str>| SinkConfig::Slack {
+            webhook_url_env: None,
+            bot_token_env: Some("PATH".to_string()),
+            channel: channel.map(String::from),
+            user_email: user_email.map(String::from),
+        };
+
+        let err = resolve_sinks(&[slack(None, None)], &http_client).err().unwrap();
+        assert!(err.to_string().contains("channel or user_email"));
+        assert_eq!(resolve_sinks(&[slack(Some("#briefs"), None)], &http_client).unwrap().len(), 1);
+        assert_eq!(resolve_sinks(&[slack(None, Some("me@example.com"))], &http_client).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_format_items_empty() {
         let result = format_items(&[]);
@@ -127,6 +208,7 @@ mod tests {
                 summary: "Bitcoin reached a new all-time high.".to_string(),
                 published: None,
                 image_url: None,
+                full_summary: false,
             },
             ContentItem {
                 title: "ETH Update".to_string(),
@@ -134,6 +216,7 @@ mod tests {
                 summary: "Ethereum ships a major update.".to_string(),
                 published: None,
                 image_url: Some("https://example.com/eth.jpg".to_string()),
+                full_summary: false,
             },
         ];
         let result = format_items(&items);
@@ -143,4 +226,21 @@ mod tests {
         assert!(!result.contains("Image: https://example.com/1"));
         assert!(result.contains("Image: https://example.com/eth.jpg"));
     }
+
+    #[test]
+    fn test_format_items_full_summary_not_truncated() {
+        let long = "word ".repeat(200);
+        let mut item = ContentItem {
+            title: "Paper".to_string(),
+            url: "https://arxiv.org/abs/2401.00001".to_string(),
+            summary: long.clone(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        };
+        assert!(format_items(std::slice::from_ref(&item)).contains("..."));
+
+        item.full_summary = true;
+        assert!(format_items(&[item]).contains(long.trim_end()));
+    }
 }
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+
+use super::{Finding, Severity};
+
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    message: String,
+    level: String,
+    code: Option<DiagnosticCode>,
+    #[serde(default)]
+    spans: Vec<Span>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    file_name: String,
+    line_start: u64,
+    is_primary: bool,
+}
+
+/// Parse `cargo clippy --message-format=json` output: one JSON object per
+/// line, of which only `compiler-message`s with a source location are kept.
+/// The same diagnostic reported for several targets is listed once.
+pub fn parse(text: &str) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    let mut seen = HashSet::new();
+    let mut parsed_any = false;
+
+    for line in text.lines().filter(|l| l.trim_start().starts_with('{')) {
+        let Ok(msg) = serde_json::from_str::<CargoMessage>(line) else {
+            continue;
+        };
+        parsed_any = true;
+        if msg.reason != "compiler-message" {
+            continue;
+        }
+        let Some(diag) = msg.message else {
+            continue;
+        };
+        // Summary lines like "aborting due to 2 previous errors" have no span
+        let Some(span) = diag.spans.iter().find(|s| s.is_primary) else {
+            continue;
+        };
+        let severity = match diag.level.as_str() {
+            "error" | "error: internal compiler error" => Severity::Error,
+            "warning" => Severity::Warning,
+            _ => Severity::Info,
+        };
+        let finding = Finding {
+            path: span.file_name.clone(),
+            line: Some(span.line_start),
+            severity,
+            rule: diag.code.map(|c| c.code),
+            message: diag.message,
+        };
+        if seen.insert((finding.path.clone(), finding.line, finding.message.clone())) {
+            findings.push(finding);
+        }
+    }
+
+    if !parsed_any {
+        bail!("no cargo JSON messages found (run clippy with --message-format=json)");
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let warning = r#"{"reason":"compiler-message","package_id":"demo 0.1.0","target":{"name":"demo"},"message":{"message":"this `if` statement can be collapsed","code":{"code":"clippy::collapsible_if","explanation":null},"level":"warning","spans":[{"file_name":"src/main.rs","line_start":12,"line_end":14,"is_primary":true}],"children":[],"rendered":"warning: ..."}}"#;
+        let text = [
+            r#"{"reason":"compiler-artifact","package_id":"dep 1.0.0"}"#,
+            warning,
+            warning, // same diagnostic for the test target
+            r#"{"reason":"compiler-message","message":{"message":"mismatched types","code":{"code":"E0308"},"level":"error","spans":[{"file_name":"src/lib.rs","line_start":3,"is_primary":false},{"file_name":"src/lib.rs","line_start":4,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"aborting due to 1 previous error","code":null,"level":"error","spans":[]}}"#,
+            r#"{"reason":"build-finished","success":false}"#,
+        ]
+        .join("\n");
+
+        let findings = parse(&text).unwrap();
+        assert_eq!(
+            findings,
+            vec![
+                Finding {
+                    path: "src/main.rs".into(),
+                    line: Some(12),
+                    severity: Severity::Warning,
+                    rule: Some("clippy::collapsible_if".into()),
+                    message: "this `if` statement can be collapsed".into(),
+                },
+                Finding {
+                    path: "src/lib.rs".into(),
+                    line: Some(4),
+                    severity: Severity::Error,
+                    rule: Some("E0308".into()),
+                    message: "mismatched types".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_human_output() {
+        assert!(parse("warning: unused variable: `x`\n --> src/main.rs:2:9").is_err());
+        // A clean run still has build messages
+        assert!(parse(r#"{"reason":"build-finished","success":true}"#).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{Finding, Severity};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileResult {
+    file_path: String,
+    #[serde(default)]
+    messages: Vec<Message>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Message {
+    rule_id: Option<String>,
+    /// 1 = warning, 2 = error
+    severity: u8,
+    message: String,
+    line: Option<u64>,
+}
+
+/// Parse `eslint --format json` output.
+pub fn parse(text: &str) -> Result<Vec<Finding>> {
+    let results: Vec<FileResult> = serde_json::from_str(text).context("invalid ESLint JSON report")?;
+    Ok(results
+        .into_iter()
+        .flat_map(|file| {
+            let path = file.file_path;
+            file.messages.into_iter().map(move |m| Finding {
+                path: path.clone(),
+                line: m.line,
+                severity: if m.severity >= 2 { Severity::Error } else { Severity::Warning },
+                rule: m.rule_id,
+                message: m.message,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = r#"[
+            {
+                "filePath": "/repo/src/app.js",
+                "messages": [
+                    { "ruleId": "no-unused-vars", "severity": 1, "message": "'x' is defined but never used.", "line": 3, "column": 7 },
+                    { "ruleId": null, "severity": 2, "message": "Parsing error: Unexpected token", "line": 9, "column": 1, "fatal": true }
+                ],
+                "errorCount": 1,
+                "warningCount": 1
+            },
+            { "filePath": "/repo/src/clean.js", "messages": [], "errorCount": 0, "warningCount": 0 }
+        ]"#;
+        let findings = parse(text).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::Warning);
+        assert_eq!(findings[0].rule.as_deref(), Some("no-unused-vars"));
+        assert_eq!(findings[0].line, Some(3));
+        assert_eq!(findings[1].severity, Severity::Error);
+        assert_eq!(findings[1].rule, None);
+
+        assert!(parse("not json").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Result, bail};
+
+use super::FileCoverage;
+
+/// Parse an lcov tracefile. Line totals come from `LF`/`LH` when present,
+/// otherwise from the record's `DA` lines.
+pub fn parse(text: &str) -> Result<Vec<FileCoverage>> {
+    let mut files = Vec::new();
+    let mut current: Option<Record> = None;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(path) = line.strip_prefix("SF:") {
+            current = Some(Record::new(path));
+        } else if line == "end_of_record" {
+            if let Some(record) = current.take() {
+                files.push(record.finish());
+            }
+        } else if let Some(record) = current.as_mut() {
+            if let Some(n) = line.strip_prefix("LF:") {
+                record.found = n.parse().ok();
+            } else if let Some(n) = line.strip_prefix("LH:") {
+                record.hit = n.parse().ok();
+            } else if let Some(da) = line.strip_prefix("DA:") {
+                // DA:<line>,<hits>[,<checksum>]
+                let hits = da.split(',').nth(1).and_then(|h| h.parse::<u64>().ok());
+                if let Some(hits) = hits {
+                    record.da_total += 1;
+                    record.da_covered += u64::from(hits > 0);
+                }
+            }
+        }
+    }
+    // Tolerate a missing final end_of_record
+    if let Some(record) = current {
+        files.push(record.finish());
+    }
+
+    if files.is_empty() {
+        bail!("no SF records found in lcov report");
+    }
+    Ok(files)
+}
+
+struct Record {
+    path: String,
+    found: Option<u64>,
+    hit: Option<u64>,
+    da_total: u64,
+    da_covered: u64,
+}
+
+impl Record {
+    fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            found: None,
+            hit: None,
+            da_total: 0,
+            da_covered: 0,
+        }
+    }
+
+    fn finish(self) -> FileCoverage {
+        FileCoverage {
+            path: self.path,
+            lines_total: self.found.unwrap_or(self.da_total),
+            lines_covered: self.hit.unwrap_or(self.da_covered),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "\
+TN:
+SF:src/lib.rs
+FN:1,main
+DA:1,4
+DA:2,0
+DA:3,1
+LF:3
+LH:2
+end_of_record
+SF:src/util.rs
+DA:1,0
+DA:2,7,abc123
+";
+        let files = parse(text).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                FileCoverage { path: "src/lib.rs".into(), lines_total: 3, lines_covered: 2 },
+                FileCoverage { path: "src/util.rs".into(), lines_total: 2, lines_covered: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_non_lcov() {
+        assert!(parse("{\"data\": []}").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use super::FileCoverage;
+
+#[derive(Deserialize)]
+struct Export {
+    data: Vec<ExportData>,
+}
+
+#[derive(Deserialize)]
+struct ExportData {
+    #[serde(default)]
+    files: Vec<ExportFile>,
+}
+
+#[derive(Deserialize)]
+struct ExportFile {
+    filename: String,
+    summary: FileSummary,
+}
+
+#[derive(Deserialize)]
+struct FileSummary {
+    lines: LineSummary,
+}
+
+#[derive(Deserialize)]
+struct LineSummary {
+    count: u64,
+    covered: u64,
+}
+
+/// Parse `cargo llvm-cov --json` output (`llvm-cov export` JSON).
+pub fn parse(text: &str) -> Result<Vec<FileCoverage>> {
+    let export: Export = serde_json::from_str(text).context("invalid llvm-cov JSON export")?;
+    let files: Vec<FileCoverage> = export
+        .data
+        .into_iter()
+        .flat_map(|d| d.files)
+        .map(|f| FileCoverage {
+            path: f.filename,
+            lines_total: f.summary.lines.count,
+            lines_covered: f.summary.lines.covered,
+        })
+        .collect();
+    if files.is_empty() {
+        bail!("llvm-cov export contains no files");
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = r#"{
+            "type": "llvm.coverage.json.export",
+            "version": "2.0.1",
+            "data": [{
+                "files": [{
+                    "filename": "/repo/src/lib.rs",
+                    "segments": [],
+                    "summary": {
+                        "lines": { "count": 40, "covered": 30, "percent": 75.0 },
+                        "functions": { "count": 4, "covered": 3, "percent": 75.0 }
+                    }
+                }],
+                "totals": { "lines": { "count": 40, "covered": 30, "percent": 75.0 } }
+            }]
+        }"#;
+        assert_eq!(
+            parse(text).unwrap(),
+            vec![FileCoverage { path: "/repo/src/lib.rs".into(), lines_total: 40, lines_covered: 30 }]
+        );
+        assert!(parse(r#"{"data": [{"files": []}]}"#).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+//! Parsers for CI artifacts — coverage reports and static-analysis output —
+//! so flows get structured results instead of raw text blobs.
+
+pub mod clippy;
+pub mod eslint;
+pub mod lcov;
+pub mod llvm_cov;
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use anyhow::{Result, bail};
+
+use super::sources::ContentItem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Lcov,
+    /// `cargo llvm-cov --json` (the `llvm-cov export` format).
+    LlvmCov,
+    /// `cargo clippy --message-format=json`
+    Clippy,
+    /// `eslint --format json`
+    Eslint,
+}
+
+impl ReportFormat {
+    pub fn parse_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "lcov" => Self::Lcov,
+            "llvm-cov" | "cargo-llvm-cov" => Self::LlvmCov,
+            "clippy" => Self::Clippy,
+            "eslint" => Self::Eslint,
+            other => bail!("unknown report format '{other}' (expected lcov, llvm-cov, clippy or eslint)"),
+        })
+    }
+
+    fn tool(self) -> &'static str {
+        match self {
+            Self::Lcov | Self::LlvmCov => "Coverage",
+            Self::Clippy => "Clippy",
+            Self::Eslint => "ESLint",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileCoverage {
+    pub path: String,
+    pub lines_total: u64,
+    pub lines_covered: u64,
+}
+
+impl FileCoverage {
+    pub fn percent(&self) -> f64 {
+        percent(self.lines_covered, self.lines_total)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub path: String,
+    pub line: Option<u64>,
+    pub severity: Severity,
+    pub rule: Option<String>,
+    pub message: String,
+}
+
+impl Finding {
+    fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{line}", self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    fn heading(&self) -> String {
+        match &self.rule {
+            Some(rule) => format!("{}[{rule}] {}", self.severity, self.location()),
+            None => format!("{} {}", self.severity, self.location()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Report {
+    Coverage(Vec<FileCoverage>),
+    Findings(Vec<Finding>),
+}
+
+pub fn parse(format: ReportFormat, text: &str) -> Result<Report> {
+    match format {
+        ReportFormat::Lcov => lcov::parse(text).map(Report::Coverage),
+        ReportFormat::LlvmCov => llvm_cov::parse(text).map(Report::Coverage),
+        ReportFormat::Clippy => clippy::parse(text).map(Report::Findings),
+        ReportFormat::Eslint => eslint::parse(text).map(Report::Findings),
+    }
+}
+
+impl Report {
+    /// One-line headline, e.g. `Coverage: 81.5% of lines (815/1000)` or
+    /// `Clippy: 2 errors, 5 warnings`.
+    pub fn headline(&self, format: ReportFormat) -> String {
+        let tool = format.tool();
+        match self {
+            Report::Coverage(files) => {
+                let (covered, total) = coverage_totals(files);
+                format!("{tool}: {:.1}% of lines ({covered}/{total})", percent(covered, total))
+            }
+            Report::Findings(findings) if findings.is_empty() => format!("{tool}: no findings"),
+            Report::Findings(findings) => {
+                let mut counts: BTreeMap<Severity, usize> = BTreeMap::new();
+                for f in findings {
+                    *counts.entry(f.severity).or_default() += 1;
+                }
+                let parts: Vec<String> = counts
+                    .into_iter()
+                    .map(|(sev, n)| format!("{n} {sev}{}", if n == 1 { "" } else { "s" }))
+                    .collect();
+                format!("{tool}: {}", parts.join(", "))
+            }
+        }
+    }
+
+    /// Markdown summary for prompts: the headline, then the `max_entries`
+    /// least-covered files or most severe findings.
+    pub fn summary(&self, format: ReportFormat, max_entries: usize) -> String {
+        let mut out = self.headline(format);
+        match self {
+            Report::Coverage(files) => {
+                for f in least_covered(files).into_iter().take(max_entries) {
+                    let _ = write!(
+                        out,
+                        "\n- {} — {:.1}% ({}/{})",
+                        f.path,
+                        f.percent(),
+                        f.lines_covered,
+                        f.lines_total
+                    );
+                }
+            }
+            Report::Findings(findings) => {
+                for f in by_severity(findings).into_iter().take(max_entries) {
+                    let _ = write!(out, "\n- {}: {}", f.heading(), f.message);
+                }
+                if findings.len() > max_entries {
+                    let _ = write!(out, "\n- … and {} more", findings.len() - max_entries);
+                }
+            }
+        }
+        out
+    }
+
+    /// A headline item followed by up to `limit` items, one per file (coverage,
+    /// least covered first) or per finding (most severe first).
+    pub fn to_items(&self, format: ReportFormat, location: &str, limit: usize) -> Vec<ContentItem> {
+        let item = |title: String, summary: String| ContentItem {
+            title,
+            url: String::new(),
+            summary,
+            published: None,
+            image_url: None,
+            full_summary: false,
+        };
+
+        let mut items = vec![ContentItem {
+            url: location.to_string(),
+            ..item(self.headline(format), format!("Report: {location}"))
+        }];
+        match self {
+            Report::Coverage(files) => items.extend(least_covered(files).into_iter().take(limit).map(|f| {
+                item(
+                    format!("{} — {:.1}% covered", f.path, f.percent()),
+                    format!("{} of {} lines covered.", f.lines_covered, f.lines_total),
+                )
+            })),
+            Report::Findings(findings) => items.extend(
+                by_severity(findings)
+                    .into_iter()
+                    .take(limit)
+                    .map(|f| item(f.heading(), f.message.clone())),
+            ),
+        }
+        items
+    }
+}
+
+fn percent(covered: u64, total: u64) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+fn coverage_totals(files: &[FileCoverage]) -> (u64, u64) {
+    files
+        .iter()
+        .fold((0, 0), |(c, t), f| (c + f.lines_covered, t + f.lines_total))
+}
+
+/// Files with coverable lines, least covered first.
+fn least_covered(files: &[FileCoverage]) -> Vec<&FileCoverage> {
+    let mut files: Vec<&FileCoverage> = files.iter().filter(|f| f.lines_total > 0).collect();
+    files.sort_by(|a, b| a.percent().total_cmp(&b.percent()).then(a.path.cmp(&b.path)));
+    files
+}
+
+/// Errors first; otherwise in report order.
+fn by_severity(findings: &[Finding]) -> Vec<&Finding> {
+    let mut sorted: Vec<&Finding> = findings.iter().collect();
+    sorted.sort_by_key(|f| f.severity);
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(path: &str, severity: Severity, message: &str) -> Finding {
+        Finding {
+            path: path.into(),
+            line: Some(3),
+            severity,
+            rule: Some("no-unused-vars".into()),
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn test_coverage_summary() {
+        let report = Report::Coverage(vec![
+            FileCoverage { path: "src/a.rs".into(), lines_total: 100, lines_covered: 90 },
+            FileCoverage { path: "src/b.rs".into(), lines_total: 50, lines_covered: 10 },
+            FileCoverage { path: "src/empty.rs".into(), lines_total: 0, lines_covered: 0 },
+        ]);
+        assert_eq!(
+            report.summary(ReportFormat::Lcov, 5),
+            "Coverage: 66.7% of lines (100/150)\n\
+             - src/b.rs — 20.0% (10/50)\n\
+             - src/a.rs — 90.0% (90/100)"
+        );
+    }
+
+    #[test]
+    fn test_findings_summary_and_items() {
+        let report = Report::Findings(vec![
+            finding("a.js", Severity::Warning, "'x' is unused"),
+            finding("b.js", Severity::Error, "'y' is not defined"),
+            finding("c.js", Severity::Warning, "'z' is unused"),
+        ]);
+        assert_eq!(
+            report.summary(ReportFormat::Eslint, 2),
+            "ESLint: 1 error, 2 warnings\n\
+             - error[no-unused-vars] b.js:3: 'y' is not defined\n\
+             - warning[no-unused-vars] a.js:3: 'x' is unused\n\
+             - … and 1 more"
+        );
+
+        let items = report.to_items(ReportFormat::Eslint, "ci/eslint.json", 1);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "ESLint: 1 error, 2 warnings");
+        assert_eq!(items[0].url, "ci/eslint.json");
+        assert_eq!(items[1].title, "error[no-unused-vars] b.js:3");
+        assert_eq!(Report::Findings(vec![]).headline(ReportFormat::Clippy), "Clippy: no findings");
+    }
+
+    #[test]
+    fn test_parse_name() {
+        assert_eq!(ReportFormat::parse_name("cargo-llvm-cov").unwrap(), ReportFormat::LlvmCov);
+        assert!(ReportFormat::parse_name("cobertura").is_err());
+    }
+}
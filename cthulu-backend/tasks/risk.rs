@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use glob::Pattern;
+
+use super::diff::FileDiff;
+
+/// Scores at or above these mark a PR as high / medium risk.
+const HIGH_RISK: u32 = 60;
+const MEDIUM_RISK: u32 = 30;
+
+/// Deterministic risk assessment of a PR diff, for routing reviews.
+///
+/// Points come from four factors (total capped at 100):
+/// - size: changed lines (0–30)
+/// - breadth: files touched (0–20)
+/// - tests: code changed without matching test changes (0–20)
+/// - critical paths: touching any configured glob (30)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskReport {
+    pub score: u32,
+    pub factors: Vec<String>,
+}
+
+impl RiskReport {
+    pub fn level(&self) -> &'static str {
+        if self.score >= HIGH_RISK {
+            "high"
+        } else if self.score >= MEDIUM_RISK {
+            "medium"
+        } else {
+            "low"
+        }
+    }
+
+    /// `{{risk_score}}`, `{{risk_level}}` and `{{risk_factors}}`.
+    pub fn vars(&self) -> HashMap<String, String> {
+        let factors = if self.factors.is_empty() {
+            "none".to_string()
+        } else {
+            self.factors.join("; ")
+        };
+        HashMap::from([
+            ("risk_score".to_string(), self.score.to_string()),
+            ("risk_level".to_string(), self.level().to_string()),
+            ("risk_factors".to_string(), factors),
+        ])
+    }
+}
+
+pub fn assess(files: &[FileDiff], critical_paths: &[String]) -> RiskReport {
+    let mut score = 0;
+    let mut factors = Vec::new();
+
+    let changed: usize = files.iter().map(|f| f.additions + f.deletions).sum();
+    let size_points = match changed {
+        0..50 => 0,
+        50..200 => 10,
+        200..500 => 20,
+        _ => 30,
+    };
+    if size_points > 0 {
+        factors.push(format!("{changed} lines changed"));
+        score += size_points;
+    }
+
+    let breadth_points = match files.len() {
+        0..5 => 0,
+        5..15 => 10,
+        _ => 20,
+    };
+    if breadth_points > 0 {
+        factors.push(format!("{} files touched", files.len()));
+        score += breadth_points;
+    }
+
+    let (test_lines, code_lines) = files.iter().fold((0, 0), |(t, c), f| {
+        let lines = f.additions + f.deletions;
+        if is_test_path(&f.path) { (t + lines, c) } else { (t, c + lines) }
+    });
+    if code_lines > 0 && test_lines == 0 {
+        factors.push("code changed with no test changes".to_string());
+        score += 20;
+    } else if test_lines * 5 < code_lines {
+        factors.push(format!("{test_lines} test lines for {code_lines} code lines"));
+        score += 10;
+    }
+
+    let patterns: Vec<Pattern> = critical_paths
+        .iter()
+        .filter_map(|p| match Pattern::new(p) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                tracing::warn!(pattern = %p, error = %e, "Ignoring invalid critical path pattern");
+                None
+            }
+        })
+        .collect();
+    let critical: Vec<&str> = files
+        .iter()
+        .filter(|f| patterns.iter().any(|p| p.matches(&f.path)))
+        .map(|f| f.path.as_str())
+        .collect();
+    if !critical.is_empty() {
+        factors.push(format!("touches critical paths: {}", critical.join(", ")));
+        score += 30;
+    }
+
+    RiskReport {
+        score: score.min(100),
+        factors,
+    }
+}
+
+/// Conventional test locations and file names across common ecosystems.
+fn is_test_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    let name = lower.rsplit('/').next().unwrap_or(&lower);
+    let stem = name.split('.').next().unwrap_or(name);
+    lower.split('/').any(|dir| matches!(dir, "test" | "tests" | "__tests__" | "spec"))
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_spec")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, additions: usize, deletions: usize) -> FileDiff {
+        FileDiff {
+            path: path.to_string(),
+            content: String::new(),
+            additions,
+            deletions,
+        }
+    }
+
+    #[test]
+    fn test_small_tested_change_is_low_risk() {
+        let files = vec![file("src/lib.rs", 10, 2), file("tests/lib.rs", 8, 0)];
+        let report = assess(&files, &[]);
+        assert_eq!(report.score, 0);
+        assert_eq!(report.level(), "low");
+        assert_eq!(report.vars()["risk_factors"], "none");
+    }
+
+    #[test]
+    fn test_large_untested_critical_change_is_high_risk() {
+        let mut files: Vec<FileDiff> = (0..16).map(|i| file(&format!("src/mod{i}.rs"), 40, 0)).collect();
+        files.push(file("migrations/0042_users.sql", 5, 0));
+        let report = assess(&files, &["migrations/**".to_string()]);
+        // size 30 + breadth 20 + no tests 20 + critical 30, capped
+        assert_eq!(report.score, 100);
+        assert_eq!(report.level(), "high");
+        assert_eq!(
+            report.factors,
+            vec![
+                "645 lines changed".to_string(),
+                "17 files touched".to_string(),
+                "code changed with no test changes".to_string(),
+                "touches critical paths: migrations/0042_users.sql".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thin_test_coverage_is_medium_risk() {
+        let files = vec![file("src/auth.rs", 120, 30), file("src/auth_test.rs", 10, 0)];
+        let report = assess(&files, &["src/auth*".to_string()]);
+        // size 10 + thin tests 10 + critical 30
+        assert_eq!(report.score, 50);
+        assert_eq!(report.level(), "medium");
+    }
+
+    #[test]
+    fn test_is_test_path() {
+        assert!(is_test_path("tests/integration.rs"));
+        assert!(is_test_path("web/src/__tests__/App.tsx"));
+        assert!(is_test_path("pkg/server_test.go"));
+        assert!(is_test_path("app/test_models.py"));
+        assert!(is_test_path("src/button.spec.ts"));
+        assert!(!is_test_path("src/attestation.rs"));
+        assert!(!is_test_path("src/contest/mod.rs"));
+    }
+}
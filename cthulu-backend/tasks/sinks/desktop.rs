@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::process::Command;
+
+use super::Sink;
+
+/// Characters of the output shown in the notification body; the rest is in
+/// the run history.
+const MAX_BODY_CHARS: usize = 240;
+
+/// Pops up a native notification on the machine cthulu runs on (macOS via
+/// `osascript`, Linux via `notify-send`) — a lightweight ping for people
+/// running flows on their laptop.
+pub struct DesktopSink {
+    title: Option<String>,
+    sound: bool,
+}
+
+impl DesktopSink {
+    pub fn new(title: Option<String>, sound: bool) -> Self {
+        Self { title, sound }
+    }
+}
+
+#[async_trait]
+impl Sink for DesktopSink {
+    async fn deliver(&self, text: &str) -> Result<()> {
+        let (heading, body) = summarize(text);
+        let title = self.title.clone().or(heading).unwrap_or_else(|| "Cthulu".to_string());
+        let (program, args) = command(std::env::consts::OS, &title, &body, self.sound)
+            .context("desktop notifications are only supported on macOS and Linux")?;
+
+        let output = Command::new(program)
+            .args(&args)
+            .output()
+            .await
+            .with_context(|| format!("failed to run {program}"))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "{program} failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        tracing::info!(title = %title, "Delivered desktop notification");
+        Ok(())
+    }
+}
+
+/// The output's first heading, and its first lines of prose flattened and
+/// cut to [`MAX_BODY_CHARS`].
+fn summarize(text: &str) -> (Option<String>, String) {
+    let mut heading = None;
+    let mut words = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with("```")) {
+        if line.starts_with('#') {
+            if heading.is_none() {
+                heading = Some(line.trim_start_matches('#').trim().to_string());
+            }
+            continue;
+        }
+        let line = line.trim_start_matches(['-', '*', '>']).trim();
+        words.extend(line.split_whitespace().map(|w| w.replace("**", "").replace('`', "")));
+        if words.iter().map(|w| w.len() + 1).sum::<usize>() > MAX_BODY_CHARS {
+            break;
+        }
+    }
+    let body = words.join(" ");
+    let body = if body.chars().count() > MAX_BODY_CHARS {
+        let cut: String = body.chars().take(MAX_BODY_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        body
+    };
+    (heading, body)
+}
+
+/// Program and arguments that show the notification on `os`. Text is passed
+/// as arguments, never spliced into a script.
+fn command(os: &str, title: &str, body: &str, sound: bool) -> Option<(&'static str, Vec<String>)> {
+    match os {
+        "macos" => {
+            let display = if sound {
+                "display notification (item 2 of argv) with title (item 1 of argv) sound name \"default\""
+            } else {
+                "display notification (item 2 of argv) with title (item 1 of argv)"
+            };
+            let args = ["-e", "on run argv", "-e", display, "-e", "end run", title, body];
+            Some(("osascript", args.iter().map(|a| a.to_string()).collect()))
+        }
+        "linux" | "freebsd" | "openbsd" | "netbsd" => {
+            let mut args = vec!["--app-name=Cthulu".to_string()];
+            if !sound {
+                args.push("--hint=boolean:suppress-sound:true".to_string());
+            }
+            // The text comes from model output; a heading like `-u critical`
+            // mustn't be read as an option
+            args.extend(["--".to_string(), title.to_string(), body.to_string()]);
+            Some(("notify-send", args))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let text = "# Morning brief\n\n## Top stories\n- **Rust 2.0** shipped\n- Postgres `18` is out\n";
+        let (heading, body) = summarize(text);
+        assert_eq!(heading.as_deref(), Some("Morning brief"));
+        assert_eq!(body, "Rust 2.0 shipped Postgres 18 is out");
+
+        let (_, body) = summarize(&"word ".repeat(100));
+        assert_eq!(body.chars().count(), MAX_BODY_CHARS);
+        assert!(body.ends_with('…'));
+    }
+
+    #[test]
+    fn test_command() {
+        let (program, args) = command("macos", "Brief \"today\"", "Body", true).unwrap();
+        assert_eq!(program, "osascript");
+        assert!(args[3].ends_with("sound name \"default\""));
+        assert_eq!(&args[6..], ["Brief \"today\"", "Body"]);
+
+        let (program, args) = command("linux", "Brief", "Body", false).unwrap();
+        assert_eq!(program, "notify-send");
+        assert_eq!(args, ["--app-name=Cthulu", "--hint=boolean:suppress-sound:true", "--", "Brief", "Body"]);
+        let (_, args) = command("linux", "-u critical", "--icon=x", true).unwrap();
+        assert_eq!(args, ["--app-name=Cthulu", "--", "-u critical", "--icon=x"]);
+
+        assert!(command("windows", "Brief", "Body", false).is_none());
+    }
+}
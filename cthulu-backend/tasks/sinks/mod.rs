@@ -1,4 +1,7 @@
+pub mod desktop;
+pub mod email;
 pub mod notion;
+pub mod read_later;
 pub mod slack;
 
 use anyhow::Result;
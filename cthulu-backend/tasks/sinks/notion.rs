@@ -5,6 +5,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 
 use super::Sink;
+use crate::egress::AuditedSend;
 
 const NOTION_API_VERSION: &str = "2022-06-28";
 const MAX_BLOCKS_PER_REQUEST: usize = 100;
@@ -66,7 +67,7 @@ impl Sink for NotionSink {
             .header("Authorization", format!("Bearer {}", self.token))
             .header("Notion-Version", NOTION_API_VERSION)
             .json(&body)
-            .send()
+            .send_audited()
             .await
             .context("failed to create Notion page")?;
 
@@ -99,7 +100,7 @@ impl Sink for NotionSink {
                 .header("Authorization", format!("Bearer {}", self.token))
                 .header("Notion-Version", NOTION_API_VERSION)
                 .json(&append_body)
-                .send()
+                .send_audited()
                 .await
                 .context("failed to append blocks to Notion page")?;
 
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::Sink;
+use crate::egress::AuditedSend;
+
+const POCKET_ADD_URL: &str = "https://getpocket.com/v3/add";
+const READWISE_SAVE_URL: &str = "https://readwise.io/api/v3/save/";
+
+// ---------------------------------------------------------------------------
+// ReadwiseSink
+// ---------------------------------------------------------------------------
+
+/// Saves every link found in the executor output to Readwise Reader.
+pub struct ReadwiseSink {
+    http_client: Arc<reqwest::Client>,
+    token: String,
+    tags: Vec<String>,
+}
+
+impl ReadwiseSink {
+    pub fn new(http_client: Arc<reqwest::Client>, token: String, tags: Vec<String>) -> Self {
+        Self {
+            http_client,
+            token,
+            tags,
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for ReadwiseSink {
+    async fn deliver(&self, text: &str) -> Result<()> {
+        let links = extract_links(text);
+        for url in &links {
+            let resp = self
+                .http_client
+                .post(READWISE_SAVE_URL)
+                .header("Authorization", format!("Token {}", self.token))
+                .json(&json!({ "url": url, "tags": &self.tags, "location": "later" }))
+                .send_audited()
+                .await
+                .context("failed to save link to Readwise")?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                anyhow::bail!("Readwise save returned {status} for {url}: {body}");
+            }
+        }
+
+        tracing::info!(count = links.len(), "Saved links to Readwise");
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PocketSink
+// ---------------------------------------------------------------------------
+
+/// Saves every link found in the executor output to Pocket.
+pub struct PocketSink {
+    http_client: Arc<reqwest::Client>,
+    consumer_key: String,
+    access_token: String,
+    tags: Vec<String>,
+}
+
+impl PocketSink {
+    pub fn new(
+        http_client: Arc<reqwest::Client>,
+        consumer_key: String,
+        access_token: String,
+        tags: Vec<String>,
+    ) -> Self {
+        Self {
+            http_client,
+            consumer_key,
+            access_token,
+            tags,
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for PocketSink {
+    async fn deliver(&self, text: &str) -> Result<()> {
+        let links = extract_links(text);
+        for url in &links {
+            let resp = self
+                .http_client
+                .post(POCKET_ADD_URL)
+                .header("X-Accept", "application/json")
+                .json(&json!({
+                    "url": url,
+                    "tags": self.tags.join(","),
+                    "consumer_key": &self.consumer_key,
+                    "access_token": &self.access_token,
+                }))
+                .send_audited()
+                .await
+                .context("failed to save link to Pocket")?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let error = resp
+                    .headers()
+                    .get("X-Error")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                anyhow::bail!("Pocket add returned {status} for {url}: {error}");
+            }
+        }
+
+        tracing::info!(count = links.len(), "Saved links to Pocket");
+        Ok(())
+    }
+}
+
+/// Collect the unique http(s) links in `text`, in order of appearance.
+/// Handles bare URLs, `<url>` autolinks, and markdown `[label](url)` links.
+fn extract_links(text: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, ')' | '>' | ']' | '"' | '\'' | '<'))
+            .unwrap_or(candidate.len());
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        if !links.iter().any(|l| l == url) {
+            links.push(url.to_string());
+        }
+        rest = &candidate[end..];
+    }
+
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_links_markdown_and_bare() {
+        let text = "## Weekly picks\n\
+            - [Rust 2024](https://blog.rust-lang.org/2024/edition) is out.\n\
+            - See https://example.com/post, and <https://example.org/a?b=c>.\n\
+            - Again: https://example.com/post";
+        assert_eq!(
+            extract_links(text),
+            vec![
+                "https://blog.rust-lang.org/2024/edition",
+                "https://example.com/post",
+                "https://example.org/a?b=c",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_links_none() {
+        assert!(extract_links("No links here, just http talk.").is_empty());
+    }
+}
@@ -16,6 +16,7 @@ pub enum Block {
     SectionFields { fields: Vec<TextObject> },
     Context { elements: Vec<ContextElement> },
     RichText { elements: Vec<RichTextElement> },
+    Actions { elements: Vec<Button> },
     Divider,
 }
 
@@ -52,6 +53,12 @@ impl Serialize for Block {
                 map.serialize_entry("elements", elements)?;
                 map.end()
             }
+            Block::Actions { elements } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("type", "actions")?;
+                map.serialize_entry("elements", elements)?;
+                map.end()
+            }
             Block::Divider => {
                 let mut map = serializer.serialize_map(Some(1))?;
                 map.serialize_entry("type", "divider")?;
@@ -68,6 +75,30 @@ pub struct TextObject {
     pub text: String,
 }
 
+// -- Actions block elements --
+
+/// An interactive button; clicks are sent to the app's interactivity URL
+/// with `action_id` and `value`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Button {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub text: TextObject,
+    pub action_id: String,
+    pub value: String,
+}
+
+impl Button {
+    pub fn new(label: &str, action_id: &str, value: String) -> Self {
+        Self {
+            kind: "button",
+            text: TextObject { kind: "plain_text", text: label.to_string() },
+            action_id: action_id.to_string(),
+            value,
+        }
+    }
+}
+
 // -- Context block elements --
 
 #[derive(Debug, Clone, Serialize)]
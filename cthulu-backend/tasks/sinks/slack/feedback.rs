@@ -0,0 +1,196 @@
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::blocks::*;
+use crate::tasks::feedback::{FeedbackStore, Vote};
+use crate::tasks::sources::ContentItem;
+
+pub const ACTION_UP: &str = "feedback_up";
+pub const ACTION_DOWN: &str = "feedback_down";
+
+/// Items per message: a section and an actions block each, plus the header,
+/// within Slack's 50-block limit.
+pub const MAX_ITEMS_PER_MESSAGE: usize = 24;
+
+/// Header carrying `v0=<hex HMAC-SHA256>` of `v0:{timestamp}:{body}`.
+pub const SIGNATURE_HEADER: &str = "x-slack-signature";
+pub const TIMESTAMP_HEADER: &str = "x-slack-request-timestamp";
+
+/// Requests signed longer ago than this are rejected as possible replays.
+const MAX_REQUEST_AGE_SECS: i64 = 300;
+
+const MAX_SUMMARY_CHARS: usize = 200;
+
+/// A digest as Block Kit: each item linked, with 👍/👎 buttons whose value
+/// identifies the flow and item ([`parse_actions`] reads them back).
+pub fn feedback_blocks(title: Option<&str>, flow_id: &str, items: &[ContentItem]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    if let Some(title) = title {
+        blocks.push(Block::Header {
+            text: TextObject {
+                kind: "plain_text",
+                text: title.chars().take(MAX_HEADER_LEN).collect(),
+            },
+        });
+    }
+    for item in items {
+        let mut text = if item.url.is_empty() {
+            format!("*{}*", item.title)
+        } else {
+            format!("*<{}|{}>*", item.url, item.title)
+        };
+        let summary = item.summary.trim();
+        if !summary.is_empty() {
+            let mut snippet: String = summary.chars().take(MAX_SUMMARY_CHARS).collect();
+            if summary.chars().count() > MAX_SUMMARY_CHARS {
+                snippet.push('…');
+            }
+            text.push('\n');
+            text.push_str(&snippet);
+        }
+        blocks.push(Block::Section { text: TextObject { kind: "mrkdwn", text } });
+
+        let value = format!("{flow_id}:{}", FeedbackStore::key(item));
+        blocks.push(Block::Actions {
+            elements: vec![
+                Button::new("👍", ACTION_UP, value.clone()),
+                Button::new("👎", ACTION_DOWN, value),
+            ],
+        });
+    }
+    blocks
+}
+
+/// A vote from a 👍/👎 button click.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedbackAction {
+    pub flow_id: String,
+    pub item_key: String,
+    pub vote: Vote,
+    /// Slack user ID of the voter.
+    pub user_id: String,
+}
+
+/// The feedback votes in a `block_actions` interaction payload; other
+/// actions are ignored.
+pub fn parse_actions(payload: &Value) -> Vec<FeedbackAction> {
+    if payload["type"] != "block_actions" {
+        return Vec::new();
+    }
+    let user_id = payload["user"]["id"].as_str().unwrap_or_default();
+    payload["actions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|action| {
+            let vote = match action["action_id"].as_str()? {
+                ACTION_UP => Vote::Up,
+                ACTION_DOWN => Vote::Down,
+                _ => return None,
+            };
+            let (flow_id, item_key) = action["value"].as_str()?.split_once(':')?;
+            Some(FeedbackAction {
+                flow_id: flow_id.to_string(),
+                item_key: item_key.to_string(),
+                vote,
+                user_id: user_id.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Check a request's `X-Slack-Signature` against the app's signing secret.
+/// `now` is the current Unix time; stale timestamps fail.
+pub fn verify_signature(
+    secret: &[u8],
+    timestamp: Option<&str>,
+    body: &[u8],
+    signature: Option<&str>,
+    now: i64,
+) -> bool {
+    let Some(ts) = timestamp.and_then(|t| t.parse::<i64>().ok()) else {
+        return false;
+    };
+    if (now - ts).abs() > MAX_REQUEST_AGE_SECS {
+        return false;
+    }
+    let Some(expected) = signature
+        .and_then(|s| s.strip_prefix("v0="))
+        .and_then(|s| hex::decode(s).ok())
+    else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret) else {
+        return false;
+    };
+    mac.update(format!("v0:{ts}:").as_bytes());
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, url: &str) -> ContentItem {
+        ContentItem {
+            title: title.to_string(),
+            url: url.to_string(),
+            summary: "x".repeat(250),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        }
+    }
+
+    #[test]
+    fn test_feedback_blocks_round_trip() {
+        let rust = item("Rust 2.0", "https://example.com/rust");
+        let blocks = feedback_blocks(Some("Morning Brief"), "flow-1", std::slice::from_ref(&rust));
+        assert_eq!(blocks.len(), 3);
+
+        let json = serde_json::to_value(&blocks).unwrap();
+        assert_eq!(json[0]["type"], "header");
+        assert!(json[1]["text"]["text"].as_str().unwrap().starts_with("*<https://example.com/rust|Rust 2.0>*\n"));
+        assert!(json[1]["text"]["text"].as_str().unwrap().ends_with('…'));
+        assert_eq!(json[2]["type"], "actions");
+        assert_eq!(json[2]["elements"][0]["type"], "button");
+        assert_eq!(json[2]["elements"][1]["action_id"], ACTION_DOWN);
+
+        let payload = serde_json::json!({
+            "type": "block_actions",
+            "user": { "id": "U123" },
+            "actions": [
+                { "action_id": ACTION_DOWN, "value": json[2]["elements"][1]["value"] },
+                { "action_id": "something_else", "value": "x:y" }
+            ]
+        });
+        assert_eq!(
+            parse_actions(&payload),
+            vec![FeedbackAction {
+                flow_id: "flow-1".into(),
+                item_key: FeedbackStore::key(&rust),
+                vote: Vote::Down,
+                user_id: "U123".into(),
+            }]
+        );
+        assert!(parse_actions(&serde_json::json!({ "type": "view_submission" })).is_empty());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        // Example from Slack's "Verifying requests from Slack" docs
+        let secret = b"8f742231b10e8888abcd99yyyzzz85a5";
+        let body = b"token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+        let sig = "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503";
+        let ts = 1531420618;
+
+        assert!(verify_signature(secret, Some("1531420618"), body, Some(sig), ts + 10));
+        assert!(!verify_signature(secret, Some("1531420618"), body, Some(sig), ts + 3600));
+        assert!(!verify_signature(b"other", Some("1531420618"), body, Some(sig), ts));
+        assert!(!verify_signature(secret, Some("1531420618"), b"tampered", Some(sig), ts));
+        assert!(!verify_signature(secret, None, body, Some(sig), ts));
+        assert!(!verify_signature(secret, Some("1531420618"), body, None, ts));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod blocks;
+pub mod feedback;
 pub mod markdown;
 
 #[cfg(test)]
@@ -14,6 +15,8 @@ use blocks::*;
 use markdown::markdown_to_blocks;
 
 use super::Sink;
+use crate::egress::AuditedSend;
+use crate::tasks::sources::ContentItem;
 
 // ---------------------------------------------------------------------------
 // SlackWebhookSink
@@ -41,22 +44,127 @@ impl Sink for SlackWebhookSink {
 // SlackApiSink
 // ---------------------------------------------------------------------------
 
+/// Where a [`SlackApiSink`] posts.
+pub enum SlackTarget {
+    /// A channel name or ID, e.g. `#briefs`.
+    Channel(String),
+    /// A direct message to the workspace member with this email address.
+    DirectMessage { user_email: String },
+}
+
 pub struct SlackApiSink {
     http_client: Arc<reqwest::Client>,
     bot_token: String,
-    channel: String,
+    target: SlackTarget,
 }
 
 impl SlackApiSink {
-    pub fn new(http_client: Arc<reqwest::Client>, bot_token: String, channel: String) -> Self {
-        Self { http_client, bot_token, channel }
+    pub fn new(http_client: Arc<reqwest::Client>, bot_token: String, target: SlackTarget) -> Self {
+        Self { http_client, bot_token, target }
+    }
+
+    async fn channel(&self) -> Result<String> {
+        match &self.target {
+            SlackTarget::Channel(channel) => Ok(channel.clone()),
+            SlackTarget::DirectMessage { user_email } => {
+                open_dm_by_email(&self.http_client, &self.bot_token, user_email).await
+            }
+        }
+    }
+
+    /// Post `items` with 👍/👎 buttons per item (see [`feedback::feedback_blocks`]).
+    /// Items beyond the first message's capacity go in thread replies.
+    pub async fn deliver_feedback_digest(
+        &self,
+        title: Option<&str>,
+        flow_id: &str,
+        items: &[ContentItem],
+    ) -> Result<()> {
+        let channel = self.channel().await?;
+        let mut chunks = items.chunks(feedback::MAX_ITEMS_PER_MESSAGE);
+        let Some(first) = chunks.next() else {
+            return Ok(());
+        };
+        let blocks = feedback::feedback_blocks(title, flow_id, first);
+        let ts = post_blocks(&self.http_client, &self.bot_token, &channel, &blocks, None)
+            .await
+            .context("failed to post feedback digest")?;
+        for chunk in chunks {
+            let blocks = feedback::feedback_blocks(None, flow_id, chunk);
+            post_blocks(&self.http_client, &self.bot_token, &channel, &blocks, Some(&ts))
+                .await
+                .context("failed to post feedback digest reply")?;
+        }
+
+        tracing::info!(items = items.len(), "Delivered feedback digest to Slack");
+        Ok(())
+    }
+
+    /// Upload a file to the target with an optional comment, via
+    /// `files.getUploadURLExternal` and `files.completeUploadExternal`.
+    /// Needs the `files:write` bot scope; channels must be given by ID.
+    pub async fn upload_file(
+        &self,
+        filename: &str,
+        title: &str,
+        bytes: Vec<u8>,
+        comment: Option<&str>,
+    ) -> Result<()> {
+        let channel = self.channel().await?;
+        let response = self
+            .http_client
+            .post("https://slack.com/api/files.getUploadURLExternal")
+            .header("Authorization", format!("Bearer {}", self.bot_token))
+            .form(&[("filename", filename.to_string()), ("length", bytes.len().to_string())])
+            .send_audited()
+            .await
+            .context("failed to call files.getUploadURLExternal")?;
+        let upload = parse_api_response("files.getUploadURLExternal", response).await?;
+        let upload_url = upload["upload_url"]
+            .as_str()
+            .context("Slack response missing upload_url field")?;
+        let file_id = upload["file_id"]
+            .as_str()
+            .context("Slack response missing file_id field")?;
+
+        let response = self
+            .http_client
+            .post(upload_url)
+            .body(bytes)
+            .send_audited()
+            .await
+            .context("failed to upload file to Slack")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Slack file upload returned {}", response.status());
+        }
+
+        let mut body = json!({
+            "files": [{ "id": file_id, "title": title }],
+            "channel_id": channel,
+        });
+        if let Some(comment) = comment {
+            body["initial_comment"] = json!(comment);
+        }
+        let response = self
+            .http_client
+            .post("https://slack.com/api/files.completeUploadExternal")
+            .header("Authorization", format!("Bearer {}", self.bot_token))
+            .json(&body)
+            .send_audited()
+            .await
+            .context("failed to call files.completeUploadExternal")?;
+        parse_api_response("files.completeUploadExternal", response).await?;
+
+        tracing::info!(filename, "Uploaded file to Slack");
+        Ok(())
     }
 }
 
 #[async_trait]
 impl Sink for SlackApiSink {
     async fn deliver(&self, text: &str) -> Result<()> {
-        post_threaded_blocks(&self.http_client, &self.bot_token, &self.channel, text).await
+        let channel = self.channel().await?;
+        post_threaded_blocks(&self.http_client, &self.bot_token, &channel, text).await
     }
 }
 
@@ -74,7 +182,7 @@ async fn post_to_url(
     let response = client
         .post(webhook_url)
         .json(&json!({ "text": slack_text }))
-        .send()
+        .send_audited()
         .await
         .context("failed to post to Slack webhook")?;
 
@@ -92,36 +200,40 @@ async fn post_to_url(
 // Web API (Block Kit + threading) path
 // ---------------------------------------------------------------------------
 
-/// Post a message with Block Kit formatting and optional threading.
-///
-/// If `full_text` contains a `---THREAD---` delimiter, the part above becomes
-/// the main channel message and the part below is posted as a thread reply.
+const THREAD_DELIMITER: &str = "---THREAD---";
+
+/// Split `full_text` on `---THREAD---`: the first part is the main channel
+/// message, each later non-empty part a separate thread reply (e.g. one per
+/// person in a standup).
+pub(crate) fn split_thread(full_text: &str) -> (&str, Vec<&str>) {
+    let mut parts = full_text.split(THREAD_DELIMITER).map(str::trim);
+    let main = parts.next().unwrap_or_default();
+    (main, parts.filter(|p| !p.is_empty()).collect())
+}
+
+/// Post a message with Block Kit formatting and optional threading; see
+/// [`split_thread`].
 async fn post_threaded_blocks(
     client: &reqwest::Client,
     bot_token: &str,
     channel: &str,
     full_text: &str,
 ) -> Result<()> {
-    let parts: Vec<&str> = full_text.splitn(2, "---THREAD---").collect();
-
-    let main_text = parts[0].trim();
-    let thread_text = parts.get(1).map(|s| s.trim());
+    let (main_text, replies) = split_thread(full_text);
 
     let main_blocks = markdown_to_blocks(main_text);
     let ts = post_blocks(client, bot_token, channel, &main_blocks, None)
         .await
         .context("failed to post main message")?;
 
-    if let Some(detail) = thread_text {
-        if !detail.is_empty() {
-            let thread_blocks = markdown_to_blocks(detail);
-            post_blocks(client, bot_token, channel, &thread_blocks, Some(&ts))
-                .await
-                .context("failed to post thread reply")?;
-        }
+    for reply in &replies {
+        let thread_blocks = markdown_to_blocks(reply);
+        post_blocks(client, bot_token, channel, &thread_blocks, Some(&ts))
+            .await
+            .context("failed to post thread reply")?;
     }
 
-    tracing::info!("Delivered Block Kit message to Slack");
+    tracing::info!(replies = replies.len(), "Delivered Block Kit message to Slack");
     Ok(())
 }
 
@@ -180,7 +292,7 @@ async fn post_blocks(
                 }
                 Some(parts.join("\n"))
             }
-            Block::Divider => None,
+            Block::Actions { .. } | Block::Divider => None,
         })
         .collect::<Vec<_>>()
         .join("\n");
@@ -199,10 +311,52 @@ async fn post_blocks(
         .post("https://slack.com/api/chat.postMessage")
         .header("Authorization", format!("Bearer {bot_token}"))
         .json(&body)
-        .send()
+        .send_audited()
         .await
         .context("failed to call chat.postMessage")?;
 
+    let resp_body = parse_api_response("chat.postMessage", response).await?;
+
+    resp_body["ts"]
+        .as_str()
+        .map(|s| s.to_string())
+        .context("Slack response missing ts field")
+}
+
+/// Resolve a workspace member by email (`users.lookupByEmail`) and open a DM
+/// with them (`conversations.open`). Returns the DM channel ID. Needs the
+/// `users:read.email` and `im:write` bot scopes.
+async fn open_dm_by_email(client: &reqwest::Client, bot_token: &str, email: &str) -> Result<String> {
+    let response = client
+        .get("https://slack.com/api/users.lookupByEmail")
+        .header("Authorization", format!("Bearer {bot_token}"))
+        .query(&[("email", email)])
+        .send_audited()
+        .await
+        .context("failed to call users.lookupByEmail")?;
+    let user = parse_api_response("users.lookupByEmail", response)
+        .await
+        .with_context(|| format!("failed to find Slack user {email}"))?;
+    let user_id = user["user"]["id"]
+        .as_str()
+        .context("Slack response missing user.id field")?;
+
+    let response = client
+        .post("https://slack.com/api/conversations.open")
+        .header("Authorization", format!("Bearer {bot_token}"))
+        .json(&json!({ "users": user_id }))
+        .send_audited()
+        .await
+        .context("failed to call conversations.open")?;
+    let conversation = parse_api_response("conversations.open", response).await?;
+    conversation["channel"]["id"]
+        .as_str()
+        .map(|s| s.to_string())
+        .context("Slack response missing channel.id field")
+}
+
+/// Parse a Web API response, failing on a non-2xx status or `"ok": false`.
+async fn parse_api_response(method: &str, response: reqwest::Response) -> Result<serde_json::Value> {
     let status = response.status();
     let resp_body: serde_json::Value = response
         .json()
@@ -211,13 +365,9 @@ async fn post_blocks(
 
     if !status.is_success() || resp_body["ok"].as_bool() != Some(true) {
         let err = resp_body["error"].as_str().unwrap_or("unknown error");
-        anyhow::bail!("chat.postMessage failed ({status}): {err}");
+        anyhow::bail!("{method} failed ({status}): {err}");
     }
-
-    resp_body["ts"]
-        .as_str()
-        .map(|s| s.to_string())
-        .context("Slack response missing ts field")
+    Ok(resp_body)
 }
 
 /// Extract plain text from a slice of rich text inlines.
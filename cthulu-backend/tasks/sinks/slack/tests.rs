@@ -1,5 +1,6 @@
 use super::blocks::*;
 use super::markdown::*;
+use super::split_thread;
 
 // --- Webhook (mrkdwn) tests ---
 
@@ -388,3 +389,18 @@ fn test_stats_block_surrounded_by_content() {
     assert!(matches!(&blocks[1], Block::RichText { .. }));
     assert!(matches!(&blocks[2], Block::SectionFields { .. }));
 }
+
+// --- Threading ---
+
+#[test]
+fn test_split_thread() {
+    assert_eq!(split_thread("just a message"), ("just a message", vec![]));
+    assert_eq!(
+        split_thread("Summary\n---THREAD---\nDetails\n"),
+        ("Summary", vec!["Details"])
+    );
+    assert_eq!(
+        split_thread("Standup\n---THREAD---\n*alice*\n---THREAD---\n\n---THREAD---\n*bob*"),
+        ("Standup", vec!["*alice*", "*bob*"])
+    );
+}
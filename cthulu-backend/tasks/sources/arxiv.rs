@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+
+use super::ContentItem;
+use crate::egress::AuditedSend;
+
+const ARXIV_API_URL: &str = "https://export.arxiv.org/api/query";
+
+/// Fetch the newest arXiv papers matching any of `categories` (e.g. `cs.CL`)
+/// and any of `keywords`. Abstracts are kept whole.
+pub async fn fetch_papers(
+    client: &reqwest::Client,
+    categories: &[String],
+    keywords: &[String],
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let query = build_query(categories, keywords)?;
+
+    let bytes = client
+        .get(ARXIV_API_URL)
+        .query(&[
+            ("search_query", query.as_str()),
+            ("sortBy", "submittedDate"),
+            ("sortOrder", "descending"),
+            ("max_results", &limit.to_string()),
+        ])
+        .timeout(std::time::Duration::from_secs(30))
+        .send_audited()
+        .await
+        .context("arXiv request failed")?
+        .error_for_status()
+        .context("arXiv API returned error status")?
+        .bytes()
+        .await
+        .context("failed to read arXiv response")?;
+
+    parse_papers(&bytes, limit)
+}
+
+/// `(cat:a OR cat:b) AND (all:"x" OR all:"y")`, omitting empty groups.
+fn build_query(categories: &[String], keywords: &[String]) -> Result<String> {
+    let group = |terms: Vec<String>| match terms.len() {
+        0 => None,
+        1 => terms.into_iter().next(),
+        _ => Some(format!("({})", terms.join(" OR "))),
+    };
+
+    let cats = group(categories.iter().map(|c| format!("cat:{}", c.trim())).collect());
+    let kws = group(
+        keywords
+            .iter()
+            .map(|k| format!("all:\"{}\"", k.trim().replace('"', "")))
+            .collect(),
+    );
+
+    match (cats, kws) {
+        (Some(c), Some(k)) => Ok(format!("{c} AND {k}")),
+        (Some(q), None) | (None, Some(q)) => Ok(q),
+        (None, None) => anyhow::bail!("arxiv source needs at least one category or keyword"),
+    }
+}
+
+fn parse_papers(bytes: &[u8], limit: usize) -> Result<Vec<ContentItem>> {
+    let feed = feed_rs::parser::parse(bytes).context("failed to parse arXiv Atom feed")?;
+
+    Ok(feed
+        .entries
+        .into_iter()
+        .take(limit)
+        .map(|entry| {
+            let title = collapse_whitespace(&entry.title.map(|t| t.content).unwrap_or_default());
+            let url = entry
+                .links
+                .iter()
+                .find(|l| l.rel.as_deref() == Some("alternate"))
+                .map(|l| l.href.clone())
+                .unwrap_or(entry.id);
+            let authors: Vec<&str> = entry.authors.iter().map(|a| a.name.as_str()).collect();
+            let categories: Vec<&str> = entry.categories.iter().map(|c| c.term.as_str()).collect();
+            let abstract_text =
+                collapse_whitespace(&entry.summary.map(|s| s.content).unwrap_or_default());
+
+            ContentItem {
+                title,
+                url,
+                summary: format!(
+                    "Authors: {}. Categories: {}.\n   {abstract_text}",
+                    authors.join(", "),
+                    categories.join(", ")
+                ),
+                published: entry.published.or(entry.updated),
+                image_url: None,
+                full_summary: true,
+            }
+        })
+        .collect())
+}
+
+/// arXiv hard-wraps titles and abstracts; fold them back into one line.
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:arxiv="http://arxiv.org/schemas/atom">
+  <title>ArXiv Query</title>
+  <id>http://arxiv.org/api/abc</id>
+  <updated>2024-06-02T00:00:00-04:00</updated>
+  <entry>
+    <id>http://arxiv.org/abs/2406.00001v1</id>
+    <updated>2024-06-01T17:59:59Z</updated>
+    <published>2024-06-01T17:59:59Z</published>
+    <title>Sparse Attention for
+      Long Contexts</title>
+    <summary>  We study sparse attention.
+      It scales to one million tokens.
+    </summary>
+    <author><name>Ada Lovelace</name></author>
+    <author><name>Alan Turing</name></author>
+    <link href="http://arxiv.org/abs/2406.00001v1" rel="alternate" type="text/html"/>
+    <link title="pdf" href="http://arxiv.org/pdf/2406.00001v1" rel="related" type="application/pdf"/>
+    <category term="cs.CL" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="cs.LG" scheme="http://arxiv.org/schemas/atom"/>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_build_query() {
+        let cats = vec!["cs.CL".to_string(), "cs.LG".to_string()];
+        let kws = vec!["sparse attention".to_string()];
+        assert_eq!(
+            build_query(&cats, &kws).unwrap(),
+            r#"(cat:cs.CL OR cat:cs.LG) AND all:"sparse attention""#
+        );
+        assert_eq!(build_query(&cats[..1], &[]).unwrap(), "cat:cs.CL");
+        assert!(build_query(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_papers() {
+        let items = parse_papers(FIXTURE.as_bytes(), 10).unwrap();
+        assert_eq!(items.len(), 1);
+        let paper = &items[0];
+        assert_eq!(paper.title, "Sparse Attention for Long Contexts");
+        assert_eq!(paper.url, "http://arxiv.org/abs/2406.00001v1");
+        assert_eq!(
+            paper.summary,
+            "Authors: Ada Lovelace, Alan Turing. Categories: cs.CL, cs.LG.\n   \
+             We study sparse attention. It scales to one million tokens."
+        );
+        assert!(paper.full_summary);
+        assert!(paper.published.is_some());
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::header::SET_COOKIE;
+use serde::Deserialize;
+
+use crate::egress::AuditedSend;
+use crate::identity::{Integration, user_agent};
+
+/// A form login performed before scraping. Session cookies set by the
+/// response are sent with every page fetch. The login request itself is
+/// always made from the host, even for sandboxed sources.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginStep {
+    pub url: String,
+    /// Literal form fields (e.g. `username`).
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    /// Form fields read from env vars, keyed by field name (e.g. `password: DASH_PASSWORD`).
+    #[serde(default)]
+    pub fields_env: BTreeMap<String, String>,
+}
+
+/// Build the `Cookie` header for an authenticated scrape from a cookie string
+/// held in `cookies_env` and/or a login step. Returns `None` when neither is set.
+pub async fn session_cookie(
+    cookies_env: Option<&str>,
+    login: Option<&LoginStep>,
+) -> Result<Option<String>> {
+    let mut parts = Vec::new();
+    if let Some(env) = cookies_env {
+        let raw = crate::secrets::var(env).with_context(|| format!("cookie env var {env} is not set"))?;
+        parts.extend(split_cookie_header(&raw));
+    }
+    if let Some(login) = login {
+        parts.extend(login_cookies(login).await?);
+    }
+    Ok((!parts.is_empty()).then(|| parts.join("; ")))
+}
+
+async fn login_cookies(login: &LoginStep) -> Result<Vec<String>> {
+    let mut form = login.fields.clone();
+    for (field, env) in &login.fields_env {
+        let value = crate::secrets::var(env)
+            .with_context(|| format!("login field '{field}' requires env var {env} but it is not set"))?;
+        form.insert(field.clone(), value);
+    }
+
+    // Logins usually answer with a redirect; the session cookie is on that
+    // response, so don't follow it.
+    let client = reqwest::Client::builder()
+        .user_agent(user_agent(Integration::Http))
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("failed to build login client")?;
+
+    let resp = client
+        .post(&login.url)
+        .form(&form)
+        .send_audited()
+        .await
+        .with_context(|| format!("login request to {} failed", login.url))?;
+
+    let status = resp.status();
+    if !status.is_success() && !status.is_redirection() {
+        anyhow::bail!("login to {} returned {status}", login.url);
+    }
+
+    let cookies = cookies_from_set_cookie(
+        resp.headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok()),
+    );
+    if cookies.is_empty() {
+        anyhow::bail!("login to {} did not set any cookies", login.url);
+    }
+    Ok(cookies)
+}
+
+/// Keep only the `name=value` pair of each `Set-Cookie` header.
+fn cookies_from_set_cookie<'a>(headers: impl Iterator<Item = &'a str>) -> Vec<String> {
+    headers
+        .filter_map(|h| h.split(';').next())
+        .map(str::trim)
+        .filter(|pair| pair.contains('='))
+        .map(String::from)
+        .collect()
+}
+
+fn split_cookie_header(raw: &str) -> Vec<String> {
+    raw.split(';')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookies_from_set_cookie() {
+        let headers = [
+            "session=abc123; Path=/; HttpOnly; Secure",
+            "csrftoken=xyz; Max-Age=3600",
+            "garbage",
+        ];
+        assert_eq!(
+            cookies_from_set_cookie(headers.into_iter()),
+            vec!["session=abc123", "csrftoken=xyz"]
+        );
+    }
+
+    #[test]
+    fn test_split_cookie_header() {
+        assert_eq!(split_cookie_header(" a=1; b=2 ;"), vec!["a=1", "b=2"]);
+    }
+
+    #[test]
+    fn test_login_step_deserialize() {
+        let step: LoginStep = serde_json::from_value(serde_json::json!({
+            "url": "https://dash.example.com/login",
+            "fields": { "username": "bot" },
+            "fields_env": { "password": "DASH_PASSWORD" }
+        }))
+        .unwrap();
+        assert_eq!(step.fields["username"], "bot");
+        assert_eq!(step.fields_env["password"], "DASH_PASSWORD");
+    }
+
+    #[tokio::test]
+    async fn test_session_cookie_none() {
+        assert!(session_cookie(None, None).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,302 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::egress::AuditedSend;
+
+/// Default chromedriver listen address.
+pub const DEFAULT_WEBDRIVER_URL: &str = "http://localhost:9515";
+
+/// Element reference key defined by the W3C WebDriver spec.
+const ELEMENT_KEY: &str = "element-6066-11e4-a52e-4f735466cecf";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A declarative interaction performed before extraction.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BrowserStep {
+    Click {
+        selector: String,
+    },
+    Fill {
+        selector: String,
+        value: String,
+    },
+    WaitFor {
+        selector: String,
+        #[serde(default = "default_wait_ms")]
+        timeout_ms: u64,
+    },
+    Sleep {
+        ms: u64,
+    },
+}
+
+fn default_wait_ms() -> u64 {
+    10_000
+}
+
+/// Parse a node's `steps` array. Returns an error naming the first bad step.
+pub fn parse_steps(value: &Value) -> Result<Vec<BrowserStep>> {
+    let Some(arr) = value.as_array() else {
+        return Ok(Vec::new());
+    };
+    arr.iter()
+        .enumerate()
+        .map(|(i, v)| {
+            serde_json::from_value(v.clone()).with_context(|| format!("invalid browser step #{}", i + 1))
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct WdResponse {
+    value: Value,
+}
+
+/// Minimal W3C WebDriver client (chromedriver / geckodriver) over HTTP.
+///
+/// Only the handful of commands the scraping sources need are implemented.
+/// Sessions are headless and must be closed with [`WebDriverSession::close`].
+pub struct WebDriverSession<'a> {
+    client: &'a reqwest::Client,
+    base: String,
+    session_id: String,
+}
+
+impl<'a> WebDriverSession<'a> {
+    pub async fn start(client: &'a reqwest::Client, webdriver_url: &str) -> Result<Self> {
+        let base = webdriver_url.trim_end_matches('/').to_string();
+        let caps = json!({
+            "capabilities": {
+                "alwaysMatch": {
+                    "goog:chromeOptions": { "args": ["--headless=new", "--disable-gpu"] },
+                    "moz:firefoxOptions": { "args": ["-headless"] }
+                }
+            }
+        });
+        let value = send(client.post(format!("{base}/session")).json(&caps))
+            .await
+            .context("failed to start WebDriver session")?;
+        let session_id = value["sessionId"]
+            .as_str()
+            .context("WebDriver did not return a sessionId")?
+            .to_string();
+        Ok(Self {
+            client,
+            base,
+            session_id,
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/session/{}{path}", self.base, self.session_id)
+    }
+
+    pub async fn navigate(&self, url: &str) -> Result<()> {
+        send(self.client.post(self.url("/url")).json(&json!({ "url": url })))
+            .await
+            .with_context(|| format!("failed to navigate to {url}"))?;
+        Ok(())
+    }
+
+    async fn find(&self, selector: &str) -> Result<String> {
+        let value = send(
+            self.client
+                .post(self.url("/element"))
+                .json(&json!({ "using": "css selector", "value": selector })),
+        )
+        .await
+        .with_context(|| format!("element not found: {selector}"))?;
+        value[ELEMENT_KEY]
+            .as_str()
+            .map(String::from)
+            .with_context(|| format!("WebDriver returned no element id for {selector}"))
+    }
+
+    /// Poll until `selector` matches an element or `timeout` elapses.
+    pub async fn wait_for(&self, selector: &str, timeout: Duration) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if self.find(selector).await.is_ok() {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!("timed out after {}ms waiting for {selector}", timeout.as_millis());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    pub async fn run_step(&self, step: &BrowserStep) -> Result<()> {
+        match step {
+            BrowserStep::Click { selector } => {
+                let id = self.find(selector).await?;
+                send(self.client.post(self.url(&format!("/element/{id}/click"))).json(&json!({})))
+                    .await
+                    .with_context(|| format!("failed to click {selector}"))?;
+            }
+            BrowserStep::Fill { selector, value } => {
+                let id = self.find(selector).await?;
+                send(self.client.post(self.url(&format!("/element/{id}/clear"))).json(&json!({})))
+                    .await
+                    .with_context(|| format!("failed to clear {selector}"))?;
+                send(
+                    self.client
+                        .post(self.url(&format!("/element/{id}/value")))
+                        .json(&json!({ "text": value })),
+                )
+                .await
+                .with_context(|| format!("failed to fill {selector}"))?;
+            }
+            BrowserStep::WaitFor {
+                selector,
+                timeout_ms,
+            } => {
+                self.wait_for(selector, Duration::from_millis(*timeout_ms)).await?;
+            }
+            BrowserStep::Sleep { ms } => {
+                tokio::time::sleep(Duration::from_millis(*ms)).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set a cookie for the domain of the currently loaded page.
+    pub async fn add_cookie(&self, name: &str, value: &str) -> Result<()> {
+        send(
+            self.client
+                .post(self.url("/cookie"))
+                .json(&json!({ "cookie": { "name": name, "value": value } })),
+        )
+        .await
+        .with_context(|| format!("failed to set cookie {name}"))?;
+        Ok(())
+    }
+
+    /// The current DOM serialized as HTML (after scripts have run).
+    pub async fn page_source(&self) -> Result<String> {
+        let value = send(self.client.get(self.url("/source")))
+            .await
+            .context("failed to read page source")?;
+        value
+            .as_str()
+            .map(String::from)
+            .context("WebDriver returned non-string page source")
+    }
+
+    /// End the browser session. Errors are logged, not returned.
+    pub async fn close(self) {
+        let url = format!("{}/session/{}", self.base, self.session_id);
+        if let Err(e) = self.client.delete(url).send_audited().await {
+            tracing::warn!(error = %e, "failed to close WebDriver session");
+        }
+    }
+}
+
+async fn send(req: reqwest::RequestBuilder) -> Result<Value> {
+    let resp = req
+        .timeout(Duration::from_secs(60))
+        .send_audited()
+        .await
+        .context("WebDriver request failed")?;
+    let status = resp.status();
+    if !status.is_success() {
+        // WebDriver errors are JSON too, but a proxy's may not be
+        let text = resp.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<WdResponse>(&text)
+            .ok()
+            .and_then(|body| {
+                let value = body.value;
+                value["message"].as_str().or_else(|| value["error"].as_str()).map(String::from)
+            })
+            .unwrap_or(text);
+        anyhow::bail!("WebDriver returned {status}: {}", message.trim());
+    }
+    let body: WdResponse = resp
+        .json()
+        .await
+        .context("failed to parse WebDriver response")?;
+    Ok(body.value)
+}
+
+/// Load `url` in a fresh headless session, run `steps` in order, and return
+/// the rendered HTML. The session is always closed, even on failure.
+///
+/// `cookie` is a `Cookie` header value; WebDriver can only set cookies for
+/// the loaded domain, so the page is loaded once, cookies set, then reloaded.
+pub async fn render_page(
+    client: &reqwest::Client,
+    webdriver_url: &str,
+    url: &str,
+    steps: &[BrowserStep],
+    cookie: Option<&str>,
+) -> Result<String> {
+    let session = WebDriverSession::start(client, webdriver_url).await?;
+    let result = async {
+        session.navigate(url).await?;
+        if let Some(cookie) = cookie {
+            for (name, value) in cookie_pairs(cookie) {
+                session.add_cookie(name, value).await?;
+            }
+            session.navigate(url).await?;
+        }
+        for (i, step) in steps.iter().enumerate() {
+            session
+                .run_step(step)
+                .await
+                .with_context(|| format!("browser step #{} failed", i + 1))?;
+        }
+        session.page_source().await
+    }
+    .await;
+    session.close().await;
+    result
+}
+
+fn cookie_pairs(cookie: &str) -> impl Iterator<Item = (&str, &str)> {
+    cookie
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_steps() {
+        let value = json!([
+            { "action": "fill", "selector": "input[name=q]", "value": "rust" },
+            { "action": "click", "selector": "button[type=submit]" },
+            { "action": "wait_for", "selector": ".results" },
+            { "action": "sleep", "ms": 500 }
+        ]);
+        let steps = parse_steps(&value).unwrap();
+        assert_eq!(steps.len(), 4);
+        assert!(matches!(&steps[0], BrowserStep::Fill { value, .. } if value == "rust"));
+        assert!(matches!(&steps[2], BrowserStep::WaitFor { timeout_ms: 10_000, .. }));
+        assert!(matches!(&steps[3], BrowserStep::Sleep { ms: 500 }));
+    }
+
+    #[test]
+    fn test_parse_steps_missing_is_empty() {
+        assert!(parse_steps(&Value::Null).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_steps_rejects_unknown_action() {
+        let value = json!([{ "action": "hover", "selector": "a" }]);
+        let err = parse_steps(&value).unwrap_err();
+        assert!(err.to_string().contains("step #1"));
+    }
+
+    #[test]
+    fn test_cookie_pairs() {
+        let pairs: Vec<_> = cookie_pairs("session=abc; csrf=x=y ; junk").collect();
+        assert_eq!(pairs, vec![("session", "abc"), ("csrf", "x=y")]);
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::ContentItem;
+use crate::tasks::cache::TtlCache;
+
+/// On-disk cache of source fetches, keyed by a hash of the node's kind and
+/// config, so re-running a flow while iterating on its prompt doesn't scrape
+/// the same slow sites again.
+///
+/// Opt-in per node via `cache_ttl_secs`. Entries live under
+/// `~/.cthulu/cache/source/{key}.json` and expire after the node's TTL.
+#[derive(Clone)]
+pub struct SourceCache {
+    entries: TtlCache<CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    items: Vec<ContentItem>,
+}
+
+impl SourceCache {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            entries: TtlCache::new(data_dir.join("cache").join("source")),
+        }
+    }
+
+    /// `since` is the previous run's start for sources that fetch "since
+    /// last run", whose results change with it. The TTL itself is left out,
+    /// so changing it keeps the entry.
+    pub fn key(kind: &str, config: &Value, since: Option<DateTime<Utc>>) -> String {
+        let mut config = config.clone();
+        if let Some(map) = config.as_object_mut() {
+            map.remove("cache_ttl_secs");
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(kind.as_bytes());
+        hasher.update([0]);
+        hasher.update(config.to_string().as_bytes());
+        hasher.update([0]);
+        if let Some(since) = since {
+            hasher.update(since.to_rfc3339().as_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Return the cached items if present and younger than `ttl`.
+    pub async fn get(&self, key: &str, ttl: Duration) -> Option<Vec<ContentItem>> {
+        self.entries.get(key, ttl).await.map(|entry| entry.items)
+    }
+
+    pub async fn put(&self, key: &str, ttl: Duration, items: &[ContentItem]) -> Result<()> {
+        self.entries.put(key, ttl, CacheEntry { items: items.to_vec() }).await
+    }
+
+    /// Delete expired entries, returning how many went.
+    pub async fn prune(&self) -> usize {
+        self.entries.prune().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_key_ignores_ttl() {
+        let config = json!({ "url": "https://news.ycombinator.com", "cache_ttl_secs": 600 });
+        let key = SourceCache::key("web-scrape", &config, None);
+        assert_eq!(key, SourceCache::key("web-scrape", &json!({ "url": "https://news.ycombinator.com" }), None));
+        assert_ne!(key, SourceCache::key("rss", &config, None));
+        assert_ne!(key, SourceCache::key("web-scrape", &json!({ "url": "https://lobste.rs" }), None));
+        assert_ne!(key, SourceCache::key("web-scrape", &config, Some(Utc::now())));
+    }
+
+    #[tokio::test]
+    async fn test_put_then_get() {
+        let dir = tempdir().unwrap();
+        let cache = SourceCache::new(dir.path());
+        let key = SourceCache::key("rss", &json!({ "url": "https://example.com/feed" }), None);
+        let item = ContentItem {
+            title: "Launch".to_string(),
+            url: "https://example.com/launch".to_string(),
+            summary: String::new(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        };
+
+        let ttl = Duration::from_secs(60);
+        assert!(cache.get(&key, ttl).await.is_none());
+        cache.put(&key, ttl, &[item]).await.unwrap();
+        let hit = cache.get(&key, ttl).await.unwrap();
+        assert_eq!(hit[0].title, "Launch");
+        assert!(cache.get(&key, Duration::ZERO).await.is_none());
+    }
+}
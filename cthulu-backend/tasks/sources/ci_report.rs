@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use super::ContentItem;
+use crate::egress::AuditedSend;
+use crate::platform::expand_home;
+use crate::tasks::reports::{self, ReportFormat};
+
+/// Fetch a CI artifact — a local path/glob (newest match wins) or an http(s)
+/// URL — and parse it as `format` into a headline item plus up to `limit`
+/// per-file or per-finding items.
+pub async fn fetch_report(
+    client: &reqwest::Client,
+    path: &str,
+    format: ReportFormat,
+    token: Option<&str>,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let (text, location) = if path.starts_with("http://") || path.starts_with("https://") {
+        let mut req = client.get(path).timeout(std::time::Duration::from_secs(30));
+        if let Some(token) = token {
+            req = req.bearer_auth(token);
+        }
+        let text = req
+            .send_audited()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("failed to download report: {path}"))?
+            .text()
+            .await
+            .context("failed to read report body")?;
+        (text, path.to_string())
+    } else {
+        let file = newest_match(path)?;
+        let text = tokio::fs::read_to_string(&file)
+            .await
+            .with_context(|| format!("failed to read report: {}", file.display()))?;
+        (text, file.display().to_string())
+    };
+
+    let report = reports::parse(format, &text).with_context(|| format!("failed to parse {location}"))?;
+    Ok(report.to_items(format, &location, limit))
+}
+
+fn newest_match(pattern: &str) -> Result<PathBuf> {
+    let pattern = expand_home(pattern);
+    glob::glob(&pattern)
+        .with_context(|| format!("invalid report path: {pattern}"))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|path| {
+            let mtime = std::fs::metadata(&path).ok()?.modified().ok()?;
+            Some((path, mtime))
+        })
+        .max_by_key(|(_, mtime)| *mtime)
+        .map(|(path, _)| path)
+        .with_context(|| format!("no report found matching {pattern}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_fetch_local_report() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lcov.info"),
+            "SF:src/lib.rs\nLF:10\nLH:4\nend_of_record\n",
+        )
+        .unwrap();
+
+        let pattern = format!("{}/*.info", dir.path().display());
+        let client = reqwest::Client::new();
+        let items = fetch_report(&client, &pattern, ReportFormat::Lcov, None, 10).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Coverage: 40.0% of lines (4/10)");
+        assert!(items[0].url.ends_with("lcov.info"));
+        assert_eq!(items[1].title, "src/lib.rs — 40.0% covered");
+
+        let missing = format!("{}/*.json", dir.path().display());
+        assert!(fetch_report(&client, &missing, ReportFormat::Eslint, None, 10).await.is_err());
+    }
+}
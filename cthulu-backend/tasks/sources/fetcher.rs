@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use scraper::{Html, Selector};
+
+use crate::egress::AuditedSend;
+use crate::identity::{self, Integration};
+use crate::sandbox::provider::SandboxProvider;
+use crate::sandbox::types::*;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Headless Chromium in the sandbox image.
+const CHROMIUM_BIN: &str = "chromium";
+
+/// How long scripts get to build a rendered page, in the browser's virtual
+/// time (it skips ahead while idle, so this is rarely spent in full).
+const RENDER_BUDGET: Duration = Duration::from_secs(10);
+
+/// Redirects a sandboxed fetch follows before giving up.
+const MAX_REDIRECTS: usize = 5;
+
+/// Where page fetches for scraping sources are performed.
+///
+/// `Host` uses the shared reqwest client. `Sandbox` provisions a throwaway
+/// sandbox per fetch, allowlists only the target host, and runs the download
+/// inside it so untrusted pages never touch the host network stack directly.
+/// `Rendered` loads the page in headless Chromium inside such a sandbox, so
+/// JS-built content is present, and checks that `wait_for` showed up.
+#[derive(Clone, Copy)]
+pub enum PageFetcher<'a> {
+    Host(&'a reqwest::Client),
+    Sandbox(&'a Arc<dyn SandboxProvider>),
+    Rendered {
+        provider: &'a Arc<dyn SandboxProvider>,
+        wait_for: Option<&'a str>,
+    },
+}
+
+impl PageFetcher<'_> {
+    /// Fetch a page body as text. `cookie` is sent verbatim as the `Cookie` header.
+    pub async fn get_html(
+        &self,
+        url: &str,
+        user_agent: Option<&str>,
+        cookie: Option<&str>,
+    ) -> Result<String> {
+        match self {
+            PageFetcher::Host(client) => {
+                let mut req = client.get(url).timeout(FETCH_TIMEOUT);
+                if let Some(ua) = user_agent {
+                    req = req.header("User-Agent", ua);
+                }
+                if let Some(cookie) = cookie {
+                    req = req.header("Cookie", cookie);
+                }
+                req.send_audited()
+                    .await
+                    .context("failed to fetch page")?
+                    .error_for_status()
+                    .with_context(|| format!("page returned error status: {url}"))?
+                    .text()
+                    .await
+                    .context("failed to read page body")
+            }
+            PageFetcher::Sandbox(provider) => {
+                let user_agent = user_agent.unwrap_or(identity::user_agent(Integration::Http));
+                // curl reports redirects instead of following them, so each
+                // hop is checked against the host the source named.
+                let mut current = url.to_string();
+                for _ in 0..=MAX_REDIRECTS {
+                    let (body, location) =
+                        fetch_in_sandbox(provider, &current, curl_request(&current, Some(user_agent), cookie)).await?;
+                    let location = location.trim();
+                    if location.is_empty() {
+                        return Ok(body);
+                    }
+                    check_redirect(url, location)?;
+                    current = location.to_string();
+                }
+                anyhow::bail!("more than {MAX_REDIRECTS} redirects: {url}")
+            }
+            PageFetcher::Rendered { provider, wait_for } => {
+                if cookie.is_some() {
+                    anyhow::bail!("render can't send session cookies: the sandboxed browser has no way to set them");
+                }
+                let user_agent = user_agent.unwrap_or(identity::user_agent(Integration::Http));
+                let (html, _) = fetch_in_sandbox(provider, url, chromium_request(url, user_agent)).await?;
+                if let Some(selector) = wait_for {
+                    let sel = Selector::parse(selector)
+                        .map_err(|e| anyhow::anyhow!("invalid wait_for_selector '{selector}': {e:?}"))?;
+                    if Html::parse_document(&html).select(&sel).next().is_none() {
+                        anyhow::bail!("{selector} never appeared in the rendered page: {url}");
+                    }
+                }
+                Ok(html)
+            }
+        }
+    }
+}
+
+/// A redirect from a fetch of `url` may only stay on its host, and can't
+/// drop from https to http.
+fn check_redirect(url: &str, location: &str) -> Result<()> {
+    let from = reqwest::Url::parse(url).with_context(|| format!("invalid url: {url}"))?;
+    let to = reqwest::Url::parse(location).with_context(|| format!("invalid redirect from {url}: {location}"))?;
+    if to.host_str() != from.host_str() {
+        anyhow::bail!("{url} redirects to another host: {location}");
+    }
+    if from.scheme() == "https" && to.scheme() != "https" {
+        anyhow::bail!("{url} redirects off https: {location}");
+    }
+    Ok(())
+}
+
+/// Run `request` (which prints the page on stdout) in a fresh sandbox whose
+/// network reaches only `url`'s host. Returns its stdout and stderr.
+async fn fetch_in_sandbox(
+    provider: &Arc<dyn SandboxProvider>,
+    url: &str,
+    request: ExecRequest,
+) -> Result<(String, String)> {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(String::from))
+        .with_context(|| format!("invalid url for sandboxed fetch: {url}"))?;
+
+    let spec = SandboxSpec {
+        workspace_id: format!("fetch-{}", uuid::Uuid::new_v4()),
+        profile: SandboxProfile::Base,
+        filesystem: FilesystemSpec::default(),
+        resources: ResourceHints::default(),
+        env: BTreeMap::new(),
+        mounts: vec![],
+        network: NetworkPolicy {
+            mode: NetworkMode::Allowlist,
+            allowed_hosts: vec![host],
+            blocked_hosts: vec![],
+            allow_loopback: false,
+        },
+        lifecycle: LifecyclePolicy::default(),
+        labels: BTreeMap::from([("purpose".into(), "source-fetch".into())]),
+    };
+
+    let handle = provider
+        .provision(spec)
+        .await
+        .map_err(|e| anyhow::anyhow!("sandbox provision failed: {e}"))?;
+
+    let result = handle.exec(request).await;
+    // Each fetch gets a fresh sandbox, so always tear it down.
+    let _ = handle.destroy().await;
+    let result = result.map_err(|e| anyhow::anyhow!("sandbox exec failed: {e}"))?;
+
+    if result.timed_out {
+        anyhow::bail!("sandboxed fetch timed out after {}s: {url}", FETCH_TIMEOUT.as_secs());
+    }
+    match result.exit_code {
+        Some(0) => Ok((
+            String::from_utf8_lossy(&result.stdout).into_owned(),
+            String::from_utf8_lossy(&result.stderr).into_owned(),
+        )),
+        code => {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            anyhow::bail!("sandboxed fetch of {url} failed (exit {code:?}): {}", stderr.trim())
+        }
+    }
+}
+
+/// curl printing the page on stdout, and on stderr where it redirects to
+/// (nothing when it doesn't) rather than following it.
+fn curl_request(url: &str, user_agent: Option<&str>, cookie: Option<&str>) -> ExecRequest {
+    let mut command = vec![
+        "curl".to_string(),
+        "--silent".to_string(),
+        "--show-error".to_string(),
+        "--fail".to_string(),
+        "--max-redirs".to_string(),
+        "0".to_string(),
+        "--write-out".to_string(),
+        "%{stderr}%{redirect_url}".to_string(),
+        "--max-time".to_string(),
+        FETCH_TIMEOUT.as_secs().to_string(),
+    ];
+    if let Some(ua) = user_agent {
+        command.push("--user-agent".to_string());
+        command.push(ua.to_string());
+    }
+    if let Some(cookie) = cookie {
+        command.push("--header".to_string());
+        command.push(format!("Cookie: {cookie}"));
+    }
+    command.push("--".to_string());
+    command.push(url.to_string());
+
+    ExecRequest {
+        command,
+        cwd: None,
+        env: BTreeMap::new(),
+        stdin: None,
+        // Leave headroom over curl's own --max-time
+        timeout: Some(FETCH_TIMEOUT + Duration::from_secs(10)),
+        tty: false,
+        detach: false,
+    }
+}
+
+/// Chromium printing the DOM once scripts have run. Its own sandbox stays
+/// on: the page is untrusted even inside ours.
+fn chromium_request(url: &str, user_agent: &str) -> ExecRequest {
+    let command = vec![
+        CHROMIUM_BIN.to_string(),
+        "--headless=new".to_string(),
+        "--disable-gpu".to_string(),
+        "--dump-dom".to_string(),
+        format!("--virtual-time-budget={}", RENDER_BUDGET.as_millis()),
+        format!("--timeout={}", FETCH_TIMEOUT.as_millis()),
+        format!("--user-agent={user_agent}"),
+        "--".to_string(),
+        url.to_string(),
+    ];
+    ExecRequest {
+        command,
+        cwd: None,
+        env: BTreeMap::new(),
+        stdin: None,
+        timeout: Some(FETCH_TIMEOUT + Duration::from_secs(10)),
+        tty: false,
+        detach: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curl_request_args() {
+        let req = curl_request("https://example.com/page", Some("TestAgent/1.0"), None);
+        assert_eq!(req.command[0], "curl");
+        assert!(req.command.contains(&"--fail".to_string()));
+        assert!(!req.command.contains(&"--location".to_string()));
+        assert!(req.command.contains(&"TestAgent/1.0".to_string()));
+        // URL is passed after `--` so it can never be parsed as a flag
+        let n = req.command.len();
+        assert_eq!(req.command[n - 2], "--");
+        assert_eq!(req.command[n - 1], "https://example.com/page");
+    }
+
+    #[test]
+    fn test_check_redirect() {
+        assert!(check_redirect("https://example.com/a", "https://example.com/b").is_ok());
+        assert!(check_redirect("http://example.com/a", "https://example.com/a").is_ok());
+        assert!(check_redirect("https://example.com/a", "https://evil.example/a").is_err());
+        assert!(check_redirect("https://example.com/a", "https://sub.example.com/a").is_err());
+        assert!(check_redirect("https://example.com/a", "http://example.com/a").is_err());
+        assert!(check_redirect("https://example.com/a", "/relative").is_err());
+    }
+
+    #[test]
+    fn test_curl_request_without_user_agent() {
+        let req = curl_request("https://example.com", None, None);
+        assert!(!req.command.contains(&"--user-agent".to_string()));
+        assert!(!req.command.contains(&"--header".to_string()));
+    }
+
+    #[test]
+    fn test_chromium_request_args() {
+        let req = chromium_request("https://example.com/app", "TestAgent/1.0");
+        assert_eq!(req.command[0], CHROMIUM_BIN);
+        assert!(req.command.contains(&"--dump-dom".to_string()));
+        assert!(req.command.contains(&"--user-agent=TestAgent/1.0".to_string()));
+        assert!(!req.command.contains(&"--no-sandbox".to_string()));
+        assert_eq!(req.command[req.command.len() - 2..], ["--", "https://example.com/app"]);
+    }
+
+    #[test]
+    fn test_curl_request_with_cookie() {
+        let req = curl_request("https://example.com", None, Some("session=abc; csrf=x"));
+        let i = req.command.iter().position(|a| a == "--header").unwrap();
+        assert_eq!(req.command[i + 1], "Cookie: session=abc; csrf=x");
+    }
+}
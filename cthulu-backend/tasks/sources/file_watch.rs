@@ -0,0 +1,149 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use super::ContentItem;
+use crate::platform::expand_home;
+
+/// Bytes read from each file when building a snippet; more than enough for
+/// any sensible `snippet_chars`, and keeps large binaries from being slurped.
+const SNIPPET_READ_BYTES: u64 = 64 * 1024;
+
+/// Find files matching `pattern` (e.g. `~/Downloads/**/*.csv`) modified after
+/// `since`, newest first. When `snippet_chars > 0`, text files get the start
+/// of their content in the summary.
+pub async fn scan(
+    pattern: &str,
+    since: DateTime<Utc>,
+    snippet_chars: usize,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let pattern = expand_home(pattern);
+    tokio::task::spawn_blocking(move || scan_blocking(&pattern, since, snippet_chars, limit))
+        .await
+        .context("file scan task panicked")?
+}
+
+fn scan_blocking(
+    pattern: &str,
+    since: DateTime<Utc>,
+    snippet_chars: usize,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let paths = glob::glob(pattern).with_context(|| format!("invalid file pattern: {pattern}"))?;
+
+    let mut found: Vec<(PathBuf, DateTime<Utc>, u64)> = paths
+        .filter_map(|entry| entry.ok())
+        .filter_map(|path| {
+            let meta = std::fs::metadata(&path).ok()?;
+            if !meta.is_file() {
+                return None;
+            }
+            let mtime: DateTime<Utc> = meta.modified().ok()?.into();
+            (mtime > since).then_some((path, mtime, meta.len()))
+        })
+        .collect();
+
+    found.sort_by_key(|(_, mtime, _)| std::cmp::Reverse(*mtime));
+    found.truncate(limit);
+
+    Ok(found
+        .into_iter()
+        .map(|(path, mtime, size)| file_item(&path, mtime, size, snippet_chars))
+        .collect())
+}
+
+fn file_item(path: &Path, mtime: DateTime<Utc>, size: u64, snippet_chars: usize) -> ContentItem {
+    let title = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+
+    let mut summary = format!("Path: {}. Size: {size} bytes.", path.display());
+    if snippet_chars > 0
+        && let Some(snippet) = read_snippet(path, snippet_chars)
+    {
+        summary.push_str("\n   ");
+        summary.push_str(&snippet);
+    }
+
+    ContentItem {
+        title,
+        url: format!("file://{}", path.display()),
+        summary,
+        published: Some(mtime),
+        image_url: None,
+        full_summary: false,
+    }
+}
+
+/// First `max_chars` characters of a UTF-8 text file; `None` for binaries.
+fn read_snippet(path: &Path, max_chars: usize) -> Option<String> {
+    let mut buf = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(SNIPPET_READ_BYTES)
+        .read_to_end(&mut buf)
+        .ok()?;
+    if buf.contains(&0) {
+        return None;
+    }
+    // A truncated read may split a multi-byte char; keep the valid prefix.
+    let text = match std::str::from_utf8(&buf) {
+        Ok(t) => t,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&buf[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    let snippet: String = text.chars().take(max_chars).collect();
+    let snippet = snippet.trim();
+    (!snippet.is_empty()).then(|| snippet.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_scan_filters_by_pattern_and_mtime() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("report.csv"), "date,total\n2024-06-01,42\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/more.csv"), "a,b\n").unwrap();
+
+        let pattern = format!("{}/**/*.csv", dir.path().display());
+        let since = Utc::now() - chrono::Duration::hours(1);
+        let items = scan(&pattern, since, 0, 10).await.unwrap();
+        let mut titles: Vec<_> = items.iter().map(|i| i.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["more.csv", "report.csv"]);
+
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(scan(&pattern, future, 0, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_snippet() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("report.csv"), "date,total\n2024-06-01,42\n").unwrap();
+        std::fs::write(dir.path().join("image.bin"), [0u8, 1, 2, 3]).unwrap();
+
+        let pattern = format!("{}/*", dir.path().display());
+        let since = Utc::now() - chrono::Duration::hours(1);
+        let items = scan(&pattern, since, 10, 10).await.unwrap();
+
+        let csv = items.iter().find(|i| i.title == "report.csv").unwrap();
+        assert!(csv.summary.ends_with("date,total"));
+        assert!(csv.url.starts_with("file://"));
+        let bin = items.iter().find(|i| i.title == "image.bin").unwrap();
+        assert!(bin.summary.ends_with("Size: 4 bytes."));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(scan_blocking("[", Utc::now(), 0, 10).is_err());
+    }
+}
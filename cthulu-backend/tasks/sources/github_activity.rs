@@ -0,0 +1,277 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::ContentItem;
+use crate::egress::AuditedSend;
+use crate::identity::{Integration, user_agent};
+
+const MAX_PER_KIND: usize = 30;
+
+#[derive(Deserialize)]
+struct SearchResponse<T> {
+    items: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct IssueHit {
+    title: String,
+    html_url: String,
+    repository_url: String,
+}
+
+#[derive(Deserialize)]
+struct CommitHit {
+    sha: String,
+    html_url: String,
+    commit: CommitHitDetail,
+    repository: CommitHitRepo,
+}
+
+#[derive(Deserialize)]
+struct CommitHitDetail {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct CommitHitRepo {
+    full_name: String,
+}
+
+/// One line of a person's activity.
+#[derive(Debug, Clone, PartialEq)]
+struct Activity {
+    repo: String,
+    title: String,
+    url: String,
+}
+
+#[derive(Debug, Default)]
+struct PersonActivity {
+    commits: Vec<Activity>,
+    opened: Vec<Activity>,
+    merged: Vec<Activity>,
+    reviewed: Vec<Activity>,
+}
+
+/// Collect each user's commits, opened and merged PRs, and PR reviews from
+/// the last `since_hours`, scoped to `repos` and/or `orgs`. Emits one item
+/// per user (including users with no activity, so standups can say so).
+pub async fn fetch_activity(
+    http_client: &reqwest::Client,
+    token: &str,
+    users: &[String],
+    repos: &[String],
+    orgs: &[String],
+    since_hours: u64,
+) -> Result<Vec<ContentItem>> {
+    let since = Utc::now() - chrono::Duration::hours(since_hours as i64);
+    let scope = scope_clause(repos, orgs);
+
+    let mut items = Vec::new();
+    for user in users {
+        let activity = PersonActivity {
+            commits: search_commits(http_client, token, &commit_query(user, &scope, since)).await?,
+            opened: search_issues(http_client, token, &pr_query(user, "created", &scope, since)).await?,
+            merged: search_issues(http_client, token, &pr_query(user, "merged", &scope, since)).await?,
+            reviewed: search_issues(http_client, token, &review_query(user, &scope, since)).await?,
+        };
+        items.push(person_item(user, &activity, since));
+    }
+    Ok(items)
+}
+
+fn scope_clause(repos: &[String], orgs: &[String]) -> String {
+    repos
+        .iter()
+        .map(|r| format!("repo:{r}"))
+        .chain(orgs.iter().map(|o| format!("org:{o}")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn since_str(since: DateTime<Utc>) -> String {
+    since.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn commit_query(user: &str, scope: &str, since: DateTime<Utc>) -> String {
+    format!("author:{user} committer-date:>={} {scope}", since_str(since))
+        .trim()
+        .to_string()
+}
+
+/// `field` is `created` or `merged`.
+fn pr_query(user: &str, field: &str, scope: &str, since: DateTime<Utc>) -> String {
+    format!("is:pr author:{user} {field}:>={} {scope}", since_str(since))
+        .trim()
+        .to_string()
+}
+
+/// PRs by others that `user` reviewed and that moved since `since`. Search
+/// can't filter on review time, so this may include older reviews.
+fn review_query(user: &str, scope: &str, since: DateTime<Utc>) -> String {
+    format!("is:pr reviewed-by:{user} -author:{user} updated:>={} {scope}", since_str(since))
+        .trim()
+        .to_string()
+}
+
+async fn search<T: for<'de> Deserialize<'de>>(
+    http_client: &reqwest::Client,
+    token: &str,
+    endpoint: &str,
+    query: &str,
+) -> Result<Vec<T>> {
+    let resp = http_client
+        .get(format!("https://api.github.com/search/{endpoint}"))
+        .query(&[("q", query), ("per_page", &MAX_PER_KIND.to_string())])
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", user_agent(Integration::Github))
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .send_audited()
+        .await
+        .with_context(|| format!("GitHub {endpoint} search request failed"))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("GitHub {endpoint} search returned {status} for '{query}': {body}");
+    }
+
+    let search: SearchResponse<T> = resp
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse {endpoint} search response"))?;
+    Ok(search.items)
+}
+
+async fn search_issues(http_client: &reqwest::Client, token: &str, query: &str) -> Result<Vec<Activity>> {
+    let hits: Vec<IssueHit> = search(http_client, token, "issues", query).await?;
+    Ok(hits
+        .into_iter()
+        .map(|hit| Activity {
+            repo: repo_from_api_url(&hit.repository_url),
+            title: hit.title,
+            url: hit.html_url,
+        })
+        .collect())
+}
+
+async fn search_commits(http_client: &reqwest::Client, token: &str, query: &str) -> Result<Vec<Activity>> {
+    let hits: Vec<CommitHit> = search(http_client, token, "commits", query).await?;
+    Ok(hits
+        .into_iter()
+        .map(|hit| Activity {
+            repo: hit.repository.full_name,
+            title: format!(
+                "{} {}",
+                &hit.sha[..7.min(hit.sha.len())],
+                hit.commit.message.lines().next().unwrap_or_default()
+            ),
+            url: hit.html_url,
+        })
+        .collect())
+}
+
+/// `https://api.github.com/repos/owner/repo` -> `owner/repo`
+fn repo_from_api_url(url: &str) -> String {
+    url.split("/repos/").nth(1).unwrap_or(url).to_string()
+}
+
+fn person_item(user: &str, activity: &PersonActivity, since: DateTime<Utc>) -> ContentItem {
+    let counts = format!(
+        "{} commits, {} PRs opened, {} merged, {} reviewed",
+        activity.commits.len(),
+        activity.opened.len(),
+        activity.merged.len(),
+        activity.reviewed.len()
+    );
+
+    let sections = [
+        ("Commits", &activity.commits),
+        ("PRs opened", &activity.opened),
+        ("PRs merged", &activity.merged),
+        ("PRs reviewed", &activity.reviewed),
+    ];
+    let mut summary = String::new();
+    for (heading, entries) in sections {
+        if entries.is_empty() {
+            continue;
+        }
+        summary.push_str(&format!("{heading}:\n"));
+        for a in entries.iter() {
+            summary.push_str(&format!("- [{}] {} ({})\n", a.repo, a.title, a.url));
+        }
+    }
+    if summary.is_empty() {
+        summary.push_str("No GitHub activity.");
+    }
+
+    ContentItem {
+        title: format!("{user}: {counts}"),
+        url: format!("https://github.com/{user}"),
+        summary: summary.trim_end().to_string(),
+        published: Some(since),
+        image_url: None,
+        full_summary: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queries() {
+        let since = DateTime::parse_from_rfc3339("2025-01-15T09:00:00Z").unwrap().with_timezone(&Utc);
+        let scope = scope_clause(&["acme/api".into()], &["acme-labs".into()]);
+        assert_eq!(scope, "repo:acme/api org:acme-labs");
+        assert_eq!(
+            commit_query("alice", &scope, since),
+            "author:alice committer-date:>=2025-01-15T09:00:00Z repo:acme/api org:acme-labs"
+        );
+        assert_eq!(
+            pr_query("alice", "merged", "", since),
+            "is:pr author:alice merged:>=2025-01-15T09:00:00Z"
+        );
+        assert!(review_query("alice", "", since).contains("reviewed-by:alice -author:alice"));
+    }
+
+    #[test]
+    fn test_deserialize_commit_search() {
+        let json = r#"{
+            "total_count": 1,
+            "items": [{
+                "sha": "abcdef1234567",
+                "html_url": "https://github.com/acme/api/commit/abcdef1234567",
+                "commit": { "message": "Fix retry loop\n\nDetails", "author": { "name": "Alice" } },
+                "repository": { "full_name": "acme/api" }
+            }]
+        }"#;
+        let resp: SearchResponse<CommitHit> = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.items[0].repository.full_name, "acme/api");
+        assert_eq!(repo_from_api_url("https://api.github.com/repos/acme/api"), "acme/api");
+    }
+
+    #[test]
+    fn test_person_item() {
+        let since = Utc::now();
+        let activity = PersonActivity {
+            merged: vec![Activity {
+                repo: "acme/api".into(),
+                title: "Add caching".into(),
+                url: "https://github.com/acme/api/pull/7".into(),
+            }],
+            ..Default::default()
+        };
+        let item = person_item("alice", &activity, since);
+        assert_eq!(item.title, "alice: 0 commits, 0 PRs opened, 1 merged, 0 reviewed");
+        assert_eq!(
+            item.summary,
+            "PRs merged:\n- [acme/api] Add caching (https://github.com/acme/api/pull/7)"
+        );
+
+        let idle = person_item("bob", &PersonActivity::default(), since);
+        assert_eq!(idle.summary, "No GitHub activity.");
+    }
+}
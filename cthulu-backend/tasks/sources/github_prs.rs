@@ -3,6 +3,8 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
 use super::ContentItem;
+use crate::egress::AuditedSend;
+use crate::identity::{Integration, user_agent};
 
 #[derive(Deserialize)]
 struct SearchResponse {
@@ -56,9 +58,9 @@ pub async fn fetch_merged_prs(
             ])
             .header("Authorization", format!("Bearer {token}"))
             .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "cthulu-bot")
+            .header("User-Agent", user_agent(Integration::Github))
             .header("X-GitHub-Api-Version", "2022-11-28")
-            .send()
+            .send_audited()
             .await
             .context("GitHub search API request failed")?;
 
@@ -86,6 +88,7 @@ pub async fn fetch_merged_prs(
                 summary: item.body.unwrap_or_default(),
                 published: merged_at,
                 image_url: None,
+                full_summary: false,
             });
         }
 
@@ -3,6 +3,7 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
 
 use super::ContentItem;
+use crate::egress::AuditedSend;
 
 #[derive(Deserialize)]
 struct SheetResponse {
@@ -35,7 +36,7 @@ pub async fn fetch_sheet(
         request = request.bearer_auth(token.as_str());
     }
 
-    let resp = request.send().await.context("Failed to fetch Google Sheets")?;
+    let resp = request.send_audited().await.context("Failed to fetch Google Sheets")?;
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
@@ -109,6 +110,7 @@ fn parse_rows(values: &[Vec<String>], limit: Option<usize>) -> Result<Vec<Conten
                 summary,
                 published,
                 image_url: None,
+                full_summary: false,
             }
         })
         .collect();
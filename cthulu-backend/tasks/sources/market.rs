@@ -1,6 +1,11 @@
 use anyhow::Result;
+use futures::future::join_all;
 use serde::Deserialize;
 
+use super::ContentItem;
+use crate::egress::AuditedSend;
+use crate::identity::{Integration, user_agent};
+
 const BROWSER_UA: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 
 // ── CoinGecko types ──────────────────────────────────────────────
@@ -60,6 +65,10 @@ struct YahooChartResult {
 struct YahooMeta {
     regular_market_price: f64,
     chart_previous_close: f64,
+    #[serde(default)]
+    currency: Option<String>,
+    #[serde(default)]
+    short_name: Option<String>,
 }
 
 // ── Public API ───────────────────────────────────────────────────
@@ -142,6 +151,54 @@ pub async fn fetch_market_snapshot(client: &reqwest::Client) -> Result<String> {
     }
 }
 
+/// Fetch quotes for a configurable list of ticker symbols (Yahoo Finance
+/// notation, e.g. `AAPL`, `^GSPC`, `BTC-USD`). Emits one item per symbol;
+/// symbols that fail to resolve are logged and skipped.
+pub async fn fetch_quotes(
+    client: &reqwest::Client,
+    symbols: &[String],
+) -> Result<Vec<ContentItem>> {
+    let futures: Vec<_> = symbols
+        .iter()
+        .map(|symbol| fetch_yahoo_meta(client, symbol))
+        .collect();
+    let results = join_all(futures).await;
+
+    let mut items = Vec::with_capacity(symbols.len());
+    for (symbol, result) in symbols.iter().zip(results) {
+        match result {
+            Ok(meta) => items.push(quote_item(symbol, &meta)),
+            Err(e) => tracing::warn!(symbol = %symbol, error = %e, "Quote fetch failed"),
+        }
+    }
+
+    if items.is_empty() && !symbols.is_empty() {
+        anyhow::bail!("no quotes could be fetched for {}", symbols.join(", "));
+    }
+    Ok(items)
+}
+
+fn quote_item(symbol: &str, meta: &YahooMeta) -> ContentItem {
+    let change_pct = percent_change(meta.regular_market_price, meta.chart_previous_close);
+    let sign = if change_pct >= 0.0 { "+" } else { "" };
+    let currency = meta.currency.as_deref().unwrap_or("");
+    let price = format!("{} {currency}", format_quote_price(meta.regular_market_price));
+    let name = meta.short_name.as_deref().unwrap_or(symbol);
+
+    ContentItem {
+        title: format!("{symbol}: {} ({sign}{change_pct:.2}%)", price.trim_end()),
+        url: format!("https://finance.yahoo.com/quote/{symbol}"),
+        summary: format!(
+            "{name} last traded at {} ({sign}{change_pct:.2}% vs previous close {}).",
+            price.trim_end(),
+            format_quote_price(meta.chart_previous_close)
+        ),
+        published: Some(chrono::Utc::now()),
+        image_url: None,
+        full_summary: false,
+    }
+}
+
 // ── Individual fetchers ──────────────────────────────────────────
 
 async fn fetch_cnn_fear_greed(client: &reqwest::Client) -> Result<(f64, String)> {
@@ -150,7 +207,7 @@ async fn fetch_cnn_fear_greed(client: &reqwest::Client) -> Result<(f64, String)>
         .header("User-Agent", BROWSER_UA)
         .header("Referer", "https://www.cnn.com/markets/fear-and-greed")
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_audited()
         .await?
         .error_for_status()?
         .json()
@@ -162,8 +219,9 @@ async fn fetch_cnn_fear_greed(client: &reqwest::Client) -> Result<(f64, String)>
 async fn fetch_crypto_fear_greed(client: &reqwest::Client) -> Result<(String, String)> {
     let resp: CryptoFngResponse = client
         .get("https://api.alternative.me/fng/?limit=1")
+        .header("User-Agent", user_agent(Integration::Market))
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_audited()
         .await?
         .error_for_status()?
         .json()
@@ -179,14 +237,14 @@ async fn fetch_crypto_fear_greed(client: &reqwest::Client) -> Result<(String, St
 async fn fetch_coin_prices(client: &reqwest::Client) -> Result<Vec<CoinMarket>> {
     let resp = client
         .get("https://api.coingecko.com/api/v3/coins/markets")
-        .header("User-Agent", "cthulu-bot")
+        .header("User-Agent", user_agent(Integration::Market))
         .query(&[
             ("vs_currency", "usd"),
             ("ids", "bitcoin,ethereum"),
             ("order", "market_cap_desc"),
         ])
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_audited()
         .await?
         .error_for_status()?
         .json()
@@ -196,11 +254,32 @@ async fn fetch_coin_prices(client: &reqwest::Client) -> Result<Vec<CoinMarket>>
 }
 
 async fn fetch_sp500(client: &reqwest::Client) -> Result<(f64, f64)> {
+    let meta = fetch_yahoo_meta(client, "^GSPC").await?;
+    let price = meta.regular_market_price;
+    let change_pct = percent_change(price, meta.chart_previous_close);
+
+    Ok((price, change_pct))
+}
+
+/// Yahoo's chart endpoint for `symbol`, percent-encoded as one path
+/// segment so nothing a configured symbol holds can reach another path.
+fn yahoo_chart_url(symbol: &str) -> reqwest::Url {
+    let mut url = reqwest::Url::parse("https://query1.finance.yahoo.com/v8/finance/chart").expect("valid URL");
+    url.path_segments_mut().expect("base URL").push(symbol);
+    // Index symbols like ^GSPC need their caret encoded too, which path
+    // segments otherwise leave alone
+    let path = url.path().replace('^', "%5E");
+    url.set_path(&path);
+    url
+}
+
+async fn fetch_yahoo_meta(client: &reqwest::Client, symbol: &str) -> Result<YahooMeta> {
     let resp: YahooChartResponse = client
-        .get("https://query1.finance.yahoo.com/v8/finance/chart/%5EGSPC?interval=1d&range=1d")
+        .get(yahoo_chart_url(symbol))
+        .query(&[("interval", "1d"), ("range", "1d")])
         .header("User-Agent", BROWSER_UA)
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_audited()
         .await?
         .error_for_status()?
         .json()
@@ -210,20 +289,37 @@ async fn fetch_sp500(client: &reqwest::Client) -> Result<(f64, f64)> {
         .chart
         .result
         .and_then(|r| r.into_iter().next())
-        .ok_or_else(|| anyhow::anyhow!("Yahoo Finance returned no results"))?;
+        .ok_or_else(|| anyhow::anyhow!("Yahoo Finance returned no results for {symbol}"))?;
 
-    let price = result.meta.regular_market_price;
-    let prev = result.meta.chart_previous_close;
-    let change_pct = if prev > 0.0 {
+    Ok(result.meta)
+}
+
+// ── Formatting helpers ───────────────────────────────────────────
+
+fn percent_change(price: f64, prev: f64) -> f64 {
+    if prev > 0.0 {
         ((price - prev) / prev) * 100.0
     } else {
         0.0
-    };
-
-    Ok((price, change_pct))
+    }
 }
 
-// ── Formatting helpers ───────────────────────────────────────────
+/// Quote prices keep cents (unlike the snapshot's rounded headline prices).
+fn format_quote_price(price: f64) -> String {
+    if price >= 1000.0 {
+        let whole = price.trunc() as u64;
+        let cents = ((price - price.trunc()) * 100.0).round() as u64;
+        if cents >= 100 {
+            format!("{}.00", format_with_commas(whole + 1))
+        } else {
+            format!("{}.{cents:02}", format_with_commas(whole))
+        }
+    } else if price >= 1.0 {
+        format!("{price:.2}")
+    } else {
+        format!("{price:.4}")
+    }
+}
 
 fn score_color(score: f64) -> &'static str {
     if score < 25.0 {
@@ -279,6 +375,14 @@ fn format_with_commas(n: u64) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_yahoo_chart_url_encodes_the_symbol() {
+        assert_eq!(yahoo_chart_url("AAPL").as_str(), "https://query1.finance.yahoo.com/v8/finance/chart/AAPL");
+        assert_eq!(yahoo_chart_url("^GSPC").path(), "/v8/finance/chart/%5EGSPC");
+        assert_eq!(yahoo_chart_url("../x?y#z%").path(), "/v8/finance/chart/..%2Fx%3Fy%23z%25");
+        assert_eq!(yahoo_chart_url("a/b").query(), None);
+    }
+
     #[test]
     fn test_format_price_large() {
         assert_eq!(format_price(97000.0), "97,000");
@@ -321,6 +425,35 @@ mod tests {
         assert_eq!(progress_bar(30.0), "███░░░░░░░");
     }
 
+    #[test]
+    fn test_percent_change() {
+        assert!((percent_change(110.0, 100.0) - 10.0).abs() < 1e-9);
+        assert!((percent_change(90.0, 100.0) + 10.0).abs() < 1e-9);
+        assert_eq!(percent_change(50.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_format_quote_price() {
+        assert_eq!(format_quote_price(189.2), "189.20");
+        assert_eq!(format_quote_price(5123.456), "5,123.46");
+        assert_eq!(format_quote_price(1999.999), "2,000.00");
+        assert_eq!(format_quote_price(0.12345), "0.1235");
+    }
+
+    #[test]
+    fn test_quote_item() {
+        let meta = YahooMeta {
+            regular_market_price: 101.5,
+            chart_previous_close: 100.0,
+            currency: Some("USD".to_string()),
+            short_name: Some("Apple Inc.".to_string()),
+        };
+        let item = quote_item("AAPL", &meta);
+        assert_eq!(item.title, "AAPL: 101.50 USD (+1.50%)");
+        assert_eq!(item.url, "https://finance.yahoo.com/quote/AAPL");
+        assert!(item.summary.starts_with("Apple Inc. last traded at 101.50 USD"));
+    }
+
     #[test]
     fn test_change_color() {
         assert_eq!(change_color(2.3), "green");
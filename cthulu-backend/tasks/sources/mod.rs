@@ -1,21 +1,64 @@
+pub mod arxiv;
+pub mod auth;
+pub mod browser;
+pub mod cache;
+pub mod ci_report;
+pub mod fetcher;
+pub mod file_watch;
+pub mod github_activity;
 pub mod google_sheets;
 pub mod market;
+pub mod read_later;
 pub mod github_prs;
+pub mod repo_health;
 pub mod rss;
+pub mod seen;
+pub mod sentry;
+pub mod sql;
+pub mod stackexchange;
+pub mod weather;
 pub mod web_scrape;
 
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use futures::future::join_all;
 
 use crate::config::SourceConfig;
+use crate::github::client::HttpGithubClient;
+use crate::sandbox::provider::SandboxProvider;
+use fetcher::PageFetcher;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContentItem {
     pub title: String,
     pub url: String,
     pub summary: String,
     pub published: Option<DateTime<Utc>>,
     pub image_url: Option<String>,
+    /// Keep the whole summary when formatting for prompts (e.g. paper abstracts)
+    /// instead of cutting it at 500 chars.
+    pub full_summary: bool,
+}
+
+/// Lowercased title words of 4+ characters, deduplicated; short words are
+/// mostly stopwords and carry little signal. Used to match similar items.
+pub fn title_terms(title: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in title.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if word.chars().count() >= 4 && !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// The site an item came from (`www.` stripped), e.g. `news.ycombinator.com`.
+pub fn url_domain(url: &str) -> Option<String> {
+    let host = reqwest::Url::parse(url).ok()?.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").map(String::from).unwrap_or(host))
 }
 
 fn keyword_matches(item: &ContentItem, keywords: &[String]) -> bool {
@@ -26,10 +69,34 @@ fn keyword_matches(item: &ContentItem, keywords: &[String]) -> bool {
     keywords.iter().any(|kw| haystack.contains(&kw.to_lowercase()))
 }
 
+/// Pick where a scraping source's page fetch runs. Sandboxed sources
+/// refuse to fall back to the host, whether no provider is configured or
+/// the provider runs on the host itself.
+fn page_fetcher<'a>(
+    sandboxed: bool,
+    http_client: &'a reqwest::Client,
+    sandbox_provider: Option<&'a Arc<dyn SandboxProvider>>,
+) -> anyhow::Result<PageFetcher<'a>> {
+    if !sandboxed {
+        return Ok(PageFetcher::Host(http_client));
+    }
+    let provider = sandbox_provider
+        .ok_or_else(|| anyhow::anyhow!("source is sandboxed but no sandbox provider is configured"))?;
+    let kind = provider.info().kind;
+    if crate::tasks::executors::sandbox::runs_on_host(&**provider) {
+        anyhow::bail!("source is sandboxed but the {kind:?} sandbox provider runs on the host; configure one that isn't (e.g. Firecracker)");
+    }
+    Ok(PageFetcher::Sandbox(provider))
+}
+
+/// `last_run_at` is when the flow's previous successful run started, for
+/// sources that only emit what changed since then.
 pub async fn fetch_all(
     sources: &[SourceConfig],
     http_client: &reqwest::Client,
     github_token: Option<&str>,
+    sandbox_provider: Option<&Arc<dyn SandboxProvider>>,
+    last_run_at: Option<DateTime<Utc>>,
 ) -> Vec<ContentItem> {
     let futures: Vec<_> = sources
         .iter()
@@ -51,8 +118,12 @@ pub async fn fetch_all(
                         }
                     }
                 }
-                SourceConfig::WebScrape { url, keywords } => {
-                    match web_scrape::fetch_page_text(http_client, url).await {
+                SourceConfig::WebScrape { url, keywords, sandboxed } => {
+                    let result = match page_fetcher(*sandboxed, http_client, sandbox_provider) {
+                        Ok(fetcher) => web_scrape::fetch_page_text(fetcher, url).await,
+                        Err(e) => Err(e),
+                    };
+                    match result {
                         Ok(items) => {
                             let filtered: Vec<_> = items
                                 .into_iter()
@@ -69,7 +140,7 @@ pub async fn fetch_all(
                 }
                 SourceConfig::GithubMergedPrs { repos, since_days } => {
                     let Some(token) = github_token else {
-                        tracing::error!("GithubMergedPrs source requires GITHUB_TOKEN but none is set");
+                        tracing::error!("GithubMergedPrs source requires GITHUB_TOKEN or GitHub App credentials but neither is set");
                         return Vec::new();
                     };
                     match github_prs::fetch_merged_prs(http_client, token, repos, *since_days).await {
@@ -89,7 +160,7 @@ pub async fn fetch_all(
                     let env_var = service_account_key_env
                         .as_deref()
                         .unwrap_or("GOOGLE_SERVICE_ACCOUNT_KEY");
-                    let key_path = std::env::var(env_var).ok();
+                    let key_path = crate::secrets::var(env_var).ok();
                     match google_sheets::fetch_sheet(
                         http_client,
                         spreadsheet_id,
@@ -110,14 +181,29 @@ pub async fn fetch_all(
                 SourceConfig::WebScraper {
                     url, base_url, items_selector, title_selector,
                     url_selector, summary_selector, date_selector,
-                    date_format, limit,
+                    date_format, limit, next_page_selector, max_pages,
+                    cookies_env, login, sandboxed, render, wait_for_selector,
                 } => {
-                    match web_scrape::fetch_page(
-                        http_client, url, items_selector,
-                        title_selector.as_deref(), url_selector.as_deref(),
-                        summary_selector.as_deref(), date_selector.as_deref(),
-                        date_format.as_deref(), *limit, base_url.as_deref(),
-                    ).await {
+                    let result = async {
+                        let fetcher = if *render {
+                            // Rendering runs scripts of arbitrary pages, so only ever in a real sandbox
+                            let provider = sandbox_provider
+                                .filter(|p| !crate::tasks::executors::sandbox::runs_on_host(&***p))
+                                .ok_or_else(|| anyhow::anyhow!("render needs a sandbox provider that isn't the host (e.g. Firecracker)"))?;
+                            PageFetcher::Rendered { provider, wait_for: wait_for_selector.as_deref() }
+                        } else {
+                            page_fetcher(*sandboxed, http_client, sandbox_provider)?
+                        };
+                        let cookie = auth::session_cookie(cookies_env.as_deref(), login.as_ref()).await?;
+                        web_scrape::fetch_page(
+                            fetcher, url, items_selector,
+                            title_selector.as_deref(), url_selector.as_deref(),
+                            summary_selector.as_deref(), date_selector.as_deref(),
+                            date_format.as_deref(), *limit, base_url.as_deref(),
+                            next_page_selector.as_deref(), *max_pages, cookie.as_deref(),
+                        ).await
+                    }.await;
+                    match result {
                         Ok(items) => {
                             tracing::debug!(url = %url, count = items.len(), "Fetched web scrape");
                             items
@@ -128,6 +214,253 @@ pub async fn fetch_all(
                         }
                     }
                 }
+                SourceConfig::Weather { latitude, longitude, location, days } => {
+                    match weather::fetch_forecast(
+                        http_client, *latitude, *longitude, location.as_deref(), *days,
+                    ).await {
+                        Ok(items) => {
+                            tracing::debug!(latitude, longitude, count = items.len(), "Fetched weather forecast");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::warn!(latitude, longitude, error = %e, "Failed to fetch weather forecast");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::MarketQuotes { symbols } => {
+                    match market::fetch_quotes(http_client, symbols).await {
+                        Ok(items) => {
+                            tracing::debug!(symbols = ?symbols, count = items.len(), "Fetched market quotes");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::warn!(symbols = ?symbols, error = %e, "Failed to fetch market quotes");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::Sql { connection_env, query, title_column, summary_column } => {
+                    let Ok(connection_url) = crate::secrets::var(connection_env) else {
+                        tracing::error!(env = %connection_env, "Sql source requires env var but it is not set");
+                        return Vec::new();
+                    };
+                    match sql::fetch_rows(
+                        &connection_url, query, title_column, summary_column.as_deref(),
+                    ).await {
+                        Ok(items) => {
+                            tracing::debug!(env = %connection_env, count = items.len(), "Fetched SQL rows");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(env = %connection_env, error = %e, "Failed to run SQL query");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::SentryIssues {
+                    token_env, organization, project, base_url, since_hours, limit,
+                } => {
+                    let Ok(token) = crate::secrets::var(token_env) else {
+                        tracing::error!(env = %token_env, "SentryIssues source requires env var but it is not set");
+                        return Vec::new();
+                    };
+                    match sentry::fetch_issues(
+                        http_client, &token, base_url.as_deref(),
+                        organization, project, *since_hours, *limit,
+                    ).await {
+                        Ok(items) => {
+                            tracing::debug!(project = %project, count = items.len(), "Fetched Sentry issues");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(project = %project, error = %e, "Failed to fetch Sentry issues");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::FileWatch { pattern, since_hours, snippet_chars, limit } => {
+                    let since = last_run_at
+                        .unwrap_or_else(|| Utc::now() - chrono::Duration::hours(*since_hours as i64));
+                    match file_watch::scan(pattern, since, *snippet_chars, *limit).await {
+                        Ok(items) => {
+                            tracing::debug!(pattern = %pattern, count = items.len(), "Scanned files");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::warn!(pattern = %pattern, error = %e, "Failed to scan files");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::Arxiv { categories, keywords, limit } => {
+                    match arxiv::fetch_papers(http_client, categories, keywords, *limit).await {
+                        Ok(items) => {
+                            tracing::debug!(categories = ?categories, count = items.len(), "Fetched arXiv papers");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::warn!(categories = ?categories, error = %e, "Failed to fetch arXiv papers");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::StackExchange {
+                    tags, site, min_score, min_answers, unanswered, since_days, key_env, limit,
+                } => {
+                    let api_key = key_env.as_deref().and_then(|env| crate::secrets::var(env).ok());
+                    let filter = stackexchange::QuestionFilter {
+                        min_score: *min_score,
+                        min_answers: *min_answers,
+                        unanswered: *unanswered,
+                    };
+                    match stackexchange::fetch_questions(
+                        http_client, site, tags, &filter, *since_days, api_key.as_deref(), *limit,
+                    ).await {
+                        Ok(items) => {
+                            tracing::debug!(site = %site, tags = ?tags, count = items.len(), "Fetched Stack Exchange questions");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::warn!(site = %site, tags = ?tags, error = %e, "Failed to fetch Stack Exchange questions");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::GithubActivity { users, repos, orgs, since_hours } => {
+                    let Some(token) = github_token else {
+                        tracing::error!("GithubActivity source requires GITHUB_TOKEN or GitHub App credentials but neither is set");
+                        return Vec::new();
+                    };
+                    match github_activity::fetch_activity(http_client, token, users, repos, orgs, *since_hours).await {
+                        Ok(items) => {
+                            tracing::debug!(users = ?users, count = items.len(), "Fetched GitHub activity");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(users = ?users, error = %e, "Failed to fetch GitHub activity");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::GithubRepoHealth { repos, signals, stale_days, response_days } => {
+                    let Some(token) = github_token else {
+                        tracing::error!("GithubRepoHealth source requires GITHUB_TOKEN or GitHub App credentials but neither is set");
+                        return Vec::new();
+                    };
+                    let signals = if signals.is_empty() {
+                        repo_health::Signal::ALL.to_vec()
+                    } else {
+                        signals
+                            .iter()
+                            .filter_map(|s| repo_health::Signal::parse_name(s).ok())
+                            .collect()
+                    };
+                    let config = repo_health::HealthConfig {
+                        signals,
+                        stale_days: *stale_days,
+                        response_days: *response_days,
+                    };
+                    let client = HttpGithubClient::new(http_client.clone(), token.to_string());
+                    match repo_health::fetch_health(&client, repos, &config).await {
+                        Ok(items) => {
+                            tracing::debug!(repos = ?repos, count = items.len(), "Computed repo health");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(repos = ?repos, error = %e, "Failed to compute repo health");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::CiReport { path, format, token_env, limit } => {
+                    let token = token_env.as_deref().and_then(|env| crate::secrets::var(env).ok());
+                    let result = match crate::tasks::reports::ReportFormat::parse_name(format) {
+                        Ok(fmt) => ci_report::fetch_report(http_client, path, fmt, token.as_deref(), *limit).await,
+                        Err(e) => Err(e),
+                    };
+                    match result {
+                        Ok(items) => {
+                            tracing::debug!(path = %path, count = items.len(), "Fetched CI report");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::warn!(path = %path, error = %e, "Failed to fetch CI report");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::Pocket {
+                    consumer_key_env, access_token_env, tag, since_days, limit,
+                } => {
+                    let (Ok(consumer_key), Ok(access_token)) =
+                        (crate::secrets::var(consumer_key_env), crate::secrets::var(access_token_env))
+                    else {
+                        tracing::error!(
+                            consumer_key_env = %consumer_key_env,
+                            access_token_env = %access_token_env,
+                            "Pocket source requires env vars but they are not set"
+                        );
+                        return Vec::new();
+                    };
+                    match read_later::fetch_pocket(
+                        http_client, &consumer_key, &access_token, tag.as_deref(), *since_days, *limit,
+                    ).await {
+                        Ok(items) => {
+                            tracing::debug!(count = items.len(), "Fetched Pocket saves");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(error = %e, "Failed to fetch Pocket saves");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::Readwise { token_env, location, since_days, limit } => {
+                    let Ok(token) = crate::secrets::var(token_env) else {
+                        tracing::error!(env = %token_env, "Readwise source requires env var but it is not set");
+                        return Vec::new();
+                    };
+                    match read_later::fetch_readwise(
+                        http_client, &token, location.as_deref(), *since_days, *limit,
+                    ).await {
+                        Ok(items) => {
+                            tracing::debug!(count = items.len(), "Fetched Readwise documents");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(error = %e, "Failed to fetch Readwise documents");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::WebInteractive {
+                    url, webdriver_url_env, steps, base_url, items_selector,
+                    title_selector, url_selector, summary_selector,
+                    date_selector, date_format, limit,
+                } => {
+                    let webdriver_url = crate::secrets::var(webdriver_url_env)
+                        .unwrap_or_else(|_| browser::DEFAULT_WEBDRIVER_URL.to_string());
+                    let result = match browser::render_page(http_client, &webdriver_url, url, steps, None).await {
+                        Ok(html) => web_scrape::parse_page(
+                            &html, items_selector,
+                            title_selector.as_deref(), url_selector.as_deref(),
+                            summary_selector.as_deref(), date_selector.as_deref(),
+                            date_format.as_deref(), *limit, base_url.as_deref(),
+                        ),
+                        Err(e) => Err(e),
+                    };
+                    match result {
+                        Ok(items) => {
+                            tracing::debug!(url = %url, count = items.len(), "Fetched interactive scrape");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(url = %url, error = %e, "Failed interactive scrape");
+                            Vec::new()
+                        }
+                    }
+                }
             }
         })
         .collect();
@@ -146,6 +479,7 @@ mod tests {
             summary: summary.to_string(),
             published: None,
             image_url: None,
+            full_summary: false,
         }
     }
 
@@ -186,4 +520,24 @@ mod tests {
         let item = make_item("Crypto regulation", "new laws proposed");
         assert!(keyword_matches(&item, &["bitcoin".to_string(), "crypto".to_string()]));
     }
+
+    #[test]
+    fn test_sandboxed_fetch_refuses_the_host_provider() {
+        use crate::sandbox::backends::dangerous::DangerousHostProvider;
+        use crate::sandbox::types::DangerousConfig;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let provider: Arc<dyn SandboxProvider> = Arc::new(
+            DangerousHostProvider::new(DangerousConfig {
+                root_dir: tmp.path().to_path_buf(),
+                ..DangerousConfig::default()
+            })
+            .unwrap(),
+        );
+        let client = reqwest::Client::new();
+        assert!(matches!(page_fetcher(false, &client, Some(&provider)), Ok(PageFetcher::Host(_))));
+        let err = page_fetcher(true, &client, Some(&provider)).err().unwrap();
+        assert!(err.to_string().contains("DangerousHost"), "{err}");
+        assert!(page_fetcher(true, &client, None).is_err());
+    }
 }
@@ -0,0 +1,267 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::ContentItem;
+use crate::egress::AuditedSend;
+
+const POCKET_GET_URL: &str = "https://getpocket.com/v3/get";
+const READWISE_LIST_URL: &str = "https://readwise.io/api/v3/list/";
+
+/// Fetch articles saved to Pocket in the last `since_days` days, newest first.
+pub async fn fetch_pocket(
+    client: &reqwest::Client,
+    consumer_key: &str,
+    access_token: &str,
+    tag: Option<&str>,
+    since_days: u64,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let since = Utc::now() - chrono::Duration::days(since_days as i64);
+    let mut body = json!({
+        "consumer_key": consumer_key,
+        "access_token": access_token,
+        "state": "all",
+        "sort": "newest",
+        "detailType": "simple",
+        "since": since.timestamp(),
+        "count": limit,
+    });
+    if let Some(tag) = tag {
+        body["tag"] = json!(tag);
+    }
+
+    let resp = client
+        .post(POCKET_GET_URL)
+        .header("X-Accept", "application/json")
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(30))
+        .send_audited()
+        .await
+        .context("Pocket request failed")?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let error = resp
+            .headers()
+            .get("X-Error")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        anyhow::bail!("Pocket API returned {status}: {error}");
+    }
+
+    let value: Value = resp
+        .json()
+        .await
+        .context("Failed to parse Pocket response")?;
+
+    Ok(pocket_items(&value, limit))
+}
+
+/// Pocket returns `list` as an object keyed by item id, or `[]` when empty.
+fn pocket_items(value: &Value, limit: usize) -> Vec<ContentItem> {
+    let Some(list) = value["list"].as_object() else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<ContentItem> = list
+        .values()
+        .filter_map(|entry| {
+            let url = non_empty(&entry["resolved_url"]).or_else(|| non_empty(&entry["given_url"]))?;
+            let title = non_empty(&entry["resolved_title"])
+                .or_else(|| non_empty(&entry["given_title"]))
+                .unwrap_or_else(|| url.clone());
+            let published = entry["time_added"]
+                .as_str()
+                .and_then(|s| s.parse::<i64>().ok())
+                .and_then(|ts| DateTime::from_timestamp(ts, 0));
+            Some(ContentItem {
+                title,
+                url,
+                summary: non_empty(&entry["excerpt"]).unwrap_or_default(),
+                published,
+                image_url: non_empty(&entry["top_image_url"]),
+                full_summary: false,
+            })
+        })
+        .collect();
+
+    items.sort_by_key(|item| std::cmp::Reverse(item.published));
+    items.truncate(limit);
+    items
+}
+
+fn non_empty(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadwiseList {
+    results: Vec<ReadwiseDocument>,
+    #[serde(default)]
+    next_page_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReadwiseDocument {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    source_url: Option<String>,
+    url: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    saved_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    image_url: Option<String>,
+}
+
+/// Fetch Readwise Reader documents saved in the last `since_days` days.
+/// `location` is one of `new`, `later`, `shortlist`, `archive`, `feed`.
+pub async fn fetch_readwise(
+    client: &reqwest::Client,
+    token: &str,
+    location: Option<&str>,
+    since_days: u64,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let since = (Utc::now() - chrono::Duration::days(since_days as i64)).to_rfc3339();
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let mut query = vec![("updatedAfter", since.clone())];
+        if let Some(location) = location {
+            query.push(("location", location.to_string()));
+        }
+        if let Some(c) = &cursor {
+            query.push(("pageCursor", c.clone()));
+        }
+
+        let resp = client
+            .get(READWISE_LIST_URL)
+            .query(&query)
+            .header("Authorization", format!("Token {token}"))
+            .timeout(std::time::Duration::from_secs(30))
+            .send_audited()
+            .await
+            .context("Readwise request failed")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Readwise API returned {status}: {body}");
+        }
+
+        let page: ReadwiseList = resp
+            .json()
+            .await
+            .context("Failed to parse Readwise response")?;
+
+        items.extend(page.results.into_iter().map(readwise_item));
+        cursor = page.next_page_cursor;
+        if items.len() >= limit || cursor.is_none() {
+            break;
+        }
+    }
+
+    items.truncate(limit);
+    Ok(items)
+}
+
+fn readwise_item(doc: ReadwiseDocument) -> ContentItem {
+    let url = doc.source_url.filter(|u| !u.is_empty()).unwrap_or(doc.url);
+    let mut summary = doc.summary.unwrap_or_default();
+    if let Some(author) = doc.author.filter(|a| !a.is_empty()) {
+        summary = if summary.is_empty() {
+            format!("By {author}")
+        } else {
+            format!("By {author}. {summary}")
+        };
+    }
+    ContentItem {
+        title: doc.title.filter(|t| !t.is_empty()).unwrap_or_else(|| url.clone()),
+        url,
+        summary,
+        published: doc.saved_at,
+        image_url: doc.image_url,
+        full_summary: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pocket_items() {
+        let value = json!({
+            "status": 1,
+            "list": {
+                "111": {
+                    "item_id": "111",
+                    "given_url": "https://example.com/a?utm=x",
+                    "resolved_url": "https://example.com/a",
+                    "given_title": "",
+                    "resolved_title": "Article A",
+                    "excerpt": "First article.",
+                    "time_added": "1717200000"
+                },
+                "222": {
+                    "item_id": "222",
+                    "given_url": "https://example.com/b",
+                    "resolved_title": "",
+                    "time_added": "1717300000"
+                }
+            }
+        });
+        let items = pocket_items(&value, 10);
+        assert_eq!(items.len(), 2);
+        // Newest first; untitled items fall back to their URL
+        assert_eq!(items[0].title, "https://example.com/b");
+        assert_eq!(items[1].title, "Article A");
+        assert_eq!(items[1].url, "https://example.com/a");
+        assert_eq!(items[1].summary, "First article.");
+        assert_eq!(items[1].published.unwrap().timestamp(), 1717200000);
+    }
+
+    #[test]
+    fn test_pocket_items_empty_list() {
+        let value = json!({ "status": 2, "list": [] });
+        assert!(pocket_items(&value, 10).is_empty());
+    }
+
+    #[test]
+    fn test_readwise_item() {
+        let json = r#"{
+            "results": [{
+                "id": "01abc",
+                "url": "https://read.readwise.io/read/01abc",
+                "source_url": "https://blog.example.com/post",
+                "title": "A Post",
+                "author": "Jane Doe",
+                "summary": "Why things work.",
+                "saved_at": "2024-06-01T08:00:00Z",
+                "image_url": null
+            }],
+            "nextPageCursor": null
+        }"#;
+        let page: ReadwiseList = serde_json::from_str(json).unwrap();
+        assert!(page.next_page_cursor.is_none());
+        let item = readwise_item(page.results.into_iter().next().unwrap());
+        assert_eq!(item.title, "A Post");
+        assert_eq!(item.url, "https://blog.example.com/post");
+        assert_eq!(item.summary, "By Jane Doe. Why things work.");
+        assert!(item.published.is_some());
+    }
+}
@@ -0,0 +1,393 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+
+use super::ContentItem;
+use crate::github::client::GithubClient;
+use crate::github::models::{Branch, Issue, PullRequest};
+
+/// Lockfiles whose last update stands in for "when were dependencies bumped".
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.sum",
+    "poetry.lock",
+    "Gemfile.lock",
+];
+
+const DEPENDENCY_BOTS: &[&str] = &["dependabot[bot]", "renovate[bot]"];
+
+/// Per-branch commit lookups are one request each; cap them.
+const MAX_BRANCHES: usize = 100;
+const MAX_LISTED: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    StaleBranches,
+    PrAge,
+    UnansweredIssues,
+    Dependencies,
+}
+
+impl Signal {
+    pub const ALL: [Signal; 4] = [
+        Signal::StaleBranches,
+        Signal::PrAge,
+        Signal::UnansweredIssues,
+        Signal::Dependencies,
+    ];
+
+    pub fn parse_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "stale-branches" => Signal::StaleBranches,
+            "pr-age" => Signal::PrAge,
+            "unanswered-issues" => Signal::UnansweredIssues,
+            "dependencies" => Signal::Dependencies,
+            other => bail!(
+                "unknown repo health signal '{other}' (expected stale-branches, pr-age, unanswered-issues or dependencies)"
+            ),
+        })
+    }
+}
+
+pub struct HealthConfig {
+    pub signals: Vec<Signal>,
+    /// Branches without commits for this many days count as stale.
+    pub stale_days: i64,
+    /// Issues without a comment for this many days count as unanswered.
+    pub response_days: i64,
+}
+
+/// Compute the requested health signals for each `owner/repo`, one item per
+/// signal per repo. A repo that fails is logged and skipped.
+pub async fn fetch_health(
+    client: &dyn GithubClient,
+    repos: &[String],
+    config: &HealthConfig,
+) -> Result<Vec<ContentItem>> {
+    let now = Utc::now();
+    let mut items = Vec::new();
+    for slug in repos {
+        let Some((owner, repo)) = slug.split_once('/') else {
+            bail!("invalid repo slug '{slug}' (expected owner/repo)");
+        };
+        match repo_health(client, owner, repo, config, now).await {
+            Ok(repo_items) => items.extend(repo_items),
+            Err(e) => tracing::warn!(repo = %slug, error = %e, "Failed to compute repo health"),
+        }
+    }
+    Ok(items)
+}
+
+async fn repo_health(
+    client: &dyn GithubClient,
+    owner: &str,
+    repo: &str,
+    config: &HealthConfig,
+    now: DateTime<Utc>,
+) -> Result<Vec<ContentItem>> {
+    let slug = format!("{owner}/{repo}");
+    let wants = |s: Signal| config.signals.contains(&s);
+
+    let prs = if wants(Signal::PrAge) || wants(Signal::Dependencies) {
+        client.fetch_open_prs(owner, repo).await.context("failed to list open PRs")?
+    } else {
+        Vec::new()
+    };
+
+    let mut items = Vec::new();
+    if wants(Signal::StaleBranches) {
+        let branches = client.fetch_branches(owner, repo).await.context("failed to list branches")?;
+        let candidates: Vec<&Branch> = branches
+            .iter()
+            .filter(|b| !b.protected && !matches!(b.name.as_str(), "main" | "master"))
+            .take(MAX_BRANCHES)
+            .collect();
+        let dates = join_all(candidates.iter().map(|b| async move {
+            let commit = client.fetch_last_commit(owner, repo, Some(&b.commit.sha), None).await;
+            (b.name.clone(), commit.ok().flatten().and_then(|c| c.date()))
+        }))
+        .await;
+        let dated: Vec<(String, DateTime<Utc>)> =
+            dates.into_iter().filter_map(|(name, date)| Some((name, date?))).collect();
+        items.push(stale_branches_item(&slug, &dated, config.stale_days, now));
+    }
+    if wants(Signal::PrAge) {
+        items.push(pr_age_item(&slug, &prs, now));
+    }
+    if wants(Signal::UnansweredIssues) {
+        let issues = client.fetch_open_issues(owner, repo).await.context("failed to list issues")?;
+        items.push(unanswered_issues_item(&slug, &issues, config.response_days, now));
+    }
+    if wants(Signal::Dependencies) {
+        let updates = join_all(LOCKFILES.iter().map(|path| async move {
+            let commit = client.fetch_last_commit(owner, repo, None, Some(path)).await;
+            (*path, commit.ok().flatten().and_then(|c| c.date()))
+        }))
+        .await;
+        let lockfiles: Vec<(&str, DateTime<Utc>)> =
+            updates.into_iter().filter_map(|(path, date)| Some((path, date?))).collect();
+        items.push(dependencies_item(&slug, &lockfiles, &prs, now));
+    }
+    Ok(items)
+}
+
+fn age_days(date: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (now - date).num_days().max(0)
+}
+
+fn stale_branches_item(
+    slug: &str,
+    branches: &[(String, DateTime<Utc>)],
+    stale_days: i64,
+    now: DateTime<Utc>,
+) -> ContentItem {
+    let mut stale: Vec<(&str, i64)> = branches
+        .iter()
+        .map(|(name, date)| (name.as_str(), age_days(*date, now)))
+        .filter(|(_, age)| *age >= stale_days)
+        .collect();
+    stale.sort_by_key(|(_, age)| std::cmp::Reverse(*age));
+
+    let lines: Vec<String> = stale
+        .iter()
+        .take(MAX_LISTED)
+        .map(|(name, age)| format!("- {name}: last commit {age} days ago"))
+        .collect();
+    let mut summary = format!(
+        "{} of {} non-default branches have no commits in {stale_days}+ days.",
+        stale.len(),
+        branches.len()
+    );
+    if !lines.is_empty() {
+        summary.push('\n');
+        summary.push_str(&lines.join("\n"));
+    }
+
+    ContentItem {
+        title: format!("{slug}: {} stale branches", stale.len()),
+        url: format!("https://github.com/{slug}/branches/stale"),
+        summary,
+        published: Some(now),
+        image_url: None,
+        full_summary: true,
+    }
+}
+
+fn pr_age_item(slug: &str, prs: &[PullRequest], now: DateTime<Utc>) -> ContentItem {
+    let mut buckets = [0usize; 4];
+    let mut aged: Vec<(&PullRequest, i64)> = Vec::new();
+    for pr in prs {
+        let Some(created) = pr.created_at else { continue };
+        let age = age_days(created, now);
+        let bucket = match age {
+            0 => 0,
+            1..=6 => 1,
+            7..=29 => 2,
+            _ => 3,
+        };
+        buckets[bucket] += 1;
+        aged.push((pr, age));
+    }
+    aged.sort_by_key(|(_, age)| std::cmp::Reverse(*age));
+
+    let mut summary = format!(
+        "{} open PRs ({} drafts). Age: <1d {}, 1-7d {}, 7-30d {}, >30d {}.",
+        prs.len(),
+        prs.iter().filter(|pr| pr.draft).count(),
+        buckets[0],
+        buckets[1],
+        buckets[2],
+        buckets[3],
+    );
+    if !aged.is_empty() {
+        summary.push_str("\nOldest:");
+        for (pr, age) in aged.iter().take(5) {
+            summary.push_str(&format!("\n- #{} {} ({age} days)", pr.number, pr.title));
+        }
+    }
+
+    ContentItem {
+        title: format!("{slug}: {} open PRs, {} older than 30 days", prs.len(), buckets[3]),
+        url: format!("https://github.com/{slug}/pulls"),
+        summary,
+        published: Some(now),
+        image_url: None,
+        full_summary: true,
+    }
+}
+
+fn unanswered_issues_item(slug: &str, issues: &[Issue], response_days: i64, now: DateTime<Utc>) -> ContentItem {
+    let mut unanswered: Vec<(&Issue, i64)> = issues
+        .iter()
+        .filter(|i| i.pull_request.is_none() && i.comments == 0)
+        .filter_map(|i| Some((i, age_days(i.created_at?, now))))
+        .filter(|(_, age)| *age >= response_days)
+        .collect();
+    unanswered.sort_by_key(|(_, age)| std::cmp::Reverse(*age));
+
+    let mut summary = format!(
+        "{} open issues have had no response for {response_days}+ days.",
+        unanswered.len()
+    );
+    for (issue, age) in unanswered.iter().take(MAX_LISTED) {
+        summary.push_str(&format!("\n- #{} {} ({age} days) {}", issue.number, issue.title, issue.html_url));
+    }
+
+    ContentItem {
+        title: format!("{slug}: {} issues without response", unanswered.len()),
+        url: format!("https://github.com/{slug}/issues?q=is%3Aissue+is%3Aopen+comments%3A0"),
+        summary,
+        published: Some(now),
+        image_url: None,
+        full_summary: true,
+    }
+}
+
+fn dependencies_item(
+    slug: &str,
+    lockfiles: &[(&str, DateTime<Utc>)],
+    prs: &[PullRequest],
+    now: DateTime<Utc>,
+) -> ContentItem {
+    let bot_prs: Vec<&PullRequest> = prs
+        .iter()
+        .filter(|pr| pr.user.as_ref().is_some_and(|u| DEPENDENCY_BOTS.contains(&u.login.as_str())))
+        .collect();
+    let oldest_bot_pr = bot_prs.iter().filter_map(|pr| pr.created_at).min();
+    let stalest = lockfiles.iter().map(|(_, date)| age_days(*date, now)).max();
+
+    let mut summary = String::new();
+    if lockfiles.is_empty() {
+        summary.push_str("No lockfiles found.");
+    } else {
+        summary.push_str("Lockfiles last updated:");
+        for (path, date) in lockfiles {
+            summary.push_str(&format!("\n- {path}: {} days ago", age_days(*date, now)));
+        }
+    }
+    summary.push_str(&format!("\n{} open dependency update PRs", bot_prs.len()));
+    match oldest_bot_pr {
+        Some(created) => summary.push_str(&format!(", oldest {} days.", age_days(created, now))),
+        None => summary.push('.'),
+    }
+
+    let title = match stalest {
+        Some(days) => format!("{slug}: dependencies last updated {days} days ago"),
+        None => format!("{slug}: no lockfiles found"),
+    };
+    ContentItem {
+        title,
+        url: format!("https://github.com/{slug}/pulls?q=is%3Apr+is%3Aopen+label%3Adependencies"),
+        summary,
+        published: Some(now),
+        image_url: None,
+        full_summary: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::models::{PrRef, PrUser};
+
+    fn days_ago(now: DateTime<Utc>, days: i64) -> DateTime<Utc> {
+        now - chrono::Duration::days(days)
+    }
+
+    fn pr(number: u64, created: DateTime<Utc>, author: &str) -> PullRequest {
+        let r = PrRef { sha: "abc".into(), ref_name: "x".into(), repo: None };
+        PullRequest {
+            number,
+            title: format!("PR {number}"),
+            body: None,
+            draft: false,
+            head: r.clone(),
+            base: r,
+            user: Some(PrUser { login: author.into() }),
+            created_at: Some(created),
+            author_association: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!(Signal::parse_name("pr-age").unwrap(), Signal::PrAge);
+        assert!(Signal::parse_name("vibes").is_err());
+    }
+
+    #[test]
+    fn test_stale_branches() {
+        let now = Utc::now();
+        let branches = vec![
+            ("fresh".to_string(), days_ago(now, 2)),
+            ("old".to_string(), days_ago(now, 45)),
+            ("ancient".to_string(), days_ago(now, 400)),
+        ];
+        let item = stale_branches_item("o/r", &branches, 30, now);
+        assert_eq!(item.title, "o/r: 2 stale branches");
+        assert!(item.summary.starts_with("2 of 3 non-default branches"));
+        // Oldest first
+        let ancient = item.summary.find("ancient").unwrap();
+        assert!(ancient < item.summary.find("- old").unwrap());
+        assert!(!item.summary.contains("fresh"));
+    }
+
+    #[test]
+    fn test_pr_age_buckets() {
+        let now = Utc::now();
+        let prs = vec![
+            pr(1, days_ago(now, 0), "a"),
+            pr(2, days_ago(now, 3), "a"),
+            pr(3, days_ago(now, 10), "a"),
+            pr(4, days_ago(now, 90), "a"),
+            pr(5, days_ago(now, 31), "a"),
+        ];
+        let item = pr_age_item("o/r", &prs, now);
+        assert_eq!(item.title, "o/r: 5 open PRs, 2 older than 30 days");
+        assert!(item.summary.contains("<1d 1, 1-7d 1, 7-30d 1, >30d 2"));
+        assert!(item.summary.contains("Oldest:\n- #4 PR 4 (90 days)"));
+    }
+
+    #[test]
+    fn test_unanswered_issues() {
+        let now = Utc::now();
+        let issue = |number, comments, days, is_pr: bool| Issue {
+            number,
+            title: format!("Issue {number}"),
+            comments,
+            created_at: Some(days_ago(now, days)),
+            pull_request: is_pr.then(|| serde_json::json!({})),
+            ..Default::default()
+        };
+        let issues = vec![
+            issue(1, 0, 10, false),
+            issue(2, 3, 10, false), // has replies
+            issue(3, 0, 2, false),  // too recent
+            issue(4, 0, 10, true),  // a PR
+        ];
+        let item = unanswered_issues_item("o/r", &issues, 7, now);
+        assert_eq!(item.title, "o/r: 1 issues without response");
+        assert!(item.summary.contains("#1 Issue 1 (10 days)"));
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let now = Utc::now();
+        let lockfiles = vec![("Cargo.lock", days_ago(now, 60)), ("package-lock.json", days_ago(now, 5))];
+        let prs = vec![
+            pr(1, days_ago(now, 20), "dependabot[bot]"),
+            pr(2, days_ago(now, 4), "renovate[bot]"),
+            pr(3, days_ago(now, 50), "human"),
+        ];
+        let item = dependencies_item("o/r", &lockfiles, &prs, now);
+        assert_eq!(item.title, "o/r: dependencies last updated 60 days ago");
+        assert!(item.summary.contains("- Cargo.lock: 60 days ago"));
+        assert!(item.summary.contains("2 open dependency update PRs, oldest 20 days."));
+
+        let item = dependencies_item("o/r", &[], &[], now);
+        assert_eq!(item.title, "o/r: no lockfiles found");
+    }
+}
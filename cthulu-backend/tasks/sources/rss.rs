@@ -3,6 +3,7 @@ use chrono::{DateTime, Utc};
 use futures::future::join_all;
 
 use super::ContentItem;
+use crate::egress::AuditedSend;
 
 pub async fn fetch_feed(
     client: &reqwest::Client,
@@ -12,7 +13,7 @@ pub async fn fetch_feed(
     let bytes = client
         .get(url)
         .timeout(std::time::Duration::from_secs(30))
-        .send()
+        .send_audited()
         .await
         .context("failed to fetch feed")?
         .error_for_status()
@@ -52,6 +53,7 @@ pub async fn fetch_feed(
                 summary,
                 published,
                 image_url: None,
+                full_summary: false,
             }
         })
         .collect();
@@ -83,7 +85,7 @@ async fn extract_og_image(client: &reqwest::Client, url: &str) -> Option<String>
     let html = client
         .get(url)
         .timeout(std::time::Duration::from_secs(5))
-        .send()
+        .send_audited()
         .await
         .ok()?
         .text()
@@ -205,6 +207,7 @@ mod tests {
                 summary: String::new(),
                 published: None,
                 image_url: None,
+                full_summary: false,
             })
             .collect();
 
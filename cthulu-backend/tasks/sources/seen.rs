@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::json_file;
+use super::ContentItem;
+
+/// Seen entries older than this are forgotten, keeping state files bounded.
+const SEEN_RETENTION_DAYS: i64 = 90;
+
+/// State file → URL hashes staged by one run.
+type StagedKeys = HashMap<PathBuf, Vec<String>>;
+
+/// Per-node record of items already emitted, for sources with `dedupe: true`.
+///
+/// State lives in the data directory under `state/seen/{flow_id}/{node_id}.json`
+/// as a map of URL hash → first-seen time; ids that aren't plain names are
+/// hashed, so a node id can't point outside it. Items filtered during a run
+/// are only marked seen by `commit`, so a failed run doesn't swallow them.
+#[derive(Clone)]
+pub struct SeenStore {
+    dir: PathBuf,
+    /// Keyed by run id.
+    pending: Arc<Mutex<HashMap<String, StagedKeys>>>,
+}
+
+impl SeenStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join("state").join("seen"),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn path(&self, flow_id: &str, node_id: &str) -> PathBuf {
+        self.dir
+            .join(path_component(flow_id))
+            .join(format!("{}.json", path_component(node_id)))
+    }
+
+    /// Items without a URL are keyed by title instead.
+    pub(crate) fn key(item: &ContentItem) -> String {
+        let id = if item.url.is_empty() { &item.title } else { &item.url };
+        format!("{:x}", Sha256::digest(id.as_bytes()))
+    }
+
+    fn load(path: &Path) -> HashMap<String, DateTime<Utc>> {
+        json_file::load_or_default(path)
+    }
+
+    /// Drop items seen by earlier runs (and repeats within this batch), and
+    /// stage the rest to be marked seen when `run_id` commits.
+    pub async fn filter_unseen(
+        &self,
+        flow_id: &str,
+        node_id: &str,
+        run_id: &str,
+        items: Vec<ContentItem>,
+    ) -> Vec<ContentItem> {
+        let path = self.path(flow_id, node_id);
+        let load_path = path.clone();
+        let seen = tokio::task::spawn_blocking(move || Self::load(&load_path))
+            .await
+            .unwrap_or_default();
+
+        let mut batch = HashSet::new();
+        let mut fresh = Vec::new();
+        let mut keys = Vec::new();
+        for item in items {
+            let key = Self::key(&item);
+            if seen.contains_key(&key) || !batch.insert(key.clone()) {
+                continue;
+            }
+            keys.push(key);
+            fresh.push(item);
+        }
+
+        self.pending
+            .lock()
+            .unwrap()
+            .entry(run_id.to_string())
+            .or_default()
+            .entry(path)
+            .or_default()
+            .extend(keys);
+        fresh
+    }
+
+    /// Persist everything `run_id` staged. Call once the run has succeeded.
+    pub async fn commit(&self, run_id: &str) -> Result<()> {
+        let Some(staged) = self.pending.lock().unwrap().remove(run_id) else {
+            return Ok(());
+        };
+        tokio::task::spawn_blocking(move || Self::write_staged(staged))
+            .await
+            .context("seen state task panicked")?
+    }
+
+    fn write_staged(staged: StagedKeys) -> Result<()> {
+        let now = Utc::now();
+        let cutoff = now - chrono::Duration::days(SEEN_RETENTION_DAYS);
+        for (path, keys) in staged {
+            let mut seen = Self::load(&path);
+            seen.retain(|_, at| *at > cutoff);
+            for key in keys {
+                seen.entry(key).or_insert(now);
+            }
+            json_file::write(&path, &seen)?;
+        }
+        Ok(())
+    }
+
+    /// Forget everything `run_id` staged (the run failed).
+    pub fn discard(&self, run_id: &str) {
+        self.pending.lock().unwrap().remove(run_id);
+    }
+}
+
+/// `id` as a file or directory name: itself when it's a plain name, else
+/// its hash.
+fn path_component(id: &str) -> String {
+    let plain = !id.is_empty()
+        && id.len() <= 128
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if plain { id.to_string() } else { format!("{:x}", Sha256::digest(id.as_bytes())) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(url: &str) -> ContentItem {
+        ContentItem {
+            title: format!("Title for {url}"),
+            url: url.to_string(),
+            summary: String::new(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        }
+    }
+
+    fn urls(items: &[ContentItem]) -> Vec<&str> {
+        items.iter().map(|i| i.url.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_committed_items_are_filtered_next_run() {
+        let dir = tempdir().unwrap();
+        let store = SeenStore::new(dir.path());
+
+        let first = store.filter_unseen("f1", "rss", "r1", vec![item("a"), item("b"), item("a")]).await;
+        assert_eq!(urls(&first), vec!["a", "b"]);
+        store.commit("r1").await.unwrap();
+
+        let second = store.filter_unseen("f1", "rss", "r2", vec![item("b"), item("c")]).await;
+        assert_eq!(urls(&second), vec!["c"]);
+
+        // State is per node
+        let other = store.filter_unseen("f1", "scrape", "r2", vec![item("b")]).await;
+        assert_eq!(urls(&other), vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn test_discarded_run_does_not_mark_items() {
+        let dir = tempdir().unwrap();
+        let store = SeenStore::new(dir.path());
+
+        store.filter_unseen("f1", "rss", "r1", vec![item("a")]).await;
+        store.discard("r1");
+        store.commit("r1").await.unwrap();
+
+        let retry = store.filter_unseen("f1", "rss", "r2", vec![item("a")]).await;
+        assert_eq!(urls(&retry), vec!["a"]);
+    }
+
+    #[tokio::test]
+    async fn test_old_entries_are_pruned_on_commit() {
+        let dir = tempdir().unwrap();
+        let store = SeenStore::new(dir.path());
+        let path = store.path("f1", "rss");
+
+        let mut old = HashMap::new();
+        old.insert(
+            SeenStore::key(&item("ancient")),
+            Utc::now() - chrono::Duration::days(SEEN_RETENTION_DAYS + 1),
+        );
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, serde_json::to_string(&old).unwrap()).unwrap();
+
+        store.filter_unseen("f1", "rss", "r1", vec![item("new")]).await;
+        store.commit("r1").await.unwrap();
+
+        let seen = SeenStore::load(&path);
+        assert_eq!(seen.len(), 1);
+        assert!(seen.contains_key(&SeenStore::key(&item("new"))));
+    }
+
+    #[tokio::test]
+    async fn test_ids_stay_inside_the_state_dir() {
+        let dir = tempdir().unwrap();
+        let store = SeenStore::new(dir.path());
+        assert_eq!(store.path("f1", "rss-1"), dir.path().join("state/seen/f1/rss-1.json"));
+
+        let path = store.path("../../..", "../../outside");
+        assert!(path.starts_with(dir.path().join("state").join("seen")));
+        assert_eq!(path.components().count(), store.dir.components().count() + 2);
+
+        store.filter_unseen("f1", "../../escape", "r1", vec![item("a")]).await;
+        store.commit("r1").await.unwrap();
+        assert!(!dir.path().join("state").join("escape.json").exists());
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::ContentItem;
+use crate::egress::AuditedSend;
+
+const DEFAULT_BASE_URL: &str = "https://sentry.io";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SentryIssue {
+    short_id: String,
+    title: String,
+    #[serde(default)]
+    culprit: Option<String>,
+    permalink: String,
+    #[serde(default)]
+    level: Option<String>,
+    /// Sentry returns the event count as a string.
+    #[serde(default)]
+    count: Option<String>,
+    #[serde(default)]
+    user_count: Option<u64>,
+    first_seen: Option<DateTime<Utc>>,
+    last_seen: Option<DateTime<Utc>>,
+}
+
+/// Fetch unresolved issues for a Sentry project that were seen within the
+/// last `since_hours` hours, most recent first.
+pub async fn fetch_issues(
+    client: &reqwest::Client,
+    token: &str,
+    base_url: Option<&str>,
+    organization: &str,
+    project: &str,
+    since_hours: u64,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let base = base_url.unwrap_or(DEFAULT_BASE_URL).trim_end_matches('/');
+    let url = format!("{base}/api/0/projects/{organization}/{project}/issues/");
+    let query = format!("is:unresolved lastSeen:-{since_hours}h");
+
+    let resp = client
+        .get(&url)
+        .query(&[
+            ("query", query.as_str()),
+            ("sort", "date"),
+            ("limit", &limit.to_string()),
+        ])
+        .bearer_auth(token)
+        .timeout(std::time::Duration::from_secs(30))
+        .send_audited()
+        .await
+        .context("Sentry issues request failed")?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("Sentry API returned {status}: {body}");
+    }
+
+    let issues: Vec<SentryIssue> = resp
+        .json()
+        .await
+        .context("Failed to parse Sentry issues response")?;
+
+    Ok(issues.into_iter().take(limit).map(issue_to_item).collect())
+}
+
+fn issue_to_item(issue: SentryIssue) -> ContentItem {
+    let level = issue.level.as_deref().unwrap_or("error").to_uppercase();
+
+    let mut details = Vec::new();
+    if let Some(culprit) = issue.culprit.as_deref().filter(|c| !c.is_empty()) {
+        details.push(format!("Culprit: {culprit}"));
+    }
+    let events = issue.count.as_deref().unwrap_or("0");
+    let users = issue.user_count.unwrap_or(0);
+    details.push(format!("{events} events affecting {users} users"));
+    if let Some(first) = issue.first_seen {
+        details.push(format!("first seen {}", first.format("%Y-%m-%d %H:%M UTC")));
+    }
+
+    ContentItem {
+        title: format!("[{level}] {}: {}", issue.short_id, issue.title),
+        url: issue.permalink,
+        summary: details.join(". "),
+        published: issue.last_seen.or(issue.first_seen),
+        image_url: None,
+        full_summary: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_convert_issue() {
+        let json = r#"[{
+            "id": "123",
+            "shortId": "API-4F",
+            "title": "TypeError: cannot read property 'id' of undefined",
+            "culprit": "handlers/users.js in getUser",
+            "permalink": "https://sentry.io/organizations/acme/issues/123/",
+            "level": "error",
+            "count": "157",
+            "userCount": 23,
+            "firstSeen": "2024-06-01T02:14:00Z",
+            "lastSeen": "2024-06-01T06:30:00Z"
+        }]"#;
+        let issues: Vec<SentryIssue> = serde_json::from_str(json).unwrap();
+        let item = issue_to_item(issues.into_iter().next().unwrap());
+
+        assert_eq!(
+            item.title,
+            "[ERROR] API-4F: TypeError: cannot read property 'id' of undefined"
+        );
+        assert_eq!(item.url, "https://sentry.io/organizations/acme/issues/123/");
+        assert_eq!(
+            item.summary,
+            "Culprit: handlers/users.js in getUser. 157 events affecting 23 users. first seen 2024-06-01 02:14 UTC"
+        );
+        assert_eq!(item.published.unwrap().to_rfc3339(), "2024-06-01T06:30:00+00:00");
+    }
+
+    #[test]
+    fn test_issue_with_minimal_fields() {
+        let json = r#"{
+            "shortId": "WEB-1",
+            "title": "Timeout",
+            "permalink": "https://sentry.example.com/issues/1/",
+            "firstSeen": null,
+            "lastSeen": null
+        }"#;
+        let issue: SentryIssue = serde_json::from_str(json).unwrap();
+        let item = issue_to_item(issue);
+        assert_eq!(item.title, "[ERROR] WEB-1: Timeout");
+        assert_eq!(item.summary, "0 events affecting 0 users");
+        assert!(item.published.is_none());
+    }
+}
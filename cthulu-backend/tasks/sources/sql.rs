@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use sqlx::any::AnyRow;
+use sqlx::{Column, Connection, Executor, Row, TypeInfo, ValueRef};
+
+use super::ContentItem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dialect {
+    Postgres,
+    MySql,
+}
+
+/// The dialect of `url`, and the url as sqlx's `Any` driver takes it: it
+/// only knows MariaDB as `mysql://`.
+fn dialect_for(url: &str) -> Result<(Dialect, String)> {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    match scheme {
+        "postgres" | "postgresql" => Ok((Dialect::Postgres, url.to_string())),
+        "mysql" => Ok((Dialect::MySql, url.to_string())),
+        "mariadb" => Ok((Dialect::MySql, format!("mysql://{rest}"))),
+        other => anyhow::bail!(
+            "unsupported SQL connection scheme '{other}' (expected postgres://, mysql:// or mariadb://)"
+        ),
+    }
+}
+
+/// Reject anything that isn't a single SELECT/WITH statement. The query also
+/// runs inside a read-only transaction; this is an early, friendlier error.
+fn ensure_read_only(query: &str, dialect: Dialect) -> Result<()> {
+    let trimmed = query.trim().trim_end_matches(';').trim();
+    let first = trimmed
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if first != "select" && first != "with" {
+        anyhow::bail!("sql source only allows SELECT/WITH queries");
+    }
+    if has_statement_separator(trimmed, dialect) {
+        anyhow::bail!("sql source only allows a single statement");
+    }
+    Ok(())
+}
+
+/// Whether `query` has a `;` outside string literals, quoted identifiers
+/// and comments, as `dialect` reads them. Constructs it doesn't know (such
+/// as Postgres `$$` strings) only make it see a separator that isn't one.
+fn has_statement_separator(query: &str, dialect: Dialect) -> bool {
+    let chars: Vec<char> = query.chars().collect();
+    let at = |i: usize| chars.get(i).copied();
+    let mut i = 0;
+    while let Some(c) = at(i) {
+        i += 1;
+        match c {
+            ';' => return true,
+            '\'' | '"' | '`' => {
+                // A doubled quote is an escaped one, as is a backslashed one
+                // in MySQL strings
+                while let Some(next) = at(i) {
+                    i += 1;
+                    if next == '\\' && c != '`' && dialect == Dialect::MySql {
+                        i += 1;
+                    } else if next == c {
+                        if at(i) != Some(c) {
+                            break;
+                        }
+                        i += 1;
+                    }
+                }
+            }
+            // MySQL needs whitespace after `--` (`1--1` is arithmetic) and
+            // also takes `#`, which Postgres reads as an operator
+            '-' if at(i) == Some('-')
+                && (dialect == Dialect::Postgres || at(i + 1).is_none_or(char::is_whitespace)) =>
+            {
+                i = chars[i..].iter().position(|&n| n == '\n').map_or(chars.len(), |p| i + p);
+            }
+            '#' if dialect == Dialect::MySql => {
+                i = chars[i..].iter().position(|&n| n == '\n').map_or(chars.len(), |p| i + p);
+            }
+            '/' if at(i) == Some('*') => {
+                i = comment_end(&chars, i + 1);
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Index just past the `*/` closing a comment whose body starts at `start`.
+fn comment_end(chars: &[char], start: usize) -> usize {
+    chars[start..]
+        .windows(2)
+        .position(|w| w == ['*', '/'])
+        .map_or(chars.len(), |p| start + p + 2)
+}
+
+/// Run a read-only query and emit one item per row.
+///
+/// `title_column` is required; `summary_column` falls back to a
+/// `column: value` listing of every other column when unset.
+pub async fn fetch_rows(
+    connection_url: &str,
+    query: &str,
+    title_column: &str,
+    summary_column: Option<&str>,
+) -> Result<Vec<ContentItem>> {
+    let (dialect, connection_url) = dialect_for(connection_url)?;
+    ensure_read_only(query, dialect)?;
+
+    let rows = run_query(dialect, &connection_url, query).await?;
+
+    rows.iter()
+        .map(|row| row_to_item(row, title_column, summary_column))
+        .collect()
+}
+
+async fn run_query(
+    dialect: Dialect,
+    connection_url: &str,
+    query: &str,
+) -> Result<Vec<Vec<(String, String)>>> {
+    sqlx::any::install_default_drivers();
+    let mut conn = sqlx::AnyConnection::connect(connection_url)
+        .await
+        .context("failed to connect to SQL database")?;
+
+    // MySQL applies SET TRANSACTION to the *next* transaction; Postgres to the current one.
+    if dialect == Dialect::MySql {
+        conn.execute("SET TRANSACTION READ ONLY")
+            .await
+            .context("failed to set read-only transaction")?;
+    }
+    let mut tx = conn.begin().await.context("failed to begin transaction")?;
+    if dialect == Dialect::Postgres {
+        tx.execute("SET TRANSACTION READ ONLY")
+            .await
+            .context("failed to set read-only transaction")?;
+    }
+
+    // Columns of types the `Any` driver doesn't map at all (dates,
+    // numerics, uuids, json) fail the whole fetch
+    let rows = tx.fetch_all(query).await.map_err(|e| match e {
+        sqlx::Error::ColumnDecode { index, source } => unreadable_column(&index, &source.to_string()),
+        e => anyhow::Error::new(e).context("SQL query failed"),
+    })?;
+    tx.rollback().await.ok();
+
+    rows.iter().map(row_values).collect()
+}
+
+/// Decode every column of a row as `(name, text)` pairs.
+fn row_values(row: &AnyRow) -> Result<Vec<(String, String)>> {
+    row.columns()
+        .iter()
+        .enumerate()
+        .map(|(i, col)| Ok((col.name().to_string(), decode_text(row, i)?)))
+        .collect()
+}
+
+/// The `Any` driver only decodes a column into its native type, so try the
+/// common ones in turn. NULLs become empty strings; any other type (a blob)
+/// is an error asking for a cast.
+fn decode_text(row: &AnyRow, i: usize) -> Result<String> {
+    if let Ok(v) = row.try_get::<Option<String>, _>(i) {
+        return Ok(v.unwrap_or_default());
+    }
+    if let Ok(v) = row.try_get::<Option<i64>, _>(i) {
+        return Ok(v.map(|n| n.to_string()).unwrap_or_default());
+    }
+    if let Ok(v) = row.try_get::<Option<f64>, _>(i) {
+        return Ok(v.map(|n| n.to_string()).unwrap_or_default());
+    }
+    if let Ok(v) = row.try_get::<Option<bool>, _>(i) {
+        return Ok(v.map(|b| b.to_string()).unwrap_or_default());
+    }
+    // The value's own type: SQLite doesn't declare one for expressions
+    let column = &row.columns()[i];
+    let type_name = match row.try_get_raw(i) {
+        Ok(value) => value.type_info().name().to_string(),
+        Err(_) => column.type_info().name().to_string(),
+    };
+    Err(unreadable_column(column.name(), &type_name))
+}
+
+fn unreadable_column(column: &str, type_name: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "column '{column}' can't be read as text ({type_name}); cast it in the query, \
+         e.g. `{column}::text` on Postgres or `CAST({column} AS CHAR)` on MySQL"
+    )
+}
+
+fn row_to_item(
+    values: &[(String, String)],
+    title_column: &str,
+    summary_column: Option<&str>,
+) -> Result<ContentItem> {
+    let get = |name: &str| values.iter().find(|(col, _)| col == name).map(|(_, v)| v.clone());
+
+    let title = get(title_column)
+        .with_context(|| format!("query result has no column '{title_column}'"))?;
+
+    let summary = match summary_column {
+        Some(col) => get(col).with_context(|| format!("query result has no column '{col}'"))?,
+        None => values
+            .iter()
+            .filter(|(col, _)| col != title_column)
+            .map(|(col, v)| format!("{col}: {v}"))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    Ok(ContentItem {
+        title,
+        url: String::new(),
+        summary,
+        published: None,
+        image_url: None,
+        full_summary: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_column_type_is_an_error() {
+        sqlx::any::install_default_drivers();
+        let mut conn = sqlx::AnyConnection::connect("sqlite::memory:").await.unwrap();
+        let rows = conn
+            .fetch_all("SELECT 'Hello' AS title, 3 AS n, NULL AS missing, x'00ff' AS payload")
+            .await
+            .unwrap();
+        let err = row_values(&rows[0]).unwrap_err().to_string();
+        assert!(err.contains("column 'payload'") && err.contains("BLOB"), "{err}");
+
+        let rows = conn.fetch_all("SELECT 'Hello' AS title, 3 AS n, NULL AS missing").await.unwrap();
+        assert_eq!(row_values(&rows[0]).unwrap(), values(&[("title", "Hello"), ("n", "3"), ("missing", "")]));
+    }
+
+    #[test]
+    fn test_dialect_for() {
+        assert_eq!(dialect_for("postgres://u@h/db").unwrap().0, Dialect::Postgres);
+        assert_eq!(dialect_for("postgresql://u@h/db").unwrap().0, Dialect::Postgres);
+        assert_eq!(dialect_for("mysql://u@h/db").unwrap(), (Dialect::MySql, "mysql://u@h/db".to_string()));
+        assert_eq!(dialect_for("mariadb://u@h/db").unwrap(), (Dialect::MySql, "mysql://u@h/db".to_string()));
+        assert!(dialect_for("sqlite://db.sqlite").is_err());
+    }
+
+    #[test]
+    fn test_ensure_read_only() {
+        for dialect in [Dialect::Postgres, Dialect::MySql] {
+            assert!(ensure_read_only("SELECT * FROM users", dialect).is_ok());
+            assert!(ensure_read_only("  with t as (select 1) select * from t;", dialect).is_ok());
+            assert!(ensure_read_only("DELETE FROM users", dialect).is_err());
+            assert!(ensure_read_only("SELECT 1; DROP TABLE users", dialect).is_err());
+
+            // Semicolons inside literals, identifiers and comments are fine
+            assert!(ensure_read_only("SELECT * FROM notes WHERE body LIKE '%;%'", dialect).is_ok());
+            assert!(ensure_read_only("SELECT 'it''s; fine', \"a;b\", `c;d` FROM t", dialect).is_ok());
+            assert!(ensure_read_only("SELECT 1 -- one; two\nFROM t", dialect).is_ok());
+            assert!(ensure_read_only("SELECT /* a; b */ 1", dialect).is_ok());
+            assert!(ensure_read_only("SELECT ';'; DROP TABLE users", dialect).is_err());
+            assert!(ensure_read_only("SELECT 1 /* ; */; DELETE FROM t", dialect).is_err());
+        }
+
+        // Backslashes escape quotes in MySQL strings only
+        assert!(ensure_read_only("SELECT 'a\\';' FROM t", Dialect::MySql).is_ok());
+        assert!(ensure_read_only("SELECT 'a\\'; DELETE FROM t; --'", Dialect::Postgres).is_err());
+        // `#` comments and `--` without a space are MySQL's
+        assert!(ensure_read_only("SELECT 1 # one; two", Dialect::MySql).is_ok());
+        assert!(ensure_read_only("SELECT 1 # 2; DELETE FROM t", Dialect::Postgres).is_err());
+        assert!(ensure_read_only("SELECT 1--1; DELETE FROM t", Dialect::MySql).is_err());
+    }
+
+    #[test]
+    fn test_row_to_item_with_summary_column() {
+        let row = values(&[("day", "2024-06-01"), ("signups", "42"), ("note", "launch")]);
+        let item = row_to_item(&row, "day", Some("signups")).unwrap();
+        assert_eq!(item.title, "2024-06-01");
+        assert_eq!(item.summary, "42");
+    }
+
+    #[test]
+    fn test_row_to_item_default_summary() {
+        let row = values(&[("day", "2024-06-01"), ("signups", "42"), ("churned", "3")]);
+        let item = row_to_item(&row, "day", None).unwrap();
+        assert_eq!(item.summary, "signups: 42, churned: 3");
+    }
+
+    #[test]
+    fn test_row_to_item_missing_column() {
+        let row = values(&[("day", "2024-06-01")]);
+        assert!(row_to_item(&row, "title", None).is_err());
+        assert!(row_to_item(&row, "day", Some("missing")).is_err());
+    }
+}
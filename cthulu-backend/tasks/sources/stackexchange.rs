@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::ContentItem;
+use crate::egress::AuditedSend;
+
+const STACKEXCHANGE_API_URL: &str = "https://api.stackexchange.com/2.3/questions";
+
+/// Question bodies are stripped to text and cut to this many characters.
+const EXCERPT_CHARS: usize = 400;
+
+#[derive(Debug, Clone)]
+pub struct QuestionFilter {
+    pub min_score: i64,
+    pub min_answers: u64,
+    /// Only questions without an accepted or upvoted answer.
+    pub unanswered: bool,
+}
+
+#[derive(Deserialize)]
+struct QuestionsResponse {
+    #[serde(default)]
+    items: Vec<Question>,
+    error_message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Question {
+    question_id: u64,
+    title: String,
+    link: String,
+    score: i64,
+    answer_count: u64,
+    is_answered: bool,
+    #[serde(default)]
+    accepted_answer_id: Option<u64>,
+    #[serde(default)]
+    tags: Vec<String>,
+    creation_date: i64,
+    #[serde(default)]
+    body: Option<String>,
+    owner: Option<QuestionOwner>,
+}
+
+#[derive(Deserialize)]
+struct QuestionOwner {
+    display_name: Option<String>,
+}
+
+/// Fetch recent questions on `site` (e.g. `stackoverflow`) tagged with any of
+/// `tags`, asked within `since_days`, newest first.
+///
+/// The API ANDs tags in a single query, so each tag is queried separately and
+/// the results merged.
+pub async fn fetch_questions(
+    client: &reqwest::Client,
+    site: &str,
+    tags: &[String],
+    filter: &QuestionFilter,
+    since_days: u64,
+    api_key: Option<&str>,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let from_date = (Utc::now() - chrono::Duration::days(since_days as i64))
+        .timestamp()
+        .to_string();
+
+    let mut seen = HashSet::new();
+    let mut questions = Vec::new();
+    for tag in tags {
+        let mut params = vec![
+            ("site", site),
+            ("tagged", tag.as_str()),
+            ("order", "desc"),
+            ("sort", "creation"),
+            ("filter", "withbody"),
+            ("pagesize", "100"),
+            ("fromdate", from_date.as_str()),
+        ];
+        if let Some(key) = api_key {
+            params.push(("key", key));
+        }
+
+        let resp: QuestionsResponse = client
+            .get(STACKEXCHANGE_API_URL)
+            .query(&params)
+            .timeout(std::time::Duration::from_secs(30))
+            .send_audited()
+            .await
+            .context("Stack Exchange request failed")?
+            .json()
+            .await
+            .context("failed to parse Stack Exchange response")?;
+
+        if let Some(msg) = resp.error_message {
+            anyhow::bail!("Stack Exchange API error for tag '{tag}': {msg}");
+        }
+
+        questions.extend(resp.items.into_iter().filter(|q| seen.insert(q.question_id)));
+    }
+
+    questions.retain(|q| passes(q, filter));
+    questions.sort_by_key(|q| std::cmp::Reverse(q.creation_date));
+    questions.truncate(limit);
+
+    Ok(questions.into_iter().map(question_item).collect())
+}
+
+fn passes(q: &Question, filter: &QuestionFilter) -> bool {
+    q.score >= filter.min_score
+        && q.answer_count >= filter.min_answers
+        && !(filter.unanswered && q.is_answered)
+}
+
+fn question_item(q: Question) -> ContentItem {
+    let answers = match (q.answer_count, q.accepted_answer_id) {
+        (1, Some(_)) => "1 answer (accepted)".to_string(),
+        (1, None) => "1 answer".to_string(),
+        (n, Some(_)) => format!("{n} answers (accepted)"),
+        (n, None) => format!("{n} answers"),
+    };
+    let mut summary = format!("Score {}. {answers}. Tags: {}.", q.score, q.tags.join(", "));
+    if let Some(name) = q.owner.and_then(|o| o.display_name) {
+        summary.push_str(&format!(" Asked by {}.", html_to_text(&name)));
+    }
+    if let Some(body) = q.body.as_deref() {
+        let text = html_to_text(body);
+        if !text.is_empty() {
+            summary.push_str("\n   ");
+            summary.extend(text.chars().take(EXCERPT_CHARS));
+        }
+    }
+
+    ContentItem {
+        title: html_to_text(&q.title),
+        url: q.link,
+        summary,
+        published: DateTime::from_timestamp(q.creation_date, 0),
+        image_url: None,
+        full_summary: false,
+    }
+}
+
+/// Titles and names come entity-encoded and bodies as HTML; flatten to text.
+fn html_to_text(html: &str) -> String {
+    let fragment = scraper::Html::parse_fragment(html);
+    let text: String = fragment.root_element().text().collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"{
+        "items": [
+            {
+                "question_id": 101,
+                "title": "How do I use &quot;select!&quot; with tokio?",
+                "link": "https://stackoverflow.com/questions/101",
+                "score": 5,
+                "answer_count": 2,
+                "is_answered": true,
+                "accepted_answer_id": 555,
+                "tags": ["rust", "tokio"],
+                "creation_date": 1717243200,
+                "body": "<p>I have two   <code>futures</code>.</p>\n<pre>let x = 1;</pre>",
+                "owner": { "display_name": "Ferris &amp; Co" }
+            },
+            {
+                "question_id": 102,
+                "title": "Borrow checker error",
+                "link": "https://stackoverflow.com/questions/102",
+                "score": -1,
+                "answer_count": 0,
+                "is_answered": false,
+                "tags": ["rust"],
+                "creation_date": 1717246800
+            }
+        ],
+        "has_more": false,
+        "quota_remaining": 299
+    }"#;
+
+    fn questions() -> Vec<Question> {
+        serde_json::from_str::<QuestionsResponse>(FIXTURE).unwrap().items
+    }
+
+    #[test]
+    fn test_question_item() {
+        let item = question_item(questions().remove(0));
+        assert_eq!(item.title, r#"How do I use "select!" with tokio?"#);
+        assert_eq!(item.url, "https://stackoverflow.com/questions/101");
+        assert_eq!(
+            item.summary,
+            "Score 5. 2 answers (accepted). Tags: rust, tokio. Asked by Ferris & Co.\n   \
+             I have two futures. let x = 1;"
+        );
+        assert_eq!(item.published.unwrap().timestamp(), 1717243200);
+    }
+
+    #[test]
+    fn test_filters() {
+        let qs = questions();
+        let any = QuestionFilter { min_score: i64::MIN, min_answers: 0, unanswered: false };
+        assert!(qs.iter().all(|q| passes(q, &any)));
+
+        let scored = QuestionFilter { min_score: 0, ..any.clone() };
+        assert_eq!(qs.iter().filter(|q| passes(q, &scored)).count(), 1);
+
+        let unanswered = QuestionFilter { unanswered: true, ..any.clone() };
+        let ids: Vec<u64> = qs.iter().filter(|q| passes(q, &unanswered)).map(|q| q.question_id).collect();
+        assert_eq!(ids, vec![102]);
+
+        let answered = QuestionFilter { min_answers: 1, ..any };
+        assert_eq!(qs.iter().filter(|q| passes(q, &answered)).count(), 1);
+    }
+
+    #[test]
+    fn test_api_error_message() {
+        let resp: QuestionsResponse =
+            serde_json::from_str(r#"{"error_id":502,"error_message":"too many requests","error_name":"throttle_violation"}"#)
+                .unwrap();
+        assert_eq!(resp.error_message.as_deref(), Some("too many requests"));
+        assert!(resp.items.is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+
+use super::ContentItem;
+use crate::egress::AuditedSend;
+
+// ── Open-Meteo types ─────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct ForecastResponse {
+    daily: DailyForecast,
+}
+
+#[derive(Deserialize)]
+struct DailyForecast {
+    time: Vec<String>,
+    weather_code: Vec<Option<u32>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_probability_max: Vec<Option<f64>>,
+}
+
+// ── Public API ───────────────────────────────────────────────────
+
+/// Fetch a daily forecast from Open-Meteo (no API key required).
+/// Emits one item per forecast day, starting with today.
+pub async fn fetch_forecast(
+    client: &reqwest::Client,
+    latitude: f64,
+    longitude: f64,
+    location: Option<&str>,
+    days: u32,
+) -> Result<Vec<ContentItem>> {
+    let resp: ForecastResponse = client
+        .get("https://api.open-meteo.com/v1/forecast")
+        .query(&[
+            ("latitude", latitude.to_string()),
+            ("longitude", longitude.to_string()),
+            (
+                "daily",
+                "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max"
+                    .to_string(),
+            ),
+            ("timezone", "auto".to_string()),
+            ("forecast_days", days.clamp(1, 16).to_string()),
+        ])
+        .timeout(std::time::Duration::from_secs(10))
+        .send_audited()
+        .await
+        .context("failed to fetch Open-Meteo forecast")?
+        .error_for_status()
+        .context("Open-Meteo returned error status")?
+        .json()
+        .await
+        .context("failed to parse Open-Meteo response")?;
+
+    let location = location
+        .map(String::from)
+        .unwrap_or_else(|| format!("{latitude:.2}, {longitude:.2}"));
+
+    Ok(daily_items(&resp.daily, &location, latitude, longitude))
+}
+
+fn daily_items(
+    daily: &DailyForecast,
+    location: &str,
+    latitude: f64,
+    longitude: f64,
+) -> Vec<ContentItem> {
+    let url =
+        format!("https://open-meteo.com/en/forecast?latitude={latitude}&longitude={longitude}");
+
+    daily
+        .time
+        .iter()
+        .enumerate()
+        .map(|(i, day)| {
+            let code = daily.weather_code.get(i).copied().flatten();
+            let high = daily.temperature_2m_max.get(i).copied().flatten();
+            let low = daily.temperature_2m_min.get(i).copied().flatten();
+            let precip = daily
+                .precipitation_probability_max
+                .get(i)
+                .copied()
+                .flatten();
+
+            let conditions = code.map(describe_weather_code).unwrap_or("Unknown conditions");
+            let mut parts = vec![conditions.to_string()];
+            if let (Some(high), Some(low)) = (high, low) {
+                parts.push(format!("high {high:.0}°C / low {low:.0}°C"));
+            }
+            if let Some(precip) = precip {
+                parts.push(format!("{precip:.0}% chance of precipitation"));
+            }
+
+            let published = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| Utc.from_utc_datetime(&dt));
+
+            ContentItem {
+                title: format!("Weather for {location} — {day}"),
+                url: url.clone(),
+                summary: parts.join(", "),
+                published,
+                image_url: None,
+                full_summary: false,
+            }
+        })
+        .collect()
+}
+
+/// Map a WMO weather interpretation code to a short description.
+fn describe_weather_code(code: u32) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 | 53 | 55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 | 63 | 65 => "Rain",
+        66 | 67 => "Freezing rain",
+        71 | 73 | 75 | 77 => "Snow",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown conditions",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_weather_code() {
+        assert_eq!(describe_weather_code(0), "Clear sky");
+        assert_eq!(describe_weather_code(2), "Partly cloudy");
+        assert_eq!(describe_weather_code(63), "Rain");
+        assert_eq!(describe_weather_code(81), "Rain showers");
+        assert_eq!(describe_weather_code(99), "Thunderstorm with hail");
+        assert_eq!(describe_weather_code(1234), "Unknown conditions");
+    }
+
+    #[test]
+    fn test_daily_items_from_response() {
+        let json = r#"{
+            "daily": {
+                "time": ["2024-06-01", "2024-06-02"],
+                "weather_code": [3, 61],
+                "temperature_2m_max": [21.4, 18.0],
+                "temperature_2m_min": [12.2, null],
+                "precipitation_probability_max": [10, 80]
+            }
+        }"#;
+        let resp: ForecastResponse = serde_json::from_str(json).unwrap();
+        let items = daily_items(&resp.daily, "Berlin", 52.52, 13.41);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Weather for Berlin — 2024-06-01");
+        assert_eq!(
+            items[0].summary,
+            "Overcast, high 21°C / low 12°C, 10% chance of precipitation"
+        );
+        assert!(items[0].published.is_some());
+        // Missing low temperature drops the range but keeps the rest
+        assert_eq!(items[1].summary, "Rain, 80% chance of precipitation");
+    }
+
+    #[test]
+    fn test_daily_items_without_precipitation() {
+        let json = r#"{
+            "daily": {
+                "time": ["2024-06-01"],
+                "weather_code": [0],
+                "temperature_2m_max": [25.0],
+                "temperature_2m_min": [15.0]
+            }
+        }"#;
+        let resp: ForecastResponse = serde_json::from_str(json).unwrap();
+        let items = daily_items(&resp.daily, "Home", 0.0, 0.0);
+        assert_eq!(items[0].summary, "Clear sky, high 25°C / low 15°C");
+    }
+}
@@ -1,10 +1,13 @@
-use anyhow::{Context, Result};
+use std::collections::HashSet;
+
+use anyhow::Result;
 use scraper::{Html, Selector};
 
 use super::ContentItem;
+use super::fetcher::PageFetcher;
 
 pub async fn fetch_page(
-    client: &reqwest::Client,
+    fetcher: PageFetcher<'_>,
     url: &str,
     items_selector: &str,
     title_selector: Option<&str>,
@@ -14,23 +17,54 @@ pub async fn fetch_page(
     date_format: Option<&str>,
     limit: usize,
     base_url: Option<&str>,
+    next_page_selector: Option<&str>,
+    max_pages: usize,
+    cookie: Option<&str>,
 ) -> Result<Vec<ContentItem>> {
-    let html = client
-        .get(url)
-        .timeout(std::time::Duration::from_secs(30))
-        .send()
-        .await
-        .context("failed to fetch page")?
-        .error_for_status()
-        .with_context(|| format!("page returned error status: {url}"))?
-        .text()
-        .await
-        .context("failed to read page body")?;
-
-    parse_page(&html, items_selector, title_selector, url_selector, summary_selector, date_selector, date_format, limit, base_url)
+    let mut results = Vec::new();
+    let mut visited = HashSet::new();
+    let mut page_url = url.to_string();
+
+    for _ in 0..max_pages.max(1) {
+        if !visited.insert(page_url.clone()) {
+            break;
+        }
+        let html = fetcher.get_html(&page_url, None, cookie).await?;
+        let remaining = limit.saturating_sub(results.len());
+        results.extend(parse_page(&html, items_selector, title_selector, url_selector, summary_selector, date_selector, date_format, remaining, base_url)?);
+
+        if results.len() >= limit {
+            break;
+        }
+        let Some(next) = next_page_selector.and_then(|sel| next_page_url(&html, sel, &page_url)) else {
+            break;
+        };
+        page_url = next;
+    }
+
+    Ok(results)
+}
+
+/// Resolve the `href` of the first element matching `selector` against the
+/// current page URL. Returns `None` when there is no next page, or when it's
+/// on another origin: the page's `cookie` mustn't go to a host it links to.
+fn next_page_url(html: &str, selector: &str, current_url: &str) -> Option<String> {
+    let sel = Selector::parse(selector).ok()?;
+    let document = Html::parse_document(html);
+    let href = document.select(&sel).next()?.value().attr("href")?.trim();
+    if href.is_empty() || href.starts_with('#') {
+        return None;
+    }
+    let current = reqwest::Url::parse(current_url).ok()?;
+    let next = current.join(href).ok()?;
+    if next.origin() != current.origin() {
+        tracing::debug!(next = %next, "Not following a next-page link to another origin");
+        return None;
+    }
+    Some(next.into())
 }
 
-fn parse_page(
+pub(crate) fn parse_page(
     html: &str,
     items_selector: &str,
     title_selector: Option<&str>,
@@ -118,6 +152,7 @@ fn parse_page(
             summary,
             published,
             image_url: None,
+            full_summary: false,
         });
     }
 
@@ -145,19 +180,10 @@ fn resolve_url(raw: &str, base_url: Option<&str>) -> String {
 
 /// Simple full-page text fetcher for `WebScrape` source variant.
 /// Strips all HTML tags and returns the page body as a single ContentItem.
-pub async fn fetch_page_text(client: &reqwest::Client, url: &str) -> Result<Vec<ContentItem>> {
-    let html = client
-        .get(url)
-        .header("User-Agent", "Mozilla/5.0 (compatible; Cthulu/1.0)")
-        .timeout(std::time::Duration::from_secs(30))
-        .send()
-        .await
-        .context("failed to fetch page")?
-        .error_for_status()
-        .with_context(|| format!("page returned error status: {url}"))?
-        .text()
-        .await
-        .context("failed to read page body")?;
+pub async fn fetch_page_text(fetcher: PageFetcher<'_>, url: &str) -> Result<Vec<ContentItem>> {
+    let html = fetcher
+        .get_html(url, Some("Mozilla/5.0 (compatible; Cthulu/1.0)"), None)
+        .await?;
 
     let title = extract_title(&html).unwrap_or_else(|| url.to_string());
     let body = strip_html(&html);
@@ -168,6 +194,7 @@ pub async fn fetch_page_text(client: &reqwest::Client, url: &str) -> Result<Vec<
         summary: body,
         published: None,
         image_url: None,
+        full_summary: false,
     }])
 }
 
@@ -329,6 +356,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_next_page_url_relative() {
+        let html = r#"<ul class="pager"><li><a class="next" href="?page=3">Next</a></li></ul>"#;
+        assert_eq!(
+            next_page_url(html, "a.next", "https://example.com/news?page=2").as_deref(),
+            Some("https://example.com/news?page=3")
+        );
+    }
+
+    #[test]
+    fn test_next_page_url_absolute_path() {
+        let html = r#"<a rel="next" href="/archive/page/2/">Older</a>"#;
+        assert_eq!(
+            next_page_url(html, "a[rel=next]", "https://blog.example.com/archive/").as_deref(),
+            Some("https://blog.example.com/archive/page/2/")
+        );
+    }
+
+    #[test]
+    fn test_next_page_url_missing() {
+        assert!(next_page_url(FIXTURE_HTML, "a.next", "https://www.sec.gov/news").is_none());
+        let html = r##"<a class="next" href="#">Next</a>"##;
+        assert!(next_page_url(html, "a.next", "https://example.com").is_none());
+        let html = r#"<a class="next" href="https://attacker.example/collect">Next</a>"#;
+        assert!(next_page_url(html, "a.next", "https://example.com/news").is_none());
+    }
+
     #[test]
     fn test_resolve_url_absolute() {
         assert_eq!(
@@ -0,0 +1,322 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::json_file;
+use super::sources::{ContentItem, title_terms, url_domain};
+
+/// Executor kind that ranks items by how widely and how newly a story is
+/// being covered.
+pub const TRENDING_KIND: &str = "trending";
+
+/// Sightings older than this are forgotten.
+const HISTORY_RETENTION_DAYS: i64 = 7;
+
+/// Share of title terms two items must have in common to be the same story.
+const SAME_STORY_SIMILARITY: f64 = 0.5;
+
+/// Each extra feed covering a story is worth this much; a story never seen
+/// before the window earns it once more as a novelty bonus.
+const SOURCE_WEIGHT: i64 = 2;
+
+/// Serializes read-modify-write of history files between concurrent runs.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// One item seen in an earlier run: enough to match later coverage of the
+/// same story.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sighting {
+    terms: Vec<String>,
+    /// Domain of the item's URL, standing in for the feed it came from.
+    source: String,
+    seen_at: DateTime<Utc>,
+}
+
+/// How a story stands against the flow's history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoryStats {
+    /// Distinct feeds covering it within the window, this run included.
+    pub sources: usize,
+    /// Earlier days (before the window) it was already covered on.
+    pub earlier_days: usize,
+}
+
+impl StoryStats {
+    /// Wide, fresh coverage first; stories that keep coming back sink.
+    pub fn score(&self) -> i64 {
+        let novelty = if self.earlier_days == 0 { SOURCE_WEIGHT } else { -(self.earlier_days as i64) };
+        self.sources as i64 * SOURCE_WEIGHT + novelty
+    }
+
+    /// e.g. "appears in 4 feeds today".
+    pub fn describe(&self, window: Duration) -> String {
+        let span = if window == Duration::hours(24) {
+            "today".to_string()
+        } else {
+            format!("in the last {}h", window.num_hours())
+        };
+        let feeds = if self.sources == 1 { "feed" } else { "feeds" };
+        let mut text = format!("appears in {} {feeds} {span}", self.sources);
+        if self.earlier_days == 0 {
+            text.push_str(", new");
+        } else {
+            text.push_str(&format!(", covered on {} earlier days", self.earlier_days));
+        }
+        text
+    }
+}
+
+/// Options from a `trending` node's config.
+#[derive(Debug, Clone, Copy)]
+pub struct TrendingOptions {
+    /// How far back coverage counts towards "today".
+    pub window: Duration,
+    /// Keep one item per story (the first seen) instead of every copy.
+    pub collapse: bool,
+    /// Drop stories covered by fewer feeds than this.
+    pub min_sources: usize,
+    /// Prefix each summary with the story's stats.
+    pub annotate: bool,
+}
+
+impl TrendingOptions {
+    pub fn from_config(config: &serde_json::Value) -> Self {
+        Self {
+            window: Duration::hours(config["window_hours"].as_i64().filter(|h| *h > 0).unwrap_or(24)),
+            collapse: config["collapse"].as_bool().unwrap_or(true),
+            min_sources: config["min_sources"].as_u64().unwrap_or(1) as usize,
+            annotate: config["annotate"].as_bool().unwrap_or(true),
+        }
+    }
+}
+
+/// Per-flow history of item sightings, so a story's spread and age can be
+/// judged across runs.
+///
+/// State lives under `~/.cthulu/state/trending/{flow_id}.json`.
+#[derive(Clone)]
+pub struct TrendingStore {
+    dir: PathBuf,
+}
+
+impl TrendingStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { dir: data_dir.join("state").join("trending") }
+    }
+
+    fn path(&self, flow_id: &str) -> PathBuf {
+        self.dir.join(format!("{flow_id}.json"))
+    }
+
+    fn load(path: &Path) -> Vec<Sighting> {
+        json_file::load_or_default(path)
+    }
+
+    /// Rank `items` against the flow's history (see [`rank`]) and add them
+    /// to it.
+    pub fn rank(&self, flow_id: &str, items: Vec<ContentItem>, options: &TrendingOptions) -> Result<Vec<ContentItem>> {
+        let _guard = FILE_LOCK.lock().unwrap();
+        let path = self.path(flow_id);
+        let mut history = Self::load(&path);
+        let now = Utc::now();
+
+        let sightings: Vec<Sighting> = items
+            .iter()
+            .map(|item| Sighting {
+                terms: title_terms(&item.title),
+                source: source_of(item),
+                seen_at: now,
+            })
+            .collect();
+        let ranked = rank(items, &history, options, now);
+
+        let cutoff = now - Duration::days(HISTORY_RETENTION_DAYS);
+        history.retain(|s| s.seen_at > cutoff);
+        history.extend(sightings);
+        json_file::write(&path, &history)?;
+        Ok(ranked)
+    }
+}
+
+fn source_of(item: &ContentItem) -> String {
+    url_domain(&item.url).unwrap_or_else(|| item.url.clone())
+}
+
+/// Overlap of two term sets relative to the smaller one, so a short headline
+/// still matches a longer one about the same story.
+fn similarity(a: &[String], b: &[String]) -> f64 {
+    let smaller = a.len().min(b.len());
+    if smaller == 0 {
+        return 0.0;
+    }
+    let shared = a.iter().filter(|t| b.contains(t)).count();
+    shared as f64 / smaller as f64
+}
+
+fn same_story(a: &[String], b: &[String]) -> bool {
+    similarity(a, b) >= SAME_STORY_SIMILARITY
+}
+
+/// Stats for a story with title `terms`, from `history` plus this run's
+/// `batch` of (terms, source).
+fn story_stats(
+    terms: &[String],
+    batch: &[(Vec<String>, String)],
+    history: &[Sighting],
+    window_start: DateTime<Utc>,
+) -> StoryStats {
+    let mut sources: HashSet<&str> = batch
+        .iter()
+        .filter(|(other, _)| same_story(terms, other))
+        .map(|(_, source)| source.as_str())
+        .collect();
+    let mut earlier_days = HashSet::new();
+    for sighting in history.iter().filter(|s| same_story(terms, &s.terms)) {
+        if sighting.seen_at >= window_start {
+            sources.insert(&sighting.source);
+        } else {
+            earlier_days.insert(sighting.seen_at.date_naive());
+        }
+    }
+    StoryStats { sources: sources.len(), earlier_days: earlier_days.len() }
+}
+
+/// Order `items` by [`StoryStats::score`] (ties keep source order): stories
+/// covered by more feeds within the window first, new ones ahead of ones
+/// already covered on earlier days.
+fn rank(
+    items: Vec<ContentItem>,
+    history: &[Sighting],
+    options: &TrendingOptions,
+    now: DateTime<Utc>,
+) -> Vec<ContentItem> {
+    let window_start = now - options.window;
+    let batch: Vec<(Vec<String>, String)> = items
+        .iter()
+        .map(|item| (title_terms(&item.title), source_of(item)))
+        .collect();
+
+    let mut stories: Vec<(Vec<String>, ContentItem)> = Vec::new();
+    for ((terms, _), item) in batch.iter().zip(items) {
+        if options.collapse && stories.iter().any(|(seen, _)| same_story(&terms[..], seen)) {
+            continue;
+        }
+        stories.push((terms.clone(), item));
+    }
+
+    let mut scored: Vec<(i64, ContentItem)> = stories
+        .into_iter()
+        .filter_map(|(terms, mut item)| {
+            let stats = story_stats(&terms, &batch, history, window_start);
+            if stats.sources < options.min_sources {
+                return None;
+            }
+            if options.annotate {
+                item.summary = format!("[{}] {}", stats.describe(options.window), item.summary);
+            }
+            Some((stats.score(), item))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(title: &str, url: &str) -> ContentItem {
+        ContentItem {
+            title: title.to_string(),
+            url: url.to_string(),
+            summary: "summary".to_string(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        }
+    }
+
+    fn options() -> TrendingOptions {
+        TrendingOptions::from_config(&serde_json::json!({}))
+    }
+
+    fn titles(items: &[ContentItem]) -> Vec<&str> {
+        items.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rank_prefers_widely_covered_stories() {
+        let items = vec![
+            item("Local bakery opens", "https://town.example/bakery"),
+            item("OpenAI releases new model", "https://a.example/1"),
+            item("New model released by OpenAI today", "https://b.example/2"),
+            item("OpenAI model release analysis", "https://c.example/3"),
+        ];
+        let ranked = rank(items, &[], &options(), Utc::now());
+        assert_eq!(titles(&ranked), ["OpenAI releases new model", "Local bakery opens"]);
+        assert!(ranked[0].summary.starts_with("[appears in 3 feeds today, new] "));
+
+        let keep_all = TrendingOptions { collapse: false, annotate: false, ..options() };
+        let items = vec![
+            item("Unrelated bakery news", "https://a.example/x"),
+            item("Duplicate story here", "https://a.example/y"),
+            item("Duplicate story here", "https://b.example/y"),
+        ];
+        let ranked = rank(items, &[], &keep_all, Utc::now());
+        assert_eq!(titles(&ranked), ["Duplicate story here", "Duplicate story here", "Unrelated bakery news"]);
+        assert_eq!(ranked[0].summary, "summary");
+    }
+
+    #[test]
+    fn test_rank_uses_history() {
+        let now = Utc::now();
+        let sighting = |title: &str, source: &str, hours_ago: i64| Sighting {
+            terms: title_terms(title),
+            source: source.to_string(),
+            seen_at: now - Duration::hours(hours_ago),
+        };
+        let history = vec![
+            // Earlier today on other feeds: spreading
+            sighting("Rust 2.0 announced", "x.example", 2),
+            sighting("Rust 2.0 announced officially", "y.example", 5),
+            // Covered on previous days: stale
+            sighting("Kubernetes outage postmortem", "x.example", 30),
+            sighting("Kubernetes outage postmortem", "x.example", 60),
+        ];
+        let items = vec![
+            item("Kubernetes outage postmortem", "https://k.example/a"),
+            item("Quiet story", "https://q.example/a"),
+            item("Rust 2.0 announced", "https://r.example/a"),
+        ];
+        let ranked = rank(items, &history, &options(), now);
+        assert_eq!(titles(&ranked), ["Rust 2.0 announced", "Quiet story", "Kubernetes outage postmortem"]);
+        assert!(ranked[0].summary.starts_with("[appears in 3 feeds today, new]"));
+        assert!(ranked[2].summary.starts_with("[appears in 1 feed today, covered on 2 earlier days]"));
+
+        let min_two = TrendingOptions { min_sources: 2, ..options() };
+        let ranked = rank(vec![item("Quiet story", "https://q.example/a")], &history, &min_two, now);
+        assert!(ranked.is_empty());
+    }
+
+    #[test]
+    fn test_store_records_sightings_across_runs() {
+        let dir = tempdir().unwrap();
+        let store = TrendingStore::new(dir.path());
+        let opts = TrendingOptions { annotate: false, ..options() };
+
+        let second_run = || vec![item("Quiet story", "https://q.example/1"), item("Rust 2.0 announced", "https://b.example/1")];
+
+        store.rank("f1", vec![item("Rust 2.0 announced", "https://a.example/1")], &opts).unwrap();
+        let ranked = store.rank("f1", second_run(), &opts).unwrap();
+        assert_eq!(titles(&ranked), ["Rust 2.0 announced", "Quiet story"]);
+
+        // History is per flow
+        let ranked = store.rank("f2", second_run(), &opts).unwrap();
+        assert_eq!(titles(&ranked), ["Quiet story", "Rust 2.0 announced"]);
+    }
+}
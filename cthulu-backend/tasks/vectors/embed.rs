@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::egress::AuditedSend;
+
+/// Texts per embeddings request.
+const BATCH_SIZE: usize = 64;
+
+/// Dimensions of [`Embedder::Hashed`] vectors.
+const HASHED_DIM: usize = 512;
+
+const DEFAULT_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_URL: &str = "https://api.openai.com/v1";
+
+/// Turns text into vectors for the vector store.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Embedder {
+    /// An OpenAI-compatible `/embeddings` endpoint (OpenAI, Ollama, vLLM, ...).
+    Remote { url: String, api_key: Option<String>, model: String },
+    /// Feature-hashed word counts: no service needed, but only matches
+    /// shared words, not meaning.
+    Hashed,
+}
+
+impl Embedder {
+    /// `EMBEDDINGS_URL` / `EMBEDDINGS_API_KEY` / `EMBEDDINGS_MODEL`; with
+    /// neither a URL nor a key set, falls back to [`Embedder::Hashed`].
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let (url, api_key) = (var("EMBEDDINGS_URL"), var("EMBEDDINGS_API_KEY"));
+        if url.is_none() && api_key.is_none() {
+            return Embedder::Hashed;
+        }
+        Embedder::Remote {
+            url: url.unwrap_or_else(|| DEFAULT_URL.to_string()),
+            api_key,
+            model: var("EMBEDDINGS_MODEL").unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        }
+    }
+
+    /// Stored with every vector, so switching models never compares vectors
+    /// from different embedding spaces.
+    pub fn model(&self) -> String {
+        match self {
+            Embedder::Remote { model, .. } => model.clone(),
+            Embedder::Hashed => format!("hashed-{HASHED_DIM}"),
+        }
+    }
+
+    pub async fn embed(&self, http_client: &reqwest::Client, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        match self {
+            Embedder::Hashed => Ok(texts.iter().map(|t| hashed(t)).collect()),
+            Embedder::Remote { url, api_key, model } => {
+                let mut vectors = Vec::with_capacity(texts.len());
+                for batch in texts.chunks(BATCH_SIZE) {
+                    vectors.extend(embed_remote(http_client, url, api_key.as_deref(), model, batch).await?);
+                }
+                Ok(vectors)
+            }
+        }
+    }
+}
+
+async fn embed_remote(
+    http_client: &reqwest::Client,
+    url: &str,
+    api_key: Option<&str>,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let mut request = http_client
+        .post(format!("{}/embeddings", url.trim_end_matches('/')))
+        .json(&json!({ "model": model, "input": texts }));
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+    let response = request.send_audited().await.context("embeddings request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("embeddings API returned {status}: {body}");
+    }
+    let body: Value = response.json().await.context("invalid embeddings response")?;
+    parse_embeddings(&body, texts.len())
+}
+
+/// `data[].embedding`, put back in input order by `index`.
+fn parse_embeddings(body: &Value, expected: usize) -> Result<Vec<Vec<f32>>> {
+    let data = body["data"].as_array().context("embeddings response has no data")?;
+    let mut vectors = vec![Vec::new(); expected];
+    for (position, entry) in data.iter().enumerate() {
+        let index = entry["index"].as_u64().map(|i| i as usize).unwrap_or(position);
+        let vector = entry["embedding"]
+            .as_array()
+            .context("embeddings response entry has no embedding")?
+            .iter()
+            .map(|v| v.as_f64().unwrap_or_default() as f32)
+            .collect();
+        *vectors.get_mut(index).context("embeddings response index out of range")? = vector;
+    }
+    if vectors.iter().any(Vec::is_empty) {
+        anyhow::bail!("embeddings response is missing vectors ({} of {expected} returned)", data.len());
+    }
+    Ok(vectors)
+}
+
+/// Word counts hashed into [`HASHED_DIM`] buckets (with a hashed sign to
+/// spread collisions), L2-normalized. SHA-256 keeps it stable across builds.
+fn hashed(text: &str) -> Vec<f32> {
+    let mut vector = vec![0f32; HASHED_DIM];
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        if word.chars().count() < 3 {
+            continue;
+        }
+        let digest = Sha256::digest(word.to_lowercase().as_bytes());
+        let bucket = u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]]) as usize % HASHED_DIM;
+        vector[bucket] += if digest[4] & 1 == 0 { 1.0 } else { -1.0 };
+    }
+    normalize(&mut vector);
+    vector
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// Cosine similarity, 0 for mismatched or zero vectors.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms = a.iter().map(|v| v * v).sum::<f32>().sqrt() * b.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_embeddings_match_shared_words() {
+        let query = hashed("rust compiler performance");
+        let close = hashed("The Rust compiler got faster: performance notes");
+        let far = hashed("Celebrity gossip roundup");
+        assert!(cosine(&query, &close) > cosine(&query, &far));
+        assert!((cosine(&close, &close) - 1.0).abs() < 1e-5);
+        assert_eq!(hashed("a an"), vec![0.0; HASHED_DIM]);
+    }
+
+    #[test]
+    fn test_parse_embeddings_reorders_by_index() {
+        let body = json!({ "data": [
+            { "index": 1, "embedding": [0.0, 1.0] },
+            { "index": 0, "embedding": [1.0, 0.0] }
+        ] });
+        assert_eq!(parse_embeddings(&body, 2).unwrap(), vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert!(parse_embeddings(&body, 3).is_err());
+    }
+}
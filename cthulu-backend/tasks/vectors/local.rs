@@ -0,0 +1,180 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::{QueryBuilder, Row, Sqlite};
+
+use super::embed::cosine;
+use super::{Hit, Record, parse_time};
+use crate::tasks::sources::ContentItem;
+
+/// Rows per INSERT, well under SQLite's bound-parameter limit.
+const INSERT_CHUNK: usize = 500;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS vectors (
+    collection TEXT NOT NULL,
+    item_key TEXT NOT NULL,
+    model TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    published TEXT,
+    embedding BLOB NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (collection, item_key)
+);
+";
+
+async fn connect(path: &Path) -> Result<SqlitePool> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .busy_timeout(Duration::from_secs(5));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .with_context(|| format!("failed to open vector store: {}", path.display()))?;
+    sqlx::raw_sql(SCHEMA)
+        .execute(&pool)
+        .await
+        .context("failed to create vector store schema")?;
+    Ok(pool)
+}
+
+pub(super) async fn upsert(path: &Path, collection: &str, model: &str, records: &[Record]) -> Result<()> {
+    let pool = connect(path).await?;
+    let updated_at = Utc::now().to_rfc3339();
+    for chunk in records.chunks(INSERT_CHUNK) {
+        let mut insert: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "INSERT INTO vectors \
+             (collection, item_key, model, title, url, summary, published, embedding, updated_at) ",
+        );
+        insert.push_values(chunk, |mut row, record| {
+            row.push_bind(collection.to_string())
+                .push_bind(record.key.clone())
+                .push_bind(model.to_string())
+                .push_bind(record.item.title.clone())
+                .push_bind(record.item.url.clone())
+                .push_bind(record.item.summary.clone())
+                .push_bind(record.item.published.map(|p| p.to_rfc3339()))
+                .push_bind(to_blob(&record.vector))
+                .push_bind(updated_at.clone());
+        });
+        insert.push(
+            " ON CONFLICT (collection, item_key) DO UPDATE SET \
+             model = excluded.model, title = excluded.title, url = excluded.url, \
+             summary = excluded.summary, published = excluded.published, \
+             embedding = excluded.embedding, updated_at = excluded.updated_at",
+        );
+        insert
+            .build()
+            .execute(&pool)
+            .await
+            .context("failed to write vectors")?;
+    }
+    pool.close().await;
+    Ok(())
+}
+
+/// Exact nearest neighbours: scores every vector in the collection, which
+/// stays fast up to tens of thousands of items. Use qdrant or pgvector
+/// beyond that.
+pub(super) async fn search(
+    path: &Path,
+    collection: &str,
+    model: &str,
+    query: &[f32],
+    top_k: usize,
+) -> Result<Vec<Hit>> {
+    let pool = connect(path).await?;
+    let rows = sqlx::query(
+        "SELECT title, url, summary, published, embedding FROM vectors WHERE collection = ? AND model = ?",
+    )
+    .bind(collection.to_string())
+    .bind(model.to_string())
+    .fetch_all(&pool)
+    .await
+    .context("vector search failed")?;
+    pool.close().await;
+
+    let mut hits: Vec<Hit> = rows
+        .iter()
+        .map(|row| Hit {
+            score: cosine(query, &from_blob(row.get("embedding"))),
+            item: ContentItem {
+                title: row.get("title"),
+                url: row.get("url"),
+                summary: row.get("summary"),
+                published: row.get::<Option<String>, _>("published").as_deref().and_then(parse_time),
+                image_url: None,
+                full_summary: false,
+            },
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(top_k);
+    Ok(hits)
+}
+
+fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: Vec<u8>) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::sources::seen::SeenStore;
+    use tempfile::tempdir;
+
+    fn record(title: &str, summary: &str, vector: Vec<f32>) -> Record {
+        let item = ContentItem {
+            title: title.to_string(),
+            url: format!("https://example.com/{}", title.len()),
+            summary: summary.to_string(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        };
+        Record { key: SeenStore::key(&item), item, vector }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_and_search() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("vectors.db");
+        let records = vec![
+            record("Rust", "first", vec![1.0, 0.0]),
+            record("Golang", "second", vec![0.0, 1.0]),
+            record("Zig lang", "third", vec![0.7, 0.7]),
+        ];
+        upsert(&path, "notes", "m1", &records).await.unwrap();
+
+        let hits = search(&path, "notes", "m1", &[1.0, 0.1], 2).await.unwrap();
+        let titles: Vec<&str> = hits.iter().map(|h| h.item.title.as_str()).collect();
+        assert_eq!(titles, ["Rust", "Zig lang"]);
+        assert!(hits[0].score > hits[1].score);
+
+        // Upserting the same key replaces it
+        upsert(&path, "notes", "m1", &[record("Rust", "updated", vec![0.0, 1.0])]).await.unwrap();
+        let hits = search(&path, "notes", "m1", &[0.0, 1.0], 10).await.unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!(hits[..2].iter().any(|h| h.item.summary == "updated"));
+
+        // Other collections and models are kept apart
+        assert!(search(&path, "other", "m1", &[1.0, 0.0], 10).await.unwrap().is_empty());
+        assert!(search(&path, "notes", "m2", &[1.0, 0.0], 10).await.unwrap().is_empty());
+    }
+}
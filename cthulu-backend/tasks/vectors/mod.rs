@@ -0,0 +1,173 @@
+pub mod embed;
+mod local;
+mod pgvector;
+mod qdrant;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::sources::ContentItem;
+use super::sources::seen::SeenStore;
+use embed::Embedder;
+
+/// Sink kind that embeds incoming items (or an executor's text) into a
+/// vector store collection.
+pub const VECTOR_STORE_KIND: &str = "vector-store";
+
+/// Source kind that fetches the items in a collection closest to a query.
+pub const VECTOR_SEARCH_KIND: &str = "vector-search";
+
+pub const DEFAULT_COLLECTION: &str = "default";
+pub const DEFAULT_TOP_K: usize = 5;
+
+/// Characters of an item embedded (title plus summary); the rest is stored
+/// but doesn't affect matching.
+const MAX_EMBED_CHARS: usize = 8_000;
+
+/// A stored item and how close it is to the query (cosine similarity, 1 is
+/// identical).
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub item: ContentItem,
+    pub score: f32,
+}
+
+/// An item with its key and embedding, as written to a backend.
+struct Record {
+    key: String,
+    item: ContentItem,
+    vector: Vec<f32>,
+}
+
+/// Where a node's collection lives, from its `backend` config.
+#[derive(Debug, Clone, PartialEq)]
+enum Backend {
+    /// SQLite at `~/.cthulu/state/vectors.db`, searched exhaustively.
+    Local(PathBuf),
+    Qdrant { url: String, api_key: Option<String> },
+    Pgvector { connection_url: String },
+}
+
+/// Vector store shared by `vector-store` and `vector-search` nodes. Items
+/// are upserted by key (hash of the URL, or title when there is none), so
+/// re-indexing an item replaces it.
+///
+/// Nodes pick a backend (`local`, `qdrant` or `pgvector`) and a `collection`;
+/// embeddings come from [`Embedder::from_env`].
+#[derive(Clone)]
+pub struct VectorStore {
+    local_path: PathBuf,
+}
+
+impl VectorStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { local_path: data_dir.join("state").join("vectors.db") }
+    }
+
+    fn backend(&self, config: &Value) -> Result<Backend> {
+        let env = |key: &str, default: &str| {
+            let name = config[key].as_str().unwrap_or(default);
+            crate::secrets::var(name).with_context(|| format!("env var {name} not set"))
+        };
+        match config["backend"].as_str().unwrap_or("local") {
+            "local" => Ok(Backend::Local(self.local_path.clone())),
+            "qdrant" => Ok(Backend::Qdrant {
+                url: env("url_env", "QDRANT_URL")?,
+                api_key: env("api_key_env", "QDRANT_API_KEY").ok(),
+            }),
+            "pgvector" => Ok(Backend::Pgvector { connection_url: env("connection_env", "PGVECTOR_URL")? }),
+            other => anyhow::bail!("unknown vector store backend '{other}' (expected local, qdrant or pgvector)"),
+        }
+    }
+
+    /// Embed `items` and add them to the node's collection. Returns how many
+    /// were written.
+    pub async fn upsert(&self, http_client: &reqwest::Client, config: &Value, items: Vec<ContentItem>) -> Result<usize> {
+        if items.is_empty() {
+            return Ok(0);
+        }
+        let backend = self.backend(config)?;
+        let collection = collection(config);
+        let embedder = Embedder::from_env();
+        let texts: Vec<String> = items.iter().map(embed_text).collect();
+        let vectors = embedder.embed(http_client, &texts).await?;
+        let records: Vec<Record> = items
+            .into_iter()
+            .zip(vectors)
+            .map(|(item, vector)| Record { key: SeenStore::key(&item), item, vector })
+            .collect();
+
+        let model = embedder.model();
+        match &backend {
+            Backend::Local(path) => local::upsert(path, collection, &model, &records).await?,
+            Backend::Qdrant { url, api_key } => {
+                qdrant::upsert(http_client, url, api_key.as_deref(), collection, &model, &records).await?
+            }
+            Backend::Pgvector { connection_url } => pgvector::upsert(connection_url, collection, &model, &records).await?,
+        }
+        Ok(records.len())
+    }
+
+    /// The `top_k` items in the node's collection closest to `query`, best
+    /// first.
+    pub async fn search(
+        &self,
+        http_client: &reqwest::Client,
+        config: &Value,
+        query: &str,
+        top_k: usize,
+    ) -> Result<Vec<Hit>> {
+        let backend = self.backend(config)?;
+        let collection = collection(config);
+        let embedder = Embedder::from_env();
+        let vector = embedder
+            .embed(http_client, &[query.to_string()])
+            .await?
+            .pop()
+            .context("no embedding returned for query")?;
+
+        let model = embedder.model();
+        match &backend {
+            Backend::Local(path) => local::search(path, collection, &model, &vector, top_k).await,
+            Backend::Qdrant { url, api_key } => {
+                qdrant::search(http_client, url, api_key.as_deref(), collection, &model, &vector, top_k).await
+            }
+            Backend::Pgvector { connection_url } => pgvector::search(connection_url, collection, &model, &vector, top_k).await,
+        }
+    }
+}
+
+fn collection(config: &Value) -> &str {
+    config["collection"].as_str().filter(|c| !c.is_empty()).unwrap_or(DEFAULT_COLLECTION)
+}
+
+fn embed_text(item: &ContentItem) -> String {
+    format!("{}\n{}", item.title, item.summary).chars().take(MAX_EMBED_CHARS).collect()
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_backend_from_config() {
+        let dir = tempdir().unwrap();
+        let store = VectorStore::new(dir.path());
+        assert_eq!(
+            store.backend(&json!({})).unwrap(),
+            Backend::Local(dir.path().join("state").join("vectors.db"))
+        );
+        assert!(store.backend(&json!({ "backend": "qdrant", "url_env": "CTHULU_TEST_UNSET_QDRANT" })).is_err());
+        assert!(store.backend(&json!({ "backend": "milvus" })).is_err());
+        assert_eq!(collection(&json!({ "collection": "" })), DEFAULT_COLLECTION);
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::{Postgres, QueryBuilder, Row};
+
+use super::{Hit, Record, parse_time};
+use crate::tasks::sources::ContentItem;
+
+/// Rows per INSERT, well under Postgres' bound-parameter limit.
+const INSERT_CHUNK: usize = 500;
+
+/// The `vector` column is left without a fixed dimension so any embedding
+/// model fits; rows are only compared within one `model`.
+const SCHEMA: &str = "
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE TABLE IF NOT EXISTS cthulu_vectors (
+    collection TEXT NOT NULL,
+    item_key TEXT NOT NULL,
+    model TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    published TEXT,
+    embedding vector NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (collection, item_key)
+);
+";
+
+async fn connect(connection_url: &str) -> Result<PgPool> {
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(connection_url)
+        .await
+        .context("failed to connect to pgvector database")?;
+    sqlx::raw_sql(SCHEMA)
+        .execute(&pool)
+        .await
+        .context("failed to create pgvector schema (is the vector extension installed?)")?;
+    Ok(pool)
+}
+
+pub(super) async fn upsert(connection_url: &str, collection: &str, model: &str, records: &[Record]) -> Result<()> {
+    let pool = connect(connection_url).await?;
+    let updated_at = Utc::now().to_rfc3339();
+    for chunk in records.chunks(INSERT_CHUNK) {
+        let mut insert: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO cthulu_vectors \
+             (collection, item_key, model, title, url, summary, published, embedding, updated_at) ",
+        );
+        insert.push_values(chunk, |mut row, record| {
+            row.push_bind(collection.to_string())
+                .push_bind(record.key.clone())
+                .push_bind(model.to_string())
+                .push_bind(record.item.title.clone())
+                .push_bind(record.item.url.clone())
+                .push_bind(record.item.summary.clone())
+                .push_bind(record.item.published.map(|p| p.to_rfc3339()))
+                .push_bind(vector_literal(&record.vector))
+                .push_unseparated("::vector")
+                .push_bind(updated_at.clone());
+        });
+        insert.push(
+            " ON CONFLICT (collection, item_key) DO UPDATE SET \
+             model = EXCLUDED.model, title = EXCLUDED.title, url = EXCLUDED.url, \
+             summary = EXCLUDED.summary, published = EXCLUDED.published, \
+             embedding = EXCLUDED.embedding, updated_at = EXCLUDED.updated_at",
+        );
+        insert
+            .build()
+            .execute(&pool)
+            .await
+            .context("failed to write vectors")?;
+    }
+    pool.close().await;
+    Ok(())
+}
+
+pub(super) async fn search(
+    connection_url: &str,
+    collection: &str,
+    model: &str,
+    query: &[f32],
+    top_k: usize,
+) -> Result<Vec<Hit>> {
+    let pool = connect(connection_url).await?;
+    let rows = sqlx::query(
+        "SELECT title, url, summary, published, 1 - (embedding <=> $1::vector) AS score
+         FROM cthulu_vectors
+         WHERE collection = $2 AND model = $3
+         ORDER BY embedding <=> $1::vector
+         LIMIT $4",
+    )
+    .bind(vector_literal(query))
+    .bind(collection.to_string())
+    .bind(model.to_string())
+    .bind(top_k as i64)
+    .fetch_all(&pool)
+    .await
+    .context("pgvector search failed")?;
+    pool.close().await;
+
+    Ok(rows
+        .iter()
+        .map(|row| Hit {
+            score: row.get::<f64, _>("score") as f32,
+            item: ContentItem {
+                title: row.get("title"),
+                url: row.get("url"),
+                summary: row.get("summary"),
+                published: row.get::<Option<String>, _>("published").as_deref().and_then(parse_time),
+                image_url: None,
+                full_summary: false,
+            },
+        })
+        .collect())
+}
+
+/// pgvector's text input format, e.g. `[0.5,-1]`.
+fn vector_literal(vector: &[f32]) -> String {
+    let values: Vec<String> = vector.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_literal() {
+        assert_eq!(vector_literal(&[0.5, -1.0, 0.0]), "[0.5,-1,0]");
+        assert_eq!(vector_literal(&[]), "[]");
+    }
+}
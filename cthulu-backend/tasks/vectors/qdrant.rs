@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::{Hit, Record, parse_time};
+use crate::egress::AuditedSend;
+use crate::tasks::sources::ContentItem;
+
+/// Points per upsert request.
+const UPSERT_BATCH: usize = 256;
+
+fn request(
+    http_client: &reqwest::Client,
+    method: reqwest::Method,
+    url: &str,
+    path: &str,
+    api_key: Option<&str>,
+) -> reqwest::RequestBuilder {
+    let mut request = http_client.request(method, format!("{}{path}", url.trim_end_matches('/')));
+    if let Some(key) = api_key {
+        request = request.header("api-key", key);
+    }
+    request
+}
+
+async fn check(response: reqwest::Response, action: &str) -> Result<Value> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("qdrant {action} returned {status}: {body}");
+    }
+    response.json().await.with_context(|| format!("invalid qdrant {action} response"))
+}
+
+/// Create the collection for `dim`-sized cosine vectors unless it exists.
+async fn ensure_collection(
+    http_client: &reqwest::Client,
+    url: &str,
+    api_key: Option<&str>,
+    collection: &str,
+    dim: usize,
+) -> Result<()> {
+    let path = format!("/collections/{collection}");
+    let response = request(http_client, reqwest::Method::GET, url, &path, api_key)
+        .send_audited()
+        .await
+        .context("qdrant request failed")?;
+    if response.status() != StatusCode::NOT_FOUND {
+        check(response, "collection lookup").await?;
+        return Ok(());
+    }
+    let response = request(http_client, reqwest::Method::PUT, url, &path, api_key)
+        .json(&json!({ "vectors": { "size": dim, "distance": "Cosine" } }))
+        .send_audited()
+        .await
+        .context("qdrant request failed")?;
+    check(response, "collection create").await?;
+    Ok(())
+}
+
+pub(super) async fn upsert(
+    http_client: &reqwest::Client,
+    url: &str,
+    api_key: Option<&str>,
+    collection: &str,
+    model: &str,
+    records: &[Record],
+) -> Result<()> {
+    let Some(first) = records.first() else {
+        return Ok(());
+    };
+    ensure_collection(http_client, url, api_key, collection, first.vector.len()).await?;
+
+    let path = format!("/collections/{collection}/points?wait=true");
+    for batch in records.chunks(UPSERT_BATCH) {
+        let points: Vec<Value> = batch.iter().map(|r| point(r, model)).collect();
+        let response = request(http_client, reqwest::Method::PUT, url, &path, api_key)
+            .json(&json!({ "points": points }))
+            .send_audited()
+            .await
+            .context("qdrant request failed")?;
+        check(response, "upsert").await?;
+    }
+    Ok(())
+}
+
+pub(super) async fn search(
+    http_client: &reqwest::Client,
+    url: &str,
+    api_key: Option<&str>,
+    collection: &str,
+    model: &str,
+    query: &[f32],
+    top_k: usize,
+) -> Result<Vec<Hit>> {
+    let path = format!("/collections/{collection}/points/search");
+    let response = request(http_client, reqwest::Method::POST, url, &path, api_key)
+        .json(&json!({
+            "vector": query,
+            "limit": top_k,
+            "with_payload": true,
+            "filter": { "must": [{ "key": "model", "match": { "value": model } }] }
+        }))
+        .send_audited()
+        .await
+        .context("qdrant request failed")?;
+    // Nothing has been stored in it yet
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    let body = check(response, "search").await?;
+    Ok(parse_hits(&body))
+}
+
+/// Qdrant point IDs must be integers or UUIDs; derive a stable UUID from
+/// the item key so upserts replace earlier copies.
+fn point_id(key: &str) -> Uuid {
+    let digest = Sha256::digest(key.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Uuid::from_bytes(bytes)
+}
+
+fn point(record: &Record, model: &str) -> Value {
+    json!({
+        "id": point_id(&record.key).to_string(),
+        "vector": record.vector,
+        "payload": {
+            "key": record.key,
+            "model": model,
+            "title": record.item.title,
+            "url": record.item.url,
+            "summary": record.item.summary,
+            "published": record.item.published.map(|p| p.to_rfc3339()),
+        }
+    })
+}
+
+fn parse_hits(body: &Value) -> Vec<Hit> {
+    body["result"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|hit| {
+            let payload = &hit["payload"];
+            let text = |key: &str| payload[key].as_str().unwrap_or_default().to_string();
+            Hit {
+                score: hit["score"].as_f64().unwrap_or_default() as f32,
+                item: ContentItem {
+                    title: text("title"),
+                    url: text("url"),
+                    summary: text("summary"),
+                    published: payload["published"].as_str().and_then(parse_time),
+                    image_url: None,
+                    full_summary: false,
+                },
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_round_trip() {
+        let item = ContentItem {
+            title: "Rust 2.0".to_string(),
+            url: "https://example.com/rust".to_string(),
+            summary: "Faster builds.".to_string(),
+            published: None,
+            image_url: None,
+            full_summary: false,
+        };
+        let record = Record { key: "abc".to_string(), item, vector: vec![0.5, 0.5] };
+        let point = point(&record, "m1");
+        assert_eq!(point["id"], point_id("abc").to_string());
+        assert_ne!(point_id("abc"), point_id("abd"));
+        assert_eq!(point["payload"]["model"], "m1");
+
+        let body = json!({ "result": [{ "id": point["id"], "score": 0.9, "payload": point["payload"] }] });
+        let hits = parse_hits(&body);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].item.title, "Rust 2.0");
+        assert!((hits[0].score - 0.9).abs() < 1e-6);
+    }
+}
@@ -129,11 +129,191 @@ export async function triggerFlow(
   return apiFetch(`/flows/${id}/trigger`, { method: "POST" });
 }
 
+export interface FlowInputSpec {
+  name: string;
+  type?: "string" | "number" | "boolean";
+  required?: boolean;
+  default?: string | number | boolean;
+  description?: string;
+  options?: string[];
+}
+
+export async function getFlowInputs(id: string): Promise<FlowInputSpec[]> {
+  const data = await apiFetch<{ inputs: FlowInputSpec[] }>(`/flows/${id}/inputs`);
+  return data.inputs;
+}
+
+export async function runFlow(
+  id: string,
+  inputs: Record<string, string | number | boolean>
+): Promise<{
+  status: "triggered" | "batched" | "dropped" | "queued";
+  flow_id: string;
+  inputs: Record<string, string>;
+  run_at?: string;
+}> {
+  return apiFetch(`/flows/${id}/run`, {
+    method: "POST",
+    body: JSON.stringify({ inputs }),
+  });
+}
+
+export type SubscriptionDelivery =
+  | { kind: "slack-channel"; channel: string }
+  | { kind: "slack-dm"; user_email: string }
+  | { kind: "email"; to: string };
+
+export interface FlowSubscription {
+  id: string;
+  flow_id: string;
+  subscriber: string;
+  delivery: SubscriptionDelivery;
+  deliver_at?: string;
+  timezone?: string;
+  interests?: string[];
+  enabled: boolean;
+  created_at: string;
+}
+
+export async function listSubscriptions(flowId: string): Promise<FlowSubscription[]> {
+  const data = await apiFetch<{ subscriptions: FlowSubscription[] }>(`/flows/${flowId}/subscriptions`);
+  return data.subscriptions;
+}
+
+export async function createSubscription(
+  flowId: string,
+  body: {
+    subscriber: string;
+    delivery: SubscriptionDelivery;
+    deliver_at?: string;
+    timezone?: string;
+    interests?: string[];
+  }
+): Promise<FlowSubscription> {
+  return apiFetch<FlowSubscription>(`/flows/${flowId}/subscriptions`, {
+    method: "POST",
+    body: JSON.stringify(body),
+  });
+}
+
+export async function updateSubscriptionInterests(
+  flowId: string,
+  subscriptionId: string,
+  interests: string[]
+): Promise<FlowSubscription> {
+  return apiFetch<FlowSubscription>(`/flows/${flowId}/subscriptions/${subscriptionId}/interests`, {
+    method: "PUT",
+    body: JSON.stringify({ interests }),
+  });
+}
+
+export async function deleteSubscription(flowId: string, subscriptionId: string): Promise<void> {
+  await apiFetch(`/flows/${flowId}/subscriptions/${subscriptionId}`, { method: "DELETE" });
+}
+
+export interface TriggerToken {
+  id: string;
+  flow_id: string;
+  name: string;
+  hint: string;
+  created_at: string;
+  last_used_at: string | null;
+}
+
+export async function listTriggerTokens(flowId: string): Promise<TriggerToken[]> {
+  const data = await apiFetch<{ tokens: TriggerToken[] }>(`/flows/${flowId}/tokens`);
+  return data.tokens;
+}
+
+/** The returned `token` secret is shown once and never again. */
+export async function createTriggerToken(
+  flowId: string,
+  name: string
+): Promise<TriggerToken & { token: string; trigger_path: string }> {
+  return apiFetch(`/flows/${flowId}/tokens`, {
+    method: "POST",
+    body: JSON.stringify({ name }),
+  });
+}
+
+export async function deleteTriggerToken(flowId: string, tokenId: string): Promise<void> {
+  await apiFetch(`/flows/${flowId}/tokens/${tokenId}`, { method: "DELETE" });
+}
+
+export interface ArchivedItem {
+  flow_id: string;
+  flow_name: string;
+  title: string;
+  url: string;
+  snippet: string;
+  published?: string;
+  archived_at: string;
+}
+
+export async function searchArchive(
+  q: string,
+  opts: { flowId?: string; limit?: number } = {}
+): Promise<ArchivedItem[]> {
+  const params = new URLSearchParams({ q });
+  if (opts.flowId) params.set("flow_id", opts.flowId);
+  if (opts.limit) params.set("limit", String(opts.limit));
+  const data = await apiFetch<{ results: ArchivedItem[] }>(`/search?${params}`);
+  return data.results;
+}
+
+export interface AskCitation {
+  index: number;
+  kind: "run" | "item";
+  flow_id: string;
+  flow_name: string;
+  title: string;
+  link: string;
+  run_id?: string;
+  date: string;
+  cited: boolean;
+}
+
+export interface AskAnswer {
+  question: string;
+  answer: string;
+  citations: AskCitation[];
+  cost_usd?: number;
+}
+
+export async function askQuestion(
+  question: string,
+  opts: { flowId?: string; agentId?: string } = {}
+): Promise<AskAnswer> {
+  return apiFetch<AskAnswer>("/ask", {
+    method: "POST",
+    body: JSON.stringify({ question, flow_id: opts.flowId, agent_id: opts.agentId }),
+  });
+}
+
 export async function getFlowRuns(id: string): Promise<FlowRun[]> {
   const data = await apiFetch<{ runs: FlowRun[] }>(`/flows/${id}/runs`);
   return data.runs;
 }
 
+/** Link to a run's shareable report (HTML page or PDF download). */
+export interface FlowCosts {
+  flow_id: string;
+  spent: { today: number; week: number; month: number; total: number };
+  budget: {
+    max_cost_usd: number | null;
+    max_flow_cost_usd: number | null;
+    cost_period: "day" | "week" | "month";
+  };
+}
+
+export async function getFlowCosts(flowId: string): Promise<FlowCosts> {
+  return apiFetch<FlowCosts>(`/flows/${flowId}/costs`);
+}
+
+export function runReportUrl(runId: string, format: "html" | "pdf" = "html"): string {
+  return `${getBaseUrl()}/api/runs/${runId}/report?format=${format}`;
+}
+
 export async function getNodeTypes(): Promise<NodeTypeSchema[]> {
   const data = await apiFetch<{ node_types: NodeTypeSchema[] }>("/node-types");
   return data.node_types;
@@ -437,14 +617,15 @@ export async function getSchedulerStatus(): Promise<SchedulerStatus> {
 export interface CronValidation {
   valid: boolean;
   expression?: string;
+  timezone?: string;
   error?: string;
   next_runs: string[];
 }
 
-export async function validateCron(expression: string): Promise<CronValidation> {
+export async function validateCron(expression: string, timezone?: string): Promise<CronValidation> {
   return apiFetch<CronValidation>("/validate/cron", {
     method: "POST",
-    body: JSON.stringify({ expression }),
+    body: JSON.stringify({ expression, timezone }),
   });
 }
 
@@ -50,6 +50,7 @@ function toRFEdges(flow: Flow): RFEdge[] {
     id: e.id,
     source: e.source,
     target: e.target,
+    label: e.label,
     sourceHandle: "out",
     targetHandle: "in",
     type: "smoothstep",
@@ -74,6 +75,7 @@ function toFlowEdges(rfEdges: RFEdge[]): FlowEdge[] {
     id: e.id,
     source: e.source,
     target: e.target,
+    ...(typeof e.label === "string" && e.label ? { label: e.label } : {}),
   }));
 }
 
@@ -164,6 +166,7 @@ const Canvas = forwardRef<CanvasHandle, CanvasProps>(function Canvas(
       id: fe.id,
       source: fe.source,
       target: fe.target,
+      label: fe.label,
       sourceHandle: "out",
       targetHandle: "in",
       type: "smoothstep",
@@ -22,6 +22,10 @@ export interface FlowEdge {
   id: string;
   source: string;
   target: string;
+  /** Out of a branch node: the outcome ("true" / "false") this edge is taken on; out of a for-each node, "each" for edges into its loop body. */
+  label?: string;
+  /** Take the edge only when its source succeeded, or only when it failed (carrying what broke as text). */
+  condition?: "on_success" | "on_failure";
 }
 
 export interface Flow {
@@ -32,6 +36,8 @@ export interface Flow {
   nodes: FlowNode[];
   edges: FlowEdge[];
   version: number;
+  owners?: string[];
+  require_approval: boolean;
   created_at: string;
   updated_at: string;
 }
@@ -41,13 +47,25 @@ export interface FlowSummary {
   name: string;
   description: string;
   enabled: boolean;
+  owners: string[];
+  require_approval: boolean;
   node_count: number;
   edge_count: number;
   created_at: string;
   updated_at: string;
 }
 
-export type RunStatus = "running" | "success" | "failed";
+/** A non-owner's edit to a flow, waiting for an owner's approval. */
+export interface PendingChange {
+  id: string;
+  flow_id: string;
+  author: string | null;
+  base_version: number;
+  changes: Partial<Pick<Flow, "name" | "description" | "enabled" | "nodes" | "edges" | "owners" | "require_approval">>;
+  created_at: string;
+}
+
+export type RunStatus = "running" | "success" | "failed" | "interrupted";
 
 export interface NodeRun {
   node_id: string;
@@ -55,6 +73,47 @@ export interface NodeRun {
   started_at: string;
   finished_at: string | null;
   output_preview: string | null;
+  attempts?: ModelAttempt[];
+  level?: number;
+  ready_at?: string;
+}
+
+export interface ModelAttempt {
+  model: string;
+  cost_usd: number;
+  duration_ms: number;
+  error?: string;
+}
+
+export interface RunTimeline {
+  run_id: string;
+  flow_id: string;
+  status: RunStatus;
+  started_at: string;
+  finished_at: string | null;
+  duration_ms: number;
+  levels: LevelSpan[];
+  nodes: NodeSpan[];
+}
+
+export interface LevelSpan {
+  level: number;
+  start_ms: number;
+  end_ms: number;
+  node_ids: string[];
+}
+
+export interface NodeSpan {
+  node_id: string;
+  label: string;
+  kind: string;
+  level: number | null;
+  status: RunStatus;
+  start_ms: number;
+  end_ms: number;
+  duration_ms: number;
+  queue_ms: number;
+  cost_usd: number | null;
 }
 
 export interface FlowRun {
@@ -65,6 +124,7 @@ export interface FlowRun {
   finished_at: string | null;
   node_runs: NodeRun[];
   error: string | null;
+  cost_usd?: number;
 }
 
 export interface NodeTypeSchema {